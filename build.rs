@@ -0,0 +1,82 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Embed every file under `web-ui/` in the binary: the table written to
+/// `$OUT_DIR/web_ui.rs` is included by `src/web/assets.rs`, and Cargo
+/// rebuilds whenever anything in the directory changes. Also set the git
+/// commit and build date `src/version.rs` reports.
+fn main() {
+    embed_version();
+    println!("cargo:rerun-if-changed=web-ui");
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("web-ui");
+    let mut files = Vec::new();
+    collect(&root, &mut files);
+    files.sort();
+
+    let mut table = String::from("pub static ASSETS: &[(&str, &[u8])] = &[\n");
+    for file in &files {
+        let name = file.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/");
+        writeln!(table, "    ({:?}, include_bytes!({:?})),", name, file).unwrap();
+    }
+    table.push_str("];\n");
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("web_ui.rs");
+    std::fs::write(out, table).unwrap();
+}
+
+/// `DOCKET_GIT_HASH` and `DOCKET_BUILD_DATE`, which builds without `.git`
+/// (Docker, say) can pass in. `SOURCE_DATE_EPOCH` pins the date for
+/// reproducible builds.
+fn embed_version() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    for path in [".git/HEAD", ".git/refs", ".git/packed-refs"] {
+        if manifest_dir.join(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+    for var in ["DOCKET_GIT_HASH", "DOCKET_BUILD_DATE", "SOURCE_DATE_EPOCH"] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+
+    let git_hash = std::env::var("DOCKET_GIT_HASH").ok().or_else(|| {
+        let output = Command::new("git").args(["rev-parse", "--short=10", "HEAD"]).current_dir(manifest_dir).output();
+        let hash = String::from_utf8(output.ok().filter(|o| o.status.success())?.stdout).ok()?;
+        Some(hash.trim().to_string())
+    });
+    let build_date = std::env::var("DOCKET_BUILD_DATE").unwrap_or_else(|_| {
+        let epoch = std::env::var("SOURCE_DATE_EPOCH").ok().and_then(|s| s.parse().ok());
+        let seconds = epoch.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
+        civil_date(seconds / 86_400)
+    });
+    println!("cargo:rustc-env=DOCKET_GIT_HASH={}", git_hash.as_deref().unwrap_or("unknown"));
+    println!("cargo:rustc-env=DOCKET_BUILD_DATE={}", build_date);
+}
+
+/// `YYYY-MM-DD` for a count of days since 1970-01-01 (Howard Hinnant's
+/// `civil_from_days`; build scripts can't use chrono without a build dependency)
+fn civil_date(days: u64) -> String {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = std::fs::read_dir(dir).unwrap_or_else(|e| panic!("Can't read {}: {}", dir.display(), e));
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,22 @@
+// Typed async client for the REST API of a docket server. The structs are
+// the ones the server itself sends, so they can't drift from the API. The
+// docket library is built without `app`, so this pulls in no database, web
+// server or terminal crates:
+//
+//     let client = Client::new("http://nas:3000").with_token(Some(token));
+//     for project in client.list_projects(&ProjectQuery::default()).await? {
+//         println!("{}: {} open", project.project.name, project.active_todos());
+//     }
+pub use docket::client::{Client, ProjectQuery};
+pub use docket::core::batch::{BatchOp, BatchResult, Direction};
+pub use docket::core::error::{DocketError, Result};
+pub use docket::core::models::{
+    LinkKind, Project, ProjectStats, ProjectWithStats, RelatedTodo, Todo, TodoContext, TodoLink, TodoMention, TodoPatch,
+    TodoTemplate, TodoVersion, Tombstone, Workspace,
+};
+pub use docket::core::share::{ShareLink, SharedProject, SharedTodo};
+pub use docket::core::sort::{SortField, SortKey, TodoOrder};
+pub use docket::core::sync::{
+    ChangeSet, ConflictReason, Entity, ProjectChange, PushChange, PushResult, SyncConflict, TodoChange,
+};
+pub use docket::version::VersionInfo;
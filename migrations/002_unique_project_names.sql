@@ -0,0 +1,12 @@
+-- Enforce case-insensitive uniqueness of project names
+
+-- Disambiguate any existing case-insensitive duplicates so the index can be built
+UPDATE projects
+SET name = name || ' (' || id || ')'
+WHERE EXISTS (
+    SELECT 1 FROM projects other
+    WHERE other.name = projects.name COLLATE NOCASE
+      AND other.id < projects.id
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_projects_name_nocase ON projects(name COLLATE NOCASE);
@@ -0,0 +1,3 @@
+-- Track when each project was last walked through in review mode
+
+ALTER TABLE projects ADD COLUMN last_reviewed_at TIMESTAMP;
@@ -0,0 +1,3 @@
+-- Optional effort estimate per todo (points or minutes - the unit is up to the user)
+
+ALTER TABLE todos ADD COLUMN estimate INTEGER;
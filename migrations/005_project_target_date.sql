@@ -0,0 +1,3 @@
+-- Optional goal/target completion date per project
+
+ALTER TABLE projects ADD COLUMN target_date DATE;
@@ -0,0 +1,84 @@
+-- Delta sync: every row carries updated_at and a version from a global change
+-- counter, and deletions leave tombstones. Clients pull everything with a
+-- version above their cursor.
+
+CREATE TABLE sync_clock (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    version INTEGER NOT NULL
+);
+INSERT INTO sync_clock (id, version) VALUES (1, 1);
+
+ALTER TABLE projects ADD COLUMN updated_at TIMESTAMP;
+ALTER TABLE projects ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+UPDATE projects SET updated_at = COALESCE(archived_at, last_reviewed_at, created_at);
+
+ALTER TABLE todos ADD COLUMN updated_at TIMESTAMP;
+ALTER TABLE todos ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+UPDATE todos SET updated_at = COALESCE(completed_at, created_at);
+
+CREATE INDEX idx_projects_version ON projects(version);
+CREATE INDEX idx_todos_version ON todos(version);
+
+CREATE TABLE tombstones (
+    entity TEXT NOT NULL,          -- 'project' or 'todo'
+    entity_id INTEGER NOT NULL,
+    deleted_at TIMESTAMP NOT NULL,
+    version INTEGER NOT NULL
+);
+
+CREATE INDEX idx_tombstones_version ON tombstones(version);
+
+-- Stamp rows on insert and on any update that didn't already change the
+-- version (which also stops the stamping UPDATE from re-triggering)
+
+CREATE TRIGGER projects_sync_insert AFTER INSERT ON projects
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE projects
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER projects_sync_update AFTER UPDATE ON projects
+WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE projects
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER projects_sync_delete AFTER DELETE ON projects
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    INSERT INTO tombstones (entity, entity_id, deleted_at, version)
+    VALUES ('project', OLD.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (SELECT version FROM sync_clock));
+END;
+
+CREATE TRIGGER todos_sync_insert AFTER INSERT ON todos
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE todos
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER todos_sync_update AFTER UPDATE ON todos
+WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE todos
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER todos_sync_delete AFTER DELETE ON todos
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    INSERT INTO tombstones (entity, entity_id, deleted_at, version)
+    VALUES ('todo', OLD.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (SELECT version FROM sync_clock));
+END;
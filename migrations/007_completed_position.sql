@@ -0,0 +1,3 @@
+-- Position a todo had before it was completed, so uncompleting can put it back
+
+ALTER TABLE todos ADD COLUMN completed_position INTEGER;
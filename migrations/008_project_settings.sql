@@ -0,0 +1,9 @@
+-- Per-project TUI view preferences, restored when the project is opened
+
+CREATE TABLE IF NOT EXISTS project_settings (
+    project_id INTEGER PRIMARY KEY,
+    show_completed BOOLEAN NOT NULL DEFAULT 1,
+    sort TEXT,
+    filter TEXT,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
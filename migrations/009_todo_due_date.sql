@@ -0,0 +1,5 @@
+-- Optional day a todo should be done by, shown in the TUI calendar
+
+ALTER TABLE todos ADD COLUMN due_date DATE;
+
+CREATE INDEX idx_todos_due_date ON todos(due_date);
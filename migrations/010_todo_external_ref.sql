@@ -0,0 +1,3 @@
+-- Link from a todo to something tracked elsewhere: a JIRA key or a URL
+
+ALTER TABLE todos ADD COLUMN external_ref TEXT;
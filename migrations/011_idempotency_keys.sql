@@ -0,0 +1,11 @@
+-- Responses to POSTs sent with an Idempotency-Key header, replayed when a
+-- client retries the same request
+
+CREATE TABLE idempotency_keys (
+    key TEXT PRIMARY KEY,
+    request_hash TEXT NOT NULL,
+    status INTEGER,                -- NULL while the first request is running
+    content_type TEXT,
+    body BLOB,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,10 @@
+-- Reusable todos (a description plus details, often a checklist) that
+-- `docket add --template` and the TUI's :template copy into a project
+
+CREATE TABLE todo_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    description TEXT NOT NULL,
+    details TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,7 @@
+-- Keys the server signs with, generated on first use (e.g. `share` for
+-- read-only project share links)
+
+CREATE TABLE secrets (
+    name TEXT PRIMARY KEY,
+    value BLOB NOT NULL
+);
@@ -0,0 +1,5 @@
+-- Opt-in public status page per project, served at /p/<slug>
+
+ALTER TABLE projects ADD COLUMN slug TEXT;
+
+CREATE UNIQUE INDEX idx_projects_slug ON projects(slug);
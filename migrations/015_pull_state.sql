@@ -0,0 +1,9 @@
+-- Where a local copy of a remote server (`docket --remote`) stands: the
+-- server's sync cursor it last pulled, and its own sync version right
+-- after, so rows changed since (edits made offline) can be told apart
+
+CREATE TABLE pull_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    server_cursor INTEGER NOT NULL,
+    local_version INTEGER NOT NULL
+);
@@ -0,0 +1,17 @@
+-- Completed todos that `docket prune` moved out of `todos` under an
+-- archiving retention policy. Rows keep their old IDs and the project's name
+-- at the time, so they still make sense after the project is deleted.
+
+CREATE TABLE archived_todos (
+    id INTEGER PRIMARY KEY,
+    project_id INTEGER NOT NULL,
+    project_name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    details TEXT,
+    estimate INTEGER,
+    due_date DATE,
+    external_ref TEXT,
+    created_at TIMESTAMP NOT NULL,
+    completed_at TIMESTAMP NOT NULL,
+    archived_at TIMESTAMP NOT NULL
+);
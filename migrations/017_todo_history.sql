@@ -0,0 +1,24 @@
+-- Earlier descriptions and details of todos. A trigger keeps the old text
+-- whenever an update changes either, so every path that edits a todo (the
+-- TUI, the API, sync pushes, scripts) is covered; the 50 latest are kept.
+
+CREATE TABLE todo_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    todo_id INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    details TEXT,
+    replaced_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_todo_history_todo ON todo_history(todo_id, id);
+
+CREATE TRIGGER todos_history_update AFTER UPDATE OF description, details ON todos
+WHEN OLD.description IS NOT NEW.description OR OLD.details IS NOT NEW.details
+BEGIN
+    INSERT INTO todo_history (todo_id, description, details, replaced_at)
+    VALUES (OLD.id, OLD.description, OLD.details, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    DELETE FROM todo_history
+    WHERE todo_id = OLD.id
+      AND id NOT IN (SELECT id FROM todo_history WHERE todo_id = OLD.id ORDER BY id DESC LIMIT 50);
+END;
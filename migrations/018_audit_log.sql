@@ -0,0 +1,16 @@
+-- Who changed what, for `GET /api/admin/audit`. The service writes a row
+-- for every change it publishes; `actor` is whoever the web server
+-- authenticated, or 'local' for the TUI and CLI.
+
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    at TIMESTAMP NOT NULL,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,          -- a change kind, e.g. 'todo.toggled'
+    entity TEXT NOT NULL,          -- 'project' or 'todo'
+    entity_id INTEGER,
+    project_id INTEGER
+);
+
+CREATE INDEX idx_audit_log_at ON audit_log(at);
+CREATE INDEX idx_audit_log_entity ON audit_log(entity, entity_id);
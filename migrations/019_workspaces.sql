@@ -0,0 +1,12 @@
+-- Workspaces group projects into areas such as Work, Home or OSS. A project
+-- belongs to at most one; deleting a workspace leaves its projects unfiled.
+
+CREATE TABLE workspaces (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE projects ADD COLUMN workspace_id INTEGER;
+
+CREATE INDEX idx_projects_workspace ON projects(workspace_id);
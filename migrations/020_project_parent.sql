@@ -0,0 +1,7 @@
+-- Sub-projects: a project may sit under a parent project, and the project
+-- list shows them as a tree. Deleting a parent moves its children up to
+-- the top level.
+
+ALTER TABLE projects ADD COLUMN parent_id INTEGER;
+
+CREATE INDEX idx_projects_parent ON projects(parent_id);
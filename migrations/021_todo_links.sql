@@ -0,0 +1,15 @@
+-- Links between todos, possibly in different projects: "see also", or
+-- "duplicates" pointing from the duplicate to the todo it repeats
+
+CREATE TABLE todo_links (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    todo_id INTEGER NOT NULL,
+    target_id INTEGER NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('see_also', 'duplicates')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (todo_id, target_id),
+    FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE,
+    FOREIGN KEY (target_id) REFERENCES todos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_todo_links_target ON todo_links(target_id);
@@ -0,0 +1,12 @@
+-- Todos mentioned in another todo's details, as #123 or docket://todo/123,
+-- so a todo can list the ones that refer to it. Rewritten whenever the
+-- details are; the mentioned todo need not exist (any more).
+
+CREATE TABLE todo_references (
+    todo_id INTEGER NOT NULL,
+    target_id INTEGER NOT NULL,
+    PRIMARY KEY (todo_id, target_id),
+    FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_todo_references_target ON todo_references(target_id);
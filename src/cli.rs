@@ -0,0 +1,752 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::core::groom::GroomChange;
+use crate::core::models::{Project, ProjectWithStats, Todo};
+use crate::core::query::TodoQuery;
+use crate::core::service::DocketService;
+use crate::core::time::DisplayZone;
+use crate::core::vault;
+use crate::git::{self, GitContext};
+use crate::jira::JiraClient;
+use crate::mail;
+
+/// Project used by `docket add` when no `--project` is given
+pub const DEFAULT_PROJECT: &str = "Inbox";
+
+/// Add todos from the command line or stdin.
+///
+/// A description of `-` (or none, when stdin is piped) reads the description from
+/// stdin: the first line becomes the todo and any further lines its details.
+/// With `each`, every non-empty line of stdin becomes its own todo. With
+/// `template`, the todo is copied from that template and a description is
+/// optional.
+pub async fn add(
+    service: &DocketService,
+    project: Option<&str>,
+    description: Option<&str>,
+    each: bool,
+    template: Option<&str>,
+) -> Result<()> {
+    if let Some(name) = template {
+        if each {
+            bail!("--each and --template can't be combined");
+        }
+        // Look the template up before creating a missing project
+        service.get_template(name).await?;
+        let project = service
+            .find_or_create_project(project.unwrap_or(DEFAULT_PROJECT))
+            .await?;
+        let todo = service.create_todo_from_template(project.id, name, description).await?;
+        println!("Added #{} to {}: {}", todo.id, project.name, todo.description);
+        return Ok(());
+    }
+
+    let from_stdin = matches!(description, None | Some("-"));
+    if each && !from_stdin {
+        bail!("--each reads todos from stdin; drop the description argument");
+    }
+    let input = todo_text(description)?;
+
+    let project = service
+        .find_or_create_project(project.unwrap_or(DEFAULT_PROJECT))
+        .await?;
+
+    if each {
+        let lines: Vec<&str> = input.lines().map(strip_list_marker).filter(|l| !l.is_empty()).collect();
+        if lines.is_empty() {
+            bail!("No todos on stdin");
+        }
+        for line in &lines {
+            service.create_todo(project.id, line).await?;
+        }
+        println!("Added {} todos to {}", lines.len(), project.name);
+    } else {
+        let (first, rest) = input.trim().split_once('\n').unwrap_or((input.trim(), ""));
+        let todo = service.create_todo_with_details(project.id, first, Some(rest)).await?;
+        println!("Added #{} to {}: {}", todo.id, project.name, todo.description);
+    }
+
+    Ok(())
+}
+
+/// Complete a todo named by its short ID (`HOME-42`) or `#42`
+pub async fn done(service: &DocketService, reference: &str) -> Result<()> {
+    let todo = service.find_todo_by_ref(reference).await?;
+    let project = service.get_project(todo.project_id).await?;
+    let short_id = project.todo_short_id(todo.id);
+    if todo.is_completed() {
+        println!("{} is already done: {}", short_id, todo.description);
+    } else {
+        service.toggle_todo(todo.id).await?;
+        println!("Completed {} {}", short_id, todo.description);
+    }
+    Ok(())
+}
+
+/// Copy a project with its open todos (and with `completed`, its completed
+/// ones too, reopened)
+pub async fn project_clone(service: &DocketService, name: &str, new_name: Option<&str>, completed: bool) -> Result<()> {
+    let Some(project) = service.find_project_by_name(name).await? else {
+        bail!("No project named '{}'", name);
+    };
+    let copy = service.clone_project(project.id, new_name, completed).await?;
+    let todos = service.list_active_todos(copy.id).await?.len();
+    println!("Created project #{}: {} with {} todo{}", copy.id, copy.name, todos, plural(todos));
+    Ok(())
+}
+
+/// Publish a project's status page under `slug`, or take it down with `None`
+pub async fn project_publish(service: &DocketService, name: &str, slug: Option<&str>) -> Result<()> {
+    let Some(project) = service.find_project_by_name(name).await? else {
+        bail!("No project named '{}'", name);
+    };
+    let project = service.update_project_slug(project.id, slug).await?;
+    match project.slug {
+        Some(slug) => println!("Published {} at /p/{}", project.name, slug),
+        None => println!("Unpublished {}", project.name),
+    }
+    Ok(())
+}
+
+/// The text of a todo: `description`, or stdin when it is `-` or missing
+fn todo_text(description: Option<&str>) -> Result<String> {
+    match description {
+        Some(text) if text != "-" => Ok(text.to_string()),
+        _ if io::stdin().is_terminal() => bail!("Provide a description, or pipe one in on stdin"),
+        _ => {
+            let mut buf = String::new();
+            io::stdin().read_to_string(&mut buf)?;
+            Ok(buf)
+        }
+    }
+}
+
+/// Save a todo template: the first line of the text is the description,
+/// any further lines its details
+pub async fn template_add(service: &DocketService, name: &str, description: Option<&str>) -> Result<()> {
+    let input = todo_text(description)?;
+    let (first, rest) = input.trim().split_once('\n').unwrap_or((input.trim(), ""));
+    let template = service.create_template(name, first, Some(rest)).await?;
+    println!("Saved template {}: {}", template.name, template.description);
+    Ok(())
+}
+
+/// List todo templates
+pub async fn template_list(service: &DocketService) -> Result<()> {
+    let templates = service.list_templates().await?;
+    if templates.is_empty() {
+        println!("No templates; save one with `docket template add <name> <description>`");
+    }
+    for template in templates {
+        let lines = template.details.as_deref().map_or(0, |d| d.lines().count());
+        match lines {
+            0 => println!("{}: {}", template.name, template.description),
+            n => println!("{}: {} (+{} line{} of details)", template.name, template.description, n, plural(n)),
+        }
+    }
+    Ok(())
+}
+
+/// Delete a todo template
+pub async fn template_remove(service: &DocketService, name: &str) -> Result<()> {
+    service.delete_template(name).await?;
+    println!("Removed template {}", name.trim());
+    Ok(())
+}
+
+/// Create a project from the command line
+pub async fn project_add(service: &DocketService, name: &str, description: Option<&str>) -> Result<()> {
+    let project = service.create_project_with_description(name, description).await?;
+    println!("Created project #{}: {}", project.id, project.name);
+    Ok(())
+}
+
+/// Output style for `docket list`
+pub enum ListFormat {
+    /// `[ ] #id [project] description`, for humans
+    Plain,
+    Json,
+    Tsv,
+    /// A `{{field}}` template applied to each todo
+    Template(String),
+}
+
+/// A todo as printed by `docket list`: every todo field plus its project name
+#[derive(Serialize)]
+struct ListedTodo {
+    #[serde(flatten)]
+    todo: Todo,
+    project: String,
+    /// e.g. `HOME-42`
+    short_id: String,
+}
+
+/// Print todos across active projects (or a single project) for scripting
+pub async fn list(
+    service: &DocketService,
+    project: Option<&str>,
+    all: bool,
+    query: Option<&str>,
+    format: ListFormat,
+) -> Result<()> {
+    let query = match query {
+        Some(query) => Some(query.parse::<TodoQuery>().map_err(|e| anyhow!("Invalid --query: {}", e))?),
+        None => None,
+    };
+    // A `status:` term picks open or completed todos itself
+    let all = all || query.as_ref().is_some_and(TodoQuery::has_status);
+    let today = service.timezone().today();
+    let projects = match project {
+        Some(name) => match service.find_project_by_name(name).await? {
+            Some(project) => vec![project],
+            None => bail!("No project named '{}'", name),
+        },
+        None => service
+            .list_active_projects()
+            .await?
+            .into_iter()
+            .map(|p| p.project)
+            .collect(),
+    };
+
+    let mut rows = Vec::new();
+    for project in projects {
+        let todos = if all {
+            service.list_all_todos(project.id).await?
+        } else {
+            service.list_active_todos(project.id).await?
+        };
+        rows.extend(
+            todos
+                .into_iter()
+                .filter(|todo| query.as_ref().is_none_or(|q| q.matches(todo, &project.name, today)))
+                .map(|todo| ListedTodo {
+                    short_id: project.todo_short_id(todo.id),
+                    todo,
+                    project: project.name.clone(),
+                }),
+        );
+    }
+
+    match format {
+        ListFormat::Plain => {
+            for row in &rows {
+                let check = if row.todo.is_completed() { "x" } else { " " };
+                println!("[{}] {} [{}] {}", check, row.short_id, row.project, row.todo.description);
+            }
+        }
+        ListFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+        ListFormat::Tsv => {
+            for row in &rows {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    row.todo.id,
+                    tsv_field(&row.project),
+                    tsv_field(&row.todo.description),
+                    row.todo.is_completed()
+                );
+            }
+        }
+        ListFormat::Template(template) => {
+            for row in &rows {
+                println!("{}", render_template(&template, &serde_json::to_value(row)?)?);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Output style for `docket status`
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum StatusFormat {
+    Plain,
+    Json,
+}
+
+/// One-line summary for status bars
+#[derive(Debug, PartialEq, Serialize)]
+struct Status {
+    /// Open todos across active projects
+    open: i64,
+    /// Projects with open todos whose target date is today
+    due_today: usize,
+    /// Projects with open todos whose target date has passed
+    overdue: usize,
+    /// The most recently active project with open todos
+    current_project: Option<String>,
+}
+
+impl Status {
+    fn from_projects(projects: &[ProjectWithStats], zone: DisplayZone) -> Self {
+        let open_projects: Vec<&ProjectWithStats> =
+            projects.iter().filter(|p| p.active_todos() > 0).collect();
+        let days = |p: &&ProjectWithStats| p.project.days_remaining(zone);
+        Self {
+            open: open_projects.iter().map(|p| p.active_todos()).sum(),
+            due_today: open_projects.iter().filter(|p| days(p) == Some(0)).count(),
+            overdue: open_projects
+                .iter()
+                .filter(|p| days(p).is_some_and(|d| d < 0))
+                .count(),
+            current_project: open_projects
+                .iter()
+                .max_by_key(|p| p.last_activity_at)
+                .map(|p| p.project.name.clone()),
+        }
+    }
+
+    /// `5 open · 1 due today · 2 overdue · Work`, omitting empty parts
+    fn to_plain(&self) -> String {
+        let mut parts = vec![format!("{} open", self.open)];
+        if self.due_today > 0 {
+            parts.push(format!("{} due today", self.due_today));
+        }
+        if self.overdue > 0 {
+            parts.push(format!("{} overdue", self.overdue));
+        }
+        if let Some(project) = &self.current_project {
+            parts.push(project.clone());
+        }
+        parts.join(" · ")
+    }
+}
+
+/// Print a compact status line, cheap enough to poll from tmux or Waybar
+pub async fn status(service: &DocketService, format: StatusFormat) -> Result<()> {
+    let status = Status::from_projects(&service.list_active_projects().await?, service.timezone());
+    match format {
+        StatusFormat::Plain => println!("{}", status.to_plain()),
+        StatusFormat::Json => println!("{}", serde_json::to_string(&status)?),
+    }
+    Ok(())
+}
+
+/// Print the agenda of overdue and due-soon todos, or mail it to `email`
+/// through the configured mail command (for cron or a systemd timer)
+pub async fn digest(
+    service: &DocketService,
+    config: &Config,
+    days: i64,
+    html: bool,
+    email: Option<&str>,
+) -> Result<()> {
+    let digest = service.build_digest(days).await?;
+    let Some(to) = email else {
+        print!("{}", if html { digest.to_html() } else { digest.to_markdown() });
+        return Ok(());
+    };
+
+    let message = mail::Message {
+        from: config.mail_from.as_deref(),
+        to,
+        subject: &digest.subject(),
+        text: &digest.to_markdown(),
+        html: &digest.to_html(),
+    };
+    mail::send(&config.mail_command, &message)?;
+    println!("Sent agenda to {}", to);
+    Ok(())
+}
+
+/// Mirror every active project into `dir` as a Markdown checklist, first
+/// applying checkbox, wording and new-line edits made in the vault
+pub async fn vault(service: &DocketService, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    // Files docket wrote before, found by their frontmatter so renamed projects keep theirs
+    let mut files: HashMap<i64, PathBuf> = HashMap::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "md")
+            && let Ok(text) = std::fs::read_to_string(&path)
+            && let Some(id) = vault::project_id(&text)
+        {
+            files.insert(id, path);
+        }
+    }
+
+    let (mut imported, mut written) = (0, 0);
+    for project in service.list_active_projects().await?.into_iter().map(|p| p.project) {
+        let path = dir.join(vault::file_name(&project));
+        let existing = files.get(&project.id);
+        if path.exists() && existing != Some(&path) {
+            eprintln!("Skipping {}: {} is not docket's file", project.name, path.display());
+            continue;
+        }
+        if let Some(existing) = existing {
+            imported += import_vault_file(service, &project, existing).await?;
+            if *existing != path {
+                std::fs::rename(existing, &path)?;
+            }
+        }
+
+        let text = vault::render(&project, &service.list_all_todos(project.id).await?);
+        if std::fs::read_to_string(&path).ok().as_deref() != Some(text.as_str()) {
+            std::fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+            written += 1;
+        }
+    }
+    println!("Imported {} change{}, wrote {} file{}", imported, plural(imported), written, plural(written));
+    Ok(())
+}
+
+/// Apply edits from a project's vault file. Where a todo changed in docket
+/// after the file was last saved, docket's version wins.
+async fn import_vault_file(service: &DocketService, project: &Project, path: &Path) -> Result<usize> {
+    let text = std::fs::read_to_string(path)?;
+    let saved_at: DateTime<Utc> = std::fs::metadata(path)?.modified()?.into();
+    let todos = service.list_all_todos(project.id).await?;
+
+    let mut changes = 0;
+    for item in vault::parse(&text) {
+        let Some(id) = item.todo_id else {
+            let todo = service.create_todo(project.id, &item.description).await?;
+            if item.checked {
+                service.toggle_todo(todo.id).await?;
+            }
+            changes += 1;
+            continue;
+        };
+        // Deleted, or moved to another project
+        let Some(todo) = todos.iter().find(|t| t.id == id) else { continue };
+        if todo.updated_at > saved_at {
+            continue;
+        }
+        if todo.is_completed() != item.checked {
+            service.toggle_todo(id).await?;
+            changes += 1;
+        }
+        if todo.description != item.description {
+            service.update_todo(id, &item.description).await?;
+            changes += 1;
+        }
+    }
+    Ok(changes)
+}
+
+/// Create a todo for each JIRA issue matching `jql`, linked to the issue.
+/// Issues already linked from a todo in the project are skipped, so the
+/// import can be re-run to pick up new tickets.
+pub async fn import_jira(
+    service: &DocketService,
+    client: &JiraClient,
+    jql: &str,
+    project: Option<&str>,
+) -> Result<()> {
+    let issues = client.search(jql).await?;
+    let project = service
+        .find_or_create_project(project.unwrap_or(DEFAULT_PROJECT))
+        .await?;
+    let linked: Vec<String> = service
+        .list_all_todos(project.id)
+        .await?
+        .iter()
+        .filter_map(|t| client.issue_key(t.external_ref.as_deref()?))
+        .collect();
+
+    let mut imported = 0;
+    for issue in issues.iter().filter(|i| !linked.contains(&i.key)) {
+        let todo = service.create_todo(project.id, &issue.summary).await?;
+        service.update_todo_external_ref(todo.id, Some(&issue.key)).await?;
+        imported += 1;
+    }
+    println!(
+        "Imported {} issue{} into {} ({} already linked)",
+        imported,
+        plural(imported),
+        project.name,
+        issues.len() - imported
+    );
+    Ok(())
+}
+
+/// First line of hooks written by `docket hook install`, so they can be
+/// replaced without `--force`
+const HOOK_MARKER: &str = "# Installed by docket hook install";
+
+/// Tag marking todos that must be done before anything more is committed
+const BLOCKING_TAG: &str = "blocking";
+
+/// Install a commit-msg hook in the git repository containing `dir`
+pub fn hook_install(dir: &Path, force: bool) -> Result<()> {
+    let Some(hooks) = git::hooks_dir(dir) else {
+        bail!("Not inside a git repository");
+    };
+    let path = hooks.join("commit-msg");
+    if let Ok(existing) = std::fs::read_to_string(&path)
+        && !existing.contains(HOOK_MARKER)
+        && !force
+    {
+        bail!("{} already exists; pass --force to replace it", path.display());
+    }
+
+    // Call this binary by path so the hook works without docket on PATH
+    let exe = std::env::current_exe()?.display().to_string().replace('\'', "'\\''");
+    std::fs::create_dir_all(&hooks)?;
+    std::fs::write(&path, format!("#!/bin/sh\n{}\nexec '{}' hook commit-msg \"$1\"\n", HOOK_MARKER, exe))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    println!("Installed {}", path.display());
+    Ok(())
+}
+
+/// The commit-msg hook: refuse the commit while the repository's project has
+/// open `#blocking` todos, otherwise list todos completed since the last
+/// commit at the end of the message
+pub async fn hook_commit_msg(service: &DocketService, config: &Config, file: &Path) -> Result<()> {
+    let dir = std::env::current_dir()?;
+    let Some(context) = GitContext::detect(&dir) else { return Ok(()) };
+    let Some(project) = service
+        .find_project_by_name(context.project_name(&config.git_projects))
+        .await?
+    else {
+        return Ok(());
+    };
+    let todos = service.list_all_todos(project.id).await?;
+
+    let blocking: Vec<&Todo> = todos
+        .iter()
+        .filter(|t| !t.is_completed() && t.tags().iter().any(|tag| tag == BLOCKING_TAG))
+        .collect();
+    if !blocking.is_empty() {
+        eprintln!("Open #{} todos in {}:", BLOCKING_TAG, project.name);
+        for todo in &blocking {
+            eprintln!("  #{} {}", todo.id, todo.description);
+        }
+        bail!("Commit blocked; complete them first (or commit with --no-verify)");
+    }
+
+    let Some(last_commit) = git::last_commit_at(&dir) else { return Ok(()) };
+    // Commit dates have whole seconds; skip the second the last commit was
+    // made in rather than repeat its references
+    let since = last_commit + chrono::Duration::seconds(1);
+    let references: Vec<String> = todos
+        .iter()
+        .filter(|t| t.completed_at.is_some_and(|at| at >= since))
+        .map(todo_reference)
+        .collect();
+    let message = std::fs::read_to_string(file)?;
+    if let Some(updated) = append_references(&message, &references) {
+        std::fs::write(file, updated)?;
+    }
+    Ok(())
+}
+
+/// `Docket: #12 Fix login (OPS-42)`
+fn todo_reference(todo: &Todo) -> String {
+    match &todo.external_ref {
+        Some(reference) => format!("Docket: #{} {} ({})", todo.id, todo.description, reference),
+        None => format!("Docket: #{} {}", todo.id, todo.description),
+    }
+}
+
+/// Add reference lines to a commit message ahead of git's `#` comments,
+/// skipping ones already there. `None` when there is nothing to add or the
+/// message is empty, so git still aborts an empty commit.
+fn append_references(message: &str, references: &[String]) -> Option<String> {
+    let (body, comments) = match message.find("\n#") {
+        Some(i) => message.split_at(i + 1),
+        None if message.starts_with('#') => ("", message),
+        None => (message, ""),
+    };
+    let new: Vec<&String> = references.iter().filter(|r| !body.contains(r.as_str())).collect();
+    if new.is_empty() || body.trim().is_empty() {
+        return None;
+    }
+    let mut out = format!("{}\n\n", body.trim_end());
+    for reference in new {
+        out.push_str(reference);
+        out.push('\n');
+    }
+    if !comments.is_empty() {
+        out.push('\n');
+        out.push_str(comments);
+    }
+    Some(out)
+}
+
+/// Renumber todo positions 1..n in one project, or in every project
+pub async fn compact(service: &DocketService, project: Option<&str>) -> Result<()> {
+    let projects = match project {
+        Some(name) => match service.find_project_by_name(name).await? {
+            Some(project) => vec![project],
+            None => bail!("No project named '{}'", name),
+        },
+        None => service.list_all_projects().await?.into_iter().map(|p| p.project).collect(),
+    };
+
+    let mut moved = 0;
+    for project in &projects {
+        moved += service.normalize_positions(project.id).await?;
+    }
+    println!(
+        "Renumbered {} todo{} in {} project{}",
+        moved,
+        plural(moved),
+        projects.len(),
+        plural(projects.len())
+    );
+    Ok(())
+}
+
+/// Apply the config file's grooming rules, or with `dry_run` list what
+/// they would change
+pub async fn groom(service: &DocketService, config: &Config, dry_run: bool) -> Result<()> {
+    if config.groom_rules.is_empty() {
+        bail!("No groom_rules in the config file, e.g. [{{\"open_days\": 60, \"tag\": \"stale\", \"review\": true}}]");
+    }
+    let changes = service.groom(&config.groom_rules, dry_run).await?;
+    if changes.is_empty() {
+        println!("Nothing to groom");
+        return Ok(());
+    }
+    let (tag_verb, review_verb) = if dry_run { ("Would add", "Would queue") } else { ("Added", "Queued") };
+    for change in &changes {
+        match change {
+            GroomChange::Tag { description, project, age_days, tag, .. } => println!(
+                "{} #{} to \"{}\" in {} (open {} days)",
+                tag_verb, tag, description, project, age_days
+            ),
+            GroomChange::Review { project, .. } => println!("{} {} for review", review_verb, project),
+        }
+    }
+    Ok(())
+}
+
+/// Apply the config file's retention policy for completed todos, or with
+/// `dry_run` list what it would remove
+pub async fn prune(service: &DocketService, config: &Config, dry_run: bool) -> Result<()> {
+    let Some(policy) = &config.prune else {
+        bail!("No prune policy in the config file, e.g. {{\"completed_days\": 365, \"archive\": true}}");
+    };
+    let pruned = service.prune(policy, dry_run).await?;
+    if pruned.is_empty() {
+        println!("Nothing to prune");
+        return Ok(());
+    }
+    let verb = match (dry_run, policy.archive) {
+        (true, true) => "Would archive",
+        (true, false) => "Would delete",
+        (false, true) => "Archived",
+        (false, false) => "Deleted",
+    };
+    for todo in &pruned {
+        println!(
+            "{} \"{}\" in {} (completed {} days ago)",
+            verb, todo.description, todo.project, todo.completed_days_ago
+        );
+    }
+    print!("{} {} todo{}", verb, pruned.len(), plural(pruned.len()));
+    if policy.archive && !dry_run {
+        print!(" ({} in the archive)", service.count_archived_todos().await?);
+    }
+    println!();
+    Ok(())
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+/// Keep a value on one TSV cell by replacing tabs and newlines with spaces
+fn tsv_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Fill `{{field}}` placeholders from a JSON object. `\t` and `\n` in the
+/// template are unescaped so shell-quoted formats work as expected.
+fn render_template(template: &str, fields: &Value) -> Result<String> {
+    let template = template.replace("\\t", "\t").replace("\\n", "\n");
+    let mut out = String::new();
+    let mut rest = template.as_str();
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| anyhow!("Unclosed '{{{{' in format"))?;
+        let name = after[..end].trim();
+        match fields.get(name) {
+            Some(Value::String(s)) => out.push_str(s),
+            Some(Value::Null) => {}
+            Some(other) => out.push_str(&other.to_string()),
+            None => bail!("Unknown field '{}' in format", name),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+
+    Ok(out)
+}
+
+/// Strip a leading Markdown list marker (`- `, `* `, `- [ ] `, `- [x] `) from a line
+fn strip_list_marker(line: &str) -> &str {
+    let line = line.trim();
+    let line = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .unwrap_or(line);
+    line.strip_prefix("[ ] ")
+        .or_else(|| line.strip_prefix("[x] "))
+        .unwrap_or(line)
+        .trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_list_marker() {
+        assert_eq!(strip_list_marker("  fix the deploy script "), "fix the deploy script");
+        assert_eq!(strip_list_marker("- buy milk"), "buy milk");
+        assert_eq!(strip_list_marker("* [ ] call mom"), "call mom");
+        assert_eq!(strip_list_marker("- [x] done thing"), "done thing");
+        assert_eq!(strip_list_marker("-1 degrees"), "-1 degrees");
+    }
+
+    #[test]
+    fn test_status_plain_omits_empty_parts() {
+        let status = Status { open: 4, due_today: 0, overdue: 2, current_project: Some("Work".to_string()) };
+        assert_eq!(status.to_plain(), "4 open · 2 overdue · Work");
+        let status = Status { open: 0, due_today: 0, overdue: 0, current_project: None };
+        assert_eq!(status.to_plain(), "0 open");
+    }
+
+    #[test]
+    fn test_append_references_before_comments() {
+        let references = vec!["Docket: #3 Fix login".to_string()];
+        let message = "Fix auth\n# Please enter the commit message\n";
+        assert_eq!(
+            append_references(message, &references).unwrap(),
+            "Fix auth\n\nDocket: #3 Fix login\n\n# Please enter the commit message\n"
+        );
+        assert_eq!(append_references("Fix auth\n\nDocket: #3 Fix login\n", &references), None);
+        assert_eq!(append_references("# Aborted\n", &references), None);
+    }
+
+    #[test]
+    fn test_render_template() {
+        let fields = serde_json::json!({"id": 3, "project": "Work", "description": "Ship it", "estimate": null});
+        assert_eq!(
+            render_template("{{id}}\\t{{project}}\\t{{ description }}", &fields).unwrap(),
+            "3\tWork\tShip it"
+        );
+        assert_eq!(render_template("~{{estimate}}", &fields).unwrap(), "~");
+        assert!(render_template("{{nope}}", &fields).is_err());
+        assert!(render_template("{{id", &fields).is_err());
+    }
+}
@@ -0,0 +1,572 @@
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::core::batch::{BatchOp, BatchResult, Direction};
+use crate::core::error::{DocketError, Result};
+use crate::core::models::{
+    LinkKind, Project, ProjectStats, ProjectWithStats, RelatedTodo, Todo, TodoContext, TodoLink, TodoMention, TodoPatch,
+    TodoTemplate, TodoVersion, Workspace,
+};
+use crate::core::share::{ShareLink, SharedProject};
+use crate::core::sort::TodoOrder;
+use crate::core::sync::{ChangeSet, PushChange, PushResult};
+use crate::version::VersionInfo;
+
+/// Seconds to wait for a connection before counting the server as offline
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+
+/// Seconds a whole request may take, e.g. on a connection that stalls
+const MAX_TIME_SECS: u64 = 30;
+
+/// Typed client for the REST API of a docket server, one method per
+/// endpoint, answering with the same structs the server sends. Requests go
+/// through `curl`, so the machine needs it on the `PATH`.
+///
+/// Error answers come back as the `DocketError` the server started from
+/// (400 is `Validation`, 404 `NotFound`, 409 `Conflict` with its
+/// suggestion); a server that can't be reached is `DocketError::Offline`.
+/// Every `POST` carries an `Idempotency-Key` and is sent once more when it
+/// went out but no answer came back, so the server answers the retry from
+/// the first attempt instead of applying it twice. Other requests that go
+/// unanswered fail with `DocketError::Remote`, never `Offline`: they may
+/// have been applied.
+/// The event stream (`/api/events`), the dashboard widget and the HTML
+/// status pages are not covered.
+#[derive(Debug, Clone)]
+pub struct Client {
+    base_url: String,
+    token: Option<String>,
+}
+
+/// Filters for `Client::list_projects`
+#[derive(Debug, Clone, Default)]
+pub struct ProjectQuery {
+    pub include_archived: bool,
+    /// Only projects with a target date on or before this date
+    pub target_before: Option<NaiveDate>,
+    /// Only projects at risk of missing their target date
+    pub at_risk: bool,
+    /// Only projects in this workspace
+    pub workspace: Option<i64>,
+}
+
+impl Client {
+    /// A client for the server at `base_url`, e.g. `http://nas:3000`
+    pub fn new(base_url: &str) -> Self {
+        Self { base_url: base_url.trim_end_matches('/').to_string(), token: None }
+    }
+
+    /// Send `token` as the bearer token the server's `api_token` asks for
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// The server this client talks to, without a trailing `/`
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    // ===== Projects =====
+
+    pub async fn list_projects(&self, query: &ProjectQuery) -> Result<Vec<ProjectWithStats>> {
+        let mut params = vec![format!("include_archived={}", query.include_archived)];
+        if let Some(date) = query.target_before {
+            params.push(format!("target_before={}", date));
+        }
+        if query.at_risk {
+            params.push("at_risk=true".to_string());
+        }
+        if let Some(workspace) = query.workspace {
+            params.push(format!("workspace={}", workspace));
+        }
+        self.get(&format!("/api/projects?{}", params.join("&"))).await
+    }
+
+    pub async fn create_project(&self, name: &str, description: Option<&str>) -> Result<Project> {
+        self.fetch("POST", "/api/projects", json!({ "name": name, "description": description })).await
+    }
+
+    pub async fn get_project(&self, id: i64) -> Result<Project> {
+        self.get(&format!("/api/projects/{}", id)).await
+    }
+
+    /// Rename a project and/or set its description; `None` leaves a field as it is
+    pub async fn update_project(&self, id: i64, name: Option<&str>, description: Option<&str>) -> Result<()> {
+        let body = json!({ "name": name, "description": description });
+        self.call("PATCH", &format!("/api/projects/{}", id), body).await
+    }
+
+    /// Set every editable field of a project; `None` clears a field
+    pub async fn replace_project(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+        target_date: Option<NaiveDate>,
+        archived: bool,
+    ) -> Result<Project> {
+        let body = json!({
+            "name": name,
+            "description": description,
+            "target_date": target_date,
+            "archived": archived,
+        });
+        self.fetch("PUT", &format!("/api/projects/{}", id), body).await
+    }
+
+    pub async fn delete_project(&self, id: i64) -> Result<()> {
+        self.send("DELETE", &format!("/api/projects/{}", id), None).await.map(drop)
+    }
+
+    /// Copy a project with its open todos; without a name the copy gets a
+    /// free numbered one
+    pub async fn clone_project(&self, id: i64, name: Option<&str>, include_completed: bool) -> Result<Project> {
+        let body = json!({ "name": name, "include_completed": include_completed });
+        self.fetch("POST", &format!("/api/projects/{}/clone", id), body).await
+    }
+
+    /// Create a read-only link to a project that works for `days` days
+    pub async fn share_project(&self, id: i64, days: i64) -> Result<ShareLink> {
+        self.fetch("POST", &format!("/api/projects/{}/share", id), json!({ "days": days })).await
+    }
+
+    pub async fn archive_project(&self, id: i64) -> Result<()> {
+        self.call("PATCH", &format!("/api/projects/{}/archive", id), json!({})).await
+    }
+
+    pub async fn unarchive_project(&self, id: i64) -> Result<()> {
+        self.call("PATCH", &format!("/api/projects/{}/unarchive", id), json!({})).await
+    }
+
+    pub async fn update_project_description(&self, id: i64, description: Option<&str>) -> Result<()> {
+        let body = json!({ "description": description });
+        self.call("PATCH", &format!("/api/projects/{}/description", id), body).await
+    }
+
+    pub async fn update_project_target_date(&self, id: i64, target_date: Option<NaiveDate>) -> Result<()> {
+        let body = json!({ "target_date": target_date });
+        self.call("PATCH", &format!("/api/projects/{}/target-date", id), body).await
+    }
+
+    /// Publish the project's status page at `/p/<slug>`, or take it down with `None`
+    pub async fn update_project_slug(&self, id: i64, slug: Option<&str>) -> Result<Project> {
+        self.fetch("PATCH", &format!("/api/projects/{}/slug", id), json!({ "slug": slug })).await
+    }
+
+    /// File the project under a workspace, or under none with `None`
+    pub async fn update_project_workspace(&self, id: i64, workspace_id: Option<i64>) -> Result<Project> {
+        let body = json!({ "workspace_id": workspace_id });
+        self.fetch("PATCH", &format!("/api/projects/{}/workspace", id), body).await
+    }
+
+    /// Nest the project under another, or move it to the top level with `None`
+    pub async fn update_project_parent(&self, id: i64, parent_id: Option<i64>) -> Result<Project> {
+        let body = json!({ "parent_id": parent_id });
+        self.fetch("PATCH", &format!("/api/projects/{}/parent", id), body).await
+    }
+
+    pub async fn list_workspaces(&self) -> Result<Vec<Workspace>> {
+        self.get("/api/workspaces").await
+    }
+
+    pub async fn create_workspace(&self, name: &str) -> Result<Workspace> {
+        self.fetch("POST", "/api/workspaces", json!({ "name": name })).await
+    }
+
+    pub async fn rename_workspace(&self, id: i64, name: &str) -> Result<Workspace> {
+        self.fetch("PATCH", &format!("/api/workspaces/{}", id), json!({ "name": name })).await
+    }
+
+    pub async fn delete_workspace(&self, id: i64) -> Result<()> {
+        self.send("DELETE", &format!("/api/workspaces/{}", id), None).await.map(drop)
+    }
+
+    pub async fn mark_project_reviewed(&self, id: i64) -> Result<()> {
+        self.call("PATCH", &format!("/api/projects/{}/reviewed", id), json!({})).await
+    }
+
+    /// Statistics over the last `weeks` weeks; `tz` is `local`, `utc` or an
+    /// offset such as `+02:00`, and defaults to the server's zone
+    pub async fn get_project_stats(&self, id: i64, weeks: i64, tz: Option<&str>) -> Result<ProjectStats> {
+        let mut path = format!("/api/projects/{}/stats?weeks={}", id, weeks);
+        if let Some(tz) = tz {
+            path.push_str(&format!("&tz={}", encode_query(tz)));
+        }
+        self.get(&path).await
+    }
+
+    // ===== Todos =====
+
+    pub async fn list_todos(&self, project_id: i64, include_completed: bool) -> Result<Vec<Todo>> {
+        let path = format!("/api/projects/{}/todos?include_completed={}", project_id, include_completed);
+        self.get(&path).await
+    }
+
+    pub async fn list_sorted_todos(
+        &self,
+        project_id: i64,
+        include_completed: bool,
+        order: &TodoOrder,
+    ) -> Result<Vec<Todo>> {
+        let path = format!(
+            "/api/projects/{}/todos?include_completed={}&sort={}",
+            project_id,
+            include_completed,
+            encode_query(&order.to_string())
+        );
+        self.get(&path).await
+    }
+
+    pub async fn create_todo(&self, project_id: i64, description: &str, details: Option<&str>) -> Result<Todo> {
+        let body = json!({ "description": description, "details": details });
+        self.fetch("POST", &format!("/api/projects/{}/todos", project_id), body).await
+    }
+
+    /// Add a todo from the template called `template`; `description`
+    /// replaces the template's own
+    pub async fn create_todo_from_template(
+        &self,
+        project_id: i64,
+        template: &str,
+        description: Option<&str>,
+    ) -> Result<Todo> {
+        let body = json!({ "template": template, "description": description.unwrap_or("") });
+        self.fetch("POST", &format!("/api/projects/{}/todos", project_id), body).await
+    }
+
+    pub async fn list_templates(&self) -> Result<Vec<TodoTemplate>> {
+        self.get("/api/templates").await
+    }
+
+    pub async fn get_todo(&self, id: i64) -> Result<Todo> {
+        self.get(&format!("/api/todos/{}", id)).await
+    }
+
+    /// A todo with its project, tags and neighbours in the open list
+    pub async fn get_todo_context(&self, id: i64) -> Result<TodoContext> {
+        self.get(&format!("/api/todos/{}/full", id)).await
+    }
+
+    pub async fn delete_todo(&self, id: i64) -> Result<()> {
+        self.send("DELETE", &format!("/api/todos/{}", id), None).await.map(drop)
+    }
+
+    /// Change any of a todo's fields at once and return the result
+    pub async fn patch_todo(&self, id: i64, patch: &TodoPatch) -> Result<Todo> {
+        let body = serde_json::to_value(patch).map_err(|e| DocketError::remote(e.to_string()))?;
+        self.fetch("PATCH", &format!("/api/todos/{}", id), body).await
+    }
+
+    /// A todo's earlier descriptions and details, most recently replaced first
+    pub async fn todo_history(&self, id: i64) -> Result<Vec<TodoVersion>> {
+        self.get(&format!("/api/todos/{}/history", id)).await
+    }
+
+    /// Put back a todo's text from an earlier version and return the result
+    pub async fn restore_todo_version(&self, id: i64, version_id: i64) -> Result<Todo> {
+        self.fetch("POST", &format!("/api/todos/{}/history/{}/restore", id, version_id), json!({})).await
+    }
+
+    /// Link a todo to another, perhaps in another project
+    pub async fn link_todos(&self, id: i64, target_id: i64, kind: LinkKind) -> Result<TodoLink> {
+        let body = json!({ "target_id": target_id, "kind": kind });
+        self.fetch("POST", &format!("/api/todos/{}/links", id), body).await
+    }
+
+    /// The todos a todo links to or is linked from
+    pub async fn related_todos(&self, id: i64) -> Result<Vec<RelatedTodo>> {
+        self.get(&format!("/api/todos/{}/links", id)).await
+    }
+
+    pub async fn unlink_todos(&self, link_id: i64) -> Result<()> {
+        self.send("DELETE", &format!("/api/links/{}", link_id), None).await.map(drop)
+    }
+
+    /// The todos a todo's details mention, then those whose details mention it
+    pub async fn todo_mentions(&self, id: i64) -> Result<Vec<TodoMention>> {
+        self.get(&format!("/api/todos/{}/mentions", id)).await
+    }
+
+    pub async fn toggle_todo(&self, id: i64) -> Result<()> {
+        self.call("PATCH", &format!("/api/todos/{}/toggle", id), json!({})).await
+    }
+
+    pub async fn move_todo(&self, id: i64, direction: Direction) -> Result<()> {
+        self.call("PATCH", &format!("/api/todos/{}/move", id), json!({ "direction": direction })).await
+    }
+
+    pub async fn update_todo_details(&self, id: i64, details: Option<&str>) -> Result<()> {
+        self.call("PATCH", &format!("/api/todos/{}/details", id), json!({ "details": details })).await
+    }
+
+    pub async fn update_todo_estimate(&self, id: i64, estimate: Option<i64>) -> Result<()> {
+        self.call("PATCH", &format!("/api/todos/{}/estimate", id), json!({ "estimate": estimate })).await
+    }
+
+    pub async fn update_todo_due_date(&self, id: i64, due_date: Option<NaiveDate>) -> Result<()> {
+        self.call("PATCH", &format!("/api/todos/{}/due-date", id), json!({ "due_date": due_date })).await
+    }
+
+    /// Link a todo to a JIRA key or URL; `None` clears the link
+    pub async fn update_todo_external_ref(&self, id: i64, external_ref: Option<&str>) -> Result<()> {
+        let body = json!({ "external_ref": external_ref });
+        self.call("PATCH", &format!("/api/todos/{}/external-ref", id), body).await
+    }
+
+    // ===== Batches and Sync =====
+
+    /// Run todo operations atomically, one result per operation
+    pub async fn batch(&self, operations: Vec<BatchOp>) -> Result<Vec<BatchResult>> {
+        let response: Value = self.fetch("POST", "/api/batch", json!({ "operations": operations })).await?;
+        parse(response["results"].to_string().as_bytes())
+    }
+
+    /// Everything created, updated or deleted after the `since` cursor
+    pub async fn changes_since(&self, since: i64) -> Result<ChangeSet> {
+        self.get(&format!("/api/changes?since={}", since)).await
+    }
+
+    /// Push offline edits; ones older than the server's copy come back as conflicts
+    pub async fn push_changes(&self, changes: Vec<PushChange>) -> Result<PushResult> {
+        // Keyed by content: a push resent after going unanswered, even by
+        // the next sync, gets the first one's result instead of creating
+        // its new projects and todos again
+        let body = json!({ "changes": changes });
+        let key = format!("push-{:x}", Sha256::digest(body.to_string().as_bytes()));
+        parse(&self.send_keyed("POST", "/api/changes", Some(&body), Some(&key)).await?)
+    }
+
+    /// The server's version and schema, to check it can sync with this build
+    pub async fn version(&self) -> Result<VersionInfo> {
+        self.get("/api/version").await
+    }
+
+    /// The project behind a share link's token
+    pub async fn shared_project(&self, token: &str) -> Result<SharedProject> {
+        self.get(&format!("/share/{}", encode_query(token))).await
+    }
+
+    // ===== Transport =====
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        parse(&self.send("GET", path, None).await?)
+    }
+
+    /// Send `body` and read the answer
+    async fn fetch<T: DeserializeOwned>(&self, method: &str, path: &str, body: Value) -> Result<T> {
+        parse(&self.send(method, path, Some(&body)).await?)
+    }
+
+    /// Send `body` to an endpoint that answers with no content
+    async fn call(&self, method: &str, path: &str, body: Value) -> Result<()> {
+        self.send(method, path, Some(&body)).await.map(drop)
+    }
+
+    /// Make a request and return the response body. Error answers turn
+    /// back into the `DocketError` the server started from.
+    async fn send(&self, method: &str, path: &str, body: Option<&Value>) -> Result<Vec<u8>> {
+        let key = (method == "POST").then(new_idempotency_key);
+        self.send_keyed(method, path, body, key.as_deref()).await
+    }
+
+    /// `send` with `key` as the `Idempotency-Key`, sent again with the same
+    /// key when the request went out unanswered
+    async fn send_keyed(&self, method: &str, path: &str, body: Option<&Value>, key: Option<&str>) -> Result<Vec<u8>> {
+        let mut attempt = self.attempt(method, path, body, key).await?;
+        if key.is_some() && matches!(attempt, Attempt::Unanswered(_)) {
+            attempt = self.attempt(method, path, body, key).await?;
+        }
+        match attempt {
+            Attempt::Answered(status, body) if (200..300).contains(&status) => Ok(body),
+            Attempt::Answered(status, body) => Err(error_from_response(status, &body)),
+            Attempt::NotSent(message) => {
+                Err(DocketError::Offline(format!("Could not reach {}: {}", self.base_url, message)))
+            }
+            Attempt::Unanswered(message) => Err(DocketError::remote(format!(
+                "No answer from {} ({}); the change may have been made anyway",
+                self.base_url, message
+            ))),
+        }
+    }
+
+    /// Run one request through curl
+    async fn attempt(&self, method: &str, path: &str, body: Option<&Value>, key: Option<&str>) -> Result<Attempt> {
+        // Everything goes in on stdin, so the token never shows up in `ps`
+        let mut config = String::from("silent\nshow-error\n");
+        config.push_str(&format!("connect-timeout = {}\nmax-time = {}\n", CONNECT_TIMEOUT_SECS, MAX_TIME_SECS));
+        config.push_str(&format!("request = \"{}\"\n", method));
+        config.push_str("header = \"Accept: application/json\"\n");
+        if let Some(token) = &self.token {
+            config.push_str(&format!("header = \"{}\"\n", curl_quote(&format!("Authorization: Bearer {}", token))));
+        }
+        if let Some(key) = key {
+            config.push_str(&format!("header = \"{}\"\n", curl_quote(&format!("Idempotency-Key: {}", key))));
+        }
+        if let Some(body) = body {
+            config.push_str("header = \"Content-Type: application/json\"\n");
+            config.push_str(&format!("data-binary = \"{}\"\n", curl_quote(&body.to_string())));
+        }
+        // Written on failures too: how much of the request went out
+        config.push_str("write-out = \"\\n%{http_code} %{size_request}\"\n");
+        config.push_str(&format!("url = \"{}\"\n", curl_quote(&format!("{}{}", self.base_url, path))));
+
+        let mut child = Command::new("curl")
+            .args(["--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|_| DocketError::remote("Failed to run curl, which docket uses to reach the server"))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(config.as_bytes())
+                .await
+                .map_err(|e| DocketError::remote(format!("Failed to talk to curl: {}", e)))?;
+        }
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| DocketError::remote(format!("Failed to talk to curl: {}", e)))?;
+        let (body, status, sent) = split_trailer(output.stdout);
+        if output.status.success() {
+            return Ok(Attempt::Answered(status, body));
+        }
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        // Only a request that never went out is safe to make offline instead
+        Ok(if sent { Attempt::Unanswered(message) } else { Attempt::NotSent(message) })
+    }
+}
+
+/// How one curl run went
+enum Attempt {
+    Answered(u16, Vec<u8>),
+    /// No connection, so the server never saw the request
+    NotSent(String),
+    /// The request went out but no answer came back, e.g. on a timeout
+    Unanswered(String),
+}
+
+/// Split curl's output into the body, the status and whether any of the
+/// request was sent, from the `write-out` line after the body
+fn split_trailer(mut output: Vec<u8>) -> (Vec<u8>, u16, bool) {
+    let split = output.iter().rposition(|b| *b == b'\n').unwrap_or(0);
+    let trailer = String::from_utf8_lossy(&output[split..]).to_string();
+    output.truncate(split);
+    let mut fields = trailer.split_whitespace();
+    let status = fields.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let sent = fields.next().and_then(|s| s.parse::<u64>().ok()).unwrap_or(0) > 0;
+    (output, status, sent)
+}
+
+/// A key no other request from this machine uses
+fn new_idempotency_key() -> String {
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    format!("{:x}-{:x}-{:x}", std::process::id(), nanos, COUNT.fetch_add(1, Ordering::Relaxed))
+}
+
+fn parse<T: DeserializeOwned>(body: &[u8]) -> Result<T> {
+    serde_json::from_slice(body).map_err(|e| DocketError::remote(format!("Unexpected answer from the server: {}", e)))
+}
+
+/// The error behind a non-2xx answer, from the API's `{"error": ...}` bodies
+fn error_from_response(status: u16, body: &[u8]) -> DocketError {
+    let value = serde_json::from_slice::<Value>(body).unwrap_or(Value::Null);
+    let message = value["error"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| String::from_utf8_lossy(body).trim().trim_start_matches("Error: ").to_string());
+    match status {
+        400 => DocketError::validation(field_name(value["field"].as_str().unwrap_or("")), message),
+        401 => DocketError::remote("The server turned down the API token; pass --token or set api_token"),
+        404 => DocketError::not_found(message),
+        409 => DocketError::Conflict {
+            msg: message,
+            suggestion: value["suggestion"].as_str().map(str::to_string),
+        },
+        410 => DocketError::Expired(message),
+        _ => DocketError::remote(format!("The server answered {}: {}", status, message)),
+    }
+}
+
+/// The `field` of a 400 answer as one of the names the core uses
+fn field_name(field: &str) -> &'static str {
+    const FIELDS: &[&str] = &[
+        "name",
+        "description",
+        "estimate",
+        "position",
+        "project_id",
+        "project_ref",
+        "parent_id",
+        "target_id",
+        "slug",
+        "days",
+        "operations",
+        "groom_rules",
+    ];
+    FIELDS.iter().find(|f| **f == field).copied().unwrap_or("input")
+}
+
+/// Percent-encode a value for a URL query string
+pub fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Escape a value for a double-quoted string in a curl config file
+pub fn curl_quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curl_trailer_tells_whether_the_request_went_out() {
+        let (body, status, sent) = split_trailer(b"{\"id\": 1}\n201 148".to_vec());
+        assert_eq!((body.as_slice(), status, sent), (&b"{\"id\": 1}"[..], 201, true));
+        // Connection refused: nothing sent, so offline is safe
+        assert_eq!(split_trailer(b"\n000 0".to_vec()), (Vec::new(), 0, false));
+        // Timed out after sending: the server may have applied it
+        assert_eq!(split_trailer(b"\n000 148".to_vec()), (Vec::new(), 0, true));
+        assert_ne!(new_idempotency_key(), new_idempotency_key());
+    }
+
+    #[test]
+    fn test_error_answers_become_docket_errors() {
+        let body = br#"{"error": "Project name 'Home' is taken", "suggestion": "Home (2)"}"#;
+        match error_from_response(409, body) {
+            DocketError::Conflict { msg, suggestion } => {
+                assert_eq!(msg, "Project name 'Home' is taken");
+                assert_eq!(suggestion.as_deref(), Some("Home (2)"));
+            }
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+        assert!(matches!(
+            error_from_response(400, br#"{"error": "Estimate cannot be negative", "field": "estimate"}"#),
+            DocketError::Validation { field: "estimate", .. }
+        ));
+        assert!(matches!(error_from_response(404, br#"{"error": "Todo 9 not found"}"#), DocketError::NotFound(_)));
+        assert_eq!(
+            error_from_response(500, b"Error: Failed to list projects").to_string(),
+            "The server answered 500: Failed to list projects"
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use clap::{Arg, Command, ValueEnum, ValueHint};
+use std::fmt::Write;
+
+/// Shells supported by `docket completions`
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// A word the shell can offer, with its help text
+struct Candidate {
+    word: String,
+    help: String,
+}
+
+/// What the shell offers after an option that takes a value
+enum Values {
+    /// The option's possible values, e.g. a `ValueEnum`'s variants
+    OneOf(Vec<Candidate>),
+    /// File and directory names, for options with a path value hint
+    Path,
+}
+
+/// A (sub)command reachable from the root. `path` joins command names with
+/// `__` (e.g. `docket__server`) and is what the generated scripts switch on.
+struct Node {
+    path: String,
+    /// (child name, child path) pairs used to walk the command line
+    children: Vec<(String, String)>,
+    candidates: Vec<Candidate>,
+    /// (option word, its values) pairs, e.g. `--theme` with the palettes
+    values: Vec<(String, Values)>,
+}
+
+/// Generate a completion script for `shell` from the CLI definition. The
+/// scripts are written here rather than by clap_complete, which can't be
+/// built offline; they complete subcommands, options and option values.
+pub fn generate(shell: Shell, mut cmd: Command) -> String {
+    cmd.build();
+    let bin = cmd.get_name().to_string();
+    let mut nodes = Vec::new();
+    collect(&cmd, &bin, &mut nodes);
+
+    match shell {
+        Shell::Bash => bash(&bin, &nodes),
+        Shell::Zsh => zsh(&bin, &nodes),
+        Shell::Fish => fish(&bin, &nodes),
+        Shell::Powershell => powershell(&bin, &nodes),
+    }
+}
+
+/// Walk the command tree, recording the words available at each level
+fn collect(cmd: &Command, path: &str, nodes: &mut Vec<Node>) {
+    let mut candidates = Vec::new();
+    let mut children = Vec::new();
+    let mut values = Vec::new();
+
+    for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+        let child_path = format!("{}__{}", path, sub.get_name());
+        children.push((sub.get_name().to_string(), child_path));
+        candidates.push(Candidate {
+            word: sub.get_name().to_string(),
+            help: sub.get_about().map(|a| a.to_string()).unwrap_or_default(),
+        });
+    }
+
+    for arg in cmd.get_arguments().filter(|a| !a.is_hide_set()) {
+        let help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+        if arg.is_positional() {
+            for value in arg.get_possible_values() {
+                candidates.push(Candidate {
+                    word: value.get_name().to_string(),
+                    help: value.get_help().map(|h| h.to_string()).unwrap_or_default(),
+                });
+            }
+            continue;
+        }
+        let words: Vec<String> = arg.get_long().map(|long| format!("--{}", long)).into_iter()
+            .chain(arg.get_short().map(|short| format!("-{}", short)))
+            .collect();
+        for word in words {
+            if let Some(arg_values) = option_values(arg) {
+                values.push((word.clone(), arg_values));
+            }
+            candidates.push(Candidate { word, help: help.clone() });
+        }
+    }
+
+    nodes.push(Node { path: path.to_string(), children, candidates, values });
+
+    for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+        collect(sub, &format!("{}__{}", path, sub.get_name()), nodes);
+    }
+}
+
+/// What to complete after `arg`, if it takes a value the shell can guess
+fn option_values(arg: &Arg) -> Option<Values> {
+    if !arg.get_action().takes_values() {
+        return None;
+    }
+    let possible = arg.get_possible_values();
+    if !possible.is_empty() {
+        return Some(Values::OneOf(
+            possible
+                .iter()
+                .filter(|value| !value.is_hide_set())
+                .map(|value| Candidate {
+                    word: value.get_name().to_string(),
+                    help: value.get_help().map(|h| h.to_string()).unwrap_or_default(),
+                })
+                .collect(),
+        ));
+    }
+    match arg.get_value_hint() {
+        ValueHint::AnyPath | ValueHint::FilePath | ValueHint::DirPath | ValueHint::ExecutablePath => Some(Values::Path),
+        _ => None,
+    }
+}
+
+/// Quote a string for a single-quoted shell literal
+fn single_quoted(s: &str) -> String {
+    s.replace('\'', "'\\''")
+}
+
+fn bash(bin: &str, nodes: &[Node]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "_{}() {{", bin);
+    let _ = writeln!(out, "    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"");
+    let _ = writeln!(out, "    local cmd_path=\"{}\"", bin);
+    let _ = writeln!(out, "    local i");
+    let _ = writeln!(out, "    for ((i = 1; i < COMP_CWORD; i++)); do");
+    let _ = writeln!(out, "        case \"${{cmd_path}}:${{COMP_WORDS[i]}}\" in");
+    for node in nodes {
+        for (name, child) in &node.children {
+            let _ = writeln!(out, "            {}:{}) cmd_path=\"{}\" ;;", node.path, name, child);
+        }
+    }
+    let _ = writeln!(out, "        esac");
+    let _ = writeln!(out, "    done");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    case \"${{cmd_path}}:${{COMP_WORDS[COMP_CWORD-1]}}\" in");
+    for node in nodes {
+        for (word, values) in &node.values {
+            let reply = match values {
+                Values::OneOf(words) => {
+                    let words: Vec<&str> = words.iter().map(|c| c.word.as_str()).collect();
+                    format!("compgen -W \"{}\" -- \"${{cur}}\"", words.join(" "))
+                }
+                Values::Path => "compgen -f -- \"${cur}\"".to_string(),
+            };
+            let _ = writeln!(out, "        {}:{}) COMPREPLY=( $({}) ); return ;;", node.path, word, reply);
+        }
+    }
+    let _ = writeln!(out, "    esac");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    local opts=\"\"");
+    let _ = writeln!(out, "    case \"${{cmd_path}}\" in");
+    for node in nodes {
+        let words: Vec<&str> = node.candidates.iter().map(|c| c.word.as_str()).collect();
+        let _ = writeln!(out, "        {}) opts=\"{}\" ;;", node.path, words.join(" "));
+    }
+    let _ = writeln!(out, "    esac");
+    let _ = writeln!(out, "    COMPREPLY=( $(compgen -W \"${{opts}}\" -- \"${{cur}}\") )");
+    let _ = writeln!(out, "}}");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "complete -F _{} {}", bin, bin);
+    out
+}
+
+fn zsh(bin: &str, nodes: &[Node]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "#compdef {}", bin);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "_{}() {{", bin);
+    let _ = writeln!(out, "    local cmd_path={} i", bin);
+    let _ = writeln!(out, "    for ((i = 2; i < CURRENT; i++)); do");
+    let _ = writeln!(out, "        case \"${{cmd_path}}:${{words[i]}}\" in");
+    for node in nodes {
+        for (name, child) in &node.children {
+            let _ = writeln!(out, "            '{}:{}') cmd_path={} ;;", node.path, name, child);
+        }
+    }
+    let _ = writeln!(out, "        esac");
+    let _ = writeln!(out, "    done");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    local -a candidates");
+    let _ = writeln!(out, "    case \"${{cmd_path}}:${{words[CURRENT-1]}}\" in");
+    for node in nodes {
+        for (word, values) in &node.values {
+            let _ = writeln!(out, "        '{}:{}')", node.path, single_quoted(word));
+            match values {
+                Values::OneOf(words) => {
+                    let _ = writeln!(out, "            candidates=(");
+                    for c in words {
+                        let entry = format!("{}:{}", c.word.replace(':', "\\:"), c.help);
+                        let _ = writeln!(out, "                '{}'", single_quoted(&entry));
+                    }
+                    let _ = writeln!(out, "            )");
+                    let _ = writeln!(out, "            _describe -t values 'value' candidates");
+                }
+                Values::Path => {
+                    let _ = writeln!(out, "            _files");
+                }
+            }
+            let _ = writeln!(out, "            return");
+            let _ = writeln!(out, "            ;;");
+        }
+    }
+    let _ = writeln!(out, "    esac");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    case $cmd_path in");
+    for node in nodes {
+        let _ = writeln!(out, "        {})", node.path);
+        let _ = writeln!(out, "            candidates=(");
+        for c in &node.candidates {
+            let entry = format!("{}:{}", c.word.replace(':', "\\:"), c.help);
+            let _ = writeln!(out, "                '{}'", single_quoted(&entry));
+        }
+        let _ = writeln!(out, "            )");
+        let _ = writeln!(out, "            ;;");
+    }
+    let _ = writeln!(out, "    esac");
+    let _ = writeln!(out, "    _describe -t values '{}' candidates", bin);
+    let _ = writeln!(out, "}}");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "_{} \"$@\"", bin);
+    out
+}
+
+fn fish(bin: &str, nodes: &[Node]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "function __{}_path", bin);
+    let _ = writeln!(out, "    set -l cmd_path {}", bin);
+    let _ = writeln!(out, "    for word in (commandline -opc)[2..-1]");
+    let _ = writeln!(out, "        switch \"$cmd_path:$word\"");
+    for node in nodes {
+        for (name, child) in &node.children {
+            let _ = writeln!(out, "            case '{}:{}'", node.path, name);
+            let _ = writeln!(out, "                set cmd_path {}", child);
+        }
+    }
+    let _ = writeln!(out, "        end");
+    let _ = writeln!(out, "    end");
+    let _ = writeln!(out, "    echo $cmd_path");
+    let _ = writeln!(out, "end");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "complete -c {} -f", bin);
+    for node in nodes {
+        let condition = format!("test (__{}_path) = {}", bin, node.path);
+        for c in &node.candidates {
+            let mut word = if let Some(long) = c.word.strip_prefix("--") {
+                format!("-l {}", long)
+            } else if let Some(short) = c.word.strip_prefix('-') {
+                format!("-s {}", short)
+            } else {
+                format!("-a '{}'", single_quoted(&c.word))
+            };
+            match node.values.iter().find(|(option, _)| *option == c.word).map(|(_, values)| values) {
+                Some(Values::OneOf(words)) => {
+                    let words: Vec<&str> = words.iter().map(|c| c.word.as_str()).collect();
+                    let _ = write!(word, " -x -a '{}'", single_quoted(&words.join(" ")));
+                }
+                Some(Values::Path) => word.push_str(" -r -F"),
+                None => {}
+            }
+            let _ = writeln!(
+                out,
+                "complete -c {} -n '{}' {} -d '{}'",
+                bin,
+                condition,
+                word,
+                single_quoted(&c.help)
+            );
+        }
+    }
+    out
+}
+
+fn powershell(bin: &str, nodes: &[Node]) -> String {
+    let quote = |s: &str| s.replace('\'', "''");
+    let mut out = String::new();
+    let _ = writeln!(out, "Register-ArgumentCompleter -Native -CommandName '{}' -ScriptBlock {{", bin);
+    let _ = writeln!(out, "    param($wordToComplete, $commandAst, $cursorPosition)");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    $cmd_path = '{}'", bin);
+    let _ = writeln!(out, "    $prev = ''");
+    let _ = writeln!(out, "    foreach ($element in $commandAst.CommandElements | Select-Object -Skip 1) {{");
+    let _ = writeln!(out, "        if ($element.Extent.EndOffset -ge $cursorPosition) {{ break }}");
+    let _ = writeln!(out, "        switch (\"${{cmd_path}}:$($element.ToString())\") {{");
+    for node in nodes {
+        for (name, child) in &node.children {
+            let _ = writeln!(out, "            '{}:{}' {{ $cmd_path = '{}' }}", node.path, name, child);
+        }
+    }
+    let _ = writeln!(out, "        }}");
+    let _ = writeln!(out, "        $prev = $element.ToString()");
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    switch (\"${{cmd_path}}:$prev\") {{");
+    for node in nodes {
+        for (word, values) in &node.values {
+            match values {
+                Values::OneOf(words) => {
+                    let words: Vec<String> = words.iter().map(|c| format!("'{}'", quote(&c.word))).collect();
+                    let _ = writeln!(
+                        out,
+                        "        '{}:{}' {{ return {} | Where-Object {{ $_ -like \"$wordToComplete*\" }} }}",
+                        node.path,
+                        word,
+                        words.join(", ")
+                    );
+                }
+                Values::Path => {
+                    let files = "[System.Management.Automation.CompletionCompleters]::CompleteFilename($wordToComplete)";
+                    let _ = writeln!(out, "        '{}:{}' {{ return {} }}", node.path, word, files);
+                }
+            }
+        }
+    }
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    $candidates = switch ($cmd_path) {{");
+    for node in nodes {
+        let _ = writeln!(out, "        '{}' {{ @(", node.path);
+        for c in &node.candidates {
+            let tooltip = if c.help.is_empty() { &c.word } else { &c.help };
+            let _ = writeln!(
+                out,
+                "            [System.Management.Automation.CompletionResult]::new('{}', '{}', 'ParameterValue', '{}')",
+                quote(&c.word),
+                quote(&c.word),
+                quote(tooltip)
+            );
+        }
+        let _ = writeln!(out, "        ) }}");
+    }
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out, "    $candidates | Where-Object {{ $_.CompletionText -like \"$wordToComplete*\" }}");
+    let _ = writeln!(out, "}}");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_subcommands_are_walked() {
+        let cmd = Command::new("docket")
+            .arg(clap::Arg::new("port").long("port").short('p').help("Port"))
+            .subcommand(Command::new("server").about("Start the web server"));
+
+        let script = generate(Shell::Bash, cmd.clone());
+        assert!(script.contains("docket:server) cmd_path=\"docket__server\""));
+        assert!(script.contains("docket) opts=\"server help --port -p"));
+
+        let script = generate(Shell::Fish, cmd);
+        assert!(script.contains("-a 'server' -d 'Start the web server'"));
+        assert!(script.contains("-l port -d 'Port'"));
+    }
+
+    #[test]
+    fn test_option_values_are_completed() {
+        let cmd = Command::new("docket")
+            .arg(clap::Arg::new("shell").long("shell").value_parser(clap::builder::EnumValueParser::<Shell>::new()))
+            .arg(clap::Arg::new("db").long("db").value_hint(ValueHint::FilePath))
+            .arg(clap::Arg::new("fresh").long("fresh").action(clap::ArgAction::SetTrue));
+
+        let script = generate(Shell::Bash, cmd.clone());
+        assert!(script.contains("docket:--shell) COMPREPLY=( $(compgen -W \"bash zsh fish powershell\""));
+        assert!(script.contains("docket:--db) COMPREPLY=( $(compgen -f"));
+        assert!(!script.contains("docket:--fresh)"));
+
+        let script = generate(Shell::Fish, cmd.clone());
+        assert!(script.contains("-l shell -x -a 'bash zsh fish powershell'"));
+        assert!(script.contains("-l db -r -F"));
+
+        let script = generate(Shell::Zsh, cmd);
+        assert!(script.contains("'docket:--db')\n            _files"));
+    }
+}
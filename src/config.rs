@@ -1,36 +1,407 @@
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
-use std::path::PathBuf;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::level_filters::LevelFilter;
+
+use crate::core::db::PoolSizes;
+use crate::core::groom::GroomRule;
+use crate::core::limits::Limits;
+use crate::core::prune::PrunePolicy;
+use crate::core::time::DisplayZone;
+use crate::plugins::PluginConfig;
+use crate::tui::columns::{Column, ProjectColumn, TodoColumn};
+use crate::tui::theme::{Icons, Palette};
 
 /// Application configuration
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database_path: PathBuf,
+    /// The database used without `--profile` or `--db`; profiles live beside it
+    pub main_database_path: PathBuf,
+    /// Profile whose database is open, `None` for the main one
+    pub profile: Option<String>,
+    /// Connections kept open to the database, for writes and for lists
+    pub pool_sizes: PoolSizes,
     pub server_port: u16,
+    /// Address the web server listens on, or `unix:<path>` for a Unix socket
+    pub server_bind: String,
+    /// Days without activity before a finished project is suggested for archiving
+    pub stale_after_days: i64,
+    /// Initial todo ordering in the TUI, by `:sort` name
+    pub default_sort: Option<String>,
+    /// Reject changes made through the web API
+    pub read_only: bool,
+    /// Browser origins allowed to call the web API; empty allows any origin
+    pub cors_origins: Vec<String>,
+    /// Token required by `GET /api/widget`, for dashboards; grants nothing else
+    pub widget_token: Option<String>,
+    /// Bearer token the rest of the API requires when set; also what
+    /// `docket --remote` sends when no `--token` is given
+    pub api_token: Option<String>,
+    /// `user:pass` the web server asks browsers for on every page and API call
+    pub basic_auth: Option<String>,
+    /// Bearer token for `/api/admin/`, which stays off without one
+    pub admin_token: Option<String>,
+    /// Caps on projects, todos and request sizes, for shared instances
+    pub limits: Limits,
+    /// Put uncompleted todos back where they were, rather than at the end
+    pub restore_positions: bool,
+    /// Time zone timestamps are shown in and "today" is judged by
+    pub timezone: DisplayZone,
+    /// sendmail-compatible command that delivers `docket digest --email`
+    pub mail_command: String,
+    /// Sender address for digest mail; the mail command's default when unset
+    pub mail_from: Option<String>,
+    /// Notes vault directory that `docket vault` mirrors projects into
+    pub vault_dir: Option<PathBuf>,
+    /// JIRA site for `docket import jira` and ticket status, e.g. `https://acme.atlassian.net`
+    pub jira_url: Option<String>,
+    /// Account email for JIRA Cloud; without it the token is sent as a bearer token
+    pub jira_user: Option<String>,
+    pub jira_token: Option<String>,
+    /// Project for each git repository, keyed by repository directory name
+    /// or `repo@branch` (used by `docket here` and `docket add`)
+    pub git_projects: BTreeMap<String, String>,
+    /// TUI colour scheme; `NO_COLOR` forces `no-color`
+    pub theme: Palette,
+    /// Draw the TUI with ASCII only, for terminals without Unicode
+    pub ascii: bool,
+    /// Glyphs for todo status, due dates and projects
+    pub icons: Icons,
+    /// Columns of the todo table, in order
+    pub todo_columns: Vec<TodoColumn>,
+    /// Columns of the project table, in order
+    pub project_columns: Vec<ProjectColumn>,
+    /// How long a todo stays selected before its preview pops up; 0 turns it off
+    pub preview_delay_ms: u64,
+    /// Rules `docket groom` applies to long-open todos
+    pub groom_rules: Vec<GroomRule>,
+    /// Apply `groom_rules` whenever the TUI starts
+    pub groom_on_start: bool,
+    /// How long `docket prune` keeps completed todos, and whether it archives them
+    pub prune: Option<PrunePolicy>,
+    /// Apply the `prune` policy whenever the TUI starts
+    pub prune_on_start: bool,
+    /// Tags to add and commands to run when projects and todos change
+    pub plugins: Vec<PluginConfig>,
+    /// File tracing output is appended to; the TUI logs nowhere without one
+    pub log_file: Option<PathBuf>,
+    /// Least severe tracing output logged
+    pub log_level: LevelFilter,
+}
+
+/// Built-in settings, without reading the config file or the environment;
+/// `load` falls back to these
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database_path: PathBuf::from("docket.db"),
+            main_database_path: PathBuf::from("docket.db"),
+            profile: None,
+            pool_sizes: PoolSizes::default(),
+            server_port: 3000,
+            server_bind: "0.0.0.0".to_string(),
+            stale_after_days: 14,
+            default_sort: None,
+            read_only: false,
+            cors_origins: Vec::new(),
+            widget_token: None,
+            api_token: None,
+            basic_auth: None,
+            admin_token: None,
+            limits: Limits::default(),
+            restore_positions: true,
+            timezone: DisplayZone::default(),
+            mail_command: "sendmail -t".to_string(),
+            mail_from: None,
+            vault_dir: None,
+            jira_url: None,
+            jira_user: None,
+            jira_token: None,
+            git_projects: BTreeMap::new(),
+            theme: Palette::default(),
+            ascii: false,
+            icons: Icons::default(),
+            todo_columns: TodoColumn::DEFAULT.to_vec(),
+            project_columns: ProjectColumn::DEFAULT.to_vec(),
+            preview_delay_ms: 1000,
+            groom_rules: Vec::new(),
+            groom_on_start: false,
+            prune: None,
+            prune_on_start: false,
+            plugins: Vec::new(),
+            log_file: None,
+            log_level: LevelFilter::INFO,
+        }
+    }
+}
+
+/// Optional settings read from `config.json` in the config directory.
+/// Environment variables take precedence over the file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    database_path: Option<PathBuf>,
+    pool_size: Option<u32>,
+    read_pool_size: Option<u32>,
+    server_port: Option<u16>,
+    server_bind: Option<String>,
+    stale_after_days: Option<i64>,
+    default_sort: Option<String>,
+    read_only: Option<bool>,
+    cors_origins: Option<Vec<String>>,
+    widget_token: Option<String>,
+    api_token: Option<String>,
+    basic_auth: Option<String>,
+    admin_token: Option<String>,
+    limits: Option<Limits>,
+    restore_positions: Option<bool>,
+    timezone: Option<String>,
+    mail_command: Option<String>,
+    mail_from: Option<String>,
+    vault_dir: Option<PathBuf>,
+    jira_url: Option<String>,
+    jira_user: Option<String>,
+    jira_token: Option<String>,
+    git_projects: Option<BTreeMap<String, String>>,
+    theme: Option<String>,
+    ascii: Option<bool>,
+    icons: Option<String>,
+    todo_columns: Option<Vec<TodoColumn>>,
+    project_columns: Option<Vec<ProjectColumn>>,
+    preview_delay_ms: Option<u64>,
+    groom_rules: Option<Vec<GroomRule>>,
+    groom_on_start: Option<bool>,
+    prune: Option<PrunePolicy>,
+    prune_on_start: Option<bool>,
+    plugins: Option<Vec<PluginConfig>>,
+    log_file: Option<PathBuf>,
+    log_level: Option<String>,
 }
 
 impl Config {
-    /// Load configuration from environment and defaults
+    /// Load configuration from environment, config file and defaults
     pub fn load() -> Result<Self> {
-        let database_path = Self::get_database_path()?;
-        let server_port = std::env::var("DOCKET_PORT")
+        let file = Self::read_config_file()?;
+
+        let database_path = match std::env::var("DOCKET_DB_PATH") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => match file.database_path {
+                Some(path) => path,
+                None => Self::config_dir()?.join("docket.db"),
+            },
+        };
+        let defaults = Self::default();
+        let pool_sizes = PoolSizes {
+            write: env_parse("DOCKET_POOL_SIZE").or(file.pool_size).unwrap_or(defaults.pool_sizes.write),
+            read: env_parse("DOCKET_READ_POOL_SIZE").or(file.read_pool_size).unwrap_or(defaults.pool_sizes.read),
+        };
+        if pool_sizes.write == 0 {
+            anyhow::bail!("pool_size must be at least 1");
+        }
+        let server_port = env_parse("DOCKET_PORT")
+            .or(file.server_port)
+            .unwrap_or(defaults.server_port);
+        let server_bind = std::env::var("DOCKET_BIND")
+            .ok()
+            .or(file.server_bind)
+            .unwrap_or(defaults.server_bind);
+        let stale_after_days = env_parse("DOCKET_STALE_DAYS")
+            .or(file.stale_after_days)
+            .unwrap_or(defaults.stale_after_days);
+        let preview_delay_ms = env_parse("DOCKET_PREVIEW_DELAY_MS")
+            .or(file.preview_delay_ms)
+            .unwrap_or(defaults.preview_delay_ms);
+        let read_only = env_parse("DOCKET_READ_ONLY")
+            .or(file.read_only)
+            .unwrap_or(defaults.read_only);
+        let restore_positions = env_parse("DOCKET_RESTORE_POSITIONS")
+            .or(file.restore_positions)
+            .unwrap_or(defaults.restore_positions);
+        let timezone = match env_parse("DOCKET_TIMEZONE") {
+            Some(zone) => zone,
+            None => match file.timezone {
+                Some(zone) => zone.parse().map_err(anyhow::Error::msg)?,
+                None => DisplayZone::default(),
+            },
+        };
+
+        // https://no-color.org: any non-empty NO_COLOR turns colours off
+        let theme = if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            Palette::NoColor
+        } else {
+            match env_parse("DOCKET_THEME") {
+                Some(theme) => theme,
+                None => match file.theme {
+                    Some(theme) => theme.parse().map_err(anyhow::Error::msg)?,
+                    None => Palette::default(),
+                },
+            }
+        };
+        let basic_auth = std::env::var("DOCKET_BASIC_AUTH").ok().or(file.basic_auth).filter(|auth| !auth.is_empty());
+        if basic_auth.as_deref().is_some_and(|auth| !auth.contains(':')) {
+            anyhow::bail!("basic_auth must be given as user:pass");
+        }
+        let ascii = env_parse("DOCKET_ASCII").or(file.ascii).unwrap_or(false);
+        let icons = match env_parse("DOCKET_ICONS") {
+            Some(icons) => icons,
+            None => match file.icons {
+                Some(icons) => icons.parse().map_err(anyhow::Error::msg)?,
+                None => Icons::default(),
+            },
+        };
+
+        let log_level = match env_parse("DOCKET_LOG_LEVEL") {
+            Some(level) => level,
+            None => match file.log_level {
+                Some(level) => level.parse().map_err(|_| anyhow::anyhow!("Unknown log_level '{}'", level))?,
+                None => LevelFilter::INFO,
+            },
+        };
+
+        let mail_command = std::env::var("DOCKET_MAIL_COMMAND")
             .ok()
-            .and_then(|p| p.parse().ok())
-            .unwrap_or(3000);
+            .or(file.mail_command)
+            .unwrap_or(defaults.mail_command);
 
         Ok(Self {
+            main_database_path: database_path.clone(),
             database_path,
+            profile: None,
+            pool_sizes,
             server_port,
+            server_bind,
+            stale_after_days,
+            default_sort: file.default_sort,
+            read_only,
+            cors_origins: file.cors_origins.unwrap_or_default(),
+            widget_token: std::env::var("DOCKET_WIDGET_TOKEN").ok().or(file.widget_token),
+            api_token: std::env::var("DOCKET_API_TOKEN").ok().or(file.api_token),
+            basic_auth,
+            admin_token: std::env::var("DOCKET_ADMIN_TOKEN").ok().or(file.admin_token),
+            limits: file.limits.unwrap_or_default(),
+            restore_positions,
+            timezone,
+            mail_command,
+            mail_from: file.mail_from,
+            vault_dir: std::env::var_os("DOCKET_VAULT_DIR").map(PathBuf::from).or(file.vault_dir),
+            jira_url: file.jira_url,
+            jira_user: file.jira_user,
+            jira_token: std::env::var("DOCKET_JIRA_TOKEN").ok().or(file.jira_token),
+            git_projects: file.git_projects.unwrap_or_default(),
+            theme,
+            ascii,
+            icons,
+            // An empty list would leave a table with nothing to show
+            todo_columns: file
+                .todo_columns
+                .filter(|columns| !columns.is_empty())
+                .unwrap_or_else(|| TodoColumn::DEFAULT.to_vec()),
+            project_columns: file
+                .project_columns
+                .filter(|columns| !columns.is_empty())
+                .unwrap_or_else(|| ProjectColumn::DEFAULT.to_vec()),
+            preview_delay_ms,
+            groom_rules: file.groom_rules.unwrap_or_default(),
+            groom_on_start: env_parse("DOCKET_GROOM_ON_START").or(file.groom_on_start).unwrap_or(false),
+            prune: file.prune,
+            prune_on_start: env_parse("DOCKET_PRUNE_ON_START").or(file.prune_on_start).unwrap_or(false),
+            plugins: file.plugins.unwrap_or_default(),
+            log_file: std::env::var_os("DOCKET_LOG_FILE").map(PathBuf::from).or(file.log_file),
+            log_level,
         })
     }
 
-    /// Get the database file path, creating parent directories if needed
-    fn get_database_path() -> Result<PathBuf> {
-        // Check for environment override first
-        if let Ok(path) = std::env::var("DOCKET_DB_PATH") {
-            return Ok(PathBuf::from(path));
+    /// When the config file was last changed, used to notice edits while running
+    pub fn file_modified_at() -> Option<SystemTime> {
+        let path = Self::config_file_path().ok()?;
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Path of the config file (`DOCKET_CONFIG` overrides the default location)
+    pub fn config_file_path() -> Result<PathBuf> {
+        match std::env::var("DOCKET_CONFIG") {
+            Ok(path) => Ok(PathBuf::from(path)),
+            Err(_) => Ok(Self::config_dir()?.join("config.json")),
         }
+    }
 
+    /// Open profile `name`'s database, `profiles/<name>/docket.db` next to
+    /// the main one, so its session and caches stay apart too. `default` is
+    /// the main database.
+    pub fn use_profile(&mut self, name: &str) -> Result<()> {
+        let name = valid_profile_name(name)?;
+        if name == DEFAULT_PROFILE {
+            self.database_path = self.main_database_path.clone();
+            self.profile = None;
+            return Ok(());
+        }
+        let dir = self.profiles_dir().join(name);
+        std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        self.database_path = dir.join("docket.db");
+        self.profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Name of the open profile
+    pub fn profile_name(&self) -> &str {
+        self.profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Every profile with a database, `default` first
+    pub fn profiles(&self) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(self.profiles_dir())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join("docket.db").exists())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        names.sort();
+        names.insert(0, DEFAULT_PROFILE.to_string());
+        names
+    }
+
+    fn profiles_dir(&self) -> PathBuf {
+        self.main_database_path.with_file_name("profiles")
+    }
+
+    /// Where the TUI remembers its pane layout
+    pub fn layout_file_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("layout.json"))
+    }
+
+    /// Where the TUI remembers what was open when it quit; kept next to the
+    /// database, since it refers to that database's projects and todos
+    pub fn session_file_path(&self) -> PathBuf {
+        self.database_path.with_file_name("session.json")
+    }
+
+    /// Read the config file if it exists
+    fn read_config_file() -> Result<ConfigFile> {
+        let path = Self::config_file_path()?;
+        if !path.exists() {
+            return Ok(ConfigFile::default());
+        }
+        parse_config_file(&path)
+    }
+
+    /// Get the config directory, creating it if needed
+    /// Where `--log-file` logs when given no path: `~/.local/state/docket/docket.log`
+    /// on Linux, next to the database elsewhere
+    pub fn default_log_path(&self) -> PathBuf {
+        let dirs = ProjectDirs::from("com", "docket", "docket");
+        let state_dir = dirs.as_ref().and_then(|dirs| dirs.state_dir());
+        match state_dir {
+            Some(dir) => dir.join("docket.log"),
+            None => self.database_path.with_file_name("docket.log"),
+        }
+    }
+
+    fn config_dir() -> Result<PathBuf> {
         // Use XDG config directory
         let proj_dirs = ProjectDirs::from("com", "docket", "docket")
             .context("Failed to determine project directories")?;
@@ -39,8 +410,31 @@ impl Config {
         std::fs::create_dir_all(config_dir)
             .context("Failed to create config directory")?;
 
-        Ok(config_dir.join("docket.db"))
+        Ok(config_dir.to_path_buf())
+    }
+}
+
+/// Parse an environment variable, ignoring it if unset or malformed
+/// Profile name that means the main database
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Profile names become directory names, so keep them to letters, digits, `-` and `_`
+pub fn valid_profile_name(name: &str) -> Result<&str> {
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        anyhow::bail!("Profile names use letters, digits, - and _ only, e.g. work");
     }
+    Ok(name)
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+fn parse_config_file(path: &Path) -> Result<ConfigFile> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("Invalid config file {}", path.display()))
 }
 
 #[cfg(test)]
@@ -53,4 +447,44 @@ mod tests {
         assert!(config.database_path.to_string_lossy().contains("docket.db"));
         assert!(config.server_port > 0);
     }
+
+    #[test]
+    fn test_config_file_rejects_unknown_keys() {
+        let path = std::env::temp_dir().join(format!("docket-config-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{"server_bind": "127.0.0.1", "server_port": 8080, "read_only": true, "cors_origins": ["https://example.com"], "widget_token": "s3cret"}"#,
+        )
+        .unwrap();
+        let file = parse_config_file(&path).unwrap();
+        assert_eq!(file.server_bind.as_deref(), Some("127.0.0.1"));
+        assert_eq!(file.server_port, Some(8080));
+        assert_eq!(file.read_only, Some(true));
+        assert_eq!(file.cors_origins, Some(vec!["https://example.com".to_string()]));
+        assert_eq!(file.widget_token.as_deref(), Some("s3cret"));
+
+        std::fs::write(&path, r#"{"server_adress": "127.0.0.1"}"#).unwrap();
+        assert!(parse_config_file(&path).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_profiles_keep_their_own_database() {
+        let dir = std::env::temp_dir().join(format!("docket-profiles-{}", std::process::id()));
+        let mut config = Config::default();
+        config.main_database_path = dir.join("docket.db");
+        config.database_path = config.main_database_path.clone();
+
+        config.use_profile("work").unwrap();
+        assert_eq!(config.database_path, dir.join("profiles/work/docket.db"));
+        assert_eq!(config.session_file_path(), dir.join("profiles/work/session.json"));
+        assert_eq!(config.profile_name(), "work");
+        std::fs::write(&config.database_path, "").unwrap();
+        assert_eq!(config.profiles(), ["default", "work"]);
+
+        config.use_profile("default").unwrap();
+        assert_eq!(config.database_path, dir.join("docket.db"));
+        assert!(config.use_profile("../home").is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
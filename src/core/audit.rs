@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+use super::error::DocketError;
+
+/// Actor recorded for changes made outside the web server (TUI, CLI)
+pub const LOCAL_ACTOR: &str = "local";
+
+/// Entries per page unless `limit` asks for another number
+pub const DEFAULT_AUDIT_LIMIT: i64 = 50;
+
+/// Most entries one page may hold
+pub const MAX_AUDIT_LIMIT: i64 = 500;
+
+tokio::task_local! {
+    static ACTOR: String;
+}
+
+/// Run `future` with changes it makes recorded as done by `actor`. The web
+/// server wraps each request in this once it knows who sent it.
+pub async fn as_actor<F: Future>(actor: String, future: F) -> F::Output {
+    ACTOR.scope(actor, future).await
+}
+
+/// Who the current change is being made by
+pub fn current_actor() -> String {
+    ACTOR.try_with(String::clone).unwrap_or_else(|_| LOCAL_ACTOR.to_string())
+}
+
+/// What a change touched: a todo or workspace when it names one, otherwise
+/// its project
+pub fn entity(project_id: Option<i64>, todo_id: Option<i64>, workspace_id: Option<i64>) -> (&'static str, Option<i64>) {
+    match (todo_id, workspace_id) {
+        (Some(id), _) => ("todo", Some(id)),
+        (None, Some(id)) => ("workspace", Some(id)),
+        (None, None) => ("project", project_id),
+    }
+}
+
+/// One recorded change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct AuditEntry {
+    pub id: i64,
+    pub at: DateTime<Utc>,
+    pub actor: String,
+    /// Change kind, e.g. `todo.toggled`
+    pub action: String,
+    /// `project`, `todo` or `workspace`
+    pub entity: String,
+    pub entity_id: Option<i64>,
+    pub project_id: Option<i64>,
+}
+
+/// Filters for the audit log; every one is optional
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct AuditQuery {
+    /// `project`, `todo` or `workspace`
+    pub entity: Option<String>,
+    pub entity_id: Option<i64>,
+    pub actor: Option<String>,
+    /// Only changes at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only changes before this time
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl AuditQuery {
+    /// Page size, 50 unless asked otherwise
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_AUDIT_LIMIT)
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0)
+    }
+
+    pub fn validate(&self) -> Result<(), DocketError> {
+        if let Some(entity) = self.entity.as_deref().filter(|e| !matches!(*e, "project" | "todo" | "workspace")) {
+            return Err(DocketError::validation(
+                "entity",
+                format!("Unknown entity '{}' (expected project, todo or workspace)", entity),
+            ));
+        }
+        if !(1..=MAX_AUDIT_LIMIT).contains(&self.limit()) {
+            return Err(DocketError::validation(
+                "limit",
+                format!("limit must be between 1 and {}", MAX_AUDIT_LIMIT),
+            ));
+        }
+        if self.offset() < 0 {
+            return Err(DocketError::validation("offset", "offset cannot be negative"));
+        }
+        Ok(())
+    }
+}
+
+/// A page of audit entries, newest first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    /// Entries matching the filters across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Timestamps as the database stores them, so they compare as text
+pub fn db_timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_actor_is_scoped_to_the_request() {
+        assert_eq!(current_actor(), LOCAL_ACTOR);
+        let inside = as_actor("alice".to_string(), async { current_actor() }).await;
+        assert_eq!(inside, "alice");
+        assert_eq!(current_actor(), LOCAL_ACTOR);
+
+        assert_eq!(entity(Some(1), Some(4), None), ("todo", Some(4)));
+        // Reordering a project's todos names no single todo
+        assert_eq!(entity(Some(1), None, None), ("project", Some(1)));
+        assert_eq!(entity(None, None, Some(2)), ("workspace", Some(2)));
+
+        let query = AuditQuery { limit: Some(0), ..Default::default() };
+        assert!(query.validate().is_err());
+        let query = AuditQuery { entity: Some("user".to_string()), ..Default::default() };
+        assert!(query.validate().is_err());
+        assert!(AuditQuery::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use super::models::Todo;
+
+/// One operation of a `POST /api/batch` request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOp {
+    Create {
+        project_id: i64,
+        description: String,
+        details: Option<String>,
+    },
+    Toggle { id: i64 },
+    Delete { id: i64 },
+    Move { id: i64, direction: Direction },
+}
+
+/// Which way `move` shifts a todo in its list
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+/// Outcome of one batch operation, reported in request order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchResult {
+    Create { todo: Todo },
+    Toggle { todo: Todo },
+    Delete { id: i64, project_id: i64 },
+    Move { todo: Todo },
+}
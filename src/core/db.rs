@@ -1,160 +1,615 @@
-use anyhow::{Context, Result};
-use chrono::Utc;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
-use sqlx::Row;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::{Connection, FromRow, Row, Sqlite, Transaction};
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use super::models::{Project, ProjectWithStats, Todo};
+use super::audit::{self, AuditEntry, AuditQuery};
+use super::batch::{BatchOp, BatchResult, Direction};
+use super::error::{DbContext, DocketError, Result};
+use super::models::{
+    IdempotentRequest, LinkKind, Project, ProjectSettings, ProjectStats, ProjectWithStats, Todo, TodoLink, TodoTemplate, TodoVersion, Tombstone, Totals, WeeklyCompletions, Workspace, OLDEST_OPEN_TODOS,
+};
+use super::references;
+use super::search;
+use super::sort::TodoOrder;
+use super::sync::{ChangeSet, PullState};
+
+/// Schema migrations in order. A database at `user_version` N has had the
+/// first N applied; 001 is idempotent so databases that predate versioning
+/// (version 0) simply re-run it.
+const MIGRATIONS: &[&str] = &[
+    include_str!("../../migrations/001_init.sql"),
+    include_str!("../../migrations/002_unique_project_names.sql"),
+    include_str!("../../migrations/003_project_review.sql"),
+    include_str!("../../migrations/004_todo_estimates.sql"),
+    include_str!("../../migrations/005_project_target_date.sql"),
+    include_str!("../../migrations/006_sync.sql"),
+    include_str!("../../migrations/007_completed_position.sql"),
+    include_str!("../../migrations/008_project_settings.sql"),
+    include_str!("../../migrations/009_todo_due_date.sql"),
+    include_str!("../../migrations/010_todo_external_ref.sql"),
+    include_str!("../../migrations/011_idempotency_keys.sql"),
+    include_str!("../../migrations/012_todo_templates.sql"),
+    include_str!("../../migrations/013_secrets.sql"),
+    include_str!("../../migrations/014_project_slug.sql"),
+    include_str!("../../migrations/015_pull_state.sql"),
+    include_str!("../../migrations/016_archived_todos.sql"),
+    include_str!("../../migrations/017_todo_history.sql"),
+    include_str!("../../migrations/018_audit_log.sql"),
+    include_str!("../../migrations/019_workspaces.sql"),
+    include_str!("../../migrations/020_project_parent.sql"),
+    include_str!("../../migrations/021_todo_links.sql"),
+    include_str!("../../migrations/022_todo_references.sql"),
+];
+
+/// The `user_version` a database is at once every migration is applied
+pub const SCHEMA_VERSION: usize = MIGRATIONS.len();
+
+/// Schema version that adds `todo_references`, filled from the details
+/// already there once it is applied: mentions can't be parsed in SQL
+const REFERENCES_VERSION: usize = 22;
+
+/// Projects joined with their todo statistics; callers append WHERE/GROUP BY clauses
+const PROJECT_STATS_SELECT: &str = r#"
+    SELECT
+        p.*,
+        COUNT(t.id) as total_todos,
+        COUNT(CASE WHEN t.completed_at IS NOT NULL THEN 1 END) as completed_todos,
+        MAX(
+            datetime(p.created_at),
+            COALESCE(MAX(datetime(t.created_at)), datetime(p.created_at)),
+            COALESCE(MAX(datetime(t.completed_at)), datetime(p.created_at))
+        ) as last_activity_at,
+        COALESCE(SUM(t.estimate), 0) as total_estimate,
+        COALESCE(SUM(CASE WHEN t.completed_at IS NULL THEN t.estimate END), 0) as remaining_estimate
+    FROM projects p
+    LEFT JOIN todos t ON p.id = t.project_id
+"#;
+
+/// How long a write waits for another process holding the database lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Oldest SQLite docket runs on: queries use UPSERT (3.24) and window
+/// functions (3.25). Inserts read `last_insert_rowid()` rather than use
+/// `RETURNING` (3.35), so builds linked against an older system SQLite work.
+pub const MIN_SQLITE_VERSION: (u32, u32, u32) = (3, 25, 0);
+
+/// Fail with a clear message when `version` (as `sqlite_version()` gives
+/// it, e.g. `3.45.1`) is older than `MIN_SQLITE_VERSION`
+pub fn check_sqlite_version(version: &str) -> Result<()> {
+    let mut parts = version.trim().split('.').map(|part| part.parse::<u32>().ok());
+    let parsed = match (parts.next().flatten(), parts.next().flatten(), parts.next()) {
+        (Some(major), Some(minor), patch) => (major, minor, patch.flatten().unwrap_or(0)),
+        _ => return Ok(()),
+    };
+    if parsed >= MIN_SQLITE_VERSION {
+        return Ok(());
+    }
+    let (major, minor, patch) = MIN_SQLITE_VERSION;
+    Err(DocketError::Db {
+        context: format!("SQLite {} is too old; docket needs {}.{}.{} or newer", version, major, minor, patch),
+        source: sqlx::Error::Configuration("unsupported SQLite version".into()),
+    })
+}
+
+/// Map a row produced by `PROJECT_STATS_SELECT`
+fn project_with_stats_from_row(row: &SqliteRow) -> Result<ProjectWithStats> {
+    Ok(ProjectWithStats {
+        project: Project::from_row(row)?,
+        total_todos: row.get("total_todos"),
+        completed_todos: row.get("completed_todos"),
+        last_activity_at: row.get("last_activity_at"),
+        total_estimate: row.get("total_estimate"),
+        remaining_estimate: row.get("remaining_estimate"),
+    })
+}
+
+/// How many connections the database keeps open
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolSizes {
+    /// Connections for writes and for reads other than lists
+    pub write: u32,
+    /// Read-only connections for list queries, so a burst of them (the web
+    /// server under load, say) doesn't queue behind writers for a
+    /// connection; 0 sends them to the write pool too
+    pub read: u32,
+}
+
+impl Default for PoolSizes {
+    fn default() -> Self {
+        Self { write: 5, read: 4 }
+    }
+}
+
+/// `list_projects` results as of one `sync_clock` version. Every write to
+/// projects or todos moves the clock, whichever process makes it, so an
+/// entry is good for as long as the version stays put.
+#[derive(Default)]
+struct ProjectStatsCache {
+    version: i64,
+    /// Active projects, then all projects
+    lists: [Option<Vec<ProjectWithStats>>; 2],
+}
+
+impl ProjectStatsCache {
+    fn get(&self, version: i64, include_archived: bool) -> Option<Vec<ProjectWithStats>> {
+        (self.version == version).then(|| self.lists[include_archived as usize].clone()).flatten()
+    }
+
+    fn store(&mut self, version: i64, include_archived: bool, projects: &[ProjectWithStats]) {
+        if self.version != version {
+            *self = Self { version, ..Self::default() };
+        }
+        self.lists[include_archived as usize] = Some(projects.to_vec());
+    }
+}
 
 /// Database connection pool wrapper
 #[derive(Clone)]
 pub struct Database {
     pool: SqlitePool,
+    /// Read-only connections for list queries; the write pool itself when
+    /// there are none
+    reader: SqlitePool,
+    /// Shared by clones, which all see the same file
+    project_stats: Arc<Mutex<ProjectStatsCache>>,
+    /// The directory of a test database, deleted with the last clone
+    #[cfg(any(test, feature = "test-util"))]
+    _test_dir: Option<Arc<TestDir>>,
+}
+
+/// A fresh directory in the temp dir for test databases, deleted with
+/// everything in it when dropped
+#[cfg(any(test, feature = "test-util"))]
+#[doc(hidden)]
+pub struct TestDir(std::path::PathBuf);
+
+#[cfg(any(test, feature = "test-util"))]
+impl TestDir {
+    pub fn new() -> Self {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "docket-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("Failed to create test directory");
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Where a database in this directory goes
+    pub fn database_path(&self) -> std::path::PathBuf {
+        self.0.join("docket.db")
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Default for TestDir {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
 }
 
 impl Database {
     /// Initialize database connection and run migrations
     pub async fn new(database_path: &Path) -> Result<Self> {
+        Self::with_pool_sizes(database_path, PoolSizes::default()).await
+    }
+
+    /// Open the database with `sizes` connections in its pools, running
+    /// migrations first
+    pub async fn with_pool_sizes(database_path: &Path, sizes: PoolSizes) -> Result<Self> {
         // Create connection options
         let options = SqliteConnectOptions::from_str(
             &format!("sqlite://{}", database_path.display())
         )?
-        .create_if_missing(true);
+        .create_if_missing(true)
+        // Another docket process (server, TUI, CLI) may be writing; wait for it
+        .busy_timeout(BUSY_TIMEOUT)
+        // Only ever used in queries, never in the schema, so other SQLite
+        // tools can still read the file
+        .collation(search::COLLATION, search::compare);
+
+        // Migrate on a dedicated connection before the pool exists, so no pooled
+        // connection can hold a pre-migration view of the schema
+        let mut conn = SqliteConnection::connect_with(&options)
+            .await
+            .context("Failed to connect to database")?;
+        let version: String = sqlx::query_scalar("SELECT sqlite_version()")
+            .fetch_one(&mut conn)
+            .await
+            .context("Failed to read SQLite version")?;
+        check_sqlite_version(&version)?;
+        Self::migrate(&mut conn).await?;
+        conn.close().await?;
 
         // Create connection pool
         let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect_with(options)
+            .max_connections(sizes.write.max(1))
+            .connect_with(options.clone())
             .await
             .context("Failed to connect to database")?;
+        let reader = match sizes.read {
+            0 => pool.clone(),
+            read => SqlitePoolOptions::new()
+                .max_connections(read)
+                .connect_with(options.read_only(true))
+                .await
+                .context("Failed to open read-only connections")?,
+        };
+
+        Ok(Self {
+            pool,
+            reader,
+            project_stats: Arc::default(),
+            #[cfg(any(test, feature = "test-util"))]
+            _test_dir: None,
+        })
+    }
+
+    /// A fresh database in its own temp directory, which is deleted when
+    /// the last clone is dropped
+    #[cfg(any(test, feature = "test-util"))]
+    #[doc(hidden)]
+    pub async fn open_temp() -> Result<Self> {
+        let dir = TestDir::new();
+        let db = Self::new(&dir.database_path()).await?;
+        Ok(Self { _test_dir: Some(Arc::new(dir)), ..db })
+    }
+
+    /// Apply any schema migrations newer than the database's `user_version`
+    async fn migrate(conn: &mut SqliteConnection) -> Result<()> {
+        let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+            .fetch_one(&mut *conn)
+            .await
+            .context("Failed to read schema version")?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            let mut tx = conn.begin().await?;
+            sqlx::query(migration)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("Failed to apply schema migration {}", index + 1))?;
+            if index + 1 == REFERENCES_VERSION {
+                Self::refresh_references_in(&mut tx, None).await?;
+            }
+            sqlx::query(&format!("PRAGMA user_version = {}", index + 1))
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
 
-        // Run schema initialization (idempotent - uses CREATE TABLE IF NOT EXISTS)
-        sqlx::query(include_str!("../../migrations/001_init.sql"))
-            .execute(&pool)
+    /// Begin a transaction that takes SQLite's write lock up front. Writes
+    /// that depend on what they read (positions, say) must use this: with a
+    /// plain `BEGIN` another process can change the rows between the read and
+    /// the write.
+    async fn begin_write(&self) -> Result<Transaction<'static, Sqlite>> {
+        self.pool
+            .begin_with("BEGIN IMMEDIATE")
             .await
-            .context("Failed to initialize database schema")?;
+            .context("Failed to lock database for writing")
+    }
 
-        Ok(Self { pool })
+    /// Begin a write transaction for a service operation that reads before
+    /// it writes, or writes more than once
+    pub async fn begin(&self) -> Result<WriteTransaction> {
+        Ok(WriteTransaction { tx: self.begin_write().await? })
     }
 
     // ===== Project Operations =====
 
     /// Create a new project
     pub async fn create_project(&self, name: &str, description: Option<&str>) -> Result<Project> {
-        let result = sqlx::query(
-            "INSERT INTO projects (name, description) VALUES (?, ?) RETURNING id, name, description, created_at, archived_at"
+        Self::create_project_in(&mut *self.pool.acquire().await?, name, description).await
+    }
+
+    async fn create_project_in(conn: &mut SqliteConnection, name: &str, description: Option<&str>) -> Result<Project> {
+        // Read back after the insert trigger has stamped the sync columns
+        let id = sqlx::query("INSERT INTO projects (name, description) VALUES (?, ?)")
+            .bind(name)
+            .bind(description)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to create project")?
+            .last_insert_rowid();
+        Self::fetch_project(conn, id).await
+    }
+
+    /// Copy a project under a new name, with its open todos (and with
+    /// `include_completed`, its completed ones, reopened) in their current
+    /// order, all in one transaction
+    pub async fn clone_project(&self, id: i64, name: &str, include_completed: bool) -> Result<i64> {
+        let mut tx = self.begin_write().await?;
+        let inserted = sqlx::query(
+            "INSERT INTO projects (name, description, workspace_id, parent_id)
+             SELECT ?, description, workspace_id, parent_id FROM projects WHERE id = ?"
         )
         .bind(name)
-        .bind(description)
-        .fetch_one(&self.pool)
+        .bind(id)
+        .execute(&mut *tx)
         .await
-        .context("Failed to create project")?;
-
-        Ok(Project {
-            id: result.get("id"),
-            name: result.get("name"),
-            description: result.get("description"),
-            created_at: result.get("created_at"),
-            archived_at: result.get("archived_at"),
-        })
+        .context("Failed to clone project")?;
+        if inserted.rows_affected() == 0 {
+            return Err(DocketError::not_found(format!("Project {} not found", id)));
+        }
+        let clone_id = inserted.last_insert_rowid();
+        sqlx::query(
+            r#"INSERT INTO todos (project_id, description, details, estimate, external_ref, position)
+               SELECT ?, description, details, estimate, external_ref,
+                      ROW_NUMBER() OVER (ORDER BY completed_at IS NOT NULL, position, completed_at, id)
+               FROM todos
+               WHERE project_id = ? AND (? OR completed_at IS NULL)"#
+        )
+        .bind(clone_id)
+        .bind(id)
+        .bind(include_completed)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to copy todos")?;
+        Self::refresh_references_in(&mut tx, Some(clone_id)).await?;
+        tx.commit().await?;
+        Ok(clone_id)
     }
 
     /// List all projects with statistics
     pub async fn list_projects(&self, include_archived: bool) -> Result<Vec<ProjectWithStats>> {
-        let query = if include_archived {
-            r#"
-            SELECT
-                p.id,
-                p.name,
-                p.description,
-                p.created_at,
-                p.archived_at,
-                COUNT(t.id) as total_todos,
-                COUNT(CASE WHEN t.completed_at IS NOT NULL THEN 1 END) as completed_todos
-            FROM projects p
-            LEFT JOIN todos t ON p.id = t.project_id
-            GROUP BY p.id
-            ORDER BY p.created_at DESC
-            "#
-        } else {
-            r#"
-            SELECT
-                p.id,
-                p.name,
-                p.description,
-                p.created_at,
-                p.archived_at,
-                COUNT(t.id) as total_todos,
-                COUNT(CASE WHEN t.completed_at IS NOT NULL THEN 1 END) as completed_todos
-            FROM projects p
-            LEFT JOIN todos t ON p.id = t.project_id
-            WHERE p.archived_at IS NULL
-            GROUP BY p.id
-            ORDER BY p.created_at DESC
-            "#
-        };
+        // The TUI reloads these on nearly every key press; reading the clock
+        // is far cheaper than the join. Read it first, so a write landing
+        // mid-query leaves an entry that is already out of date, not one
+        // that looks current.
+        let version = self.current_sync_version().await?;
+        if let Some(projects) = self.project_stats.lock().unwrap().get(version, include_archived) {
+            return Ok(projects);
+        }
 
-        let rows = sqlx::query(query)
-            .fetch_all(&self.pool)
+        let filter = if include_archived { "" } else { "WHERE p.archived_at IS NULL" };
+        let query = format!(
+            "{} {} GROUP BY p.id ORDER BY p.created_at DESC",
+            PROJECT_STATS_SELECT, filter
+        );
+
+        let rows = sqlx::query(&query)
+            .fetch_all(&self.reader)
             .await
             .context("Failed to list projects")?;
 
-        let projects = rows
-            .iter()
-            .map(|row| {
-                Ok(ProjectWithStats {
-                    project: Project {
-                        id: row.get("id"),
-                        name: row.get("name"),
-                        description: row.get("description"),
-                        created_at: row.get("created_at"),
-                        archived_at: row.get("archived_at"),
-                    },
-                    total_todos: row.get("total_todos"),
-                    completed_todos: row.get("completed_todos"),
-                })
-            })
-            .collect::<Result<Vec<_>>>()?;
-
+        let projects = rows.iter().map(project_with_stats_from_row).collect::<Result<Vec<_>>>()?;
+        self.project_stats.lock().unwrap().store(version, include_archived, &projects);
         Ok(projects)
     }
 
+    /// Count projects, archived ones included
+    pub async fn count_projects(&self) -> Result<i64> {
+        Self::count_projects_in(&mut *self.pool.acquire().await?).await
+    }
+
+    async fn count_projects_in(conn: &mut SqliteConnection) -> Result<i64> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM projects")
+            .fetch_one(&mut *conn)
+            .await
+            .context("Failed to count projects")
+    }
+
+    /// Get a single project with statistics
+    pub async fn get_project_with_stats(&self, id: i64) -> Result<ProjectWithStats> {
+        let query = format!("{} WHERE p.id = ? GROUP BY p.id", PROJECT_STATS_SELECT);
+
+        let row = sqlx::query(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to get project")?
+            .ok_or_else(|| DocketError::not_found(format!("Project {} not found", id)))?;
+
+        project_with_stats_from_row(&row)
+    }
+
+    /// Aggregate todo statistics for a project, with weekly completions for
+    /// the last `weeks` weeks
+    pub async fn get_project_stats(&self, id: i64, weeks: i64, offset: FixedOffset) -> Result<ProjectStats> {
+        let totals = sqlx::query(
+            r#"SELECT
+                 COUNT(CASE WHEN completed_at IS NULL THEN 1 END) AS open_todos,
+                 COUNT(completed_at) AS completed_todos,
+                 AVG((julianday(completed_at) - julianday(created_at)) * 24) AS average_completion_hours
+               FROM todos WHERE project_id = ?"#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to get project stats")?;
+
+        // Shift to local time first so completions late in the evening land in
+        // the right week. 'weekday 0' moves to the coming Sunday, so -6 days is
+        // that week's Monday.
+        let completions_per_week = sqlx::query_as::<_, WeeklyCompletions>(
+            r#"SELECT date(completed_at, ?2, 'weekday 0', '-6 days') AS week_start, COUNT(*) AS completed
+               FROM todos
+               WHERE project_id = ?1 AND completed_at IS NOT NULL
+                 AND date(completed_at, ?2) >= date('now', ?2, ?3)
+               GROUP BY week_start
+               ORDER BY week_start"#,
+        )
+        .bind(id)
+        .bind(format!("{:+} minutes", offset.local_minus_utc() / 60))
+        .bind(format!("-{} days", weeks * 7))
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get weekly completions")?;
+
+        let oldest_open_todos = sqlx::query_as::<_, Todo>(
+            "SELECT * FROM todos WHERE project_id = ? AND completed_at IS NULL ORDER BY datetime(created_at), id LIMIT ?",
+        )
+        .bind(id)
+        .bind(OLDEST_OPEN_TODOS)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get oldest open todos")?;
+
+        Ok(ProjectStats {
+            project_id: id,
+            open_todos: totals.get("open_todos"),
+            completed_todos: totals.get("completed_todos"),
+            completions_per_week,
+            oldest_open_todo: oldest_open_todos.first().cloned(),
+            oldest_open_todos,
+            average_completion_hours: totals.get("average_completion_hours"),
+        })
+    }
+
+    /// Count active and archived projects, and open todos in active projects
+    pub async fn get_totals(&self) -> Result<Totals> {
+        sqlx::query_as::<_, Totals>(
+            r#"SELECT
+                 (SELECT COUNT(*) FROM projects WHERE archived_at IS NULL) AS active_projects,
+                 (SELECT COUNT(*) FROM projects WHERE archived_at IS NOT NULL) AS archived_projects,
+                 (SELECT COUNT(*) FROM todos t JOIN projects p ON p.id = t.project_id
+                  WHERE p.archived_at IS NULL AND t.completed_at IS NULL) AS open_todos"#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count projects and todos")
+    }
+
     /// Get a project by ID
     pub async fn get_project(&self, id: i64) -> Result<Project> {
+        Self::fetch_project(&mut *self.pool.acquire().await?, id).await
+    }
+
+    async fn fetch_project(conn: &mut SqliteConnection, id: i64) -> Result<Project> {
         sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = ?")
             .bind(id)
-            .fetch_one(&self.pool)
+            .fetch_optional(&mut *conn)
+            .await
+            .context("Failed to get project")?
+            .ok_or_else(|| DocketError::not_found(format!("Project {} not found", id)))
+    }
+
+    /// Find a project by name, ignoring case
+    pub async fn find_project_by_name(&self, name: &str) -> Result<Option<Project>> {
+        Self::find_project_by_name_in(&mut *self.pool.acquire().await?, name).await
+    }
+
+    async fn find_project_by_name_in(conn: &mut SqliteConnection, name: &str) -> Result<Option<Project>> {
+        sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE name = ? COLLATE NOCASE")
+            .bind(name)
+            .fetch_optional(&mut *conn)
+            .await
+            .context("Failed to look up project by name")
+    }
+
+    /// Find a project whose name matches ignoring case and accents, the
+    /// closest spelling first
+    pub async fn find_project_by_folded_name(&self, name: &str) -> Result<Option<Project>> {
+        sqlx::query_as::<_, Project>(
+            "SELECT * FROM projects WHERE name = ? COLLATE FOLD ORDER BY name = ? DESC, id LIMIT 1",
+        )
+        .bind(name)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to look up project by name")
+    }
+
+    /// Look up the project published under a status page slug
+    pub async fn find_project_by_slug(&self, slug: &str) -> Result<Option<Project>> {
+        sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE slug = ?")
+            .bind(slug)
+            .fetch_optional(&self.pool)
             .await
-            .context("Failed to get project")
+            .context("Failed to look up project by slug")
     }
 
     /// Archive a project
     pub async fn archive_project(&self, id: i64) -> Result<()> {
+        Self::archive_project_in(&mut *self.pool.acquire().await?, id).await
+    }
+
+    async fn archive_project_in(conn: &mut SqliteConnection, id: i64) -> Result<()> {
         sqlx::query("UPDATE projects SET archived_at = ? WHERE id = ?")
             .bind(Utc::now())
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *conn)
             .await
             .context("Failed to archive project")?;
         Ok(())
     }
 
+    /// Record that a project has just been reviewed
+    pub async fn mark_project_reviewed(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE projects SET last_reviewed_at = ? WHERE id = ?")
+            .bind(Utc::now())
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to mark project reviewed")?;
+        Ok(())
+    }
+
+    /// Forget when a project was last reviewed, so it is due for review again
+    pub async fn clear_project_review(&self, id: i64) -> Result<()> {
+        Self::clear_project_review_in(&mut *self.pool.acquire().await?, id).await
+    }
+
+    async fn clear_project_review_in(conn: &mut SqliteConnection, id: i64) -> Result<()> {
+        sqlx::query("UPDATE projects SET last_reviewed_at = NULL WHERE id = ?")
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to queue project for review")?;
+        Ok(())
+    }
+
     /// Unarchive a project
     pub async fn unarchive_project(&self, id: i64) -> Result<()> {
+        Self::unarchive_project_in(&mut *self.pool.acquire().await?, id).await
+    }
+
+    async fn unarchive_project_in(conn: &mut SqliteConnection, id: i64) -> Result<()> {
         sqlx::query("UPDATE projects SET archived_at = NULL WHERE id = ?")
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *conn)
             .await
             .context("Failed to unarchive project")?;
         Ok(())
     }
 
-    /// Delete a project
+    /// Delete a project, moving its sub-projects up to the top level
     pub async fn delete_project(&self, id: i64) -> Result<()> {
+        let mut tx = self.begin_write().await?;
+        Self::delete_project_in(&mut tx, id).await?;
+        tx.commit().await.context("Failed to commit project delete")
+    }
+
+    async fn delete_project_in(conn: &mut SqliteConnection, id: i64) -> Result<()> {
+        sqlx::query("UPDATE projects SET parent_id = NULL WHERE parent_id = ?")
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to move sub-projects up")?;
         sqlx::query("DELETE FROM projects WHERE id = ?")
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *conn)
             .await
             .context("Failed to delete project")?;
         Ok(())
@@ -162,173 +617,955 @@ impl Database {
 
     /// Update a project's description
     pub async fn update_project_description(&self, id: i64, description: Option<&str>) -> Result<()> {
+        Self::update_project_description_in(&mut *self.pool.acquire().await?, id, description).await
+    }
+
+    async fn update_project_description_in(
+        conn: &mut SqliteConnection,
+        id: i64,
+        description: Option<&str>,
+    ) -> Result<()> {
         sqlx::query("UPDATE projects SET description = ? WHERE id = ?")
             .bind(description)
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *conn)
             .await
             .context("Failed to update project description")?;
         Ok(())
     }
 
+    /// Update a project's target date
+    pub async fn update_project_target_date(&self, id: i64, target_date: Option<NaiveDate>) -> Result<()> {
+        Self::update_project_target_date_in(&mut *self.pool.acquire().await?, id, target_date).await
+    }
+
+    async fn update_project_target_date_in(
+        conn: &mut SqliteConnection,
+        id: i64,
+        target_date: Option<NaiveDate>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE projects SET target_date = ? WHERE id = ?")
+            .bind(target_date)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to update project target date")?;
+        Ok(())
+    }
+
+    /// Set or clear a project's status page slug
+    pub async fn update_project_slug(&self, id: i64, slug: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE projects SET slug = ? WHERE id = ?")
+            .bind(slug)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to update project slug")?;
+        Ok(())
+    }
+
     /// Update a project's name
     pub async fn update_project_name(&self, id: i64, name: &str) -> Result<()> {
+        Self::update_project_name_in(&mut *self.pool.acquire().await?, id, name).await
+    }
+
+    async fn update_project_name_in(conn: &mut SqliteConnection, id: i64, name: &str) -> Result<()> {
         sqlx::query("UPDATE projects SET name = ? WHERE id = ?")
             .bind(name)
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *conn)
             .await
             .context("Failed to update project name")?;
         Ok(())
     }
 
-    // ===== Todo Operations =====
-
-    /// Get a todo by ID
-    pub async fn get_todo(&self, id: i64) -> Result<Todo> {
-        sqlx::query_as::<_, Todo>("SELECT * FROM todos WHERE id = ?")
+    /// Update a project's name, description and target date together
+    pub async fn update_project(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+        target_date: Option<NaiveDate>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE projects SET name = ?, description = ?, target_date = ? WHERE id = ?")
+            .bind(name)
+            .bind(description)
+            .bind(target_date)
             .bind(id)
-            .fetch_one(&self.pool)
+            .execute(&self.pool)
             .await
-            .context("Failed to get todo")
+            .context("Failed to update project")?;
+        Ok(())
     }
 
-    /// Create a new todo
-    pub async fn create_todo(&self, project_id: i64, description: &str) -> Result<Todo> {
-        // Get the max position for this project's active todos
-        let max_position: i64 = sqlx::query_scalar(
-            "SELECT COALESCE(MAX(position), 0) FROM todos WHERE project_id = ? AND completed_at IS NULL"
+    /// View preferences saved for a project, if any
+    pub async fn get_project_settings(&self, project_id: i64) -> Result<Option<ProjectSettings>> {
+        sqlx::query_as::<_, ProjectSettings>(
+            "SELECT show_completed, sort, filter FROM project_settings WHERE project_id = ?"
         )
         .bind(project_id)
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await
-        .context("Failed to get max position")?;
-
-        // New todo gets max_position + 1
-        let new_position = max_position + 1;
+        .context("Failed to get project settings")
+    }
 
-        let result = sqlx::query(
-            "INSERT INTO todos (project_id, description, position) VALUES (?, ?, ?) RETURNING id, project_id, description, details, created_at, completed_at, position"
+    /// Save a project's view preferences, replacing any saved before
+    pub async fn save_project_settings(&self, project_id: i64, settings: &ProjectSettings) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO project_settings (project_id, show_completed, sort, filter)
+               VALUES (?, ?, ?, ?)
+               ON CONFLICT(project_id) DO UPDATE SET
+                 show_completed = excluded.show_completed,
+                 sort = excluded.sort,
+                 filter = excluded.filter"#
         )
         .bind(project_id)
-        .bind(description)
-        .bind(new_position)
-        .fetch_one(&self.pool)
+        .bind(settings.show_completed)
+        .bind(&settings.sort)
+        .bind(&settings.filter)
+        .execute(&self.pool)
         .await
-        .context("Failed to create todo")?;
-
-        Ok(Todo {
-            id: result.get("id"),
-            project_id: result.get("project_id"),
-            description: result.get("description"),
-            details: result.get("details"),
-            created_at: result.get("created_at"),
-            completed_at: result.get("completed_at"),
-            position: result.get("position"),
-        })
+        .context("Failed to save project settings")?;
+        Ok(())
     }
 
-    /// List todos for a project
-    pub async fn list_todos(&self, project_id: i64, include_completed: bool) -> Result<Vec<Todo>> {
-        let query = if include_completed {
-            // Active todos first (ordered by position), then completed todos (ordered by completion date DESC)
-            r#"SELECT * FROM todos
-               WHERE project_id = ?
-               ORDER BY
-                 CASE WHEN completed_at IS NULL THEN 0 ELSE 1 END,
-                 CASE WHEN completed_at IS NULL THEN position ELSE 0 END,
-                 completed_at DESC"#
-        } else {
-            // Only active todos, ordered by position
-            "SELECT * FROM todos WHERE project_id = ? AND completed_at IS NULL ORDER BY position ASC"
-        };
-
-        sqlx::query_as::<_, Todo>(query)
-            .bind(project_id)
-            .fetch_all(&self.pool)
+    /// A random signing key, created the first time it is asked for
+    pub async fn secret(&self, name: &str) -> Result<Vec<u8>> {
+        sqlx::query("INSERT OR IGNORE INTO secrets (name, value) VALUES (?, randomblob(32))")
+            .bind(name)
+            .execute(&self.pool)
             .await
-            .context("Failed to list todos")
+            .context("Failed to create secret")?;
+        sqlx::query_scalar("SELECT value FROM secrets WHERE name = ?")
+            .bind(name)
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to read secret")
     }
 
-    /// Complete a todo
-    pub async fn complete_todo(&self, id: i64) -> Result<()> {
-        // Set completed_at and reset position to 0 (completed todos don't need position)
-        sqlx::query("UPDATE todos SET completed_at = ?, position = 0 WHERE id = ?")
-            .bind(Utc::now())
-            .bind(id)
+    /// Save a todo template
+    pub async fn create_template(&self, name: &str, description: &str, details: Option<&str>) -> Result<TodoTemplate> {
+        let id = sqlx::query("INSERT INTO todo_templates (name, description, details) VALUES (?, ?, ?)")
+            .bind(name)
+            .bind(description)
+            .bind(details)
             .execute(&self.pool)
             .await
-            .context("Failed to complete todo")?;
-        Ok(())
+            .context("Failed to create template")?
+            .last_insert_rowid();
+        sqlx::query_as::<_, TodoTemplate>("SELECT * FROM todo_templates WHERE id = ?")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to read template")
     }
 
-    /// Uncomplete a todo
-    pub async fn uncomplete_todo(&self, id: i64) -> Result<()> {
-        // First, get the project_id for this todo
-        let todo = self.get_todo(id).await?;
-
-        // Get the max position for active todos in this project
-        let max_position: i64 = sqlx::query_scalar(
-            "SELECT COALESCE(MAX(position), 0) FROM todos WHERE project_id = ? AND completed_at IS NULL"
-        )
-        .bind(todo.project_id)
-        .fetch_one(&self.pool)
-        .await
-        .context("Failed to get max position")?;
+    /// List todo templates by name
+    pub async fn list_templates(&self) -> Result<Vec<TodoTemplate>> {
+        sqlx::query_as::<_, TodoTemplate>("SELECT * FROM todo_templates ORDER BY name COLLATE FOLD")
+            .fetch_all(&self.reader)
+            .await
+            .context("Failed to list templates")
+    }
 
-        // Assign new position at the end
-        let new_position = max_position + 1;
+    /// Find a todo template by name, ignoring case
+    pub async fn find_template_by_name(&self, name: &str) -> Result<Option<TodoTemplate>> {
+        sqlx::query_as::<_, TodoTemplate>("SELECT * FROM todo_templates WHERE name = ? COLLATE NOCASE")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to look up template")
+    }
 
-        sqlx::query("UPDATE todos SET completed_at = NULL, position = ? WHERE id = ?")
-            .bind(new_position)
+    /// Delete a todo template
+    pub async fn delete_template(&self, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM todo_templates WHERE id = ?")
             .bind(id)
             .execute(&self.pool)
             .await
-            .context("Failed to uncomplete todo")?;
+            .context("Failed to delete template")?;
         Ok(())
     }
 
-    /// Delete a todo
-    pub async fn delete_todo(&self, id: i64) -> Result<()> {
-        sqlx::query("DELETE FROM todos WHERE id = ?")
-            .bind(id)
-            .execute(&self.pool)
+    // ===== Workspaces =====
+
+    /// List workspaces by name
+    pub async fn list_workspaces(&self) -> Result<Vec<Workspace>> {
+        sqlx::query_as::<_, Workspace>("SELECT * FROM workspaces ORDER BY name COLLATE FOLD")
+            .fetch_all(&self.reader)
             .await
-            .context("Failed to delete todo")?;
-        Ok(())
+            .context("Failed to list workspaces")
     }
 
-    /// Update a todo's details
-    pub async fn update_todo_details(&self, id: i64, details: Option<&str>) -> Result<()> {
-        sqlx::query("UPDATE todos SET details = ? WHERE id = ?")
-            .bind(details)
+    /// Get a workspace by ID
+    pub async fn get_workspace(&self, id: i64) -> Result<Workspace> {
+        sqlx::query_as::<_, Workspace>("SELECT * FROM workspaces WHERE id = ?")
             .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to get workspace")?
+            .ok_or_else(|| DocketError::not_found(format!("Workspace {} not found", id)))
+    }
+
+    /// Find a workspace by name, ignoring case
+    pub async fn find_workspace_by_name(&self, name: &str) -> Result<Option<Workspace>> {
+        sqlx::query_as::<_, Workspace>("SELECT * FROM workspaces WHERE name = ? COLLATE NOCASE")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to look up workspace")
+    }
+
+    /// Create a workspace
+    pub async fn create_workspace(&self, name: &str) -> Result<Workspace> {
+        let id = sqlx::query("INSERT INTO workspaces (name) VALUES (?)")
+            .bind(name)
             .execute(&self.pool)
             .await
-            .context("Failed to update todo details")?;
-        Ok(())
+            .context("Failed to create workspace")?
+            .last_insert_rowid();
+        self.get_workspace(id).await
     }
 
-    /// Update a todo's description
-    pub async fn update_todo(&self, id: i64, description: &str) -> Result<()> {
-        sqlx::query("UPDATE todos SET description = ? WHERE id = ?")
-            .bind(description)
+    /// Rename a workspace
+    pub async fn rename_workspace(&self, id: i64, name: &str) -> Result<()> {
+        sqlx::query("UPDATE workspaces SET name = ? WHERE id = ?")
+            .bind(name)
             .bind(id)
             .execute(&self.pool)
             .await
-            .context("Failed to update todo description")?;
+            .context("Failed to rename workspace")?;
         Ok(())
     }
 
+    /// Delete a workspace, leaving its projects in no workspace
+    pub async fn delete_workspace(&self, id: i64) -> Result<()> {
+        let mut tx = self.begin_write().await?;
+        sqlx::query("UPDATE projects SET workspace_id = NULL WHERE workspace_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to take projects out of the workspace")?;
+        sqlx::query("DELETE FROM workspaces WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete workspace")?;
+        tx.commit().await.context("Failed to commit workspace delete")
+    }
+
+    /// Nest a project under another, or move it to the top level
+    pub async fn update_project_parent(&self, id: i64, parent_id: Option<i64>) -> Result<()> {
+        sqlx::query("UPDATE projects SET parent_id = ? WHERE id = ?")
+            .bind(parent_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to update project parent")?;
+        Ok(())
+    }
+
+    /// Put a project in a workspace, or in none
+    pub async fn update_project_workspace(&self, id: i64, workspace_id: Option<i64>) -> Result<()> {
+        sqlx::query("UPDATE projects SET workspace_id = ? WHERE id = ?")
+            .bind(workspace_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to update project workspace")?;
+        Ok(())
+    }
+
+    // ===== History =====
+
+    /// List a todo's earlier versions, most recently replaced first
+    pub async fn list_todo_history(&self, todo_id: i64) -> Result<Vec<TodoVersion>> {
+        sqlx::query_as::<_, TodoVersion>("SELECT * FROM todo_history WHERE todo_id = ? ORDER BY id DESC")
+            .bind(todo_id)
+            .fetch_all(&self.reader)
+            .await
+            .context("Failed to list todo history")
+    }
+
+    /// Get one earlier version of a todo
+    pub async fn get_todo_version(&self, todo_id: i64, id: i64) -> Result<TodoVersion> {
+        sqlx::query_as::<_, TodoVersion>("SELECT * FROM todo_history WHERE id = ? AND todo_id = ?")
+            .bind(id)
+            .bind(todo_id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to look up todo version")?
+            .ok_or_else(|| DocketError::not_found(format!("Version {} of todo {} not found", id, todo_id)))
+    }
+
+    // ===== Links =====
+
+    /// Link `todo_id` to `target_id`
+    pub async fn create_todo_link(&self, todo_id: i64, target_id: i64, kind: LinkKind) -> Result<TodoLink> {
+        let id = sqlx::query("INSERT INTO todo_links (todo_id, target_id, kind) VALUES (?, ?, ?)")
+            .bind(todo_id)
+            .bind(target_id)
+            .bind(kind)
+            .execute(&self.pool)
+            .await
+            .context("Failed to create todo link")?
+            .last_insert_rowid();
+        self.get_todo_link(id).await
+    }
+
+    /// The link between two todos, whichever way it points
+    pub async fn find_todo_link(&self, a: i64, b: i64) -> Result<Option<TodoLink>> {
+        sqlx::query_as::<_, TodoLink>(
+            "SELECT * FROM todo_links WHERE (todo_id = ?1 AND target_id = ?2) OR (todo_id = ?2 AND target_id = ?1)",
+        )
+        .bind(a)
+        .bind(b)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to look up todo link")
+    }
+
+    pub async fn get_todo_link(&self, id: i64) -> Result<TodoLink> {
+        sqlx::query_as::<_, TodoLink>("SELECT * FROM todo_links WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to look up todo link")?
+            .ok_or_else(|| DocketError::not_found(format!("Link {} not found", id)))
+    }
+
+    /// Links from or to a todo, oldest first
+    pub async fn list_todo_links(&self, todo_id: i64) -> Result<Vec<TodoLink>> {
+        sqlx::query_as::<_, TodoLink>("SELECT * FROM todo_links WHERE todo_id = ?1 OR target_id = ?1 ORDER BY id")
+            .bind(todo_id)
+            .fetch_all(&self.reader)
+            .await
+            .context("Failed to list todo links")
+    }
+
+    pub async fn delete_todo_link(&self, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM todo_links WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete todo link")?;
+        Ok(())
+    }
+
+    // ===== References =====
+
+    /// Record the todos a todo's details mention, in place of those they
+    /// mentioned before
+    async fn set_references_in(conn: &mut SqliteConnection, todo_id: i64, details: Option<&str>) -> Result<()> {
+        sqlx::query("DELETE FROM todo_references WHERE todo_id = ?")
+            .bind(todo_id)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to clear todo references")?;
+        for target_id in details.map(references::todo_ids).unwrap_or_default() {
+            if target_id == todo_id {
+                continue;
+            }
+            sqlx::query("INSERT INTO todo_references (todo_id, target_id) VALUES (?, ?)")
+                .bind(todo_id)
+                .bind(target_id)
+                .execute(&mut *conn)
+                .await
+                .context("Failed to record todo reference")?;
+        }
+        Ok(())
+    }
+
+    /// Record the mentions in the details of every todo, or of one
+    /// project's todos
+    async fn refresh_references_in(conn: &mut SqliteConnection, project_id: Option<i64>) -> Result<()> {
+        let todos: Vec<(i64, String)> = sqlx::query_as(
+            "SELECT id, details FROM todos WHERE details IS NOT NULL AND (?1 IS NULL OR project_id = ?1)",
+        )
+        .bind(project_id)
+        .fetch_all(&mut *conn)
+        .await
+        .context("Failed to read todo details")?;
+        for (id, details) in todos {
+            Self::set_references_in(conn, id, Some(&details)).await?;
+        }
+        Ok(())
+    }
+
+    /// Todos whose details mention `todo_id`, oldest first
+    pub async fn list_referencing_todos(&self, todo_id: i64) -> Result<Vec<Todo>> {
+        sqlx::query_as::<_, Todo>(
+            "SELECT t.* FROM todos t JOIN todo_references r ON r.todo_id = t.id WHERE r.target_id = ? ORDER BY t.id",
+        )
+        .bind(todo_id)
+        .fetch_all(&self.reader)
+        .await
+        .context("Failed to list referencing todos")
+    }
+
+    // ===== Audit Log =====
+
+    /// Record that `actor` made a change
+    pub async fn insert_audit_entry(
+        &self,
+        actor: &str,
+        action: &str,
+        entity: &str,
+        entity_id: Option<i64>,
+        project_id: Option<i64>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO audit_log (at, actor, action, entity, entity_id, project_id)
+             VALUES (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), ?, ?, ?, ?, ?)",
+        )
+        .bind(actor)
+        .bind(action)
+        .bind(entity)
+        .bind(entity_id)
+        .bind(project_id)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record audit entry")?;
+        Ok(())
+    }
+
+    /// One page of audit entries matching `query`, newest first, and how
+    /// many match in all
+    pub async fn list_audit_entries(&self, query: &AuditQuery) -> Result<(Vec<AuditEntry>, i64)> {
+        const FILTER: &str = "WHERE (?1 IS NULL OR entity = ?1)
+              AND (?2 IS NULL OR entity_id = ?2)
+              AND (?3 IS NULL OR actor = ?3)
+              AND (?4 IS NULL OR at >= ?4)
+              AND (?5 IS NULL OR at < ?5)";
+        let since = query.since.map(audit::db_timestamp);
+        let until = query.until.map(audit::db_timestamp);
+
+        let entries = sqlx::query_as::<_, AuditEntry>(&format!(
+            "SELECT * FROM audit_log {} ORDER BY id DESC LIMIT ?6 OFFSET ?7",
+            FILTER
+        ))
+        .bind(&query.entity)
+        .bind(query.entity_id)
+        .bind(&query.actor)
+        .bind(&since)
+        .bind(&until)
+        .bind(query.limit())
+        .bind(query.offset())
+        .fetch_all(&self.reader)
+        .await
+        .context("Failed to list audit entries")?;
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM audit_log {}", FILTER))
+            .bind(&query.entity)
+            .bind(query.entity_id)
+            .bind(&query.actor)
+            .bind(&since)
+            .bind(&until)
+            .fetch_one(&self.reader)
+            .await
+            .context("Failed to count audit entries")?;
+        Ok((entries, total))
+    }
+
+    // ===== Batches =====
+
+    /// Run todo operations in one transaction: all of them apply, or none
+    /// do. Errors name the failing operation by its index.
+    pub async fn apply_batch(&self, ops: &[BatchOp], restore_positions: bool) -> Result<Vec<BatchResult>> {
+        let mut tx = self.begin_write().await?;
+        let mut results = Vec::with_capacity(ops.len());
+        for (index, op) in ops.iter().enumerate() {
+            let result = Self::apply_batch_op(&mut tx, op, restore_positions)
+                .await
+                .map_err(|err| err.context(format!("Operation {} failed", index)))?;
+            results.push(result);
+        }
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    async fn apply_batch_op(conn: &mut SqliteConnection, op: &BatchOp, restore_positions: bool) -> Result<BatchResult> {
+        Ok(match op {
+            BatchOp::Create { project_id, description, details } => {
+                let id = Self::insert_todo(conn, *project_id, description, details.as_deref()).await?;
+                BatchResult::Create { todo: Self::fetch_todo(conn, id).await? }
+            }
+            BatchOp::Toggle { id } => {
+                if Self::fetch_todo(conn, *id).await?.is_completed() {
+                    Self::uncomplete_todo_in(conn, *id, restore_positions).await?;
+                } else {
+                    Self::complete_todo_in(conn, *id).await?;
+                }
+                BatchResult::Toggle { todo: Self::fetch_todo(conn, *id).await? }
+            }
+            BatchOp::Delete { id } => {
+                let todo = Self::fetch_todo(conn, *id).await?;
+                Self::delete_todo_in(conn, *id).await?;
+                BatchResult::Delete { id: *id, project_id: todo.project_id }
+            }
+            BatchOp::Move { id, direction } => {
+                let step = match direction {
+                    Direction::Up => -1,
+                    Direction::Down => 1,
+                };
+                Self::reorder_todo_in(conn, *id, step).await?;
+                BatchResult::Move { todo: Self::fetch_todo(conn, *id).await? }
+            }
+        })
+    }
+
+    async fn fetch_todo(conn: &mut SqliteConnection, id: i64) -> Result<Todo> {
+        sqlx::query_as::<_, Todo>("SELECT * FROM todos WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+            .context("Failed to get todo")?
+            .ok_or_else(|| DocketError::not_found(format!("Todo {} not found", id)))
+    }
+
+    // ===== Idempotency Keys =====
+
+    /// Claim an idempotency key for a request, or report what became of the
+    /// request that claimed it first. Keys older than `max_age_hours` are
+    /// forgotten.
+    pub async fn claim_idempotency_key(
+        &self,
+        key: &str,
+        request_hash: &str,
+        max_age_hours: i64,
+    ) -> Result<IdempotentRequest> {
+        let mut tx = self.begin_write().await?;
+        sqlx::query("DELETE FROM idempotency_keys WHERE created_at < datetime('now', ?)")
+            .bind(format!("-{} hours", max_age_hours))
+            .execute(&mut *tx)
+            .await
+            .context("Failed to expire idempotency keys")?;
+
+        let claimed = sqlx::query("INSERT OR IGNORE INTO idempotency_keys (key, request_hash) VALUES (?, ?)")
+            .bind(key)
+            .bind(request_hash)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to claim idempotency key")?
+            .rows_affected()
+            == 1;
+        let state = if claimed {
+            IdempotentRequest::New
+        } else {
+            let row = sqlx::query("SELECT request_hash, status, content_type, body FROM idempotency_keys WHERE key = ?")
+                .bind(key)
+                .fetch_one(&mut *tx)
+                .await
+                .context("Failed to read idempotency key")?;
+            let status: Option<u16> = row.get("status");
+            match status {
+                _ if row.get::<String, _>("request_hash") != request_hash => IdempotentRequest::Mismatch,
+                None => IdempotentRequest::InProgress,
+                Some(status) => IdempotentRequest::Done {
+                    status,
+                    content_type: row.get("content_type"),
+                    body: row.get("body"),
+                },
+            }
+        };
+        tx.commit().await?;
+        Ok(state)
+    }
+
+    /// Store the response to a request that claimed an idempotency key
+    pub async fn finish_idempotency_key(
+        &self,
+        key: &str,
+        status: u16,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Result<()> {
+        sqlx::query("UPDATE idempotency_keys SET status = ?, content_type = ?, body = ? WHERE key = ?")
+            .bind(status)
+            .bind(content_type)
+            .bind(body)
+            .bind(key)
+            .execute(&self.pool)
+            .await
+            .context("Failed to store idempotent response")?;
+        Ok(())
+    }
+
+    /// Forget an idempotency key, so a failed request can be retried
+    pub async fn release_idempotency_key(&self, key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE key = ?")
+            .bind(key)
+            .execute(&self.pool)
+            .await
+            .context("Failed to release idempotency key")?;
+        Ok(())
+    }
+
+    // ===== Todo Operations =====
+
+    /// Get a todo by ID
+    pub async fn get_todo(&self, id: i64) -> Result<Todo> {
+        Self::fetch_todo(&mut *self.pool.acquire().await?, id).await
+    }
+
+    /// Create a new todo
+    pub async fn create_todo(&self, project_id: i64, description: &str, details: Option<&str>) -> Result<Todo> {
+        let mut conn = self.pool.acquire().await?;
+        let id = Self::insert_todo(&mut conn, project_id, description, details).await?;
+        drop(conn);
+        self.get_todo(id).await
+    }
+
+    async fn insert_todo(
+        conn: &mut SqliteConnection,
+        project_id: i64,
+        description: &str,
+        details: Option<&str>,
+    ) -> Result<i64> {
+        // New todo goes after the project's active todos; computing the position
+        // in the INSERT keeps it atomic when another process adds one too.
+        let id = sqlx::query(
+            r#"INSERT INTO todos (project_id, description, details, position)
+               VALUES (?1, ?2, ?3, (
+                 SELECT COALESCE(MAX(position), 0) + 1 FROM todos
+                 WHERE project_id = ?1 AND completed_at IS NULL
+               ))"#
+        )
+        .bind(project_id)
+        .bind(description)
+        .bind(details)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to create todo")?
+        .last_insert_rowid();
+        Self::set_references_in(conn, id, details).await?;
+        Ok(id)
+    }
+
+    /// List todos for a project
+    pub async fn list_todos(&self, project_id: i64, include_completed: bool) -> Result<Vec<Todo>> {
+        // Active todos first (ordered by position), then completed todos (ordered by completion date DESC)
+        self.list_sorted_todos(project_id, include_completed, &TodoOrder::default()).await
+    }
+
+    /// List a project's todos in `order`, which only ever puts fixed column
+    /// names into the ORDER BY
+    pub async fn list_sorted_todos(
+        &self,
+        project_id: i64,
+        include_completed: bool,
+        order: &TodoOrder,
+    ) -> Result<Vec<Todo>> {
+        let open_only = if include_completed { "" } else { " AND completed_at IS NULL" };
+        let query = format!("SELECT * FROM todos WHERE project_id = ?{} ORDER BY {}", open_only, order.order_by());
+
+        sqlx::query_as::<_, Todo>(&query)
+            .bind(project_id)
+            .fetch_all(&self.reader)
+            .await
+            .context("Failed to list todos")
+    }
+
+    /// Complete a todo
+    async fn complete_todo_in(conn: &mut SqliteConnection, id: i64) -> Result<()> {
+        // Set completed_at and reset position to 0 (completed todos don't need
+        // position), keeping the old one for `uncomplete_todo`
+        sqlx::query(
+            "UPDATE todos SET completed_at = ?, completed_position = position, position = 0 WHERE id = ?"
+        )
+            .bind(Utc::now())
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to complete todo")?;
+        Ok(())
+    }
+
+    /// Uncomplete a todo, putting it back where it was before completion
+    /// when `restore` is set, otherwise at the end of the list
+    async fn uncomplete_todo_in(tx: &mut SqliteConnection, id: i64, restore: bool) -> Result<()> {
+        let (project_id, completed_position): (i64, Option<i64>) =
+            sqlx::query_as("SELECT project_id, completed_position FROM todos WHERE id = ?")
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await
+                .context("Failed to get todo")?;
+
+        match completed_position.filter(|&position| restore && position > 0) {
+            Some(position) => {
+                // Make room: everything from the old slot down moves one place
+                sqlx::query(
+                    r#"UPDATE todos SET position = position + 1
+                       WHERE project_id = ? AND completed_at IS NULL AND position >= ?"#
+                )
+                .bind(project_id)
+                .bind(position)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to make room for todo")?;
+                sqlx::query(
+                    "UPDATE todos SET completed_at = NULL, completed_position = NULL, position = ? WHERE id = ?"
+                )
+                .bind(position)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to uncomplete todo")?;
+            }
+            None => {
+                sqlx::query(
+                    r#"UPDATE todos SET completed_at = NULL, completed_position = NULL, position = (
+                         SELECT COALESCE(MAX(t.position), 0) + 1 FROM todos t
+                         WHERE t.project_id = todos.project_id AND t.completed_at IS NULL
+                       )
+                       WHERE id = ?"#
+                )
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to uncomplete todo")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete a todo
+    pub async fn delete_todo(&self, id: i64) -> Result<()> {
+        Self::delete_todo_in(&mut *self.pool.acquire().await?, id).await
+    }
+
+    async fn delete_todo_in(conn: &mut SqliteConnection, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM todos WHERE id = ?")
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to delete todo")?;
+        Ok(())
+    }
+
+    /// List todos in active projects due between `from` and `to` (inclusive),
+    /// by due date and then by their place in the project
+    pub async fn list_todos_due_between(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Todo>> {
+        sqlx::query_as::<_, Todo>(
+            r#"SELECT t.* FROM todos t
+               JOIN projects p ON p.id = t.project_id
+               WHERE p.archived_at IS NULL AND t.due_date BETWEEN ? AND ?
+               ORDER BY t.due_date, t.completed_at IS NOT NULL, t.position, t.id"#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.reader)
+        .await
+        .context("Failed to list todos by due date")
+    }
+
+    /// List open todos in active projects, grouped by project in project
+    /// list order and in their place within each project
+    pub async fn list_open_todos(&self) -> Result<Vec<Todo>> {
+        sqlx::query_as::<_, Todo>(
+            r#"SELECT t.* FROM todos t
+               JOIN projects p ON p.id = t.project_id
+               WHERE p.archived_at IS NULL AND t.completed_at IS NULL
+               ORDER BY p.created_at DESC, p.id, t.position, t.id"#,
+        )
+        .fetch_all(&self.reader)
+        .await
+        .context("Failed to list open todos")
+    }
+
+    /// List open todos in active projects due on or before `date`, by due date
+    pub async fn list_open_todos_due_by(&self, date: NaiveDate) -> Result<Vec<Todo>> {
+        sqlx::query_as::<_, Todo>(
+            r#"SELECT t.* FROM todos t
+               JOIN projects p ON p.id = t.project_id
+               WHERE p.archived_at IS NULL AND t.completed_at IS NULL AND t.due_date <= ?
+               ORDER BY t.due_date, t.position, t.id"#,
+        )
+        .bind(date)
+        .fetch_all(&self.reader)
+        .await
+        .context("Failed to list due todos")
+    }
+
+    /// List todos completed before `cutoff` in any project, archived ones
+    /// included, with their project's name, oldest completed first
+    pub async fn list_todos_completed_before(&self, cutoff: DateTime<Utc>) -> Result<Vec<(Todo, String)>> {
+        let todos = sqlx::query_as::<_, Todo>(
+            r#"SELECT * FROM todos
+               WHERE completed_at IS NOT NULL AND completed_at < ?
+               ORDER BY completed_at, id"#,
+        )
+        .bind(cutoff)
+        .fetch_all(&self.reader)
+        .await
+        .context("Failed to list completed todos")?;
+
+        let names: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM projects")
+            .fetch_all(&self.reader)
+            .await
+            .context("Failed to list project names")?;
+        Ok(todos
+            .into_iter()
+            .map(|todo| {
+                let name = names.iter().find(|(id, _)| *id == todo.project_id).map(|(_, n)| n.clone());
+                (todo, name.unwrap_or_default())
+            })
+            .collect())
+    }
+
+    /// Remove completed todos in one transaction, first copying them into
+    /// `archived_todos` when `archive` is set. Deleting leaves sync tombstones
+    /// like any other delete.
+    pub async fn prune_todos(&self, ids: &[i64], archive: bool) -> Result<()> {
+        let mut tx = self.begin_write().await?;
+        for &id in ids {
+            if archive {
+                sqlx::query(
+                    r#"INSERT OR REPLACE INTO archived_todos
+                       (id, project_id, project_name, description, details, estimate, due_date, external_ref,
+                        created_at, completed_at, archived_at)
+                       SELECT t.id, t.project_id, COALESCE(p.name, ''), t.description, t.details, t.estimate,
+                              t.due_date, t.external_ref, t.created_at, t.completed_at, ?
+                       FROM todos t LEFT JOIN projects p ON p.id = t.project_id
+                       WHERE t.id = ? AND t.completed_at IS NOT NULL"#,
+                )
+                .bind(Utc::now())
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to archive todo")?;
+            }
+            sqlx::query("DELETE FROM todos WHERE id = ? AND completed_at IS NOT NULL")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to delete todo")?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Count the todos `prune_todos` has archived so far
+    pub async fn count_archived_todos(&self) -> Result<i64> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM archived_todos")
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count archived todos")?;
+        Ok(count)
+    }
+
+    /// Move an active todo behind all other active todos in its project
+    pub async fn move_todo_to_end(&self, id: i64) -> Result<()> {
+        let mut tx = self.begin_write().await?;
+        let todo = Self::fetch_todo(&mut tx, id).await?;
+        if todo.completed_at.is_some() {
+            return Err(DocketError::validation("position", "Cannot reorder completed todos"));
+        }
+
+        sqlx::query(
+            r#"UPDATE todos SET position = (
+                 SELECT COALESCE(MAX(position), 0) + 1 FROM todos
+                 WHERE project_id = ? AND completed_at IS NULL
+               )
+               WHERE id = ?"#
+        )
+        .bind(todo.project_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to move todo")?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Put an active todo at a 1-based place in its project's active list,
+    /// renumbering the list 1..n. Places past the end go last.
+    async fn place_todo_in(tx: &mut SqliteConnection, id: i64, place: i64) -> Result<()> {
+        let todo = Self::fetch_todo(tx, id).await?;
+        if todo.completed_at.is_some() {
+            return Err(DocketError::validation("position", "Cannot reorder completed todos"));
+        }
+
+        let mut ids: Vec<i64> = sqlx::query_scalar(
+            r#"SELECT id FROM todos
+               WHERE project_id = ? AND completed_at IS NULL AND id != ?
+               ORDER BY position ASC, id ASC"#
+        )
+        .bind(todo.project_id)
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await
+        .context("Failed to list todo positions")?;
+        let index = (place.max(1) as usize - 1).min(ids.len());
+        ids.insert(index, id);
+
+        for (index, todo_id) in ids.into_iter().enumerate() {
+            sqlx::query("UPDATE todos SET position = ? WHERE id = ? AND position != ?")
+                .bind(index as i64 + 1)
+                .bind(todo_id)
+                .bind(index as i64 + 1)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to renumber todo")?;
+        }
+        Ok(())
+    }
+
+    /// Renumber a project's active todos 1..n in their current order, ties
+    /// broken by ID. Returns how many todos got a new position.
+    pub async fn normalize_positions(&self, project_id: i64) -> Result<usize> {
+        let mut tx = self.begin_write().await?;
+        let moved = Self::normalize_positions_in(&mut tx, project_id).await?;
+        tx.commit().await?;
+        Ok(moved)
+    }
+
+    async fn normalize_positions_in(tx: &mut SqliteConnection, project_id: i64) -> Result<usize> {
+        let ids: Vec<(i64, i64)> = sqlx::query_as(
+            r#"SELECT id, position FROM todos
+               WHERE project_id = ? AND completed_at IS NULL
+               ORDER BY position ASC, id ASC"#
+        )
+        .bind(project_id)
+        .fetch_all(&mut *tx)
+        .await
+        .context("Failed to list todo positions")?;
+
+        let mut moved = 0;
+        for (index, (id, position)) in ids.into_iter().enumerate() {
+            let wanted = index as i64 + 1;
+            if position == wanted {
+                continue;
+            }
+            sqlx::query("UPDATE todos SET position = ? WHERE id = ?")
+                .bind(wanted)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to renumber todo")?;
+            moved += 1;
+        }
+        Ok(moved)
+    }
+
     /// Reorder a todo by swapping positions with an adjacent todo
     /// direction: -1 for up (decrease position), +1 for down (increase position)
     pub async fn reorder_todo(&self, todo_id: i64, direction: i8) -> Result<()> {
+        // Read and swap under the write lock, so another process reordering the
+        // same list can't swap with positions that are already out of date
+        let mut tx = self.begin_write().await?;
+        Self::reorder_todo_in(&mut tx, todo_id, direction).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn reorder_todo_in(tx: &mut SqliteConnection, todo_id: i64, direction: i8) -> Result<()> {
+
         // Get the current todo
-        let current_todo = self.get_todo(todo_id).await?;
+        let current_todo = Self::fetch_todo(tx, todo_id).await?;
 
         // Can only reorder active todos
         if current_todo.completed_at.is_some() {
-            anyhow::bail!("Cannot reorder completed todos");
+            return Err(DocketError::validation("position", "Cannot reorder completed todos"));
         }
 
         // Find the todo to swap with
@@ -353,7 +1590,7 @@ impl Database {
         let swap_result = sqlx::query(swap_query)
             .bind(current_todo.project_id)
             .bind(current_todo.position)
-            .fetch_optional(&self.pool)
+            .fetch_optional(&mut *tx)
             .await
             .context("Failed to find swap target")?;
 
@@ -365,9 +1602,6 @@ impl Database {
         let swap_id: i64 = swap_row.get("id");
         let swap_position: i64 = swap_row.get("position");
 
-        // Perform the swap in a transaction
-        let mut tx = self.pool.begin().await?;
-
         // Temporarily set one to a negative value to avoid unique constraint issues
         sqlx::query("UPDATE todos SET position = -1 WHERE id = ?")
             .bind(current_todo.id)
@@ -386,8 +1620,437 @@ impl Database {
             .execute(&mut *tx)
             .await?;
 
-        tx.commit().await?;
+        Ok(())
+    }
+
+    // ===== Sync Operations =====
+
+    /// Latest sync version handed out by the change counter
+    pub async fn current_sync_version(&self) -> Result<i64> {
+        sqlx::query_scalar("SELECT version FROM sync_clock")
+            .fetch_one(&self.reader)
+            .await
+            .context("Failed to read sync version")
+    }
+
+    /// Latest `updated_at` or deletion time across projects and todos
+    pub async fn last_modified_at(&self) -> Result<Option<DateTime<Utc>>> {
+        let latest: Option<NaiveDateTime> = sqlx::query_scalar(
+            r#"SELECT MAX(ts) FROM (
+                 SELECT MAX(datetime(updated_at)) AS ts FROM projects
+                 UNION ALL SELECT MAX(datetime(updated_at)) FROM todos
+                 UNION ALL SELECT MAX(datetime(deleted_at)) FROM tombstones
+               )"#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to read last modification time")?;
+        Ok(latest.map(|at| at.and_utc()))
+    }
+
+    /// Projects (archived included) changed after `version`
+    pub async fn projects_changed_since(&self, version: i64) -> Result<Vec<Project>> {
+        sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE version > ? ORDER BY version")
+            .bind(version)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to list changed projects")
+    }
+
+    /// Todos changed after `version`
+    pub async fn todos_changed_since(&self, version: i64) -> Result<Vec<Todo>> {
+        sqlx::query_as::<_, Todo>("SELECT * FROM todos WHERE version > ? ORDER BY version")
+            .bind(version)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to list changed todos")
+    }
+
+    /// Deletions recorded after `version`
+    pub async fn tombstones_since(&self, version: i64) -> Result<Vec<Tombstone>> {
+        sqlx::query_as::<_, Tombstone>("SELECT * FROM tombstones WHERE version > ? ORDER BY version")
+            .bind(version)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to list deletions")
+    }
+
+    // ===== Remote Copies =====
+
+    /// How far this copy of a remote server has pulled, if it ever has
+    pub async fn pull_state(&self) -> Result<Option<PullState>> {
+        sqlx::query_as::<_, PullState>("SELECT server_cursor, local_version FROM pull_state")
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to read pull state")
+    }
+
+    /// Write rows pulled from a remote server over this copy, keeping their
+    /// IDs, and record the pull. A `snapshot` (a pull from 0) also removes
+    /// rows the server doesn't have, such as ones created here offline.
+    pub async fn store_pulled(&self, changes: &ChangeSet, snapshot: bool) -> Result<()> {
+        let mut tx = self.begin_write().await?;
+
+        if snapshot {
+            let todo_ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM todos")
+                .fetch_all(&mut *tx)
+                .await
+                .context("Failed to list todos")?;
+            for id in todo_ids.into_iter().filter(|id| !changes.todos.iter().any(|t| t.id == *id)) {
+                Self::delete_todo_in(&mut tx, id).await?;
+            }
+            let project_ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM projects")
+                .fetch_all(&mut *tx)
+                .await
+                .context("Failed to list projects")?;
+            for id in project_ids.into_iter().filter(|id| !changes.projects.iter().any(|p| p.id == *id)) {
+                sqlx::query("DELETE FROM projects WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await
+                    .context("Failed to delete project")?;
+            }
+        }
+
+        for project in &changes.projects {
+            sqlx::query(
+                r#"INSERT INTO projects
+                     (id, name, description, created_at, archived_at, last_reviewed_at, target_date, slug,
+                      workspace_id, parent_id)
+                   VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                   ON CONFLICT (id) DO UPDATE SET
+                     name = excluded.name, description = excluded.description,
+                     created_at = excluded.created_at, archived_at = excluded.archived_at,
+                     last_reviewed_at = excluded.last_reviewed_at, target_date = excluded.target_date,
+                     slug = excluded.slug, workspace_id = excluded.workspace_id,
+                     parent_id = excluded.parent_id"#,
+            )
+            .bind(project.id)
+            .bind(&project.name)
+            .bind(&project.description)
+            .bind(project.created_at)
+            .bind(project.archived_at)
+            .bind(project.last_reviewed_at)
+            .bind(project.target_date)
+            .bind(&project.slug)
+            .bind(project.workspace_id)
+            .bind(project.parent_id)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to store project {}", project.id))?;
+        }
+        for todo in &changes.todos {
+            sqlx::query(
+                r#"INSERT INTO todos
+                     (id, project_id, description, details, created_at, completed_at, position,
+                      estimate, due_date, external_ref)
+                   VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                   ON CONFLICT (id) DO UPDATE SET
+                     project_id = excluded.project_id, description = excluded.description,
+                     details = excluded.details, created_at = excluded.created_at,
+                     completed_at = excluded.completed_at, position = excluded.position,
+                     estimate = excluded.estimate, due_date = excluded.due_date,
+                     external_ref = excluded.external_ref"#,
+            )
+            .bind(todo.id)
+            .bind(todo.project_id)
+            .bind(&todo.description)
+            .bind(&todo.details)
+            .bind(todo.created_at)
+            .bind(todo.completed_at)
+            .bind(todo.position)
+            .bind(todo.estimate)
+            .bind(todo.due_date)
+            .bind(&todo.external_ref)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to store todo {}", todo.id))?;
+            Self::set_references_in(&mut tx, todo.id, todo.details.as_deref()).await?;
+        }
+        for tombstone in &changes.deleted {
+            let table = if tombstone.entity == "project" { "projects" } else { "todos" };
+            sqlx::query(&format!("DELETE FROM {} WHERE id = ?", table))
+                .bind(tombstone.entity_id)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to delete a row deleted on the server")?;
+        }
+
+        sqlx::query(
+            r#"INSERT INTO pull_state (id, server_cursor, local_version)
+               VALUES (1, ?, (SELECT version FROM sync_clock))
+               ON CONFLICT (id) DO UPDATE SET
+                 server_cursor = excluded.server_cursor, local_version = excluded.local_version"#,
+        )
+        .bind(changes.cursor)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record pull state")?;
+        tx.commit().await.context("Failed to commit pulled changes")
+    }
+
+    /// Make projects and todos created from now on get IDs of at least
+    /// `floor`, out of the way of the IDs a remote server hands out
+    pub async fn reserve_ids_from(&self, floor: i64) -> Result<()> {
+        let mut tx = self.begin_write().await?;
+        for table in ["projects", "todos"] {
+            sqlx::query("DELETE FROM sqlite_sequence WHERE name = ? AND seq < ?")
+                .bind(table)
+                .bind(floor)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to reserve IDs")?;
+            sqlx::query(
+                r#"INSERT INTO sqlite_sequence (name, seq)
+                   SELECT ?, ? WHERE NOT EXISTS (SELECT 1 FROM sqlite_sequence WHERE name = ?)"#,
+            )
+            .bind(table)
+            .bind(floor)
+            .bind(table)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to reserve IDs")?;
+        }
+        tx.commit().await.context("Failed to reserve IDs")
+    }
+
+    /// Replace every template with `templates`, as pulled from a remote server
+    pub async fn replace_templates(&self, templates: &[TodoTemplate]) -> Result<()> {
+        let mut tx = self.begin_write().await?;
+        sqlx::query("DELETE FROM todo_templates")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear templates")?;
+        for template in templates {
+            sqlx::query("INSERT INTO todo_templates (id, name, description, details, created_at) VALUES (?, ?, ?, ?, ?)")
+                .bind(template.id)
+                .bind(&template.name)
+                .bind(&template.description)
+                .bind(&template.details)
+                .bind(template.created_at)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to store template")?;
+        }
+        tx.commit().await.context("Failed to commit templates")
+    }
+
+    /// Replace every workspace with `workspaces`, as pulled from a remote server
+    pub async fn replace_workspaces(&self, workspaces: &[Workspace]) -> Result<()> {
+        let mut tx = self.begin_write().await?;
+        sqlx::query("DELETE FROM workspaces")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear workspaces")?;
+        for workspace in workspaces {
+            sqlx::query("INSERT INTO workspaces (id, name, created_at) VALUES (?, ?, ?)")
+                .bind(workspace.id)
+                .bind(&workspace.name)
+                .bind(workspace.created_at)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to store workspace")?;
+        }
+        tx.commit().await.context("Failed to commit workspaces")
+    }
+}
+
+/// A write transaction: it holds the write lock from the start, and rolls
+/// back unless committed, so an error half way through (`?` returning early
+/// drops it) leaves the database as it was
+pub struct WriteTransaction {
+    tx: Transaction<'static, Sqlite>,
+}
+
+impl WriteTransaction {
+    /// Make the transaction's writes permanent
+    pub async fn commit(self) -> Result<()> {
+        self.tx.commit().await.context("Failed to commit changes")
+    }
+
+    /// Get a project by ID
+    pub async fn get_project(&mut self, id: i64) -> Result<Project> {
+        Database::fetch_project(&mut self.tx, id).await
+    }
+
+    /// Get a todo by ID
+    pub async fn get_todo(&mut self, id: i64) -> Result<Todo> {
+        Database::fetch_todo(&mut self.tx, id).await
+    }
+
+    /// Find a project by name, ignoring case
+    pub async fn find_project_by_name(&mut self, name: &str) -> Result<Option<Project>> {
+        Database::find_project_by_name_in(&mut self.tx, name).await
+    }
+
+    /// Count projects, archived ones included
+    pub async fn count_projects(&mut self) -> Result<i64> {
+        Database::count_projects_in(&mut self.tx).await
+    }
+
+    /// Create a new project
+    pub async fn create_project(&mut self, name: &str, description: Option<&str>) -> Result<Project> {
+        Database::create_project_in(&mut self.tx, name, description).await
+    }
+
+    /// Update a project's name
+    pub async fn update_project_name(&mut self, id: i64, name: &str) -> Result<()> {
+        Database::update_project_name_in(&mut self.tx, id, name).await
+    }
+
+    /// Update a project's description
+    pub async fn update_project_description(&mut self, id: i64, description: Option<&str>) -> Result<()> {
+        Database::update_project_description_in(&mut self.tx, id, description).await
+    }
+
+    /// Update a project's target date
+    pub async fn update_project_target_date(&mut self, id: i64, target_date: Option<NaiveDate>) -> Result<()> {
+        Database::update_project_target_date_in(&mut self.tx, id, target_date).await
+    }
+
+    /// Archive a project
+    pub async fn archive_project(&mut self, id: i64) -> Result<()> {
+        Database::archive_project_in(&mut self.tx, id).await
+    }
+
+    /// Unarchive a project
+    pub async fn unarchive_project(&mut self, id: i64) -> Result<()> {
+        Database::unarchive_project_in(&mut self.tx, id).await
+    }
+
+    /// Forget when a project was last reviewed, so it is due for review again
+    pub async fn clear_project_review(&mut self, id: i64) -> Result<()> {
+        Database::clear_project_review_in(&mut self.tx, id).await
+    }
+
+    /// Delete a project, moving its sub-projects up to the top level
+    pub async fn delete_project(&mut self, id: i64) -> Result<()> {
+        Database::delete_project_in(&mut self.tx, id).await
+    }
+
+    /// Create a new todo at the end of its project's active list
+    pub async fn create_todo(&mut self, project_id: i64, description: &str) -> Result<Todo> {
+        let id = Database::insert_todo(&mut self.tx, project_id, description, None).await?;
+        Database::fetch_todo(&mut self.tx, id).await
+    }
+
+    /// Delete a todo
+    pub async fn delete_todo(&mut self, id: i64) -> Result<()> {
+        Database::delete_todo_in(&mut self.tx, id).await
+    }
+
+    /// Renumber a project's active todos 1..n; returns how many moved
+    pub async fn normalize_positions(&mut self, project_id: i64) -> Result<usize> {
+        Database::normalize_positions_in(&mut self.tx, project_id).await
+    }
+
+    /// Count a project's todos, completed ones included
+    pub async fn count_todos(&mut self, project_id: i64) -> Result<i64> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM todos WHERE project_id = ?")
+            .bind(project_id)
+            .fetch_one(&mut *self.tx)
+            .await
+            .context("Failed to count todos")
+    }
 
+    /// Complete a todo
+    pub async fn complete_todo(&mut self, id: i64) -> Result<()> {
+        Database::complete_todo_in(&mut self.tx, id).await
+    }
+
+    /// Uncomplete a todo, putting it back where it was before completion
+    /// when `restore` is set, otherwise at the end of the list
+    pub async fn uncomplete_todo(&mut self, id: i64, restore: bool) -> Result<()> {
+        Database::uncomplete_todo_in(&mut self.tx, id, restore).await
+    }
+
+    /// Put an active todo at a 1-based place in its project's active list
+    pub async fn place_todo(&mut self, id: i64, place: i64) -> Result<()> {
+        Database::place_todo_in(&mut self.tx, id, place).await
+    }
+
+    /// Update a todo's details
+    pub async fn update_todo_details(&mut self, id: i64, details: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE todos SET details = ? WHERE id = ?")
+            .bind(details)
+            .bind(id)
+            .execute(&mut *self.tx)
+            .await
+            .context("Failed to update todo details")?;
+        Database::set_references_in(&mut self.tx, id, details).await
+    }
+
+    /// Update a todo's description
+    pub async fn update_todo(&mut self, id: i64, description: &str) -> Result<()> {
+        sqlx::query("UPDATE todos SET description = ? WHERE id = ?")
+            .bind(description)
+            .bind(id)
+            .execute(&mut *self.tx)
+            .await
+            .context("Failed to update todo description")?;
+        Ok(())
+    }
+
+    /// Set a todo's description and details in one update, so the history
+    /// keeps what they replaced as one version
+    pub async fn update_todo_text(&mut self, id: i64, description: &str, details: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE todos SET description = ?, details = ? WHERE id = ?")
+            .bind(description)
+            .bind(details)
+            .bind(id)
+            .execute(&mut *self.tx)
+            .await
+            .context("Failed to update todo")?;
+        Database::set_references_in(&mut self.tx, id, details).await
+    }
+
+    /// Update a todo's effort estimate
+    pub async fn update_todo_estimate(&mut self, id: i64, estimate: Option<i64>) -> Result<()> {
+        sqlx::query("UPDATE todos SET estimate = ? WHERE id = ?")
+            .bind(estimate)
+            .bind(id)
+            .execute(&mut *self.tx)
+            .await
+            .context("Failed to update todo estimate")?;
+        Ok(())
+    }
+
+    /// Update a todo's due date
+    pub async fn update_todo_due_date(&mut self, id: i64, due_date: Option<NaiveDate>) -> Result<()> {
+        sqlx::query("UPDATE todos SET due_date = ? WHERE id = ?")
+            .bind(due_date)
+            .bind(id)
+            .execute(&mut *self.tx)
+            .await
+            .context("Failed to update todo due date")?;
+        Ok(())
+    }
+
+    /// Update a todo's external reference
+    pub async fn update_todo_external_ref(&mut self, id: i64, external_ref: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE todos SET external_ref = ? WHERE id = ?")
+            .bind(external_ref)
+            .bind(id)
+            .execute(&mut *self.tx)
+            .await
+            .context("Failed to update todo external reference")?;
+        Ok(())
+    }
+
+    /// Move a todo to the end of another project's active list
+    pub async fn move_todo_to_project(&mut self, id: i64, project_id: i64) -> Result<()> {
+        sqlx::query(
+            r#"UPDATE todos SET project_id = ?1, position = (
+                 SELECT COALESCE(MAX(position), 0) + 1 FROM todos
+                 WHERE project_id = ?1 AND completed_at IS NULL
+               )
+               WHERE id = ?2"#
+        )
+        .bind(project_id)
+        .bind(id)
+        .execute(&mut *self.tx)
+        .await
+        .context("Failed to move todo")?;
         Ok(())
     }
 }
@@ -0,0 +1,70 @@
+// Sample data for `docket server --demo`, which puts it back every time
+// the demo resets.
+
+/// A todo in the sample data
+pub struct SampleTodo {
+    pub description: &'static str,
+    pub done: bool,
+    /// Due this many days from the day the demo resets; negative is overdue
+    pub due_in_days: Option<i64>,
+    pub estimate: Option<i64>,
+}
+
+/// A project in the sample data
+pub struct SampleProject {
+    pub name: &'static str,
+    pub description: Option<&'static str>,
+    /// Workspace the project is filed under, made if need be
+    pub workspace: Option<&'static str>,
+    pub todos: &'static [SampleTodo],
+}
+
+const fn todo(description: &'static str) -> SampleTodo {
+    SampleTodo { description, done: false, due_in_days: None, estimate: None }
+}
+
+const fn done(description: &'static str) -> SampleTodo {
+    SampleTodo { done: true, ..todo(description) }
+}
+
+const fn due(description: &'static str, days: i64) -> SampleTodo {
+    SampleTodo { due_in_days: Some(days), ..todo(description) }
+}
+
+const fn estimated(description: &'static str, estimate: i64) -> SampleTodo {
+    SampleTodo { estimate: Some(estimate), ..todo(description) }
+}
+
+pub const SAMPLE_PROJECTS: &[SampleProject] = &[
+    SampleProject {
+        name: "Kitchen Renovation",
+        description: Some("New counters and a fresh coat of paint before the holidays"),
+        workspace: Some("Home"),
+        todos: &[
+            done("Measure the counters"),
+            done("Pick a paint colour"),
+            due("Order the quartz slab", -2),
+            due("Book the plumber", 3),
+            estimated("Paint the cabinets #weekend", 8),
+            todo("Replace the cabinet handles"),
+        ],
+    },
+    SampleProject {
+        name: "Launch the Newsletter",
+        description: Some("Monthly notes for friends and readers"),
+        workspace: Some("Writing"),
+        todos: &[
+            done("Choose a mailing service"),
+            estimated("Write the first issue", 3),
+            due("Design the header image", 1),
+            due("Send issue #1", 7),
+        ],
+    },
+    SampleProject {
+        name: "Groceries",
+        description: None,
+        workspace: None,
+        todos: &[todo("Oat milk"), todo("Coffee beans"), done("Bread"), todo("Lemons")],
+    },
+];
+
@@ -0,0 +1,119 @@
+use chrono::NaiveDate;
+use std::fmt::Write;
+
+use super::html::escape_html;
+use super::models::{ProjectWithStats, Todo};
+
+/// Agenda of open todos that are overdue or due soon, and projects at risk
+/// of missing their target date
+#[derive(Debug, Clone)]
+pub struct Digest {
+    pub today: NaiveDate,
+    /// Todos due up to this many days ahead count as due soon
+    pub days: i64,
+    /// Open todos due before today, oldest first
+    pub overdue: Vec<DigestTodo>,
+    /// Open todos due from today on, soonest first
+    pub due_soon: Vec<DigestTodo>,
+    pub at_risk: Vec<ProjectWithStats>,
+}
+
+/// A todo with the name of its project
+#[derive(Debug, Clone)]
+pub struct DigestTodo {
+    pub project: String,
+    pub todo: Todo,
+}
+
+impl Digest {
+    /// Check if there is nothing to report
+    pub fn is_empty(&self) -> bool {
+        self.overdue.is_empty() && self.due_soon.is_empty() && self.at_risk.is_empty()
+    }
+
+    /// Subject line for mailing the digest
+    pub fn subject(&self) -> String {
+        format!(
+            "Docket agenda for {}: {} overdue, {} due soon",
+            self.today,
+            self.overdue.len(),
+            self.due_soon.len()
+        )
+    }
+
+    /// Render the digest as Markdown, for the terminal and plain-text mail
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Agenda for {}", self.today);
+        if self.is_empty() {
+            let _ = writeln!(out, "\nNothing overdue or due in the next {} days.", self.days);
+            return out;
+        }
+
+        for (title, todos) in self.sections() {
+            let _ = writeln!(out, "\n## {}\n", title);
+            for item in todos {
+                let due = item.todo.due_date.map(|d| d.to_string()).unwrap_or_default();
+                let _ = writeln!(out, "- [ ] {} ({}, due {})", item.todo.description, item.project, due);
+            }
+        }
+        if !self.at_risk.is_empty() {
+            let _ = writeln!(out, "\n## Projects at risk\n");
+            for project in &self.at_risk {
+                let _ = writeln!(out, "- {} ({})", project.project.name, self.target_text(project));
+            }
+        }
+        out
+    }
+
+    /// Render the digest as a standalone HTML page, for mail clients
+    pub fn to_html(&self) -> String {
+        let mut out = String::from("<!DOCTYPE html>\n<html>\n<body style=\"font-family: sans-serif\">\n");
+        let _ = writeln!(out, "<h1>Agenda for {}</h1>", self.today);
+        if self.is_empty() {
+            let _ = writeln!(out, "<p>Nothing overdue or due in the next {} days.</p>", self.days);
+        }
+
+        for (title, todos) in self.sections() {
+            let _ = writeln!(out, "<h2>{}</h2>\n<ul>", title);
+            for item in todos {
+                let due = item.todo.due_date.map(|d| d.to_string()).unwrap_or_default();
+                let _ = writeln!(
+                    out,
+                    "<li>{} <small>({}, due {})</small></li>",
+                    escape_html(&item.todo.description),
+                    escape_html(&item.project),
+                    due
+                );
+            }
+            out.push_str("</ul>\n");
+        }
+        if !self.at_risk.is_empty() {
+            out.push_str("<h2>Projects at risk</h2>\n<ul>\n");
+            for project in &self.at_risk {
+                let _ = writeln!(
+                    out,
+                    "<li>{} <small>({})</small></li>",
+                    escape_html(&project.project.name),
+                    self.target_text(project)
+                );
+            }
+            out.push_str("</ul>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Non-empty todo sections with their headings
+    fn sections(&self) -> impl Iterator<Item = (&'static str, &Vec<DigestTodo>)> {
+        [("Overdue", &self.overdue), ("Due soon", &self.due_soon)]
+            .into_iter()
+            .filter(|(_, todos)| !todos.is_empty())
+    }
+
+    /// `3 open, target 2026-10-20` for an at-risk project
+    fn target_text(&self, project: &ProjectWithStats) -> String {
+        let target = project.project.target_date.map(|d| d.to_string()).unwrap_or_default();
+        format!("{} open, target {}", project.active_todos(), target)
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+
+/// Result of a core operation
+pub type Result<T, E = DocketError> = std::result::Result<T, E>;
+
+/// What went wrong in the core, in terms callers can act on: the TUI shows
+/// mistakes as warnings, the API maps each kind to a status code
+#[derive(Debug)]
+pub enum DocketError {
+    /// No project, todo or template with the given ID or name
+    NotFound(String),
+    /// Input that breaks a rule; `field` names the input, e.g. `estimate`
+    Validation { field: &'static str, msg: String },
+    /// The change clashes with what is already there, e.g. a project name
+    /// in use; `suggestion` is a free alternative when there is one
+    Conflict { msg: String, suggestion: Option<String> },
+    /// A share link past its expiry
+    Expired(String),
+    /// A cap from the configured `limits` would be exceeded
+    LimitReached(String),
+    /// The database failed; `context` says what docket was doing
+    #[cfg(feature = "db")]
+    Db { context: String, source: sqlx::Error },
+    /// A remote docket server turned the request down, or answered with
+    /// something docket can't read
+    Remote(String),
+    /// A remote docket server could not be reached at all
+    Offline(String),
+}
+
+impl DocketError {
+    pub fn not_found(msg: impl Into<String>) -> Self {
+        DocketError::NotFound(msg.into())
+    }
+
+    pub fn validation(field: &'static str, msg: impl Into<String>) -> Self {
+        DocketError::Validation { field, msg: msg.into() }
+    }
+
+    pub fn conflict(msg: impl Into<String>) -> Self {
+        DocketError::Conflict { msg: msg.into(), suggestion: None }
+    }
+
+    pub fn remote(msg: impl Into<String>) -> Self {
+        DocketError::Remote(msg.into())
+    }
+
+    /// The same error with `context` in front of its message, e.g. which
+    /// change of a sync push failed
+    pub fn context(self, context: impl fmt::Display) -> Self {
+        let prefix = |msg: String| format!("{}: {}", context, msg);
+        match self {
+            DocketError::NotFound(msg) => DocketError::NotFound(prefix(msg)),
+            DocketError::Validation { field, msg } => DocketError::Validation { field, msg: prefix(msg) },
+            DocketError::Conflict { msg, suggestion } => DocketError::Conflict { msg: prefix(msg), suggestion },
+            DocketError::Expired(msg) => DocketError::Expired(prefix(msg)),
+            DocketError::LimitReached(msg) => DocketError::LimitReached(prefix(msg)),
+            #[cfg(feature = "db")]
+            DocketError::Db { context: inner, source } => DocketError::Db { context: prefix(inner), source },
+            DocketError::Remote(msg) => DocketError::Remote(prefix(msg)),
+            DocketError::Offline(msg) => DocketError::Offline(prefix(msg)),
+        }
+    }
+}
+
+impl fmt::Display for DocketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocketError::NotFound(msg)
+            | DocketError::Validation { msg, .. }
+            | DocketError::Conflict { msg, .. }
+            | DocketError::Expired(msg)
+            | DocketError::LimitReached(msg)
+            | DocketError::Remote(msg)
+            | DocketError::Offline(msg) => write!(f, "{}", msg),
+            #[cfg(feature = "db")]
+            DocketError::Db { context, .. } => write!(f, "{}", context),
+        }
+    }
+}
+
+impl std::error::Error for DocketError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "db")]
+            DocketError::Db { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "db")]
+impl From<sqlx::Error> for DocketError {
+    fn from(source: sqlx::Error) -> Self {
+        DocketError::Db { context: "Database error".to_string(), source }
+    }
+}
+
+/// `.context("Failed to ...")` for database calls, as with anyhow
+#[cfg(feature = "db")]
+pub trait DbContext<T> {
+    fn context(self, context: &str) -> Result<T>;
+    fn with_context(self, context: impl FnOnce() -> String) -> Result<T>;
+}
+
+#[cfg(feature = "db")]
+impl<T> DbContext<T> for std::result::Result<T, sqlx::Error> {
+    fn context(self, context: &str) -> Result<T> {
+        self.map_err(|source| DocketError::Db { context: context.to_string(), source })
+    }
+
+    fn with_context(self, context: impl FnOnce() -> String) -> Result<T> {
+        self.map_err(|source| DocketError::Db { context: context(), source })
+    }
+}
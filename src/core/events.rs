@@ -0,0 +1,240 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// How many recent changes are kept for clients resuming with a last-seen ID
+const REPLAY_CAPACITY: usize = 256;
+
+/// What happened to a project or todo
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ChangeKind {
+    #[serde(rename = "project.created")]
+    ProjectCreated,
+    #[serde(rename = "project.updated")]
+    ProjectUpdated,
+    #[serde(rename = "project.archived")]
+    ProjectArchived,
+    #[serde(rename = "project.unarchived")]
+    ProjectUnarchived,
+    #[serde(rename = "project.deleted")]
+    ProjectDeleted,
+    #[serde(rename = "todo.created")]
+    TodoCreated,
+    #[serde(rename = "todo.updated")]
+    TodoUpdated,
+    #[serde(rename = "todo.toggled")]
+    TodoToggled,
+    #[serde(rename = "todo.moved")]
+    TodoMoved,
+    #[serde(rename = "todo.deleted")]
+    TodoDeleted,
+    #[serde(rename = "workspace.created")]
+    WorkspaceCreated,
+    #[serde(rename = "workspace.updated")]
+    WorkspaceUpdated,
+    #[serde(rename = "workspace.deleted")]
+    WorkspaceDeleted,
+}
+
+impl ChangeKind {
+    pub const ALL: [ChangeKind; 13] = [
+        ChangeKind::ProjectCreated,
+        ChangeKind::ProjectUpdated,
+        ChangeKind::ProjectArchived,
+        ChangeKind::ProjectUnarchived,
+        ChangeKind::ProjectDeleted,
+        ChangeKind::TodoCreated,
+        ChangeKind::TodoUpdated,
+        ChangeKind::TodoToggled,
+        ChangeKind::TodoMoved,
+        ChangeKind::TodoDeleted,
+        ChangeKind::WorkspaceCreated,
+        ChangeKind::WorkspaceUpdated,
+        ChangeKind::WorkspaceDeleted,
+    ];
+
+    /// Name used on the wire, e.g. `todo.created`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::ProjectCreated => "project.created",
+            ChangeKind::ProjectUpdated => "project.updated",
+            ChangeKind::ProjectArchived => "project.archived",
+            ChangeKind::ProjectUnarchived => "project.unarchived",
+            ChangeKind::ProjectDeleted => "project.deleted",
+            ChangeKind::TodoCreated => "todo.created",
+            ChangeKind::TodoUpdated => "todo.updated",
+            ChangeKind::TodoToggled => "todo.toggled",
+            ChangeKind::TodoMoved => "todo.moved",
+            ChangeKind::TodoDeleted => "todo.deleted",
+            ChangeKind::WorkspaceCreated => "workspace.created",
+            ChangeKind::WorkspaceUpdated => "workspace.updated",
+            ChangeKind::WorkspaceDeleted => "workspace.deleted",
+        }
+    }
+}
+
+impl std::str::FromStr for ChangeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ChangeKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("Unknown event '{}' (expected e.g. todo.created or project.archived)", s))
+    }
+}
+
+/// A change notification published by the service layer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    /// The database's sync version once the change was saved, so IDs keep
+    /// increasing across restarts
+    pub id: u64,
+    pub kind: ChangeKind,
+    pub project_id: Option<i64>,
+    pub todo_id: Option<i64>,
+    /// Only set for `workspace.*` changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<i64>,
+    pub at: DateTime<Utc>,
+}
+
+/// What a subscriber resuming from a last-seen ID gets before live changes
+#[derive(Debug, Clone, PartialEq)]
+pub enum Replay {
+    /// The changes it missed, possibly none
+    Changes(Vec<Change>),
+    /// It missed more than the buffer holds, or the ID isn't from this
+    /// database: it has to refetch
+    Resync,
+}
+
+/// In-process broadcast of changes, with a short replay buffer so
+/// subscribers can resume after a dropped connection
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Change>,
+    state: Arc<Mutex<BusState>>,
+}
+
+struct BusState {
+    last_id: u64,
+    recent: VecDeque<Change>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(REPLAY_CAPACITY);
+        Self {
+            sender,
+            state: Arc::new(Mutex::new(BusState {
+                last_id: 0,
+                recent: VecDeque::with_capacity(REPLAY_CAPACITY),
+            })),
+        }
+    }
+
+    /// Record a change saved at sync version `version` and notify current
+    /// subscribers. Changes published together get the next IDs up.
+    pub fn publish(
+        &self,
+        version: u64,
+        kind: ChangeKind,
+        project_id: Option<i64>,
+        todo_id: Option<i64>,
+        workspace_id: Option<i64>,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        let change = Change {
+            id: version.max(state.last_id + 1),
+            kind,
+            project_id,
+            todo_id,
+            workspace_id,
+            at: Utc::now(),
+        };
+        state.last_id = change.id;
+        if state.recent.len() == REPLAY_CAPACITY {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(change.clone());
+        // Sending under the lock keeps replay and live delivery gap-free
+        let _ = self.sender.send(change);
+    }
+
+    /// ID of the most recent change, 0 before the first
+    pub fn last_id(&self) -> u64 {
+        self.state.lock().unwrap().last_id
+    }
+
+    /// Subscribe to future changes
+    pub fn subscribe(&self) -> broadcast::Receiver<Change> {
+        self.sender.subscribe()
+    }
+
+    /// Subscribe to future changes after `last_id`, with what was missed
+    /// since. `current` is the database's sync version, read before this
+    /// call: an ID behind it needs buffered changes reaching back to it, as
+    /// after a restart the buffer starts empty, and an ID ahead of it comes
+    /// from another database.
+    pub fn resume(&self, last_id: u64, current: u64) -> (Replay, broadcast::Receiver<Change>) {
+        let state = self.state.lock().unwrap();
+        let newest = current.max(state.last_id);
+        let replay = match state.recent.front() {
+            _ if last_id > newest => Replay::Resync,
+            _ if last_id == newest => Replay::Changes(Vec::new()),
+            Some(oldest) if last_id + 1 >= oldest.id => {
+                Replay::Changes(state.recent.iter().filter(|c| c.id > last_id).cloned().collect())
+            }
+            _ => Replay::Resync,
+        };
+        (replay, self.sender.subscribe())
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_replays_changes_after_last_id() {
+        let bus = EventBus::new();
+        bus.publish(11, ChangeKind::ProjectCreated, Some(1), None, None);
+        bus.publish(12, ChangeKind::TodoCreated, Some(1), Some(1), None);
+        // Versions read after a concurrent write still give increasing IDs
+        bus.publish(12, ChangeKind::TodoToggled, Some(1), Some(1), None);
+
+        let ids = |replay: Replay| match replay {
+            Replay::Changes(changes) => changes.iter().map(|c| c.id).collect::<Vec<_>>(),
+            Replay::Resync => panic!("Unexpected resync"),
+        };
+        let (replay, mut rx) = bus.resume(11, 13);
+        assert_eq!(ids(replay), vec![12, 13]);
+        assert_eq!(ids(bus.resume(10, 13).0), vec![11, 12, 13]);
+        assert!(ids(bus.resume(13, 13).0).is_empty());
+
+        bus.publish(14, ChangeKind::TodoDeleted, Some(1), Some(1), None);
+        assert_eq!(rx.try_recv().unwrap().id, 14);
+    }
+
+    #[test]
+    fn test_resume_resyncs_when_changes_are_gone() {
+        let bus = EventBus::new();
+        // Before the oldest buffered change, or from before a restart
+        bus.publish(40, ChangeKind::ProjectCreated, Some(1), None, None);
+        assert_eq!(bus.resume(30, 40).0, Replay::Resync);
+        assert_eq!(EventBus::new().resume(30, 40).0, Replay::Resync);
+        // Nothing missed since a restart
+        assert_eq!(EventBus::new().resume(40, 40).0, Replay::Changes(Vec::new()));
+        // Ahead of this database
+        assert_eq!(bus.resume(90, 40).0, Replay::Resync);
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use super::models::{Project, Todo};
+
+/// Supported export formats
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Markdown,
+    Csv,
+}
+
+impl ExportFormat {
+    /// File extension used when writing an export to disk
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Csv => "csv",
+        }
+    }
+
+    /// MIME type used when serving an export over HTTP
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    /// Pick a format from an HTTP `Accept` header, in the client's order.
+    /// Returns `None` when the client prefers JSON (or anything else).
+    pub fn from_accept(accept: &str) -> Option<Self> {
+        for media in accept.split(',') {
+            let mut params = media.split(';');
+            let media_type = params.next().unwrap_or_default().trim();
+            if params.any(|p| p.trim().replace(' ', "") == "q=0") {
+                continue;
+            }
+            match media_type {
+                "text/markdown" => return Some(ExportFormat::Markdown),
+                "text/csv" => return Some(ExportFormat::Csv),
+                "application/json" | "*/*" => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Names accepted when parsing a format
+    pub fn names() -> &'static [&'static str] {
+        &["md", "markdown", "csv"]
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(format!("Unknown export format '{}' (expected: md, csv)", s)),
+        }
+    }
+}
+
+/// Render a project and its todos in the requested format
+pub fn export_project(format: ExportFormat, project: &Project, todos: &[Todo]) -> String {
+    match format {
+        ExportFormat::Markdown => to_markdown(project, todos),
+        ExportFormat::Csv => to_csv(todos),
+    }
+}
+
+/// Render a project as a Markdown checklist
+fn to_markdown(project: &Project, todos: &[Todo]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}", project.name);
+    if let Some(description) = &project.description {
+        let _ = writeln!(out, "\n{}", description);
+    }
+    out.push('\n');
+
+    for todo in todos {
+        let check = if todo.is_completed() { "x" } else { " " };
+        let _ = writeln!(out, "- [{}] {}", check, todo.description);
+        if let Some(details) = &todo.details {
+            for line in details.lines() {
+                let _ = writeln!(out, "  {}", line);
+            }
+        }
+    }
+
+    out
+}
+
+/// Render todos as CSV with a header row
+fn to_csv(todos: &[Todo]) -> String {
+    let mut out = String::from("id,description,details,estimate,created_at,completed_at\n");
+    for todo in todos {
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            todo.id,
+            csv_field(&todo.description),
+            csv_field(todo.details.as_deref().unwrap_or("")),
+            todo.estimate.map(|e| e.to_string()).unwrap_or_default(),
+            todo.created_at.to_rfc3339(),
+            todo.completed_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+        );
+    }
+    out
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_accept_header() {
+        assert_eq!(ExportFormat::from_accept("text/csv"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::from_accept("text/markdown, */*;q=0.1"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::from_accept("application/json, text/csv"), None);
+        assert_eq!(ExportFormat::from_accept("text/csv;q=0, text/markdown"), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::from_accept("text/html"), None);
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
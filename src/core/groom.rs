@@ -0,0 +1,176 @@
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+use super::models::{ProjectWithStats, Todo};
+use super::time::DisplayZone;
+
+/// A grooming rule from the config file's `groom_rules`, e.g.
+/// `{"open_days": 60, "tag": "stale", "review": true}`: every todo open for
+/// more than `open_days` days gets `#tag` and puts its project back in
+/// today's review
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroomRule {
+    pub open_days: i64,
+    /// Hashtag added to matching todos, with or without the `#`
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Queue the projects of matching todos for review, even if reviewed today
+    #[serde(default)]
+    pub review: bool,
+}
+
+impl GroomRule {
+    /// The tag to add, lowercased and without `#`
+    fn tag(&self) -> Option<String> {
+        self.tag.as_deref().map(|t| t.trim().trim_start_matches('#').to_lowercase())
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.open_days < 0 {
+            bail!("Groom rule open_days must not be negative");
+        }
+        if let Some(tag) = self.tag()
+            && (tag.is_empty() || !tag.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_'))
+        {
+            bail!("Groom rule tag '{}' is not a valid hashtag", self.tag.as_deref().unwrap_or(""));
+        }
+        if self.tag.is_none() && !self.review {
+            bail!("Groom rule for {} days does nothing; give it a tag or set review", self.open_days);
+        }
+        Ok(())
+    }
+}
+
+/// A change the grooming rules make
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroomChange {
+    /// Append `#tag` to a todo's description
+    Tag {
+        todo_id: i64,
+        description: String,
+        project: String,
+        age_days: i64,
+        tag: String,
+    },
+    /// Put a project back in today's review
+    Review { project_id: i64, project: String },
+}
+
+/// Work out what `rules` would change for the open todos of `projects`.
+/// Tags a todo already has and projects still due for review are skipped,
+/// so grooming twice changes nothing the second time.
+pub fn plan(
+    rules: &[GroomRule],
+    projects: &[ProjectWithStats],
+    todos: &[Todo],
+    zone: DisplayZone,
+) -> Result<Vec<GroomChange>> {
+    for rule in rules {
+        rule.validate()?;
+    }
+
+    let mut changes = Vec::new();
+    let mut queued = HashSet::new();
+    for todo in todos.iter().filter(|t| !t.is_completed()) {
+        let Some(project) = projects.iter().find(|p| p.project.id == todo.project_id) else {
+            continue;
+        };
+        let mut tags = todo.tags();
+        for rule in rules.iter().filter(|r| todo.age_days() > r.open_days) {
+            if let Some(tag) = rule.tag()
+                && !tags.contains(&tag)
+            {
+                tags.push(tag.clone());
+                changes.push(GroomChange::Tag {
+                    todo_id: todo.id,
+                    description: todo.description.clone(),
+                    project: project.project.name.clone(),
+                    age_days: todo.age_days(),
+                    tag,
+                });
+            }
+            if rule.review && project.project.reviewed_today(zone) && queued.insert(project.project.id) {
+                changes.push(GroomChange::Review {
+                    project_id: project.project.id,
+                    project: project.project.name.clone(),
+                });
+            }
+        }
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Project;
+    use chrono::{Duration, Utc};
+
+    fn todo(id: i64, description: &str, days_open: i64) -> Todo {
+        Todo {
+            id,
+            project_id: 1,
+            description: description.to_string(),
+            details: None,
+            created_at: Utc::now() - Duration::days(days_open),
+            completed_at: None,
+            position: id,
+            estimate: None,
+            due_date: None,
+            external_ref: None,
+            updated_at: Utc::now(),
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn test_plan_skips_tagged_todos() {
+        let project = ProjectWithStats {
+            project: Project {
+                id: 1,
+                name: "Home".to_string(),
+                description: None,
+                created_at: Utc::now() - Duration::days(200),
+                archived_at: None,
+                last_reviewed_at: Some(Utc::now()),
+                target_date: None,
+                slug: None,
+                workspace_id: None,
+                parent_id: None,
+                updated_at: Utc::now(),
+                version: 1,
+            },
+            total_todos: 3,
+            completed_todos: 0,
+            last_activity_at: Utc::now(),
+            total_estimate: 0,
+            remaining_estimate: 0,
+        };
+        let todos = vec![
+            todo(1, "Clean gutters", 70),
+            todo(2, "Fix fence #stale", 80),
+            todo(3, "Buy milk", 2),
+        ];
+        let rules = vec![GroomRule { open_days: 60, tag: Some("#Stale".to_string()), review: true }];
+
+        let changes = plan(&rules, std::slice::from_ref(&project), &todos, DisplayZone::Local).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                GroomChange::Tag {
+                    todo_id: 1,
+                    description: "Clean gutters".to_string(),
+                    project: "Home".to_string(),
+                    age_days: 70,
+                    tag: "stale".to_string(),
+                },
+                GroomChange::Review { project_id: 1, project: "Home".to_string() },
+            ]
+        );
+
+        let idle = vec![GroomRule { open_days: 60, tag: None, review: false }];
+        assert!(plan(&idle, &[project], &todos, DisplayZone::Local).is_err());
+    }
+}
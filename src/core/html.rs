@@ -0,0 +1,18 @@
+/// Escape text for use in HTML element content and attributes
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("Fix <b> & <i> tags"), "Fix &lt;b&gt; &amp; &lt;i&gt; tags");
+        assert_eq!(escape_html(r#"<a title="x">"#), "&lt;a title=&quot;x&quot;&gt;");
+    }
+}
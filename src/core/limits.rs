@@ -0,0 +1,104 @@
+use serde::Deserialize;
+
+use super::error::{DocketError, Result};
+
+/// Caps that keep a shared or public instance (a demo, say) from being
+/// filled with garbage. Each is off unless set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Projects in all, archived ones included
+    pub max_projects: Option<i64>,
+    /// Todos in one project, completed ones included
+    pub max_todos_per_project: Option<i64>,
+    /// Workspaces in all
+    pub max_workspaces: Option<i64>,
+    /// Longest todo or template details, in bytes
+    pub max_details_bytes: Option<usize>,
+    /// Largest request body the web server accepts, in bytes
+    pub max_body_bytes: Option<usize>,
+}
+
+impl Limits {
+    /// Check there is room for one more project next to `existing`
+    pub fn check_projects(&self, existing: i64) -> Result<()> {
+        match self.max_projects {
+            Some(max) if existing >= max => Err(DocketError::LimitReached(format!(
+                "This docket holds at most {} projects; delete one to make room",
+                max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check there is room for one more workspace next to `existing`
+    pub fn check_workspaces(&self, existing: i64) -> Result<()> {
+        match self.max_workspaces {
+            Some(max) if existing >= max => Err(DocketError::LimitReached(format!(
+                "This docket holds at most {} workspaces; delete one to make room",
+                max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check `project` has room for `adding` todos next to its `existing` ones
+    pub fn check_todos(&self, project: &str, existing: i64, adding: i64) -> Result<()> {
+        match self.max_todos_per_project {
+            Some(max) if existing + adding > max => Err(DocketError::LimitReached(format!(
+                "Project '{}' holds at most {} todos; delete some to make room",
+                project, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// These limits, with caps suited to a public demo wherever none is set
+    pub fn or_demo_defaults(self) -> Self {
+        Self {
+            max_projects: self.max_projects.or(Some(20)),
+            max_todos_per_project: self.max_todos_per_project.or(Some(100)),
+            max_workspaces: self.max_workspaces.or(Some(10)),
+            max_details_bytes: self.max_details_bytes.or(Some(4 * 1024)),
+            max_body_bytes: self.max_body_bytes.or(Some(16 * 1024)),
+        }
+    }
+
+    /// Check `details` fit in `max_details_bytes`
+    pub fn check_details(&self, details: Option<&str>) -> Result<()> {
+        match (self.max_details_bytes, details) {
+            (Some(max), Some(details)) if details.len() > max => Err(DocketError::validation(
+                "details",
+                format!("Details are too long (max {} bytes)", max),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_are_off_unless_set() {
+        let none = Limits::default();
+        assert!(none.check_projects(1_000_000).is_ok());
+        assert!(none.check_todos("Home", 1_000_000, 1).is_ok());
+        assert!(none.check_workspaces(1_000_000).is_ok());
+        assert!(none.check_details(Some(&"x".repeat(1_000_000))).is_ok());
+
+        let limits: Limits =
+            serde_json::from_str(r#"{"max_projects": 2, "max_todos_per_project": 3, "max_details_bytes": 4}"#).unwrap();
+        assert!(limits.check_projects(1).is_ok());
+        assert!(matches!(limits.check_projects(2), Err(DocketError::LimitReached(_))));
+        assert!(limits.check_todos("Home", 1, 2).is_ok());
+        assert!(limits.check_todos("Home", 2, 2).is_err());
+        assert!(limits.check_details(Some("four")).is_ok());
+        assert!(limits.check_details(Some("fiver")).is_err());
+        let limits: Limits = serde_json::from_str(r#"{"max_workspaces": 1}"#).unwrap();
+        assert!(limits.check_workspaces(0).is_ok());
+        assert!(matches!(limits.check_workspaces(1), Err(DocketError::LimitReached(_))));
+        assert!(serde_json::from_str::<Limits>(r#"{"max_users": 1}"#).is_err());
+    }
+}
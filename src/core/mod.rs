@@ -1,3 +1,27 @@
 pub mod models;
+pub mod audit;
+pub mod batch;
+#[cfg(feature = "db")]
 pub mod db;
+pub mod demo;
+pub mod digest;
+pub mod error;
+pub mod events;
+pub mod export;
+pub mod groom;
+pub mod html;
+pub mod limits;
+pub mod prune;
+pub mod query;
+pub mod references;
+pub mod search;
+#[cfg(feature = "db")]
 pub mod service;
+pub mod sort;
+#[cfg(feature = "db")]
+pub mod service_api;
+pub mod share;
+pub mod sync;
+pub mod time;
+pub mod tree;
+pub mod vault;
@@ -1,14 +1,41 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::search;
+use super::time::DisplayZone;
+
+/// Projects with open todos this close to their target date count as at risk
+pub const AT_RISK_DAYS: i64 = 7;
+
+/// Open todos older than this many days are flagged as aging
+pub const AGING_DAYS: i64 = 30;
+
+/// Open todos older than this many days are flagged as stale
+pub const STALE_DAYS: i64 = 90;
+
 /// Represents a project containing todos
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
 pub struct Project {
     pub id: i64,
     pub name: String,
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
     pub archived_at: Option<DateTime<Utc>>,
+    pub last_reviewed_at: Option<DateTime<Utc>>,
+    /// Goal date for finishing the project
+    pub target_date: Option<NaiveDate>,
+    /// Public status page at `/p/<slug>`; without one the project stays private
+    pub slug: Option<String>,
+    /// The workspace (Work, Home, ...) the project is filed under
+    #[serde(default)]
+    pub workspace_id: Option<i64>,
+    /// The project this one is a sub-project of
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+    pub updated_at: DateTime<Utc>,
+    /// Sync version of the last change (see `GET /api/changes`)
+    pub version: i64,
 }
 
 impl Project {
@@ -16,10 +43,87 @@ impl Project {
     pub fn is_archived(&self) -> bool {
         self.archived_at.is_some()
     }
+
+    /// Days until the target date (negative once it has passed), counted
+    /// from today in `zone`
+    pub fn days_remaining(&self, zone: DisplayZone) -> Option<i64> {
+        self.target_date
+            .map(|target| (target - zone.today()).num_days())
+    }
+
+    /// Check if the project has already been through review mode today in `zone`
+    pub fn reviewed_today(&self, zone: DisplayZone) -> bool {
+        self.last_reviewed_at
+            .map(|at| zone.convert(at).date_naive() == zone.today())
+            .unwrap_or(false)
+    }
+
+    /// Upper-case key that starts its todos' short IDs: the initials of a
+    /// name of several words (`KR` for "Kitchen Renovation"), else the
+    /// start of the name (`HOME`)
+    pub fn key(&self) -> String {
+        let folded = search::fold(&self.name);
+        let words: Vec<&str> = folded.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()).collect();
+        let key: String = match words.as_slice() {
+            [] => "TODO".to_string(),
+            [word] => word.chars().take(4).collect(),
+            words => words.iter().take(4).filter_map(|w| w.chars().next()).collect(),
+        };
+        key.to_ascii_uppercase()
+    }
+
+    /// Short ID of one of the project's todos, e.g. `HOME-42`. The number
+    /// is the todo's ID, so it stays unique across projects and the key
+    /// only helps people tell todos apart.
+    pub fn todo_short_id(&self, todo_id: i64) -> String {
+        format!("{}-{}", self.key(), todo_id)
+    }
+}
+
+/// A todo as people name it: a short ID (`HOME-42`), `#42` or `42`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodoRef {
+    /// The project key of a short ID, as typed
+    pub key: Option<String>,
+    pub id: i64,
+}
+
+impl TodoRef {
+    /// Whether the key, if any, is that of `project`, the one the todo is
+    /// in; a mistyped key must not name another project's todo
+    pub fn fits(&self, project: &Project) -> bool {
+        self.key.as_deref().is_none_or(|key| key.eq_ignore_ascii_case(&project.key()))
+    }
+}
+
+impl std::fmt::Display for TodoRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "{}-{}", key.to_ascii_uppercase(), self.id),
+            None => write!(f, "#{}", self.id),
+        }
+    }
+}
+
+/// Read a short ID (`HOME-42`), `#42` or `42`
+pub fn parse_todo_ref(text: &str) -> Option<TodoRef> {
+    let text = text.trim();
+    let (key, number) = match text.rsplit_once('-') {
+        Some((key, number)) if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            (Some(key.to_string()), number)
+        }
+        Some(_) => return None,
+        None => (None, text.strip_prefix('#').unwrap_or(text)),
+    };
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(TodoRef { key, id: number.parse().ok()? })
 }
 
 /// Represents a todo item within a project
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
 pub struct Todo {
     pub id: i64,
     pub project_id: i64,
@@ -28,6 +132,15 @@ pub struct Todo {
     pub created_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub position: i64,
+    /// Effort estimate in whatever unit the user prefers (points, minutes, ...)
+    pub estimate: Option<i64>,
+    /// Day the todo should be done by
+    pub due_date: Option<NaiveDate>,
+    /// Where the work is tracked elsewhere: a JIRA key like `OPS-42`, or a URL
+    pub external_ref: Option<String>,
+    pub updated_at: DateTime<Utc>,
+    /// Sync version of the last change (see `GET /api/changes`)
+    pub version: i64,
 }
 
 impl Todo {
@@ -36,18 +149,179 @@ impl Todo {
         self.completed_at.is_some()
     }
 
-    /// Get a formatted completion date or "Pending"
-    pub fn completion_status(&self) -> String {
-        match &self.completed_at {
-            Some(date) => date.format("%Y-%m-%d %H:%M").to_string(),
+    /// Get a formatted completion date in `zone`, or "Pending"
+    pub fn completion_status(&self, zone: DisplayZone) -> String {
+        match self.completed_at {
+            Some(date) => zone.convert(date).format("%Y-%m-%d %H:%M").to_string(),
             None => "Pending".to_string(),
         }
     }
 
+    /// Check if the todo is still open after its due date in `zone`
+    pub fn is_overdue(&self, zone: DisplayZone) -> bool {
+        !self.is_completed() && self.due_date.is_some_and(|due| due < zone.today())
+    }
+
+    /// Days since the todo was created
+    pub fn age_days(&self) -> i64 {
+        (Utc::now() - self.created_at).num_days()
+    }
+
+    /// How long the todo has sat open; completed todos are always fresh
+    pub fn age(&self) -> TodoAge {
+        match self.age_days() {
+            _ if self.is_completed() => TodoAge::Fresh,
+            days if days > STALE_DAYS => TodoAge::Stale,
+            days if days > AGING_DAYS => TodoAge::Aging,
+            _ => TodoAge::Fresh,
+        }
+    }
+
     /// Check if this todo can be reordered (only active todos can be reordered)
     pub fn can_reorder(&self) -> bool {
         self.completed_at.is_none()
     }
+
+    /// Hashtags (`#home`, `#q3-launch`) in the description and details,
+    /// lowercased and without duplicates
+    pub fn tags(&self) -> Vec<String> {
+        let text = [self.description.as_str(), self.details.as_deref().unwrap_or("")];
+        let mut tags: Vec<String> = Vec::new();
+        for word in text.iter().flat_map(|t| t.split_whitespace()) {
+            let word = word.trim_start_matches(|c: char| !c.is_alphanumeric() && c != '#');
+            let Some(tag) = word.strip_prefix('#') else { continue };
+            let tag: String = tag
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                .collect::<String>()
+                .to_lowercase();
+            // `#123` mentions todo 123
+            let mention = tag.bytes().all(|b| b.is_ascii_digit());
+            if !tag.is_empty() && !mention && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// Checklist lines in the details: `- [ ] measure`, `- [x] buy tiles`
+    pub fn checklist(&self) -> Vec<ChecklistItem<'_>> {
+        checklist(self.details.as_deref().unwrap_or(""))
+    }
+
+    /// Checked and total checklist items, if the details have a checklist
+    pub fn checklist_progress(&self) -> Option<(usize, usize)> {
+        let items = self.checklist();
+        let checked = items.iter().filter(|item| item.checked).count();
+        (!items.is_empty()).then_some((checked, items.len()))
+    }
+}
+
+/// A checklist line in a todo's details
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChecklistItem<'a> {
+    /// Line within the details, counting from 0
+    pub line: usize,
+    pub checked: bool,
+    pub text: &'a str,
+}
+
+/// The `- [ ] text` and `- [x] text` lines of `details`, also with `*` for
+/// the bullet
+fn checklist(details: &str) -> Vec<ChecklistItem<'_>> {
+    details
+        .lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let text = text.trim_start();
+            let rest = text.strip_prefix("- ").or_else(|| text.strip_prefix("* "))?;
+            let (checked, text) = match rest.strip_prefix("[ ]") {
+                Some(text) => (false, text),
+                None => (true, rest.strip_prefix("[x]").or_else(|| rest.strip_prefix("[X]"))?),
+            };
+            Some(ChecklistItem { line, checked, text: text.trim() })
+        })
+        .collect()
+}
+
+/// `details` with checklist item `index` (counting from 0) checked or
+/// unchecked, or `None` if there are not that many items
+pub fn toggle_checklist_item(details: &str, index: usize) -> Option<String> {
+    let item = *checklist(details).get(index)?;
+    let lines = details.lines().enumerate().map(|(i, line)| {
+        if i != item.line {
+            return line.to_string();
+        }
+        let mark = line.find('[').map_or(0, |at| at + 1);
+        let box_text = if item.checked { " " } else { "x" };
+        format!("{}{}{}", &line[..mark], box_text, &line[mark + 1..])
+    });
+    Some(lines.collect::<Vec<_>>().join("\n"))
+}
+
+/// Age bracket of an open todo, to surface ones that keep being passed over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TodoAge {
+    Fresh,
+    /// Open for more than `AGING_DAYS`
+    Aging,
+    /// Open for more than `STALE_DAYS`
+    Stale,
+}
+
+/// A partial update of a todo, in JSON merge patch style: fields left out
+/// stay as they are, `null` clears a field
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TodoPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub details: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<Option<NaiveDate>>,
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub external_ref: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed: Option<bool>,
+    /// Move the todo to the end of this project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<i64>,
+    /// Priority: 1-based place among the project's open todos
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i64>,
+}
+
+/// Tell a field set to `null` (`Some(None)`) apart from a missing one (`None`)
+fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// A todo with the context needed to render its detail page in one request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoContext {
+    pub todo: Todo,
+    pub project: Project,
+    /// e.g. `HOME-42`
+    #[serde(default)]
+    pub short_id: String,
+    pub tags: Vec<String>,
+    pub position: PositionContext,
+}
+
+/// Where a todo sits among its project's open todos
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionContext {
+    /// 1-based place in the open list; `None` once completed
+    pub index: Option<usize>,
+    pub open_todos: usize,
+    pub previous_id: Option<i64>,
+    pub next_id: Option<i64>,
 }
 
 /// Project with todo statistics
@@ -57,6 +331,12 @@ pub struct ProjectWithStats {
     pub project: Project,
     pub total_todos: i64,
     pub completed_todos: i64,
+    /// Most recent of project creation, todo creation and todo completion
+    pub last_activity_at: DateTime<Utc>,
+    /// Sum of all todo estimates in the project
+    pub total_estimate: i64,
+    /// Sum of estimates on todos that are still open
+    pub remaining_estimate: i64,
 }
 
 impl ProjectWithStats {
@@ -64,4 +344,324 @@ impl ProjectWithStats {
     pub fn active_todos(&self) -> i64 {
         self.total_todos - self.completed_todos
     }
+
+    /// Check if any todo in the project carries an estimate
+    pub fn has_estimates(&self) -> bool {
+        self.total_estimate > 0
+    }
+
+    /// Fraction of estimated effort that has been completed (0.0 - 1.0)
+    pub fn burndown_ratio(&self) -> f64 {
+        if self.total_estimate == 0 {
+            return 0.0;
+        }
+        (self.total_estimate - self.remaining_estimate) as f64 / self.total_estimate as f64
+    }
+
+    /// Check if the project is at risk of missing its target date: it still
+    /// has open todos and the target has passed or is within a week
+    pub fn is_at_risk(&self, zone: DisplayZone) -> bool {
+        match self.project.days_remaining(zone) {
+            Some(days) => self.active_todos() > 0 && days <= AT_RISK_DAYS,
+            None => false,
+        }
+    }
+
+    /// Check if the project looks finished: it has todos, all of them are
+    /// completed, and nothing has happened for at least `stale_after_days`
+    pub fn is_archive_candidate(&self, stale_after_days: i64) -> bool {
+        !self.project.is_archived()
+            && self.total_todos > 0
+            && self.active_todos() == 0
+            && Utc::now() - self.last_activity_at >= Duration::days(stale_after_days)
+    }
+}
+
+/// Open todos listed in a project's statistics, oldest first
+pub const OLDEST_OPEN_TODOS: i64 = 5;
+
+/// Aggregate statistics for a single project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectStats {
+    pub project_id: i64,
+    pub open_todos: i64,
+    pub completed_todos: i64,
+    /// Completions per week (weeks start on Monday), oldest first; weeks
+    /// without completions are omitted
+    pub completions_per_week: Vec<WeeklyCompletions>,
+    pub oldest_open_todo: Option<Todo>,
+    /// Up to `OLDEST_OPEN_TODOS` open todos, oldest first
+    pub oldest_open_todos: Vec<Todo>,
+    /// Mean time from creation to completion
+    pub average_completion_hours: Option<f64>,
+}
+
+/// Counts across all projects, for the summary above the project list.
+/// Open todos of archived projects are left out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct Totals {
+    pub active_projects: i64,
+    pub archived_projects: i64,
+    pub open_todos: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct WeeklyCompletions {
+    pub week_start: NaiveDate,
+    pub completed: i64,
+}
+
+/// A todo's description and details as they were before an edit replaced
+/// them, kept so the edit can be undone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct TodoVersion {
+    pub id: i64,
+    pub todo_id: i64,
+    pub description: String,
+    pub details: Option<String>,
+    pub replaced_at: DateTime<Utc>,
+}
+
+/// How one todo relates to another it links to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "db", derive(sqlx::Type))]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "db", sqlx(rename_all = "snake_case"))]
+pub enum LinkKind {
+    /// Related work worth a look
+    SeeAlso,
+    /// The same work as the todo linked to, which is the one to keep
+    Duplicates,
+}
+
+impl LinkKind {
+    /// How the link reads from the todo at its `incoming` end, e.g.
+    /// "Duplicated by"
+    pub fn label(&self, incoming: bool) -> &'static str {
+        match (self, incoming) {
+            (LinkKind::SeeAlso, _) => "See also",
+            (LinkKind::Duplicates, false) => "Duplicates",
+            (LinkKind::Duplicates, true) => "Duplicated by",
+        }
+    }
+}
+
+/// A link from one todo to another, in the same project or not
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct TodoLink {
+    pub id: i64,
+    pub todo_id: i64,
+    pub target_id: i64,
+    pub kind: LinkKind,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A link seen from one of its todos, with the todo at the other end
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatedTodo {
+    pub link: TodoLink,
+    pub todo: Todo,
+    pub project_name: String,
+}
+
+impl RelatedTodo {
+    /// The link points at the todo it's seen from rather than away from it
+    pub fn is_incoming(&self) -> bool {
+        self.link.target_id != self.todo.id
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.link.kind.label(self.is_incoming())
+    }
+}
+
+/// A todo mentioned in another's details as `#123` or `docket://todo/123`,
+/// or one whose details mention it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TodoMention {
+    pub todo: Todo,
+    pub project_name: String,
+    /// `todo`'s details mention the todo it's seen from, rather than the
+    /// other way round
+    pub incoming: bool,
+}
+
+impl TodoMention {
+    pub fn label(&self) -> &'static str {
+        if self.incoming { "Referenced by" } else { "Mentions" }
+    }
+}
+
+/// A reusable todo, e.g. a deployment runbook with a `- [ ]` checklist in
+/// its details
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct TodoTemplate {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An area such as Work, Home or OSS that groups projects
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct Workspace {
+    pub id: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How the TUI shows a project's todos, remembered between visits
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct ProjectSettings {
+    pub show_completed: bool,
+    /// `:sort` name, or `None` for the configured default
+    pub sort: Option<String>,
+    pub filter: Option<String>,
+}
+
+impl Default for ProjectSettings {
+    fn default() -> Self {
+        Self {
+            show_completed: true,
+            sort: None,
+            filter: None,
+        }
+    }
+}
+
+/// Record of a deleted project or todo, kept for delta sync
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct Tombstone {
+    /// `project` or `todo`
+    pub entity: String,
+    pub entity_id: i64,
+    pub deleted_at: DateTime<Utc>,
+    pub version: i64,
+}
+
+/// What happened to an earlier request with the same idempotency key
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotentRequest {
+    /// First use of the key; the request should run
+    New,
+    /// The first request is still running
+    InProgress,
+    /// The key was used for a different request
+    Mismatch,
+    /// The first request finished with this response
+    Done {
+        status: u16,
+        content_type: Option<String>,
+        body: Vec<u8>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_from_description_and_details() {
+        let todo = Todo {
+            id: 1,
+            project_id: 1,
+            description: "Call plumber #home #Urgent".to_string(),
+            details: Some("re: leak (#home, #q3-launch)".to_string()),
+            created_at: Utc::now(),
+            completed_at: None,
+            position: 1,
+            estimate: None,
+            due_date: None,
+            external_ref: None,
+            updated_at: Utc::now(),
+            version: 1,
+        };
+        assert_eq!(todo.tags(), vec!["home", "urgent", "q3-launch"]);
+    }
+
+    #[test]
+    fn test_short_ids() {
+        let mut project: Project = serde_json::from_value(serde_json::json!({
+            "id": 1, "name": "Kitchen Renovation", "description": null, "created_at": Utc::now(),
+            "archived_at": null, "last_reviewed_at": null, "target_date": null, "slug": null,
+            "updated_at": Utc::now(), "version": 1,
+        }))
+        .unwrap();
+        assert_eq!(project.todo_short_id(42), "KR-42");
+        // The key has to be the project's own
+        assert!(parse_todo_ref("kr-42").unwrap().fits(&project));
+        assert!(parse_todo_ref("#42").unwrap().fits(&project));
+        assert!(!parse_todo_ref("HOME-42").unwrap().fits(&project));
+        project.name = "Café".to_string();
+        assert_eq!(project.key(), "CAFE");
+        project.name = "!!".to_string();
+        assert_eq!(project.key(), "TODO");
+
+        let id = |text| parse_todo_ref(text).map(|r| r.id);
+        assert_eq!(id("kr-42"), Some(42));
+        assert_eq!(id(" #7 "), Some(7));
+        assert_eq!(id("7"), Some(7));
+        assert_eq!(parse_todo_ref("-7"), None);
+        assert_eq!(parse_todo_ref("KR-"), None);
+        assert_eq!(parse_todo_ref("Buy paint"), None);
+    }
+
+    #[test]
+    fn test_checklist_in_details() {
+        let details = "Tiles for the hall\n- [ ] measure\n  * [X] pick colour\n- [] not an item";
+        let todo = Todo {
+            id: 1,
+            project_id: 1,
+            description: "Retile hall".to_string(),
+            details: Some(details.to_string()),
+            created_at: Utc::now(),
+            completed_at: None,
+            position: 1,
+            estimate: None,
+            due_date: None,
+            external_ref: None,
+            updated_at: Utc::now(),
+            version: 1,
+        };
+        let items: Vec<(bool, &str)> = todo.checklist().iter().map(|item| (item.checked, item.text)).collect();
+        assert_eq!(items, vec![(false, "measure"), (true, "pick colour")]);
+        assert_eq!(todo.checklist_progress(), Some((1, 2)));
+        assert_eq!(
+            toggle_checklist_item(details, 1).as_deref(),
+            Some("Tiles for the hall\n- [ ] measure\n  * [ ] pick colour\n- [] not an item")
+        );
+        assert_eq!(toggle_checklist_item(details, 2), None);
+    }
+
+    #[test]
+    fn test_age_brackets() {
+        let mut todo = Todo {
+            id: 1,
+            project_id: 1,
+            description: "Renew passport".to_string(),
+            details: None,
+            created_at: Utc::now() - Duration::days(45),
+            completed_at: None,
+            position: 1,
+            estimate: None,
+            due_date: None,
+            external_ref: None,
+            updated_at: Utc::now(),
+            version: 1,
+        };
+        assert_eq!((todo.age_days(), todo.age()), (45, TodoAge::Aging));
+        todo.created_at = Utc::now() - Duration::days(120);
+        assert_eq!(todo.age(), TodoAge::Stale);
+        todo.completed_at = Some(Utc::now());
+        assert_eq!(todo.age(), TodoAge::Fresh);
+    }
 }
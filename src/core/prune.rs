@@ -0,0 +1,71 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use super::models::Todo;
+
+/// The retention policy in the config file's `prune`, e.g.
+/// `{"completed_days": 365, "archive": true}`: todos completed more than
+/// `completed_days` days ago are deleted, or moved to the `archived_todos`
+/// table when `archive` is set
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrunePolicy {
+    pub completed_days: i64,
+    /// Keep pruned todos in `archived_todos` rather than deleting them outright
+    #[serde(default)]
+    pub archive: bool,
+}
+
+impl PrunePolicy {
+    pub fn validate(&self) -> Result<()> {
+        if self.completed_days < 0 {
+            bail!("Prune completed_days must not be negative");
+        }
+        Ok(())
+    }
+
+    /// Todos completed before this are pruned
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::days(self.completed_days)
+    }
+}
+
+/// A completed todo the policy removes, or would remove on a dry run
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrunedTodo {
+    pub todo_id: i64,
+    pub description: String,
+    pub project: String,
+    pub completed_days_ago: i64,
+}
+
+impl PrunedTodo {
+    pub fn new(todo: &Todo, project: &str, now: DateTime<Utc>) -> Self {
+        Self {
+            todo_id: todo.id,
+            description: todo.description.clone(),
+            project: project.to_string(),
+            completed_days_ago: todo.completed_at.map_or(0, |at| (now - at).num_days()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_from_config() {
+        let policy: PrunePolicy = serde_json::from_str(r#"{"completed_days": 365, "archive": true}"#).unwrap();
+        assert!(policy.validate().is_ok());
+        let now = Utc::now();
+        assert_eq!(policy.cutoff(now), now - Duration::days(365));
+
+        let delete: PrunePolicy = serde_json::from_str(r#"{"completed_days": 30}"#).unwrap();
+        assert!(!delete.archive);
+        let negative = PrunePolicy { completed_days: -1, archive: false };
+        assert!(negative.validate().unwrap_err().to_string().contains("must not be negative"));
+        assert!(serde_json::from_str::<PrunePolicy>(r#"{"completed_days": 1, "keep": 5}"#).is_err());
+    }
+}
@@ -0,0 +1,323 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::str::FromStr;
+
+use super::models::Todo;
+use super::search;
+
+/// Filter names, for error messages
+const KEYS: &str = "status, tag, due, estimate, project or text";
+
+/// Keys `parse_term` knows, including ones it only explains
+const KNOWN_KEYS: &[&str] = &["status", "is", "tag", "due", "estimate", "project", "text", "priority"];
+
+/// A todo filter such as `status:open tag:home due:<friday text:"invoice"`.
+/// Terms are separated by spaces and must all match; `-` in front of a term
+/// negates it, and a bare word or quoted string is short for `text:`.
+/// Text and project names match ignoring case and accents.
+/// Used by the TUI's `:filter`, `docket list --query` and the API's `?q=`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodoQuery {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    negated: bool,
+    test: Test,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Test {
+    Status(Status),
+    Tag(String),
+    /// `None` compares against "no due date": `due:none`
+    Due(Compare, Option<Day>),
+    /// `None` compares against "no estimate"
+    Estimate(Compare, Option<i64>),
+    Project(String),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Open,
+    Done,
+    Overdue,
+    Any,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compare {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// `due:any`: has a value at all
+    Set,
+}
+
+/// A day in a `due:` term, resolved against today when matching
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Day {
+    Date(NaiveDate),
+    /// Days from today: `today` is 0, `tomorrow` 1, `+3d` 3
+    Offset(i64),
+    /// The next such day, today included: `due:<friday` is before this Friday
+    Next(Weekday),
+}
+
+impl Day {
+    fn resolve(&self, today: NaiveDate) -> NaiveDate {
+        match *self {
+            Day::Date(date) => date,
+            Day::Offset(days) => today + Duration::days(days),
+            Day::Next(weekday) => {
+                let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+                today + Duration::days(ahead as i64)
+            }
+        }
+    }
+}
+
+impl FromStr for Day {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_lowercase();
+        let offset = match lower.as_str() {
+            "today" => Some(0),
+            "tomorrow" => Some(1),
+            "yesterday" => Some(-1),
+            _ => None,
+        };
+        if let Some(days) = offset {
+            return Ok(Day::Offset(days));
+        }
+        if let Some(days) = lower.strip_suffix('d').and_then(|n| n.parse::<i64>().ok()) {
+            return Ok(Day::Offset(days));
+        }
+        if let Some(weeks) = lower.strip_suffix('w').and_then(|n| n.parse::<i64>().ok()) {
+            return Ok(Day::Offset(weeks * 7));
+        }
+        if let Ok(weekday) = lower.parse::<Weekday>() {
+            return Ok(Day::Next(weekday));
+        }
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(Day::Date)
+            .map_err(|_| format!("'{}' is not a day (expected e.g. today, friday, +3d or 2026-06-30)", s))
+    }
+}
+
+impl Compare {
+    fn holds<T: PartialOrd>(&self, value: Option<T>, against: Option<T>) -> bool {
+        match (self, value, against) {
+            (Compare::Set, value, _) => value.is_some(),
+            (Compare::Eq, value, against) => value == against,
+            (_, None, _) | (_, _, None) => false,
+            (Compare::Lt, Some(v), Some(a)) => v < a,
+            (Compare::Le, Some(v), Some(a)) => v <= a,
+            (Compare::Gt, Some(v), Some(a)) => v > a,
+            (Compare::Ge, Some(v), Some(a)) => v >= a,
+        }
+    }
+}
+
+/// Split a leading comparison off a value: `<=friday` is (Le, "friday")
+fn comparison(value: &str) -> (Compare, &str) {
+    for (prefix, compare) in [("<=", Compare::Le), (">=", Compare::Ge), ("<", Compare::Lt), (">", Compare::Gt)] {
+        if let Some(rest) = value.strip_prefix(prefix) {
+            return (compare, rest);
+        }
+    }
+    (Compare::Eq, value.strip_prefix('=').unwrap_or(value))
+}
+
+impl FromStr for TodoQuery {
+    type Err = String;
+
+    /// Errors say what was wrong and at which column, counting from 1
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut terms = Vec::new();
+        let mut chars = input.char_indices().peekable();
+        loop {
+            while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+            let Some(&(start, _)) = chars.peek() else { break };
+            let column = input[..start].chars().count() + 1;
+
+            // A term runs to the next space outside quotes
+            let mut raw = String::new();
+            let mut quoted = false;
+            let mut in_quotes = false;
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_whitespace() && !in_quotes {
+                    break;
+                }
+                chars.next();
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                    quoted = true;
+                } else {
+                    raw.push(c);
+                }
+            }
+            if in_quotes {
+                return Err(format!("Missing closing quote for the term at column {}", column));
+            }
+            let term = parse_term(&raw, quoted).map_err(|e| format!("{} at column {}", e, column))?;
+            terms.push(term);
+        }
+        Ok(TodoQuery { terms })
+    }
+}
+
+fn parse_term(raw: &str, quoted: bool) -> Result<Term, String> {
+    let (negated, raw) = match raw.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, raw),
+    };
+    let Some((key, value)) = raw.split_once(':').filter(|(key, _)| !key.is_empty() && !key.contains(' ')) else {
+        return Ok(Term { negated, test: Test::Text(search::fold(raw)) });
+    };
+    // `OPS-42: fix` or a URL is text rather than a misspelt filter
+    let key = key.to_lowercase();
+    let looks_like_text = key.chars().any(|c| c.is_ascii_digit() || c == '.') || value.starts_with("//");
+    if !KNOWN_KEYS.contains(&key.as_str()) && looks_like_text {
+        return Ok(Term { negated, test: Test::Text(search::fold(raw)) });
+    }
+    if value.is_empty() && !quoted {
+        return Err(format!("'{}:' needs a value", key));
+    }
+    let test = match key.as_str() {
+        "status" | "is" => Test::Status(match value.to_lowercase().as_str() {
+            "open" | "todo" => Status::Open,
+            "done" | "completed" => Status::Done,
+            "overdue" => Status::Overdue,
+            "any" | "all" => Status::Any,
+            other => return Err(format!("Unknown status '{}' (expected open, done, overdue or any)", other)),
+        }),
+        "tag" => {
+            let tag = value.trim_start_matches('#').to_lowercase();
+            if tag.is_empty() || !tag.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+                return Err(format!("'{}' is not a valid hashtag", value));
+            }
+            Test::Tag(tag)
+        }
+        "due" => match comparison(value) {
+            (Compare::Eq, v) if v.eq_ignore_ascii_case("none") => Test::Due(Compare::Eq, None),
+            (Compare::Eq, v) if v.eq_ignore_ascii_case("any") => Test::Due(Compare::Set, None),
+            (compare, v) => Test::Due(compare, Some(v.parse()?)),
+        },
+        "estimate" => match comparison(value) {
+            (Compare::Eq, v) if v.eq_ignore_ascii_case("none") => Test::Estimate(Compare::Eq, None),
+            (Compare::Eq, v) if v.eq_ignore_ascii_case("any") => Test::Estimate(Compare::Set, None),
+            (compare, v) => {
+                let n = v.parse().map_err(|_| format!("'{}' is not a number of points", v))?;
+                Test::Estimate(compare, Some(n))
+            }
+        },
+        "project" => Test::Project(search::fold(value)),
+        "text" => Test::Text(search::fold(value)),
+        "priority" => return Err("Todos are prioritized by their order; use a tag instead, e.g. tag:urgent".to_string()),
+        other => return Err(format!("Unknown filter '{}' (expected {})", other, KEYS)),
+    };
+    Ok(Term { negated, test })
+}
+
+impl TodoQuery {
+    /// Whether the query has no terms, and so matches everything
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Whether the query says which todos to include by status; without a
+    /// `status:` term callers keep their usual open / completed choice
+    pub fn has_status(&self) -> bool {
+        self.terms.iter().any(|t| matches!(t.test, Test::Status(_)))
+    }
+
+    /// Check `todo`, which is in the project named `project`, with relative
+    /// days such as `due:<friday` counted from `today`
+    pub fn matches(&self, todo: &Todo, project: &str, today: NaiveDate) -> bool {
+        self.terms.iter().all(|term| term.test.matches(todo, project, today) != term.negated)
+    }
+}
+
+impl Test {
+    fn matches(&self, todo: &Todo, project: &str, today: NaiveDate) -> bool {
+        match self {
+            Test::Status(Status::Open) => !todo.is_completed(),
+            Test::Status(Status::Done) => todo.is_completed(),
+            Test::Status(Status::Overdue) => !todo.is_completed() && todo.due_date.is_some_and(|due| due < today),
+            Test::Status(Status::Any) => true,
+            Test::Tag(tag) => todo.tags().contains(tag),
+            Test::Due(compare, day) => compare.holds(todo.due_date, day.map(|d| d.resolve(today))),
+            Test::Estimate(compare, points) => compare.holds(todo.estimate, *points),
+            Test::Project(name) => search::contains(project, name),
+            Test::Text(text) => {
+                search::contains(&todo.description, text)
+                    || todo.details.as_ref().is_some_and(|d| search::contains(d, text))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn todo(description: &str, due: Option<NaiveDate>, estimate: Option<i64>) -> Todo {
+        Todo {
+            id: 1,
+            project_id: 1,
+            description: description.to_string(),
+            details: None,
+            created_at: Utc::now(),
+            completed_at: None,
+            position: 1,
+            estimate,
+            due_date: due,
+            external_ref: None,
+            updated_at: Utc::now(),
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn test_terms_combine() {
+        // A Wednesday, so `friday` is two days away
+        let today = NaiveDate::from_ymd_opt(2026, 6, 10).unwrap();
+        let query: TodoQuery = r#"status:open tag:home due:<friday "pay invoice" -estimate:>3"#.parse().unwrap();
+        let invoice = todo("Pay invoice #home", Some(today + Duration::days(1)), Some(2));
+        assert!(query.matches(&invoice, "Home", today));
+        assert!(!query.matches(&todo("Pay invoice #home", Some(today + Duration::days(2)), None), "Home", today));
+        assert!(!query.matches(&todo("Pay invoice #home", Some(today), Some(5)), "Home", today));
+        assert!(!query.matches(&todo("Pay invoice", Some(today), None), "Home", today));
+
+        let undated: TodoQuery = "due:none project:wor".parse().unwrap();
+        assert!(undated.matches(&todo("Call Sam", None, None), "Work", today));
+        assert!(!undated.matches(&todo("Call Sam", Some(today), None), "Work", today));
+        assert!(!undated.has_status());
+        assert!("OPS-42: fix https://x.io/a".parse::<TodoQuery>().is_ok());
+
+        let accents: TodoQuery = "CAFE project:zurich".parse().unwrap();
+        assert!(accents.matches(&todo("Book the Café", None, None), "Zürich office", today));
+    }
+
+    #[test]
+    fn test_parse_errors_point_at_the_term() {
+        let error = |input: &str| input.parse::<TodoQuery>().unwrap_err();
+        assert_eq!(
+            error("tag:home statsu:open"),
+            "Unknown filter 'statsu' (expected status, tag, due, estimate, project or text) at column 10"
+        );
+        assert_eq!(
+            error("due:<someday"),
+            "'someday' is not a day (expected e.g. today, friday, +3d or 2026-06-30) at column 1"
+        );
+        assert_eq!(error("tag:home text:\"unfinished"), "Missing closing quote for the term at column 10");
+        assert!(error("priority:high").contains("use a tag instead"));
+        assert!(error("status:later").contains("expected open, done, overdue or any"));
+    }
+}
@@ -0,0 +1,72 @@
+/// Link text that refers to a todo by ID, as in `docket://todo/123`
+pub const TODO_URL: &str = "docket://todo/";
+
+/// A mention of another todo in some text: `#123` or `docket://todo/123`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reference {
+    /// Byte range of the mention in the text
+    pub start: usize,
+    pub end: usize,
+    pub todo_id: i64,
+}
+
+/// The todo mentions in `text`, in order. `#123` counts when it stands on
+/// its own, so `#12abc` stays a tag and `page#12` or `/#12` a URL fragment.
+pub fn find(text: &str) -> Vec<Reference> {
+    let mut found = Vec::new();
+    let mut at = 0;
+    while let Some(offset) = text[at..].find(['#', 'd']) {
+        let start = at + offset;
+        let (prefix_len, standalone) = if text[start..].starts_with('#') {
+            let before = text[..start].chars().next_back();
+            (1, !before.is_some_and(|c| c.is_alphanumeric() || matches!(c, '/' | '&' | '#')))
+        } else if text[start..].starts_with(TODO_URL) {
+            (TODO_URL.len(), true)
+        } else {
+            (1, false)
+        };
+        at = start + prefix_len;
+        if !standalone {
+            continue;
+        }
+        let digits = text[at..].bytes().take_while(u8::is_ascii_digit).count();
+        let end = at + digits;
+        let joined = text[end..].chars().next().is_some_and(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if digits == 0 || joined {
+            continue;
+        }
+        if let Ok(todo_id) = text[at..end].parse() {
+            found.push(Reference { start, end, todo_id });
+        }
+        at = end;
+    }
+    found
+}
+
+/// IDs of the todos `text` mentions, each once, in order of first mention
+pub fn todo_ids(text: &str) -> Vec<i64> {
+    let mut ids: Vec<i64> = Vec::new();
+    for reference in find(text) {
+        if !ids.contains(&reference.todo_id) {
+            ids.push(reference.todo_id);
+        }
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_todo_mentions() {
+        let text = "After #12 (see docket://todo/7), not #12abc, page#3, /#4 or #; again #12.";
+        let found = find(text);
+        let ids: Vec<i64> = found.iter().map(|r| r.todo_id).collect();
+        assert_eq!(ids, [12, 7, 12]);
+        assert_eq!(&text[found[0].start..found[0].end], "#12");
+        assert_eq!(&text[found[1].start..found[1].end], "docket://todo/7");
+        assert_eq!(todo_ids(text), [12, 7]);
+        assert!(find("Café #ünï d").is_empty());
+    }
+}
@@ -1,32 +1,160 @@
-use anyhow::{bail, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-use super::db::Database;
-use super::models::{Project, ProjectWithStats, Todo};
+use super::audit::{self, AuditPage, AuditQuery};
+use super::batch::{BatchOp, BatchResult};
+use super::db::{Database, WriteTransaction};
+use super::digest::{Digest, DigestTodo};
+use super::error::{DocketError, Result};
+use super::events::{ChangeKind, EventBus};
+use super::groom::{self, GroomChange, GroomRule};
+use super::demo;
+use super::limits::Limits;
+use super::prune::{PrunePolicy, PrunedTodo};
+use super::references;
+use super::share::{self, ShareLink, SharedProject, MAX_SHARE_DAYS};
+use super::sort::TodoOrder;
+use super::models::{
+    parse_todo_ref, toggle_checklist_item, IdempotentRequest, LinkKind, PositionContext, Project, ProjectSettings, ProjectStats, ProjectWithStats, RelatedTodo, Todo,
+    TodoContext, TodoLink, TodoMention, TodoPatch, TodoTemplate, TodoVersion, Totals, Workspace,
+};
+use super::sync::{
+    ChangeMarker, ChangeSet, ConflictReason, Entity, ProjectChange, PushChange, PushResult, SyncConflict, TodoChange,
+};
+use super::time::DisplayZone;
+
+/// Most operations accepted in one `POST /api/batch`
+const MAX_BATCH_OPS: usize = 1000;
+
+/// How long a response is kept for replay under its idempotency key
+const IDEMPOTENCY_KEY_HOURS: i64 = 24;
 
 /// Business logic service layer
 #[derive(Clone)]
 pub struct DocketService {
     db: Database,
+    events: EventBus,
+    /// Uncompleted todos return to their old place rather than the end
+    restore_positions: bool,
+    /// Decides what "today" means for reviews and target dates
+    timezone: DisplayZone,
+    /// Caps on projects, todos and details
+    limits: Limits,
 }
 
 impl DocketService {
     /// Create a new service instance
     pub fn new(db: Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            events: EventBus::new(),
+            restore_positions: true,
+            timezone: DisplayZone::default(),
+            limits: Limits::default(),
+        }
+    }
+
+    /// Choose where uncompleted todos go: back to their old place, or the end
+    pub fn with_restore_positions(mut self, restore: bool) -> Self {
+        self.restore_positions = restore;
+        self
+    }
+
+    /// Set the time zone used to decide what "today" is
+    pub fn with_timezone(mut self, zone: DisplayZone) -> Self {
+        self.timezone = zone;
+        self
+    }
+
+    /// Cap how many projects and todos may be created, and how long details get
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Check `project_id` exists and has room for `adding` more todos
+    async fn check_todo_room(&self, project_id: i64, adding: i64) -> Result<()> {
+        let project = self.db.get_project_with_stats(project_id).await?;
+        self.limits.check_todos(&project.project.name, project.total_todos, adding)
+    }
+
+    /// Time zone used to decide what "today" is
+    pub fn timezone(&self) -> DisplayZone {
+        self.timezone
+    }
+
+    /// Change notifications for everything this service modifies
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Publish a change and add it to the audit log under the current actor.
+    /// The change itself is already saved, so a failed audit write is only
+    /// logged.
+    async fn record(&self, kind: ChangeKind, project_id: Option<i64>, todo_id: Option<i64>) {
+        self.record_change(kind, project_id, todo_id, None).await
+    }
+
+    /// `record` for a change to the workspace `id`
+    async fn record_workspace(&self, kind: ChangeKind, id: i64) {
+        self.record_change(kind, None, None, Some(id)).await
+    }
+
+    async fn record_change(
+        &self,
+        kind: ChangeKind,
+        project_id: Option<i64>,
+        todo_id: Option<i64>,
+        workspace_id: Option<i64>,
+    ) {
+        let version = self.db.current_sync_version().await.unwrap_or_default();
+        self.events.publish(version as u64, kind, project_id, todo_id, workspace_id);
+        let (entity, entity_id) = audit::entity(project_id, todo_id, workspace_id);
+        let actor = audit::current_actor();
+        if let Err(err) = self.db.insert_audit_entry(&actor, kind.as_str(), entity, entity_id, project_id).await {
+            tracing::warn!("Failed to record {} in the audit log: {}", kind.as_str(), err);
+        }
+    }
+
+    /// A page of the audit log, newest first
+    pub async fn audit_log(&self, query: &AuditQuery) -> Result<AuditPage> {
+        query.validate()?;
+        let (entries, total) = self.db.list_audit_entries(query).await?;
+        Ok(AuditPage { entries, total, limit: query.limit(), offset: query.offset() })
     }
 
     // ===== Project Operations =====
 
     /// Create a new project with validation
     pub async fn create_project(&self, name: &str) -> Result<Project> {
-        let name = name.trim();
-        if name.is_empty() {
-            bail!("Project name cannot be empty");
-        }
-        if name.len() > 255 {
-            bail!("Project name is too long (max 255 characters)");
-        }
-        self.db.create_project(name, None).await
+        self.create_project_with_description(name, None).await
+    }
+
+    /// Create a new project with a description
+    pub async fn create_project_with_description(&self, name: &str, description: Option<&str>) -> Result<Project> {
+        let name = valid_project_name(name)?;
+        let description = description.map(|d| d.trim()).filter(|d| !d.is_empty());
+        self.ensure_name_available(name, None).await?;
+        self.limits.check_projects(self.db.count_projects().await?)?;
+        let project = self.db.create_project(name, description).await?;
+        self.record(ChangeKind::ProjectCreated, Some(project.id), None).await;
+        Ok(project)
+    }
+
+    /// Copy a project with its open todos, and with `include_completed` its
+    /// completed ones too, reopened. Without a name the copy is called
+    /// `<name> (2)` (or the next free number).
+    pub async fn clone_project(&self, id: i64, name: Option<&str>, include_completed: bool) -> Result<Project> {
+        let project = self.db.get_project(id).await?;
+        let name = match name {
+            Some(name) => valid_project_name(name)?.to_string(),
+            None => self.suggest_project_name(&project.name).await?,
+        };
+        self.ensure_name_available(&name, None).await?;
+        self.limits.check_projects(self.db.count_projects().await?)?;
+        let clone_id = self.db.clone_project(id, &name, include_completed).await?;
+        self.record(ChangeKind::ProjectCreated, Some(clone_id), None).await;
+        self.db.get_project(clone_id).await
     }
 
     /// Get a project by ID
@@ -34,6 +162,55 @@ impl DocketService {
         self.db.get_project(id).await
     }
 
+    /// Get a project by ID together with its todo statistics
+    pub async fn get_project_with_stats(&self, id: i64) -> Result<ProjectWithStats> {
+        self.db.get_project_with_stats(id).await
+    }
+
+    /// Get aggregate statistics for a project, covering the last `weeks` weeks
+    /// of completions, with weeks starting on Monday in `zone`
+    pub async fn get_project_stats(&self, id: i64, weeks: i64, zone: DisplayZone) -> Result<ProjectStats> {
+        // Verify project exists
+        self.db.get_project(id).await?;
+        let offset = zone.offset_at(Utc::now());
+        self.db.get_project_stats(id, weeks.max(1), offset).await
+    }
+
+    /// Count active and archived projects, and the open todos of active ones
+    pub async fn get_totals(&self) -> Result<Totals> {
+        self.db.get_totals().await
+    }
+
+    /// Build the agenda of overdue todos, todos due in the next `days` days
+    /// and projects at risk of missing their target date
+    pub async fn build_digest(&self, days: i64) -> Result<Digest> {
+        let today = self.timezone.today();
+        let days = days.max(0);
+        let projects = self.db.list_projects(false).await?;
+        let name = |project_id: i64| {
+            projects
+                .iter()
+                .find(|p| p.project.id == project_id)
+                .map(|p| p.project.name.clone())
+                .unwrap_or_default()
+        };
+
+        let (overdue, due_soon): (Vec<_>, Vec<_>) = self
+            .db
+            .list_open_todos_due_by(today + Duration::days(days))
+            .await?
+            .into_iter()
+            .map(|todo| DigestTodo { project: name(todo.project_id), todo })
+            .partition(|item| item.todo.due_date.is_some_and(|d| d < today));
+        let at_risk = projects
+            .iter()
+            .filter(|p| p.is_at_risk(self.timezone))
+            .cloned()
+            .collect();
+
+        Ok(Digest { today, days, overdue, due_soon, at_risk })
+    }
+
     /// Update a project's description
     pub async fn update_project_description(&self, id: i64, description: Option<&str>) -> Result<()> {
         // Verify project exists
@@ -42,7 +219,18 @@ impl DocketService {
         // Trim and validate description if provided
         let description = description.map(|d| d.trim()).filter(|d| !d.is_empty());
 
-        self.db.update_project_description(id, description).await
+        self.db.update_project_description(id, description).await?;
+        self.record(ChangeKind::ProjectUpdated, Some(id), None).await;
+        Ok(())
+    }
+
+    /// Set or clear a project's target completion date
+    pub async fn update_project_target_date(&self, id: i64, target_date: Option<NaiveDate>) -> Result<()> {
+        // Verify project exists
+        self.db.get_project(id).await?;
+        self.db.update_project_target_date(id, target_date).await?;
+        self.record(ChangeKind::ProjectUpdated, Some(id), None).await;
+        Ok(())
     }
 
     /// Update a project's name
@@ -50,15 +238,80 @@ impl DocketService {
         // Verify project exists
         self.db.get_project(id).await?;
 
-        let name = name.trim();
-        if name.is_empty() {
-            bail!("Project name cannot be empty");
+        let name = valid_project_name(name)?;
+        self.ensure_name_available(name, Some(id)).await?;
+
+        self.db.update_project_name(id, name).await?;
+        self.record(ChangeKind::ProjectUpdated, Some(id), None).await;
+        Ok(())
+    }
+
+    /// Set every editable field of a project at once. Repeating the same
+    /// update changes nothing, which makes it safe to retry.
+    pub async fn replace_project(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+        target_date: Option<NaiveDate>,
+        archived: bool,
+    ) -> Result<Project> {
+        let project = self.db.get_project(id).await?;
+        let name = valid_project_name(name)?;
+        let description = description.map(|d| d.trim()).filter(|d| !d.is_empty());
+        self.ensure_name_available(name, Some(id)).await?;
+
+        if project.name != name || project.description.as_deref() != description || project.target_date != target_date {
+            self.db.update_project(id, name, description, target_date).await?;
+            self.record(ChangeKind::ProjectUpdated, Some(id), None).await;
+        }
+        match (project.archived_at.is_some(), archived) {
+            (false, true) => self.archive_project(id).await?,
+            (true, false) => self.unarchive_project(id).await?,
+            _ => {}
+        }
+        self.db.get_project(id).await
+    }
+
+    /// Fail with a `Conflict` suggesting a free name if another project
+    /// already uses `name` (ignoring case)
+    async fn ensure_name_available(&self, name: &str, exclude_id: Option<i64>) -> Result<()> {
+        match self.db.find_project_by_name(name).await? {
+            Some(existing) if Some(existing.id) != exclude_id => {
+                let suggestion = self.suggest_project_name(name).await?;
+                Err(name_taken(name, suggestion))
+            }
+            _ => Ok(()),
         }
-        if name.len() > 255 {
-            bail!("Project name is too long (max 255 characters)");
+    }
+
+    /// Suggest a free variant of `name` by appending " (2)", " (3)", ...
+    pub async fn suggest_project_name(&self, name: &str) -> Result<String> {
+        let mut n = 2;
+        loop {
+            let candidate = format!("{} ({})", name, n);
+            if self.db.find_project_by_name(&candidate).await?.is_none() {
+                return Ok(candidate);
+            }
+            n += 1;
+        }
+    }
+
+    /// Find a project by name, ignoring case, or failing that accents too,
+    /// so `cafe` finds `Café`
+    pub async fn find_project_by_name(&self, name: &str) -> Result<Option<Project>> {
+        match self.db.find_project_by_name(name.trim()).await? {
+            Some(project) => Ok(Some(project)),
+            None => self.db.find_project_by_folded_name(name.trim()).await,
         }
+    }
 
-        self.db.update_project_name(id, name).await
+    /// Find a project by name (ignoring case), creating it if it doesn't exist
+    pub async fn find_or_create_project(&self, name: &str) -> Result<Project> {
+        match self.find_project_by_name(name).await? {
+            Some(project) => Ok(project),
+            None => self.create_project(name).await,
+        }
     }
 
     /// List all active projects
@@ -71,43 +324,215 @@ impl DocketService {
         self.db.list_projects(true).await
     }
 
+    /// List active projects whose todos are all done and that have been
+    /// inactive for at least `stale_after_days`
+    pub async fn list_archive_candidates(&self, stale_after_days: i64) -> Result<Vec<ProjectWithStats>> {
+        let projects = self.db.list_projects(false).await?;
+        Ok(projects
+            .into_iter()
+            .filter(|p| p.is_archive_candidate(stale_after_days))
+            .collect())
+    }
+
+    /// Archive every project suggested by `list_archive_candidates`, returning them
+    pub async fn archive_stale_projects(&self, stale_after_days: i64) -> Result<Vec<ProjectWithStats>> {
+        let stale = self.list_archive_candidates(stale_after_days).await?;
+        for project in &stale {
+            self.db.archive_project(project.project.id).await?;
+            self.record(ChangeKind::ProjectArchived, Some(project.project.id), None).await;
+        }
+        Ok(stale)
+    }
+
+    /// Apply grooming rules to the open todos of active projects, or with
+    /// `dry_run` only report what they would change
+    pub async fn groom(&self, rules: &[GroomRule], dry_run: bool) -> Result<Vec<GroomChange>> {
+        let projects = self.db.list_projects(false).await?;
+        let todos = self.db.list_open_todos().await?;
+        let changes = groom::plan(rules, &projects, &todos, self.timezone)
+            .map_err(|err| DocketError::validation("groom_rules", err.to_string()))?;
+        if dry_run {
+            return Ok(changes);
+        }
+
+        // A todo matching several rules gets all their tags in one update
+        let mut tags: BTreeMap<i64, Vec<&str>> = BTreeMap::new();
+        let mut reviews = Vec::new();
+        for change in &changes {
+            match change {
+                GroomChange::Tag { todo_id, tag, .. } => tags.entry(*todo_id).or_default().push(tag),
+                GroomChange::Review { project_id, .. } => reviews.push(*project_id),
+            }
+        }
+
+        // Every new description is checked before the first write, and all
+        // of them land together, so a failed run leaves nothing half groomed
+        let mut tx = self.db.begin().await?;
+        let mut tagged = Vec::new();
+        for (id, tags) in tags {
+            let todo = tx.get_todo(id).await?;
+            let tags: String = tags.iter().map(|tag| format!(" #{}", tag)).collect();
+            let description = format!("{}{}", todo.description, tags);
+            valid_todo_description(&description).map_err(|err| err.context(format!("Tagging todo {}", id)))?;
+            tagged.push((todo, description));
+        }
+        for project_id in &reviews {
+            tx.clear_project_review(*project_id).await?;
+        }
+        for (todo, description) in &tagged {
+            tx.update_todo(todo.id, description).await?;
+        }
+        tx.commit().await?;
+
+        for project_id in reviews {
+            self.record(ChangeKind::ProjectUpdated, Some(project_id), None).await;
+        }
+        for (todo, _) in tagged {
+            self.record(ChangeKind::TodoUpdated, Some(todo.project_id), Some(todo.id)).await;
+        }
+        Ok(changes)
+    }
+
+    /// Remove todos completed longer ago than `policy` keeps them, in every
+    /// project, or with `dry_run` only report which ones it would remove
+    pub async fn prune(&self, policy: &PrunePolicy, dry_run: bool) -> Result<Vec<PrunedTodo>> {
+        policy.validate().map_err(|err| DocketError::validation("prune", err.to_string()))?;
+        let now = Utc::now();
+        let todos = self.db.list_todos_completed_before(policy.cutoff(now)).await?;
+        let pruned: Vec<PrunedTodo> = todos.iter().map(|(todo, project)| PrunedTodo::new(todo, project, now)).collect();
+        if dry_run || pruned.is_empty() {
+            return Ok(pruned);
+        }
+
+        let ids: Vec<i64> = todos.iter().map(|(todo, _)| todo.id).collect();
+        self.db.prune_todos(&ids, policy.archive).await?;
+        for (todo, _) in &todos {
+            self.record(ChangeKind::TodoDeleted, Some(todo.project_id), Some(todo.id)).await;
+        }
+        Ok(pruned)
+    }
+
+    /// Count the todos pruning has moved to the archive table
+    pub async fn count_archived_todos(&self) -> Result<i64> {
+        self.db.count_archived_todos().await
+    }
+
+    /// List active projects with open todos that haven't been reviewed today,
+    /// least recently reviewed first
+    pub async fn list_projects_due_for_review(&self) -> Result<Vec<ProjectWithStats>> {
+        let mut projects: Vec<_> = self
+            .db
+            .list_projects(false)
+            .await?
+            .into_iter()
+            .filter(|p| p.active_todos() > 0 && !p.project.reviewed_today(self.timezone))
+            .collect();
+        projects.sort_by_key(|p| p.project.last_reviewed_at);
+        Ok(projects)
+    }
+
+    /// Record that a project has been reviewed
+    pub async fn mark_project_reviewed(&self, id: i64) -> Result<()> {
+        self.db.get_project(id).await?;
+        self.db.mark_project_reviewed(id).await?;
+        self.record(ChangeKind::ProjectUpdated, Some(id), None).await;
+        Ok(())
+    }
+
     /// Archive a project
     pub async fn archive_project(&self, id: i64) -> Result<()> {
         // Verify project exists
         self.db.get_project(id).await?;
-        self.db.archive_project(id).await
+        self.db.archive_project(id).await?;
+        self.record(ChangeKind::ProjectArchived, Some(id), None).await;
+        Ok(())
     }
 
     /// Unarchive a project
     pub async fn unarchive_project(&self, id: i64) -> Result<()> {
         // Verify project exists
         self.db.get_project(id).await?;
-        self.db.unarchive_project(id).await
+        self.db.unarchive_project(id).await?;
+        self.record(ChangeKind::ProjectUnarchived, Some(id), None).await;
+        Ok(())
     }
 
     /// Delete a project
     pub async fn delete_project(&self, id: i64) -> Result<()> {
         // Verify project exists
         self.db.get_project(id).await?;
-        self.db.delete_project(id).await
+        self.db.delete_project(id).await?;
+        self.record(ChangeKind::ProjectDeleted, Some(id), None).await;
+        Ok(())
+    }
+
+    /// Delete every project, archived ones too, and put the sample data back;
+    /// `docket server --demo` calls this on each reset
+    pub async fn reset_demo(&self) -> Result<()> {
+        for project in self.db.list_projects(true).await? {
+            self.delete_project(project.project.id).await?;
+        }
+        for workspace in self.db.list_workspaces().await? {
+            self.db.delete_workspace(workspace.id).await?;
+        }
+        let today = self.timezone.today();
+        for sample in demo::SAMPLE_PROJECTS {
+            let project = self.create_project_with_description(sample.name, sample.description).await?;
+            if let Some(name) = sample.workspace {
+                let workspace = match self.db.find_workspace_by_name(name).await? {
+                    Some(workspace) => workspace,
+                    None => self.db.create_workspace(name).await?,
+                };
+                self.db.update_project_workspace(project.id, Some(workspace.id)).await?;
+            }
+            for todo in sample.todos {
+                let created = self.create_todo(project.id, todo.description).await?;
+                if let Some(days) = todo.due_in_days {
+                    self.update_todo_due_date(created.id, Some(today + Duration::days(days))).await?;
+                }
+                if todo.estimate.is_some() {
+                    self.update_todo_estimate(created.id, todo.estimate).await?;
+                }
+                if todo.done {
+                    self.toggle_todo(created.id).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// How the TUI last showed a project's todos, or the defaults
+    pub async fn get_project_settings(&self, project_id: i64) -> Result<ProjectSettings> {
+        Ok(self.db.get_project_settings(project_id).await?.unwrap_or_default())
+    }
+
+    /// Remember how the TUI shows a project's todos
+    pub async fn save_project_settings(&self, project_id: i64, settings: &ProjectSettings) -> Result<()> {
+        self.db.save_project_settings(project_id, settings).await
     }
 
     // ===== Todo Operations =====
 
     /// Create a new todo with validation
     pub async fn create_todo(&self, project_id: i64, description: &str) -> Result<Todo> {
-        let description = description.trim();
-        if description.is_empty() {
-            bail!("Todo description cannot be empty");
-        }
-        if description.len() > 500 {
-            bail!("Todo description is too long (max 500 characters)");
-        }
+        self.create_todo_with_details(project_id, description, None).await
+    }
 
-        // Verify project exists
-        self.db.get_project(project_id).await?;
+    /// Create a new todo with its details filled in
+    pub async fn create_todo_with_details(
+        &self,
+        project_id: i64,
+        description: &str,
+        details: Option<&str>,
+    ) -> Result<Todo> {
+        let description = valid_todo_description(description)?;
+        let details = details.map(|d| d.trim()).filter(|d| !d.is_empty());
+        self.limits.check_details(details)?;
+        self.check_todo_room(project_id, 1).await?;
 
-        self.db.create_todo(project_id, description).await
+        let todo = self.db.create_todo(project_id, description, details).await?;
+        self.record(ChangeKind::TodoCreated, Some(project_id), Some(todo.id)).await;
+        Ok(todo)
     }
 
     /// List all todos for a project (completed and active)
@@ -120,21 +545,38 @@ impl DocketService {
         self.db.list_todos(project_id, false).await
     }
 
+    /// List a project's todos in `order`, completed ones too when `include_completed`
+    pub async fn list_sorted_todos(
+        &self,
+        project_id: i64,
+        include_completed: bool,
+        order: &TodoOrder,
+    ) -> Result<Vec<Todo>> {
+        self.db.list_sorted_todos(project_id, include_completed, order).await
+    }
+
     /// Toggle todo completion status
     pub async fn toggle_todo(&self, id: i64) -> Result<()> {
-        // Get the todo to check its completion status
-        let todo = self.db.get_todo(id).await?;
-
+        // Read the status under the write lock, so two toggles at once
+        // can't both see the todo open
+        let mut tx = self.db.begin().await?;
+        let todo = tx.get_todo(id).await?;
         if todo.is_completed() {
-            self.db.uncomplete_todo(id).await
+            tx.uncomplete_todo(id, self.restore_positions).await?;
         } else {
-            self.db.complete_todo(id).await
+            tx.complete_todo(id).await?;
         }
+        tx.commit().await?;
+        self.record(ChangeKind::TodoToggled, Some(todo.project_id), Some(id)).await;
+        Ok(())
     }
 
     /// Delete a todo
     pub async fn delete_todo(&self, id: i64) -> Result<()> {
-        self.db.delete_todo(id).await
+        let project_id = self.todo_project_id(id).await;
+        self.db.delete_todo(id).await?;
+        self.record(ChangeKind::TodoDeleted, project_id, Some(id)).await;
+        Ok(())
     }
 
     /// Get a todo by ID
@@ -142,40 +584,1772 @@ impl DocketService {
         self.db.get_todo(id).await
     }
 
+    /// Get a todo with its project, tags and neighbours in the open list
+    pub async fn get_todo_context(&self, id: i64) -> Result<TodoContext> {
+        let todo = self.db.get_todo(id).await?;
+        let project = self.db.get_project(todo.project_id).await?;
+        let open = self.db.list_todos(todo.project_id, false).await?;
+        let index = open.iter().position(|t| t.id == id);
+
+        Ok(TodoContext {
+            tags: todo.tags(),
+            position: PositionContext {
+                index: index.map(|i| i + 1),
+                open_todos: open.len(),
+                previous_id: index.and_then(|i| i.checked_sub(1)).map(|i| open[i].id),
+                next_id: index.and_then(|i| open.get(i + 1)).map(|t| t.id),
+            },
+            short_id: project.todo_short_id(todo.id),
+            todo,
+            project,
+        })
+    }
+
+    /// Find a todo by its short ID (`HOME-42`), `#42` or plain ID. A short
+    /// ID whose key isn't that of the todo's project is not found.
+    pub async fn find_todo_by_ref(&self, reference: &str) -> Result<Todo> {
+        let todo_ref = parse_todo_ref(reference).ok_or_else(|| {
+            DocketError::validation("reference", format!("'{}' is not a todo ID like HOME-42 or #42", reference))
+        })?;
+        let todo = self.db.get_todo(todo_ref.id).await?;
+        let project = self.db.get_project(todo.project_id).await?;
+        if !todo_ref.fits(&project) {
+            return Err(DocketError::not_found(format!(
+                "No todo {}; todo {} is {}",
+                todo_ref,
+                todo.id,
+                project.todo_short_id(todo.id)
+            )));
+        }
+        Ok(todo)
+    }
+
     /// Update a todo's details
     pub async fn update_todo_details(&self, id: i64, details: Option<&str>) -> Result<()> {
-        // Verify todo exists
-        self.db.get_todo(id).await?;
-
         // Trim and validate details if provided
         let details = details.map(|d| d.trim()).filter(|d| !d.is_empty());
+        self.limits.check_details(details)?;
 
-        self.db.update_todo_details(id, details).await
+        let mut tx = self.db.begin().await?;
+        let todo = tx.get_todo(id).await?;
+        tx.update_todo_details(id, details).await?;
+        tx.commit().await?;
+        self.record(ChangeKind::TodoUpdated, Some(todo.project_id), Some(id)).await;
+        Ok(())
     }
 
-    /// Update a todo's description
-    pub async fn update_todo(&self, id: i64, description: &str) -> Result<()> {
-        // Verify todo exists
-        self.db.get_todo(id).await?;
+    /// Check or uncheck item `index` (counting from 0) of the checklist in
+    /// a todo's details
+    pub async fn toggle_checklist_item(&self, id: i64, index: usize) -> Result<()> {
+        // A read-modify-write of the whole text: under the write lock, so
+        // two quick toggles can't undo each other
+        let mut tx = self.db.begin().await?;
+        let todo = tx.get_todo(id).await?;
+        let Some(details) = todo.details.as_deref().and_then(|d| toggle_checklist_item(d, index)) else {
+            return Err(DocketError::not_found(format!("Todo {} has no checklist item {}", id, index + 1)));
+        };
+        tx.update_todo_details(id, Some(&details)).await?;
+        tx.commit().await?;
+        self.record(ChangeKind::TodoUpdated, Some(todo.project_id), Some(id)).await;
+        Ok(())
+    }
 
-        let description = description.trim();
-        if description.is_empty() {
-            anyhow::bail!("Todo description cannot be empty");
-        }
-        if description.len() > 500 {
-            anyhow::bail!("Todo description is too long (max 500 characters)");
+    /// Update a todo's effort estimate
+    pub async fn update_todo_estimate(&self, id: i64, estimate: Option<i64>) -> Result<()> {
+        if estimate.is_some_and(|e| e < 0) {
+            return Err(DocketError::validation("estimate", "Estimate cannot be negative"));
         }
 
-        self.db.update_todo(id, description).await
+        let mut tx = self.db.begin().await?;
+        let todo = tx.get_todo(id).await?;
+        tx.update_todo_estimate(id, estimate).await?;
+        tx.commit().await?;
+        self.record(ChangeKind::TodoUpdated, Some(todo.project_id), Some(id)).await;
+        Ok(())
+    }
+
+    /// Set or clear a todo's due date
+    pub async fn update_todo_due_date(&self, id: i64, due_date: Option<NaiveDate>) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        let todo = tx.get_todo(id).await?;
+        tx.update_todo_due_date(id, due_date).await?;
+        tx.commit().await?;
+        self.record(ChangeKind::TodoUpdated, Some(todo.project_id), Some(id)).await;
+        Ok(())
+    }
+
+    /// Link a todo to a JIRA key or URL; an empty reference clears the link
+    pub async fn update_todo_external_ref(&self, id: i64, external_ref: Option<&str>) -> Result<()> {
+        let external_ref = external_ref.map(str::trim).filter(|r| !r.is_empty());
+        let mut tx = self.db.begin().await?;
+        let todo = tx.get_todo(id).await?;
+        tx.update_todo_external_ref(id, external_ref).await?;
+        tx.commit().await?;
+        self.record(ChangeKind::TodoUpdated, Some(todo.project_id), Some(id)).await;
+        Ok(())
+    }
+
+    /// List todos in active projects due between `from` and `to` (inclusive)
+    pub async fn list_todos_due_between(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Todo>> {
+        self.db.list_todos_due_between(from, to).await
+    }
+
+    /// Open todos of every active project, project by project
+    pub async fn list_open_todos(&self) -> Result<Vec<Todo>> {
+        self.db.list_open_todos().await
+    }
+
+    /// Open todos in active projects due on or before `date`, by due date
+    pub async fn list_open_todos_due_by(&self, date: NaiveDate) -> Result<Vec<Todo>> {
+        self.db.list_open_todos_due_by(date).await
+    }
+
+    /// Update a todo's description
+    pub async fn update_todo(&self, id: i64, description: &str) -> Result<()> {
+        let description = valid_todo_description(description)?;
+
+        let mut tx = self.db.begin().await?;
+        let todo = tx.get_todo(id).await?;
+        tx.update_todo(id, description).await?;
+        tx.commit().await?;
+        self.record(ChangeKind::TodoUpdated, Some(todo.project_id), Some(id)).await;
+        Ok(())
     }
 
     /// Move a todo up in the list (decrease position number)
     pub async fn move_todo_up(&self, id: i64) -> Result<()> {
-        self.db.reorder_todo(id, -1).await
+        self.db.reorder_todo(id, -1).await?;
+        self.publish_moved(id).await;
+        Ok(())
+    }
+
+    /// Defer a todo by moving it to the end of its project's active list
+    pub async fn defer_todo(&self, id: i64) -> Result<()> {
+        self.db.move_todo_to_end(id).await?;
+        self.publish_moved(id).await;
+        Ok(())
     }
 
     /// Move a todo down in the list (increase position number)
     pub async fn move_todo_down(&self, id: i64) -> Result<()> {
-        self.db.reorder_todo(id, 1).await
+        self.db.reorder_todo(id, 1).await?;
+        self.publish_moved(id).await;
+        Ok(())
+    }
+
+    /// Apply a partial update to a todo and return the result. Everything
+    /// is checked before the first write, and all fields change in one
+    /// transaction, so a bad field or failed write changes nothing.
+    pub async fn patch_todo(&self, id: i64, patch: &TodoPatch) -> Result<Todo> {
+        let mut tx = self.db.begin().await?;
+        let todo = tx.get_todo(id).await?;
+        let description = patch.description.as_deref().map(valid_todo_description).transpose()?;
+        if let Some(Some(estimate)) = patch.estimate
+            && estimate < 0
+        {
+            return Err(DocketError::validation("estimate", "Estimate cannot be negative"));
+        }
+        if let Some(project_id) = patch.project_id {
+            let project = tx.get_project(project_id).await?;
+            if project_id != todo.project_id {
+                let existing = tx.count_todos(project_id).await?;
+                self.limits.check_todos(&project.name, existing, 1)?;
+            }
+        }
+        if let Some(details) = &patch.details {
+            self.limits.check_details(details.as_deref().map(str::trim))?;
+        }
+        if patch.position.is_some_and(|p| p < 1) {
+            return Err(DocketError::validation("position", "Position must be 1 or more"));
+        }
+        if patch.position.is_some() && patch.completed.unwrap_or(todo.is_completed()) {
+            return Err(DocketError::validation("position", "Cannot reorder completed todos"));
+        }
+
+        let moved_from = patch.project_id.filter(|&p| p != todo.project_id).map(|p| (todo.project_id, p));
+        if let Some((_, project_id)) = moved_from {
+            tx.move_todo_to_project(id, project_id).await?;
+        }
+        let mut updated = false;
+        if let Some(description) = description
+            && description != todo.description
+        {
+            tx.update_todo(id, description).await?;
+            updated = true;
+        }
+        if let Some(details) = &patch.details {
+            tx.update_todo_details(id, details.as_deref().map(str::trim).filter(|d| !d.is_empty()))
+                .await?;
+            updated = true;
+        }
+        if let Some(estimate) = patch.estimate {
+            tx.update_todo_estimate(id, estimate).await?;
+            updated = true;
+        }
+        if let Some(due_date) = patch.due_date {
+            tx.update_todo_due_date(id, due_date).await?;
+            updated = true;
+        }
+        if let Some(external_ref) = &patch.external_ref {
+            tx.update_todo_external_ref(id, external_ref.as_deref().map(str::trim).filter(|r| !r.is_empty()))
+                .await?;
+            updated = true;
+        }
+        let toggled = patch.completed.filter(|&c| c != todo.is_completed());
+        match toggled {
+            Some(true) => tx.complete_todo(id).await?,
+            Some(false) => tx.uncomplete_todo(id, self.restore_positions).await?,
+            None => {}
+        }
+        if let Some(place) = patch.position {
+            tx.place_todo(id, place).await?;
+        }
+        let patched = tx.get_todo(id).await?;
+        tx.commit().await?;
+
+        let project_id = Some(patched.project_id);
+        if let Some((from, to)) = moved_from {
+            self.record(ChangeKind::TodoMoved, Some(from), Some(id)).await;
+            self.record(ChangeKind::TodoMoved, Some(to), Some(id)).await;
+        }
+        if updated {
+            self.record(ChangeKind::TodoUpdated, project_id, Some(id)).await;
+        }
+        if toggled.is_some() {
+            self.record(ChangeKind::TodoToggled, project_id, Some(id)).await;
+        }
+        if patch.position.is_some() {
+            self.record(ChangeKind::TodoMoved, project_id, Some(id)).await;
+        }
+        Ok(patched)
+    }
+
+    /// List a todo's earlier descriptions and details, most recently
+    /// replaced first
+    pub async fn todo_history(&self, todo_id: i64) -> Result<Vec<TodoVersion>> {
+        self.db.get_todo(todo_id).await?;
+        self.db.list_todo_history(todo_id).await
+    }
+
+    /// Get one of a todo's earlier versions
+    pub async fn get_todo_version(&self, todo_id: i64, version_id: i64) -> Result<TodoVersion> {
+        self.db.get_todo_version(todo_id, version_id).await
+    }
+
+    /// Put back a todo's description and details from an earlier version.
+    /// The text it replaces goes into the history in turn, so a restore
+    /// can itself be undone.
+    pub async fn restore_todo_version(&self, todo_id: i64, version_id: i64) -> Result<Todo> {
+        let version = self.db.get_todo_version(todo_id, version_id).await?;
+        let mut tx = self.db.begin().await?;
+        tx.update_todo_text(todo_id, &version.description, version.details.as_deref()).await?;
+        let todo = tx.get_todo(todo_id).await?;
+        tx.commit().await?;
+        self.record(ChangeKind::TodoUpdated, Some(todo.project_id), Some(todo_id)).await;
+        Ok(todo)
+    }
+
+    /// Link a todo to another, perhaps in another project. Two todos have
+    /// at most one link between them, whichever way it points.
+    pub async fn link_todos(&self, todo_id: i64, target_id: i64, kind: LinkKind) -> Result<TodoLink> {
+        if todo_id == target_id {
+            return Err(DocketError::validation("target_id", "A todo can't link to itself"));
+        }
+        let todo = self.db.get_todo(todo_id).await?;
+        let target = self.db.get_todo(target_id).await?;
+        if self.db.find_todo_link(todo_id, target_id).await?.is_some() {
+            return Err(DocketError::conflict(format!(
+                "'{}' and '{}' are already linked",
+                todo.description, target.description
+            )));
+        }
+        let link = self.db.create_todo_link(todo_id, target_id, kind).await?;
+        self.record(ChangeKind::TodoUpdated, Some(todo.project_id), Some(todo_id)).await;
+        Ok(link)
+    }
+
+    /// The todos a todo links to or is linked from, oldest link first
+    pub async fn related_todos(&self, todo_id: i64) -> Result<Vec<RelatedTodo>> {
+        self.db.get_todo(todo_id).await?;
+        let mut related = Vec::new();
+        for link in self.db.list_todo_links(todo_id).await? {
+            let other = if link.todo_id == todo_id { link.target_id } else { link.todo_id };
+            let todo = self.db.get_todo(other).await?;
+            let project_name = self.db.get_project(todo.project_id).await?.name;
+            related.push(RelatedTodo { link, todo, project_name });
+        }
+        Ok(related)
+    }
+
+    /// The todos a todo's details mention, in order, then those whose
+    /// details mention it. Mentions of todos that don't exist are left out.
+    pub async fn todo_mentions(&self, todo_id: i64) -> Result<Vec<TodoMention>> {
+        let todo = self.db.get_todo(todo_id).await?;
+        let mut mentioned = Vec::new();
+        for id in todo.details.as_deref().map(references::todo_ids).unwrap_or_default() {
+            match self.db.get_todo(id).await {
+                Ok(other) if other.id != todo_id => mentioned.push((other, false)),
+                Ok(_) | Err(DocketError::NotFound(_)) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let referencing = self.db.list_referencing_todos(todo_id).await?;
+        mentioned.extend(referencing.into_iter().map(|other| (other, true)));
+
+        let mut mentions = Vec::new();
+        for (todo, incoming) in mentioned {
+            let project_name = self.db.get_project(todo.project_id).await?.name;
+            mentions.push(TodoMention { todo, project_name, incoming });
+        }
+        Ok(mentions)
+    }
+
+    /// Remove a link between two todos
+    pub async fn unlink_todos(&self, link_id: i64) -> Result<()> {
+        let link = self.db.get_todo_link(link_id).await?;
+        self.db.delete_todo_link(link_id).await?;
+        let project_id = self.todo_project_id(link.todo_id).await;
+        self.record(ChangeKind::TodoUpdated, project_id, Some(link.todo_id)).await;
+        Ok(())
+    }
+
+    /// Project of a todo, if it (still) exists
+    async fn todo_project_id(&self, id: i64) -> Option<i64> {
+        self.db.get_todo(id).await.ok().map(|t| t.project_id)
+    }
+
+    /// Renumber a project's active todos 1..n, closing gaps left by
+    /// completions and deletes. Returns how many todos moved.
+    pub async fn normalize_positions(&self, project_id: i64) -> Result<usize> {
+        let moved = self.db.normalize_positions(project_id).await?;
+        if moved > 0 {
+            self.record(ChangeKind::TodoMoved, Some(project_id), None).await;
+        }
+        Ok(moved)
+    }
+
+    async fn publish_moved(&self, id: i64) {
+        let project_id = self.todo_project_id(id).await;
+        self.record(ChangeKind::TodoMoved, project_id, Some(id)).await;
+    }
+
+    // ===== Share Links =====
+
+    /// Create a signed link that shows a project read-only for `days` days
+    pub async fn share_project(&self, id: i64, days: i64) -> Result<ShareLink> {
+        if !(1..=MAX_SHARE_DAYS).contains(&days) {
+            return Err(DocketError::validation(
+                "days",
+                format!("Share links last between 1 and {} days", MAX_SHARE_DAYS),
+            ));
+        }
+        self.db.get_project(id).await?;
+        let expires_at = Utc::now() + Duration::days(days);
+        let token = share::sign(&self.db.secret("share").await?, id, expires_at);
+        Ok(ShareLink { url: format!("/share/{}", token), token, expires_at })
+    }
+
+    /// The project behind a share token; fails with `NotFound` when the
+    /// token is invalid and `Expired` when it has run out
+    pub async fn shared_project(&self, token: &str) -> Result<SharedProject> {
+        let id = share::verify(&self.db.secret("share").await?, token, Utc::now())?;
+        // A deleted project makes the link as dead as a forged one
+        let project = self.db.get_project(id).await.map_err(|_| share::ShareError::Invalid)?;
+        let todos = self.db.list_todos(id, true).await?;
+        Ok(SharedProject::new(&project, &todos))
+    }
+
+    /// Publish a project's status page at `/p/<slug>`, or take it down with `None`
+    pub async fn update_project_slug(&self, id: i64, slug: Option<&str>) -> Result<Project> {
+        self.db.get_project(id).await?;
+        let slug = slug.map(valid_slug).transpose()?;
+        if let Some(slug) = &slug
+            && let Some(existing) = self.db.find_project_by_slug(slug).await?
+            && existing.id != id
+        {
+            return Err(DocketError::conflict(format!(
+                "The slug '{}' is already used by project '{}'",
+                slug, existing.name
+            )));
+        }
+        self.db.update_project_slug(id, slug.as_deref()).await?;
+        self.record(ChangeKind::ProjectUpdated, Some(id), None).await;
+        self.db.get_project(id).await
+    }
+
+    /// The public status page published under `slug`: progress and open
+    /// todos. `None` if no active project uses the slug.
+    pub async fn public_project(&self, slug: &str) -> Result<Option<SharedProject>> {
+        let Some(project) = self.db.find_project_by_slug(&slug.to_lowercase()).await? else {
+            return Ok(None);
+        };
+        if project.is_archived() {
+            return Ok(None);
+        }
+        let todos = self.db.list_todos(project.id, true).await?;
+        let mut page = SharedProject::new(&project, &todos);
+        page.todos.retain(|t| !t.completed);
+        Ok(Some(page))
+    }
+
+    // ===== Workspaces =====
+
+    /// List workspaces by name
+    pub async fn list_workspaces(&self) -> Result<Vec<Workspace>> {
+        self.db.list_workspaces().await
+    }
+
+    /// Add a workspace to file projects under
+    pub async fn create_workspace(&self, name: &str) -> Result<Workspace> {
+        let name = valid_workspace_name(name)?;
+        self.ensure_workspace_name_available(name, None).await?;
+        self.limits.check_workspaces(self.db.list_workspaces().await?.len() as i64)?;
+        let workspace = self.db.create_workspace(name).await?;
+        self.record_workspace(ChangeKind::WorkspaceCreated, workspace.id).await;
+        Ok(workspace)
+    }
+
+    /// Rename a workspace
+    pub async fn rename_workspace(&self, id: i64, name: &str) -> Result<Workspace> {
+        self.db.get_workspace(id).await?;
+        let name = valid_workspace_name(name)?;
+        self.ensure_workspace_name_available(name, Some(id)).await?;
+        self.db.rename_workspace(id, name).await?;
+        self.record_workspace(ChangeKind::WorkspaceUpdated, id).await;
+        self.db.get_workspace(id).await
+    }
+
+    /// Delete a workspace; its projects stay, in no workspace
+    pub async fn delete_workspace(&self, id: i64) -> Result<()> {
+        self.db.get_workspace(id).await?;
+        let projects = self.db.list_projects(true).await?;
+        self.db.delete_workspace(id).await?;
+        self.record_workspace(ChangeKind::WorkspaceDeleted, id).await;
+        for p in projects.iter().filter(|p| p.project.workspace_id == Some(id)) {
+            self.record(ChangeKind::ProjectUpdated, Some(p.project.id), None).await;
+        }
+        Ok(())
+    }
+
+    /// File a project under a workspace, or under none with `None`
+    pub async fn update_project_workspace(&self, id: i64, workspace_id: Option<i64>) -> Result<Project> {
+        self.db.get_project(id).await?;
+        if let Some(workspace_id) = workspace_id {
+            self.db.get_workspace(workspace_id).await?;
+        }
+        self.db.update_project_workspace(id, workspace_id).await?;
+        self.record(ChangeKind::ProjectUpdated, Some(id), None).await;
+        self.db.get_project(id).await
+    }
+
+    /// Nest a project under `parent_id`, or move it to the top level with
+    /// `None`. A project can't go under itself or one of its sub-projects.
+    pub async fn update_project_parent(&self, id: i64, parent_id: Option<i64>) -> Result<Project> {
+        let project = self.db.get_project(id).await?;
+        if let Some(parent_id) = parent_id {
+            let path = self.project_path(parent_id).await?;
+            if path.iter().any(|p| p.id == id) {
+                return Err(DocketError::validation(
+                    "parent_id",
+                    format!("'{}' can't go under itself or one of its sub-projects", project.name),
+                ));
+            }
+        }
+        self.db.update_project_parent(id, parent_id).await?;
+        self.record(ChangeKind::ProjectUpdated, Some(id), None).await;
+        self.db.get_project(id).await
+    }
+
+    /// A project and the projects it is nested in, outermost first
+    pub async fn project_path(&self, id: i64) -> Result<Vec<Project>> {
+        let mut path = vec![self.db.get_project(id).await?];
+        while let Some(parent_id) = path[0].parent_id {
+            // Stop at a parent already seen, should the data hold a loop
+            if path.iter().any(|p| p.id == parent_id) {
+                break;
+            }
+            path.insert(0, self.db.get_project(parent_id).await?);
+        }
+        Ok(path)
+    }
+
+    async fn ensure_workspace_name_available(&self, name: &str, except: Option<i64>) -> Result<()> {
+        match self.db.find_workspace_by_name(name).await? {
+            Some(existing) if Some(existing.id) != except => {
+                Err(DocketError::conflict(format!("Workspace '{}' already exists", existing.name)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // ===== Templates =====
+
+    /// Save a reusable todo under `name`
+    pub async fn create_template(&self, name: &str, description: &str, details: Option<&str>) -> Result<TodoTemplate> {
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(DocketError::validation("name", "Template name must be a single word, e.g. deploy-checklist"));
+        }
+        if name.len() > 100 {
+            return Err(DocketError::validation("name", "Template name is too long (max 100 characters)"));
+        }
+        if self.db.find_template_by_name(name).await?.is_some() {
+            return Err(DocketError::conflict(format!("Template '{}' already exists", name)));
+        }
+        let description = valid_todo_description(description)?;
+        let details = details.map(|d| d.trim()).filter(|d| !d.is_empty());
+        self.limits.check_details(details)?;
+        self.db.create_template(name, description, details).await
+    }
+
+    /// List todo templates by name
+    pub async fn list_templates(&self) -> Result<Vec<TodoTemplate>> {
+        self.db.list_templates().await
+    }
+
+    /// Look up a template by name, ignoring case
+    pub async fn get_template(&self, name: &str) -> Result<TodoTemplate> {
+        match self.db.find_template_by_name(name.trim()).await? {
+            Some(template) => Ok(template),
+            None => Err(DocketError::not_found(format!("No template named '{}'", name.trim()))),
+        }
+    }
+
+    /// Delete a template by name
+    pub async fn delete_template(&self, name: &str) -> Result<()> {
+        let template = self.get_template(name).await?;
+        self.db.delete_template(template.id).await
+    }
+
+    /// Add a todo to a project from a template; `description` replaces the
+    /// template's own
+    pub async fn create_todo_from_template(
+        &self,
+        project_id: i64,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<Todo> {
+        let template = self.get_template(name).await?;
+        let description = description.unwrap_or(&template.description);
+        self.create_todo_with_details(project_id, description, template.details.as_deref())
+            .await
+    }
+
+    // ===== Batches =====
+
+    /// Run todo operations atomically, returning one result per operation.
+    /// Descriptions and projects are checked before anything is written.
+    pub async fn apply_batch(&self, mut ops: Vec<BatchOp>) -> Result<Vec<BatchResult>> {
+        if ops.len() > MAX_BATCH_OPS {
+            return Err(DocketError::validation(
+                "operations",
+                format!("A batch can hold at most {} operations", MAX_BATCH_OPS),
+            ));
+        }
+        // Room is checked against every todo the batch adds to a project
+        let mut adding: HashMap<i64, i64> = HashMap::new();
+        for (index, op) in ops.iter_mut().enumerate() {
+            if let BatchOp::Create { project_id, description, details } = op {
+                let added = adding.entry(*project_id).or_default();
+                *added += 1;
+                let checked = async {
+                    *description = valid_todo_description(description)?.to_string();
+                    *details = details.as_deref().map(str::trim).filter(|d| !d.is_empty()).map(str::to_string);
+                    self.limits.check_details(details.as_deref())?;
+                    self.check_todo_room(*project_id, *added).await
+                };
+                checked.await.map_err(|err| err.context(format!("Operation {} failed", index)))?;
+            }
+        }
+
+        let results = self.db.apply_batch(&ops, self.restore_positions).await?;
+        for result in &results {
+            let (kind, project_id, id) = match result {
+                BatchResult::Create { todo } => (ChangeKind::TodoCreated, todo.project_id, todo.id),
+                BatchResult::Toggle { todo } => (ChangeKind::TodoToggled, todo.project_id, todo.id),
+                BatchResult::Delete { id, project_id } => (ChangeKind::TodoDeleted, *project_id, *id),
+                BatchResult::Move { todo } => (ChangeKind::TodoMoved, todo.project_id, todo.id),
+            };
+            self.record(kind, Some(project_id), Some(id)).await;
+        }
+        Ok(results)
+    }
+
+    // ===== Idempotency Keys =====
+
+    /// Claim an idempotency key for a request identified by `request_hash`
+    pub async fn claim_idempotency_key(&self, key: &str, request_hash: &str) -> Result<IdempotentRequest> {
+        self.db.claim_idempotency_key(key, request_hash, IDEMPOTENCY_KEY_HOURS).await
+    }
+
+    /// Remember the response to replay for a claimed key
+    pub async fn finish_idempotency_key(
+        &self,
+        key: &str,
+        status: u16,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Result<()> {
+        self.db.finish_idempotency_key(key, status, content_type, body).await
+    }
+
+    /// Give up a claimed key without a response, so the request can be retried
+    pub async fn release_idempotency_key(&self, key: &str) -> Result<()> {
+        self.db.release_idempotency_key(key).await
+    }
+
+    // ===== Sync Operations =====
+
+    /// Everything created, updated or deleted after the `since` cursor
+    pub async fn changes_since(&self, since: i64) -> Result<ChangeSet> {
+        // Read the cursor first so rows changed mid-read are sent again next time
+        let cursor = self.db.current_sync_version().await?;
+        Ok(ChangeSet {
+            cursor,
+            projects: self.db.projects_changed_since(since).await?,
+            todos: self.db.todos_changed_since(since).await?,
+            deleted: self.db.tombstones_since(since).await?,
+        })
+    }
+
+    /// Version and time of the most recent write
+    pub async fn change_marker(&self) -> Result<ChangeMarker> {
+        Ok(ChangeMarker {
+            version: self.db.current_sync_version().await?,
+            modified_at: self.db.last_modified_at().await?,
+        })
+    }
+
+    /// Apply a batch of offline edits in order, in one transaction: a push
+    /// that fails part way changes nothing, so the client can simply send it
+    /// again. Each row is last-writer-wins: an edit older than the server's
+    /// copy is skipped and reported as a conflict.
+    pub async fn apply_changes(&self, changes: Vec<PushChange>) -> Result<PushResult> {
+        let mut push = Push::default();
+        let mut tx = self.db.begin().await?;
+        // Projects whose todo positions may now have gaps
+        let mut touched = BTreeSet::new();
+
+        for (index, change) in changes.into_iter().enumerate() {
+            let applied = match change {
+                PushChange::Project(change) => self.apply_project_change(&mut tx, change, &mut push).await,
+                PushChange::Todo(change) => self
+                    .apply_todo_change(&mut tx, change, &mut push)
+                    .await
+                    .map(|project_id| touched.extend(project_id)),
+                PushChange::Delete { entity: Entity::Project, id } => match tx.get_project(id).await {
+                    Ok(_) => tx.delete_project(id).await.map(|()| push.record(ChangeKind::ProjectDeleted, id, None)),
+                    Err(DocketError::NotFound(_)) => Ok(()),
+                    Err(err) => Err(err),
+                },
+                PushChange::Delete { entity: Entity::Todo, id } => match tx.get_todo(id).await {
+                    Ok(todo) => {
+                        touched.insert(todo.project_id);
+                        let deleted = tx.delete_todo(id).await;
+                        deleted.map(|()| push.record(ChangeKind::TodoDeleted, todo.project_id, Some(id)))
+                    }
+                    Err(DocketError::NotFound(_)) => Ok(()),
+                    Err(err) => Err(err),
+                },
+            };
+            applied.map_err(|err| err.context(format!("Failed to apply change {}", index)))?;
+        }
+
+        for project_id in touched {
+            if tx.normalize_positions(project_id).await? > 0 {
+                push.record(ChangeKind::TodoMoved, project_id, None);
+            }
+        }
+        tx.commit().await?;
+
+        for (kind, project_id, todo_id) in push.changes {
+            self.record(kind, Some(project_id), todo_id).await;
+        }
+        let mut result = push.result;
+        result.cursor = self.db.current_sync_version().await?;
+        Ok(result)
+    }
+
+    async fn apply_project_change(
+        &self,
+        tx: &mut WriteTransaction,
+        change: ProjectChange,
+        push: &mut Push,
+    ) -> Result<()> {
+        let project = match change.id {
+            Some(id) => {
+                let existing = match tx.get_project(id).await {
+                    Ok(existing) => existing,
+                    Err(DocketError::NotFound(_)) => {
+                        push.conflict(Entity::Project, id, ConflictReason::DeletedOnServer);
+                        return Ok(());
+                    }
+                    Err(err) => return Err(err),
+                };
+                if existing.updated_at > change.updated_at {
+                    push.conflict(Entity::Project, id, ConflictReason::NewerOnServer);
+                    return Ok(());
+                }
+                let name = valid_project_name(&change.name)?;
+                if existing.name != name {
+                    ensure_name_available_in(tx, name, Some(id)).await?;
+                    tx.update_project_name(id, name).await?;
+                    push.record(ChangeKind::ProjectUpdated, id, None);
+                }
+                existing
+            }
+            None => {
+                let name = valid_project_name(&change.name)?;
+                ensure_name_available_in(tx, name, None).await?;
+                self.limits.check_projects(tx.count_projects().await?)?;
+                let project = tx.create_project(name, None).await?;
+                push.record(ChangeKind::ProjectCreated, project.id, None);
+                if let Some(client_ref) = change.client_ref {
+                    push.result.created.insert(client_ref, project.id);
+                }
+                project
+            }
+        };
+
+        let id = project.id;
+        if project.description != change.description {
+            let description = change.description.as_deref().map(str::trim).filter(|d| !d.is_empty());
+            tx.update_project_description(id, description).await?;
+            push.record(ChangeKind::ProjectUpdated, id, None);
+        }
+        if project.target_date != change.target_date {
+            tx.update_project_target_date(id, change.target_date).await?;
+            push.record(ChangeKind::ProjectUpdated, id, None);
+        }
+        if project.is_archived() != change.archived {
+            if change.archived {
+                tx.archive_project(id).await?;
+                push.record(ChangeKind::ProjectArchived, id, None);
+            } else {
+                tx.unarchive_project(id).await?;
+                push.record(ChangeKind::ProjectUnarchived, id, None);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the todo's project, if the change was applied
+    async fn apply_todo_change(
+        &self,
+        tx: &mut WriteTransaction,
+        change: TodoChange,
+        push: &mut Push,
+    ) -> Result<Option<i64>> {
+        let todo = match change.id {
+            Some(id) => {
+                let existing = match tx.get_todo(id).await {
+                    Ok(existing) => existing,
+                    Err(DocketError::NotFound(_)) => {
+                        push.conflict(Entity::Todo, id, ConflictReason::DeletedOnServer);
+                        return Ok(None);
+                    }
+                    Err(err) => return Err(err),
+                };
+                if existing.updated_at > change.updated_at {
+                    push.conflict(Entity::Todo, id, ConflictReason::NewerOnServer);
+                    return Ok(None);
+                }
+                let description = valid_todo_description(&change.description)?;
+                if existing.description != description {
+                    tx.update_todo(id, description).await?;
+                    push.record(ChangeKind::TodoUpdated, existing.project_id, Some(id));
+                }
+                existing
+            }
+            None => {
+                let project_id = match (change.project_id, &change.project_ref) {
+                    (Some(id), _) => id,
+                    (None, Some(project_ref)) => *push
+                        .result
+                        .created
+                        .get(project_ref)
+                        .ok_or_else(|| {
+                            DocketError::validation("project_ref", format!("Unknown project ref '{}'", project_ref))
+                        })?,
+                    (None, None) => {
+                        return Err(DocketError::validation("project_id", "New todos need a project_id or project_ref"));
+                    }
+                };
+                let description = valid_todo_description(&change.description)?;
+                let project = tx.get_project(project_id).await?;
+                self.limits.check_todos(&project.name, tx.count_todos(project_id).await?, 1)?;
+                let todo = tx.create_todo(project_id, description).await?;
+                push.record(ChangeKind::TodoCreated, project_id, Some(todo.id));
+                if let Some(client_ref) = change.client_ref {
+                    push.result.created.insert(client_ref, todo.id);
+                }
+                todo
+            }
+        };
+
+        let (id, project_id) = (todo.id, todo.project_id);
+        let mut updated = false;
+        if todo.details != change.details {
+            let details = change.details.as_deref().map(str::trim).filter(|d| !d.is_empty());
+            self.limits.check_details(details)?;
+            tx.update_todo_details(id, details).await?;
+            updated = true;
+        }
+        if todo.estimate != change.estimate {
+            if change.estimate.is_some_and(|e| e < 0) {
+                return Err(DocketError::validation("estimate", "Estimate cannot be negative"));
+            }
+            tx.update_todo_estimate(id, change.estimate).await?;
+            updated = true;
+        }
+        if todo.due_date != change.due_date {
+            tx.update_todo_due_date(id, change.due_date).await?;
+            updated = true;
+        }
+        if todo.external_ref != change.external_ref {
+            let external_ref = change.external_ref.as_deref().map(str::trim).filter(|r| !r.is_empty());
+            tx.update_todo_external_ref(id, external_ref).await?;
+            updated = true;
+        }
+        if updated {
+            push.record(ChangeKind::TodoUpdated, project_id, Some(id));
+        }
+        if todo.is_completed() != change.completed {
+            if change.completed {
+                tx.complete_todo(id).await?;
+            } else {
+                tx.uncomplete_todo(id, self.restore_positions).await?;
+            }
+            push.record(ChangeKind::TodoToggled, project_id, Some(id));
+        }
+        Ok(Some(project_id))
+    }
+}
+
+/// What a sync push has done so far: the result for the client, and the
+/// changes to publish once its transaction commits
+#[derive(Default)]
+struct Push {
+    result: PushResult,
+    changes: Vec<(ChangeKind, i64, Option<i64>)>,
+}
+
+impl Push {
+    fn record(&mut self, kind: ChangeKind, project_id: i64, todo_id: Option<i64>) {
+        self.changes.push((kind, project_id, todo_id));
+    }
+
+    fn conflict(&mut self, entity: Entity, id: i64, reason: ConflictReason) {
+        self.result.conflicts.push(SyncConflict { entity, id, reason });
+    }
+}
+
+/// `DocketService::ensure_name_available` inside a write transaction
+async fn ensure_name_available_in(tx: &mut WriteTransaction, name: &str, exclude_id: Option<i64>) -> Result<()> {
+    match tx.find_project_by_name(name).await? {
+        Some(existing) if Some(existing.id) != exclude_id => {
+            let mut n = 2;
+            while tx.find_project_by_name(&format!("{} ({})", name, n)).await?.is_some() {
+                n += 1;
+            }
+            Err(name_taken(name, format!("{} ({})", name, n)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The `Conflict` for a project name already in use, suggesting a free one
+fn name_taken(name: &str, suggestion: String) -> DocketError {
+    DocketError::Conflict {
+        msg: format!("A project named '{}' already exists (try '{}')", name, suggestion),
+        suggestion: Some(suggestion),
+    }
+}
+
+/// A trimmed project name, or an error if it is empty or too long
+fn valid_project_name(name: &str) -> Result<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DocketError::validation("name", "Project name cannot be empty"));
+    }
+    if name.len() > 255 {
+        return Err(DocketError::validation("name", "Project name is too long (max 255 characters)"));
+    }
+    Ok(name)
+}
+
+/// A trimmed workspace name, or an error if it is empty or too long
+fn valid_workspace_name(name: &str) -> Result<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DocketError::validation("name", "Workspace name cannot be empty"));
+    }
+    if name.len() > 50 {
+        return Err(DocketError::validation("name", "Workspace name is too long (max 50 characters)"));
+    }
+    Ok(name)
+}
+
+/// A status page slug, lowercased: letters, digits and dashes, e.g. `docket-roadmap`
+fn valid_slug(slug: &str) -> Result<String> {
+    let slug = slug.trim().to_lowercase();
+    if slug.is_empty() || slug.len() > 64 {
+        return Err(DocketError::validation("slug", "Slug must be 1 to 64 characters"));
+    }
+    if !slug.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') || slug.starts_with('-') || slug.ends_with('-') {
+        return Err(DocketError::validation(
+            "slug",
+            format!("Slug '{}' may only use letters, digits and dashes, e.g. docket-roadmap", slug),
+        ));
+    }
+    Ok(slug)
+}
+
+/// A trimmed todo description, or an error if it is empty or too long
+fn valid_todo_description(description: &str) -> Result<&str> {
+    let description = description.trim();
+    if description.is_empty() {
+        return Err(DocketError::validation("description", "Todo description cannot be empty"));
+    }
+    if description.len() > 500 {
+        return Err(DocketError::validation("description", "Todo description is too long (max 500 characters)"));
+    }
+    Ok(description)
+}
+
+/// Create a service backed by a fresh database in the temp dir, deleted
+/// when the service is dropped, for tests here and in the binary (which
+/// turns on `test-util`)
+#[cfg(any(test, feature = "test-util"))]
+#[doc(hidden)]
+pub async fn test_service() -> DocketService {
+    DocketService::new(Database::open_temp().await.expect("Failed to open test database"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::db::TestDir;
+
+    #[tokio::test]
+    async fn test_project_names_unique_ignoring_case() {
+        let service = test_service().await;
+        let work = service.create_project("Work").await.unwrap();
+
+        let err = service.create_project("work").await.unwrap_err();
+        let DocketError::Conflict { suggestion, .. } = err else { panic!("expected a name conflict") };
+        assert_eq!(suggestion.as_deref(), Some("work (2)"));
+
+        // Renaming a project to a different casing of its own name is fine
+        service.update_project_name(work.id, "WORK").await.unwrap();
+
+        let home = service.create_project("Home").await.unwrap();
+        let err = service.update_project_name(home.id, "work").await.unwrap_err();
+        assert!(matches!(err, DocketError::Conflict { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_replace_project_is_idempotent() {
+        let service = test_service().await;
+        let project = service.create_project_with_description("Work", Some("  ")).await.unwrap();
+        assert_eq!(project.description, None);
+
+        let target = NaiveDate::from_ymd_opt(2026, 12, 1);
+        let replaced = service
+            .replace_project(project.id, "Work 2", Some("Quarterly"), target, true)
+            .await
+            .unwrap();
+        assert_eq!(replaced.name, "Work 2");
+        assert_eq!(replaced.description.as_deref(), Some("Quarterly"));
+        assert_eq!(replaced.target_date, target);
+        assert!(replaced.archived_at.is_some());
+
+        let again = service
+            .replace_project(project.id, "Work 2", Some("Quarterly"), target, true)
+            .await
+            .unwrap();
+        assert_eq!(again.version, replaced.version);
+        assert_eq!(again.archived_at, replaced.archived_at);
+    }
+
+    #[tokio::test]
+    async fn test_archive_candidates_require_all_todos_done() {
+        let service = test_service().await;
+        let project = service.create_project("Sprint 1").await.unwrap();
+        service.create_project("Empty").await.unwrap();
+        let todo = service.create_todo(project.id, "Ship it").await.unwrap();
+
+        assert!(service.list_archive_candidates(0).await.unwrap().is_empty());
+
+        service.toggle_todo(todo.id).await.unwrap();
+        let candidates = service.list_archive_candidates(0).await.unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].project.id, project.id);
+
+        // Recently completed, so not stale yet
+        assert!(service.list_archive_candidates(30).await.unwrap().is_empty());
+
+        service.archive_stale_projects(0).await.unwrap();
+        assert!(service.get_project(project.id).await.unwrap().is_archived());
+    }
+
+    #[tokio::test]
+    async fn test_prune_reports_then_archives_completed_todos() {
+        let service = test_service().await;
+        let project = service.create_project("Chores").await.unwrap();
+        let done = service.create_todo(project.id, "Mow the lawn").await.unwrap();
+        let open = service.create_todo(project.id, "Fix the gate").await.unwrap();
+        service.toggle_todo(done.id).await.unwrap();
+
+        // Completed just now, so a year's retention keeps it
+        let year = PrunePolicy { completed_days: 365, archive: true };
+        assert!(service.prune(&year, false).await.unwrap().is_empty());
+
+        let now = PrunePolicy { completed_days: 0, archive: true };
+        let report = service.prune(&now, true).await.unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!((report[0].todo_id, report[0].project.as_str()), (done.id, "Chores"));
+        assert!(service.get_todo(done.id).await.is_ok());
+
+        service.prune(&now, false).await.unwrap();
+        assert!(service.get_todo(done.id).await.is_err());
+        assert!(service.get_todo(open.id).await.is_ok());
+        assert_eq!(service.count_archived_todos().await.unwrap(), 1);
+        assert!(service.prune(&now, true).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_edits_keep_history_to_restore_from() {
+        let service = test_service().await;
+        let project = service.create_project("Home").await.unwrap();
+        let todo = service.create_todo(project.id, "Paint the fence").await.unwrap();
+        service.update_todo_details(todo.id, Some("- [ ] buy paint")).await.unwrap();
+        service.update_todo(todo.id, "Paint the shed").await.unwrap();
+        // Completing doesn't touch the text, so it adds nothing
+        service.toggle_todo(todo.id).await.unwrap();
+
+        let history = service.todo_history(todo.id).await.unwrap();
+        let texts: Vec<_> = history.iter().map(|v| (v.description.as_str(), v.details.as_deref())).collect();
+        assert_eq!(texts, [("Paint the fence", Some("- [ ] buy paint")), ("Paint the fence", None)]);
+
+        let restored = service.restore_todo_version(todo.id, history[1].id).await.unwrap();
+        assert_eq!((restored.description.as_str(), restored.details), ("Paint the fence", None));
+        assert_eq!(service.todo_history(todo.id).await.unwrap()[0].description, "Paint the shed");
+        assert!(service.restore_todo_version(todo.id + 1, history[0].id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_names_and_descriptions_ignore_accents() {
+        let service = test_service().await;
+        let project = service.create_project("Café Crème").await.unwrap();
+        service.create_project("cafe").await.unwrap();
+        assert_eq!(service.find_project_by_name("CAFE CREME").await.unwrap().unwrap().id, project.id);
+        // The exact spelling wins over one that only folds the same
+        assert_eq!(service.find_project_by_name("Cafe").await.unwrap().unwrap().name, "cafe");
+
+        for description in ["zèbre", "Éclair", "apple", "eclair", "Ärger"] {
+            service.create_todo(project.id, description).await.unwrap();
+        }
+        let order: TodoOrder = "description".parse().unwrap();
+        let todos = service.list_sorted_todos(project.id, false, &order).await.unwrap();
+        let descriptions: Vec<_> = todos.iter().map(|t| t.description.as_str()).collect();
+        // Ties keep their manual order
+        assert_eq!(descriptions, ["apple", "Ärger", "Éclair", "eclair", "zèbre"]);
+    }
+
+    #[tokio::test]
+    async fn test_estimates_sum_into_project_stats() {
+        let service = test_service().await;
+        let project = service.create_project("Estimated").await.unwrap();
+        let a = service.create_todo(project.id, "A").await.unwrap();
+        let b = service.create_todo(project.id, "B").await.unwrap();
+        service.create_todo(project.id, "No estimate").await.unwrap();
+
+        service.update_todo_estimate(a.id, Some(3)).await.unwrap();
+        service.update_todo_estimate(b.id, Some(5)).await.unwrap();
+        service.toggle_todo(a.id).await.unwrap();
+
+        let stats = service.get_project_with_stats(project.id).await.unwrap();
+        assert_eq!(stats.total_estimate, 8);
+        assert_eq!(stats.remaining_estimate, 5);
+        assert!(service.update_todo_estimate(b.id, Some(-1)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delta_sync_pull_and_push() {
+        let service = test_service().await;
+        let project = service.create_project("Synced").await.unwrap();
+        let todo = service.create_todo(project.id, "Old").await.unwrap();
+
+        let snapshot = service.changes_since(0).await.unwrap();
+        assert_eq!(snapshot.projects.len(), 1);
+        assert_eq!(snapshot.todos.len(), 1);
+
+        // Only rows touched after the cursor come back, deletions as tombstones
+        let doomed = service.create_todo(project.id, "Doomed").await.unwrap();
+        service.delete_todo(doomed.id).await.unwrap();
+        service.update_todo(todo.id, "Renamed").await.unwrap();
+        let delta = service.changes_since(snapshot.cursor).await.unwrap();
+        assert_eq!(delta.todos.iter().map(|t| t.id).collect::<Vec<_>>(), vec![todo.id]);
+        assert_eq!(delta.deleted.len(), 1);
+        assert_eq!(delta.deleted[0].entity_id, doomed.id);
+
+        let changes: Vec<PushChange> = serde_json::from_value(serde_json::json!([
+            { "type": "project", "ref": "p1", "name": "Offline", "updated_at": "2030-01-01T00:00:00Z" },
+            { "type": "todo", "ref": "t1", "project_ref": "p1", "description": "Made offline",
+              "completed": true, "updated_at": "2030-01-01T00:00:00Z" },
+            { "type": "todo", "id": todo.id, "description": "Stale edit", "updated_at": "2000-01-01T00:00:00Z" },
+        ]))
+        .unwrap();
+        let result = service.apply_changes(changes).await.unwrap();
+
+        let new_todo = service.get_todo(result.created["t1"]).await.unwrap();
+        assert_eq!(new_todo.project_id, result.created["p1"]);
+        assert!(new_todo.is_completed());
+        let reason = ConflictReason::NewerOnServer;
+        assert_eq!(result.conflicts, vec![SyncConflict { entity: Entity::Todo, id: todo.id, reason }]);
+        assert_eq!(service.get_todo(todo.id).await.unwrap().description, "Renamed");
+        assert!(result.cursor > delta.cursor);
+    }
+
+    #[tokio::test]
+    async fn test_groom_changes_all_or_nothing() {
+        let dir = TestDir::new();
+        let path = dir.database_path();
+        let service = DocketService::new(Database::new(&path).await.unwrap());
+        let home = service.create_project("Home").await.unwrap();
+        let gutters = service.create_todo(home.id, "Clean gutters").await.unwrap();
+        let long = service.create_todo(home.id, &"x".repeat(495)).await.unwrap();
+        service.mark_project_reviewed(home.id).await.unwrap();
+        let raw = sqlx::SqlitePool::connect(&format!("sqlite://{}", path.display())).await.unwrap();
+        sqlx::query("UPDATE todos SET created_at = ?")
+            .bind(Utc::now() - Duration::days(70))
+            .execute(&raw)
+            .await
+            .unwrap();
+        raw.close().await;
+
+        // The tag would take one description over the limit, so nothing changes
+        let rules = [GroomRule { open_days: 60, tag: Some("stale".to_string()), review: true }];
+        assert!(service.groom(&rules, false).await.is_err());
+        assert_eq!(service.get_todo(gutters.id).await.unwrap().description, "Clean gutters");
+        assert!(service.get_project(home.id).await.unwrap().last_reviewed_at.is_some());
+        assert_eq!(service.groom(&rules, true).await.unwrap().len(), 3);
+
+        service.update_todo(long.id, "Sweep the porch").await.unwrap();
+        service.groom(&rules, false).await.unwrap();
+        assert_eq!(service.get_todo(gutters.id).await.unwrap().description, "Clean gutters #stale");
+        assert_eq!(service.get_todo(long.id).await.unwrap().description, "Sweep the porch #stale");
+        assert!(service.get_project(home.id).await.unwrap().last_reviewed_at.is_none());
+        assert!(service.groom(&rules, true).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_push_changes_nothing() {
+        let service = test_service().await;
+        let project = service.create_project("Synced").await.unwrap();
+        let todo = service.create_todo(project.id, "Keep").await.unwrap();
+        let before = service.changes_since(0).await.unwrap().cursor;
+
+        // The last change is invalid, so the new project and the delete before it are undone
+        let changes: Vec<PushChange> = serde_json::from_value(serde_json::json!([
+            { "type": "project", "ref": "p1", "name": "Offline", "updated_at": "2030-01-01T00:00:00Z" },
+            { "type": "delete", "entity": "todo", "id": todo.id },
+            { "type": "todo", "project_ref": "p2", "description": "Lost", "updated_at": "2030-01-01T00:00:00Z" },
+        ]))
+        .unwrap();
+        let err = service.apply_changes(changes).await.unwrap_err();
+        assert!(err.to_string().contains("change 2"));
+        assert_eq!(service.list_all_projects().await.unwrap().len(), 1);
+        assert_eq!(service.get_todo(todo.id).await.unwrap().description, "Keep");
+        assert_eq!(service.changes_since(0).await.unwrap().cursor, before);
+
+        // Deleting what is already gone is not an error
+        let changes: Vec<PushChange> = serde_json::from_value(serde_json::json!([
+            { "type": "delete", "entity": "todo", "id": 999 },
+            { "type": "delete", "entity": "project", "id": 999 },
+        ]))
+        .unwrap();
+        assert!(service.apply_changes(changes).await.unwrap().conflicts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_project_stats_aggregates() {
+        let service = test_service().await;
+        let project = service.create_project("Stats").await.unwrap();
+        let first = service.create_todo(project.id, "First").await.unwrap();
+        let done = service.create_todo(project.id, "Done").await.unwrap();
+        service.create_todo(project.id, "Third").await.unwrap();
+        service.toggle_todo(done.id).await.unwrap();
+
+        let stats = service.get_project_stats(project.id, 12, service.timezone()).await.unwrap();
+        assert_eq!(stats.open_todos, 2);
+        assert_eq!(stats.completed_todos, 1);
+        assert_eq!(stats.oldest_open_todo.map(|t| t.id), Some(first.id));
+        assert_eq!(stats.oldest_open_todos.len(), 2);
+        assert_eq!(stats.completions_per_week.len(), 1);
+        assert_eq!(stats.completions_per_week[0].completed, 1);
+        assert!(stats.average_completion_hours.unwrap() < 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_totals_leave_out_archived_todos() {
+        let service = test_service().await;
+        let work = service.create_project("Work").await.unwrap();
+        let old = service.create_project("Old").await.unwrap();
+        service.create_todo(work.id, "Open").await.unwrap();
+        let done = service.create_todo(work.id, "Done").await.unwrap();
+        service.toggle_todo(done.id).await.unwrap();
+        service.create_todo(old.id, "Forgotten").await.unwrap();
+        service.archive_project(old.id).await.unwrap();
+
+        let totals = service.get_totals().await.unwrap();
+        assert_eq!(totals, Totals { active_projects: 1, archived_projects: 1, open_todos: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_digest_splits_overdue_and_due_soon() {
+        let service = test_service().await;
+        let project = service.create_project("Work").await.unwrap();
+        let today = service.timezone().today();
+        let late = service.create_todo(project.id, "Late").await.unwrap();
+        let soon = service.create_todo(project.id, "Soon").await.unwrap();
+        let later = service.create_todo(project.id, "Later").await.unwrap();
+        let done = service.create_todo(project.id, "Done").await.unwrap();
+        service.update_todo_due_date(late.id, Some(today - Duration::days(2))).await.unwrap();
+        service.update_todo_due_date(soon.id, Some(today + Duration::days(3))).await.unwrap();
+        service.update_todo_due_date(later.id, Some(today + Duration::days(30))).await.unwrap();
+        service.update_todo_due_date(done.id, Some(today)).await.unwrap();
+        service.toggle_todo(done.id).await.unwrap();
+
+        let digest = service.build_digest(7).await.unwrap();
+        let ids = |items: &[DigestTodo]| items.iter().map(|i| i.todo.id).collect::<Vec<_>>();
+        assert_eq!(ids(&digest.overdue), vec![late.id]);
+        assert_eq!(ids(&digest.due_soon), vec![soon.id]);
+        assert_eq!(digest.overdue[0].project, "Work");
+        assert!(digest.to_markdown().contains("- [ ] Soon (Work, due "));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reorders_keep_positions_distinct() {
+        let dir = TestDir::new();
+        let path = dir.database_path();
+        let service = DocketService::new(Database::new(&path).await.unwrap());
+        // A second pool on the same file stands in for another docket process
+        let other = DocketService::new(Database::new(&path).await.unwrap());
+        let project = service.create_project("Busy").await.unwrap();
+        let mut ids = Vec::new();
+        for i in 0..5 {
+            ids.push(service.create_todo(project.id, &format!("Todo {}", i)).await.unwrap().id);
+        }
+
+        let mut moves = Vec::new();
+        for round in 0..10 {
+            let (a, b) = (service.clone(), other.clone());
+            let (first, second) = (ids[round % 5], ids[(round + 2) % 5]);
+            moves.push(tokio::spawn(async move { a.move_todo_down(first).await }));
+            moves.push(tokio::spawn(async move { b.move_todo_up(second).await }));
+        }
+        for result in futures_util::future::join_all(moves).await {
+            result.unwrap().unwrap();
+        }
+
+        let mut positions: Vec<i64> = service
+            .list_active_todos(project.id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|t| t.position)
+            .collect();
+        positions.sort();
+        positions.dedup();
+        assert_eq!(positions.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_normalize_positions_after_uncomplete_and_reorder() {
+        let service = test_service().await;
+        let project = service.create_project("Gaps").await.unwrap();
+        let mut ids = Vec::new();
+        for name in ["a", "b", "c", "d"] {
+            ids.push(service.create_todo(project.id, name).await.unwrap().id);
+        }
+        let descriptions = |todos: Vec<Todo>| {
+            todos.iter().map(|t| (t.description.clone(), t.position)).collect::<Vec<_>>()
+        };
+
+        // b leaves a gap at 2, d swaps above c (into 3), then b comes back
+        // into 2 and pushes d and c down to 4 and 5
+        service.toggle_todo(ids[1]).await.unwrap();
+        service.move_todo_up(ids[3]).await.unwrap();
+        service.toggle_todo(ids[1]).await.unwrap();
+        assert_eq!(service.normalize_positions(project.id).await.unwrap(), 2);
+        let todos = service.list_active_todos(project.id).await.unwrap();
+        assert_eq!(
+            descriptions(todos),
+            vec![("a".into(), 1), ("b".into(), 2), ("d".into(), 3), ("c".into(), 4)]
+        );
+
+        // Moves after compaction still swap neighbours, and compacting again is a no-op
+        service.move_todo_up(ids[1]).await.unwrap();
+        assert_eq!(service.normalize_positions(project.id).await.unwrap(), 0);
+        let todos = service.list_active_todos(project.id).await.unwrap();
+        assert_eq!(
+            descriptions(todos),
+            vec![("b".into(), 1), ("a".into(), 2), ("d".into(), 3), ("c".into(), 4)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_uncomplete_restores_position() {
+        let service = test_service().await;
+        let project = service.create_project("Restore").await.unwrap();
+        let mut ids = Vec::new();
+        for name in ["a", "b", "c"] {
+            ids.push(service.create_todo(project.id, name).await.unwrap().id);
+        }
+        let order = |todos: Vec<Todo>| todos.into_iter().map(|t| t.description).collect::<Vec<_>>();
+
+        service.toggle_todo(ids[1]).await.unwrap();
+        service.create_todo(project.id, "d").await.unwrap();
+        service.toggle_todo(ids[1]).await.unwrap();
+        let todos = service.list_active_todos(project.id).await.unwrap();
+        assert_eq!(order(todos), ["a", "b", "c", "d"]);
+
+        // The old behaviour, when configured, appends instead
+        let service = service.with_restore_positions(false);
+        service.toggle_todo(ids[0]).await.unwrap();
+        service.toggle_todo(ids[0]).await.unwrap();
+        let todos = service.list_active_todos(project.id).await.unwrap();
+        assert_eq!(order(todos), ["b", "c", "d", "a"]);
+    }
+
+    #[tokio::test]
+    async fn test_patch_todo_updates_several_fields() {
+        let service = test_service().await;
+        let work = service.create_project("Work").await.unwrap();
+        let home = service.create_project("Home").await.unwrap();
+        service.create_todo(home.id, "Mow lawn").await.unwrap();
+        service.create_todo(home.id, "Fix tap").await.unwrap();
+        let todo = service.create_todo(work.id, "Call plumber").await.unwrap();
+        service.update_todo_details(todo.id, Some("Leak")).await.unwrap();
+
+        let patch: TodoPatch = serde_json::from_str(
+            r#"{"description": "Call the plumber", "details": null, "due_date": "2026-11-02",
+                "project_id": 2, "position": 1}"#,
+        )
+        .unwrap();
+        let patched = service.patch_todo(todo.id, &patch).await.unwrap();
+        assert_eq!(patched.description, "Call the plumber");
+        assert_eq!(patched.details, None);
+        assert_eq!(patched.due_date, NaiveDate::from_ymd_opt(2026, 11, 2));
+        assert_eq!(patched.project_id, home.id);
+        let todos = service.list_active_todos(home.id).await.unwrap();
+        let order: Vec<&str> = todos.iter().map(|t| t.description.as_str()).collect();
+        assert_eq!(order, ["Call the plumber", "Mow lawn", "Fix tap"]);
+
+        // A bad field rejects the whole patch
+        let patch = TodoPatch { description: Some("Done".to_string()), estimate: Some(Some(-1)), ..Default::default() };
+        assert!(service.patch_todo(todo.id, &patch).await.is_err());
+        assert_eq!(service.get_todo(todo.id).await.unwrap().description, "Call the plumber");
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_lifecycle() {
+        let service = test_service().await;
+        let claim = |hash| service.claim_idempotency_key("k1", hash);
+        assert_eq!(claim("a").await.unwrap(), IdempotentRequest::New);
+        assert_eq!(claim("a").await.unwrap(), IdempotentRequest::InProgress);
+
+        service.finish_idempotency_key("k1", 201, Some("application/json"), b"{}").await.unwrap();
+        assert_eq!(
+            claim("a").await.unwrap(),
+            IdempotentRequest::Done { status: 201, content_type: Some("application/json".to_string()), body: b"{}".to_vec() }
+        );
+        assert_eq!(claim("b").await.unwrap(), IdempotentRequest::Mismatch);
+
+        service.release_idempotency_key("k1").await.unwrap();
+        assert_eq!(claim("b").await.unwrap(), IdempotentRequest::New);
+    }
+
+    #[tokio::test]
+    async fn test_clone_project_reopens_completed_todos_on_request() {
+        let service = test_service().await;
+        let sprint = service.create_project_with_description("Sprint 14", Some("Two weeks")).await.unwrap();
+        let first = service.create_todo(sprint.id, "Standup notes").await.unwrap();
+        service.create_todo(sprint.id, "Demo").await.unwrap();
+        service.update_todo_estimate(first.id, Some(2)).await.unwrap();
+        let done = service.create_todo(sprint.id, "Retro").await.unwrap();
+        service.toggle_todo(done.id).await.unwrap();
+
+        let copy = service.clone_project(sprint.id, None, false).await.unwrap();
+        assert_eq!((copy.name.as_str(), copy.description.as_deref()), ("Sprint 14 (2)", Some("Two weeks")));
+        let todos = service.list_all_todos(copy.id).await.unwrap();
+        let copied: Vec<(&str, i64, Option<i64>)> =
+            todos.iter().map(|t| (t.description.as_str(), t.position, t.estimate)).collect();
+        assert_eq!(copied, vec![("Standup notes", 1, Some(2)), ("Demo", 2, None)]);
+
+        let next = service.clone_project(sprint.id, Some("Sprint 15"), true).await.unwrap();
+        let todos = service.list_all_todos(next.id).await.unwrap();
+        assert_eq!(todos.len(), 3);
+        assert!(todos.iter().all(|t| !t.is_completed()));
+        assert_eq!(todos[2].description, "Retro");
+        assert!(service.clone_project(sprint.id, Some("sprint 15"), false).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_uncommitted_writes_roll_back() {
+        let service = test_service().await;
+        let project = service.create_project("Home").await.unwrap();
+        let todo = service.create_todo(project.id, "Buy milk").await.unwrap();
+
+        let mut tx = service.db.begin().await.unwrap();
+        tx.update_todo(todo.id, "Buy oat milk").await.unwrap();
+        tx.complete_todo(todo.id).await.unwrap();
+        drop(tx);
+        let unchanged = service.get_todo(todo.id).await.unwrap();
+        assert_eq!((unchanged.description.as_str(), unchanged.is_completed()), ("Buy milk", false));
+
+        // The write lock is free again, and a committed toggle sticks
+        service.toggle_todo(todo.id).await.unwrap();
+        assert!(service.get_todo(todo.id).await.unwrap().is_completed());
+    }
+
+    #[tokio::test]
+    async fn test_share_links_show_the_project() {
+        let service = test_service().await;
+        let project = service.create_project("Client site").await.unwrap();
+        let todo = service.create_todo(project.id, "Mockups").await.unwrap();
+        service.update_todo_details(todo.id, Some("internal notes")).await.unwrap();
+
+        let link = service.share_project(project.id, 7).await.unwrap();
+        assert_eq!(link.url, format!("/share/{}", link.token));
+        let shared = service.shared_project(&link.token).await.unwrap();
+        assert_eq!((shared.name.as_str(), shared.todos.len()), ("Client site", 1));
+        assert!(!shared.to_html().contains("internal notes"));
+
+        assert!(service.share_project(project.id, 0).await.is_err());
+        service.delete_project(project.id).await.unwrap();
+        let err = service.shared_project(&link.token).await.unwrap_err();
+        assert!(matches!(err, DocketError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_public_pages_need_a_slug() {
+        let service = test_service().await;
+        let project = service.create_project("Roadmap").await.unwrap();
+        let other = service.create_project("Other").await.unwrap();
+        service.create_todo(project.id, "Plugin API").await.unwrap();
+        let done = service.create_todo(project.id, "Search").await.unwrap();
+        service.toggle_todo(done.id).await.unwrap();
+        assert_eq!(service.public_project("roadmap").await.unwrap(), None);
+
+        let published = service.update_project_slug(project.id, Some(" Roadmap ")).await.unwrap();
+        assert_eq!(published.slug.as_deref(), Some("roadmap"));
+        let page = service.public_project("roadmap").await.unwrap().unwrap();
+        assert_eq!((page.completed_todos, page.total_todos, page.todos.len()), (1, 2, 1));
+        assert_eq!(page.todos[0].description, "Plugin API");
+
+        assert!(service.update_project_slug(other.id, Some("roadmap")).await.is_err());
+        assert!(service.update_project_slug(other.id, Some("road map")).await.is_err());
+        service.archive_project(project.id).await.unwrap();
+        assert_eq!(service.public_project("roadmap").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_todos_from_templates() {
+        let service = test_service().await;
+        let project = service.create_project("Ops").await.unwrap();
+        service
+            .create_template("deploy", "Deploy the release", Some("- [ ] tag\n- [ ] announce"))
+            .await
+            .unwrap();
+        assert!(service.create_template("Deploy", "Again", None).await.is_err());
+        assert!(service.create_template("two words", "Nope", None).await.is_err());
+
+        let todo = service.create_todo_from_template(project.id, "DEPLOY", None).await.unwrap();
+        assert_eq!(todo.description, "Deploy the release");
+        assert_eq!(todo.checklist_progress(), Some((0, 2)));
+        let todo = service
+            .create_todo_from_template(project.id, "deploy", Some("Deploy v2"))
+            .await
+            .unwrap();
+        assert_eq!(todo.description, "Deploy v2");
+
+        service.delete_template("deploy").await.unwrap();
+        assert!(service.list_templates().await.unwrap().is_empty());
+        assert!(service.create_todo_from_template(project.id, "deploy", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_all_or_nothing() {
+        let service = test_service().await;
+        let project = service.create_project("Work").await.unwrap();
+        let first = service.create_todo(project.id, "First").await.unwrap();
+
+        let ops: Vec<BatchOp> = serde_json::from_str(&format!(
+            r#"[{{"op": "create", "project_id": {p}, "description": "Second"}},
+                {{"op": "move", "id": {id}, "direction": "down"}},
+                {{"op": "toggle", "id": {id}}}]"#,
+            p = project.id,
+            id = first.id
+        ))
+        .unwrap();
+        let results = service.apply_batch(ops).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(matches!(&results[1], BatchResult::Move { todo } if todo.position == 2));
+        assert!(matches!(&results[2], BatchResult::Toggle { todo } if todo.is_completed()));
+
+        let ops = vec![
+            BatchOp::Delete { id: first.id },
+            BatchOp::Toggle { id: 999 },
+        ];
+        let err = service.apply_batch(ops).await.unwrap_err();
+        assert!(err.to_string().starts_with("Operation 1 failed: Todo 999 not found"));
+        assert!(service.get_todo(first.id).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_audited() {
+        let service = test_service().await;
+        let project = service.create_project("Garden").await.unwrap();
+        let todo = audit::as_actor("alice".to_string(), service.create_todo(project.id, "Plant")).await.unwrap();
+        audit::as_actor("bob".to_string(), service.toggle_todo(todo.id)).await.unwrap();
+
+        let page = service.audit_log(&AuditQuery::default()).await.unwrap();
+        let seen: Vec<(&str, &str)> = page.entries.iter().map(|e| (e.actor.as_str(), e.action.as_str())).collect();
+        assert_eq!(seen, [("bob", "todo.toggled"), ("alice", "todo.created"), ("local", "project.created")]);
+
+        let query = AuditQuery { entity: Some("todo".to_string()), limit: Some(1), ..Default::default() };
+        let page = service.audit_log(&query).await.unwrap();
+        assert_eq!((page.total, page.entries.len()), (2, 1));
+        assert_eq!(page.entries[0].entity_id, Some(todo.id));
+
+        let later = Utc::now() + Duration::seconds(1);
+        let query = AuditQuery { actor: Some("alice".to_string()), since: Some(later), ..Default::default() };
+        assert_eq!(service.audit_log(&query).await.unwrap().total, 0);
+        let query = AuditQuery { until: Some(Utc::now()), ..Default::default() };
+        assert_eq!(service.audit_log(&query).await.unwrap().total, 3);
+    }
+
+    #[tokio::test]
+    async fn test_demo_reset_restores_sample_data() {
+        let service = test_service().await;
+        service.create_project("Graffiti").await.unwrap();
+        service.reset_demo().await.unwrap();
+        let projects = service.list_all_projects().await.unwrap();
+        let groceries = projects.iter().find(|p| p.project.name == "Groceries").unwrap();
+        service.delete_project(groceries.project.id).await.unwrap();
+        service.reset_demo().await.unwrap();
+
+        let projects = service.list_all_projects().await.unwrap();
+        let names: Vec<&str> = projects.iter().map(|p| p.project.name.as_str()).collect();
+        assert_eq!(names.len(), demo::SAMPLE_PROJECTS.len());
+        assert!(!names.contains(&"Graffiti"));
+        let kitchen = projects.iter().find(|p| p.project.name == "Kitchen Renovation").unwrap();
+        assert_eq!((kitchen.total_todos, kitchen.completed_todos), (6, 2));
+        assert!(kitchen.project.workspace_id.is_some());
+        assert_eq!(service.list_workspaces().await.unwrap().len(), 2);
+        let todos = service.list_active_todos(kitchen.project.id).await.unwrap();
+        assert!(todos.iter().any(|t| t.is_overdue(service.timezone())));
+    }
+
+    #[tokio::test]
+    async fn test_workspaces_group_projects() {
+        let service = test_service().await;
+        let work = service.create_workspace(" Work ").await.unwrap();
+        assert_eq!(work.name, "Work");
+        assert!(matches!(service.create_workspace("work").await, Err(DocketError::Conflict { .. })));
+        assert!(service.create_workspace("  ").await.is_err());
+
+        let project = service.create_project("Quarterly report").await.unwrap();
+        let filed = service.update_project_workspace(project.id, Some(work.id)).await.unwrap();
+        assert_eq!(filed.workspace_id, Some(work.id));
+        assert!(service.update_project_workspace(project.id, Some(work.id + 1)).await.is_err());
+        let copy = service.clone_project(project.id, None, false).await.unwrap();
+        assert_eq!(copy.workspace_id, Some(work.id));
+
+        let home = service.create_workspace("Home").await.unwrap();
+        assert!(service.rename_workspace(home.id, "WORK").await.is_err());
+        assert_eq!(service.rename_workspace(work.id, "Day job").await.unwrap().name, "Day job");
+        let names: Vec<String> = service.list_workspaces().await.unwrap().into_iter().map(|w| w.name).collect();
+        assert_eq!(names, ["Day job", "Home"]);
+
+        service.delete_workspace(work.id).await.unwrap();
+        assert_eq!(service.get_project(project.id).await.unwrap().workspace_id, None);
+        assert!(service.delete_workspace(work.id).await.is_err());
+
+        // Each change is published and audited like a project's
+        let query = AuditQuery { entity: Some("workspace".to_string()), ..Default::default() };
+        let entries = service.audit_log(&query).await.unwrap().entries;
+        let actions: Vec<&str> = entries.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["workspace.deleted", "workspace.updated", "workspace.created", "workspace.created"]);
+
+        let service = service.with_limits(Limits { max_workspaces: Some(1), ..Default::default() });
+        assert!(matches!(service.create_workspace("Garden").await, Err(DocketError::LimitReached(_))));
+    }
+
+    #[tokio::test]
+    async fn test_sub_projects_nest_without_loops() {
+        let service = test_service().await;
+        let home = service.create_project("Home").await.unwrap();
+        let kitchen = service.create_project("Kitchen").await.unwrap();
+        let cabinets = service.create_project("Cabinets").await.unwrap();
+        service.update_project_parent(kitchen.id, Some(home.id)).await.unwrap();
+        service.update_project_parent(cabinets.id, Some(kitchen.id)).await.unwrap();
+
+        let path: Vec<String> = service.project_path(cabinets.id).await.unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(path, ["Home", "Kitchen", "Cabinets"]);
+        assert!(service.update_project_parent(home.id, Some(cabinets.id)).await.is_err());
+        assert!(service.update_project_parent(home.id, Some(home.id)).await.is_err());
+
+        service.delete_project(kitchen.id).await.unwrap();
+        assert_eq!(service.get_project(cabinets.id).await.unwrap().parent_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_todos_link_across_projects() {
+        let service = test_service().await;
+        let home = service.create_project("Home").await.unwrap();
+        let work = service.create_project("Work").await.unwrap();
+        let paint = service.create_todo(home.id, "Buy paint").await.unwrap();
+        let again = service.create_todo(work.id, "Pick up paint").await.unwrap();
+        let ladder = service.create_todo(work.id, "Borrow ladder").await.unwrap();
+
+        service.link_todos(again.id, paint.id, LinkKind::Duplicates).await.unwrap();
+        let link = service.link_todos(paint.id, ladder.id, LinkKind::SeeAlso).await.unwrap();
+        let err = service.link_todos(paint.id, again.id, LinkKind::SeeAlso).await.unwrap_err();
+        assert!(matches!(err, DocketError::Conflict { .. }));
+        assert!(service.link_todos(paint.id, paint.id, LinkKind::SeeAlso).await.is_err());
+
+        let related = service.related_todos(paint.id).await.unwrap();
+        let labels: Vec<(&str, &str, &str)> =
+            related.iter().map(|r| (r.label(), r.todo.description.as_str(), r.project_name.as_str())).collect();
+        assert_eq!(labels, [("Duplicated by", "Pick up paint", "Work"), ("See also", "Borrow ladder", "Work")]);
+        assert_eq!(service.related_todos(again.id).await.unwrap()[0].label(), "Duplicates");
+
+        service.unlink_todos(link.id).await.unwrap();
+        service.delete_todo(again.id).await.unwrap();
+        assert!(service.related_todos(paint.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_details_mentions_are_backlinked() {
+        let service = test_service().await;
+        let home = service.create_project("Home").await.unwrap();
+        let paint = service.create_todo(home.id, "Buy paint").await.unwrap();
+        let ladder = service.create_todo(home.id, "Borrow ladder").await.unwrap();
+        let walls = service.create_todo(home.id, "Paint walls").await.unwrap();
+
+        let details = format!("Needs #{} and docket://todo/{}, not #9999", paint.id, ladder.id);
+        service.update_todo_details(walls.id, Some(&details)).await.unwrap();
+        let mentions = service.todo_mentions(walls.id).await.unwrap();
+        let seen: Vec<(&str, &str)> = mentions.iter().map(|m| (m.label(), m.todo.description.as_str())).collect();
+        assert_eq!(seen, [("Mentions", "Buy paint"), ("Mentions", "Borrow ladder")]);
+        let backlinks = service.todo_mentions(paint.id).await.unwrap();
+        assert_eq!((backlinks[0].label(), backlinks[0].todo.id), ("Referenced by", walls.id));
+        assert!(!service.get_todo(walls.id).await.unwrap().tags().contains(&paint.id.to_string()));
+
+        // Copies mention the same todos; editing the details drops old mentions
+        let copy = service.clone_project(home.id, Some("Cottage"), false).await.unwrap();
+        assert_eq!(service.todo_mentions(ladder.id).await.unwrap().len(), 2);
+        service.update_todo_details(walls.id, Some("Nothing to see")).await.unwrap();
+        let backlinks = service.todo_mentions(ladder.id).await.unwrap();
+        assert_eq!((backlinks.len(), backlinks[0].project_name.as_str()), (1, copy.name.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_limits_cap_projects_and_todos() {
+        let limits = Limits { max_projects: Some(2), max_todos_per_project: Some(2), ..Default::default() };
+        let service = test_service().await.with_limits(limits);
+        let home = service.create_project("Home").await.unwrap();
+        let work = service.create_project("Work").await.unwrap();
+        let err = service.create_project("Garden").await.unwrap_err();
+        assert!(matches!(err, DocketError::LimitReached(_)));
+
+        service.create_todo(home.id, "Paint").await.unwrap();
+        let ops = vec![
+            BatchOp::Create { project_id: home.id, description: "Tile".to_string(), details: None },
+            BatchOp::Create { project_id: home.id, description: "Sand".to_string(), details: None },
+        ];
+        assert!(matches!(service.apply_batch(ops).await, Err(DocketError::LimitReached(_))));
+        service.create_todo(home.id, "Tile").await.unwrap();
+        assert!(matches!(service.create_todo(home.id, "Sand").await, Err(DocketError::LimitReached(_))));
+
+        // Moving a todo in counts against the project it lands in
+        let report = service.create_todo(work.id, "Report").await.unwrap();
+        let patch = TodoPatch { project_id: Some(home.id), ..Default::default() };
+        assert!(matches!(service.patch_todo(report.id, &patch).await, Err(DocketError::LimitReached(_))));
+    }
+
+    #[tokio::test]
+    async fn test_inserts_run_without_returning_on_supported_sqlite() {
+        use crate::core::db::check_sqlite_version;
+        assert!(check_sqlite_version("3.24.0").is_err());
+        assert!(check_sqlite_version("3.25.0").is_ok());
+        assert!(check_sqlite_version("3.45.1").is_ok());
+
+        // Each insert reads its own row back, clones included
+        let service = test_service().await;
+        let home = service.create_project("Home").await.unwrap();
+        let paint = service.create_todo(home.id, "Paint").await.unwrap();
+        let tile = service.create_todo(home.id, "Tile").await.unwrap();
+        assert_eq!((paint.description.as_str(), tile.position), ("Paint", paint.position + 1));
+        let copy = service.clone_project(home.id, None, false).await.unwrap();
+        assert_ne!(copy.id, home.id);
+        assert!(matches!(service.clone_project(999, None, false).await, Err(DocketError::NotFound(_))));
+        let link = service.link_todos(paint.id, tile.id, LinkKind::SeeAlso).await.unwrap();
+        assert_eq!((link.todo_id, link.target_id), (paint.id, tile.id));
+    }
+
+    #[tokio::test]
+    async fn test_lists_read_from_their_own_pool() {
+        use crate::core::db::PoolSizes;
+        let dir = TestDir::new();
+        let path = dir.database_path();
+        for read in [0, 2] {
+            let db = Database::with_pool_sizes(&path, PoolSizes { write: 1, read }).await.unwrap();
+            let service = DocketService::new(db);
+            let project = service.create_project(&format!("Pool {}", read)).await.unwrap();
+            service.create_todo(project.id, "Paint").await.unwrap();
+            // A list right after a write sees it, whichever pool it reads from
+            let todos = service.list_active_todos(project.id).await.unwrap();
+            assert_eq!(todos.len(), 1);
+            assert!(service.list_all_projects().await.unwrap().iter().any(|p| p.project.id == project.id));
+        }
+
+        // A move checks the target's room inside its own transaction, so a
+        // single write connection is enough
+        let db = Database::with_pool_sizes(&path, PoolSizes { write: 1, read: 0 }).await.unwrap();
+        let limits = Limits { max_todos_per_project: Some(2), ..Default::default() };
+        let service = DocketService::new(db).with_limits(limits);
+        let home = service.create_project("Home").await.unwrap();
+        let work = service.create_project("Work").await.unwrap();
+        let paint = service.create_todo(work.id, "Paint").await.unwrap();
+        let patch = TodoPatch { project_id: Some(home.id), ..Default::default() };
+        let moved = tokio::time::timeout(std::time::Duration::from_secs(5), service.patch_todo(paint.id, &patch)).await;
+        assert_eq!(moved.expect("move waited on the write pool").unwrap().project_id, home.id);
+    }
+
+    #[tokio::test]
+    async fn test_project_stats_follow_writes_from_any_process() {
+        let dir = TestDir::new();
+        let path = dir.database_path();
+        let service = DocketService::new(Database::new(&path).await.unwrap());
+        let other = DocketService::new(Database::new(&path).await.unwrap());
+        let home = service.create_project("Home").await.unwrap();
+        let stats = |projects: Vec<ProjectWithStats>| projects.iter().map(|p| p.total_todos).collect::<Vec<_>>();
+        assert_eq!(stats(service.list_active_projects().await.unwrap()), [0]);
+
+        // Unchanged until the next write, then read afresh
+        assert_eq!(stats(service.list_active_projects().await.unwrap()), [0]);
+        service.create_todo(home.id, "Paint").await.unwrap();
+        assert_eq!(stats(service.list_active_projects().await.unwrap()), [1]);
+        other.create_todo(home.id, "Tile").await.unwrap();
+        assert_eq!(stats(service.list_active_projects().await.unwrap()), [2]);
+
+        service.archive_project(home.id).await.unwrap();
+        assert!(service.list_active_projects().await.unwrap().is_empty());
+        assert_eq!(stats(service.list_all_projects().await.unwrap()), [2]);
     }
 }
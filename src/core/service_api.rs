@@ -0,0 +1,588 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use super::audit::{AuditPage, AuditQuery};
+use super::batch::{BatchOp, BatchResult};
+use super::error::Result;
+use super::events::EventBus;
+use super::groom::{GroomChange, GroomRule};
+use super::models::{
+    LinkKind, Project, ProjectSettings, ProjectStats, ProjectWithStats, RelatedTodo, Todo,
+    TodoContext, TodoLink, TodoMention, TodoPatch, TodoTemplate, TodoVersion, Totals, Workspace,
+};
+use super::prune::{PrunePolicy, PrunedTodo};
+use super::service::DocketService;
+use super::share::{ShareLink, SharedProject};
+use super::sort::TodoOrder;
+use super::sync::{ChangeMarker, ChangeSet, PushChange, PushResult};
+use super::time::DisplayZone;
+
+/// Everything the TUI and the web handlers ask of docket. `DocketService`
+/// implements it against the local database; other backends (a client for
+/// a remote server, a read-only snapshot, a mock in tests) can stand in
+/// for it behind an `Arc<dyn DocketServiceApi>`.
+#[async_trait]
+pub trait DocketServiceApi: Send + Sync {
+    /// Time zone used to decide what "today" is
+    fn timezone(&self) -> DisplayZone;
+
+    /// Change notifications for everything this service modifies
+    fn events(&self) -> &EventBus;
+
+    /// Messages for the user about work done in the background, such as
+    /// edits made offline being sent to a server; each is returned once
+    fn take_notices(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Create a new project with validation
+    async fn create_project(&self, name: &str) -> Result<Project>;
+
+    /// Create a new project with a description
+    async fn create_project_with_description(&self, name: &str, description: Option<&str>) -> Result<Project>;
+
+    /// Copy a project with its open todos, and with `include_completed` its
+    /// completed ones too, reopened. Without a name the copy is called
+    /// `<name> (2)` (or the next free number).
+    async fn clone_project(&self, id: i64, name: Option<&str>, include_completed: bool) -> Result<Project>;
+
+    /// Get a project by ID
+    async fn get_project(&self, id: i64) -> Result<Project>;
+
+    /// Get a project by ID together with its todo statistics
+    async fn get_project_with_stats(&self, id: i64) -> Result<ProjectWithStats>;
+
+    /// Get aggregate statistics for a project, covering the last `weeks` weeks
+    /// of completions, with weeks starting on Monday in `zone`
+    async fn get_project_stats(&self, id: i64, weeks: i64, zone: DisplayZone) -> Result<ProjectStats>;
+
+    /// Count active and archived projects, and the open todos of active ones
+    async fn get_totals(&self) -> Result<Totals>;
+
+    /// Update a project's description
+    async fn update_project_description(&self, id: i64, description: Option<&str>) -> Result<()>;
+
+    /// Set or clear a project's target completion date
+    async fn update_project_target_date(&self, id: i64, target_date: Option<NaiveDate>) -> Result<()>;
+
+    /// Update a project's name
+    async fn update_project_name(&self, id: i64, name: &str) -> Result<()>;
+
+    /// Set every editable field of a project at once. Repeating the same
+    /// update changes nothing, which makes it safe to retry.
+    async fn replace_project(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+        target_date: Option<NaiveDate>,
+        archived: bool,
+    ) -> Result<Project>;
+
+    /// Suggest a free variant of `name` by appending " (2)", " (3)", ...
+    async fn suggest_project_name(&self, name: &str) -> Result<String>;
+
+    /// List all active projects
+    async fn list_active_projects(&self) -> Result<Vec<ProjectWithStats>>;
+
+    /// List all projects including archived
+    async fn list_all_projects(&self) -> Result<Vec<ProjectWithStats>>;
+
+    /// Archive every project suggested by `list_archive_candidates`, returning them
+    async fn archive_stale_projects(&self, stale_after_days: i64) -> Result<Vec<ProjectWithStats>>;
+
+    /// Apply grooming rules to the open todos of active projects, or with
+    /// `dry_run` only report what they would change
+    async fn groom(&self, rules: &[GroomRule], dry_run: bool) -> Result<Vec<GroomChange>>;
+
+    /// Remove todos completed longer ago than `policy` keeps them, or with
+    /// `dry_run` only report which ones it would remove
+    async fn prune(&self, policy: &PrunePolicy, dry_run: bool) -> Result<Vec<PrunedTodo>>;
+
+    /// List active projects with open todos that haven't been reviewed today,
+    /// least recently reviewed first
+    async fn list_projects_due_for_review(&self) -> Result<Vec<ProjectWithStats>>;
+
+    /// Record that a project has been reviewed
+    async fn mark_project_reviewed(&self, id: i64) -> Result<()>;
+
+    /// Archive a project
+    async fn archive_project(&self, id: i64) -> Result<()>;
+
+    /// Unarchive a project
+    async fn unarchive_project(&self, id: i64) -> Result<()>;
+
+    /// Delete a project
+    async fn delete_project(&self, id: i64) -> Result<()>;
+
+    /// How the TUI last showed a project's todos, or the defaults
+    async fn get_project_settings(&self, project_id: i64) -> Result<ProjectSettings>;
+
+    /// Remember how the TUI shows a project's todos
+    async fn save_project_settings(&self, project_id: i64, settings: &ProjectSettings) -> Result<()>;
+
+    /// Create a new todo with validation
+    async fn create_todo(&self, project_id: i64, description: &str) -> Result<Todo>;
+
+    /// Create a new todo with its details filled in
+    async fn create_todo_with_details(
+        &self,
+        project_id: i64,
+        description: &str,
+        details: Option<&str>,
+    ) -> Result<Todo>;
+
+    /// List all todos for a project (completed and active)
+    async fn list_all_todos(&self, project_id: i64) -> Result<Vec<Todo>>;
+
+    /// List only active (incomplete) todos for a project
+    async fn list_active_todos(&self, project_id: i64) -> Result<Vec<Todo>>;
+
+    /// List a project's todos in `order`, completed ones too when `include_completed`
+    async fn list_sorted_todos(&self, project_id: i64, include_completed: bool, order: &TodoOrder) -> Result<Vec<Todo>>;
+
+    /// Toggle todo completion status
+    async fn toggle_todo(&self, id: i64) -> Result<()>;
+
+    /// Delete a todo
+    async fn delete_todo(&self, id: i64) -> Result<()>;
+
+    /// Get a todo by ID
+    async fn get_todo(&self, id: i64) -> Result<Todo>;
+
+    /// Get a todo with its project, tags and neighbours in the open list
+    async fn get_todo_context(&self, id: i64) -> Result<TodoContext>;
+
+    /// Update a todo's details
+    async fn update_todo_details(&self, id: i64, details: Option<&str>) -> Result<()>;
+
+    /// Check or uncheck item `index` (counting from 0) of the checklist in
+    /// a todo's details
+    async fn toggle_checklist_item(&self, id: i64, index: usize) -> Result<()>;
+
+    /// Update a todo's effort estimate
+    async fn update_todo_estimate(&self, id: i64, estimate: Option<i64>) -> Result<()>;
+
+    /// Set or clear a todo's due date
+    async fn update_todo_due_date(&self, id: i64, due_date: Option<NaiveDate>) -> Result<()>;
+
+    /// Link a todo to a JIRA key or URL; an empty reference clears the link
+    async fn update_todo_external_ref(&self, id: i64, external_ref: Option<&str>) -> Result<()>;
+
+    /// List todos in active projects due between `from` and `to` (inclusive)
+    async fn list_todos_due_between(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Todo>>;
+
+    /// Open todos of every active project, project by project
+    async fn list_open_todos(&self) -> Result<Vec<Todo>>;
+
+    /// Open todos in active projects due on or before `date`, by due date
+    async fn list_open_todos_due_by(&self, date: NaiveDate) -> Result<Vec<Todo>>;
+
+    /// Update a todo's description
+    async fn update_todo(&self, id: i64, description: &str) -> Result<()>;
+
+    /// Move a todo up in the list (decrease position number)
+    async fn move_todo_up(&self, id: i64) -> Result<()>;
+
+    /// Defer a todo by moving it to the end of its project's active list
+    async fn defer_todo(&self, id: i64) -> Result<()>;
+
+    /// Move a todo down in the list (increase position number)
+    async fn move_todo_down(&self, id: i64) -> Result<()>;
+
+    /// Apply a partial update to a todo and return the result. Everything
+    /// is checked before the first write, and all fields change in one
+    /// transaction, so a bad field or failed write changes nothing.
+    async fn patch_todo(&self, id: i64, patch: &TodoPatch) -> Result<Todo>;
+
+    /// List a todo's earlier descriptions and details, most recently
+    /// replaced first
+    async fn todo_history(&self, todo_id: i64) -> Result<Vec<TodoVersion>>;
+
+    /// Put back a todo's description and details from an earlier version,
+    /// keeping the replaced text in the history
+    async fn restore_todo_version(&self, todo_id: i64, version_id: i64) -> Result<Todo>;
+
+    /// Link a todo to another, perhaps in another project
+    async fn link_todos(&self, todo_id: i64, target_id: i64, kind: LinkKind) -> Result<TodoLink>;
+
+    /// The todos a todo links to or is linked from, oldest link first
+    async fn related_todos(&self, todo_id: i64) -> Result<Vec<RelatedTodo>>;
+
+    /// Remove a link between two todos
+    async fn unlink_todos(&self, link_id: i64) -> Result<()>;
+
+    /// The todos a todo's details mention as `#123` or `docket://todo/123`,
+    /// then those whose details mention it
+    async fn todo_mentions(&self, todo_id: i64) -> Result<Vec<TodoMention>>;
+
+    /// Renumber a project's active todos 1..n, closing gaps left by
+    /// completions and deletes. Returns how many todos moved.
+    async fn normalize_positions(&self, project_id: i64) -> Result<usize>;
+
+    /// Create a signed link that shows a project read-only for `days` days
+    async fn share_project(&self, id: i64, days: i64) -> Result<ShareLink>;
+
+    /// The project behind a share token; fails with `NotFound` when the
+    /// token is invalid and `Expired` when it has run out
+    async fn shared_project(&self, token: &str) -> Result<SharedProject>;
+
+    /// Publish a project's status page at `/p/<slug>`, or take it down with `None`
+    async fn update_project_slug(&self, id: i64, slug: Option<&str>) -> Result<Project>;
+
+    /// The public status page published under `slug`: progress and open
+    /// todos. `None` if no active project uses the slug.
+    async fn public_project(&self, slug: &str) -> Result<Option<SharedProject>>;
+
+    /// List workspaces by name
+    async fn list_workspaces(&self) -> Result<Vec<Workspace>>;
+
+    /// Add a workspace to file projects under
+    async fn create_workspace(&self, name: &str) -> Result<Workspace>;
+
+    /// Rename a workspace
+    async fn rename_workspace(&self, id: i64, name: &str) -> Result<Workspace>;
+
+    /// Delete a workspace; its projects stay, in no workspace
+    async fn delete_workspace(&self, id: i64) -> Result<()>;
+
+    /// File a project under a workspace, or under none with `None`
+    async fn update_project_workspace(&self, id: i64, workspace_id: Option<i64>) -> Result<Project>;
+
+    /// Nest a project under `parent_id`, or move it to the top level with `None`
+    async fn update_project_parent(&self, id: i64, parent_id: Option<i64>) -> Result<Project>;
+
+    /// A project and the projects it is nested in, outermost first
+    async fn project_path(&self, id: i64) -> Result<Vec<Project>>;
+
+    /// List todo templates by name
+    async fn list_templates(&self) -> Result<Vec<TodoTemplate>>;
+
+    /// Add a todo to a project from a template; `description` replaces the
+    /// template's own
+    async fn create_todo_from_template(&self, project_id: i64, name: &str, description: Option<&str>) -> Result<Todo>;
+
+    /// Run todo operations atomically, returning one result per operation.
+    /// Descriptions and projects are checked before anything is written.
+    async fn apply_batch(&self, ops: Vec<BatchOp>) -> Result<Vec<BatchResult>>;
+
+    /// A page of the audit log, newest first
+    async fn audit_log(&self, query: &AuditQuery) -> Result<AuditPage>;
+
+    /// Everything created, updated or deleted after the `since` cursor
+    async fn changes_since(&self, since: i64) -> Result<ChangeSet>;
+
+    /// Version and time of the most recent write
+    async fn change_marker(&self) -> Result<ChangeMarker>;
+
+    /// Apply a batch of offline edits in order. Each row is last-writer-wins:
+    /// an edit older than the server's copy is skipped and reported as a conflict.
+    async fn apply_changes(&self, changes: Vec<PushChange>) -> Result<PushResult>;
+}
+
+#[async_trait]
+impl DocketServiceApi for DocketService {
+    fn timezone(&self) -> DisplayZone {
+        DocketService::timezone(self)
+    }
+
+    fn events(&self) -> &EventBus {
+        DocketService::events(self)
+    }
+
+    async fn create_project(&self, name: &str) -> Result<Project> {
+        DocketService::create_project(self, name).await
+    }
+
+    async fn create_project_with_description(&self, name: &str, description: Option<&str>) -> Result<Project> {
+        DocketService::create_project_with_description(self, name, description).await
+    }
+
+    async fn clone_project(&self, id: i64, name: Option<&str>, include_completed: bool) -> Result<Project> {
+        DocketService::clone_project(self, id, name, include_completed).await
+    }
+
+    async fn get_project(&self, id: i64) -> Result<Project> {
+        DocketService::get_project(self, id).await
+    }
+
+    async fn get_project_with_stats(&self, id: i64) -> Result<ProjectWithStats> {
+        DocketService::get_project_with_stats(self, id).await
+    }
+
+    async fn get_project_stats(&self, id: i64, weeks: i64, zone: DisplayZone) -> Result<ProjectStats> {
+        DocketService::get_project_stats(self, id, weeks, zone).await
+    }
+
+    async fn get_totals(&self) -> Result<Totals> {
+        DocketService::get_totals(self).await
+    }
+
+    async fn update_project_description(&self, id: i64, description: Option<&str>) -> Result<()> {
+        DocketService::update_project_description(self, id, description).await
+    }
+
+    async fn update_project_target_date(&self, id: i64, target_date: Option<NaiveDate>) -> Result<()> {
+        DocketService::update_project_target_date(self, id, target_date).await
+    }
+
+    async fn update_project_name(&self, id: i64, name: &str) -> Result<()> {
+        DocketService::update_project_name(self, id, name).await
+    }
+
+    async fn replace_project(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+        target_date: Option<NaiveDate>,
+        archived: bool,
+    ) -> Result<Project> {
+        DocketService::replace_project(self, id, name, description, target_date, archived).await
+    }
+
+    async fn suggest_project_name(&self, name: &str) -> Result<String> {
+        DocketService::suggest_project_name(self, name).await
+    }
+
+    async fn list_active_projects(&self) -> Result<Vec<ProjectWithStats>> {
+        DocketService::list_active_projects(self).await
+    }
+
+    async fn list_all_projects(&self) -> Result<Vec<ProjectWithStats>> {
+        DocketService::list_all_projects(self).await
+    }
+
+    async fn archive_stale_projects(&self, stale_after_days: i64) -> Result<Vec<ProjectWithStats>> {
+        DocketService::archive_stale_projects(self, stale_after_days).await
+    }
+
+    async fn groom(&self, rules: &[GroomRule], dry_run: bool) -> Result<Vec<GroomChange>> {
+        DocketService::groom(self, rules, dry_run).await
+    }
+
+    async fn prune(&self, policy: &PrunePolicy, dry_run: bool) -> Result<Vec<PrunedTodo>> {
+        DocketService::prune(self, policy, dry_run).await
+    }
+
+    async fn list_projects_due_for_review(&self) -> Result<Vec<ProjectWithStats>> {
+        DocketService::list_projects_due_for_review(self).await
+    }
+
+    async fn mark_project_reviewed(&self, id: i64) -> Result<()> {
+        DocketService::mark_project_reviewed(self, id).await
+    }
+
+    async fn archive_project(&self, id: i64) -> Result<()> {
+        DocketService::archive_project(self, id).await
+    }
+
+    async fn unarchive_project(&self, id: i64) -> Result<()> {
+        DocketService::unarchive_project(self, id).await
+    }
+
+    async fn delete_project(&self, id: i64) -> Result<()> {
+        DocketService::delete_project(self, id).await
+    }
+
+    async fn get_project_settings(&self, project_id: i64) -> Result<ProjectSettings> {
+        DocketService::get_project_settings(self, project_id).await
+    }
+
+    async fn save_project_settings(&self, project_id: i64, settings: &ProjectSettings) -> Result<()> {
+        DocketService::save_project_settings(self, project_id, settings).await
+    }
+
+    async fn create_todo(&self, project_id: i64, description: &str) -> Result<Todo> {
+        DocketService::create_todo(self, project_id, description).await
+    }
+
+    async fn create_todo_with_details(
+        &self,
+        project_id: i64,
+        description: &str,
+        details: Option<&str>,
+    ) -> Result<Todo> {
+        DocketService::create_todo_with_details(self, project_id, description, details).await
+    }
+
+    async fn list_all_todos(&self, project_id: i64) -> Result<Vec<Todo>> {
+        DocketService::list_all_todos(self, project_id).await
+    }
+
+    async fn list_active_todos(&self, project_id: i64) -> Result<Vec<Todo>> {
+        DocketService::list_active_todos(self, project_id).await
+    }
+
+    async fn list_sorted_todos(&self, project_id: i64, include_completed: bool, order: &TodoOrder) -> Result<Vec<Todo>> {
+        DocketService::list_sorted_todos(self, project_id, include_completed, order).await
+    }
+
+    async fn toggle_todo(&self, id: i64) -> Result<()> {
+        DocketService::toggle_todo(self, id).await
+    }
+
+    async fn delete_todo(&self, id: i64) -> Result<()> {
+        DocketService::delete_todo(self, id).await
+    }
+
+    async fn get_todo(&self, id: i64) -> Result<Todo> {
+        DocketService::get_todo(self, id).await
+    }
+
+    async fn get_todo_context(&self, id: i64) -> Result<TodoContext> {
+        DocketService::get_todo_context(self, id).await
+    }
+
+    async fn update_todo_details(&self, id: i64, details: Option<&str>) -> Result<()> {
+        DocketService::update_todo_details(self, id, details).await
+    }
+
+    async fn toggle_checklist_item(&self, id: i64, index: usize) -> Result<()> {
+        DocketService::toggle_checklist_item(self, id, index).await
+    }
+
+    async fn update_todo_estimate(&self, id: i64, estimate: Option<i64>) -> Result<()> {
+        DocketService::update_todo_estimate(self, id, estimate).await
+    }
+
+    async fn update_todo_due_date(&self, id: i64, due_date: Option<NaiveDate>) -> Result<()> {
+        DocketService::update_todo_due_date(self, id, due_date).await
+    }
+
+    async fn update_todo_external_ref(&self, id: i64, external_ref: Option<&str>) -> Result<()> {
+        DocketService::update_todo_external_ref(self, id, external_ref).await
+    }
+
+    async fn list_todos_due_between(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Todo>> {
+        DocketService::list_todos_due_between(self, from, to).await
+    }
+
+    async fn list_open_todos(&self) -> Result<Vec<Todo>> {
+        DocketService::list_open_todos(self).await
+    }
+
+    async fn list_open_todos_due_by(&self, date: NaiveDate) -> Result<Vec<Todo>> {
+        DocketService::list_open_todos_due_by(self, date).await
+    }
+
+    async fn update_todo(&self, id: i64, description: &str) -> Result<()> {
+        DocketService::update_todo(self, id, description).await
+    }
+
+    async fn move_todo_up(&self, id: i64) -> Result<()> {
+        DocketService::move_todo_up(self, id).await
+    }
+
+    async fn defer_todo(&self, id: i64) -> Result<()> {
+        DocketService::defer_todo(self, id).await
+    }
+
+    async fn move_todo_down(&self, id: i64) -> Result<()> {
+        DocketService::move_todo_down(self, id).await
+    }
+
+    async fn patch_todo(&self, id: i64, patch: &TodoPatch) -> Result<Todo> {
+        DocketService::patch_todo(self, id, patch).await
+    }
+
+    async fn todo_history(&self, todo_id: i64) -> Result<Vec<TodoVersion>> {
+        DocketService::todo_history(self, todo_id).await
+    }
+
+    async fn restore_todo_version(&self, todo_id: i64, version_id: i64) -> Result<Todo> {
+        DocketService::restore_todo_version(self, todo_id, version_id).await
+    }
+
+    async fn link_todos(&self, todo_id: i64, target_id: i64, kind: LinkKind) -> Result<TodoLink> {
+        DocketService::link_todos(self, todo_id, target_id, kind).await
+    }
+
+    async fn related_todos(&self, todo_id: i64) -> Result<Vec<RelatedTodo>> {
+        DocketService::related_todos(self, todo_id).await
+    }
+
+    async fn unlink_todos(&self, link_id: i64) -> Result<()> {
+        DocketService::unlink_todos(self, link_id).await
+    }
+
+    async fn todo_mentions(&self, todo_id: i64) -> Result<Vec<TodoMention>> {
+        DocketService::todo_mentions(self, todo_id).await
+    }
+
+    async fn normalize_positions(&self, project_id: i64) -> Result<usize> {
+        DocketService::normalize_positions(self, project_id).await
+    }
+
+    async fn share_project(&self, id: i64, days: i64) -> Result<ShareLink> {
+        DocketService::share_project(self, id, days).await
+    }
+
+    async fn shared_project(&self, token: &str) -> Result<SharedProject> {
+        DocketService::shared_project(self, token).await
+    }
+
+    async fn update_project_slug(&self, id: i64, slug: Option<&str>) -> Result<Project> {
+        DocketService::update_project_slug(self, id, slug).await
+    }
+
+    async fn public_project(&self, slug: &str) -> Result<Option<SharedProject>> {
+        DocketService::public_project(self, slug).await
+    }
+
+    async fn list_workspaces(&self) -> Result<Vec<Workspace>> {
+        DocketService::list_workspaces(self).await
+    }
+
+    async fn create_workspace(&self, name: &str) -> Result<Workspace> {
+        DocketService::create_workspace(self, name).await
+    }
+
+    async fn rename_workspace(&self, id: i64, name: &str) -> Result<Workspace> {
+        DocketService::rename_workspace(self, id, name).await
+    }
+
+    async fn delete_workspace(&self, id: i64) -> Result<()> {
+        DocketService::delete_workspace(self, id).await
+    }
+
+    async fn update_project_workspace(&self, id: i64, workspace_id: Option<i64>) -> Result<Project> {
+        DocketService::update_project_workspace(self, id, workspace_id).await
+    }
+
+    async fn update_project_parent(&self, id: i64, parent_id: Option<i64>) -> Result<Project> {
+        DocketService::update_project_parent(self, id, parent_id).await
+    }
+
+    async fn project_path(&self, id: i64) -> Result<Vec<Project>> {
+        DocketService::project_path(self, id).await
+    }
+
+    async fn list_templates(&self) -> Result<Vec<TodoTemplate>> {
+        DocketService::list_templates(self).await
+    }
+
+    async fn create_todo_from_template(&self, project_id: i64, name: &str, description: Option<&str>) -> Result<Todo> {
+        DocketService::create_todo_from_template(self, project_id, name, description).await
+    }
+
+    async fn apply_batch(&self, ops: Vec<BatchOp>) -> Result<Vec<BatchResult>> {
+        DocketService::apply_batch(self, ops).await
+    }
+
+    async fn audit_log(&self, query: &AuditQuery) -> Result<AuditPage> {
+        DocketService::audit_log(self, query).await
+    }
+
+    async fn changes_since(&self, since: i64) -> Result<ChangeSet> {
+        DocketService::changes_since(self, since).await
+    }
+
+    async fn change_marker(&self) -> Result<ChangeMarker> {
+        DocketService::change_marker(self).await
+    }
+
+    async fn apply_changes(&self, changes: Vec<PushChange>) -> Result<PushResult> {
+        DocketService::apply_changes(self, changes).await
+    }
+}
@@ -0,0 +1,176 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+use std::fmt::Write;
+
+use super::error::DocketError;
+use super::html::escape_html;
+use super::models::{Project, Todo};
+
+/// How long a share link works unless asked otherwise
+pub const DEFAULT_SHARE_DAYS: i64 = 7;
+
+/// Longest a share link may work
+pub const MAX_SHARE_DAYS: i64 = 365;
+
+/// A read-only link to a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareLink {
+    pub token: String,
+    /// Path of the shared page, e.g. `/share/<token>`
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Why a share token was turned down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShareError {
+    /// Malformed, or not signed by this server
+    Invalid,
+    Expired,
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::Invalid => write!(f, "This share link is not valid"),
+            ShareError::Expired => write!(f, "This share link has expired"),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+impl From<ShareError> for DocketError {
+    fn from(err: ShareError) -> Self {
+        match err {
+            ShareError::Invalid => DocketError::NotFound(err.to_string()),
+            ShareError::Expired => DocketError::Expired(err.to_string()),
+        }
+    }
+}
+
+/// `<project id>.<expiry as Unix seconds>.<signature>`; the signature is an
+/// HMAC-SHA256 of the first two parts
+pub fn sign(secret: &[u8], project_id: i64, expires_at: DateTime<Utc>) -> String {
+    let payload = format!("{}.{}", project_id, expires_at.timestamp());
+    let signature = URL_SAFE_NO_PAD.encode(mac(secret, &payload).finalize().into_bytes());
+    format!("{}.{}", payload, signature)
+}
+
+/// The project a token shares, if it was signed with `secret` and has not
+/// expired by `now`
+pub fn verify(secret: &[u8], token: &str, now: DateTime<Utc>) -> Result<i64, ShareError> {
+    let (payload, signature) = token.rsplit_once('.').ok_or(ShareError::Invalid)?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| ShareError::Invalid)?;
+    mac(secret, payload)
+        .verify_slice(&signature)
+        .map_err(|_| ShareError::Invalid)?;
+
+    let (project_id, expires) = payload.split_once('.').ok_or(ShareError::Invalid)?;
+    let project_id = project_id.parse().map_err(|_| ShareError::Invalid)?;
+    let expires: i64 = expires.parse().map_err(|_| ShareError::Invalid)?;
+    if now.timestamp() >= expires {
+        return Err(ShareError::Expired);
+    }
+    Ok(project_id)
+}
+
+fn mac(secret: &[u8], payload: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// What a share link shows: the project's name, description and todo
+/// descriptions, without details or links that may be internal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedProject {
+    pub name: String,
+    pub description: Option<String>,
+    pub target_date: Option<NaiveDate>,
+    pub total_todos: usize,
+    pub completed_todos: usize,
+    /// Open todos in order, then completed ones
+    pub todos: Vec<SharedTodo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedTodo {
+    pub description: String,
+    pub due_date: Option<NaiveDate>,
+    pub completed: bool,
+}
+
+impl SharedProject {
+    pub fn new(project: &Project, todos: &[Todo]) -> Self {
+        Self {
+            name: project.name.clone(),
+            description: project.description.clone(),
+            target_date: project.target_date,
+            total_todos: todos.len(),
+            completed_todos: todos.iter().filter(|t| t.is_completed()).count(),
+            todos: todos
+                .iter()
+                .map(|t| SharedTodo {
+                    description: t.description.clone(),
+                    due_date: t.due_date,
+                    completed: t.is_completed(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Render as a standalone HTML page
+    pub fn to_html(&self) -> String {
+        let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(out, "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">");
+        let _ = writeln!(out, "<title>{}</title>", escape_html(&self.name));
+        out.push_str("</head>\n<body style=\"font-family: sans-serif; max-width: 40em; margin: 2em auto\">\n");
+        let _ = writeln!(out, "<h1>{}</h1>", escape_html(&self.name));
+        if let Some(description) = &self.description {
+            let _ = writeln!(out, "<p>{}</p>", escape_html(description));
+        }
+        let _ = write!(out, "<p>{} of {} done", self.completed_todos, self.total_todos);
+        if let Some(target) = self.target_date {
+            let _ = write!(out, ", target {}", target);
+        }
+        let _ = writeln!(
+            out,
+            "</p>\n<progress value=\"{}\" max=\"{}\"></progress>",
+            self.completed_todos,
+            self.total_todos.max(1)
+        );
+        out.push_str("<ul style=\"list-style: none; padding: 0\">\n");
+        for todo in &self.todos {
+            let due = todo.due_date.map(|d| format!(" <small>(due {})</small>", d)).unwrap_or_default();
+            if todo.completed {
+                let _ = writeln!(out, "<li>&#x2611; <s>{}</s>{}</li>", escape_html(&todo.description), due);
+            } else {
+                let _ = writeln!(out, "<li>&#x2610; {}{}</li>", escape_html(&todo.description), due);
+            }
+        }
+        out.push_str("</ul>\n</body>\n</html>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_tokens_are_signed_and_expire() {
+        let now = Utc::now();
+        let token = sign(b"secret", 7, now + Duration::days(1));
+        assert_eq!(verify(b"secret", &token, now), Ok(7));
+        assert_eq!(verify(b"other", &token, now), Err(ShareError::Invalid));
+        assert_eq!(verify(b"secret", &token.replacen('7', "8", 1), now), Err(ShareError::Invalid));
+        assert_eq!(verify(b"secret", &token, now + Duration::days(2)), Err(ShareError::Expired));
+        assert_eq!(verify(b"secret", "garbage", now), Err(ShareError::Invalid));
+    }
+}
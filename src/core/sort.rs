@@ -0,0 +1,264 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use super::models::Todo;
+use super::search;
+
+/// A todo field a list can be ordered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    /// Manual order, which is also a todo's priority
+    Position,
+    Due,
+    Estimate,
+    Created,
+    Updated,
+    Description,
+}
+
+impl SortField {
+    pub const ALL: [SortField; 6] = [
+        SortField::Position,
+        SortField::Due,
+        SortField::Estimate,
+        SortField::Created,
+        SortField::Updated,
+        SortField::Description,
+    ];
+
+    /// Names accepted in sort specs, for completion and errors
+    pub fn names() -> &'static [&'static str] {
+        &["position", "due", "estimate", "created", "updated", "description"]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortField::Position => "position",
+            SortField::Due => "due",
+            SortField::Estimate => "estimate",
+            SortField::Created => "created",
+            SortField::Updated => "updated",
+            SortField::Description => "description",
+        }
+    }
+
+    /// Direction when none is given: biggest estimates first, the rest ascending
+    pub fn descending_by_default(&self) -> bool {
+        *self == SortField::Estimate
+    }
+
+    /// SQL for the column; only ever these fixed strings reach ORDER BY
+    fn column(&self) -> &'static str {
+        match self {
+            SortField::Position => "position",
+            SortField::Due => "due_date",
+            SortField::Estimate => "estimate",
+            SortField::Created => "created_at",
+            SortField::Updated => "updated_at",
+            SortField::Description => "description COLLATE FOLD",
+        }
+    }
+
+    fn nullable(&self) -> bool {
+        matches!(self, SortField::Due | SortField::Estimate)
+    }
+
+    fn compare(&self, a: &Todo, b: &Todo) -> Ordering {
+        match self {
+            SortField::Position => a.position.cmp(&b.position),
+            SortField::Due => a.due_date.cmp(&b.due_date),
+            SortField::Estimate => a.estimate.cmp(&b.estimate),
+            SortField::Created => a.created_at.cmp(&b.created_at),
+            SortField::Updated => a.updated_at.cmp(&b.updated_at),
+            SortField::Description => search::compare(&a.description, &b.description),
+        }
+    }
+
+    fn is_missing(&self, todo: &Todo) -> bool {
+        match self {
+            SortField::Due => todo.due_date.is_none(),
+            SortField::Estimate => todo.estimate.is_none(),
+            _ => false,
+        }
+    }
+}
+
+/// One key of a sort spec, e.g. `due asc`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub fn new(field: SortField) -> Self {
+        Self { field, descending: field.descending_by_default() }
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.field.name())?;
+        if self.descending != self.field.descending_by_default() {
+            write!(f, " {}", if self.descending { "desc" } else { "asc" })?;
+        }
+        Ok(())
+    }
+}
+
+/// Order of a todo list, e.g. `due, estimate desc, position`: keys apply in
+/// turn, each breaking the previous one's ties. Open todos always come
+/// before completed ones, todos without a due date or estimate come last
+/// whichever way those keys run, and ties left over fall back to manual
+/// order, then newest completed first. No keys is manual order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TodoOrder {
+    keys: Vec<SortKey>,
+}
+
+impl TodoOrder {
+    pub fn new(keys: Vec<SortKey>) -> Self {
+        let mut order = Self { keys: Vec::new() };
+        for key in keys {
+            // A repeated field can't break any ties
+            if !order.keys.iter().any(|k| k.field == key.field) {
+                order.keys.push(key);
+            }
+        }
+        // `position` alone is manual order too, so both compare equal
+        if order.keys == [SortKey::new(SortField::Position)] {
+            order.keys.clear();
+        }
+        order
+    }
+
+    pub fn keys(&self) -> &[SortKey] {
+        &self.keys
+    }
+
+    /// Whether the list shows manual order, the only one `J` / `K` make sense in
+    pub fn is_manual(&self) -> bool {
+        self.keys.first().is_none_or(|k| *k == SortKey::new(SortField::Position))
+    }
+
+    /// The ORDER BY clause (without the keywords) for a `todos` query
+    pub fn order_by(&self) -> String {
+        let mut terms = vec!["completed_at IS NOT NULL".to_string()];
+        for key in &self.keys {
+            if key.field.nullable() {
+                terms.push(format!("{} IS NULL", key.field.column()));
+            }
+            terms.push(format!("{} {}", key.field.column(), if key.descending { "DESC" } else { "ASC" }));
+        }
+        terms.extend(["position ASC", "completed_at DESC", "id ASC"].map(str::to_string));
+        terms.join(", ")
+    }
+
+    /// Sort todos in place the way `order_by` does, for lists that don't
+    /// come straight from one query
+    pub fn apply(&self, todos: &mut [Todo]) {
+        todos.sort_by(|a, b| {
+            let mut ordering = a.is_completed().cmp(&b.is_completed());
+            for key in &self.keys {
+                let field = key.field;
+                ordering = ordering
+                    .then_with(|| field.is_missing(a).cmp(&field.is_missing(b)))
+                    .then_with(|| {
+                        let by_field = field.compare(a, b);
+                        if key.descending { by_field.reverse() } else { by_field }
+                    });
+            }
+            ordering
+                .then_with(|| a.position.cmp(&b.position))
+                .then_with(|| b.completed_at.cmp(&a.completed_at))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+    }
+}
+
+impl FromStr for TodoOrder {
+    type Err = String;
+
+    /// Comma-separated keys, each a field with an optional `asc` / `desc`
+    /// (`-due` is `due desc`). `priority` is manual order seen the other way
+    /// round: `priority desc` puts the top of the list first.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keys = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, direction) = match part.split_once(char::is_whitespace) {
+                Some((name, direction)) => (name, Some(direction.trim().to_lowercase())),
+                None => (part, None),
+            };
+            let (negated, name) = match name.strip_prefix('-') {
+                Some(name) => (true, name),
+                None => (false, name),
+            };
+            let (field, flipped) = match name.to_lowercase().as_str() {
+                "position" | "manual" => (SortField::Position, false),
+                "priority" => (SortField::Position, true),
+                "due" | "due_date" => (SortField::Due, false),
+                "estimate" => (SortField::Estimate, false),
+                "created" | "created_at" => (SortField::Created, false),
+                "updated" | "updated_at" => (SortField::Updated, false),
+                "description" | "name" => (SortField::Description, false),
+                _ => {
+                    return Err(format!(
+                        "Unknown sort '{}' (expected one of: {}, or priority)",
+                        name,
+                        SortField::names().join(", ")
+                    ));
+                }
+            };
+            // As written, before `priority` turns it around; priority defaults to desc
+            let descending = match direction.as_deref() {
+                _ if negated && direction.is_some() => {
+                    return Err(format!("Use either -{} or {} asc / desc, not both", name, name));
+                }
+                None => negated || flipped || field.descending_by_default(),
+                Some("asc") => false,
+                Some("desc") => true,
+                Some(other) => return Err(format!("Unknown direction '{}' for {} (expected asc or desc)", other, name)),
+            };
+            keys.push(SortKey { field, descending: descending != flipped });
+        }
+        Ok(TodoOrder::new(keys))
+    }
+}
+
+impl fmt::Display for TodoOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.keys.is_empty() {
+            return write!(f, "position");
+        }
+        let keys: Vec<String> = self.keys.iter().map(SortKey::to_string).collect();
+        write!(f, "{}", keys.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display_specs() {
+        let order: TodoOrder = "priority desc, due asc, position".parse().unwrap();
+        // Top priority first is manual order; the later keys never get a say
+        assert!(order.is_manual());
+        assert_eq!(order.to_string(), "position, due");
+
+        let order: TodoOrder = "due, -created, estimate".parse().unwrap();
+        assert_eq!(order.to_string(), "due, created desc, estimate");
+        assert!(!order.is_manual());
+        assert_eq!(
+            order.order_by(),
+            "completed_at IS NOT NULL, due_date IS NULL, due_date ASC, created_at DESC, estimate IS NULL, \
+             estimate DESC, position ASC, completed_at DESC, id ASC"
+        );
+        assert_eq!("priority asc".parse::<TodoOrder>().unwrap().to_string(), "position desc");
+        assert_eq!("position".parse::<TodoOrder>(), Ok(TodoOrder::default()));
+
+        assert!("due sideways".parse::<TodoOrder>().unwrap_err().contains("expected asc or desc"));
+        assert!("urgency".parse::<TodoOrder>().unwrap_err().starts_with("Unknown sort 'urgency'"));
+    }
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::models::{Project, Todo, Tombstone};
+
+/// Everything that changed after a cursor, as returned by `GET /api/changes`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeSet {
+    /// Pass this as `since` on the next pull
+    pub cursor: i64,
+    pub projects: Vec<Project>,
+    pub todos: Vec<Todo>,
+    pub deleted: Vec<Tombstone>,
+}
+
+/// How far a local copy of a remote server has pulled
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct PullState {
+    /// `cursor` of the last pull
+    pub server_cursor: i64,
+    /// The copy's own sync version right after that pull; rows above it
+    /// were changed here and not sent yet
+    pub local_version: i64,
+}
+
+/// The most recent write to the database, used for HTTP cache validation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChangeMarker {
+    /// Current sync version; bumps on every insert, update and delete
+    pub version: i64,
+    /// Latest `updated_at` or deletion time, if anything exists yet
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+/// Kind of row a change refers to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Entity {
+    Project,
+    Todo,
+}
+
+/// One offline edit pushed by a client via `POST /api/changes`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PushChange {
+    Project(ProjectChange),
+    Todo(TodoChange),
+    Delete { entity: Entity, id: i64 },
+}
+
+/// Full client-side state of a project
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectChange {
+    /// Server ID; omit to create the project
+    pub id: Option<i64>,
+    /// Client-chosen reference for a new project, so todos in the same push can use it
+    #[serde(rename = "ref")]
+    pub client_ref: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    pub target_date: Option<NaiveDate>,
+    /// When the client made the edit; loses to a newer server copy
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Full client-side state of a todo
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TodoChange {
+    /// Server ID; omit to create the todo
+    pub id: Option<i64>,
+    #[serde(rename = "ref")]
+    pub client_ref: Option<String>,
+    /// Project for a new todo: a server ID or the `ref` of a project created in the same push
+    pub project_id: Option<i64>,
+    pub project_ref: Option<String>,
+    pub description: String,
+    pub details: Option<String>,
+    #[serde(default)]
+    pub completed: bool,
+    pub estimate: Option<i64>,
+    pub due_date: Option<NaiveDate>,
+    pub external_ref: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A pushed change the server did not apply
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub entity: Entity,
+    pub id: i64,
+    pub reason: ConflictReason,
+}
+
+/// Why a pushed change was not applied
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictReason {
+    /// The server's copy was changed after the client's edit
+    NewerOnServer,
+    /// The row no longer exists on the server
+    DeletedOnServer,
+}
+
+/// Outcome of `POST /api/changes`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PushResult {
+    /// Server IDs assigned to new rows, keyed by the client's `ref`
+    pub created: HashMap<String, i64>,
+    pub conflicts: Vec<SyncConflict>,
+    /// Sync version after applying the push
+    pub cursor: i64,
+}
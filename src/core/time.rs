@@ -0,0 +1,106 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Offset, TimeZone, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+/// Time zone timestamps are shown in. Everything is stored in UTC; this only
+/// affects display and what counts as "today".
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DisplayZone {
+    /// The system time zone (honours `TZ`)
+    #[default]
+    Local,
+    /// A fixed offset from UTC; `utc` is `+00:00`
+    Fixed(FixedOffset),
+}
+
+impl DisplayZone {
+    /// UTC offset in effect at `at`
+    pub fn offset_at(&self, at: DateTime<Utc>) -> FixedOffset {
+        match self {
+            DisplayZone::Local => Local.offset_from_utc_datetime(&at.naive_utc()).fix(),
+            DisplayZone::Fixed(offset) => *offset,
+        }
+    }
+
+    /// A UTC timestamp as wall-clock time in this zone
+    pub fn convert(&self, at: DateTime<Utc>) -> DateTime<FixedOffset> {
+        at.with_timezone(&self.offset_at(at))
+    }
+
+    /// Today's date in this zone
+    pub fn today(&self) -> NaiveDate {
+        self.convert(Utc::now()).date_naive()
+    }
+}
+
+impl FromStr for DisplayZone {
+    type Err = String;
+
+    /// Accepts `local`, `utc` (or `z`) and offsets like `+02:00`, `-0530` or `+9`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.to_lowercase().as_str() {
+            "local" => return Ok(DisplayZone::Local),
+            "utc" | "z" => return Ok(DisplayZone::Fixed(FixedOffset::east_opt(0).unwrap())),
+            _ => {}
+        }
+
+        let invalid = || format!("Unknown time zone '{}' (expected local, utc or an offset like +02:00)", s);
+        let (sign, rest) = match s.as_bytes().first() {
+            Some(b'+') => (1, &s[1..]),
+            Some(b'-') => (-1, &s[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if rest.len() > 2 => rest.split_at(rest.len() - 2),
+            None => (rest, "0"),
+        };
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if minutes >= 60 {
+            return Err(invalid());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(DisplayZone::Fixed)
+            .ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for DisplayZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayZone::Local => write!(f, "local"),
+            DisplayZone::Fixed(offset) if offset.local_minus_utc() == 0 => write!(f, "utc"),
+            DisplayZone::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zones() {
+        let offset = |zone: &str| match zone.parse::<DisplayZone>().unwrap() {
+            DisplayZone::Fixed(offset) => offset.local_minus_utc(),
+            DisplayZone::Local => panic!("expected a fixed offset"),
+        };
+        assert_eq!("Local".parse::<DisplayZone>(), Ok(DisplayZone::Local));
+        assert_eq!(offset("utc"), 0);
+        assert_eq!(offset("+02:00"), 7200);
+        assert_eq!(offset("-0530"), -(5 * 3600 + 30 * 60));
+        assert_eq!(offset("+9"), 9 * 3600);
+        assert!("Europe/Paris".parse::<DisplayZone>().is_err());
+        assert!("+02:75".parse::<DisplayZone>().is_err());
+    }
+
+    #[test]
+    fn test_convert_moves_date_across_midnight() {
+        let at = "2026-03-01T23:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let tokyo: DisplayZone = "+09:00".parse().unwrap();
+        assert_eq!(tokyo.convert(at).to_rfc3339(), "2026-03-02T08:30:00+09:00");
+        assert_eq!(tokyo.to_string(), "+09:00");
+    }
+}
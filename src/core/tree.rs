@@ -0,0 +1,148 @@
+use std::collections::{HashMap, HashSet};
+
+use super::models::ProjectWithStats;
+
+/// A project's place in the project tree, with the todo counts of the
+/// project and everything nested below it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TreeRow {
+    /// Levels below the top; top-level projects are 0
+    pub depth: usize,
+    /// Some listed project has this one as its parent
+    pub has_children: bool,
+    /// Listed projects nested below this one, at any depth
+    pub sub_projects: usize,
+    pub total_todos: i64,
+    pub completed_todos: i64,
+}
+
+impl TreeRow {
+    /// Open todos in the project and its sub-projects
+    pub fn active_todos(&self) -> i64 {
+        self.total_todos - self.completed_todos
+    }
+}
+
+/// Order `projects` depth-first, each sub-project right after its parent,
+/// and leave out those under a `collapsed` project. A project whose parent
+/// isn't in the list (archived, or in another workspace) sits at the top
+/// level. Returns the rows in step with the reordered projects.
+pub fn arrange(projects: Vec<ProjectWithStats>, collapsed: &HashSet<i64>) -> (Vec<ProjectWithStats>, Vec<TreeRow>) {
+    let ids: HashSet<i64> = projects.iter().map(|p| p.project.id).collect();
+    let mut children: HashMap<Option<i64>, Vec<usize>> = HashMap::new();
+    for (index, p) in projects.iter().enumerate() {
+        let parent = p.project.parent_id.filter(|id| ids.contains(id));
+        children.entry(parent).or_default().push(index);
+    }
+
+    let seen = vec![false; projects.len()];
+    let mut walk = Walk { projects: &projects, children, collapsed, seen, rows: Vec::new() };
+    let roots = walk.children.get(&None).cloned().unwrap_or_default();
+    for index in roots {
+        walk.visit(index, 0, true);
+    }
+    // A loop of parents has no way in from the top; list it there anyway
+    for index in 0..projects.len() {
+        if !walk.seen[index] {
+            walk.visit(index, 0, true);
+        }
+    }
+
+    let rows = walk.rows;
+    let mut slots: Vec<Option<ProjectWithStats>> = projects.into_iter().map(Some).collect();
+    let ordered = rows.iter().filter_map(|(index, _)| slots[*index].take()).collect();
+    (ordered, rows.into_iter().map(|(_, row)| row).collect())
+}
+
+struct Walk<'a> {
+    projects: &'a [ProjectWithStats],
+    children: HashMap<Option<i64>, Vec<usize>>,
+    collapsed: &'a HashSet<i64>,
+    seen: Vec<bool>,
+    rows: Vec<(usize, TreeRow)>,
+}
+
+impl Walk<'_> {
+    /// Add the project at `index` and, below it, its sub-projects; returns
+    /// the todo counts of them all and how many projects that is
+    fn visit(&mut self, index: usize, depth: usize, shown: bool) -> (i64, i64, usize) {
+        self.seen[index] = true;
+        let project = &self.projects[index];
+        let slot = shown.then(|| {
+            let row = TreeRow { depth, ..TreeRow::default() };
+            self.rows.push((index, row));
+            self.rows.len() - 1
+        });
+        let mut counts = (project.total_todos, project.completed_todos, 1);
+        let children = self.children.get(&Some(project.project.id)).cloned().unwrap_or_default();
+        let open = shown && !self.collapsed.contains(&project.project.id);
+        for child in &children {
+            if !self.seen[*child] {
+                let (total, completed, projects) = self.visit(*child, depth + 1, open);
+                counts = (counts.0 + total, counts.1 + completed, counts.2 + projects);
+            }
+        }
+        if let Some(slot) = slot {
+            let row = &mut self.rows[slot].1;
+            row.has_children = !children.is_empty();
+            (row.total_todos, row.completed_todos, row.sub_projects) = (counts.0, counts.1, counts.2 - 1);
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Project;
+    use chrono::Utc;
+
+    fn project(id: i64, parent_id: Option<i64>, total_todos: i64, completed_todos: i64) -> ProjectWithStats {
+        ProjectWithStats {
+            project: Project {
+                id,
+                name: format!("Project {}", id),
+                description: None,
+                created_at: Utc::now(),
+                archived_at: None,
+                last_reviewed_at: None,
+                target_date: None,
+                slug: None,
+                workspace_id: None,
+                parent_id,
+                updated_at: Utc::now(),
+                version: 1,
+            },
+            total_todos,
+            completed_todos,
+            last_activity_at: Utc::now(),
+            total_estimate: 0,
+            remaining_estimate: 0,
+        }
+    }
+
+    #[test]
+    fn test_sub_projects_follow_their_parent_with_rolled_up_counts() {
+        // 1 > 3 > 4, and 2 whose parent 9 isn't listed
+        let projects = vec![
+            project(4, Some(3), 2, 2),
+            project(1, None, 3, 1),
+            project(2, Some(9), 1, 0),
+            project(3, Some(1), 4, 0),
+        ];
+
+        let (ordered, rows) = arrange(projects.clone(), &HashSet::new());
+        let ids: Vec<i64> = ordered.iter().map(|p| p.project.id).collect();
+        assert_eq!(ids, [1, 3, 4, 2]);
+        let depths: Vec<usize> = rows.iter().map(|r| r.depth).collect();
+        assert_eq!(depths, [0, 1, 2, 0]);
+        assert_eq!((rows[0].total_todos, rows[0].active_todos()), (9, 6));
+        assert_eq!((rows[1].total_todos, rows[1].active_todos()), (6, 4));
+        assert!(rows[1].has_children && !rows[2].has_children);
+
+        let (ordered, rows) = arrange(projects, &HashSet::from([1]));
+        let ids: Vec<i64> = ordered.iter().map(|p| p.project.id).collect();
+        assert_eq!(ids, [1, 2]);
+        assert_eq!((rows[0].total_todos, rows[0].sub_projects), (9, 2));
+    }
+}
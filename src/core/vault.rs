@@ -0,0 +1,139 @@
+use std::fmt::Write;
+
+use super::models::{Project, Todo};
+
+/// Frontmatter key tying a vault file to its project, so renamed projects
+/// keep their file
+const PROJECT_KEY: &str = "docket_project";
+
+/// Obsidian block reference marking which todo a checkbox line belongs to
+const TODO_REF: &str = " ^docket-";
+
+/// A checkbox line read back from a vault file
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultItem {
+    /// `None` for lines added in the vault
+    pub todo_id: Option<i64>,
+    pub checked: bool,
+    pub description: String,
+}
+
+/// Render a project as a Markdown checklist for a notes vault. Each todo
+/// carries a block reference (`^docket-12`) so edits can be matched back.
+pub fn render(project: &Project, todos: &[Todo]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "---\n{}: {}\n---", PROJECT_KEY, project.id);
+    let _ = writeln!(out, "# {}", project.name);
+    if let Some(description) = &project.description {
+        let _ = writeln!(out, "\n{}", description);
+    }
+    out.push('\n');
+
+    for todo in todos {
+        let check = if todo.is_completed() { "x" } else { " " };
+        let _ = writeln!(out, "- [{}] {}{}{}", check, todo.description, TODO_REF, todo.id);
+        if let Some(details) = &todo.details {
+            for line in details.lines() {
+                let _ = writeln!(out, "  {}", line);
+            }
+        }
+    }
+    out
+}
+
+/// Project ID from a vault file's frontmatter, if it was written by docket
+pub fn project_id(text: &str) -> Option<i64> {
+    let frontmatter = text.strip_prefix("---\n")?.split("\n---").next()?;
+    frontmatter.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == PROJECT_KEY).then(|| value.trim().parse().ok())?
+    })
+}
+
+/// Top-level checkbox lines of a vault file; indented lines (details,
+/// sub-tasks) are left alone
+pub fn parse(text: &str) -> Vec<VaultItem> {
+    text.lines()
+        .filter_map(|line| {
+            let rest = line.strip_prefix("- [")?;
+            let (mark, description) = rest.split_once("] ")?;
+            let checked = match mark {
+                " " => false,
+                "x" | "X" => true,
+                _ => return None,
+            };
+            let (description, todo_id) = match description.rsplit_once(TODO_REF) {
+                Some((text, id)) if id.trim().parse::<i64>().is_ok() => (text, id.trim().parse().ok()),
+                _ => (description, None),
+            };
+            let description = description.trim();
+            (!description.is_empty()).then(|| VaultItem {
+                todo_id,
+                checked,
+                description: description.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// File name for a project, without characters that trip up file systems
+pub fn file_name(project: &Project) -> String {
+    let name: String = project
+        .name
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '-' } else { c })
+        .collect();
+    format!("{}.md", name.trim().trim_start_matches('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_rendered_file_parses_back() {
+        let project = Project {
+            id: 3,
+            name: "Home/Garden".to_string(),
+            description: None,
+            created_at: Utc::now(),
+            archived_at: None,
+            last_reviewed_at: None,
+            target_date: None,
+            slug: None,
+            workspace_id: None,
+            parent_id: None,
+            updated_at: Utc::now(),
+            version: 1,
+        };
+        let todo = Todo {
+            id: 12,
+            project_id: 3,
+            description: "Plant bulbs".to_string(),
+            details: Some("- [ ] tulips".to_string()),
+            created_at: Utc::now(),
+            completed_at: None,
+            position: 1,
+            estimate: None,
+            due_date: None,
+            external_ref: None,
+            updated_at: Utc::now(),
+            version: 1,
+        };
+
+        let text = render(&project, &[todo]);
+        assert_eq!(project_id(&text), Some(3));
+        assert_eq!(file_name(&project), "Home-Garden.md");
+
+        let edited = text.replace("- [ ] Plant", "- [x] Plant") + "- [ ] Mow lawn\n";
+        assert_eq!(
+            parse(&edited),
+            vec![
+                VaultItem { todo_id: Some(12), checked: true, description: "Plant bulbs".to_string() },
+                VaultItem { todo_id: None, checked: false, description: "Mow lawn".to_string() },
+            ]
+        );
+        assert_eq!(project_id("# Just a note\n"), None);
+    }
+}
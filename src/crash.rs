@@ -0,0 +1,151 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use docket::version;
+
+/// How many recent actions a crash report lists
+const RECENT_CAPACITY: usize = 30;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Set while the TUI owns the terminal, so a panic knows to give it back
+static TERMINAL_TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Remember an action for the crash report, forgetting the oldest past
+/// `RECENT_CAPACITY`
+pub fn record(action: impl Into<String>) {
+    if let Ok(mut recent) = RECENT.lock() {
+        let at = chrono::Local::now().format("%H:%M:%S");
+        remember(&mut recent, format!("{} {}", at, action.into()));
+    }
+}
+
+fn remember(recent: &mut VecDeque<String>, entry: String) {
+    if recent.len() == RECENT_CAPACITY {
+        recent.pop_front();
+    }
+    recent.push_back(entry);
+}
+
+/// A key press as a crash report lists it, e.g. `Ctrl+d` or `Enter`
+pub fn key_label(key: &KeyEvent) -> String {
+    let mut label = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        label.push_str("Ctrl+");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        label.push_str("Alt+");
+    }
+    match key.code {
+        KeyCode::Char(' ') => label.push_str("Space"),
+        KeyCode::Char(c) => label.push(c),
+        code => {
+            let _ = write!(label, "{:?}", code);
+        }
+    }
+    label
+}
+
+/// Note whether the TUI has the terminal in raw mode on the alternate screen
+pub fn set_terminal_taken(taken: bool) {
+    TERMINAL_TAKEN.store(taken, Ordering::SeqCst);
+}
+
+/// On a panic, hand the terminal back, write a crash report to `data_dir`
+/// and say where it is instead of printing a bare backtrace
+pub fn install(data_dir: PathBuf) {
+    std::panic::set_hook(Box::new(move |info| {
+        if TERMINAL_TAKEN.swap(false, Ordering::SeqCst) {
+            restore_terminal();
+        }
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        let recent: Vec<String> = RECENT.lock().map(|r| r.iter().cloned().collect()).unwrap_or_default();
+        let report = render_report(&describe(info), &backtrace, &recent);
+
+        eprintln!("\nSorry, docket ran into a bug: {}", describe(info));
+        match write_report(&data_dir, &report) {
+            Ok(path) => {
+                eprintln!("A crash report is in {}", path.display());
+                eprintln!("Please attach it when you report the bug.");
+            }
+            Err(err) => eprintln!("Could not save a crash report ({}); here it is:\n\n{}", err, report),
+        }
+    }));
+}
+
+/// The panic's message and where it happened
+fn describe(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic");
+    match info.location() {
+        Some(location) => format!("{} ({}:{})", message, location.file(), location.line()),
+        None => message.to_string(),
+    }
+}
+
+fn render_report(panic: &str, backtrace: &str, recent: &[String]) -> String {
+    let mut report = String::from("Docket crash report\n\n");
+    let _ = writeln!(report, "Version: {}", version::LONG_VERSION);
+    let _ = writeln!(report, "Platform: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(report, "Time: {}", chrono::Local::now().to_rfc3339());
+    let _ = writeln!(report, "Panic: {}", panic);
+    report.push_str("\nRecent actions (oldest first):\n");
+    if recent.is_empty() {
+        report.push_str("  (none)\n");
+    }
+    for action in recent {
+        let _ = writeln!(report, "  {}", action);
+    }
+    let _ = write!(report, "\nBacktrace:\n{}\n", backtrace);
+    report
+}
+
+/// Save `report` as `crash-<time>.txt` in `data_dir`
+fn write_report(data_dir: &Path, report: &str) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(data_dir)?;
+    let name = format!("crash-{}.txt", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    let path = data_dir.join(name);
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+fn restore_terminal() {
+    use crossterm::{cursor::Show, event::DisableMouseCapture, execute, terminal};
+
+    let _ = terminal::disable_raw_mode();
+    let _ = execute!(std::io::stdout(), terminal::LeaveAlternateScreen, DisableMouseCapture, Show);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_keeps_the_latest_actions() {
+        let mut recent = VecDeque::new();
+        for n in 0..RECENT_CAPACITY + 5 {
+            remember(&mut recent, format!("Normal: key {}", n));
+        }
+        let recent: Vec<String> = recent.into();
+        assert_eq!(recent.len(), RECENT_CAPACITY);
+        assert_eq!(recent[0], "Normal: key 5");
+
+        let report = render_report("boom (src/main.rs:1)", "0: main", &recent);
+        assert!(report.contains(version::LONG_VERSION));
+        assert!(report.contains("Panic: boom (src/main.rs:1)"));
+        assert!(report.contains(&format!("key {}\n", RECENT_CAPACITY + 4)));
+
+        let key = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(key_label(&key), "Ctrl+d");
+        assert_eq!(key_label(&KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)), "Enter");
+    }
+}
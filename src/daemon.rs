@@ -0,0 +1,171 @@
+use anyhow::{Context, Result, bail};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Flag that asks `docket server` to detach into the background
+pub const DAEMON_FLAG: &str = "--daemon";
+
+/// First file descriptor passed by systemd socket activation
+#[cfg(unix)]
+const SD_LISTEN_FDS_START: std::os::fd::RawFd = 3;
+
+/// Re-launch the current command in the background, detached from the
+/// terminal, with output appended to `log_file`. Returns once the server has
+/// survived startup.
+#[cfg(unix)]
+pub async fn spawn_background(pid_file: &Path, log_file: &Path) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    if let Some(pid) = running_pid(pid_file) {
+        bail!("Docket is already running (pid {}, see {})", pid, pid_file.display());
+    }
+
+    let mut args: Vec<_> = std::env::args_os()
+        .skip(1)
+        .filter(|arg| arg != DAEMON_FLAG)
+        .collect();
+    if !args.iter().any(|arg| arg == "--pid-file") {
+        args.push("--pid-file".into());
+        args.push(pid_file.into());
+    }
+
+    let log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_file)
+        .with_context(|| format!("Failed to open log file {}", log_file.display()))?;
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        // Own process group, so Ctrl+C in the launching terminal doesn't reach it
+        .process_group(0)
+        .spawn()
+        .context("Failed to start background server")?;
+
+    // Bind errors and the like show up almost immediately
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    if let Some(status) = child.try_wait()? {
+        bail!("Server exited during startup ({}), see {}", status, log_file.display());
+    }
+
+    println!("🚀 Docket server running in the background (pid {})", child.id());
+    println!("   Log: {}", log_file.display());
+    println!("   Stop with: kill $(cat {})", pid_file.display());
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn spawn_background(_pid_file: &Path, _log_file: &Path) -> Result<()> {
+    bail!("Daemon mode is not supported on this platform")
+}
+
+/// A pid file that is removed again when the server shuts down
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Write the current process ID, refusing if another live server owns the file
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(pid) = running_pid(path) {
+            bail!("Docket is already running (pid {}, see {})", pid, path.display());
+        }
+        std::fs::write(path, format!("{}\n", std::process::id()))
+            .with_context(|| format!("Failed to write pid file {}", path.display()))?;
+        Ok(Self { path: path.to_path_buf() })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Process ID recorded in a pid file, if that process is still alive
+fn running_pid(path: &Path) -> Option<u32> {
+    let pid: u32 = std::fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    (pid != std::process::id() && is_alive(pid)).then_some(pid)
+}
+
+#[cfg(unix)]
+fn is_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else { return false };
+    // Signal 0 only checks whether the process exists; EPERM means it does
+    // but belongs to someone else
+    let exists = unsafe { libc::kill(pid, 0) } == 0;
+    exists || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn is_alive(_pid: u32) -> bool {
+    false
+}
+
+/// A listening socket handed over by systemd
+#[cfg(unix)]
+pub enum ActivatedListener {
+    Tcp(std::net::TcpListener),
+    Unix(std::os::unix::net::UnixListener),
+}
+
+/// Take the listening socket passed by systemd socket activation, if this
+/// process was started that way (`LISTEN_PID` and `LISTEN_FDS`)
+#[cfg(unix)]
+pub fn systemd_listener() -> Result<Option<ActivatedListener>> {
+    use std::os::fd::{FromRawFd, IntoRawFd};
+    use std::os::unix::net::UnixListener;
+
+    let for_us = std::env::var("LISTEN_PID")
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok())
+        == Some(std::process::id());
+    let count: i32 = match std::env::var("LISTEN_FDS").ok().and_then(|n| n.parse().ok()) {
+        Some(count) if for_us && count > 0 => count,
+        _ => return Ok(None),
+    };
+    if count > 1 {
+        tracing::warn!("systemd passed {} sockets; only the first is used", count);
+    }
+
+    // SAFETY: LISTEN_PID names this process, so systemd handed us ownership of
+    // the descriptors starting at SD_LISTEN_FDS_START and nothing else uses them
+    let unix = unsafe { UnixListener::from_raw_fd(SD_LISTEN_FDS_START) };
+    // getsockname only yields a Unix address for a Unix socket
+    let listener = if unix.local_addr().is_ok() {
+        unix.set_nonblocking(true)?;
+        ActivatedListener::Unix(unix)
+    } else {
+        let tcp = unsafe { std::net::TcpListener::from_raw_fd(unix.into_raw_fd()) };
+        tcp.set_nonblocking(true)?;
+        ActivatedListener::Tcp(tcp)
+    };
+    Ok(Some(listener))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pid_file_refuses_live_process_and_cleans_up() {
+        let path = std::env::temp_dir().join(format!("docket-{}.pid", std::process::id()));
+
+        // init is always running
+        std::fs::write(&path, "1\n").unwrap();
+        assert!(PidFile::create(&path).is_err());
+
+        // A pid that can't be running is treated as stale
+        std::fs::write(&path, "999999999\n").unwrap();
+        let pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+}
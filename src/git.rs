@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The git repository and branch a command was run in
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitContext {
+    /// Directory name of the repository's top level
+    pub repo: String,
+    /// Checked-out branch; `None` on a detached HEAD
+    pub branch: Option<String>,
+}
+
+impl GitContext {
+    /// Detect the repository containing `dir`, if any
+    pub fn detect(dir: &Path) -> Option<Self> {
+        let root = git(dir, &["rev-parse", "--show-toplevel"])?;
+        let repo = Path::new(&root).file_name()?.to_string_lossy().into_owned();
+        let branch = git(dir, &["symbolic-ref", "--short", "-q", "HEAD"]);
+        Some(Self { repo, branch })
+    }
+
+    /// Project mapped to this repository: `repo@branch` wins over `repo`
+    pub fn mapped_project<'a>(&self, git_projects: &'a BTreeMap<String, String>) -> Option<&'a str> {
+        self.branch
+            .as_ref()
+            .and_then(|branch| git_projects.get(&format!("{}@{}", self.repo, branch)))
+            .or_else(|| git_projects.get(&self.repo))
+            .map(String::as_str)
+    }
+
+    /// The mapped project, or one named after the repository
+    pub fn project_name<'a>(&'a self, git_projects: &'a BTreeMap<String, String>) -> &'a str {
+        self.mapped_project(git_projects).unwrap_or(&self.repo)
+    }
+}
+
+/// Hooks directory of the repository containing `dir` (honours `core.hooksPath`)
+pub fn hooks_dir(dir: &Path) -> Option<PathBuf> {
+    git(dir, &["rev-parse", "--git-path", "hooks"]).map(|path| dir.join(path))
+}
+
+/// When the checked-out commit was made; `None` before the first commit
+pub fn last_commit_at(dir: &Path) -> Option<DateTime<Utc>> {
+    let date = git(dir, &["log", "-1", "--format=%cI"])?;
+    DateTime::parse_from_rfc3339(&date).ok().map(|d| d.with_timezone(&Utc))
+}
+
+/// Run a git command in `dir`, returning its trimmed output on success
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+    let text = String::from_utf8(output.stdout).ok()?;
+    (output.status.success() && !text.trim().is_empty()).then(|| text.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branch_mapping_wins_over_repo() {
+        let map = BTreeMap::from([
+            ("docket".to_string(), "Docket".to_string()),
+            ("docket@release".to_string(), "Docket release".to_string()),
+        ]);
+        let context = |branch: Option<&str>| GitContext {
+            repo: "docket".to_string(),
+            branch: branch.map(str::to_string),
+        };
+        assert_eq!(context(Some("release")).mapped_project(&map), Some("Docket release"));
+        assert_eq!(context(Some("main")).mapped_project(&map), Some("Docket"));
+        assert_eq!(context(None).mapped_project(&map), Some("Docket"));
+        let other = GitContext { repo: "dotfiles".to_string(), branch: None };
+        assert_eq!(other.mapped_project(&map), None);
+    }
+}
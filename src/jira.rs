@@ -0,0 +1,162 @@
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::client::{curl_quote, encode_query};
+use crate::config::Config;
+
+/// Issues fetched per search request
+const PAGE_SIZE: usize = 100;
+
+/// Minimal JIRA REST client. Requests go through `curl`, which brings TLS,
+/// proxy and certificate handling without an HTTP client stack in docket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JiraClient {
+    base_url: String,
+    user: Option<String>,
+    token: Option<String>,
+}
+
+/// The fields of a JIRA issue docket uses
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub key: String,
+    pub summary: String,
+    /// Workflow status name, e.g. `In Progress`
+    pub status: String,
+}
+
+impl JiraClient {
+    /// A client for the configured JIRA site, if there is one
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let base_url = config.jira_url.as_deref()?.trim_end_matches('/').to_string();
+        Some(Self {
+            base_url,
+            user: config.jira_user.clone(),
+            token: config.jira_token.clone(),
+        })
+    }
+
+    /// The issue key a todo's external reference points at: a bare key like
+    /// `OPS-42`, or a `<jira_url>/browse/OPS-42` link
+    pub fn issue_key(&self, reference: &str) -> Option<String> {
+        let reference = reference.trim();
+        let key = match reference.strip_prefix(&self.base_url) {
+            Some(path) => path.strip_prefix("/browse/")?.split(['?', '#', '/']).next()?,
+            None => reference,
+        };
+        is_issue_key(key).then(|| key.to_string())
+    }
+
+    /// All issues matching a JQL query
+    pub async fn search(&self, jql: &str) -> Result<Vec<Issue>> {
+        let mut issues = Vec::new();
+        loop {
+            let page = self
+                .get(&format!(
+                    "/rest/api/2/search?jql={}&fields=summary,status&startAt={}&maxResults={}",
+                    encode_query(jql),
+                    issues.len(),
+                    PAGE_SIZE
+                ))
+                .await?;
+            let found = page["issues"].as_array().cloned().unwrap_or_default();
+            let total = page["total"].as_u64().unwrap_or(0) as usize;
+            let fetched = found.len();
+            issues.extend(found.iter().filter_map(parse_issue));
+            if fetched == 0 || issues.len() >= total {
+                return Ok(issues);
+            }
+        }
+    }
+
+    /// Fetch a single issue
+    pub async fn issue(&self, key: &str) -> Result<Issue> {
+        let value = self
+            .get(&format!("/rest/api/2/issue/{}?fields=summary,status", encode_query(key)))
+            .await?;
+        parse_issue(&value).with_context(|| format!("Unexpected response for {}", key))
+    }
+
+    async fn get(&self, path: &str) -> Result<Value> {
+        // Credentials go in on stdin so they never show up in `ps`
+        let mut config = String::from("silent\nshow-error\nfail-with-body\n");
+        config.push_str("header = \"Accept: application/json\"\n");
+        match (&self.user, &self.token) {
+            (Some(user), Some(token)) => {
+                config.push_str(&format!("user = \"{}\"\n", curl_quote(&format!("{}:{}", user, token))))
+            }
+            (None, Some(token)) => config.push_str(&format!(
+                "header = \"{}\"\n",
+                curl_quote(&format!("Authorization: Bearer {}", token))
+            )),
+            _ => {}
+        }
+        config.push_str(&format!("url = \"{}\"\n", curl_quote(&format!("{}{}", self.base_url, path))));
+
+        let mut child = Command::new("curl")
+            .args(["--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to run curl, which docket uses to reach JIRA")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(config.as_bytes()).await?;
+        }
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            let body = serde_json::from_slice::<Value>(&output.stdout).ok();
+            let message = body
+                .as_ref()
+                .and_then(|b| b["errorMessages"].as_array()?.first()?.as_str().map(str::to_string))
+                .unwrap_or_else(|| String::from_utf8_lossy(&output.stderr).trim().to_string());
+            bail!("JIRA request failed: {}", message);
+        }
+        serde_json::from_slice(&output.stdout).context("JIRA returned invalid JSON")
+    }
+}
+
+fn parse_issue(value: &Value) -> Option<Issue> {
+    Some(Issue {
+        key: value["key"].as_str()?.to_string(),
+        summary: value["fields"]["summary"].as_str()?.to_string(),
+        status: value["fields"]["status"]["name"].as_str().unwrap_or("Unknown").to_string(),
+    })
+}
+
+/// `OPS-42`: an uppercase project key, a dash and a number
+fn is_issue_key(text: &str) -> bool {
+    let Some((project, number)) = text.rsplit_once('-') else {
+        return false;
+    };
+    project.starts_with(|c: char| c.is_ascii_uppercase())
+        && project.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_keys_from_references() {
+        let client = JiraClient {
+            base_url: "https://acme.atlassian.net".to_string(),
+            user: None,
+            token: None,
+        };
+        assert_eq!(client.issue_key("OPS-42"), Some("OPS-42".to_string()));
+        assert_eq!(
+            client.issue_key("https://acme.atlassian.net/browse/WEB2-7?focusedCommentId=1"),
+            Some("WEB2-7".to_string())
+        );
+        assert_eq!(client.issue_key("https://github.com/acme/web/issues/7"), None);
+        assert_eq!(client.issue_key("ops-42"), None);
+        assert_eq!(encode_query("project = OPS AND status != Done"), "project%20%3D%20OPS%20AND%20status%20%21%3D%20Done");
+    }
+}
@@ -0,0 +1,5 @@
+// The core and the REST client as a library, for the `docket` binary and
+// for tools built on `docket-client`
+pub mod client;
+pub mod core;
+pub mod version;
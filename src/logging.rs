@@ -0,0 +1,68 @@
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+
+/// Lines of the log `:log` shows
+pub const TAIL_LINES: usize = 200;
+
+/// Where tracing output goes
+pub enum Target<'a> {
+    /// Appended to a file, without colours
+    File(&'a Path),
+    Stderr,
+    /// Nowhere, while the TUI draws on the terminal
+    Discard,
+}
+
+/// Send tracing output at `level` and above to `target`
+pub fn init(target: Target, level: LevelFilter) -> Result<()> {
+    let builder = tracing_subscriber::fmt().with_max_level(level);
+    match target {
+        Target::File(path) => {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+            }
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to open log file {}", path.display()))?;
+            builder.with_ansi(false).with_writer(Mutex::new(file)).init();
+        }
+        Target::Stderr => builder.with_writer(io::stderr).init(),
+        Target::Discard => builder.with_writer(io::sink).init(),
+    }
+    Ok(())
+}
+
+/// The last `count` lines of the log at `path`
+pub fn tail(path: &Path, count: usize) -> io::Result<Vec<String>> {
+    let file = std::fs::File::open(path)?;
+    let mut lines = VecDeque::with_capacity(count);
+    for line in io::BufReader::new(file).lines() {
+        if lines.len() == count {
+            lines.pop_front();
+        }
+        lines.push_back(line?);
+    }
+    Ok(lines.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_keeps_the_last_lines() {
+        let path = std::env::temp_dir().join(format!("docket-log-{}.log", std::process::id()));
+        let text: String = (1..=5).map(|n| format!("line {}\n", n)).collect();
+        std::fs::write(&path, text).unwrap();
+        assert_eq!(tail(&path, 2).unwrap(), ["line 4", "line 5"]);
+        assert_eq!(tail(&path, 10).unwrap().len(), 5);
+        std::fs::remove_file(&path).unwrap();
+        assert!(tail(&path, 2).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// A mail with plain-text and HTML versions of the same content
+pub struct Message<'a> {
+    pub from: Option<&'a str>,
+    pub to: &'a str,
+    pub subject: &'a str,
+    pub text: &'a str,
+    pub html: &'a str,
+}
+
+impl Message<'_> {
+    /// Render as a `multipart/alternative` message, headers included
+    pub fn to_mime(&self, boundary: &str) -> String {
+        let mut out = String::new();
+        if let Some(from) = self.from {
+            out.push_str(&format!("From: {}\r\n", from));
+        }
+        out.push_str(&format!("To: {}\r\n", self.to));
+        out.push_str(&format!("Subject: {}\r\n", self.subject));
+        out.push_str("MIME-Version: 1.0\r\n");
+        out.push_str(&format!("Content-Type: multipart/alternative; boundary=\"{}\"\r\n\r\n", boundary));
+        for (content_type, body) in [("text/plain", self.text), ("text/html", self.html)] {
+            out.push_str(&format!("--{}\r\n", boundary));
+            out.push_str(&format!("Content-Type: {}; charset=utf-8\r\n", content_type));
+            out.push_str("Content-Transfer-Encoding: 8bit\r\n\r\n");
+            out.push_str(&body.replace("\r\n", "\n").replace('\n', "\r\n"));
+            out.push_str("\r\n");
+        }
+        out.push_str(&format!("--{}--\r\n", boundary));
+        out
+    }
+}
+
+/// Hand a message to a sendmail-compatible command (`sendmail -t`,
+/// `msmtp -t`, ...), which reads the recipients from the headers and takes
+/// care of SMTP, TLS and credentials
+pub fn send(command: &str, message: &Message) -> Result<()> {
+    let mut words = command.split_whitespace();
+    let Some(program) = words.next() else {
+        bail!("mail_command is empty");
+    };
+    let boundary = format!("docket-{}", std::process::id());
+
+    let mut child = Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run '{}' (set mail_command in the config file)", command))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.to_mime(&boundary).as_bytes())?;
+    }
+    let status = child.wait()?;
+    anyhow::ensure!(status.success(), "'{}' exited with {}", command, status);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mime_message_has_both_parts() {
+        let message = Message {
+            from: None,
+            to: "me@example.com",
+            subject: "Agenda",
+            text: "# Agenda\n- one",
+            html: "<h1>Agenda</h1>",
+        };
+        let mime = message.to_mime("b");
+        assert!(mime.starts_with("To: me@example.com\r\nSubject: Agenda\r\n"));
+        assert!(mime.contains("--b\r\nContent-Type: text/plain; charset=utf-8\r\n"));
+        assert!(mime.contains("# Agenda\r\n- one\r\n--b\r\nContent-Type: text/html"));
+        assert!(mime.ends_with("--b--\r\n"));
+    }
+}
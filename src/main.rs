@@ -1,5 +1,5 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{bail, Result};
+use clap::{CommandFactory, Parser, Subcommand, ValueHint};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
@@ -7,19 +7,35 @@ use crossterm::{
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
+use std::path::PathBuf;
+use tracing::level_filters::LevelFilter;
 
+mod cli;
+mod completions;
+mod crash;
 mod config;
-mod core;
+mod daemon;
+mod git;
+mod jira;
+mod logging;
+mod mail;
+mod man;
+mod mcp;
+mod offline;
+mod plugins;
+mod remote;
 mod tui;
 mod web;
 
+use docket::{client, core, version};
+
 use config::Config;
-use core::{db::Database, service::DocketService};
+use core::{db::Database, service::DocketService, service_api::DocketServiceApi};
 use tui::{App, input, ui};
 
 /// Docket - Project-based todo manager
 #[derive(Parser)]
-#[command(name = "docket")]
+#[command(name = "docket", version = version::LONG_VERSION)]
 #[command(about = "Project-based todo manager with TUI and web interfaces", long_about = None)]
 struct Cli {
     #[command(subcommand)]
@@ -28,6 +44,61 @@ struct Cli {
     /// Port for web server (default: 3000)
     #[arg(short, long)]
     port: Option<u16>,
+
+    /// Address for web server, or unix:<path> for a Unix socket (default: 0.0.0.0)
+    #[arg(short, long)]
+    bind: Option<String>,
+
+    /// Run the web server inside the TUI, so edits in either show up in the other at once
+    #[arg(long)]
+    with_server: bool,
+
+    /// Start on the project list instead of where the last session left off
+    #[arg(long)]
+    fresh: bool,
+
+    /// Colour scheme of the TUI (default: theme from the config file)
+    #[arg(long, value_enum)]
+    theme: Option<tui::theme::Palette>,
+
+    /// Draw the TUI with ASCII characters only
+    #[arg(long)]
+    ascii: bool,
+
+    /// Icon set for todo status, due dates and projects (default: from the config file)
+    #[arg(long, value_enum)]
+    icons: Option<tui::theme::Icons>,
+
+    /// Line-by-line interface without full-screen drawing, for screen readers and braille displays
+    #[arg(long, conflicts_with = "with_server")]
+    plain: bool,
+
+    /// Open the TUI on a docket server's data through its API instead of the
+    /// local database, e.g. http://nas:3000
+    #[arg(long, value_name = "URL", conflicts_with_all = ["with_server", "port", "bind"])]
+    remote: Option<String>,
+
+    /// API token for --remote (default: api_token from the config file)
+    #[arg(long, requires = "remote")]
+    token: Option<String>,
+
+    /// Use this database file instead of the configured one
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath, conflicts_with = "profile")]
+    db: Option<PathBuf>,
+
+    /// Use a separate database kept under this name, e.g. work or personal
+    /// (default: DOCKET_PROFILE; `default` is the main database)
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Append log output to this file (default: log_file from the config
+    /// file, or ~/.local/state/docket/docket.log when given no path)
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath, num_args = 0..=1)]
+    log_file: Option<Option<PathBuf>>,
+
+    /// Least severe log output to keep: error, warn, info, debug or trace
+    #[arg(long, value_name = "LEVEL")]
+    log_level: Option<LevelFilter>,
 }
 
 #[derive(Subcommand)]
@@ -37,63 +108,555 @@ enum Commands {
         /// Port to bind to
         #[arg(short, long)]
         port: Option<u16>,
+        /// Address to bind to, or unix:<path> for a Unix socket
+        #[arg(short, long)]
+        bind: Option<String>,
+        /// Run in the background (logs to docket.log next to the database)
+        #[arg(long)]
+        daemon: bool,
+        /// Write the server's process ID here (default with --daemon: docket.pid next to the database)
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        pid_file: Option<PathBuf>,
+        /// Host a public demo: sample data in a scratch database, tight limits,
+        /// and a reset every --reset-every minutes
+        #[arg(long)]
+        demo: bool,
+        /// Minutes between demo resets
+        #[arg(long, value_name = "MINUTES", default_value_t = 60, requires = "demo",
+              value_parser = clap::value_parser!(u64).range(1..))]
+        reset_every: u64,
+    },
+    /// Open the TUI on the project for the current git repository and branch
+    Here,
+    /// Open the TUI on a todo by its short ID, e.g. `docket open HOME-42`
+    Open {
+        id: String,
+    },
+    /// Complete a todo by its short ID, e.g. `docket done HOME-42`
+    Done {
+        id: String,
+    },
+    /// Add a todo (use `-` or pipe into stdin to read the description)
+    Add {
+        /// Project to add to, created if missing (default: the git repository's
+        /// project from git_projects, else Inbox)
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Todo description, or `-` to read it from stdin
+        description: Option<String>,
+        /// Add one todo per line of stdin
+        #[arg(long)]
+        each: bool,
+        /// Copy the todo from this template (the description then replaces the template's)
+        #[arg(short, long)]
+        template: Option<String>,
+    },
+    /// Manage projects
+    Project {
+        #[command(subcommand)]
+        action: ProjectAction,
+    },
+    /// Manage todo templates, e.g. runbooks with a checklist in their details
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+    /// List todos for scripts (fzf, rofi, status bars)
+    List {
+        /// Only list todos in this project
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Include completed todos
+        #[arg(short, long)]
+        all: bool,
+        /// Only todos matching a filter, e.g. 'tag:home due:<friday'
+        #[arg(short, long)]
+        query: Option<String>,
+        /// Output template, e.g. '{{id}}\t{{project}}\t{{description}}'
+        #[arg(long, conflicts_with_all = ["json", "tsv"])]
+        format: Option<String>,
+        /// Output JSON
+        #[arg(long, conflicts_with = "tsv")]
+        json: bool,
+        /// Output tab-separated id, project, description, completed
+        #[arg(long)]
+        tsv: bool,
+    },
+    /// Print a one-line summary for tmux or Waybar
+    Status {
+        /// Output format
+        #[arg(long, value_enum, default_value = "plain")]
+        format: cli::StatusFormat,
+    },
+    /// Print (or mail) an agenda of overdue and due-soon todos
+    Digest {
+        /// Todos due this many days ahead count as due soon
+        #[arg(long, default_value_t = 7)]
+        days: i64,
+        /// Print HTML instead of Markdown
+        #[arg(long)]
+        html: bool,
+        /// Mail the agenda to this address instead of printing it
+        #[arg(long)]
+        email: Option<String>,
+    },
+    /// Create todos from another tracker
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// Mirror projects into a Markdown notes vault, importing checkbox edits made there
+    Vault {
+        /// Vault directory (default: vault_dir from the config file)
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        dir: Option<PathBuf>,
+    },
+    /// Manage the git hook that checks commits against the repository's project
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+    /// Renumber todo positions, closing gaps left by completed and deleted todos
+    Compact {
+        /// Only renumber this project
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Tag long-open todos and queue their projects for review, following groom_rules in the config file
+    Groom {
+        /// Only list what the rules would change
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Delete or archive long-completed todos, following the prune policy in the config file
+    Prune {
+        /// Only list what the policy would remove
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Serve the Model Context Protocol on stdin/stdout for LLM assistants
+    Mcp,
+    /// Print a shell completion script
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: completions::Shell,
+    },
+    /// Print the man page in roff format
+    Man,
+}
+
+#[derive(Subcommand)]
+enum ImportSource {
+    /// Import JIRA issues matching a JQL query, linked to their tickets
+    Jira {
+        /// JQL query, e.g. 'assignee = currentUser() AND resolution = Unresolved'
+        #[arg(long)]
+        jql: String,
+        /// Project to import into, created if missing (default: Inbox)
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProjectAction {
+    /// Create a project
+    Add {
+        name: String,
+        /// Project description
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+    /// Copy a project with its open todos, e.g. to start the next sprint
+    Clone {
+        name: String,
+        /// Name of the copy (default: the name with " (2)" or the next free number)
+        #[arg(long = "as")]
+        new_name: Option<String>,
+        /// Copy completed todos too, as open todos
+        #[arg(long)]
+        completed: bool,
+    },
+    /// Publish a public status page for a project at /p/<slug> on the server
+    Publish {
+        name: String,
+        /// Letters, digits and dashes, e.g. docket-roadmap
+        slug: String,
+    },
+    /// Take down a project's status page
+    Unpublish {
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplateAction {
+    /// Save a template (use `-` or pipe into stdin for the text; lines after
+    /// the first become the details)
+    Add {
+        name: String,
+        /// Todo description, or `-` to read the text from stdin
+        description: Option<String>,
+    },
+    /// List templates
+    List,
+    /// Delete a template
+    Remove {
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HookAction {
+    /// Install a commit-msg hook that blocks commits while #blocking todos are
+    /// open and lists todos completed since the last commit in the message
+    Install {
+        /// Replace an existing commit-msg hook
+        #[arg(long)]
+        force: bool,
+    },
+    /// Run the hook on a commit message file (called by git)
+    #[command(hide = true)]
+    CommitMsg {
+        file: PathBuf,
     },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
-
     let cli = Cli::parse();
 
+    // Generators only need the CLI definition, not the database
+    match cli.command {
+        Some(Commands::Hook { action: HookAction::Install { force } }) => {
+            cli::hook_install(&std::env::current_dir()?, force)?;
+            return Ok(());
+        }
+        Some(Commands::Completions { shell }) => {
+            print!("{}", completions::generate(shell, Cli::command()));
+            return Ok(());
+        }
+        Some(Commands::Man) => {
+            print!("{}", man::render(Cli::command()));
+            return Ok(());
+        }
+        _ => {}
+    }
+
     // Load configuration
-    let config = Config::load()?;
+    let mut config = Config::load()?;
+    if let Some(path) = &cli.db {
+        config.database_path = path.clone();
+    } else if let Some(profile) = cli.profile.clone().or_else(|| std::env::var("DOCKET_PROFILE").ok()) {
+        config.use_profile(&profile)?;
+    }
+    if let Some(theme) = cli.theme {
+        config.theme = theme;
+    }
+    config.ascii |= cli.ascii;
+    if let Some(icons) = cli.icons {
+        config.icons = icons;
+    }
+    if let Some(path) = &cli.log_file {
+        config.log_file = Some(path.clone().unwrap_or_else(|| config.default_log_path()));
+    }
+    config.log_level = cli.log_level.unwrap_or(config.log_level);
+
+    // Log to stderr so stdout stays clean for `list`, `status` and `mcp`. In the
+    // TUI, log lines would scribble over the screen, so it logs only to a file.
+    let target = match &config.log_file {
+        Some(path) => logging::Target::File(path),
+        None if cli.command.is_none() && !cli.plain => logging::Target::Discard,
+        None => logging::Target::Stderr,
+    };
+    logging::init(target, config.log_level)?;
+
+    // Crash reports go next to the database
+    crash::install(config.database_path.parent().unwrap_or(std::path::Path::new(".")).to_path_buf());
+
+    // Detach before touching the database; the background copy does the rest
+    if let Some(Commands::Server { daemon: true, pid_file, .. }) = &cli.command {
+        let data_dir = config.database_path.parent().unwrap_or(std::path::Path::new("."));
+        let pid_file = pid_file.clone().unwrap_or_else(|| data_dir.join("docket.pid"));
+        daemon::spawn_background(&pid_file, &data_dir.join("docket.log")).await?;
+        return Ok(());
+    }
+
+    if let Some(url) = &cli.remote {
+        if cli.command.is_some() {
+            bail!("--remote only opens the TUI; subcommands work on the local database");
+        }
+        let token = cli.token.clone().or_else(|| config.api_token.clone());
+        let remote = remote::RemoteService::new(url, token, config.timezone);
+        // Fail here rather than with the terminal already taken over
+        let cache = offline::cache_path(&config.database_path, url);
+        let service = offline::CachedService::open(remote, &cache, config.restore_positions).await?;
+        let start = Start::from_flag(cli.fresh);
+        let switch_profile = if cli.plain {
+            run_plain(service, config, start).await?
+        } else {
+            run_tui(service, config, None, start).await?
+        };
+        return switch_profile.map_or(Ok(()), |profile| reopen_on_profile(&profile));
+    }
+
+    // A demo never touches the real database, and runs no plugins for visitors
+    if let Some(Commands::Server { demo: true, .. }) = &cli.command {
+        config.database_path = std::env::temp_dir().join(format!("docket-demo-{}.db", std::process::id()));
+        config.limits = config.limits.or_demo_defaults();
+        config.plugins.clear();
+    }
 
     // Initialize database
-    let db = Database::new(&config.database_path).await?;
-    let service = DocketService::new(db);
+    let db = Database::with_pool_sizes(&config.database_path, config.pool_sizes).await?;
+    let service = DocketService::new(db)
+        .with_restore_positions(config.restore_positions)
+        .with_timezone(config.timezone)
+        .with_limits(config.limits);
+    let plugins = plugins::start(service.clone(), plugins::from_config(&config.plugins)?);
+    // Set when `:profile` asks for the TUI to reopen on another database
+    let mut switch_profile = None;
 
     match cli.command {
-        Some(Commands::Server { port }) => {
+        Some(Commands::Add { project, description, each, template }) => {
+            let project = project.or_else(|| {
+                let context = git::GitContext::detect(&std::env::current_dir().ok()?)?;
+                context.mapped_project(&config.git_projects).map(str::to_string)
+            });
+            cli::add(&service, project.as_deref(), description.as_deref(), each, template.as_deref()).await?;
+        }
+        Some(Commands::Template { action }) => match action {
+            TemplateAction::Add { name, description } => {
+                cli::template_add(&service, &name, description.as_deref()).await?
+            }
+            TemplateAction::List => cli::template_list(&service).await?,
+            TemplateAction::Remove { name } => cli::template_remove(&service, &name).await?,
+        },
+        Some(Commands::Project { action: ProjectAction::Add { name, description } }) => {
+            cli::project_add(&service, &name, description.as_deref()).await?;
+        }
+        Some(Commands::Project { action: ProjectAction::Clone { name, new_name, completed } }) => {
+            cli::project_clone(&service, &name, new_name.as_deref(), completed).await?;
+        }
+        Some(Commands::Project { action: ProjectAction::Publish { name, slug } }) => {
+            cli::project_publish(&service, &name, Some(&slug)).await?;
+        }
+        Some(Commands::Project { action: ProjectAction::Unpublish { name } }) => {
+            cli::project_publish(&service, &name, None).await?;
+        }
+        Some(Commands::Here) => {
+            let Some(context) = git::GitContext::detect(&std::env::current_dir()?) else {
+                bail!("Not inside a git repository");
+            };
+            let project = service
+                .find_or_create_project(context.project_name(&config.git_projects))
+                .await?;
+            switch_profile = run_tui(service, config, None, Start::Project(project.id)).await?;
+        }
+        Some(Commands::Open { id }) => {
+            let todo = service.find_todo_by_ref(&id).await?;
+            switch_profile = run_tui(service, config, None, Start::Todo(todo.id)).await?;
+        }
+        Some(Commands::Done { id }) => {
+            cli::done(&service, &id).await?;
+        }
+        Some(Commands::List { project, all, query, format, json, tsv }) => {
+            let format = match format {
+                Some(template) => cli::ListFormat::Template(template),
+                None if json => cli::ListFormat::Json,
+                None if tsv => cli::ListFormat::Tsv,
+                None => cli::ListFormat::Plain,
+            };
+            cli::list(&service, project.as_deref(), all, query.as_deref(), format).await?;
+        }
+        Some(Commands::Status { format }) => {
+            cli::status(&service, format).await?;
+        }
+        Some(Commands::Digest { days, html, email }) => {
+            cli::digest(&service, &config, days, html, email.as_deref()).await?;
+        }
+        Some(Commands::Import { source: ImportSource::Jira { jql, project } }) => {
+            let Some(client) = jira::JiraClient::from_config(&config) else {
+                bail!("Set jira_url (and jira_user / jira_token) in the config file");
+            };
+            cli::import_jira(&service, &client, &jql, project.as_deref()).await?;
+        }
+        Some(Commands::Vault { dir }) => {
+            let Some(dir) = dir.or(config.vault_dir.clone()) else {
+                bail!("Pass --dir or set vault_dir in the config file");
+            };
+            cli::vault(&service, &dir).await?;
+        }
+        Some(Commands::Hook { action: HookAction::CommitMsg { file } }) => {
+            cli::hook_commit_msg(&service, &config, &file).await?;
+        }
+        Some(Commands::Compact { project }) => {
+            cli::compact(&service, project.as_deref()).await?;
+        }
+        Some(Commands::Groom { dry_run }) => {
+            cli::groom(&service, &config, dry_run).await?;
+        }
+        Some(Commands::Prune { dry_run }) => {
+            cli::prune(&service, &config, dry_run).await?;
+        }
+        Some(Commands::Mcp) => {
+            mcp::serve(service).await?;
+        }
+        Some(Commands::Server { port, bind, pid_file, demo, reset_every, .. }) => {
             // Run web server
-            let port = port
-                .or_else(|| std::env::var("DOCKET_PORT").ok().and_then(|p| p.parse().ok()))
-                .unwrap_or(config.server_port);
+            let mut settings = web::ServerSettings::from(&config);
+            if demo {
+                settings.demo = true;
+                service.reset_demo().await?;
+                tokio::spawn(reset_demo_every(service.clone(), reset_every));
+                let path = config.database_path.display();
+                println!("Demo mode: sample data in {}, reset every {} minutes", path, reset_every);
+            }
+            let port = port.unwrap_or(config.server_port);
+            let bind = bind.unwrap_or(config.server_bind);
+            let _pid_file = pid_file.as_deref().map(daemon::PidFile::create).transpose()?;
 
-            web::start_server(service, port).await?;
+            web::start_server(service, settings, &bind, port).await?;
         }
-        None if cli.port.is_some() => {
-            // Port specified without subcommand, run web server
-            let port = cli.port.unwrap();
-            web::start_server(service, port).await?;
+        None if cli.with_server => {
+            // Run TUI and web server on the same service and event bus
+            let settings = web::ServerSettings::from(&config);
+            let port = cli.port.unwrap_or(config.server_port);
+            let bind = cli.bind.clone().unwrap_or_else(|| config.server_bind.clone());
+            let listener = web::Listener::bind(&bind, port).await?;
+            let url = listener.url()?;
+            let server = tokio::spawn(web::serve(service.clone(), settings, listener));
+            switch_profile = run_tui(service, config, Some(url), Start::from_flag(cli.fresh)).await?;
+            server.abort();
+        }
+        None if cli.port.is_some() || cli.bind.is_some() => {
+            // Port or address specified without subcommand, run web server
+            let settings = web::ServerSettings::from(&config);
+            let port = cli.port.unwrap_or(config.server_port);
+            let bind = cli.bind.unwrap_or(config.server_bind);
+            web::start_server(service, settings, &bind, port).await?;
+        }
+        Some(Commands::Completions { .. })
+        | Some(Commands::Man)
+        | Some(Commands::Hook { action: HookAction::Install { .. } }) => unreachable!(),
+        None if cli.plain => {
+            switch_profile = run_plain(service, config, Start::from_flag(cli.fresh)).await?;
         }
         None => {
             // Run TUI
-            run_tui(service).await?;
+            switch_profile = run_tui(service, config, None, Start::from_flag(cli.fresh)).await?;
         }
     }
 
-    Ok(())
+    plugins.flush().await;
+    match switch_profile {
+        Some(profile) => reopen_on_profile(&profile),
+        None => Ok(()),
+    }
+}
+
+/// Start docket over on profile `name`'s database (after `:profile`), with
+/// the same command and flags otherwise
+fn reopen_on_profile(name: &str) -> Result<()> {
+    let mut args = Vec::new();
+    let mut skip_value = false;
+    for arg in std::env::args_os().skip(1) {
+        if std::mem::take(&mut skip_value) {
+            continue;
+        }
+        let text = arg.to_string_lossy();
+        if text == "--profile" || text == "--db" {
+            skip_value = true;
+        } else if !text.starts_with("--profile=") && !text.starts_with("--db=") {
+            args.push(arg);
+        }
+    }
+    let mut command = std::process::Command::new(std::env::current_exe()?);
+    command.args(args).args(["--profile", name]);
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        Err(command.exec().into())
+    }
+    #[cfg(not(unix))]
+    {
+        let status = command.status()?;
+        std::process::exit(status.code().unwrap_or(1));
+    }
+}
+
+/// Where the TUI opens
+enum Start {
+    /// Reopen what was open when it last quit
+    Restore,
+    /// The project list
+    Fresh,
+    Project(i64),
+    /// A todo, selected and expanded in its project
+    Todo(i64),
+}
+
+impl Start {
+    fn from_flag(fresh: bool) -> Self {
+        if fresh { Start::Fresh } else { Start::Restore }
+    }
+}
+
+/// Put the demo's sample data back every `minutes`, whatever visitors did
+async fn reset_demo_every(service: DocketService, minutes: u64) {
+    let every = std::time::Duration::from_secs(minutes * 60);
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+    loop {
+        interval.tick().await;
+        match service.reset_demo().await {
+            Ok(()) => tracing::info!("Demo data reset"),
+            Err(err) => tracing::error!("Demo reset failed: {}", err),
+        }
+    }
 }
 
-/// Run the TUI application
-async fn run_tui(service: DocketService) -> Result<()> {
+/// Run the TUI application, with `server_url` set when the web server runs
+/// alongside. Returns the profile `:profile` asked to switch to, if any.
+async fn run_tui(
+    service: impl DocketServiceApi + 'static,
+    config: Config,
+    server_url: Option<String>,
+    start: Start,
+) -> Result<Option<String>> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    crash::set_terminal_taken(true);
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // Create app
-    let mut app = App::new(service);
+    let session_path = config.session_file_path();
+    let mut app = App::new(service, config);
+    app.apply_layout(tui::prefs::LayoutPrefs::load());
     app.init().await?;
+    match start {
+        Start::Restore => app.restore_session(tui::prefs::SessionState::load(&session_path)).await?,
+        Start::Fresh => {}
+        Start::Project(project_id) => app.open_project(project_id).await?,
+        Start::Todo(todo_id) => app.open_todo_by_id(todo_id).await?,
+    }
+    if let Some(url) = server_url {
+        app.watch_events();
+        app.set_status(format!("Web server running on {}", url));
+    }
 
     // Main loop
     let res = run_app(&mut terminal, &mut app).await;
+    let saved = app
+        .layout_prefs()
+        .save()
+        .and_then(|()| app.session_state().save(&session_path));
 
     // Restore terminal
+    crash::set_terminal_taken(false);
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -105,8 +668,27 @@ async fn run_tui(service: DocketService) -> Result<()> {
     if let Err(err) = res {
         println!("Error: {:?}", err);
     }
+    if let Err(err) = saved {
+        println!("Could not save TUI state: {:#}", err);
+    }
 
-    Ok(())
+    Ok(app.switch_profile.take())
+}
+
+/// Run the line-oriented interface on the same App as the TUI
+async fn run_plain(service: impl DocketServiceApi + 'static, config: Config, start: Start) -> Result<Option<String>> {
+    let session_path = config.session_file_path();
+    let mut app = App::new(service, config);
+    app.init().await?;
+    match start {
+        Start::Restore => app.restore_session(tui::prefs::SessionState::load(&session_path)).await?,
+        Start::Fresh => {}
+        Start::Project(project_id) => app.open_project(project_id).await?,
+        Start::Todo(todo_id) => app.open_todo_by_id(todo_id).await?,
+    }
+    tui::plain::run(&mut app).await?;
+    app.session_state().save(&session_path)?;
+    Ok(app.switch_profile.take())
 }
 
 /// Main TUI event loop
@@ -118,11 +700,56 @@ async fn run_app(
         terminal.draw(|f| ui::render(f, app))?;
 
         input::handle_input(app).await?;
+        if let Some(target) = app.editor_request.take() {
+            edit_in_editor(terminal, app, target).await?;
+        }
+        app.reload_config_if_changed().await?;
+        app.refresh_if_changed().await?;
+        app.refresh_preview();
+        app.refresh_ticket();
+        app.refresh_related();
+        app.poll_loads();
+        app.poll_tasks().await?;
 
         if app.should_quit {
+            if app.tasks.is_busy() {
+                app.set_status("Finishing background tasks...");
+                terminal.draw(|f| ui::render(f, app))?;
+                app.finish_tasks().await?;
+            }
             break;
         }
     }
 
     Ok(())
 }
+
+/// Hand the terminal to `$EDITOR` for a long text, then take it back and
+/// save what was written
+async fn edit_in_editor(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    target: tui::editor::EditorTarget,
+) -> Result<()> {
+    let original = app.editor_text(target);
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+    terminal.show_cursor()?;
+    let edited = tui::editor::edit(&original);
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+
+    match edited {
+        Ok(Some(text)) if text.trim_end() != original.trim_end() => {
+            if let Err(e) = app.save_editor_text(target, &text).await {
+                app.set_error(format!("Error saving: {}", e));
+            }
+        }
+        Ok(Some(_)) => app.set_status("No changes"),
+        Ok(None) => app.set_warning("Editor exited with an error; changes discarded"),
+        Err(e) => app.set_error(format!("Error: {}", e)),
+    }
+    Ok(())
+}
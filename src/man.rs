@@ -0,0 +1,124 @@
+use clap::{Arg, Command};
+use std::fmt::Write;
+
+/// Environment variables read by `Config::load`, documented in the man page
+const ENVIRONMENT: &[(&str, &str)] = &[
+    ("DOCKET_DB_PATH", "Path to the SQLite database (default: the platform data directory)."),
+    ("DOCKET_PORT", "Port for the web server (default: 3000)."),
+    ("DOCKET_BIND", "Address for the web server, or unix:<path> for a Unix socket (default: 0.0.0.0)."),
+    ("DOCKET_READ_ONLY", "Set to true to reject changes through the web API."),
+    ("DOCKET_CONFIG", "Path to the JSON config file (default: config.json in the config directory)."),
+    ("DOCKET_MAIL_COMMAND", "sendmail-compatible command used by digest --email (default: sendmail -t)."),
+    ("DOCKET_RESTORE_POSITIONS", "Set to false to put uncompleted todos at the end of the list instead of their old place."),
+    ("DOCKET_TIMEZONE", "Time zone for displayed times and for what counts as today: local (default), utc or an offset like +02:00."),
+    ("DOCKET_VAULT_DIR", "Notes vault directory mirrored by the vault command."),
+    ("DOCKET_THEME", "TUI colour scheme: default, high-contrast or no-color."),
+    ("DOCKET_ASCII", "Set to true to draw the TUI with ASCII characters only."),
+    ("NO_COLOR", "When set to a non-empty value, the TUI uses no colours."),
+    ("DOCKET_JIRA_TOKEN", "API token for the JIRA site set as jira_url in the config file."),
+    ("DOCKET_STALE_DAYS", "Days without activity before a finished project is suggested for archiving (default: 14)."),
+];
+
+/// Render a roff man page for the CLI definition
+pub fn render(mut cmd: Command) -> String {
+    cmd.build();
+    let name = cmd.get_name().to_string();
+    let version = cmd.get_version().unwrap_or_default().to_string();
+    let mut out = String::new();
+
+    let _ = writeln!(out, ".TH {} 1 \"\" \"{} {}\"", name.to_uppercase(), name, version);
+    let _ = writeln!(out, ".SH NAME");
+    let _ = writeln!(
+        out,
+        "{} \\- {}",
+        name,
+        escape(&cmd.get_about().map(|a| a.to_string()).unwrap_or_default())
+    );
+
+    let _ = writeln!(out, ".SH SYNOPSIS");
+    let _ = writeln!(out, "\\fB{}\\fR [\\fIOPTIONS\\fR] [\\fICOMMAND\\fR]", name);
+
+    let _ = writeln!(out, ".SH DESCRIPTION");
+    let _ = writeln!(
+        out,
+        "Without a command, {} opens the terminal interface. \
+         The web interface is started with \\fBserver\\fR or \\fB\\-\\-port\\fR.",
+        name
+    );
+
+    let _ = writeln!(out, ".SH OPTIONS");
+    write_options(&mut out, &cmd);
+
+    let _ = writeln!(out, ".SH COMMANDS");
+    for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+        let positionals: Vec<String> = sub
+            .get_arguments()
+            .filter(|a| a.is_positional() && !a.is_hide_set())
+            .map(|a| format!(" \\fI<{}>\\fR", a.get_id()))
+            .collect();
+        let _ = writeln!(out, ".TP");
+        let _ = writeln!(out, "\\fB{}\\fR{}", sub.get_name(), positionals.concat());
+        let _ = writeln!(out, "{}", escape(&sub.get_about().map(|a| a.to_string()).unwrap_or_default()));
+        for arg in sub.get_arguments().filter(|a| a.is_positional() && !a.is_hide_set()) {
+            let values: Vec<String> = arg
+                .get_possible_values()
+                .iter()
+                .map(|v| v.get_name().to_string())
+                .collect();
+            if !values.is_empty() {
+                let _ = writeln!(out, "(\\fI{}\\fR: {})", arg.get_id(), values.join(", "));
+            }
+        }
+        if sub.get_arguments().any(|a| !a.is_positional() && !a.is_hide_set()) {
+            let _ = writeln!(out, ".RS");
+            write_options(&mut out, sub);
+            let _ = writeln!(out, ".RE");
+        }
+    }
+
+    let _ = writeln!(out, ".SH ENVIRONMENT");
+    for (var, description) in ENVIRONMENT {
+        let _ = writeln!(out, ".TP");
+        let _ = writeln!(out, "\\fB{}\\fR", var);
+        let _ = writeln!(out, "{}", escape(description));
+    }
+
+    out
+}
+
+/// Write a `.TP` entry for each flag of `cmd`
+fn write_options(out: &mut String, cmd: &Command) {
+    for arg in cmd.get_arguments().filter(|a| !a.is_positional() && !a.is_hide_set()) {
+        let _ = writeln!(out, ".TP");
+        let _ = writeln!(out, "{}", flag_names(arg));
+        let _ = writeln!(out, "{}", escape(&arg.get_help().map(|h| h.to_string()).unwrap_or_default()));
+    }
+}
+
+/// `\fB-p\fR, \fB--port\fR \fI<PORT>\fR`
+fn flag_names(arg: &Arg) -> String {
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("\\fB\\-{}\\fR", short));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("\\fB\\-\\-{}\\fR", long));
+    }
+    let mut line = names.join(", ");
+    if arg.get_action().takes_values() {
+        for value in arg.get_value_names().unwrap_or_default() {
+            let _ = write!(line, " \\fI<{}>\\fR", value);
+        }
+    }
+    line
+}
+
+/// Escape text for roff: backslashes, hyphens and leading control characters
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\\\").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::core::models::Project;
+use crate::core::service::DocketService;
+
+/// MCP protocol revision this server speaks
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serve the Model Context Protocol over stdin/stdout, one JSON-RPC message per line
+pub async fn serve(service: DocketService) -> Result<()> {
+    let server = McpServer { service };
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => server.handle(message).await,
+            Err(e) => Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        if let Some(response) = response {
+            stdout.write_all(format!("{}\n", response).as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+
+    Ok(())
+}
+
+struct McpServer {
+    service: DocketService,
+}
+
+impl McpServer {
+    /// Handle one JSON-RPC message. Notifications (no `id`) get no response.
+    async fn handle(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned()?;
+        let method = message.get("method").and_then(Value::as_str).unwrap_or_default();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "docket", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            "ping" => json!({}),
+            "tools/list" => json!({ "tools": tools() }),
+            "tools/call" => {
+                let Some(name) = params.get("name").and_then(Value::as_str) else {
+                    return Some(error_response(id, INVALID_PARAMS, "Missing tool name"));
+                };
+                let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+                // Tool failures are reported to the model, not as protocol errors
+                match self.call_tool(name, &arguments).await {
+                    Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+                    Err(e) => json!({
+                        "content": [{ "type": "text", "text": e.to_string() }],
+                        "isError": true,
+                    }),
+                }
+            }
+            other => {
+                return Some(error_response(
+                    id,
+                    METHOD_NOT_FOUND,
+                    &format!("Method not found: {}", other),
+                ));
+            }
+        };
+
+        Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    /// Run a tool and return its output as text for the assistant
+    async fn call_tool(&self, name: &str, args: &Value) -> Result<String> {
+        match name {
+            "list_projects" => {
+                let projects = self.service.list_active_projects().await?;
+                Ok(serde_json::to_string_pretty(&projects)?)
+            }
+            "list_todos" => {
+                let project = self.project_arg(args).await?;
+                let todos = if args.get("include_completed").and_then(Value::as_bool).unwrap_or(false) {
+                    self.service.list_all_todos(project.id).await?
+                } else {
+                    self.service.list_active_todos(project.id).await?
+                };
+                Ok(serde_json::to_string_pretty(&todos)?)
+            }
+            "create_todo" => {
+                let project_name = str_arg(args, "project")?;
+                let project = self.service.find_or_create_project(project_name).await?;
+                let todo = self.service.create_todo(project.id, str_arg(args, "description")?).await?;
+                if let Some(details) = args.get("details").and_then(Value::as_str) {
+                    self.service.update_todo_details(todo.id, Some(details)).await?;
+                }
+                Ok(format!("Created todo #{} in {}", todo.id, project.name))
+            }
+            "complete_todo" => {
+                let id = args
+                    .get("id")
+                    .and_then(Value::as_i64)
+                    .ok_or_else(|| anyhow!("Missing integer argument 'id'"))?;
+                let todo = self.service.get_todo(id).await.context("Todo not found")?;
+                if todo.is_completed() {
+                    return Ok(format!("Todo #{} was already completed", id));
+                }
+                self.service.toggle_todo(id).await?;
+                Ok(format!("Completed todo #{}: {}", id, todo.description))
+            }
+            other => bail!("Unknown tool: {}", other),
+        }
+    }
+
+    /// Resolve the required `project` argument to an existing project
+    async fn project_arg(&self, args: &Value) -> Result<Project> {
+        let name = str_arg(args, "project")?;
+        self.service
+            .find_project_by_name(name)
+            .await?
+            .ok_or_else(|| anyhow!("No project named '{}'", name))
+    }
+}
+
+/// Read a required string argument
+fn str_arg<'a>(args: &'a Value, key: &str) -> Result<&'a str> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("Missing string argument '{}'", key))
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Tool definitions advertised by `tools/list`
+fn tools() -> Value {
+    json!([
+        {
+            "name": "list_projects",
+            "description": "List active projects with todo counts",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "list_todos",
+            "description": "List the todos in a project",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": { "type": "string", "description": "Project name (case-insensitive)" },
+                    "include_completed": { "type": "boolean", "description": "Also list completed todos" },
+                },
+                "required": ["project"],
+            },
+        },
+        {
+            "name": "create_todo",
+            "description": "Add a todo to a project, creating the project if it doesn't exist",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": { "type": "string", "description": "Project name (case-insensitive)" },
+                    "description": { "type": "string", "description": "One-line todo description" },
+                    "details": { "type": "string", "description": "Optional longer notes" },
+                },
+                "required": ["project", "description"],
+            },
+        },
+        {
+            "name": "complete_todo",
+            "description": "Mark a todo as completed",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer", "description": "Todo ID" },
+                },
+                "required": ["id"],
+            },
+        },
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::service::test_service;
+
+    #[tokio::test]
+    async fn test_tool_calls_round_trip() {
+        let server = McpServer { service: test_service().await };
+
+        let call = |name: &str, arguments: Value| {
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+                    "params": { "name": name, "arguments": arguments } })
+        };
+
+        let response = server
+            .handle(call("create_todo", json!({ "project": "Work", "description": "Ship it" })))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["content"][0]["text"], "Created todo #1 in Work");
+
+        let response = server.handle(call("complete_todo", json!({ "id": 1 }))).await.unwrap();
+        assert!(response["result"].get("isError").is_none());
+        assert!(server.service.get_todo(1).await.unwrap().is_completed());
+
+        let response = server.handle(call("list_todos", json!({ "project": "Nope" }))).await.unwrap();
+        assert_eq!(response["result"]["isError"], true);
+
+        // Notifications get no reply, unknown methods an error
+        assert!(server.handle(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await.is_none());
+        let response = server.handle(json!({ "jsonrpc": "2.0", "id": 2, "method": "bogus" })).await.unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+}
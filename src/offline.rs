@@ -0,0 +1,702 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::core::audit::{AuditPage, AuditQuery};
+use crate::core::batch::{BatchOp, BatchResult};
+use crate::core::db::Database;
+use crate::core::error::{DocketError, Result};
+use crate::core::events::EventBus;
+use crate::core::groom::{GroomChange, GroomRule};
+use crate::core::prune::{PrunePolicy, PrunedTodo};
+use crate::core::models::{
+    LinkKind, Project, ProjectSettings, ProjectStats, ProjectWithStats, RelatedTodo, Todo,
+    TodoContext, TodoLink, TodoMention, TodoPatch, TodoTemplate, TodoVersion, Totals, Workspace,
+};
+use crate::core::service::DocketService;
+use crate::core::service_api::DocketServiceApi;
+use crate::core::share::{ShareLink, SharedProject};
+use crate::core::sort::TodoOrder;
+use crate::core::sync::{ChangeMarker, ChangeSet, ConflictReason, Entity, ProjectChange, PushChange, PushResult, TodoChange};
+use crate::core::time::DisplayZone;
+use crate::remote::RemoteService;
+
+/// Projects and todos created offline get IDs from here up, out of the way
+/// of the server's, which count up from 1
+const LOCAL_ID_FLOOR: i64 = 1 << 40;
+
+/// How long to work offline before trying the server again
+const RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// `docket --remote` with a copy of the server's data on this machine.
+/// Reads come from the copy; changes go to the server and are pulled back.
+/// While the server can't be reached, changes are made to the copy alone,
+/// and once it's back they are sent with `POST /api/changes`, where an
+/// edit older than the server's own loses and is reported as a notice.
+///
+/// A push carries what the sync API does: a todo's text, details,
+/// completion, estimate, due date and link, and a project's name,
+/// description, target date and archiving. Reordering and reviews done
+/// offline are replaced by the server's state once it's back.
+pub struct CachedService {
+    remote: RemoteService,
+    db: Database,
+    cache: DocketService,
+    online: AtomicBool,
+    /// When the server was last tried, to wait `RETRY_AFTER` while offline
+    tried_at: Mutex<Instant>,
+    /// Held while syncing and while changing the copy offline, so no edit
+    /// slips in between reading what to push and recording the pull
+    sync_lock: tokio::sync::Mutex<()>,
+    notices: Mutex<Vec<String>>,
+    /// The last sync failure reported, so a lasting one is shown once
+    last_error: Mutex<Option<String>>,
+}
+
+impl CachedService {
+    /// Open the copy at `path` and bring it up to date. Without the server
+    /// it starts offline on what was pulled last time, or fails if nothing was.
+    pub async fn open(remote: RemoteService, path: &Path, restore_positions: bool) -> Result<Self> {
+        let db = Database::new(path).await?;
+        db.reserve_ids_from(LOCAL_ID_FLOOR).await?;
+        let cache = DocketService::new(db.clone())
+            .with_restore_positions(restore_positions)
+            .with_timezone(remote.timezone());
+        let service = Self {
+            remote,
+            db,
+            cache,
+            online: AtomicBool::new(true),
+            tried_at: Mutex::new(Instant::now()),
+            sync_lock: tokio::sync::Mutex::new(()),
+            notices: Mutex::new(Vec::new()),
+            last_error: Mutex::new(None),
+        };
+        match service.sync(true).await {
+            Ok(()) => Ok(service),
+            Err(DocketError::Offline(msg)) if service.db.pull_state().await?.is_some() => {
+                service.go_offline(&msg);
+                Ok(service)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Send edits made offline, then pull what changed on the server. After
+    /// a push, or with `full`, everything is pulled, so the copy ends up
+    /// exactly like the server.
+    async fn sync(&self, full: bool) -> Result<()> {
+        let _guard = self.sync_lock.lock().await;
+        *self.tried_at.lock().unwrap() = Instant::now();
+        let state = self.db.pull_state().await?;
+        let mut snapshot = full || state.is_none();
+
+        if let Some(state) = state {
+            let local = self.cache.changes_since(state.local_version).await?;
+            let changes = pending_changes(&local);
+            if !changes.is_empty() {
+                let sent = changes.len();
+                let result = self.remote.apply_changes(changes).await?;
+                self.notice(push_summary(sent, &result, &local));
+                snapshot = true;
+            }
+        }
+
+        let since = match state {
+            Some(state) if !snapshot => state.server_cursor,
+            _ => 0,
+        };
+        let changes = self.remote.changes_since(since).await?;
+        if snapshot {
+            self.db.replace_templates(&self.remote.list_templates().await?).await?;
+            self.db.replace_workspaces(&self.remote.list_workspaces().await?).await?;
+        }
+        let moved = state.is_none_or(|state| state.server_cursor != changes.cursor);
+        if snapshot || moved {
+            self.db.store_pulled(&changes, snapshot).await?;
+        }
+        Ok(())
+    }
+
+    /// Sync if the server is up or due to be tried again, and tell the
+    /// user when it goes away, comes back or keeps failing
+    async fn refresh(&self) {
+        if !self.online.load(Ordering::SeqCst) && self.tried_at.lock().unwrap().elapsed() < RETRY_AFTER {
+            return;
+        }
+        match self.sync(false).await {
+            Ok(()) => {
+                *self.last_error.lock().unwrap() = None;
+                if !self.online.swap(true, Ordering::SeqCst) {
+                    self.notice("Back online".to_string());
+                }
+            }
+            Err(DocketError::Offline(msg)) => self.go_offline(&msg),
+            Err(err) => {
+                let msg = format!("Sync failed: {}", err);
+                let mut last = self.last_error.lock().unwrap();
+                if last.as_deref() != Some(msg.as_str()) {
+                    *last = Some(msg.clone());
+                    drop(last);
+                    self.notice(msg);
+                }
+            }
+        }
+    }
+
+    fn go_offline(&self, msg: &str) {
+        *self.tried_at.lock().unwrap() = Instant::now();
+        if self.online.swap(false, Ordering::SeqCst) {
+            self.notice(format!("{}; working offline, changes are sent once it's back", msg));
+        }
+    }
+
+    fn notice(&self, msg: String) {
+        self.notices.lock().unwrap().push(msg);
+    }
+
+    /// Make a change on the server and pull it into the copy, or while the
+    /// server can't be reached, make it in the copy to send later. Only a
+    /// request that never reached the server counts as offline; one that
+    /// went unanswered may have been applied, so it fails instead of being
+    /// made a second time here.
+    async fn write<T>(
+        &self,
+        remote: impl Future<Output = Result<T>> + Send,
+        local: impl Future<Output = Result<T>> + Send,
+    ) -> Result<T> {
+        if self.online.load(Ordering::SeqCst) {
+            match remote.await {
+                Ok(value) => {
+                    self.refresh().await;
+                    return Ok(value);
+                }
+                Err(DocketError::Offline(msg)) => self.go_offline(&msg),
+                Err(err) => return Err(err),
+            }
+        }
+        let _guard = self.sync_lock.lock().await;
+        local.await
+    }
+
+    /// Run a change on the copy and send it like an offline edit
+    async fn write_locally<T>(&self, local: impl Future<Output = Result<T>> + Send) -> Result<T> {
+        let value = {
+            let _guard = self.sync_lock.lock().await;
+            local.await?
+        };
+        self.refresh().await;
+        Ok(value)
+    }
+}
+
+/// Where the copy of the server at `url` lives: next to the local
+/// database, named after the server, e.g. `remote-nas-3000.db`
+pub fn cache_path(database_path: &Path, url: &str) -> PathBuf {
+    let host = url.split_once("://").map_or(url, |(_, rest)| rest).trim_end_matches('/');
+    let name: String = host.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '-' }).collect();
+    database_path.with_file_name(format!("remote-{}.db", name))
+}
+
+/// Edits made in the copy, as a push. Rows created offline go by a `ref`
+/// instead of an ID, and deleting one needs no push at all.
+fn pending_changes(local: &ChangeSet) -> Vec<PushChange> {
+    let is_local = |id: i64| id >= LOCAL_ID_FLOOR;
+    let server_id = |id: i64| (!is_local(id)).then_some(id);
+
+    let projects = local.projects.iter().map(|project| {
+        PushChange::Project(ProjectChange {
+            id: server_id(project.id),
+            client_ref: is_local(project.id).then(|| format!("project-{}", project.id)),
+            name: project.name.clone(),
+            description: project.description.clone(),
+            archived: project.is_archived(),
+            target_date: project.target_date,
+            updated_at: project.updated_at,
+        })
+    });
+    let todos = local.todos.iter().map(|todo| {
+        PushChange::Todo(TodoChange {
+            id: server_id(todo.id),
+            client_ref: is_local(todo.id).then(|| format!("todo-{}", todo.id)),
+            project_id: server_id(todo.project_id),
+            project_ref: is_local(todo.project_id).then(|| format!("project-{}", todo.project_id)),
+            description: todo.description.clone(),
+            details: todo.details.clone(),
+            completed: todo.is_completed(),
+            estimate: todo.estimate,
+            due_date: todo.due_date,
+            external_ref: todo.external_ref.clone(),
+            updated_at: todo.updated_at,
+        })
+    });
+    let deleted = local.deleted.iter().filter(|t| !is_local(t.entity_id)).map(|tombstone| PushChange::Delete {
+        entity: if tombstone.entity == "project" { Entity::Project } else { Entity::Todo },
+        id: tombstone.entity_id,
+    });
+    projects.chain(todos).chain(deleted).collect()
+}
+
+/// `Sent 3 changes made offline; not applied: todo 'Buy milk' (changed on
+/// the server since)`
+fn push_summary(sent: usize, result: &PushResult, local: &ChangeSet) -> String {
+    let noun = if sent == 1 { "change" } else { "changes" };
+    let mut summary = format!("Sent {} {} made offline", sent, noun);
+    if result.conflicts.is_empty() {
+        return summary;
+    }
+    let lost: Vec<String> = result
+        .conflicts
+        .iter()
+        .map(|conflict| {
+            let what = match conflict.entity {
+                Entity::Project => match local.projects.iter().find(|p| p.id == conflict.id) {
+                    Some(project) => format!("project '{}'", project.name),
+                    None => format!("project {}", conflict.id),
+                },
+                Entity::Todo => match local.todos.iter().find(|t| t.id == conflict.id) {
+                    Some(todo) => format!("todo '{}'", todo.description),
+                    None => format!("todo {}", conflict.id),
+                },
+            };
+            let why = match conflict.reason {
+                ConflictReason::NewerOnServer => "changed on the server since",
+                ConflictReason::DeletedOnServer => "deleted on the server",
+            };
+            format!("{} ({})", what, why)
+        })
+        .collect();
+    summary.push_str(&format!("; not applied: {}", lost.join(", ")));
+    summary
+}
+
+#[async_trait]
+impl DocketServiceApi for CachedService {
+    fn timezone(&self) -> DisplayZone {
+        self.cache.timezone()
+    }
+
+    fn events(&self) -> &EventBus {
+        self.cache.events()
+    }
+
+    fn take_notices(&self) -> Vec<String> {
+        std::mem::take(&mut *self.notices.lock().unwrap())
+    }
+
+    async fn create_project(&self, name: &str) -> Result<Project> {
+        self.write(self.remote.create_project(name), self.cache.create_project(name)).await
+    }
+
+    async fn create_project_with_description(&self, name: &str, description: Option<&str>) -> Result<Project> {
+        self.write(
+            self.remote.create_project_with_description(name, description),
+            self.cache.create_project_with_description(name, description),
+        )
+        .await
+    }
+
+    async fn clone_project(&self, id: i64, name: Option<&str>, include_completed: bool) -> Result<Project> {
+        self.write(
+            self.remote.clone_project(id, name, include_completed),
+            self.cache.clone_project(id, name, include_completed),
+        )
+        .await
+    }
+
+    async fn get_project(&self, id: i64) -> Result<Project> {
+        self.cache.get_project(id).await
+    }
+
+    async fn get_project_with_stats(&self, id: i64) -> Result<ProjectWithStats> {
+        self.cache.get_project_with_stats(id).await
+    }
+
+    async fn get_project_stats(&self, id: i64, weeks: i64, zone: DisplayZone) -> Result<ProjectStats> {
+        self.cache.get_project_stats(id, weeks, zone).await
+    }
+
+    async fn get_totals(&self) -> Result<Totals> {
+        self.cache.get_totals().await
+    }
+
+    async fn update_project_description(&self, id: i64, description: Option<&str>) -> Result<()> {
+        self.write(
+            self.remote.update_project_description(id, description),
+            self.cache.update_project_description(id, description),
+        )
+        .await
+    }
+
+    async fn update_project_target_date(&self, id: i64, target_date: Option<NaiveDate>) -> Result<()> {
+        self.write(
+            self.remote.update_project_target_date(id, target_date),
+            self.cache.update_project_target_date(id, target_date),
+        )
+        .await
+    }
+
+    async fn update_project_name(&self, id: i64, name: &str) -> Result<()> {
+        self.write(self.remote.update_project_name(id, name), self.cache.update_project_name(id, name)).await
+    }
+
+    async fn replace_project(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+        target_date: Option<NaiveDate>,
+        archived: bool,
+    ) -> Result<Project> {
+        self.write(
+            self.remote.replace_project(id, name, description, target_date, archived),
+            self.cache.replace_project(id, name, description, target_date, archived),
+        )
+        .await
+    }
+
+    async fn suggest_project_name(&self, name: &str) -> Result<String> {
+        self.cache.suggest_project_name(name).await
+    }
+
+    async fn list_active_projects(&self) -> Result<Vec<ProjectWithStats>> {
+        self.cache.list_active_projects().await
+    }
+
+    async fn list_all_projects(&self) -> Result<Vec<ProjectWithStats>> {
+        self.cache.list_all_projects().await
+    }
+
+    async fn archive_stale_projects(&self, stale_after_days: i64) -> Result<Vec<ProjectWithStats>> {
+        // The API has no endpoint for it; the archiving is sent as edits
+        self.write_locally(self.cache.archive_stale_projects(stale_after_days)).await
+    }
+
+    async fn groom(&self, rules: &[GroomRule], dry_run: bool) -> Result<Vec<GroomChange>> {
+        self.write_locally(self.cache.groom(rules, dry_run)).await
+    }
+
+    async fn prune(&self, policy: &PrunePolicy, dry_run: bool) -> Result<Vec<PrunedTodo>> {
+        self.write_locally(self.cache.prune(policy, dry_run)).await
+    }
+
+    async fn list_projects_due_for_review(&self) -> Result<Vec<ProjectWithStats>> {
+        self.cache.list_projects_due_for_review().await
+    }
+
+    async fn mark_project_reviewed(&self, id: i64) -> Result<()> {
+        self.write(self.remote.mark_project_reviewed(id), self.cache.mark_project_reviewed(id)).await
+    }
+
+    async fn archive_project(&self, id: i64) -> Result<()> {
+        self.write(self.remote.archive_project(id), self.cache.archive_project(id)).await
+    }
+
+    async fn unarchive_project(&self, id: i64) -> Result<()> {
+        self.write(self.remote.unarchive_project(id), self.cache.unarchive_project(id)).await
+    }
+
+    async fn delete_project(&self, id: i64) -> Result<()> {
+        self.write(self.remote.delete_project(id), self.cache.delete_project(id)).await
+    }
+
+    async fn get_project_settings(&self, project_id: i64) -> Result<ProjectSettings> {
+        self.cache.get_project_settings(project_id).await
+    }
+
+    async fn save_project_settings(&self, project_id: i64, settings: &ProjectSettings) -> Result<()> {
+        self.cache.save_project_settings(project_id, settings).await
+    }
+
+    async fn create_todo(&self, project_id: i64, description: &str) -> Result<Todo> {
+        self.write(
+            self.remote.create_todo(project_id, description),
+            self.cache.create_todo(project_id, description),
+        )
+        .await
+    }
+
+    async fn create_todo_with_details(
+        &self,
+        project_id: i64,
+        description: &str,
+        details: Option<&str>,
+    ) -> Result<Todo> {
+        self.write(
+            self.remote.create_todo_with_details(project_id, description, details),
+            self.cache.create_todo_with_details(project_id, description, details),
+        )
+        .await
+    }
+
+    async fn list_all_todos(&self, project_id: i64) -> Result<Vec<Todo>> {
+        self.cache.list_all_todos(project_id).await
+    }
+
+    async fn list_active_todos(&self, project_id: i64) -> Result<Vec<Todo>> {
+        self.cache.list_active_todos(project_id).await
+    }
+
+    async fn list_sorted_todos(&self, project_id: i64, include_completed: bool, order: &TodoOrder) -> Result<Vec<Todo>> {
+        self.cache.list_sorted_todos(project_id, include_completed, order).await
+    }
+
+    async fn toggle_todo(&self, id: i64) -> Result<()> {
+        self.write(self.remote.toggle_todo(id), self.cache.toggle_todo(id)).await
+    }
+
+    async fn delete_todo(&self, id: i64) -> Result<()> {
+        self.write(self.remote.delete_todo(id), self.cache.delete_todo(id)).await
+    }
+
+    async fn get_todo(&self, id: i64) -> Result<Todo> {
+        self.cache.get_todo(id).await
+    }
+
+    async fn get_todo_context(&self, id: i64) -> Result<TodoContext> {
+        self.cache.get_todo_context(id).await
+    }
+
+    async fn update_todo_details(&self, id: i64, details: Option<&str>) -> Result<()> {
+        self.write(self.remote.update_todo_details(id, details), self.cache.update_todo_details(id, details)).await
+    }
+
+    async fn toggle_checklist_item(&self, id: i64, index: usize) -> Result<()> {
+        self.write(self.remote.toggle_checklist_item(id, index), self.cache.toggle_checklist_item(id, index)).await
+    }
+
+    async fn update_todo_estimate(&self, id: i64, estimate: Option<i64>) -> Result<()> {
+        self.write(self.remote.update_todo_estimate(id, estimate), self.cache.update_todo_estimate(id, estimate))
+            .await
+    }
+
+    async fn update_todo_due_date(&self, id: i64, due_date: Option<NaiveDate>) -> Result<()> {
+        self.write(self.remote.update_todo_due_date(id, due_date), self.cache.update_todo_due_date(id, due_date))
+            .await
+    }
+
+    async fn update_todo_external_ref(&self, id: i64, external_ref: Option<&str>) -> Result<()> {
+        self.write(
+            self.remote.update_todo_external_ref(id, external_ref),
+            self.cache.update_todo_external_ref(id, external_ref),
+        )
+        .await
+    }
+
+    async fn list_todos_due_between(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Todo>> {
+        self.cache.list_todos_due_between(from, to).await
+    }
+
+    async fn list_open_todos(&self) -> Result<Vec<Todo>> {
+        self.cache.list_open_todos().await
+    }
+
+    async fn list_open_todos_due_by(&self, date: NaiveDate) -> Result<Vec<Todo>> {
+        self.cache.list_open_todos_due_by(date).await
+    }
+
+    async fn update_todo(&self, id: i64, description: &str) -> Result<()> {
+        self.write(self.remote.update_todo(id, description), self.cache.update_todo(id, description)).await
+    }
+
+    async fn move_todo_up(&self, id: i64) -> Result<()> {
+        self.write(self.remote.move_todo_up(id), self.cache.move_todo_up(id)).await
+    }
+
+    async fn defer_todo(&self, id: i64) -> Result<()> {
+        self.write(self.remote.defer_todo(id), self.cache.defer_todo(id)).await
+    }
+
+    async fn move_todo_down(&self, id: i64) -> Result<()> {
+        self.write(self.remote.move_todo_down(id), self.cache.move_todo_down(id)).await
+    }
+
+    async fn patch_todo(&self, id: i64, patch: &TodoPatch) -> Result<Todo> {
+        self.write(self.remote.patch_todo(id, patch), self.cache.patch_todo(id, patch)).await
+    }
+
+    /// The copy's own history, which starts when the copy was made: it
+    /// records the edits pulled from the server as well as offline ones
+    async fn todo_history(&self, todo_id: i64) -> Result<Vec<TodoVersion>> {
+        self.cache.todo_history(todo_id).await
+    }
+
+    async fn restore_todo_version(&self, todo_id: i64, version_id: i64) -> Result<Todo> {
+        // Version IDs are the copy's, so the server gets the text as an edit
+        let version = self.cache.get_todo_version(todo_id, version_id).await?;
+        let patch = TodoPatch {
+            description: Some(version.description),
+            details: Some(version.details),
+            ..Default::default()
+        };
+        self.patch_todo(todo_id, &patch).await
+    }
+
+    async fn link_todos(&self, todo_id: i64, target_id: i64, kind: LinkKind) -> Result<TodoLink> {
+        // Links aren't part of the sync snapshot, so they live on the server
+        self.remote.link_todos(todo_id, target_id, kind).await
+    }
+
+    async fn related_todos(&self, todo_id: i64) -> Result<Vec<RelatedTodo>> {
+        self.remote.related_todos(todo_id).await
+    }
+
+    async fn unlink_todos(&self, link_id: i64) -> Result<()> {
+        self.remote.unlink_todos(link_id).await
+    }
+
+    /// Mentions come from the details, which the copy keeps too
+    async fn todo_mentions(&self, todo_id: i64) -> Result<Vec<TodoMention>> {
+        self.cache.todo_mentions(todo_id).await
+    }
+
+    async fn normalize_positions(&self, project_id: i64) -> Result<usize> {
+        self.write(self.remote.normalize_positions(project_id), self.cache.normalize_positions(project_id)).await
+    }
+
+    async fn share_project(&self, id: i64, days: i64) -> Result<ShareLink> {
+        // Links are signed with the server's key, so only it can make them
+        self.remote.share_project(id, days).await
+    }
+
+    async fn shared_project(&self, token: &str) -> Result<SharedProject> {
+        self.remote.shared_project(token).await
+    }
+
+    async fn update_project_slug(&self, id: i64, slug: Option<&str>) -> Result<Project> {
+        let project = self.remote.update_project_slug(id, slug).await?;
+        self.refresh().await;
+        Ok(project)
+    }
+
+    async fn public_project(&self, slug: &str) -> Result<Option<SharedProject>> {
+        self.remote.public_project(slug).await
+    }
+
+    async fn list_workspaces(&self) -> Result<Vec<Workspace>> {
+        self.cache.list_workspaces().await
+    }
+
+    // Workspaces are made on the server, then copied down whole
+
+    async fn create_workspace(&self, name: &str) -> Result<Workspace> {
+        let workspace = self.remote.create_workspace(name).await?;
+        self.db.replace_workspaces(&self.remote.list_workspaces().await?).await?;
+        Ok(workspace)
+    }
+
+    async fn rename_workspace(&self, id: i64, name: &str) -> Result<Workspace> {
+        let workspace = self.remote.rename_workspace(id, name).await?;
+        self.db.replace_workspaces(&self.remote.list_workspaces().await?).await?;
+        Ok(workspace)
+    }
+
+    async fn delete_workspace(&self, id: i64) -> Result<()> {
+        self.remote.delete_workspace(id).await?;
+        self.db.replace_workspaces(&self.remote.list_workspaces().await?).await?;
+        self.refresh().await;
+        Ok(())
+    }
+
+    async fn update_project_workspace(&self, id: i64, workspace_id: Option<i64>) -> Result<Project> {
+        let project = self.remote.update_project_workspace(id, workspace_id).await?;
+        self.refresh().await;
+        Ok(project)
+    }
+
+    async fn update_project_parent(&self, id: i64, parent_id: Option<i64>) -> Result<Project> {
+        let project = self.remote.update_project_parent(id, parent_id).await?;
+        self.refresh().await;
+        Ok(project)
+    }
+
+    async fn project_path(&self, id: i64) -> Result<Vec<Project>> {
+        self.cache.project_path(id).await
+    }
+
+    async fn list_templates(&self) -> Result<Vec<TodoTemplate>> {
+        self.cache.list_templates().await
+    }
+
+    async fn create_todo_from_template(&self, project_id: i64, name: &str, description: Option<&str>) -> Result<Todo> {
+        self.write(
+            self.remote.create_todo_from_template(project_id, name, description),
+            self.cache.create_todo_from_template(project_id, name, description),
+        )
+        .await
+    }
+
+    async fn apply_batch(&self, ops: Vec<BatchOp>) -> Result<Vec<BatchResult>> {
+        self.write(self.remote.apply_batch(ops.clone()), self.cache.apply_batch(ops)).await
+    }
+
+    async fn audit_log(&self, query: &AuditQuery) -> Result<AuditPage> {
+        // The cache's log only holds what synced down, not who did it
+        self.remote.audit_log(query).await
+    }
+
+    async fn changes_since(&self, since: i64) -> Result<ChangeSet> {
+        self.cache.changes_since(since).await
+    }
+
+    async fn change_marker(&self) -> Result<ChangeMarker> {
+        // The TUI polls this, which makes it the place to sync
+        self.refresh().await;
+        self.cache.change_marker().await
+    }
+
+    async fn apply_changes(&self, changes: Vec<PushChange>) -> Result<PushResult> {
+        self.cache.apply_changes(changes).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_path_names_the_server() {
+        let path = cache_path(Path::new("/data/docket/docket.db"), "http://nas.local:3000/");
+        assert_eq!(path, Path::new("/data/docket/remote-nas-local-3000.db"));
+    }
+
+    #[tokio::test]
+    async fn test_offline_rows_are_pushed_by_ref() {
+        let db = Database::open_temp().await.unwrap();
+        let service = DocketService::new(db.clone());
+        let home = service.create_project("Home").await.unwrap();
+        let milk = service.create_todo(home.id, "Buy milk").await.unwrap();
+        let since = service.change_marker().await.unwrap().version;
+
+        service.toggle_todo(milk.id).await.unwrap();
+        db.reserve_ids_from(LOCAL_ID_FLOOR).await.unwrap();
+        let trip = service.create_project("Trip").await.unwrap();
+        let tent = service.create_todo(trip.id, "Pack tent").await.unwrap();
+        service.delete_todo(tent.id).await.unwrap();
+        service.create_todo(trip.id, "Book train").await.unwrap();
+
+        let changes = pending_changes(&service.changes_since(since).await.unwrap());
+        let summary: Vec<String> = changes
+            .iter()
+            .map(|change| match change {
+                PushChange::Project(p) => format!("project {:?} {:?} {}", p.id, p.client_ref, p.name),
+                PushChange::Todo(t) => {
+                    format!("todo {:?} {:?} {:?} {}", t.id, t.project_id, t.project_ref, t.description)
+                }
+                PushChange::Delete { entity, id } => format!("delete {:?} {}", entity, id),
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                format!("project None Some(\"project-{}\") Trip", trip.id),
+                format!("todo Some({}) Some({}) None Buy milk", milk.id, home.id),
+                format!("todo None None Some(\"project-{}\") Book train", trip.id),
+            ]
+        );
+    }
+}
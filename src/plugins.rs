@@ -0,0 +1,291 @@
+use anyhow::{bail, ensure, Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::core::events::{Change, ChangeKind};
+use crate::core::models::{Project, Todo};
+use crate::core::search;
+use crate::core::service::DocketService;
+
+/// How long a command that exits right after its change waits for plugins
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A `plugins` entry from the config file, e.g.
+/// `{"on": ["todo.created"], "match": "invoice", "tag": "finance"}`: each
+/// reacts to the changes its filters pass by adding a tag or running a command
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginConfig {
+    /// Event names such as `todo.created` or `project.archived`; empty for every event
+    #[serde(default)]
+    pub on: Vec<String>,
+    /// Only todos whose description contains this text, ignoring case and accents
+    #[serde(default, rename = "match")]
+    pub matches: Option<String>,
+    /// Only changes in the project with this name
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Hashtag added to the todo, with or without the `#`
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Shell command to run, with the change in `DOCKET_*` environment variables
+    #[serde(default)]
+    pub run: Option<String>,
+}
+
+/// A change together with its rows as they are afterwards; a deleted row is `None`
+#[derive(Debug)]
+pub struct PluginEvent {
+    pub change: Change,
+    pub project: Option<Project>,
+    pub todo: Option<Todo>,
+}
+
+/// Something that reacts to changes. The tag and command plugins come from
+/// the config file; others can be compiled in and passed to `start` with them.
+#[async_trait]
+pub trait Plugin: Send + Sync {
+    /// What the plugin does, for error messages, e.g. `tag #finance`
+    fn name(&self) -> String;
+
+    /// Whether the plugin wants `kind`, so changes nobody wants aren't looked up
+    fn wants(&self, kind: ChangeKind) -> bool;
+
+    /// React to a change. What a plugin changes is published like any
+    /// other change, so it must leave alone what it already did or it loops.
+    async fn on_change(&self, event: &PluginEvent, service: &DocketService) -> Result<()>;
+}
+
+/// The changes a configured plugin reacts to
+#[derive(Debug)]
+struct Filter {
+    kinds: Vec<ChangeKind>,
+    matches: Option<String>,
+    project: Option<String>,
+}
+
+impl Filter {
+    fn wants(&self, kind: ChangeKind) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&kind)
+    }
+
+    fn accepts(&self, event: &PluginEvent) -> bool {
+        if let Some(text) = &self.matches {
+            let found = event.todo.as_ref().is_some_and(|t| search::contains(&t.description, text));
+            if !found {
+                return false;
+            }
+        }
+        if let Some(name) = &self.project {
+            return event.project.as_ref().is_some_and(|p| p.name.eq_ignore_ascii_case(name));
+        }
+        true
+    }
+}
+
+/// Adds a hashtag to the todos its filter passes
+struct TagPlugin {
+    filter: Filter,
+    tag: String,
+}
+
+#[async_trait]
+impl Plugin for TagPlugin {
+    fn name(&self) -> String {
+        format!("tag #{}", self.tag)
+    }
+
+    fn wants(&self, kind: ChangeKind) -> bool {
+        self.filter.wants(kind)
+    }
+
+    async fn on_change(&self, event: &PluginEvent, service: &DocketService) -> Result<()> {
+        let Some(todo) = &event.todo else { return Ok(()) };
+        if !self.filter.accepts(event) || todo.tags().contains(&self.tag) {
+            return Ok(());
+        }
+        service.update_todo(todo.id, &format!("{} #{}", todo.description, self.tag)).await?;
+        Ok(())
+    }
+}
+
+/// Runs a shell command for the changes its filter passes
+struct CommandPlugin {
+    filter: Filter,
+    command: String,
+}
+
+#[async_trait]
+impl Plugin for CommandPlugin {
+    fn name(&self) -> String {
+        format!("run '{}'", self.command)
+    }
+
+    fn wants(&self, kind: ChangeKind) -> bool {
+        self.filter.wants(kind)
+    }
+
+    async fn on_change(&self, event: &PluginEvent, _service: &DocketService) -> Result<()> {
+        if !self.filter.accepts(event) {
+            return Ok(());
+        }
+        let change = &event.change;
+        let id = |id: Option<i64>| id.map(|id| id.to_string()).unwrap_or_default();
+        let status = Command::new("sh")
+            .args(["-c", &self.command])
+            .env("DOCKET_EVENT", change.kind.as_str())
+            .env("DOCKET_PROJECT_ID", id(change.project_id))
+            .env("DOCKET_PROJECT", event.project.as_ref().map_or("", |p| p.name.as_str()))
+            .env("DOCKET_TODO_ID", id(change.todo_id))
+            .env("DOCKET_TODO", event.todo.as_ref().map_or("", |t| t.description.as_str()))
+            .status()
+            .await
+            .context("Failed to run sh")?;
+        ensure!(status.success(), "exited with {}", status);
+        Ok(())
+    }
+}
+
+/// Turn the config file's `plugins` into plugins, checking every entry
+pub fn from_config(configs: &[PluginConfig]) -> Result<Vec<Box<dyn Plugin>>> {
+    configs
+        .iter()
+        .enumerate()
+        .map(|(i, config)| build(config).with_context(|| format!("Plugin {} in the config file", i + 1)))
+        .collect()
+}
+
+fn build(config: &PluginConfig) -> Result<Box<dyn Plugin>> {
+    let kinds = config.on.iter().map(|name| name.parse()).collect::<Result<Vec<ChangeKind>, String>>();
+    let filter = Filter {
+        kinds: kinds.map_err(anyhow::Error::msg)?,
+        matches: config.matches.as_deref().map(search::fold),
+        project: config.project.clone(),
+    };
+    match (&config.tag, &config.run) {
+        (Some(tag), None) => {
+            let tag = tag.trim().trim_start_matches('#').to_lowercase();
+            if tag.is_empty() || !tag.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+                bail!("'{}' is not a valid hashtag", config.tag.as_deref().unwrap_or(""));
+            }
+            Ok(Box::new(TagPlugin { filter, tag }))
+        }
+        (None, Some(command)) if !command.trim().is_empty() => {
+            Ok(Box::new(CommandPlugin { filter, command: command.clone() }))
+        }
+        (None, _) => bail!("give it a tag to add or a command to run"),
+        (Some(_), Some(_)) => bail!("give it either a tag or a command, not both"),
+    }
+}
+
+/// Plugins running in the background on the service's event bus
+pub struct Plugins {
+    service: DocketService,
+    /// ID of the last change every plugin has seen
+    handled: Arc<AtomicU64>,
+}
+
+/// Feed every change `service` publishes from now on to `plugins`, one
+/// change and one plugin at a time, in order. A failing plugin is logged
+/// and the rest carry on.
+pub fn start(service: DocketService, plugins: Vec<Box<dyn Plugin>>) -> Plugins {
+    let mut changes = service.events().subscribe();
+    let handled = Arc::new(AtomicU64::new(service.events().last_id()));
+    let running = Plugins { service: service.clone(), handled: handled.clone() };
+    if plugins.is_empty() {
+        handled.store(u64::MAX, Ordering::SeqCst);
+        return running;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            let change = match changes.recv().await {
+                Ok(change) => change,
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Plugins missed {} changes", missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let id = change.id;
+            if plugins.iter().any(|p| p.wants(change.kind)) {
+                let event = PluginEvent {
+                    project: match change.project_id {
+                        Some(id) => service.get_project(id).await.ok(),
+                        None => None,
+                    },
+                    todo: match change.todo_id {
+                        Some(id) => service.get_todo(id).await.ok(),
+                        None => None,
+                    },
+                    change,
+                };
+                for plugin in plugins.iter().filter(|p| p.wants(event.change.kind)) {
+                    if let Err(err) = plugin.on_change(&event, &service).await {
+                        tracing::warn!("Plugin {} failed on {}: {:#}", plugin.name(), event.change.kind.as_str(), err);
+                    }
+                }
+            }
+            handled.store(id, Ordering::SeqCst);
+        }
+    });
+    running
+}
+
+impl Plugins {
+    /// Wait until the plugins have seen every change so far, including the
+    /// ones they made themselves, for commands that exit right after a change
+    pub async fn flush(&self) {
+        let settled = async {
+            while self.handled.load(Ordering::SeqCst) < self.service.events().last_id() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        };
+        if tokio::time::timeout(FLUSH_TIMEOUT, settled).await.is_err() {
+            tracing::warn!("Gave up waiting for plugins after {} seconds", FLUSH_TIMEOUT.as_secs());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::service::test_service;
+
+    fn config(json: &str) -> PluginConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tag_plugin_tags_matching_todos_once() {
+        let service = test_service().await;
+        let json = r##"{"on": ["todo.created", "todo.updated"], "match": "invoice", "tag": "#Finance"}"##;
+        let plugins = from_config(&[config(json)]).unwrap();
+        let running = start(service.clone(), plugins);
+
+        let project = service.create_project("Home").await.unwrap();
+        let invoice = service.create_todo(project.id, "Pay invoice 42").await.unwrap();
+        let milk = service.create_todo(project.id, "Buy milk").await.unwrap();
+        running.flush().await;
+
+        assert_eq!(service.get_todo(invoice.id).await.unwrap().description, "Pay invoice 42 #finance");
+        assert_eq!(service.get_todo(milk.id).await.unwrap().description, "Buy milk");
+    }
+
+    #[test]
+    fn test_config_mistakes_are_reported() {
+        let error = |json: &str| from_config(&[config(json)]).err().map(|e| format!("{:#}", e));
+        assert!(error(r#"{"on": ["todo.created"], "run": "notify-send docket"}"#).is_none());
+        assert_eq!(
+            error(r#"{"on": ["todo.create"], "tag": "x"}"#).unwrap(),
+            "Plugin 1 in the config file: Unknown event 'todo.create' (expected e.g. todo.created or project.archived)"
+        );
+        assert!(error(r#"{"tag": "x", "run": "true"}"#).unwrap().ends_with("not both"));
+        assert!(error(r#"{"match": "x"}"#).unwrap().ends_with("a command to run"));
+    }
+}
@@ -0,0 +1,445 @@
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::client::{Client, ProjectQuery};
+use crate::core::audit::{AuditPage, AuditQuery};
+use crate::core::batch::{BatchOp, BatchResult, Direction};
+use crate::core::error::{DocketError, Result};
+use crate::core::events::EventBus;
+use crate::core::groom::{GroomChange, GroomRule};
+use crate::core::prune::{PrunePolicy, PrunedTodo};
+use crate::core::models::{
+    toggle_checklist_item, LinkKind, Project, ProjectSettings, ProjectStats, ProjectWithStats,
+    RelatedTodo, Todo, TodoContext, TodoLink, TodoMention, TodoPatch, TodoTemplate, TodoVersion, Totals, Workspace,
+};
+use crate::core::service_api::DocketServiceApi;
+use crate::core::share::{ShareLink, SharedProject};
+use crate::core::sort::TodoOrder;
+use crate::core::sync::{ChangeMarker, ChangeSet, PushChange, PushResult};
+use crate::core::time::DisplayZone;
+
+/// Docket on another machine, through its REST API, for `docket --remote`.
+/// Requests go through `client::Client`. What the API has no endpoint for
+/// is worked out from what it does have (totals, the review queue, open
+/// todos across projects); grooming, pruning and archiving stale projects fail with
+/// `DocketError::Remote`. The TUI's per-project view settings stay on this
+/// machine for the session.
+///
+/// When the server can't be reached the error is `DocketError::Offline`;
+/// `offline::CachedService` builds on that to keep working without it.
+pub struct RemoteService {
+    client: Client,
+    timezone: DisplayZone,
+    /// Nothing is published here: the TUI sees the server's changes by
+    /// polling `change_marker`
+    events: EventBus,
+    settings: Mutex<HashMap<i64, ProjectSettings>>,
+}
+
+impl RemoteService {
+    /// A client for the server at `base_url`, e.g. `http://nas:3000`
+    pub fn new(base_url: &str, token: Option<String>, timezone: DisplayZone) -> Self {
+        Self {
+            client: Client::new(base_url).with_token(token),
+            timezone,
+            events: EventBus::new(),
+            settings: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Every todo of the active projects, project by project in list order
+    async fn todos_of_active_projects(&self, include_completed: bool) -> Result<Vec<Todo>> {
+        let mut todos = Vec::new();
+        for project in self.list_active_projects().await? {
+            let id = project.project.id;
+            todos.extend(if include_completed {
+                self.list_all_todos(id).await?
+            } else {
+                self.list_active_todos(id).await?
+            });
+        }
+        Ok(todos)
+    }
+}
+
+fn unsupported<T>(what: &str) -> Result<T> {
+    Err(DocketError::remote(format!("{} is not available on a remote server", what)))
+}
+
+#[async_trait]
+impl DocketServiceApi for RemoteService {
+    fn timezone(&self) -> DisplayZone {
+        self.timezone
+    }
+
+    fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    async fn create_project(&self, name: &str) -> Result<Project> {
+        self.create_project_with_description(name, None).await
+    }
+
+    async fn create_project_with_description(&self, name: &str, description: Option<&str>) -> Result<Project> {
+        self.client.create_project(name, description).await
+    }
+
+    async fn clone_project(&self, id: i64, name: Option<&str>, include_completed: bool) -> Result<Project> {
+        self.client.clone_project(id, name, include_completed).await
+    }
+
+    async fn get_project(&self, id: i64) -> Result<Project> {
+        self.client.get_project(id).await
+    }
+
+    async fn get_project_with_stats(&self, id: i64) -> Result<ProjectWithStats> {
+        self.list_all_projects()
+            .await?
+            .into_iter()
+            .find(|p| p.project.id == id)
+            .ok_or_else(|| DocketError::not_found(format!("Project {} not found", id)))
+    }
+
+    async fn get_project_stats(&self, id: i64, weeks: i64, zone: DisplayZone) -> Result<ProjectStats> {
+        // The server's `local` is its own zone, so send ours as an offset
+        let tz = zone.offset_at(Utc::now()).to_string();
+        self.client.get_project_stats(id, weeks, Some(&tz)).await
+    }
+
+    async fn get_totals(&self) -> Result<Totals> {
+        let projects = self.list_all_projects().await?;
+        let (archived, active): (Vec<_>, Vec<_>) = projects.iter().partition(|p| p.project.is_archived());
+        Ok(Totals {
+            active_projects: active.len() as i64,
+            archived_projects: archived.len() as i64,
+            open_todos: active.iter().map(|p| p.active_todos()).sum(),
+        })
+    }
+
+    async fn update_project_description(&self, id: i64, description: Option<&str>) -> Result<()> {
+        self.client.update_project_description(id, description).await
+    }
+
+    async fn update_project_target_date(&self, id: i64, target_date: Option<NaiveDate>) -> Result<()> {
+        self.client.update_project_target_date(id, target_date).await
+    }
+
+    async fn update_project_name(&self, id: i64, name: &str) -> Result<()> {
+        self.client.update_project(id, Some(name), None).await
+    }
+
+    async fn replace_project(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+        target_date: Option<NaiveDate>,
+        archived: bool,
+    ) -> Result<Project> {
+        self.client.replace_project(id, name, description, target_date, archived).await
+    }
+
+    async fn suggest_project_name(&self, name: &str) -> Result<String> {
+        let projects = self.list_all_projects().await?;
+        let taken = |candidate: &str| projects.iter().any(|p| p.project.name.eq_ignore_ascii_case(candidate));
+        let mut n = 2;
+        loop {
+            let candidate = format!("{} ({})", name, n);
+            if !taken(&candidate) {
+                return Ok(candidate);
+            }
+            n += 1;
+        }
+    }
+
+    async fn list_active_projects(&self) -> Result<Vec<ProjectWithStats>> {
+        self.client.list_projects(&ProjectQuery::default()).await
+    }
+
+    async fn list_all_projects(&self) -> Result<Vec<ProjectWithStats>> {
+        let query = ProjectQuery { include_archived: true, ..ProjectQuery::default() };
+        self.client.list_projects(&query).await
+    }
+
+    async fn archive_stale_projects(&self, _stale_after_days: i64) -> Result<Vec<ProjectWithStats>> {
+        unsupported("Archiving stale projects")
+    }
+
+    async fn groom(&self, _rules: &[GroomRule], _dry_run: bool) -> Result<Vec<GroomChange>> {
+        unsupported("Grooming")
+    }
+
+    async fn prune(&self, _policy: &PrunePolicy, _dry_run: bool) -> Result<Vec<PrunedTodo>> {
+        unsupported("Pruning")
+    }
+
+    async fn list_projects_due_for_review(&self) -> Result<Vec<ProjectWithStats>> {
+        let mut projects: Vec<_> = self
+            .list_active_projects()
+            .await?
+            .into_iter()
+            .filter(|p| p.active_todos() > 0 && !p.project.reviewed_today(self.timezone))
+            .collect();
+        projects.sort_by_key(|p| p.project.last_reviewed_at);
+        Ok(projects)
+    }
+
+    async fn mark_project_reviewed(&self, id: i64) -> Result<()> {
+        self.client.mark_project_reviewed(id).await
+    }
+
+    async fn archive_project(&self, id: i64) -> Result<()> {
+        self.client.archive_project(id).await
+    }
+
+    async fn unarchive_project(&self, id: i64) -> Result<()> {
+        self.client.unarchive_project(id).await
+    }
+
+    async fn delete_project(&self, id: i64) -> Result<()> {
+        self.client.delete_project(id).await
+    }
+
+    async fn get_project_settings(&self, project_id: i64) -> Result<ProjectSettings> {
+        Ok(self.settings.lock().unwrap().get(&project_id).cloned().unwrap_or_default())
+    }
+
+    async fn save_project_settings(&self, project_id: i64, settings: &ProjectSettings) -> Result<()> {
+        self.settings.lock().unwrap().insert(project_id, settings.clone());
+        Ok(())
+    }
+
+    async fn create_todo(&self, project_id: i64, description: &str) -> Result<Todo> {
+        self.create_todo_with_details(project_id, description, None).await
+    }
+
+    async fn create_todo_with_details(
+        &self,
+        project_id: i64,
+        description: &str,
+        details: Option<&str>,
+    ) -> Result<Todo> {
+        self.client.create_todo(project_id, description, details).await
+    }
+
+    async fn list_all_todos(&self, project_id: i64) -> Result<Vec<Todo>> {
+        self.client.list_todos(project_id, true).await
+    }
+
+    async fn list_active_todos(&self, project_id: i64) -> Result<Vec<Todo>> {
+        self.client.list_todos(project_id, false).await
+    }
+
+    async fn list_sorted_todos(&self, project_id: i64, include_completed: bool, order: &TodoOrder) -> Result<Vec<Todo>> {
+        self.client.list_sorted_todos(project_id, include_completed, order).await
+    }
+
+    async fn toggle_todo(&self, id: i64) -> Result<()> {
+        self.client.toggle_todo(id).await
+    }
+
+    async fn delete_todo(&self, id: i64) -> Result<()> {
+        self.client.delete_todo(id).await
+    }
+
+    async fn get_todo(&self, id: i64) -> Result<Todo> {
+        self.client.get_todo(id).await
+    }
+
+    async fn get_todo_context(&self, id: i64) -> Result<TodoContext> {
+        self.client.get_todo_context(id).await
+    }
+
+    async fn update_todo_details(&self, id: i64, details: Option<&str>) -> Result<()> {
+        self.client.update_todo_details(id, details).await
+    }
+
+    async fn toggle_checklist_item(&self, id: i64, index: usize) -> Result<()> {
+        // Two requests, so unlike the local service a concurrent edit of
+        // the details in between is overwritten
+        let todo = self.get_todo(id).await?;
+        let Some(details) = todo.details.as_deref().and_then(|d| toggle_checklist_item(d, index)) else {
+            return Err(DocketError::not_found(format!("Todo {} has no checklist item {}", id, index + 1)));
+        };
+        self.update_todo_details(id, Some(&details)).await
+    }
+
+    async fn update_todo_estimate(&self, id: i64, estimate: Option<i64>) -> Result<()> {
+        self.client.update_todo_estimate(id, estimate).await
+    }
+
+    async fn update_todo_due_date(&self, id: i64, due_date: Option<NaiveDate>) -> Result<()> {
+        self.client.update_todo_due_date(id, due_date).await
+    }
+
+    async fn update_todo_external_ref(&self, id: i64, external_ref: Option<&str>) -> Result<()> {
+        self.client.update_todo_external_ref(id, external_ref).await
+    }
+
+    async fn list_todos_due_between(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Todo>> {
+        let mut todos: Vec<Todo> = self
+            .todos_of_active_projects(true)
+            .await?
+            .into_iter()
+            .filter(|t| t.due_date.is_some_and(|d| from <= d && d <= to))
+            .collect();
+        todos.sort_by_key(|t| (t.due_date, t.is_completed(), t.position, t.id));
+        Ok(todos)
+    }
+
+    async fn list_open_todos(&self) -> Result<Vec<Todo>> {
+        self.todos_of_active_projects(false).await
+    }
+
+    async fn list_open_todos_due_by(&self, date: NaiveDate) -> Result<Vec<Todo>> {
+        let mut todos: Vec<Todo> = self
+            .todos_of_active_projects(false)
+            .await?
+            .into_iter()
+            .filter(|t| t.due_date.is_some_and(|d| d <= date))
+            .collect();
+        todos.sort_by_key(|t| (t.due_date, t.position, t.id));
+        Ok(todos)
+    }
+
+    async fn update_todo(&self, id: i64, description: &str) -> Result<()> {
+        let patch = TodoPatch { description: Some(description.to_string()), ..TodoPatch::default() };
+        self.client.patch_todo(id, &patch).await.map(drop)
+    }
+
+    async fn move_todo_up(&self, id: i64) -> Result<()> {
+        self.client.move_todo(id, Direction::Up).await
+    }
+
+    async fn defer_todo(&self, id: i64) -> Result<()> {
+        // A place past the end is the last place
+        let patch = TodoPatch { position: Some(i64::MAX), ..TodoPatch::default() };
+        self.client.patch_todo(id, &patch).await.map(drop)
+    }
+
+    async fn move_todo_down(&self, id: i64) -> Result<()> {
+        self.client.move_todo(id, Direction::Down).await
+    }
+
+    async fn patch_todo(&self, id: i64, patch: &TodoPatch) -> Result<Todo> {
+        self.client.patch_todo(id, patch).await
+    }
+
+    async fn todo_history(&self, todo_id: i64) -> Result<Vec<TodoVersion>> {
+        self.client.todo_history(todo_id).await
+    }
+
+    async fn restore_todo_version(&self, todo_id: i64, version_id: i64) -> Result<Todo> {
+        self.client.restore_todo_version(todo_id, version_id).await
+    }
+
+    async fn link_todos(&self, todo_id: i64, target_id: i64, kind: LinkKind) -> Result<TodoLink> {
+        self.client.link_todos(todo_id, target_id, kind).await
+    }
+
+    async fn related_todos(&self, todo_id: i64) -> Result<Vec<RelatedTodo>> {
+        self.client.related_todos(todo_id).await
+    }
+
+    async fn unlink_todos(&self, link_id: i64) -> Result<()> {
+        self.client.unlink_todos(link_id).await
+    }
+
+    async fn todo_mentions(&self, todo_id: i64) -> Result<Vec<TodoMention>> {
+        self.client.todo_mentions(todo_id).await
+    }
+
+    async fn normalize_positions(&self, project_id: i64) -> Result<usize> {
+        let todos = self.list_active_todos(project_id).await?;
+        let moved = todos.iter().zip(1..).filter(|(t, wanted)| t.position != *wanted).count();
+        // Placing any open todo renumbers the whole list
+        if moved > 0 {
+            let patch = TodoPatch { position: Some(1), ..TodoPatch::default() };
+            self.client.patch_todo(todos[0].id, &patch).await?;
+        }
+        Ok(moved)
+    }
+
+    async fn share_project(&self, id: i64, days: i64) -> Result<ShareLink> {
+        self.client.share_project(id, days).await
+    }
+
+    async fn shared_project(&self, _token: &str) -> Result<SharedProject> {
+        unsupported("Opening share links")
+    }
+
+    async fn update_project_slug(&self, id: i64, slug: Option<&str>) -> Result<Project> {
+        self.client.update_project_slug(id, slug).await
+    }
+
+    async fn public_project(&self, _slug: &str) -> Result<Option<SharedProject>> {
+        unsupported("Opening status pages")
+    }
+
+    async fn list_workspaces(&self) -> Result<Vec<Workspace>> {
+        self.client.list_workspaces().await
+    }
+
+    async fn create_workspace(&self, name: &str) -> Result<Workspace> {
+        self.client.create_workspace(name).await
+    }
+
+    async fn rename_workspace(&self, id: i64, name: &str) -> Result<Workspace> {
+        self.client.rename_workspace(id, name).await
+    }
+
+    async fn delete_workspace(&self, id: i64) -> Result<()> {
+        self.client.delete_workspace(id).await
+    }
+
+    async fn update_project_workspace(&self, id: i64, workspace_id: Option<i64>) -> Result<Project> {
+        self.client.update_project_workspace(id, workspace_id).await
+    }
+
+    async fn update_project_parent(&self, id: i64, parent_id: Option<i64>) -> Result<Project> {
+        self.client.update_project_parent(id, parent_id).await
+    }
+
+    async fn project_path(&self, id: i64) -> Result<Vec<Project>> {
+        let mut path = vec![self.get_project(id).await?];
+        while let Some(parent_id) = path[0].parent_id {
+            if path.iter().any(|p| p.id == parent_id) {
+                break;
+            }
+            path.insert(0, self.get_project(parent_id).await?);
+        }
+        Ok(path)
+    }
+
+    async fn list_templates(&self) -> Result<Vec<TodoTemplate>> {
+        self.client.list_templates().await
+    }
+
+    async fn create_todo_from_template(&self, project_id: i64, name: &str, description: Option<&str>) -> Result<Todo> {
+        self.client.create_todo_from_template(project_id, name, description).await
+    }
+
+    async fn apply_batch(&self, ops: Vec<BatchOp>) -> Result<Vec<BatchResult>> {
+        self.client.batch(ops).await
+    }
+
+    async fn audit_log(&self, _query: &AuditQuery) -> Result<AuditPage> {
+        unsupported("Reading the audit log")
+    }
+
+    async fn changes_since(&self, since: i64) -> Result<ChangeSet> {
+        self.client.changes_since(since).await
+    }
+
+    async fn change_marker(&self) -> Result<ChangeMarker> {
+        // Nothing changed after the end of time, so only the cursor comes back
+        let changes = self.changes_since(i64::MAX).await?;
+        Ok(ChangeMarker { version: changes.cursor, modified_at: None })
+    }
+
+    async fn apply_changes(&self, changes: Vec<PushChange>) -> Result<PushResult> {
+        self.client.push_changes(changes).await
+    }
+}
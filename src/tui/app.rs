@@ -1,13 +1,158 @@
 use anyhow::Result;
-use crate::core::{models::{Project, ProjectWithStats, Todo}, service::DocketService};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+use tokio::sync::broadcast;
+use crate::config::{self, Config};
+use crate::core::events::Change;
+use crate::core::{export, models::{parse_todo_ref, LinkKind, Project, ProjectSettings, ProjectWithStats, RelatedTodo, Todo, TodoMention, TodoVersion, Totals, Workspace}, error::DocketError, service_api::DocketServiceApi};
+use crate::core::query::TodoQuery;
+use crate::core::search;
+use crate::core::sort::{SortField, SortKey, TodoOrder};
+use crate::core::time::DisplayZone;
+use crate::core::tree::{self, TreeRow};
+use crate::jira::JiraClient;
+use crate::logging;
+
+use super::clipboard;
+use super::command::Command;
+use super::editor::EditorTarget;
+use super::loader::{LoadKind, Loaded, Loader};
+use super::prefs::{LayoutPrefs, SessionState, SessionView};
+use super::tasks::Tasks;
+use super::keymap::{self, HelpSection};
+use super::columns::{Column, ProjectColumn, TodoColumn, describe, parse_columns};
+use super::theme::{Icons, Theme};
 
 /// Application view state
 #[derive(Debug, Clone, PartialEq)]
 pub enum ViewMode {
     ProjectList,
     TodoList(i64), // project_id
+    /// Active todos of every active project, opened from the top row of the project list
+    AllTodos,
     ArchivedProjects,
+    Review,
     Help,
+    Messages,
+    /// The end of the log file (`:log`)
+    Log,
+    /// Todos with due dates, a week at a time
+    Calendar,
+}
+
+/// How a status message is colored
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// A message shown in the footer and kept in the `:messages` history
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusMessage {
+    pub text: String,
+    pub severity: Severity,
+    pub at: DateTime<Utc>,
+}
+
+impl StatusMessage {
+    /// Whether the message has been shown long enough to dismiss
+    pub fn expired(&self) -> bool {
+        let seconds = match self.severity {
+            Severity::Error => ERROR_STATUS_SECONDS,
+            _ => STATUS_SECONDS,
+        };
+        Utc::now() - self.at >= Duration::seconds(seconds)
+    }
+}
+
+/// A JIRA ticket status cached for the details pane
+#[derive(Debug, Clone)]
+pub struct TicketStatus {
+    /// Status name, or why it could not be fetched
+    pub status: Result<String, String>,
+    pub fetched_at: Instant,
+}
+
+/// Largest count prefix accepted in normal mode (`5j`, `3dd`)
+const MAX_COUNT: usize = 9999;
+
+/// Seconds a status message stays in the footer; errors stay longer
+const STATUS_SECONDS: i64 = 4;
+const ERROR_STATUS_SECONDS: i64 = 8;
+
+/// Status messages kept for `:messages`
+const MESSAGE_HISTORY: usize = 100;
+
+/// Bounds for the expanded details pane height, borders included
+const MIN_DETAIL_HEIGHT: u16 = 4;
+const MAX_DETAIL_HEIGHT: u16 = 40;
+
+/// Bounds and step for the project pane width in the split view, in percent
+const MIN_PANE_WIDTH: u16 = 15;
+const MAX_PANE_WIDTH: u16 = 85;
+const PANE_WIDTH_STEP: u16 = 5;
+
+/// How often the main loop checks the config file for edits
+const CONFIG_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How often the main loop checks the database for writes by other processes
+const DATA_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How long a fetched JIRA status is shown before it is fetched again
+const TICKET_TTL: std::time::Duration = std::time::Duration::from_secs(600);
+
+const NO_RELATED: &str = "No related todos; link one with :relate or :duplicates, or mention one as #id in the details";
+
+/// `1 todo`, `3 todos`
+fn plural(n: usize, noun: &str) -> String {
+    if n == 1 { format!("1 {}", noun) } else { format!("{} {}s", n, noun) }
+}
+
+/// The `default_sort` set in the config file, ignoring an invalid one
+fn configured_sort(config: &Config) -> TodoOrder {
+    default_sort(config).and_then(Result::ok).unwrap_or_default()
+}
+
+/// The `default_sort` set in the config file, if any
+fn default_sort(config: &Config) -> Option<Result<TodoOrder, String>> {
+    config.default_sort.as_deref().map(str::parse)
+}
+
+/// The project `name` means: exact but for case, then ignoring accents too:
+/// exact, prefix, anywhere
+fn find_project<'a>(projects: &'a [ProjectWithStats], name: &str) -> Option<&'a ProjectWithStats> {
+    let needle = search::fold(name);
+    projects
+        .iter()
+        .find(|p| p.project.name.to_lowercase() == name.to_lowercase())
+        .or_else(|| projects.iter().find(|p| search::fold(&p.project.name) == needle))
+        .or_else(|| projects.iter().find(|p| search::fold(&p.project.name).starts_with(&needle)))
+        .or_else(|| projects.iter().find(|p| search::contains(&p.project.name, &needle)))
+}
+
+/// A todo copied with `y`, ready to paste with `p`
+#[derive(Debug, Clone, PartialEq)]
+pub struct YankedTodo {
+    /// Hashtags in the description and details come along with the text
+    pub description: String,
+    pub details: Option<String>,
+    pub estimate: Option<i64>,
+}
+
+impl From<&Todo> for YankedTodo {
+    fn from(todo: &Todo) -> Self {
+        Self {
+            description: todo.description.clone(),
+            details: todo.details.clone(),
+            estimate: todo.estimate,
+        }
+    }
 }
 
 /// Input mode
@@ -15,54 +160,242 @@ pub enum ViewMode {
 pub enum InputMode {
     Normal,
     Command,
+    /// Typing a search of the help screen (`/`)
+    SearchHelp,
     AddProject,
     AddTodo,
+    /// Details of the todo being added, reached with Tab from its description
+    AddTodoDetails,
     EditDescription,
     EditTodoDetails,
     EditTodo,
     EditProjectName,
+    EditEstimate,
+    EditTargetDate,
+    EditDueDate,
+    CloneProject,
+    /// Choosing sort keys in the sort modal (`o`)
+    PickSort,
+    /// Browsing the expanded todo's earlier versions (`u`)
+    PickVersion,
+    /// Reading the selected todo's full text in a popup (`f`)
+    FullText,
 }
 
 /// TUI Application state
 pub struct App {
-    pub service: DocketService,
+    pub service: Arc<dyn DocketServiceApi>,
+    pub config: Config,
     pub view_mode: ViewMode,
     pub input_mode: InputMode,
     pub projects: Vec<ProjectWithStats>,
+    /// Depth and rolled-up counts of each of `projects`, which are in tree order
+    pub project_tree: Vec<TreeRow>,
+    /// Projects whose sub-projects are folded away (Space in the project list)
+    pub collapsed_projects: HashSet<i64>,
+    /// Names of the projects the open one is nested in, outermost first
+    pub breadcrumb: Vec<String>,
+    /// Project and open todo counts shown above the project list
+    pub totals: Totals,
     pub todos: Vec<Todo>,
     pub current_project: Option<Project>,
+    /// Todo statistics for `current_project`
+    pub current_stats: Option<ProjectWithStats>,
     pub selected_index: usize,
     pub input_buffer: String,
-    pub status_message: Option<String>,
+    /// While adding a todo, the field not being typed in (Tab swaps them)
+    pub new_todo_other: String,
+    pub status_message: Option<StatusMessage>,
+    /// Recent status messages, oldest first (`:messages`)
+    pub messages: VecDeque<StatusMessage>,
+    /// The end of the log file, read when `:log` opens it
+    pub log_lines: Vec<String>,
     pub show_completed: bool,
+    /// Ordering of the todo list, set with `:sort`
+    pub todo_sort: TodoOrder,
+    /// Keys being picked in the sort modal (`o`), and the highlighted field
+    pub sort_picker: Vec<SortKey>,
+    pub sort_picker_index: usize,
+    /// Earlier versions of the expanded todo in the history modal (`u`),
+    /// newest first, and the highlighted one
+    pub history: Vec<TodoVersion>,
+    pub history_index: usize,
+    /// Case-insensitive text filter on todos, set with `:filter`
+    pub todo_filter: Option<String>,
     pub should_quit: bool,
+    /// Profile `:profile` quit to reopen on
+    pub switch_profile: Option<String>,
     pub expanded_todo_id: Option<i64>,
+    /// Lines scrolled in the expanded todo's details
+    pub detail_scroll: u16,
+    /// Names of the saved todo templates, for `:template` completion
+    pub template_names: Vec<String>,
+    /// Workspaces `W` steps through, by name
+    pub workspaces: Vec<Workspace>,
+    /// Workspace the project list is narrowed to; `None` shows every project
+    pub workspace: Option<Workspace>,
+    /// Checklist item selected in the expanded todo's details (`x`); while
+    /// set, `j`/`k` and Space work on the checklist
+    pub checklist_index: Option<usize>,
+    /// Todos linked to or from the expanded todo, and those its details
+    /// mention or that mention it, fetched for `related_for`
+    pub related: Vec<RelatedTodo>,
+    pub mentions: Vec<TodoMention>,
+    pub related_for: Option<i64>,
+    /// Row of `related` and then `mentions` selected in the expanded todo's
+    /// details (`l`); while set, `j`/`k` and Enter work on those todos
+    pub related_index: Option<usize>,
+    /// Height of the details pane, adjusted with `+`/`-`
+    pub detail_height: u16,
+    /// Show the expanded todo's details in place of the list
+    pub detail_fullscreen: bool,
+    /// Keep the project list visible beside the todos (`|`)
+    pub split_view: bool,
+    /// Width of the project pane in the split view, in percent (`<`/`>`)
+    pub project_pane_width: u16,
+    /// Hide the project pane while the todos pane has focus (`\`)
+    pub projects_collapsed: bool,
+    /// Two-line todo rows with tags, due date and details (`i`)
+    pub detailed_rows: bool,
+    /// Long descriptions wrap over several lines instead of ending in `…` (`w`)
+    pub wrap_rows: bool,
+    /// Todo table columns picked with `:columns`; `None` follows the config
+    pub todo_columns: Option<Vec<TodoColumn>>,
+    /// Project table columns picked with `:columns`; `None` follows the config
+    pub project_columns: Option<Vec<ProjectColumn>>,
+    /// Selected project row while the todos pane has focus
+    pub project_index: usize,
+    /// Todos of the highlighted project, shown in the right pane of the split view
+    pub preview_todos: Vec<Todo>,
+    preview_project_id: Option<i64>,
+    /// Project IDs queued for the current review session
+    pub review_queue: Vec<i64>,
+    /// Index into `review_queue` of the project being reviewed
+    pub review_position: usize,
+    /// View the help screen was opened from; its bindings are shown
+    pub help_from: ViewMode,
+    /// Lines scrolled in the help screen
+    pub help_scroll: u16,
+    /// Search typed with `/` on the help screen
+    pub help_query: String,
+    /// Day selected in the calendar; its week is the one shown
+    pub calendar_date: NaiveDate,
+    /// Todos due in the calendar's week
+    pub calendar_todos: Vec<Todo>,
+    /// JIRA statuses by issue key, or why they could not be fetched
+    pub tickets: HashMap<String, TicketStatus>,
+    /// Count typed before a normal-mode key, e.g. the `5` of `5j`
+    pub pending_count: Option<usize>,
+    /// First key of a two-key command (`gg`, `dd`)
+    pub pending_key: Option<char>,
+    /// Long operations running in the background
+    pub tasks: Tasks,
+    /// Lists being refreshed in the background
+    pub loader: Loader,
+    /// Todos yanked with `y` (`3y` yanks three)
+    pub register: Vec<YankedTodo>,
+    /// Text to open in `$EDITOR`; the main loop suspends the screen for it
+    pub editor_request: Option<EditorTarget>,
+    /// Table rows that fit on screen, recorded while rendering (for `H`/`M`/`L`)
+    pub visible_rows: Cell<usize>,
+    /// Selected todo and when it was selected, checked while rendering to pop
+    /// up its preview; `None` after a key press
+    pub dwell: Cell<Option<(i64, Instant)>>,
+    /// Config file timestamp when it was last loaded
+    config_modified: Option<SystemTime>,
+    config_checked_at: Instant,
+    /// Sync version of the database when it was last checked
+    data_version: Option<i64>,
+    data_checked_at: Instant,
+    /// Changes published by the web server when it runs in this process
+    events: Option<broadcast::Receiver<Change>>,
 }
 
 impl App {
     /// Create a new App instance
-    pub fn new(service: DocketService) -> Self {
+    pub fn new(service: impl DocketServiceApi + 'static, config: Config) -> Self {
+        let todo_sort = configured_sort(&config);
+        let layout = LayoutPrefs::default();
+        let today = config.timezone.today();
         Self {
-            service,
+            service: Arc::new(service),
+            config,
             view_mode: ViewMode::ProjectList,
             input_mode: InputMode::Normal,
             projects: Vec::new(),
+            project_tree: Vec::new(),
+            collapsed_projects: HashSet::new(),
+            breadcrumb: Vec::new(),
+            totals: Totals::default(),
             todos: Vec::new(),
             current_project: None,
+            current_stats: None,
             selected_index: 0,
             input_buffer: String::new(),
+            new_todo_other: String::new(),
             status_message: None,
+            messages: VecDeque::new(),
+            log_lines: Vec::new(),
             show_completed: true,
+            todo_sort,
+            sort_picker: Vec::new(),
+            sort_picker_index: 0,
+            history: Vec::new(),
+            history_index: 0,
+            todo_filter: None,
             should_quit: false,
+            switch_profile: None,
             expanded_todo_id: None,
+            detail_scroll: 0,
+            checklist_index: None,
+            related: Vec::new(),
+            mentions: Vec::new(),
+            related_for: None,
+            related_index: None,
+            template_names: Vec::new(),
+            workspaces: Vec::new(),
+            workspace: None,
+            detail_height: layout.detail_height,
+            detail_fullscreen: false,
+            split_view: layout.split_view,
+            project_pane_width: layout.project_pane_width,
+            projects_collapsed: layout.projects_collapsed,
+            detailed_rows: layout.detailed_rows,
+            wrap_rows: layout.wrap_rows,
+            todo_columns: layout.todo_columns,
+            project_columns: layout.project_columns,
+            project_index: 0,
+            preview_todos: Vec::new(),
+            preview_project_id: None,
+            review_queue: Vec::new(),
+            review_position: 0,
+            help_from: ViewMode::ProjectList,
+            help_scroll: 0,
+            help_query: String::new(),
+            calendar_date: today,
+            calendar_todos: Vec::new(),
+            tickets: HashMap::new(),
+            pending_count: None,
+            pending_key: None,
+            tasks: Tasks::new(),
+            loader: Loader::new(),
+            register: Vec::new(),
+            editor_request: None,
+            visible_rows: Cell::new(0),
+            dwell: Cell::new(None),
+            config_modified: Config::file_modified_at(),
+            config_checked_at: Instant::now(),
+            data_version: None,
+            data_checked_at: Instant::now(),
+            events: None,
         }
     }
     pub fn start_edit_todo(&mut self) {
-        if matches!(self.view_mode, ViewMode::TodoList(_)) {
-            if let Some(todo) = self.todos.get(self.selected_index) {
-                self.input_mode = InputMode::EditTodo;
-                self.input_buffer = todo.description.clone();
-            }
+        if matches!(self.view_mode, ViewMode::TodoList(_))
+            && let Some(todo) = self.todos.get(self.selected_index)
+        {
+            self.input_mode = InputMode::EditTodo;
+            self.input_buffer = todo.description.clone();
         }
     }
 
@@ -70,23 +403,73 @@ impl App {
     pub fn start_edit_project_name(&mut self) {
         let project = match &self.view_mode {
             ViewMode::ProjectList | ViewMode::ArchivedProjects => {
-                self.projects.get(self.selected_index).map(|p| &p.project)
+                self.selected_project().map(|p| p.project.clone())
             }
-            ViewMode::TodoList(_) => self.current_project.as_ref(),
+            ViewMode::TodoList(_) => self.current_project.clone(),
             _ => None,
         };
 
         if let Some(project) = project {
             self.input_mode = InputMode::EditProjectName;
-            self.input_buffer = project.name.clone();
+            self.input_buffer = project.name;
+        }
+    }
+
+    /// Start edit target date mode for the selected (or current) project
+    pub fn start_edit_target_date(&mut self) {
+        let project = match &self.view_mode {
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => {
+                self.selected_project().map(|p| p.project.clone())
+            }
+            ViewMode::TodoList(_) => self.current_project.clone(),
+            _ => None,
+        };
+
+        if let Some(project) = project {
+            self.input_mode = InputMode::EditTargetDate;
+            self.input_buffer = project
+                .target_date
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+        }
+    }
+
+    /// Save the edited target date; an empty buffer clears it
+    pub async fn save_target_date(&mut self) -> Result<()> {
+        let project_id = match &self.view_mode {
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => {
+                self.selected_project().map(|p| p.project.id)
+            }
+            ViewMode::TodoList(_) => self.current_project.as_ref().map(|p| p.id),
+            _ => None,
+        };
+
+        if let Some(id) = project_id {
+            match parse_target_date(self.input_buffer.trim(), self.config.timezone) {
+                Ok(target_date) => {
+                    self.service.update_project_target_date(id, target_date).await?;
+                    self.set_success(match target_date {
+                        Some(date) => format!("Target date set to {}", date),
+                        None => "Target date cleared".to_string(),
+                    });
+                    if let ViewMode::TodoList(_) = self.view_mode {
+                        self.current_project = Some(self.service.get_project(id).await?);
+                    } else {
+                        self.load_projects().await?;
+                    }
+                }
+                Err(e) => self.set_error(e),
+            }
         }
+        self.cancel_input();
+        Ok(())
     }
 
     /// Save the edited project name
     pub async fn save_project_name(&mut self) -> Result<()> {
         let project_id = match &self.view_mode {
             ViewMode::ProjectList | ViewMode::ArchivedProjects => {
-                self.projects.get(self.selected_index).map(|p| p.project.id)
+                self.selected_project().map(|p| p.project.id)
             }
             ViewMode::TodoList(_) => self.current_project.as_ref().map(|p| p.id),
             _ => None,
@@ -97,7 +480,7 @@ impl App {
             if !name.is_empty() {
                 match self.service.update_project_name(id, &name).await {
                     Ok(_) => {
-                        self.set_status("Project name updated");
+                        self.set_success("Project name updated");
                         // Refresh data
                         if let ViewMode::TodoList(_) = self.view_mode {
                             self.current_project = Some(self.service.get_project(id).await?);
@@ -105,7 +488,7 @@ impl App {
                             self.load_projects().await?;
                         }
                     }
-                    Err(e) => self.set_status(format!("Error: {}", e)),
+                    Err(e) => self.report_error(&e),
                 }
             }
         }
@@ -113,233 +496,2211 @@ impl App {
         Ok(())
     }
 
-    /// Save the edited todo description
-    pub async fn save_todo(&mut self) -> Result<()> {
-        if let ViewMode::TodoList(project_id) = self.view_mode {
-            if let Some(todo) = self.todos.get(self.selected_index) {
-                let description = self.input_buffer.trim().to_string();
-                if !description.is_empty() {
-                    match self.service.update_todo(todo.id, &description).await {
-                        Ok(_) => {
-                             self.load_todos(project_id).await?;
-                             self.set_status("Todo updated");
-                        }
-                        Err(e) => self.set_status(format!("Error: {}", e)),
-                    }
-                }
-            }
+    /// Start edit estimate mode for the selected todo
+    pub fn start_edit_estimate(&mut self) {
+        if matches!(self.view_mode, ViewMode::TodoList(_))
+            && let Some(todo) = self.todos.get(self.selected_index)
+        {
+            self.input_mode = InputMode::EditEstimate;
+            self.input_buffer = todo.estimate.map(|e| e.to_string()).unwrap_or_default();
         }
-        self.cancel_input();
-        Ok(())
     }
 
-
-    /// Initialize app - load projects
-    pub async fn init(&mut self) -> Result<()> {
-        self.load_projects().await?;
+    /// Start copying the selected project, prompting for the copy's name
+    pub async fn start_clone_project(&mut self) -> Result<()> {
+        let Some(project) = self.selected_project().map(|p| p.project.clone()) else { return Ok(()) };
+        self.input_buffer = self.service.suggest_project_name(&project.name).await?;
+        self.input_mode = InputMode::CloneProject;
         Ok(())
     }
 
-    /// Load projects from database
-    pub async fn load_projects(&mut self) -> Result<()> {
-        self.projects = match self.view_mode {
-            ViewMode::ArchivedProjects => self.service.list_all_projects().await?,
-            _ => self.service.list_active_projects().await?,
-        };
-        // Reset selection if out of bounds
-        if self.selected_index >= self.projects.len() && !self.projects.is_empty() {
-            self.selected_index = self.projects.len() - 1;
+    /// Copy the selected project with its open todos under the typed name
+    pub async fn save_clone_project(&mut self) -> Result<()> {
+        let name = self.input_buffer.trim().to_string();
+        let project_id = self.selected_project().map(|p| p.project.id);
+        self.cancel_input();
+        let (Some(id), false) = (project_id, name.is_empty()) else { return Ok(()) };
+        match self.service.clone_project(id, Some(&name), false).await {
+            Ok(copy) => {
+                self.set_success(format!("Copied to '{}'", copy.name));
+                self.load_projects().await?;
+                if let Some(index) = self.projects.iter().position(|p| p.project.id == copy.id) {
+                    self.selected_index = index + self.project_row_offset();
+                }
+            }
+            Err(e) => self.report_error(&e),
         }
         Ok(())
     }
 
-    /// Load todos for the current project
-    pub async fn load_todos(&mut self, project_id: i64) -> Result<()> {
-        self.todos = if self.show_completed {
-            self.service.list_all_todos(project_id).await?
-        } else {
-            self.service.list_active_todos(project_id).await?
+    /// Start edit due date mode for the selected todo
+    pub fn start_edit_due_date(&mut self) {
+        let todo = match self.view_mode {
+            ViewMode::TodoList(_) => self.todos.get(self.selected_index),
+            ViewMode::Calendar => self.calendar_day_todos().get(self.selected_index).copied(),
+            _ => None,
         };
-        // Reset selection if out of bounds
-        if self.selected_index >= self.todos.len() && !self.todos.is_empty() {
-            self.selected_index = self.todos.len() - 1;
+        if let Some(todo) = todo {
+            self.input_buffer = todo
+                .due_date
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            self.input_mode = InputMode::EditDueDate;
         }
-        Ok(())
     }
 
-    /// Navigate to previous item
-    pub fn previous_item(&mut self) {
-        let len = match &self.view_mode {
-            ViewMode::ProjectList | ViewMode::ArchivedProjects => self.projects.len(),
-            ViewMode::TodoList(_) => self.todos.len(),
-            ViewMode::Help => 0,
+    /// Save the edited due date; an empty buffer clears it
+    pub async fn save_due_date(&mut self) -> Result<()> {
+        let todo_id = match self.view_mode {
+            ViewMode::TodoList(_) => self.todos.get(self.selected_index).map(|t| t.id),
+            ViewMode::Calendar => self.calendar_day_todos().get(self.selected_index).map(|t| t.id),
+            _ => None,
         };
 
-        if len > 0 && self.selected_index > 0 {
-            self.selected_index -= 1;
+        if let Some(id) = todo_id {
+            match parse_target_date(self.input_buffer.trim(), self.config.timezone) {
+                Ok(due_date) => match self.service.update_todo_due_date(id, due_date).await {
+                    Ok(_) => {
+                        self.set_success(match due_date {
+                            Some(date) => format!("Due date set to {}", date),
+                            None => "Due date cleared".to_string(),
+                        });
+                        self.reload_view().await?;
+                    }
+                    Err(e) => self.report_error(&e),
+                },
+                Err(e) => self.set_error(e),
+            }
         }
+        self.cancel_input();
+        Ok(())
     }
 
-    /// Navigate to next item
-    pub fn next_item(&mut self) {
-        let len = match &self.view_mode {
-            ViewMode::ProjectList | ViewMode::ArchivedProjects => self.projects.len(),
-            ViewMode::TodoList(_) => self.todos.len(),
-            ViewMode::Help => 0,
-        };
-
-        if len > 0 && self.selected_index < len - 1 {
-            self.selected_index += 1;
+    /// Save the edited estimate; an empty buffer clears it
+    pub async fn save_estimate(&mut self) -> Result<()> {
+        if let ViewMode::TodoList(project_id) = self.view_mode
+            && let Some(todo) = self.todos.get(self.selected_index)
+        {
+            let input = self.input_buffer.trim();
+            let estimate = if input.is_empty() {
+                None
+            } else {
+                match input.parse::<i64>() {
+                    Ok(value) => Some(value),
+                    Err(_) => {
+                        self.set_error(format!("Invalid estimate: {}", input));
+                        self.cancel_input();
+                        return Ok(());
+                    }
+                }
+            };
+            match self.service.update_todo_estimate(todo.id, estimate).await {
+                Ok(_) => {
+                    self.load_todos(project_id).await?;
+                    self.set_success("Estimate updated");
+                }
+                Err(e) => self.report_error(&e),
+            }
         }
+        self.cancel_input();
+        Ok(())
     }
 
-    /// Enter selected project (drill down to todos)
-    pub async fn enter_project(&mut self) -> Result<()> {
-        if let Some(project) = self.projects.get(self.selected_index) {
-            let project_id = project.project.id;
-            self.current_project = Some(self.service.get_project(project_id).await?);
-            self.view_mode = ViewMode::TodoList(project_id);
-            self.selected_index = 0;
-            self.load_todos(project_id).await?;
+    /// Save the edited todo description
+    pub async fn save_todo(&mut self) -> Result<()> {
+        if let ViewMode::TodoList(project_id) = self.view_mode
+            && let Some(todo) = self.todos.get(self.selected_index)
+        {
+            let description = self.input_buffer.trim().to_string();
+            if !description.is_empty() {
+                match self.service.update_todo(todo.id, &description).await {
+                    Ok(_) => {
+                         self.load_todos(project_id).await?;
+                         self.set_success("Todo updated");
+                    }
+                    Err(e) => self.report_error(&e),
+                }
+            }
         }
+        self.cancel_input();
         Ok(())
     }
 
-    /// Go back to project list
-    pub async fn back_to_projects(&mut self) -> Result<()> {
-        self.view_mode = ViewMode::ProjectList;
-        self.current_project = None;
-        self.selected_index = 0;
+
+    /// Initialize app - apply grooming rules and the prune policy if set to
+    /// run on start, and load projects
+    pub async fn init(&mut self) -> Result<()> {
+        if self.config.groom_on_start && !self.config.groom_rules.is_empty() {
+            match self.service.groom(&self.config.groom_rules, false).await {
+                Ok(changes) if changes.is_empty() => {}
+                Ok(changes) => self.set_status(format!("Grooming rules made {}", plural(changes.len(), "change"))),
+                Err(e) => self.set_error(format!("Grooming failed: {}", e)),
+            }
+        }
+        if self.config.prune_on_start
+            && let Some(policy) = &self.config.prune
+        {
+            match self.service.prune(policy, false).await {
+                Ok(pruned) if pruned.is_empty() => {}
+                Ok(pruned) => self.set_status(format!("Pruned {}", plural(pruned.len(), "completed todo"))),
+                Err(e) => self.set_error(format!("Pruning failed: {}", e)),
+            }
+        }
+        self.load_template_names().await?;
+        self.load_workspaces().await?;
         self.load_projects().await?;
         Ok(())
     }
 
-    /// Toggle between active and archived projects
-    pub async fn toggle_archived(&mut self) -> Result<()> {
-        self.view_mode = match &self.view_mode {
-            ViewMode::ProjectList => ViewMode::ArchivedProjects,
-            ViewMode::ArchivedProjects => ViewMode::ProjectList,
-            _ => return Ok(()),
+    /// Apply edits to the config file without a restart; called from the main loop
+    pub async fn reload_config_if_changed(&mut self) -> Result<()> {
+        if self.config_checked_at.elapsed() < CONFIG_CHECK_INTERVAL {
+            return Ok(());
+        }
+        self.config_checked_at = Instant::now();
+        let modified = Config::file_modified_at();
+        if modified == self.config_modified {
+            return Ok(());
+        }
+        self.config_modified = modified;
+
+        let config = match Config::load() {
+            Ok(config) => config,
+            Err(err) => {
+                self.set_error(format!("Config not reloaded: {:#}", err));
+                return Ok(());
+            }
         };
-        self.selected_index = 0;
-        self.load_projects().await?;
+        let sort_changed = config.default_sort != self.config.default_sort;
+        let mut message = String::from("Config reloaded");
+        if config.main_database_path != self.config.main_database_path {
+            message.push_str(" (database path applies after restart)");
+        }
+        // The open database stays in use until restart, and the look and
+        // logging stay as picked at start (possibly by flags)
+        self.config = Config {
+            database_path: self.config.database_path.clone(),
+            main_database_path: self.config.main_database_path.clone(),
+            profile: self.config.profile.clone(),
+            pool_sizes: self.config.pool_sizes,
+            log_file: self.config.log_file.clone(),
+            log_level: self.config.log_level,
+            theme: self.config.theme,
+            ascii: self.config.ascii,
+            icons: self.config.icons,
+            ..config
+        };
+
+        if sort_changed {
+            match default_sort(&self.config) {
+                Some(Ok(sort)) => self.todo_sort = sort,
+                Some(Err(err)) => message = err,
+                None => self.todo_sort = TodoOrder::default(),
+            }
+            if let ViewMode::TodoList(project_id) = self.view_mode {
+                self.request_todos(project_id);
+            }
+        }
+        self.set_success(message);
         Ok(())
     }
 
-    /// Show help view
-    pub fn show_help(&mut self) {
-        self.view_mode = ViewMode::Help;
+    /// Refresh the current view when the database was written to, e.g. by
+    /// the web server or another TUI; called from the main loop
+    pub async fn refresh_if_changed(&mut self) -> Result<()> {
+        // Don't move rows under an edit in progress; checked again once it ends
+        if self.input_mode != InputMode::Normal {
+            return Ok(());
+        }
+        let published = self.drain_events();
+        if !published && self.data_checked_at.elapsed() < DATA_CHECK_INTERVAL {
+            return Ok(());
+        }
+        self.data_checked_at = Instant::now();
+        let version = self.service.change_marker().await?.version;
+        for notice in self.service.take_notices() {
+            self.set_warning(notice);
+        }
+        let changed = published || self.data_version.is_some_and(|known| known != version);
+        self.data_version = Some(version);
+        if changed {
+            self.preview_project_id = None;
+            self.reload_view().await?;
+        }
+        Ok(())
     }
 
-    /// Toggle completed todos visibility
-    pub async fn toggle_completed(&mut self) -> Result<()> {
-        if let ViewMode::TodoList(project_id) = self.view_mode {
-            self.show_completed = !self.show_completed;
-            self.selected_index = 0;
-            self.load_todos(project_id).await?;
+    /// Follow the service's event bus, for when the web server shares this
+    /// process and its edits should show up without waiting for a poll
+    pub fn watch_events(&mut self) {
+        self.events = Some(self.service.events().subscribe());
+    }
+
+    /// Whether anything was published since the last call
+    fn drain_events(&mut self) -> bool {
+        let Some(events) = &mut self.events else {
+            return false;
+        };
+        let mut published = false;
+        loop {
+            match events.try_recv() {
+                Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) => published = true,
+                Err(_) => return published,
+            }
         }
-        Ok(())
     }
 
-    /// Set status message
-    pub fn set_status(&mut self, message: impl Into<String>) {
-        self.status_message = Some(message.into());
+    /// Load projects from database
+    pub async fn load_projects(&mut self) -> Result<()> {
+        self.loader.cancel(LoadKind::Projects);
+        let projects = match self.view_mode {
+            ViewMode::ArchivedProjects => self.service.list_all_projects().await?,
+            _ => self.service.list_active_projects().await?,
+        };
+        self.set_projects(projects);
+        self.totals = self.service.get_totals().await?;
+        Ok(())
     }
 
-    /// Clear status message
-    pub fn clear_status(&mut self) {
-        self.status_message = None;
+    /// Reload projects in the background, keeping the current list on screen meanwhile
+    pub fn request_projects(&mut self) {
+        let service = self.service.clone();
+        let archived = self.view_mode == ViewMode::ArchivedProjects;
+        self.loader.request(LoadKind::Projects, async move {
+            let projects = if archived {
+                service.list_all_projects().await?
+            } else {
+                service.list_active_projects().await?
+            };
+            let totals = service.get_totals().await?;
+            Ok(Loaded::Projects { archived, projects, totals })
+        });
     }
 
-    /// Start add project mode
-    pub fn start_add_project(&mut self) {
-        self.input_mode = InputMode::AddProject;
-        self.input_buffer.clear();
+    fn set_projects(&mut self, mut projects: Vec<ProjectWithStats>) {
+        self.scope_projects(&mut projects);
+        (self.projects, self.project_tree) = tree::arrange(projects, &self.collapsed_projects);
+        // Reset selection if out of bounds; with the todos pane focused the
+        // project selection is kept in `project_index`
+        let rows = self.projects.len() + self.project_row_offset();
+        let selection = match self.view_mode {
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => &mut self.selected_index,
+            _ => &mut self.project_index,
+        };
+        if *selection >= rows && rows > 0 {
+            *selection = rows - 1;
+        }
+        self.preview_project_id = None;
     }
 
-    /// Start add todo mode
-    pub fn start_add_todo(&mut self) {
-        if matches!(self.view_mode, ViewMode::TodoList(_)) {
-            self.input_mode = InputMode::AddTodo;
-            self.input_buffer.clear();
+    /// Load the highlighted project's todos for the split view when the
+    /// selection changes; called from the main loop
+    pub fn refresh_preview(&mut self) {
+        if !self.split_view
+            || !matches!(self.view_mode, ViewMode::ProjectList | ViewMode::ArchivedProjects)
+        {
+            return;
         }
+        let project_id = self.selected_project().map(|p| p.project.id);
+        if project_id == self.preview_project_id {
+            return;
+        }
+        self.preview_project_id = project_id;
+        let Some(project_id) = project_id else {
+            self.loader.cancel(LoadKind::Preview);
+            self.preview_todos.clear();
+            return;
+        };
+        let service = self.service.clone();
+        let show_completed = self.show_completed;
+        let order = self.todo_sort.clone();
+        self.loader.request(LoadKind::Preview, async move {
+            let todos = service.list_sorted_todos(project_id, show_completed, &order).await?;
+            Ok(Loaded::Preview { project_id, todos })
+        });
     }
 
-    /// Start edit description mode
-    pub fn start_edit_description(&mut self) {
-        if matches!(self.view_mode, ViewMode::TodoList(_)) {
-            self.input_mode = InputMode::EditDescription;
-            // Pre-fill with existing description if any
-            self.input_buffer = self.current_project
-                .as_ref()
-                .and_then(|p| p.description.clone())
-                .unwrap_or_default();
+    /// JIRA issue key of a todo's external reference, when JIRA is configured
+    pub fn ticket_key(&self, todo: &Todo) -> Option<String> {
+        let client = JiraClient::from_config(&self.config)?;
+        client.issue_key(todo.external_ref.as_deref()?)
+    }
+
+    /// Fetch the JIRA status of the expanded todo's ticket unless a recent
+    /// one is cached; called from the main loop
+    pub fn refresh_ticket(&mut self) {
+        let Some(key) = self.get_expanded_todo().and_then(|todo| self.ticket_key(todo)) else {
+            return;
+        };
+        let fresh = self.tickets.get(&key).is_some_and(|t| t.fetched_at.elapsed() < TICKET_TTL);
+        if fresh || self.loader.is_pending(LoadKind::Ticket) {
+            return;
         }
+        let Some(client) = JiraClient::from_config(&self.config) else { return };
+        self.loader.request(LoadKind::Ticket, async move {
+            // Failures are shown in the details pane rather than as errors
+            let status = client.issue(&key).await.map(|issue| issue.status).map_err(|e| e.to_string());
+            Ok(Loaded::Ticket { key, status })
+        });
     }
 
-    /// Save the edited description
-    pub async fn save_description(&mut self) -> Result<()> {
-        if let ViewMode::TodoList(project_id) = self.view_mode {
-            let description = if self.input_buffer.trim().is_empty() {
-                None
-            } else {
-                Some(self.input_buffer.as_str())
-            };
-            self.service.update_project_description(project_id, description).await?;
-            // Reload project to get updated description
-            self.current_project = Some(self.service.get_project(project_id).await?);
-            self.set_status("Description updated");
+    /// Fetch the todos linked to or from the expanded todo once it changes;
+    /// called from the main loop
+    pub fn refresh_related(&mut self) {
+        if self.related_for == self.expanded_todo_id {
+            return;
         }
-        self.cancel_input();
-        Ok(())
+        self.related.clear();
+        self.mentions.clear();
+        self.related_for = self.expanded_todo_id;
+        let Some(todo_id) = self.expanded_todo_id else {
+            self.loader.cancel(LoadKind::Related);
+            self.related_index = None;
+            return;
+        };
+        let service = self.service.clone();
+        self.loader.request(LoadKind::Related, async move {
+            let related = service.related_todos(todo_id).await?;
+            let mentions = service.todo_mentions(todo_id).await?;
+            Ok(Loaded::Related { todo_id, related, mentions })
+        });
     }
 
-    /// Start command mode
-    pub fn start_command_mode(&mut self) {
-        self.input_mode = InputMode::Command;
-        self.input_buffer.clear();
+    /// Apply lists fetched in the background; called from the main loop
+    pub fn poll_loads(&mut self) {
+        for loaded in self.loader.poll() {
+            self.apply_loaded(loaded);
+        }
     }
 
-    /// Cancel input mode
-    pub fn cancel_input(&mut self) {
-        self.input_mode = InputMode::Normal;
-        self.input_buffer.clear();
+    /// Wait for background fetches to land
+    pub async fn finish_loading(&mut self) {
+        for loaded in self.loader.wait().await {
+            self.apply_loaded(loaded);
+        }
     }
 
-    /// Toggle expansion of the selected todo
-    pub fn toggle_todo_expand(&mut self) {
-        if let ViewMode::TodoList(_) = self.view_mode {
-            if let Some(todo) = self.todos.get(self.selected_index) {
-                if self.expanded_todo_id == Some(todo.id) {
-                    // Collapse if already expanded
-                    self.expanded_todo_id = None;
-                } else {
-                    // Expand this todo
-                    self.expanded_todo_id = Some(todo.id);
+    /// Show fetched data, unless the user has since moved to another view
+    fn apply_loaded(&mut self, loaded: Result<Loaded, String>) {
+        match loaded {
+            Ok(Loaded::Projects { archived, projects, totals }) => {
+                self.totals = totals;
+                if archived == (self.view_mode == ViewMode::ArchivedProjects) {
+                    self.set_projects(projects);
+                }
+            }
+            Ok(Loaded::Todos { project_id, todos, stats, projects }) => {
+                if self.view_mode == ViewMode::TodoList(project_id) {
+                    self.set_todos(todos);
+                    self.current_stats = Some(*stats);
+                    if let Some(projects) = projects {
+                        self.set_projects(projects);
+                    }
                 }
             }
+            Ok(Loaded::Preview { project_id, todos }) => {
+                if self.preview_project_id == Some(project_id) {
+                    self.preview_todos = todos;
+                }
+            }
+            Ok(Loaded::Ticket { key, status }) => {
+                self.tickets.insert(key, TicketStatus { status, fetched_at: Instant::now() });
+            }
+            Ok(Loaded::Related { todo_id, related, mentions }) => {
+                if self.related_for == Some(todo_id) {
+                    self.related = related;
+                    self.mentions = mentions;
+                    if self.related_rows() == 0 && self.related_index.take().is_some() {
+                        self.set_warning(NO_RELATED);
+                    }
+                }
+            }
+            Err(e) => self.set_error(format!("Error: {}", e)),
         }
     }
 
-    /// Start edit todo details mode
-    pub fn start_edit_todo_details(&mut self) {
-        if let ViewMode::TodoList(_) = self.view_mode {
-            if let Some(todo_id) = self.expanded_todo_id {
-                // Find the todo and pre-fill with existing details
-                if let Some(todo) = self.todos.iter().find(|t| t.id == todo_id) {
-                    self.input_buffer = todo.details.clone().unwrap_or_default();
-                    self.input_mode = InputMode::EditTodoDetails;
-                }
-            }
+    /// Current pane layout, for saving between sessions
+    pub fn layout_prefs(&self) -> LayoutPrefs {
+        LayoutPrefs {
+            split_view: self.split_view,
+            project_pane_width: self.project_pane_width,
+            projects_collapsed: self.projects_collapsed,
+            detail_height: self.detail_height,
+            detailed_rows: self.detailed_rows,
+            wrap_rows: self.wrap_rows,
+            todo_columns: self.todo_columns.clone(),
+            project_columns: self.project_columns.clone(),
         }
     }
 
-    /// Save the edited todo details
-    pub async fn save_todo_details(&mut self) -> Result<()> {
-        if let Some(todo_id) = self.expanded_todo_id {
-            let details = if self.input_buffer.trim().is_empty() {
-                None
-            } else {
+    /// Restore a saved pane layout, clamping sizes to what the keys allow
+    pub fn apply_layout(&mut self, layout: LayoutPrefs) {
+        self.split_view = layout.split_view;
+        self.project_pane_width = layout.project_pane_width.clamp(MIN_PANE_WIDTH, MAX_PANE_WIDTH);
+        self.projects_collapsed = layout.projects_collapsed;
+        self.detail_height = layout.detail_height.clamp(MIN_DETAIL_HEIGHT, MAX_DETAIL_HEIGHT);
+        self.detailed_rows = layout.detailed_rows;
+        self.wrap_rows = layout.wrap_rows;
+        self.todo_columns = layout.todo_columns;
+        self.project_columns = layout.project_columns;
+    }
+
+    /// What is open now, for picking up there next session
+    pub fn session_state(&self) -> SessionState {
+        let project_id = |row: usize| self.project_at_row(row).map(|p| p.project.id);
+        match self.view_mode {
+            ViewMode::TodoList(project_id) => SessionState {
+                view: SessionView::Todos,
+                project_id: Some(project_id),
+                todo_id: self.todos.get(self.selected_index).map(|t| t.id),
+                expanded_todo_id: self.expanded_todo_id,
+                detail_scroll: self.detail_scroll,
+            },
+            ViewMode::ArchivedProjects => SessionState {
+                view: SessionView::Archived,
+                project_id: project_id(self.selected_index),
+                ..SessionState::default()
+            },
+            ViewMode::ProjectList => SessionState {
+                project_id: project_id(self.selected_index),
+                ..SessionState::default()
+            },
+            // Review, help and messages are passing visits; reopen the list behind them
+            ViewMode::AllTodos
+            | ViewMode::Review
+            | ViewMode::Help
+            | ViewMode::Messages
+            | ViewMode::Log
+            | ViewMode::Calendar => SessionState {
+                project_id: project_id(self.project_index),
+                ..SessionState::default()
+            },
+        }
+    }
+
+    /// Reopen what was open last session. Projects or todos deleted since are
+    /// skipped, leaving the project list.
+    pub async fn restore_session(&mut self, session: SessionState) -> Result<()> {
+        if session.view == SessionView::Archived {
+            self.view_mode = ViewMode::ArchivedProjects;
+            self.load_projects().await?;
+        }
+        let Some(index) = session
+            .project_id
+            .and_then(|id| self.projects.iter().position(|p| p.project.id == id))
+        else {
+            return Ok(());
+        };
+        self.selected_index = index + self.project_row_offset();
+        if session.view != SessionView::Todos {
+            return Ok(());
+        }
+
+        let project_id = self.projects[index].project.id;
+        self.open_project(project_id).await?;
+        self.load_todos(project_id).await?;
+        if let Some(row) = session
+            .todo_id
+            .and_then(|id| self.todos.iter().position(|t| t.id == id))
+        {
+            self.selected_index = row;
+        }
+        if session
+            .expanded_todo_id
+            .is_some_and(|id| self.todos.iter().any(|t| t.id == id))
+        {
+            self.expanded_todo_id = session.expanded_todo_id;
+            self.detail_scroll = session.detail_scroll;
+        }
+        Ok(())
+    }
+
+    /// Widen or narrow the project pane of the split view
+    pub fn resize_project_pane(&mut self, grow: bool, steps: u16) {
+        let delta = PANE_WIDTH_STEP.saturating_mul(steps);
+        self.project_pane_width = if grow {
+            self.project_pane_width.saturating_add(delta).min(MAX_PANE_WIDTH)
+        } else {
+            self.project_pane_width.saturating_sub(delta).max(MIN_PANE_WIDTH)
+        };
+    }
+
+    /// Toggle the side-by-side projects and todos layout
+    pub fn toggle_split_view(&mut self) {
+        self.split_view = !self.split_view;
+        if self.split_view && matches!(self.view_mode, ViewMode::TodoList(_)) {
+            // The project list may be stale after edits in the todo view
+            self.request_projects();
+        }
+    }
+
+    /// Switch the todo list between one-line and two-line rows
+    pub fn toggle_detailed_rows(&mut self) {
+        self.detailed_rows = !self.detailed_rows;
+        self.set_status(if self.detailed_rows { "Detailed rows" } else { "Compact rows" });
+    }
+
+    /// Switch long descriptions between wrapping and ending in `…`
+    pub fn toggle_wrap_rows(&mut self) {
+        self.wrap_rows = !self.wrap_rows;
+        self.set_status(if self.wrap_rows { "Wrapping long descriptions" } else { "Cutting long descriptions short" });
+    }
+
+    /// Open the selected todo's full text in a popup
+    pub fn show_full_text(&mut self) {
+        if matches!(self.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos)
+            && self.todos.get(self.selected_index).is_some()
+        {
+            self.input_mode = InputMode::FullText;
+        }
+    }
+
+    /// The selected todo once it has stayed selected for `preview_delay_ms`,
+    /// for its preview popup
+    pub fn hovered_todo(&self) -> Option<&Todo> {
+        if self.config.preview_delay_ms == 0
+            || self.input_mode != InputMode::Normal
+            || !matches!(self.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos)
+            || self.expanded_todo_id.is_some()
+        {
+            return None;
+        }
+        let todo = self.todos.get(self.selected_index)?;
+        match self.dwell.get() {
+            Some((id, since)) if id == todo.id => {
+                (since.elapsed() >= std::time::Duration::from_millis(self.config.preview_delay_ms)).then_some(todo)
+            }
+            _ => {
+                self.dwell.set(Some((todo.id, Instant::now())));
+                None
+            }
+        }
+    }
+
+    /// Columns of the todo table, in order
+    pub fn todo_table_columns(&self) -> &[TodoColumn] {
+        self.todo_columns.as_deref().unwrap_or(&self.config.todo_columns)
+    }
+
+    /// Columns of the project table, in order
+    pub fn project_table_columns(&self) -> &[ProjectColumn] {
+        self.project_columns.as_deref().unwrap_or(&self.config.project_columns)
+    }
+
+    /// Load todos for the current project
+    pub async fn load_todos(&mut self, project_id: i64) -> Result<()> {
+        self.loader.cancel(LoadKind::Todos);
+        let todos = self.service.list_sorted_todos(project_id, self.show_completed, &self.todo_sort).await?;
+        self.set_todos(todos);
+        self.current_stats = Some(self.service.get_project_with_stats(project_id).await?);
+        if self.split_view {
+            // Keep the counts in the project pane current
+            self.load_projects().await?;
+        }
+        Ok(())
+    }
+
+    /// Reload the current project's todos in the background, keeping the
+    /// current list on screen meanwhile
+    pub fn request_todos(&mut self, project_id: i64) {
+        let service = self.service.clone();
+        let show_completed = self.show_completed;
+        let order = self.todo_sort.clone();
+        let with_projects = self.split_view;
+        self.loader.request(LoadKind::Todos, async move {
+            let todos = service.list_sorted_todos(project_id, show_completed, &order).await?;
+            let stats = service.get_project_with_stats(project_id).await?;
+            let projects = match with_projects {
+                true => Some(service.list_active_projects().await?),
+                false => None,
+            };
+            Ok(Loaded::Todos { project_id, todos, stats: Box::new(stats), projects })
+        });
+    }
+
+    /// Filter fetched todos, already in `todo_sort` order, into the list
+    fn set_todos(&mut self, todos: Vec<Todo>) {
+        // The expanded todo's mentions follow its details
+        let details = |todos: &[Todo], id: i64| todos.iter().find(|t| t.id == id).map(|t| t.details.clone());
+        if let Some(id) = self.expanded_todo_id
+            && details(&self.todos, id) != details(&todos, id)
+        {
+            self.related_for = None;
+        }
+        self.todos = todos;
+        // A filter saved before it was checked and no longer parsing hides nothing
+        if let Some(Ok(query)) = self.todo_filter.as_deref().map(str::parse::<TodoQuery>) {
+            let today = self.config.timezone.today();
+            let project_name = |project_id: i64| match &self.current_project {
+                Some(project) if project.id == project_id => project.name.as_str(),
+                _ => self
+                    .projects
+                    .iter()
+                    .find(|p| p.project.id == project_id)
+                    .map_or("", |p| p.project.name.as_str()),
+            };
+            self.todos.retain(|t| query.matches(t, project_name(t.project_id), today));
+        }
+        // Reset selection if out of bounds
+        if self.selected_index >= self.todos.len() && !self.todos.is_empty() {
+            self.selected_index = self.todos.len() - 1;
+        }
+    }
+
+    /// Number of rows in the current list
+    pub fn list_len(&self) -> usize {
+        match &self.view_mode {
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => {
+                self.projects.len() + self.project_row_offset()
+            }
+            ViewMode::TodoList(_) | ViewMode::AllTodos | ViewMode::Review => self.todos.len(),
+            ViewMode::Calendar => self.calendar_day_todos().len(),
+            ViewMode::Help | ViewMode::Messages | ViewMode::Log => 0,
+        }
+    }
+
+    /// Navigate to previous item
+    pub fn previous_item(&mut self) {
+        if self.list_len() > 0 && self.selected_index > 0 {
+            self.selected_index -= 1;
+        }
+    }
+
+    /// Navigate to next item
+    pub fn next_item(&mut self) {
+        let len = self.list_len();
+        if len > 0 && self.selected_index < len - 1 {
+            self.selected_index += 1;
+        }
+    }
+
+    /// Select a row by index, clamped to the list
+    pub fn select_row(&mut self, index: usize) {
+        self.selected_index = index.min(self.list_len().saturating_sub(1));
+    }
+
+    /// Select the top, middle or bottom row on screen (`H`/`M`/`L`)
+    pub fn select_visible_row(&mut self, position: char) {
+        let last = self.list_len().min(self.visible_rows.get()).saturating_sub(1);
+        let index = match position {
+            'H' => 0,
+            'M' => last / 2,
+            _ => last,
+        };
+        self.select_row(index);
+    }
+
+    /// Add a digit to the pending count
+    pub fn push_count_digit(&mut self, digit: u32) {
+        let count = self.pending_count.unwrap_or(0) * 10 + digit as usize;
+        self.pending_count = Some(count.min(MAX_COUNT));
+    }
+
+    /// Pending count and key as typed so far, e.g. `3d`
+    pub fn pending_keys(&self) -> String {
+        let count = self.pending_count.map(|n| n.to_string()).unwrap_or_default();
+        format!("{}{}", count, self.pending_key.map(String::from).unwrap_or_default())
+    }
+
+    /// Enter selected project (drill down to todos), or All todos on the top row
+    pub async fn enter_project(&mut self) -> Result<()> {
+        if self.view_mode == ViewMode::ProjectList && self.selected_index == 0 {
+            return self.open_all_todos().await;
+        }
+        if let Some(project) = self.selected_project() {
+            self.open_project(project.project.id).await?;
+        }
+        Ok(())
+    }
+
+    /// Rows above the projects in the project list: the active list starts
+    /// with All todos, the archived list has none
+    pub fn project_row_offset(&self) -> usize {
+        match self.view_mode {
+            ViewMode::ArchivedProjects => 0,
+            _ => 1,
+        }
+    }
+
+    /// The project on a row of the project list
+    fn project_at_row(&self, row: usize) -> Option<&ProjectWithStats> {
+        self.projects.get(row.checked_sub(self.project_row_offset())?)
+    }
+
+    /// The project on the selected row, unless All todos is selected
+    pub fn selected_project(&self) -> Option<&ProjectWithStats> {
+        self.project_at_row(self.selected_index)
+    }
+
+    /// Open todos in the listed projects, folded sub-projects included
+    pub fn listed_open_todos(&self) -> i64 {
+        self.top_level_rows().map(TreeRow::active_todos).sum()
+    }
+
+    fn top_level_rows(&self) -> impl Iterator<Item = &TreeRow> {
+        self.project_tree.iter().filter(|row| row.depth == 0)
+    }
+
+    /// Drop the projects outside the current workspace
+    fn scope_projects(&self, projects: &mut Vec<ProjectWithStats>) {
+        if let Some(workspace) = &self.workspace {
+            projects.retain(|p| p.project.workspace_id == Some(workspace.id));
+        }
+    }
+
+    /// `3 active projects, 1 archived, 12 open todos`
+    pub fn totals_summary(&self) -> String {
+        if self.workspace.is_some() {
+            // The totals cover every workspace, so count the narrowed list
+            let listed: usize = self.top_level_rows().map(|row| 1 + row.sub_projects).sum();
+            return format!(
+                "{}, {}",
+                plural(listed, "project"),
+                plural(self.listed_open_todos() as usize, "open todo")
+            );
+        }
+        format!(
+            "{}, {} archived, {}",
+            plural(self.totals.active_projects as usize, "active project"),
+            self.totals.archived_projects,
+            plural(self.totals.open_todos as usize, "open todo")
+        )
+    }
+
+    /// Name of a loaded project, for labelling todos from several projects
+    pub fn project_name(&self, project_id: i64) -> &str {
+        self.projects
+            .iter()
+            .find(|p| p.project.id == project_id)
+            .map_or("?", |p| p.project.name.as_str())
+    }
+
+    /// Open the active todos of every active project in one list. Each
+    /// project keeps its own order, so reordering is left to its own list.
+    pub async fn open_all_todos(&mut self) -> Result<()> {
+        self.project_index = 0;
+        self.current_project = None;
+        self.current_stats = None;
+        self.todo_sort = configured_sort(&self.config);
+        self.todo_filter = None;
+        self.view_mode = ViewMode::AllTodos;
+        self.selected_index = 0;
+        self.expanded_todo_id = None;
+        self.detail_fullscreen = false;
+        self.load_all_todos().await
+    }
+
+    /// Load the active todos of every active project, and the project names to label them
+    pub async fn load_all_todos(&mut self) -> Result<()> {
+        let mut todos = self.service.list_open_todos().await?;
+        let mut projects = self.service.list_active_projects().await?;
+        self.scope_projects(&mut projects);
+        if self.workspace.is_some() {
+            todos.retain(|t| projects.iter().any(|p| p.project.id == t.project_id));
+        }
+        self.projects = projects;
+        // Spread over projects, so sorted here rather than by one query
+        self.todo_sort.apply(&mut todos);
+        self.set_todos(todos);
+        Ok(())
+    }
+
+    /// Open a project's todo list by ID
+    pub async fn open_project(&mut self, project_id: i64) -> Result<()> {
+        match self.projects.iter().position(|p| p.project.id == project_id) {
+            Some(index) => {
+                // Its row in the project list, below All todos
+                self.project_index = index + 1;
+                self.current_project = Some(self.projects[index].project.clone());
+                self.current_stats = Some(self.projects[index].clone());
+            }
+            None => {
+                self.current_project = Some(self.service.get_project(project_id).await?);
+                self.current_stats = None;
+            }
+        }
+        self.breadcrumb = match self.current_project.as_ref().and_then(|p| p.parent_id) {
+            Some(_) => {
+                let mut path = self.service.project_path(project_id).await?;
+                path.pop();
+                path.into_iter().map(|p| p.name).collect()
+            }
+            None => Vec::new(),
+        };
+        let settings = self.service.get_project_settings(project_id).await?;
+        self.apply_project_settings(settings);
+        self.view_mode = ViewMode::TodoList(project_id);
+        self.selected_index = 0;
+        self.expanded_todo_id = None;
+        self.detail_fullscreen = false;
+        // The split view preview already holds this project's todos
+        if self.preview_project_id == Some(project_id) {
+            self.set_todos(self.preview_todos.clone());
+        } else {
+            self.todos.clear();
+        }
+        self.request_todos(project_id);
+        Ok(())
+    }
+
+    /// Yank `count` todos starting at the selection into the register
+    pub fn yank_todos(&mut self, count: usize) {
+        if !matches!(self.view_mode, ViewMode::TodoList(_)) {
+            return;
+        }
+        let yanked: Vec<YankedTodo> = self
+            .todos
+            .iter()
+            .skip(self.selected_index)
+            .take(count)
+            .map(YankedTodo::from)
+            .collect();
+        match yanked.len() {
+            0 => return,
+            1 => self.set_success("Yanked 1 todo"),
+            n => self.set_success(format!("Yanked {} todos", n)),
+        }
+        self.register = yanked;
+    }
+
+    /// Paste the register into the open project as new todos at the end
+    pub async fn paste_todos(&mut self) -> Result<()> {
+        let ViewMode::TodoList(project_id) = self.view_mode else {
+            return Ok(());
+        };
+        if self.register.is_empty() {
+            self.set_warning("Nothing yanked (y yanks the selected todo)");
+            return Ok(());
+        }
+
+        let service = self.service.clone();
+        let register = self.register.clone();
+        let label = format!("Pasting {}", plural(register.len(), "todo"));
+        self.tasks.spawn(label, register.len(), move |progress| async move {
+            for (i, yanked) in register.iter().enumerate() {
+                let todo = service.create_todo(project_id, &yanked.description).await?;
+                if yanked.details.is_some() {
+                    service.update_todo_details(todo.id, yanked.details.as_deref()).await?;
+                }
+                if yanked.estimate.is_some() {
+                    service.update_todo_estimate(todo.id, yanked.estimate).await?;
+                }
+                progress.set(i + 1);
+            }
+            Ok(format!("Pasted {}", plural(register.len(), "todo")))
+        });
+        Ok(())
+    }
+
+    /// Delete `count` items starting at the selection (`dd`, `3dd`)
+    pub fn delete_items(&mut self, count: usize) {
+        let (noun, ids): (&str, Vec<i64>) = match self.view_mode {
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => {
+                // All todos is not a project to delete
+                let Some(start) = self.selected_index.checked_sub(self.project_row_offset()) else {
+                    return;
+                };
+                ("project", self.projects.iter().skip(start).take(count).map(|p| p.project.id).collect())
+            }
+            ViewMode::TodoList(_) => (
+                "todo",
+                self.todos.iter().skip(self.selected_index).take(count).map(|t| t.id).collect(),
+            ),
+            _ => return,
+        };
+        if ids.is_empty() {
+            return;
+        }
+
+        let service = self.service.clone();
+        let project_id = match self.view_mode {
+            ViewMode::TodoList(project_id) => Some(project_id),
+            _ => None,
+        };
+        let label = format!("Deleting {}", plural(ids.len(), noun));
+        self.tasks.spawn(label, ids.len(), move |progress| async move {
+            for (i, id) in ids.iter().enumerate() {
+                if noun == "project" {
+                    service.delete_project(*id).await?;
+                } else {
+                    service.delete_todo(*id).await?;
+                }
+                progress.set(i + 1);
+            }
+            if let Some(project_id) = project_id {
+                service.normalize_positions(project_id).await?;
+            }
+            Ok(match ids.len() {
+                1 if noun == "project" => "Project deleted".to_string(),
+                1 => "Todo deleted".to_string(),
+                n => format!("{} deleted", plural(n, noun)),
+            })
+        });
+    }
+
+    /// Show the results of finished background tasks and reload what they changed
+    pub async fn poll_tasks(&mut self) -> Result<()> {
+        let finished = self.tasks.poll();
+        self.show_task_results(finished).await
+    }
+
+    /// Wait for background tasks, so quitting doesn't cut a bulk operation short
+    pub async fn finish_tasks(&mut self) -> Result<()> {
+        let finished = self.tasks.wait().await;
+        self.show_task_results(finished).await
+    }
+
+    async fn show_task_results(&mut self, finished: Vec<Result<String, String>>) -> Result<()> {
+        if finished.is_empty() {
+            return Ok(());
+        }
+        for result in finished {
+            match result {
+                Ok(message) => self.set_success(message),
+                Err(e) => self.set_error(format!("Error: {}", e)),
+            }
+        }
+        self.reload_view().await
+    }
+
+    /// Reload the data behind the current view
+    pub async fn reload_view(&mut self) -> Result<()> {
+        match self.view_mode {
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => {
+                self.request_projects();
+                Ok(())
+            }
+            ViewMode::TodoList(project_id) => {
+                self.request_todos(project_id);
+                Ok(())
+            }
+            ViewMode::AllTodos => self.load_all_todos().await,
+            ViewMode::Review => self.reload_review_todos().await,
+            ViewMode::Calendar => self.load_calendar().await,
+            ViewMode::Help | ViewMode::Messages | ViewMode::Log => Ok(()),
+        }
+    }
+
+    /// Copy the selected todo's text, or the selected project as a Markdown
+    /// checklist, to the system clipboard
+    pub async fn copy_to_clipboard(&mut self) -> Result<()> {
+        let (what, text) = match &self.view_mode {
+            ViewMode::TodoList(_) => {
+                let Some(todo) = self.todos.get(self.selected_index) else { return Ok(()) };
+                let text = match &todo.details {
+                    Some(details) => format!("{}\n\n{}", todo.description, details),
+                    None => todo.description.clone(),
+                };
+                ("todo", text)
+            }
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => {
+                let Some(project) = self.selected_project() else { return Ok(()) };
+                let project = project.project.clone();
+                let todos = self.service.list_all_todos(project.id).await?;
+                ("project", export::export_project(export::ExportFormat::Markdown, &project, &todos))
+            }
+            _ => return Ok(()),
+        };
+        match clipboard::copy(&text) {
+            Ok(via) => self.set_success(format!("Copied {} to clipboard ({})", what, via)),
+            Err(e) => self.set_error(format!("Error copying to clipboard: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Go back to project list
+    pub async fn back_to_projects(&mut self) -> Result<()> {
+        self.view_mode = ViewMode::ProjectList;
+        self.current_project = None;
+        self.current_stats = None;
+        // Return to the project that was open
+        self.selected_index = self.project_index;
+        self.request_projects();
+        Ok(())
+    }
+
+    /// Fold or unfold the sub-projects of the selected project
+    pub async fn toggle_project_collapsed(&mut self) -> Result<()> {
+        let Some((id, has_children)) = self
+            .selected_index
+            .checked_sub(self.project_row_offset())
+            .and_then(|i| Some((self.projects.get(i)?.project.id, self.project_tree.get(i)?.has_children)))
+        else {
+            return Ok(());
+        };
+        if !has_children {
+            self.set_status("No sub-projects to fold");
+            return Ok(());
+        }
+        if !self.collapsed_projects.remove(&id) {
+            self.collapsed_projects.insert(id);
+        }
+        self.load_projects().await
+    }
+
+    /// Go up from the open project to the one it is nested in
+    pub async fn open_parent_project(&mut self) -> Result<()> {
+        match self.current_project.as_ref().and_then(|p| p.parent_id) {
+            Some(parent_id) => {
+                // Unfold the parent, so its row can be found in the list
+                self.collapsed_projects.remove(&parent_id);
+                self.load_projects().await?;
+                self.open_project(parent_id).await
+            }
+            None => {
+                self.set_status("This is a top-level project");
+                Ok(())
+            }
+        }
+    }
+
+    /// Toggle between active and archived projects
+    pub fn toggle_archived(&mut self) {
+        self.view_mode = match &self.view_mode {
+            ViewMode::ProjectList => ViewMode::ArchivedProjects,
+            ViewMode::ArchivedProjects => ViewMode::ProjectList,
+            _ => return,
+        };
+        self.selected_index = 0;
+        self.projects.clear();
+        self.preview_project_id = None;
+        self.request_projects();
+    }
+
+    /// Start a review session over projects not yet reviewed today
+    pub async fn start_review(&mut self) -> Result<()> {
+        let due = self.service.list_projects_due_for_review().await?;
+        if due.is_empty() {
+            self.set_status("Nothing left to review today");
+            return Ok(());
+        }
+        self.review_queue = due.iter().map(|p| p.project.id).collect();
+        self.review_position = 0;
+        self.view_mode = ViewMode::Review;
+        self.load_review_project().await
+    }
+
+    /// Load the project at the current review position and its active todos
+    async fn load_review_project(&mut self) -> Result<()> {
+        let project_id = self.review_queue[self.review_position];
+        self.current_project = Some(self.service.get_project(project_id).await?);
+        self.expanded_todo_id = None;
+        self.selected_index = 0;
+        self.reload_review_todos().await
+    }
+
+    /// Reload the active todos of the project under review
+    pub async fn reload_review_todos(&mut self) -> Result<()> {
+        if let Some(project) = &self.current_project {
+            self.todos = self.service.list_active_todos(project.id).await?;
+            self.current_stats = Some(self.service.get_project_with_stats(project.id).await?);
+            if self.selected_index >= self.todos.len() && !self.todos.is_empty() {
+                self.selected_index = self.todos.len() - 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Mark the current project reviewed and advance to the next one
+    pub async fn next_review_project(&mut self) -> Result<()> {
+        if let Some(project) = &self.current_project {
+            self.service.mark_project_reviewed(project.id).await?;
+        }
+        self.review_position += 1;
+        if self.review_position < self.review_queue.len() {
+            self.load_review_project().await
+        } else {
+            let reviewed = self.review_queue.len();
+            self.end_review().await?;
+            self.set_success(format!("Review complete: {}", plural(reviewed, "project")));
+            Ok(())
+        }
+    }
+
+    /// Leave review mode, returning to the project list
+    pub async fn end_review(&mut self) -> Result<()> {
+        self.review_queue.clear();
+        self.review_position = 0;
+        self.back_to_projects().await
+    }
+
+    /// Show the week containing today in the calendar
+    pub async fn open_calendar(&mut self) -> Result<()> {
+        self.calendar_date = self.config.timezone.today();
+        self.view_mode = ViewMode::Calendar;
+        self.selected_index = 0;
+        self.expanded_todo_id = None;
+        self.load_calendar().await
+    }
+
+    /// Monday of the week shown in the calendar
+    pub fn calendar_week_start(&self) -> NaiveDate {
+        self.calendar_date - Duration::days(self.calendar_date.weekday().num_days_from_monday() as i64)
+    }
+
+    /// Load the todos due in the calendar's week, and the project names to label them
+    pub async fn load_calendar(&mut self) -> Result<()> {
+        let start = self.calendar_week_start();
+        self.calendar_todos = self
+            .service
+            .list_todos_due_between(start, start + Duration::days(6))
+            .await?;
+        self.projects = self.service.list_active_projects().await?;
+        self.select_row(self.selected_index);
+        Ok(())
+    }
+
+    /// Todos due on the selected calendar day
+    pub fn calendar_day_todos(&self) -> Vec<&Todo> {
+        self.calendar_todos
+            .iter()
+            .filter(|t| t.due_date == Some(self.calendar_date))
+            .collect()
+    }
+
+    /// Move the calendar selection by `days`, loading another week when it
+    /// leaves the one shown
+    pub async fn move_calendar(&mut self, days: i64) -> Result<()> {
+        let week = self.calendar_week_start();
+        self.calendar_date += Duration::days(days);
+        self.selected_index = 0;
+        if self.calendar_week_start() != week {
+            self.load_calendar().await?;
+        }
+        Ok(())
+    }
+
+    /// Jump the calendar back to today
+    pub async fn calendar_today(&mut self) -> Result<()> {
+        let today = self.config.timezone.today();
+        self.move_calendar((today - self.calendar_date).num_days()).await
+    }
+
+    /// Open the project of the selected calendar todo, with the todo selected
+    pub async fn open_calendar_todo(&mut self) -> Result<()> {
+        let Some(todo) = self.calendar_day_todos().get(self.selected_index).map(|t| (*t).clone()) else {
+            return Ok(());
+        };
+        self.open_todo(&todo).await?;
+        Ok(())
+    }
+
+    /// Open a todo's project with the todo selected; returns whether it is
+    /// listed there
+    async fn open_todo(&mut self, todo: &Todo) -> Result<bool> {
+        self.open_project(todo.project_id).await?;
+        self.load_todos(todo.project_id).await?;
+        match self.todos.iter().position(|t| t.id == todo.id) {
+            Some(row) => {
+                self.selected_index = row;
+                Ok(true)
+            }
+            None => {
+                self.set_warning("The todo is hidden by this project's filter or completed todos setting");
+                Ok(false)
+            }
+        }
+    }
+
+    /// Open the TUI on a todo, as `docket open HOME-42` does: its project
+    /// with the todo selected and its details shown
+    pub async fn open_todo_by_id(&mut self, todo_id: i64) -> Result<()> {
+        let todo = self.service.get_todo(todo_id).await?;
+        if self.open_todo(&todo).await? {
+            self.expanded_todo_id = Some(todo.id);
+        }
+        Ok(())
+    }
+
+    /// A todo's short ID, e.g. `HOME-42`, or `#42` when its project isn't
+    /// loaded
+    pub fn short_id(&self, todo: &Todo) -> String {
+        self.current_project
+            .iter()
+            .chain(self.projects.iter().map(|p| &p.project))
+            .find(|p| p.id == todo.project_id)
+            .map_or_else(|| format!("#{}", todo.id), |p| p.todo_short_id(todo.id))
+    }
+
+    /// Toggle completion of the selected calendar todo
+    pub async fn toggle_calendar_todo(&mut self) -> Result<()> {
+        if let Some(todo) = self.calendar_day_todos().get(self.selected_index) {
+            let todo_id = todo.id;
+            if let Err(e) = self.service.toggle_todo(todo_id).await {
+                self.set_error(format!("Error toggling todo: {}", e));
+            } else {
+                self.load_calendar().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Show help for the current view
+    pub fn show_help(&mut self) {
+        if self.view_mode != ViewMode::Help {
+            self.help_from = self.view_mode.clone();
+        }
+        self.view_mode = ViewMode::Help;
+        self.help_scroll = 0;
+        self.help_query.clear();
+    }
+
+    /// Leave the help screen for the view it was opened from
+    pub async fn close_help(&mut self) -> Result<()> {
+        self.view_mode = match self.help_from {
+            ViewMode::Help | ViewMode::Messages | ViewMode::Log => ViewMode::ProjectList,
+            ref view => view.clone(),
+        };
+        self.reload_view().await
+    }
+
+    /// Sections shown on the help screen
+    pub fn help_sections(&self) -> Vec<HelpSection> {
+        keymap::help_sections(&self.help_from, &self.help_query)
+    }
+
+    /// Scroll the help screen by `lines`, stopping at its last line
+    pub fn scroll_help(&mut self, lines: i32) {
+        // A title and a blank line per section
+        let total: usize = self.help_sections().iter().map(|s| s.bindings.len() + 2).sum();
+        let max = total.saturating_sub(1) as i32;
+        self.help_scroll = (self.help_scroll as i32 + lines).clamp(0, max) as u16;
+    }
+
+    /// Toggle completed todos visibility
+    pub async fn toggle_completed(&mut self) -> Result<()> {
+        if let ViewMode::TodoList(project_id) = self.view_mode {
+            self.show_completed = !self.show_completed;
+            self.selected_index = 0;
+            self.save_project_settings().await?;
+            self.request_todos(project_id);
+        }
+        Ok(())
+    }
+
+    /// Show completed todos, sort and filter as saved for a project
+    fn apply_project_settings(&mut self, settings: ProjectSettings) {
+        self.show_completed = settings.show_completed;
+        self.todo_sort = settings
+            .sort
+            .and_then(|name| name.parse().ok())
+            .unwrap_or_else(|| configured_sort(&self.config));
+        self.todo_filter = settings.filter;
+    }
+
+    /// Remember the open project's show completed, sort and filter
+    async fn save_project_settings(&mut self) -> Result<()> {
+        if let ViewMode::TodoList(project_id) = self.view_mode {
+            let settings = ProjectSettings {
+                show_completed: self.show_completed,
+                // Left unset, the project follows the configured default
+                sort: (self.todo_sort != configured_sort(&self.config))
+                    .then(|| self.todo_sort.to_string()),
+                filter: self.todo_filter.clone(),
+            };
+            self.service.save_project_settings(project_id, &settings).await?;
+        }
+        Ok(())
+    }
+
+    /// Show a neutral status message
+    pub fn set_status(&mut self, message: impl Into<String>) {
+        self.push_status(Severity::Info, message.into());
+    }
+
+    /// Show a status message confirming a change
+    pub fn set_success(&mut self, message: impl Into<String>) {
+        self.push_status(Severity::Success, message.into());
+    }
+
+    /// Show a status message about something that couldn't be done
+    pub fn set_warning(&mut self, message: impl Into<String>) {
+        self.push_status(Severity::Warning, message.into());
+    }
+
+    /// Show an error status message
+    pub fn set_error(&mut self, message: impl Into<String>) {
+        self.push_status(Severity::Error, message.into());
+    }
+
+    /// Show a failed core operation: mistakes such as a taken name or a bad
+    /// value as a warning, database failures as an error
+    pub fn report_error(&mut self, err: &DocketError) {
+        match err {
+            DocketError::Db { .. } | DocketError::Remote(_) | DocketError::Offline(_) => {
+                self.set_error(format!("Error: {}", err))
+            }
+            _ => self.set_warning(err.to_string()),
+        }
+    }
+
+    fn push_status(&mut self, severity: Severity, text: String) {
+        let message = StatusMessage {
+            text,
+            severity,
+            at: Utc::now(),
+        };
+        if self.messages.len() == MESSAGE_HISTORY {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message.clone());
+        self.status_message = Some(message);
+    }
+
+    /// The status message to show, until it times out
+    pub fn visible_status(&self) -> Option<&StatusMessage> {
+        self.status_message.as_ref().filter(|m| !m.expired())
+    }
+
+    /// Show the status message history
+    pub fn show_messages(&mut self) {
+        self.view_mode = ViewMode::Messages;
+    }
+
+    /// Show the end of the log file (`:log`)
+    pub fn show_log(&mut self) {
+        let Some(path) = self.config.log_file.clone() else {
+            self.set_warning("Not logging to a file; start with --log-file or set log_file");
+            return;
+        };
+        match logging::tail(&path, logging::TAIL_LINES) {
+            Ok(lines) => {
+                self.log_lines = lines;
+                self.view_mode = ViewMode::Log;
+            }
+            Err(err) => self.set_error(format!("Can't read {}: {}", path.display(), err)),
+        }
+    }
+
+    /// Start add project mode
+    pub fn start_add_project(&mut self) {
+        self.input_mode = InputMode::AddProject;
+        self.input_buffer.clear();
+    }
+
+    /// Start add todo mode
+    pub fn start_add_todo(&mut self) {
+        if matches!(self.view_mode, ViewMode::TodoList(_)) {
+            self.input_mode = InputMode::AddTodo;
+            self.input_buffer.clear();
+            self.new_todo_other.clear();
+        }
+    }
+
+    /// Switch between the new todo's description and details
+    pub fn switch_new_todo_field(&mut self) {
+        self.input_mode = match self.input_mode {
+            InputMode::AddTodo => InputMode::AddTodoDetails,
+            InputMode::AddTodoDetails => InputMode::AddTodo,
+            _ => return,
+        };
+        std::mem::swap(&mut self.input_buffer, &mut self.new_todo_other);
+    }
+
+    /// Create the todo typed in add todo mode, with details if any were given
+    pub async fn save_new_todo(&mut self) -> Result<()> {
+        let ViewMode::TodoList(project_id) = self.view_mode else {
+            self.cancel_input();
+            return Ok(());
+        };
+        let (description, details) = match self.input_mode {
+            InputMode::AddTodoDetails => (&self.new_todo_other, &self.input_buffer),
+            _ => (&self.input_buffer, &self.new_todo_other),
+        };
+        let (description, details) = (description.trim().to_string(), details.clone());
+        if description.is_empty() {
+            if self.input_mode == InputMode::AddTodoDetails {
+                self.switch_new_todo_field();
+                self.set_warning("A todo needs a description");
+            } else {
+                self.cancel_input();
+            }
+            return Ok(());
+        }
+        match self.service.create_todo_with_details(project_id, &description, Some(&details)).await {
+            Ok(_) => {
+                self.set_success("Todo created");
+                self.load_todos(project_id).await?;
+            }
+            Err(e) => self.report_error(&e),
+        }
+        self.cancel_input();
+        Ok(())
+    }
+
+    /// Start edit description mode
+    pub fn start_edit_description(&mut self) {
+        if matches!(self.view_mode, ViewMode::TodoList(_)) {
+            self.input_mode = InputMode::EditDescription;
+            // Pre-fill with existing description if any
+            self.input_buffer = self.current_project
+                .as_ref()
+                .and_then(|p| p.description.clone())
+                .unwrap_or_default();
+        }
+    }
+
+    /// Save the edited description
+    pub async fn save_description(&mut self) -> Result<()> {
+        if let ViewMode::TodoList(project_id) = self.view_mode {
+            let description = if self.input_buffer.trim().is_empty() {
+                None
+            } else {
+                Some(self.input_buffer.as_str())
+            };
+            self.service.update_project_description(project_id, description).await?;
+            // Reload project to get updated description
+            self.current_project = Some(self.service.get_project(project_id).await?);
+            self.set_success("Description updated");
+        }
+        self.cancel_input();
+        Ok(())
+    }
+
+    /// Archive all finished projects that have gone stale
+    pub fn archive_stale_projects(&mut self) {
+        let service = self.service.clone();
+        let stale_after_days = self.config.stale_after_days;
+        self.tasks.spawn("Archiving stale projects", 0, move |_| async move {
+            let archived = service.archive_stale_projects(stale_after_days).await?;
+            Ok(match archived.len() {
+                0 => "No stale projects to archive".to_string(),
+                1 => format!("Archived '{}'", archived[0].project.name),
+                n => format!("Archived {} stale projects", n),
+            })
+        });
+    }
+
+    /// Run a parsed `:` command
+    pub async fn execute_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Quit => self.should_quit = true,
+            Command::Help => self.show_help(),
+            Command::Messages => self.show_messages(),
+            Command::Log => self.show_log(),
+            Command::Profile(None) => {
+                let current = self.config.profile_name();
+                let names: Vec<String> = self
+                    .config
+                    .profiles()
+                    .into_iter()
+                    .map(|name| if name == current { format!("[{}]", name) } else { name })
+                    .collect();
+                self.set_status(format!("Profiles: {}", names.join(", ")));
+            }
+            Command::Profile(Some(name)) => match config::valid_profile_name(&name) {
+                Ok(name) if name == self.config.profile_name() => self.set_status(format!("Already on {}", name)),
+                Ok(name) => {
+                    self.switch_profile = Some(name.to_string());
+                    self.should_quit = true;
+                }
+                Err(err) => self.set_error(err.to_string()),
+            },
+            Command::Workspace(None) => {
+                self.load_workspaces().await?;
+                if self.workspaces.is_empty() {
+                    self.set_warning("No workspaces yet; make one with :workspace <name>");
+                } else {
+                    let current = self.workspace.as_ref().map(|w| w.id);
+                    let names: Vec<String> = self
+                        .workspaces
+                        .iter()
+                        .map(|w| if Some(w.id) == current { format!("[{}]", w.name) } else { w.name.clone() })
+                        .collect();
+                    self.set_status(format!("Workspaces: {}", names.join(", ")));
+                }
+            }
+            Command::Workspace(Some(name)) if name.eq_ignore_ascii_case("all") => self.switch_workspace(None).await?,
+            Command::Workspace(Some(name)) => match self.find_or_create_workspace(&name).await {
+                Ok(workspace) => {
+                    self.load_workspaces().await?;
+                    self.switch_workspace(Some(workspace)).await?;
+                }
+                Err(e) => self.report_error(&e),
+            },
+            Command::Assign(name) => {
+                let Some(project) = self.target_project() else {
+                    self.set_warning("Select a project to file under a workspace");
+                    return Ok(());
+                };
+                let workspace = match name {
+                    Some(name) => match self.find_or_create_workspace(&name).await {
+                        Ok(workspace) => Some(workspace),
+                        Err(e) => {
+                            self.report_error(&e);
+                            return Ok(());
+                        }
+                    },
+                    None => None,
+                };
+                match self.service.update_project_workspace(project.id, workspace.as_ref().map(|w| w.id)).await {
+                    Ok(updated) => {
+                        if self.current_project.as_ref().is_some_and(|p| p.id == updated.id) {
+                            self.current_project = Some(updated);
+                        }
+                        self.load_workspaces().await?;
+                        self.load_projects().await?;
+                        self.set_success(match workspace {
+                            Some(workspace) => format!("Filed '{}' under {}", project.name, workspace.name),
+                            None => format!("Took '{}' out of its workspace", project.name),
+                        });
+                    }
+                    Err(e) => self.report_error(&e),
+                }
+            }
+            Command::Nest(name) => {
+                let Some(project) = self.target_project() else {
+                    self.set_warning("Select a project to nest");
+                    return Ok(());
+                };
+                let parent = match name {
+                    Some(name) => {
+                        let projects = self.service.list_active_projects().await?;
+                        match find_project(&projects, &name) {
+                            Some(parent) => Some(parent.project.clone()),
+                            None => {
+                                self.set_warning(format!("No active project matching '{}'", name));
+                                return Ok(());
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                match self.service.update_project_parent(project.id, parent.as_ref().map(|p| p.id)).await {
+                    Ok(updated) => {
+                        if self.current_project.as_ref().is_some_and(|p| p.id == updated.id) {
+                            self.current_project = Some(updated);
+                        }
+                        if let Some(parent) = &parent {
+                            self.collapsed_projects.remove(&parent.id);
+                        }
+                        self.load_projects().await?;
+                        self.set_success(match parent {
+                            Some(parent) => format!("Nested '{}' under '{}'", project.name, parent.name),
+                            None => format!("Moved '{}' to the top level", project.name),
+                        });
+                    }
+                    Err(e) => self.report_error(&e),
+                }
+            }
+            Command::Review => self.start_review().await?,
+            Command::Calendar => self.open_calendar().await?,
+            Command::ArchiveStale => self.archive_stale_projects(),
+            Command::Add(description) => {
+                let ViewMode::TodoList(project_id) = self.view_mode else {
+                    self.set_warning("Open a project to add todos");
+                    return Ok(());
+                };
+                match self.service.create_todo(project_id, &description).await {
+                    Ok(_) => {
+                        self.load_todos(project_id).await?;
+                        self.set_success("Todo created");
+                    }
+                    Err(e) => self.report_error(&e),
+                }
+            }
+            Command::Project(name) => {
+                let projects = self.service.list_active_projects().await?;
+                match find_project(&projects, &name) {
+                    Some(project) => self.open_project(project.project.id).await?,
+                    None => self.set_warning(format!("No active project matching '{}'", name)),
+                }
+            }
+            Command::Sort(sort) => {
+                self.todo_sort = sort;
+                self.save_project_settings().await?;
+                match self.view_mode {
+                    ViewMode::TodoList(project_id) => self.load_todos(project_id).await?,
+                    ViewMode::AllTodos => self.load_all_todos().await?,
+                    _ => {}
+                }
+            }
+            Command::Filter(filter) => {
+                if let Some(Err(e)) = filter.as_deref().map(str::parse::<TodoQuery>) {
+                    self.set_warning(e);
+                    return Ok(());
+                }
+                self.todo_filter = filter;
+                self.save_project_settings().await?;
+                match self.view_mode {
+                    ViewMode::TodoList(project_id) => {
+                        self.selected_index = 0;
+                        self.load_todos(project_id).await?;
+                    }
+                    ViewMode::AllTodos => {
+                        self.selected_index = 0;
+                        self.load_all_todos().await?;
+                    }
+                    _ => {}
+                }
+            }
+            Command::Archive => {
+                let project_id = match &self.view_mode {
+                    ViewMode::ProjectList => {
+                        self.selected_project().map(|p| p.project.id)
+                    }
+                    ViewMode::TodoList(id) => Some(*id),
+                    _ => None,
+                };
+                if let Some(id) = project_id {
+                    self.service.archive_project(id).await?;
+                    self.set_success("Project archived");
+                    if matches!(self.view_mode, ViewMode::TodoList(_)) {
+                        self.back_to_projects().await?;
+                    } else {
+                        self.load_projects().await?;
+                    }
+                }
+            }
+            Command::Link(reference) => {
+                let ViewMode::TodoList(project_id) = self.view_mode else {
+                    self.set_warning("Open a project to link todos");
+                    return Ok(());
+                };
+                let Some(todo_id) = self.todos.get(self.selected_index).map(|t| t.id) else {
+                    return Ok(());
+                };
+                let cleared = reference.is_none();
+                match self.service.update_todo_external_ref(todo_id, reference.as_deref()).await {
+                    Ok(_) => {
+                        self.load_todos(project_id).await?;
+                        self.set_success(if cleared { "Link removed" } else { "Todo linked" });
+                    }
+                    Err(e) => self.report_error(&e),
+                }
+            }
+            Command::Relate(kind, query) => {
+                if !matches!(self.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos) {
+                    self.set_warning("Open a project to link todos");
+                    return Ok(());
+                }
+                let Some(todo_id) = self.todos.get(self.selected_index).map(|t| t.id) else {
+                    return Ok(());
+                };
+                let Some(target) = self.find_related_target(&query, todo_id).await? else {
+                    return Ok(());
+                };
+                match self.service.link_todos(todo_id, target.id, kind).await {
+                    Ok(_) => {
+                        // Fetched again for the expanded todo
+                        self.related_for = None;
+                        let verb = if kind == LinkKind::Duplicates { "Marked as duplicating" } else { "Linked to" };
+                        self.set_success(format!("{} '{}'", verb, target.description));
+                    }
+                    Err(e) => self.report_error(&e),
+                }
+            }
+            Command::Template(None) => {
+                self.load_template_names().await?;
+                if self.template_names.is_empty() {
+                    self.set_warning("No templates; save one with `docket template add <name> <description>`");
+                } else {
+                    self.set_status(format!("Templates: {}", self.template_names.join(", ")));
+                }
+            }
+            Command::Template(Some(name)) => {
+                let ViewMode::TodoList(project_id) = self.view_mode else {
+                    self.set_warning("Open a project to add todos");
+                    return Ok(());
+                };
+                match self.service.create_todo_from_template(project_id, &name, None).await {
+                    Ok(todo) => {
+                        self.load_todos(project_id).await?;
+                        if let Some(index) = self.todos.iter().position(|t| t.id == todo.id) {
+                            self.selected_index = index;
+                        }
+                        self.set_success(format!("Added '{}' from {}", todo.description, name));
+                    }
+                    Err(e) => self.report_error(&e),
+                }
+            }
+            Command::Columns(spec) => {
+                let outcome = match self.view_mode {
+                    ViewMode::TodoList(_) | ViewMode::AllTodos => {
+                        pick_columns(&mut self.todo_columns, &self.config.todo_columns, spec.as_deref())
+                    }
+                    ViewMode::ProjectList | ViewMode::ArchivedProjects => {
+                        pick_columns(&mut self.project_columns, &self.config.project_columns, spec.as_deref())
+                    }
+                    _ => Err("Open the project or todo list to pick its columns".to_string()),
+                };
+                match outcome {
+                    Ok(message) => self.set_status(message),
+                    Err(e) => self.set_warning(e),
+                }
+            }
+            Command::History => {
+                if !matches!(self.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos) {
+                    self.set_warning("Open a project to see a todo's history");
+                    return Ok(());
+                }
+                self.start_history().await?;
+            }
+            Command::Script(script) => {
+                let ViewMode::TodoList(project_id) = self.view_mode else {
+                    self.set_warning("Open a project to run a script on its todos");
+                    return Ok(());
+                };
+                let outcome = script.run(self.service.as_ref(), project_id).await;
+                self.load_todos(project_id).await?;
+                match outcome {
+                    Ok(summary) => self.set_success(summary),
+                    Err(e) => self.report_error(&e),
+                }
+            }
+            Command::Export(format) => {
+                let project = match &self.view_mode {
+                    ViewMode::ProjectList | ViewMode::ArchivedProjects => {
+                        self.selected_project().map(|p| p.project.clone())
+                    }
+                    ViewMode::TodoList(_) => self.current_project.clone(),
+                    _ => None,
+                };
+                let Some(project) = project else {
+                    self.set_warning("Select a project to export");
+                    return Ok(());
+                };
+                let todos = self.service.list_all_todos(project.id).await?;
+                let path = std::env::current_dir()?
+                    .join(format!("{}.{}", slugify(&project.name), format.extension()));
+                std::fs::write(&path, export::export_project(format, &project, &todos))?;
+                self.set_success(format!("Exported to {}", path.display()));
+            }
+        }
+        Ok(())
+    }
+
+    /// The project a command about projects works on: the selected one in
+    /// the project list, or the open one
+    fn target_project(&self) -> Option<Project> {
+        match self.view_mode {
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => self.selected_project().map(|p| p.project.clone()),
+            ViewMode::TodoList(_) => self.current_project.clone(),
+            _ => None,
+        }
+    }
+
+    /// Refresh the workspaces, letting go of the current one if it was deleted
+    async fn load_workspaces(&mut self) -> Result<()> {
+        self.workspaces = self.service.list_workspaces().await?;
+        if let Some(current) = &self.workspace {
+            self.workspace = self.workspaces.iter().find(|w| w.id == current.id).cloned();
+        }
+        Ok(())
+    }
+
+    /// Narrow the project list (and All todos) to `workspace`, or show
+    /// everything with `None`
+    pub async fn switch_workspace(&mut self, workspace: Option<Workspace>) -> Result<()> {
+        let label = workspace.as_ref().map_or("all workspaces".to_string(), |w| w.name.clone());
+        self.workspace = workspace;
+        if matches!(self.view_mode, ViewMode::ProjectList | ViewMode::ArchivedProjects) {
+            self.selected_index = 0;
+        }
+        match self.view_mode {
+            ViewMode::AllTodos => self.load_all_todos().await?,
+            _ => self.load_projects().await?,
+        }
+        self.set_status(format!("Showing {}", label));
+        Ok(())
+    }
+
+    /// Step to the next workspace, and after the last back to all of them (`W`)
+    pub async fn cycle_workspace(&mut self) -> Result<()> {
+        self.load_workspaces().await?;
+        if self.workspaces.is_empty() {
+            self.set_warning("No workspaces yet; make one with :workspace <name>");
+            return Ok(());
+        }
+        let next = match &self.workspace {
+            None => self.workspaces.first().cloned(),
+            Some(current) => {
+                let index = self.workspaces.iter().position(|w| w.id == current.id);
+                index.and_then(|i| self.workspaces.get(i + 1)).cloned()
+            }
+        };
+        self.switch_workspace(next).await
+    }
+
+    /// The workspace called `name` (ignoring case), made if there is none yet
+    async fn find_or_create_workspace(&mut self, name: &str) -> crate::core::error::Result<Workspace> {
+        let existing = self.service.list_workspaces().await?;
+        match existing.into_iter().find(|w| w.name.eq_ignore_ascii_case(name.trim())) {
+            Some(workspace) => Ok(workspace),
+            None => {
+                let workspace = self.service.create_workspace(name).await?;
+                self.set_success(format!("Workspace '{}' created", workspace.name));
+                Ok(workspace)
+            }
+        }
+    }
+
+    /// Refresh the template names `:template` completes
+    async fn load_template_names(&mut self) -> Result<()> {
+        self.template_names = self.service.list_templates().await?.into_iter().map(|t| t.name).collect();
+        Ok(())
+    }
+
+    /// Open the sort modal on the current order
+    pub fn start_sort_picker(&mut self) {
+        self.sort_picker = self.todo_sort.keys().to_vec();
+        self.sort_picker_index = 0;
+        self.input_mode = InputMode::PickSort;
+    }
+
+    /// Highlight the next or previous field in the sort modal
+    pub fn move_sort_picker(&mut self, down: bool) {
+        let last = SortField::ALL.len() - 1;
+        self.sort_picker_index = if down {
+            (self.sort_picker_index + 1).min(last)
+        } else {
+            self.sort_picker_index.saturating_sub(1)
+        };
+    }
+
+    /// Cycle the highlighted field through its default direction, the other
+    /// direction and unused; a newly used field becomes the last key
+    pub fn cycle_sort_key(&mut self) {
+        let field = SortField::ALL[self.sort_picker_index];
+        let default = SortKey::new(field);
+        match self.sort_picker.iter().position(|k| k.field == field) {
+            None => self.sort_picker.push(default),
+            Some(i) if self.sort_picker[i] == default => self.sort_picker[i].descending = !default.descending,
+            Some(i) => {
+                self.sort_picker.remove(i);
+            }
+        }
+    }
+
+    /// Sort by the keys picked in the modal
+    pub async fn apply_sort_picker(&mut self) -> Result<()> {
+        self.input_mode = InputMode::Normal;
+        let order = TodoOrder::new(std::mem::take(&mut self.sort_picker));
+        self.set_status(format!("Sorted by {}", order));
+        self.execute_command(Command::Sort(order)).await
+    }
+
+    /// Open the history modal on the selected todo's earlier versions,
+    /// expanding the todo if needed
+    pub async fn start_history(&mut self) -> Result<()> {
+        if self.expanded_todo_id.is_none() {
+            self.toggle_todo_expand();
+        }
+        let Some(todo_id) = self.expanded_todo_id else {
+            self.set_warning("Select a todo to see its history");
+            return Ok(());
+        };
+        match self.service.todo_history(todo_id).await {
+            Ok(history) if history.is_empty() => {
+                self.set_warning("No earlier versions; edits to the text are kept here")
+            }
+            Ok(history) => {
+                self.history = history;
+                self.history_index = 0;
+                self.input_mode = InputMode::PickVersion;
+            }
+            Err(e) => self.report_error(&e),
+        }
+        Ok(())
+    }
+
+    /// Highlight the next (older) or previous version in the history modal
+    pub fn move_history(&mut self, down: bool) {
+        let last = self.history.len().saturating_sub(1);
+        self.history_index = if down {
+            (self.history_index + 1).min(last)
+        } else {
+            self.history_index.saturating_sub(1)
+        };
+    }
+
+    /// Put back the highlighted version's description and details
+    pub async fn restore_history_version(&mut self) -> Result<()> {
+        self.input_mode = InputMode::Normal;
+        let Some(version) = self.history.get(self.history_index).cloned() else { return Ok(()) };
+        self.history.clear();
+        match self.service.restore_todo_version(version.todo_id, version.id).await {
+            Ok(_) => {
+                let at = self.config.timezone.convert(version.replaced_at).format("%Y-%m-%d %H:%M");
+                self.set_success(format!("Restored the text replaced on {}", at));
+            }
+            Err(e) => {
+                self.report_error(&e);
+                return Ok(());
+            }
+        }
+        match self.view_mode {
+            ViewMode::TodoList(project_id) => self.load_todos(project_id).await,
+            ViewMode::AllTodos => self.load_all_todos().await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Start command mode
+    pub fn start_command_mode(&mut self) {
+        self.input_mode = InputMode::Command;
+        self.input_buffer.clear();
+    }
+
+    /// Cancel input mode
+    pub fn cancel_input(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.input_buffer.clear();
+        self.new_todo_other.clear();
+    }
+
+    /// Toggle expansion of the selected todo
+    pub fn toggle_todo_expand(&mut self) {
+        if let ViewMode::TodoList(_) | ViewMode::AllTodos = self.view_mode
+            && let Some(todo) = self.todos.get(self.selected_index)
+        {
+            if self.expanded_todo_id == Some(todo.id) {
+                // Collapse if already expanded
+                self.expanded_todo_id = None;
+                self.detail_fullscreen = false;
+            } else {
+                // Expand this todo
+                self.expanded_todo_id = Some(todo.id);
+            }
+            self.detail_scroll = 0;
+            self.checklist_index = None;
+            self.related_index = None;
+        }
+    }
+
+    /// Selected checklist item, while the expanded todo's checklist has focus
+    pub fn checklist_focus(&self) -> Option<usize> {
+        let index = self.checklist_index?;
+        (index < self.get_expanded_todo()?.checklist().len()).then_some(index)
+    }
+
+    /// Move into the checklist of the selected todo's details (`x`),
+    /// expanding the todo if needed
+    pub fn start_checklist(&mut self) {
+        if self.expanded_todo_id.is_none() {
+            self.toggle_todo_expand();
+        }
+        let Some(todo) = self.get_expanded_todo() else { return };
+        if todo.checklist().is_empty() {
+            self.set_warning("No checklist in the details; add lines like - [ ] step");
+            return;
+        }
+        self.checklist_index = Some(0);
+        self.scroll_to_checklist_item();
+    }
+
+    /// Select the next or previous checklist item
+    pub fn move_checklist(&mut self, down: bool) {
+        let (Some(index), Some(todo)) = (self.checklist_focus(), self.get_expanded_todo()) else { return };
+        let last = todo.checklist().len() - 1;
+        self.checklist_index = Some(if down { (index + 1).min(last) } else { index.saturating_sub(1) });
+        self.scroll_to_checklist_item();
+    }
+
+    /// Check or uncheck the selected checklist item
+    pub async fn toggle_checklist_item(&mut self) -> Result<()> {
+        let (Some(index), Some(todo_id)) = (self.checklist_focus(), self.expanded_todo_id) else { return Ok(()) };
+        if let Err(e) = self.service.toggle_checklist_item(todo_id, index).await {
+            self.set_error(format!("Error updating checklist: {}", e));
+            return Ok(());
+        }
+        match self.view_mode {
+            ViewMode::TodoList(project_id) => self.load_todos(project_id).await,
+            ViewMode::AllTodos => self.load_all_todos().await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Selected related todo, while the expanded todo's related todos have focus
+    pub fn related_focus(&self) -> Option<usize> {
+        let index = self.related_index?;
+        (self.related_for.is_some() && self.related_for == self.expanded_todo_id && index < self.related_rows())
+            .then_some(index)
+    }
+
+    /// Linked and mentioned todos listed under the expanded todo's header
+    pub fn related_rows(&self) -> usize {
+        self.related.len() + self.mentions.len()
+    }
+
+    /// Label, todo and project name on a row of the related todos, links
+    /// first
+    pub fn related_row(&self, index: usize) -> Option<(&'static str, &Todo, &str)> {
+        match index.checked_sub(self.related.len()) {
+            None => self.related.get(index).map(|r| (r.label(), &r.todo, r.project_name.as_str())),
+            Some(index) => self.mentions.get(index).map(|m| (m.label(), &m.todo, m.project_name.as_str())),
+        }
+    }
+
+    /// Move into the todos linked to or from the selected todo (`l`),
+    /// expanding it if needed. If they are still loading, focus moves there
+    /// once they arrive.
+    pub fn start_related(&mut self) {
+        if self.expanded_todo_id.is_none() {
+            self.toggle_todo_expand();
+        }
+        if self.expanded_todo_id.is_none() {
+            return;
+        }
+        self.refresh_related();
+        if self.related_rows() == 0 && !self.loader.is_pending(LoadKind::Related) {
+            self.set_warning(NO_RELATED);
+            return;
+        }
+        self.checklist_index = None;
+        self.related_index = Some(0);
+    }
+
+    /// Select the next or previous related todo
+    pub fn move_related(&mut self, down: bool) {
+        let Some(index) = self.related_focus() else { return };
+        let last = self.related_rows() - 1;
+        self.related_index = Some(if down { (index + 1).min(last) } else { index.saturating_sub(1) });
+    }
+
+    /// Jump to the selected related todo in its project, expanded so its own
+    /// links can be followed in turn
+    pub async fn open_related(&mut self) -> Result<()> {
+        let Some((_, todo, _)) = self.related_focus().and_then(|index| self.related_row(index)) else {
+            return Ok(());
+        };
+        let todo = todo.clone();
+        self.related_index = None;
+        if self.open_todo(&todo).await? {
+            self.expanded_todo_id = Some(todo.id);
+        }
+        Ok(())
+    }
+
+    /// Remove the link to the selected related todo
+    pub async fn remove_related(&mut self) {
+        let Some(index) = self.related_focus() else { return };
+        if index >= self.related.len() {
+            self.set_warning("Mentions come from the details; edit them to drop one");
+            return;
+        }
+        if let Err(e) = self.service.unlink_todos(self.related[index].link.id).await {
+            self.report_error(&e);
+            return;
+        }
+        let removed = self.related.remove(index);
+        self.related_index = match self.related_rows() {
+            0 => None,
+            len => Some(index.min(len - 1)),
+        };
+        self.set_success(format!("Unlinked '{}'", removed.todo.description));
+    }
+
+    /// The todo `:relate` names: `#id`, or the open todo other than
+    /// `todo_id` whose description is `query` or the only one containing it
+    async fn find_related_target(&mut self, query: &str, todo_id: i64) -> Result<Option<Todo>> {
+        // `#12` or a short ID like `HOME-12`; a bare number is searched for
+        let todo_ref = parse_todo_ref(query).filter(|_| !query.trim().bytes().all(|b| b.is_ascii_digit()));
+        if let Some(todo_ref) = todo_ref {
+            let found = async {
+                let todo = self.service.get_todo(todo_ref.id).await?;
+                let project = self.service.get_project(todo.project_id).await?;
+                Ok::<_, DocketError>(todo_ref.fits(&project).then_some(todo))
+            };
+            return match found.await {
+                Ok(Some(todo)) => Ok(Some(todo)),
+                Ok(None) => {
+                    self.set_warning(format!("No todo {}", todo_ref));
+                    Ok(None)
+                }
+                Err(e) => {
+                    self.report_error(&e);
+                    Ok(None)
+                }
+            };
+        }
+        let needle = search::fold(query);
+        let mut matches: Vec<Todo> = self
+            .service
+            .list_open_todos()
+            .await?
+            .into_iter()
+            .filter(|t| t.id != todo_id && search::contains(&t.description, &needle))
+            .collect();
+        if let Some(exact) = matches.iter().position(|t| search::fold(&t.description) == needle) {
+            return Ok(Some(matches.swap_remove(exact)));
+        }
+        match matches.len() {
+            0 => self.set_warning(format!("No open todo matching '{}'", query)),
+            1 => return Ok(matches.pop()),
+            n => self.set_warning(format!("{} open todos match '{}'; add words or use #id", n, query)),
+        }
+        Ok(None)
+    }
+
+    /// Scroll the details so the selected checklist item is in view
+    fn scroll_to_checklist_item(&mut self) {
+        let (Some(index), Some(todo)) = (self.checklist_index, self.get_expanded_todo()) else { return };
+        let Some(item) = todo.checklist().get(index).copied() else { return };
+        // Header, link, related todos and blank line come before the details
+        let line = 2 + todo.external_ref.is_some() as u16 + self.related_rows() as u16 + item.line as u16;
+        let rows = self.detail_height.saturating_sub(2).max(1);
+        if line < self.detail_scroll {
+            self.detail_scroll = line;
+        } else if line >= self.detail_scroll + rows {
+            self.detail_scroll = line + 1 - rows;
+        }
+    }
+
+    /// Scroll the expanded details by half a pane (`Ctrl+d` / `Ctrl+u`)
+    pub fn scroll_details(&mut self, down: bool) {
+        let Some(todo) = self.get_expanded_todo() else { return };
+        // Header, link, blank line and the details themselves
+        let lines = 2 + todo.external_ref.is_some() as u16 + todo.details.as_deref().map_or(1, |d| d.lines().count()) as u16;
+        let step = (self.detail_height / 2).max(1);
+        self.detail_scroll = if down {
+            (self.detail_scroll + step).min(lines.saturating_sub(1))
+        } else {
+            self.detail_scroll.saturating_sub(step)
+        };
+    }
+
+    /// Grow or shrink the details pane by `rows`
+    pub fn resize_details(&mut self, grow: bool, rows: u16) {
+        self.detail_height = if grow {
+            self.detail_height.saturating_add(rows).min(MAX_DETAIL_HEIGHT)
+        } else {
+            self.detail_height.saturating_sub(rows).max(MIN_DETAIL_HEIGHT)
+        };
+    }
+
+    /// Switch between the details pane and a full-screen details view,
+    /// expanding the selected todo if needed
+    pub fn toggle_detail_fullscreen(&mut self) {
+        if self.detail_fullscreen {
+            self.detail_fullscreen = false;
+            return;
+        }
+        if self.expanded_todo_id.is_none() {
+            self.toggle_todo_expand();
+        }
+        self.detail_fullscreen = self.expanded_todo_id.is_some();
+    }
+
+    /// Start edit todo details mode
+    pub fn start_edit_todo_details(&mut self) {
+        if let ViewMode::TodoList(_) = self.view_mode
+            && let Some(todo_id) = self.expanded_todo_id
+        {
+            // Find the todo and pre-fill with existing details
+            if let Some(todo) = self.todos.iter().find(|t| t.id == todo_id) {
+                self.input_buffer = todo.details.clone().unwrap_or_default();
+                self.input_mode = InputMode::EditTodoDetails;
+            }
+        }
+    }
+
+    /// Save the edited todo details
+    pub async fn save_todo_details(&mut self) -> Result<()> {
+        if let Some(todo_id) = self.expanded_todo_id {
+            let details = if self.input_buffer.trim().is_empty() {
+                None
+            } else {
                 Some(self.input_buffer.as_str())
             };
             self.service.update_todo_details(todo_id, details).await?;
@@ -347,15 +2708,455 @@ impl App {
             if let ViewMode::TodoList(project_id) = self.view_mode {
                 self.load_todos(project_id).await?;
             }
-            self.set_status("Details updated");
+            self.set_success("Details updated");
         }
         self.cancel_input();
         Ok(())
     }
 
+    /// Ask the main loop to open the expanded todo's details, or else the
+    /// project description, in `$EDITOR` (`E`)
+    pub fn start_external_edit(&mut self) {
+        let ViewMode::TodoList(project_id) = self.view_mode else { return };
+        self.editor_request = Some(match self.expanded_todo_id {
+            Some(todo_id) => EditorTarget::TodoDetails(todo_id),
+            None => EditorTarget::ProjectDescription(project_id),
+        });
+    }
+
+    /// Current text of an editor target
+    pub fn editor_text(&self, target: EditorTarget) -> String {
+        let text = match target {
+            EditorTarget::TodoDetails(id) => self.todos.iter().find(|t| t.id == id).and_then(|t| t.details.clone()),
+            EditorTarget::ProjectDescription(id) => self
+                .current_project
+                .as_ref()
+                .filter(|p| p.id == id)
+                .and_then(|p| p.description.clone()),
+        };
+        text.unwrap_or_default()
+    }
+
+    /// Save text coming back from `$EDITOR`; empty text clears the field
+    pub async fn save_editor_text(&mut self, target: EditorTarget, text: &str) -> Result<()> {
+        let text = text.trim_end();
+        let value = (!text.trim().is_empty()).then_some(text);
+        match target {
+            EditorTarget::TodoDetails(id) => {
+                self.service.update_todo_details(id, value).await?;
+                if let ViewMode::TodoList(project_id) = self.view_mode {
+                    self.load_todos(project_id).await?;
+                }
+                self.set_success("Details updated");
+            }
+            EditorTarget::ProjectDescription(id) => {
+                self.service.update_project_description(id, value).await?;
+                self.current_project = Some(self.service.get_project(id).await?);
+                self.set_success("Description updated");
+            }
+        }
+        Ok(())
+    }
+
+    /// Colours and glyphs to draw with
+    pub fn theme(&self) -> Theme {
+        Theme { palette: self.config.theme, ascii: self.config.ascii }
+    }
+
+    /// Icon set to draw with; ASCII drawing implies ASCII icons
+    pub fn icons(&self) -> Icons {
+        if self.config.ascii { Icons::Ascii } else { self.config.icons }
+    }
+
     /// Get the currently expanded todo, if any
     pub fn get_expanded_todo(&self) -> Option<&Todo> {
         self.expanded_todo_id
             .and_then(|id| self.todos.iter().find(|t| t.id == id))
     }
 }
+
+/// Parse a target date as `YYYY-MM-DD` or `+N` days from today in `zone`;
+/// empty clears it
+fn parse_target_date(input: &str, zone: DisplayZone) -> Result<Option<NaiveDate>, String> {
+    if input.is_empty() {
+        return Ok(None);
+    }
+    if let Some(days) = input.strip_prefix('+') {
+        let days: i64 = days
+            .parse()
+            .map_err(|_| format!("Invalid number of days: {}", days))?;
+        return Ok(Some(zone.today() + Duration::days(days)));
+    }
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| format!("Invalid date '{}' (use YYYY-MM-DD or +N days)", input))
+}
+
+/// Show, set or reset (`default`) a table's columns for `:columns`,
+/// returning the message to show
+fn pick_columns<C: Column>(picked: &mut Option<Vec<C>>, configured: &[C], spec: Option<&str>) -> Result<String, String> {
+    match spec {
+        None => {
+            let current = picked.as_deref().unwrap_or(configured);
+            Ok(format!("Columns: {} (available: {})", describe(current), C::names().join(", ")))
+        }
+        Some(spec) if spec.eq_ignore_ascii_case("default") => {
+            *picked = None;
+            Ok(format!("Columns: {}", describe(configured)))
+        }
+        Some(spec) => {
+            let columns = parse_columns(spec)?;
+            let message = format!("Columns: {}", describe(&columns));
+            *picked = Some(columns);
+            Ok(message)
+        }
+    }
+}
+
+/// Turn a project name into a filesystem-friendly file stem
+fn slugify(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() { "project".to_string() } else { slug }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::service::test_service;
+
+    #[tokio::test]
+    async fn test_counts_and_row_jumps() {
+        let service = test_service().await;
+        let project = service.create_project("Work").await.unwrap();
+        for i in 0..20 {
+            service.create_todo(project.id, &format!("Todo {}", i)).await.unwrap();
+        }
+        let mut app = App::new(service, Config::default());
+        app.open_project(project.id).await.unwrap();
+        app.finish_loading().await;
+
+        app.push_count_digit(1);
+        app.push_count_digit(2);
+        assert_eq!(app.pending_keys(), "12");
+        let count = app.pending_count.take().unwrap();
+        app.select_row(count - 1);
+        assert_eq!(app.selected_index, 11);
+
+        app.select_row(usize::MAX);
+        assert_eq!(app.selected_index, 19);
+
+        // Only the first 8 rows fit on screen
+        app.visible_rows.set(8);
+        app.select_visible_row('L');
+        assert_eq!(app.selected_index, 7);
+        app.select_visible_row('M');
+        assert_eq!(app.selected_index, 3);
+        app.select_visible_row('H');
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[tokio::test]
+    async fn test_yank_and_paste_into_another_project() {
+        let service = test_service().await;
+        let work = service.create_project("Work").await.unwrap();
+        let home = service.create_project("Home").await.unwrap();
+        let todo = service.create_todo(work.id, "Call plumber #home").await.unwrap();
+        service.update_todo_details(todo.id, Some("Leak under sink")).await.unwrap();
+        service.update_todo_estimate(todo.id, Some(2)).await.unwrap();
+        service.create_todo(work.id, "Second").await.unwrap();
+
+        let mut app = App::new(service, Config::default());
+        app.open_project(work.id).await.unwrap();
+        app.finish_loading().await;
+        app.yank_todos(5);
+        assert_eq!(app.register.len(), 2);
+
+        app.open_project(home.id).await.unwrap();
+        app.paste_todos().await.unwrap();
+        app.finish_tasks().await.unwrap();
+        app.finish_loading().await;
+        assert_eq!(app.todos.len(), 2);
+        assert_eq!(app.todos[0].description, "Call plumber #home");
+        assert_eq!(app.todos[0].details.as_deref(), Some("Leak under sink"));
+        assert_eq!(app.todos[0].estimate, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_add_todo_with_details() {
+        let service = test_service().await;
+        let work = service.create_project("Work").await.unwrap();
+        let mut app = App::new(service, Config::default());
+        app.open_project(work.id).await.unwrap();
+        app.finish_loading().await;
+
+        app.start_add_todo();
+        app.input_buffer.push_str("Write report");
+        app.switch_new_todo_field();
+        assert_eq!(app.input_mode, InputMode::AddTodoDetails);
+        app.input_buffer.push_str("Q3 numbers");
+        app.save_new_todo().await.unwrap();
+        app.finish_loading().await;
+
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert_eq!(app.todos[0].description, "Write report");
+        assert_eq!(app.todos[0].details.as_deref(), Some("Q3 numbers"));
+    }
+
+    #[tokio::test]
+    async fn test_detailed_rows_are_remembered() {
+        let mut app = App::new(test_service().await, Config::default());
+        app.toggle_detailed_rows();
+        let layout = app.layout_prefs();
+        assert!(layout.detailed_rows);
+
+        let mut next = App::new(test_service().await, Config::default());
+        next.apply_layout(layout);
+        assert!(next.detailed_rows);
+    }
+
+    #[tokio::test]
+    async fn test_preview_pops_up_once_selection_rests() {
+        let service = test_service().await;
+        let project = service.create_project("Work").await.unwrap();
+        service.create_todo(project.id, "Renew passport #admin").await.unwrap();
+        let mut app = App::new(service, Config::default());
+        app.config.preview_delay_ms = 20;
+        app.open_project(project.id).await.unwrap();
+        app.finish_loading().await;
+
+        assert!(app.hovered_todo().is_none());
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        assert_eq!(app.hovered_todo().map(|t| t.description.as_str()), Some("Renew passport #admin"));
+
+        // A key press starts the wait again
+        app.dwell.set(None);
+        assert!(app.hovered_todo().is_none());
+        app.config.preview_delay_ms = 0;
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        assert!(app.hovered_todo().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_split_view_keeps_project_selection() {
+        let service = test_service().await;
+        service.create_project("Work").await.unwrap();
+        let home = service.create_project("Home").await.unwrap();
+        service.create_todo(home.id, "Call plumber").await.unwrap();
+
+        let mut app = App::new(service, Config::default());
+        app.init().await.unwrap();
+        app.toggle_split_view();
+        // Below the All todos row
+        app.selected_index = app.projects.iter().position(|p| p.project.id == home.id).unwrap() + 1;
+        let home_row = app.selected_index;
+
+        app.refresh_preview();
+        app.finish_loading().await;
+        assert_eq!(app.preview_todos.len(), 1);
+
+        app.enter_project().await.unwrap();
+        assert_eq!(app.project_index, home_row);
+        app.back_to_projects().await.unwrap();
+        assert_eq!(app.selected_index, home_row);
+    }
+
+    #[tokio::test]
+    async fn test_checklist_in_expanded_details() {
+        let service = test_service().await;
+        let project = service.create_project("Home").await.unwrap();
+        let todo = service.create_todo(project.id, "Retile hall").await.unwrap();
+        service
+            .update_todo_details(todo.id, Some("- [ ] measure\n- [ ] buy tiles"))
+            .await
+            .unwrap();
+
+        let mut app = App::new(service, Config::default());
+        app.init().await.unwrap();
+        app.open_project(project.id).await.unwrap();
+        app.finish_loading().await;
+        app.start_checklist();
+        assert_eq!((app.expanded_todo_id, app.checklist_focus()), (Some(todo.id), Some(0)));
+
+        app.move_checklist(true);
+        app.move_checklist(true);
+        app.toggle_checklist_item().await.unwrap();
+        assert_eq!(app.todos[0].details.as_deref(), Some("- [ ] measure\n- [x] buy tiles"));
+        assert_eq!(app.todos[0].checklist_progress(), Some((1, 2)));
+    }
+
+    #[tokio::test]
+    async fn test_all_todos_lists_every_active_project() {
+        let service = test_service().await;
+        let work = service.create_project("Work").await.unwrap();
+        let home = service.create_project("Home").await.unwrap();
+        let shelved = service.create_project("Shelved").await.unwrap();
+        service.create_todo(work.id, "Write report").await.unwrap();
+        let done = service.create_todo(work.id, "Send invoice").await.unwrap();
+        service.toggle_todo(done.id).await.unwrap();
+        service.create_todo(home.id, "Call plumber").await.unwrap();
+        service.create_todo(shelved.id, "Someday").await.unwrap();
+        service.archive_project(shelved.id).await.unwrap();
+
+        let mut app = App::new(service, Config::default());
+        app.init().await.unwrap();
+        assert_eq!(app.list_len(), app.projects.len() + 1);
+        assert!(app.selected_project().is_none());
+
+        app.enter_project().await.unwrap();
+        assert_eq!(app.view_mode, ViewMode::AllTodos);
+        let mut listed: Vec<(&str, &str)> = app
+            .todos
+            .iter()
+            .map(|t| (app.project_name(t.project_id), t.description.as_str()))
+            .collect();
+        listed.sort();
+        assert_eq!(listed, vec![("Home", "Call plumber"), ("Work", "Write report")]);
+
+        app.back_to_projects().await.unwrap();
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[tokio::test]
+    async fn test_superseded_loads_are_dropped() {
+        let service = test_service().await;
+        let work = service.create_project("Work").await.unwrap();
+        let home = service.create_project("Home").await.unwrap();
+        service.create_todo(work.id, "Write report").await.unwrap();
+        service.create_todo(home.id, "Call plumber").await.unwrap();
+
+        let mut app = App::new(service, Config::default());
+        app.init().await.unwrap();
+        // Moving on before the first project's todos arrive
+        app.open_project(work.id).await.unwrap();
+        app.open_project(home.id).await.unwrap();
+        app.finish_loading().await;
+        assert!(!app.loader.is_loading());
+        assert_eq!(app.todos.len(), 1);
+        assert_eq!(app.todos[0].description, "Call plumber");
+    }
+
+    #[tokio::test]
+    async fn test_refreshes_after_external_write() {
+        let service = test_service().await;
+        let project = service.create_project("Work").await.unwrap();
+
+        let mut app = App::new(service.clone(), Config::default());
+        app.open_project(project.id).await.unwrap();
+        app.finish_loading().await;
+        app.data_checked_at -= DATA_CHECK_INTERVAL;
+        app.refresh_if_changed().await.unwrap();
+        assert!(!app.loader.is_loading());
+
+        // Written by someone else, e.g. the web server
+        service.create_todo(project.id, "Added over the API").await.unwrap();
+        app.data_checked_at -= DATA_CHECK_INTERVAL;
+        app.refresh_if_changed().await.unwrap();
+        app.finish_loading().await;
+        assert_eq!(app.todos.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shared_service_refreshes_without_waiting() {
+        let service = test_service().await;
+        let project = service.create_project("Work").await.unwrap();
+
+        let mut app = App::new(service.clone(), Config::default());
+        app.watch_events();
+        app.open_project(project.id).await.unwrap();
+        app.finish_loading().await;
+
+        // An API request handled in the same process publishes on the bus
+        service.create_todo(project.id, "Added in the browser").await.unwrap();
+        app.refresh_if_changed().await.unwrap();
+        app.finish_loading().await;
+        assert_eq!(app.todos.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_project_view_settings_are_remembered() {
+        let service = test_service().await;
+        let work = service.create_project("Work").await.unwrap();
+        let home = service.create_project("Home").await.unwrap();
+
+        let mut app = App::new(service.clone(), Config::default());
+        app.open_project(work.id).await.unwrap();
+        app.toggle_completed().await.unwrap();
+        app.execute_command(Command::Sort("estimate, due".parse().unwrap())).await.unwrap();
+        app.execute_command(Command::Filter(Some("report".into()))).await.unwrap();
+
+        // Another project starts from the defaults
+        app.open_project(home.id).await.unwrap();
+        assert!(app.show_completed);
+        assert_eq!(app.todo_filter, None);
+
+        // A new session restores Work's view
+        let mut app = App::new(service, Config::default());
+        app.open_project(work.id).await.unwrap();
+        assert!(!app.show_completed);
+        assert_eq!(app.todo_sort.to_string(), "estimate, due");
+        assert_eq!(app.todo_filter.as_deref(), Some("report"));
+    }
+
+    #[tokio::test]
+    async fn test_session_restores_open_project_and_selection() {
+        let service = test_service().await;
+        service.create_project("Home").await.unwrap();
+        let work = service.create_project("Work").await.unwrap();
+        service.create_todo(work.id, "Write report").await.unwrap();
+        let second = service.create_todo(work.id, "Send invoice").await.unwrap();
+
+        let mut app = App::new(service.clone(), Config::default());
+        app.init().await.unwrap();
+        app.open_project(work.id).await.unwrap();
+        app.finish_loading().await;
+        app.select_row(1);
+        app.toggle_todo_expand();
+        let session = app.session_state();
+
+        let mut app = App::new(service, Config::default());
+        app.init().await.unwrap();
+        app.restore_session(session).await.unwrap();
+        assert_eq!(app.view_mode, ViewMode::TodoList(work.id));
+        assert_eq!(app.selected_index, 1);
+        assert_eq!(app.expanded_todo_id, Some(second.id));
+
+        // Leaving the project returns to its row in the list
+        app.back_to_projects().await.unwrap();
+        assert_eq!(app.selected_project().unwrap().project.id, work.id);
+    }
+
+    #[tokio::test]
+    async fn test_calendar_lays_out_week_and_opens_todo() {
+        let service = test_service().await;
+        let home = service.create_project("Home").await.unwrap();
+        let work = service.create_project("Work").await.unwrap();
+        service.create_todo(work.id, "Undated").await.unwrap();
+        let report = service.create_todo(work.id, "Write report").await.unwrap();
+        let plumber = service.create_todo(home.id, "Call plumber").await.unwrap();
+
+        let mut app = App::new(service.clone(), Config::default());
+        app.init().await.unwrap();
+        app.open_calendar().await.unwrap();
+        let monday = app.calendar_week_start();
+        service.update_todo_due_date(report.id, Some(monday + Duration::days(2))).await.unwrap();
+        service.update_todo_due_date(plumber.id, Some(monday + Duration::days(9))).await.unwrap();
+        app.load_calendar().await.unwrap();
+        assert_eq!(app.calendar_todos.len(), 1);
+
+        // Walking off the end of the week loads the next one
+        app.calendar_date = monday + Duration::days(6);
+        app.move_calendar(3).await.unwrap();
+        assert_eq!(app.calendar_week_start(), monday + Duration::days(7));
+        assert_eq!(app.calendar_day_todos()[0].id, plumber.id);
+
+        app.move_calendar(-7).await.unwrap();
+        assert_eq!(app.calendar_day_todos()[0].id, report.id);
+        app.open_calendar_todo().await.unwrap();
+        assert_eq!(app.view_mode, ViewMode::TodoList(work.id));
+        assert_eq!(app.todos[app.selected_index].id, report.id);
+    }
+}
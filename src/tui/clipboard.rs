@@ -0,0 +1,57 @@
+use anyhow::Result;
+use base64::Engine;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Clipboard helpers tried in order, with the environment variable that must be set
+const COMMANDS: &[(&str, &[&str], Option<&str>)] = &[
+    ("pbcopy", &[], None),
+    ("wl-copy", &[], Some("WAYLAND_DISPLAY")),
+    ("xclip", &["-selection", "clipboard"], Some("DISPLAY")),
+    ("xsel", &["--clipboard", "--input"], Some("DISPLAY")),
+    ("clip.exe", &[], None),
+];
+
+/// Copy text to the system clipboard, returning how it was copied.
+///
+/// Uses the platform's clipboard command when one is installed, otherwise an
+/// OSC 52 escape sequence, which most terminals (and tmux with
+/// `set-clipboard on`) turn into a clipboard write. Over SSH OSC 52 is tried
+/// first, since it reaches the clipboard of the machine you are sitting at.
+pub fn copy(text: &str) -> Result<&'static str> {
+    if std::env::var_os("SSH_CONNECTION").is_none() {
+        for (program, args, needs) in COMMANDS {
+            if needs.is_some_and(|var| std::env::var_os(var).is_none()) {
+                continue;
+            }
+            if pipe_to(program, args, text).is_ok() {
+                return Ok(program);
+            }
+        }
+    }
+    osc52(text)?;
+    Ok("terminal")
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    anyhow::ensure!(status.success(), "{} exited with {}", program, status);
+    Ok(())
+}
+
+fn osc52(text: &str) -> Result<()> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", encoded)?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use ratatui::layout::Constraint;
+use serde::{Deserialize, Serialize};
+
+/// A column the project or todo table can show, chosen in the config file
+/// or with `:columns`
+pub trait Column: Copy + PartialEq + Sized + 'static {
+    /// Every column, in the order `:columns` lists them
+    const ALL: &'static [Self];
+    /// Columns shown when none are configured
+    const DEFAULT: &'static [Self];
+
+    /// Name in the config file and `:columns`
+    fn name(&self) -> &'static str;
+    fn header(&self) -> &'static str;
+    fn width(&self) -> Constraint;
+
+    /// Every column name, for usage messages and completion
+    fn names() -> Vec<&'static str> {
+        Self::ALL.iter().map(Column::name).collect()
+    }
+}
+
+/// Parse a list of column names separated by commas or spaces
+pub fn parse_columns<C: Column>(spec: &str) -> Result<Vec<C>, String> {
+    let mut columns = Vec::new();
+    for name in spec.split([',', ' ']).map(str::trim).filter(|n| !n.is_empty()) {
+        let column = C::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown column '{}' (expected {})", name, C::names().join(", ")))?;
+        if !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+    if columns.is_empty() {
+        return Err("Name at least one column".to_string());
+    }
+    Ok(columns)
+}
+
+/// Column names as `:columns` takes them
+pub fn describe<C: Column>(columns: &[C]) -> String {
+    columns.iter().map(Column::name).collect::<Vec<_>>().join(", ")
+}
+
+/// Column of the todo table
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoColumn {
+    Status,
+    Description,
+    /// The todo's project; only in All todos
+    Project,
+    Created,
+    Due,
+    /// Place in the project's manual order
+    Priority,
+    Tags,
+    /// Days since the todo was created
+    Age,
+    /// Time from creation to completion
+    Spent,
+    Estimate,
+    Completed,
+}
+
+impl Column for TodoColumn {
+    const ALL: &'static [Self] = &[
+        TodoColumn::Status,
+        TodoColumn::Description,
+        TodoColumn::Project,
+        TodoColumn::Created,
+        TodoColumn::Due,
+        TodoColumn::Priority,
+        TodoColumn::Tags,
+        TodoColumn::Age,
+        TodoColumn::Spent,
+        TodoColumn::Estimate,
+        TodoColumn::Completed,
+    ];
+    const DEFAULT: &'static [Self] =
+        &[TodoColumn::Status, TodoColumn::Description, TodoColumn::Project, TodoColumn::Completed];
+
+    fn name(&self) -> &'static str {
+        match self {
+            TodoColumn::Status => "status",
+            TodoColumn::Description => "description",
+            TodoColumn::Project => "project",
+            TodoColumn::Created => "created",
+            TodoColumn::Due => "due",
+            TodoColumn::Priority => "priority",
+            TodoColumn::Tags => "tags",
+            TodoColumn::Age => "age",
+            TodoColumn::Spent => "spent",
+            TodoColumn::Estimate => "estimate",
+            TodoColumn::Completed => "completed",
+        }
+    }
+
+    fn header(&self) -> &'static str {
+        match self {
+            TodoColumn::Status => "Status",
+            TodoColumn::Description => "Description",
+            TodoColumn::Project => "Project",
+            TodoColumn::Created => "Created",
+            TodoColumn::Due => "Due",
+            TodoColumn::Priority => "Priority",
+            TodoColumn::Tags => "Tags",
+            TodoColumn::Age => "Age",
+            TodoColumn::Spent => "Spent",
+            TodoColumn::Estimate => "Estimate",
+            TodoColumn::Completed => "Completed",
+        }
+    }
+
+    fn width(&self) -> Constraint {
+        match self {
+            TodoColumn::Status => Constraint::Length(8),
+            TodoColumn::Description => Constraint::Fill(3),
+            TodoColumn::Project | TodoColumn::Tags => Constraint::Fill(1),
+            TodoColumn::Created | TodoColumn::Due => Constraint::Length(10),
+            TodoColumn::Priority | TodoColumn::Estimate => Constraint::Length(8),
+            TodoColumn::Age | TodoColumn::Spent => Constraint::Length(6),
+            TodoColumn::Completed => Constraint::Length(16),
+        }
+    }
+}
+
+/// Column of the project table
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectColumn {
+    Name,
+    Active,
+    Completed,
+    Total,
+    Created,
+    /// Days since the project was created
+    Age,
+    /// Remaining and total estimates; only while some project has them
+    Effort,
+    /// Days to the target date; only while some project has one
+    Target,
+}
+
+impl Column for ProjectColumn {
+    const ALL: &'static [Self] = &[
+        ProjectColumn::Name,
+        ProjectColumn::Active,
+        ProjectColumn::Completed,
+        ProjectColumn::Total,
+        ProjectColumn::Created,
+        ProjectColumn::Age,
+        ProjectColumn::Effort,
+        ProjectColumn::Target,
+    ];
+    const DEFAULT: &'static [Self] = &[
+        ProjectColumn::Name,
+        ProjectColumn::Active,
+        ProjectColumn::Completed,
+        ProjectColumn::Total,
+        ProjectColumn::Effort,
+        ProjectColumn::Target,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            ProjectColumn::Name => "name",
+            ProjectColumn::Active => "active",
+            ProjectColumn::Completed => "completed",
+            ProjectColumn::Total => "total",
+            ProjectColumn::Created => "created",
+            ProjectColumn::Age => "age",
+            ProjectColumn::Effort => "effort",
+            ProjectColumn::Target => "target",
+        }
+    }
+
+    fn header(&self) -> &'static str {
+        match self {
+            ProjectColumn::Name => "Name",
+            ProjectColumn::Active => "Active",
+            ProjectColumn::Completed => "Completed",
+            ProjectColumn::Total => "Total",
+            ProjectColumn::Created => "Created",
+            ProjectColumn::Age => "Age",
+            ProjectColumn::Effort => "Effort Left",
+            ProjectColumn::Target => "Target",
+        }
+    }
+
+    fn width(&self) -> Constraint {
+        match self {
+            ProjectColumn::Name => Constraint::Fill(1),
+            ProjectColumn::Active | ProjectColumn::Total => Constraint::Length(8),
+            ProjectColumn::Completed | ProjectColumn::Created => Constraint::Length(10),
+            ProjectColumn::Age => Constraint::Length(6),
+            ProjectColumn::Effort => Constraint::Length(12),
+            ProjectColumn::Target => Constraint::Length(14),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_columns() {
+        assert_eq!(
+            parse_columns::<TodoColumn>("Description, due tags,due"),
+            Ok(vec![TodoColumn::Description, TodoColumn::Due, TodoColumn::Tags])
+        );
+        assert!(parse_columns::<ProjectColumn>("name, tags").unwrap_err().contains("Unknown column 'tags'"));
+        assert!(parse_columns::<TodoColumn>(" , ").is_err());
+        let json: Vec<ProjectColumn> = serde_json::from_str(r#"["name", "target"]"#).unwrap();
+        assert_eq!(describe(&json), "name, target");
+    }
+}
@@ -0,0 +1,283 @@
+use crate::core::export::ExportFormat;
+use crate::core::models::LinkKind;
+
+use crate::core::search;
+use crate::core::sort::{SortField, TodoOrder};
+
+use super::columns::{Column, ProjectColumn, TodoColumn};
+use super::script::Script;
+
+/// A parsed `:` command
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Quit,
+    Help,
+    Messages,
+    Log,
+    Review,
+    Calendar,
+    ArchiveStale,
+    Archive,
+    Add(String),
+    Project(String),
+    /// Reopen on another profile's database; `None` lists the profiles
+    Profile(Option<String>),
+    /// Show only one workspace's projects (`all` shows every project),
+    /// making it if need be; `None` lists the workspaces
+    Workspace(Option<String>),
+    /// File the selected project under a workspace; `None` takes it out
+    Assign(Option<String>),
+    /// Nest the selected project under another; `None` moves it to the top level
+    Nest(Option<String>),
+    Sort(TodoOrder),
+    Filter(Option<String>),
+    Export(ExportFormat),
+    /// Set the selected todo's external reference; `None` clears it
+    Link(Option<String>),
+    /// Link the selected todo to another, found by `#id` or text
+    Relate(LinkKind, String),
+    /// Add a todo from a template; `None` lists the templates
+    Template(Option<String>),
+    /// Run a script over the open project's todos
+    Script(Script),
+    /// Show the selected todo's earlier versions to restore one
+    History,
+    /// Set the open table's columns; `None` shows them
+    Columns(Option<String>),
+}
+
+/// All command names, used for completion and suggestions
+pub const COMMAND_NAMES: &[&str] = &[
+    "add",
+    "archive",
+    "archive-stale",
+    "assign",
+    "calendar",
+    "columns",
+    "duplicates",
+    "export",
+    "filter",
+    "help",
+    "history",
+    "link",
+    "log",
+    "messages",
+    "nest",
+    "profile",
+    "project",
+    "quit",
+    "relate",
+    "review",
+    "script",
+    "sort",
+    "template",
+    "workspace",
+];
+
+/// Parse a command line (without the leading `:`)
+pub fn parse(input: &str) -> Result<Command, String> {
+    let input = input.trim();
+    let (name, args) = match input.split_once(char::is_whitespace) {
+        Some((name, args)) => (name, args.trim()),
+        None => (input, ""),
+    };
+
+    match name.to_lowercase().as_str() {
+        "q" | "quit" => Ok(Command::Quit),
+        "help" => Ok(Command::Help),
+        "messages" => Ok(Command::Messages),
+        "log" => Ok(Command::Log),
+        "review" => Ok(Command::Review),
+        "calendar" => Ok(Command::Calendar),
+        "archive-stale" => Ok(Command::ArchiveStale),
+        "archive" => Ok(Command::Archive),
+        "history" => Ok(Command::History),
+        "add" => required(args, ":add <description>").map(Command::Add),
+        "project" => required(args, ":project <name>").map(Command::Project),
+        "profile" => Ok(Command::Profile((!args.is_empty()).then(|| args.to_string()))),
+        "workspace" => Ok(Command::Workspace((!args.is_empty()).then(|| args.to_string()))),
+        "assign" => Ok(Command::Assign((!args.is_empty()).then(|| args.to_string()))),
+        "nest" => Ok(Command::Nest((!args.is_empty()).then(|| args.to_string()))),
+        "sort" => {
+            let spec = required(args, &format!(":sort <{}>[ asc|desc], ...", SortField::names().join("|")))?;
+            spec.parse().map(Command::Sort)
+        }
+        "filter" => Ok(Command::Filter((!args.is_empty()).then(|| args.to_string()))),
+        "link" => Ok(Command::Link((!args.is_empty()).then(|| args.to_string()))),
+        "relate" => required(args, ":relate <todo or #id>").map(|todo| Command::Relate(LinkKind::SeeAlso, todo)),
+        "duplicates" => {
+            required(args, ":duplicates <todo or #id>").map(|todo| Command::Relate(LinkKind::Duplicates, todo))
+        }
+        "template" => Ok(Command::Template((!args.is_empty()).then(|| args.to_string()))),
+        "columns" => Ok(Command::Columns((!args.is_empty()).then(|| args.to_string()))),
+        "script" => args.parse().map(Command::Script),
+        "export" => {
+            if args.is_empty() {
+                Ok(Command::Export(ExportFormat::Markdown))
+            } else {
+                args.parse().map(Command::Export)
+            }
+        }
+        other => Err(unknown_command(other)),
+    }
+}
+
+/// Return the argument or a usage error
+fn required(args: &str, usage: &str) -> Result<String, String> {
+    if args.is_empty() {
+        Err(format!("Usage: {}", usage))
+    } else {
+        Ok(args.to_string())
+    }
+}
+
+/// Build an "unknown command" error listing close matches
+fn unknown_command(name: &str) -> String {
+    let suggestions = suggest(name);
+    if suggestions.is_empty() {
+        format!("Unknown command: {} (see :help)", name)
+    } else {
+        let list: Vec<String> = suggestions.iter().map(|s| format!(":{}", s)).collect();
+        format!("Unknown command: {} (did you mean {}?)", name, list.join(", "))
+    }
+}
+
+/// Commands that share a prefix with `name` or are within a couple of edits of it
+fn suggest(name: &str) -> Vec<&'static str> {
+    let name = name.to_lowercase();
+    COMMAND_NAMES
+        .iter()
+        .copied()
+        .filter(|cmd| cmd.starts_with(&name) || edit_distance(&name, cmd) <= 2)
+        .collect()
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current.push((prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+/// Tab-complete a command line, returning the new buffer if anything changed.
+/// Completes command names, then project / template / workspace names, sort
+/// keys and export formats.
+pub fn complete(
+    input: &str,
+    project_names: &[String],
+    template_names: &[String],
+    workspace_names: &[String],
+) -> Option<String> {
+    match input.split_once(' ') {
+        None => {
+            let candidates: Vec<&str> = COMMAND_NAMES
+                .iter()
+                .copied()
+                .filter(|cmd| cmd.starts_with(&input.to_lowercase()))
+                .collect();
+            let mut completed = common_prefix(&candidates)?;
+            if candidates.len() == 1 {
+                completed.push(' ');
+            }
+            (completed != input).then_some(completed)
+        }
+        Some((name, partial)) => {
+            // A sort spec or column list completes its last entry
+            let (done, partial) = match partial.rsplit_once(',') {
+                Some((done, last)) if name == "sort" || name == "columns" => (format!("{}, ", done), last.trim_start()),
+                _ => (String::new(), partial),
+            };
+            let candidates: Vec<&str> = match name {
+                "project" | "nest" => project_names.iter().map(String::as_str).collect(),
+                "template" => template_names.iter().map(String::as_str).collect(),
+                "workspace" | "assign" => workspace_names.iter().map(String::as_str).collect(),
+                "sort" => SortField::names().to_vec(),
+                "export" => ExportFormat::names().to_vec(),
+                "columns" => {
+                    let mut names = TodoColumn::names();
+                    names.extend(ProjectColumn::names().into_iter().filter(|n| !TodoColumn::names().contains(n)));
+                    names
+                }
+                _ => return None,
+            };
+            // `:project caf` completes to `Café`
+            let partial = search::fold(partial);
+            let matches: Vec<&str> = candidates.into_iter().filter(|c| search::fold(c).starts_with(&partial)).collect();
+            let completed = format!("{} {}{}", name, done, common_prefix(&matches)?);
+            (completed != input).then_some(completed)
+        }
+    }
+}
+
+/// Longest common (case-insensitive) prefix of the candidates, in the first candidate's casing
+fn common_prefix(candidates: &[&str]) -> Option<String> {
+    let first = candidates.first()?;
+    let mut len = first.len();
+    for candidate in &candidates[1..] {
+        len = first
+            .char_indices()
+            .zip(candidate.chars())
+            .take_while(|((_, a), b)| a.eq_ignore_ascii_case(b))
+            .last()
+            .map(|((i, a), _)| i + a.len_utf8())
+            .unwrap_or(0)
+            .min(len);
+    }
+    Some(first[..len].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands_with_arguments() {
+        assert_eq!(parse("add Buy milk"), Ok(Command::Add("Buy milk".to_string())));
+        assert_eq!(parse("sort created"), Ok(Command::Sort("created".parse().unwrap())));
+        assert_eq!(parse("sort due, -estimate"), Ok(Command::Sort("due, estimate desc".parse().unwrap())));
+        assert_eq!(parse("filter"), Ok(Command::Filter(None)));
+        assert_eq!(parse("filter #home"), Ok(Command::Filter(Some("#home".to_string()))));
+        assert_eq!(parse("export"), Ok(Command::Export(ExportFormat::Markdown)));
+        assert_eq!(parse("messages"), Ok(Command::Messages));
+        assert_eq!(parse("log"), Ok(Command::Log));
+        assert_eq!(parse("profile work"), Ok(Command::Profile(Some("work".to_string()))));
+        assert_eq!(parse("workspace Home"), Ok(Command::Workspace(Some("Home".to_string()))));
+        assert_eq!(parse("assign"), Ok(Command::Assign(None)));
+        assert_eq!(parse("nest Home"), Ok(Command::Nest(Some("Home".to_string()))));
+        assert_eq!(parse("link OPS-42"), Ok(Command::Link(Some("OPS-42".to_string()))));
+        assert_eq!(parse("relate #12"), Ok(Command::Relate(LinkKind::SeeAlso, "#12".to_string())));
+        assert_eq!(parse("duplicates buy paint"), Ok(Command::Relate(LinkKind::Duplicates, "buy paint".to_string())));
+        assert_eq!(parse("template"), Ok(Command::Template(None)));
+        assert_eq!(parse("columns due, tags"), Ok(Command::Columns(Some("due, tags".to_string()))));
+        assert!(parse("add").unwrap_err().starts_with("Usage"));
+        assert!(parse("sort sideways").is_err());
+    }
+
+    #[test]
+    fn test_unknown_command_suggests_close_matches() {
+        let err = parse("archve").unwrap_err();
+        assert!(err.contains(":archive"), "{}", err);
+    }
+
+    #[test]
+    fn test_complete_names_and_arguments() {
+        assert_eq!(complete("rev", &[], &[], &[]), Some("review ".to_string()));
+        assert_eq!(complete("arch", &[], &[], &[]), Some("archive".to_string()));
+        let projects = vec!["Work".to_string(), "Home".to_string()];
+        assert_eq!(complete("project w", &projects, &[], &[]), Some("project Work".to_string()));
+        assert_eq!(complete("sort cr", &[], &[], &[]), Some("sort created".to_string()));
+        assert_eq!(complete("sort due,est", &[], &[], &[]), Some("sort due, estimate".to_string()));
+        let workspaces = vec!["OSS".to_string()];
+        assert_eq!(complete("assign o", &[], &[], &workspaces), Some("assign OSS".to_string()));
+        let templates = vec!["deploy-checklist".to_string()];
+        assert_eq!(complete("template dep", &[], &templates, &[]), Some("template deploy-checklist".to_string()));
+    }
+}
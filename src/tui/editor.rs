@@ -0,0 +1,104 @@
+use anyhow::{bail, Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Text to edit in `$EDITOR`, and where the result is saved
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditorTarget {
+    TodoDetails(i64),
+    ProjectDescription(i64),
+}
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, then `vi`
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|cmd| !cmd.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Edit text in an external editor, through a temporary Markdown file.
+/// Returns `None` when the editor exits with an error, so the edit is dropped.
+/// The terminal must already be out of raw mode and the alternate screen.
+pub fn edit(text: &str) -> Result<Option<String>> {
+    let command = editor_command();
+    let mut words = command.split_whitespace();
+    let Some(program) = words.next() else {
+        bail!("$EDITOR is empty");
+    };
+
+    let (temp, mut file) = TempFile::create()?;
+    file.write_all(text.as_bytes())
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("Failed to write {}", temp.0.display()))?;
+    drop(file);
+    let status = Command::new(program)
+        .args(words)
+        .arg(&temp.0)
+        .status()
+        .with_context(|| format!("Failed to run '{}' (set $EDITOR)", command))?;
+    if !status.success() {
+        return Ok(None);
+    }
+    let edited = std::fs::read_to_string(&temp.0).with_context(|| format!("Failed to read {}", temp.0.display()))?;
+    Ok(Some(edited))
+}
+
+/// The file being edited, removed however `edit` returns
+struct TempFile(PathBuf);
+
+impl TempFile {
+    /// Create a file in the temp directory that only this user can read.
+    /// The name has a random part, and `create_new` refuses a path someone
+    /// else made first (a symlink to their own file, say) rather than
+    /// writing through it.
+    fn create() -> Result<(Self, File)> {
+        use std::hash::{BuildHasher, RandomState};
+
+        let dir = std::env::temp_dir();
+        for _ in 0..16 {
+            let suffix = RandomState::new().hash_one(std::time::SystemTime::now());
+            let path = dir.join(format!("docket-edit-{}-{:016x}.md", std::process::id(), suffix));
+            let mut options = OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            match options.open(&path) {
+                Ok(file) => return Ok((TempFile(path), file)),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err).with_context(|| format!("Failed to create {}", path.display())),
+            }
+        }
+        bail!("Failed to create a temporary file in {}", dir.display())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_files_are_private_and_removed() {
+        let (first, _) = TempFile::create().unwrap();
+        let (second, _) = TempFile::create().unwrap();
+        assert_ne!(first.0, second.0);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&first.0).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let path = first.0.clone();
+        drop(first);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,124 @@
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Cut a line to `width` columns, ending it with `ellipsis` (when that
+/// fits) if anything was dropped
+pub fn truncate(line: Line<'static>, width: usize, ellipsis: &str) -> Line<'static> {
+    if line.width() <= width {
+        return line;
+    }
+    let style = line.style;
+    let mut builder = Builder::default();
+    let ellipsis = if ellipsis.width() <= width { ellipsis } else { "" };
+    let budget = width - ellipsis.width();
+    let mut last_style = Style::default();
+    'spans: for span in &line.spans {
+        last_style = span.style;
+        for c in span.content.chars() {
+            if builder.used + c.width().unwrap_or(0) > budget {
+                break 'spans;
+            }
+            builder.push(c, span.style);
+        }
+    }
+    builder.push_str(ellipsis, last_style);
+    Line::from(builder.spans).style(style)
+}
+
+/// Wrap a line at spaces so no piece is wider than `width` columns; words
+/// longer than a line are broken where they reach the edge
+pub fn wrap(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    if width == 0 || line.width() <= width {
+        return vec![line];
+    }
+    let style = line.style;
+    let mut lines = Vec::new();
+    let mut builder = Builder::default();
+    for span in &line.spans {
+        for word in span.content.split_inclusive(' ') {
+            let word_width: usize = word.trim_end().chars().map(|c| c.width().unwrap_or(0)).sum();
+            if builder.used > 0 && builder.used + word_width > width {
+                lines.push(builder.finish(style));
+            }
+            for c in word.chars() {
+                let char_width = c.width().unwrap_or(0);
+                if builder.used + char_width > width {
+                    // A space at the edge is where the line breaks
+                    if c == ' ' {
+                        continue;
+                    }
+                    lines.push(builder.finish(style));
+                }
+                if c == ' ' && builder.used == 0 && !lines.is_empty() {
+                    continue;
+                }
+                builder.push(c, span.style);
+            }
+        }
+    }
+    if !builder.spans.is_empty() {
+        lines.push(builder.finish(style));
+    }
+    lines
+}
+
+/// Spans being filled one character at a time, with their width so far
+#[derive(Default)]
+struct Builder {
+    spans: Vec<Span<'static>>,
+    used: usize,
+}
+
+impl Builder {
+    fn push(&mut self, c: char, style: Style) {
+        self.used += c.width().unwrap_or(0);
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.content.to_mut().push(c),
+            _ => self.spans.push(Span::styled(c.to_string(), style)),
+        }
+    }
+
+    fn push_str(&mut self, text: &str, style: Style) {
+        for c in text.chars() {
+            self.push(c, style);
+        }
+    }
+
+    fn finish(&mut self, style: Style) -> Line<'static> {
+        self.used = 0;
+        Line::from(std::mem::take(&mut self.spans)).style(style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_truncate_and_wrap() {
+        let badge = Style::default().fg(Color::Red);
+        let line = Line::from(vec![Span::raw("Renew the car"), Span::styled("  due Mar 03", badge)]);
+
+        assert_eq!(truncate(line.clone(), 40, "…"), line);
+        let cut = truncate(line.clone(), 16, "…");
+        assert_eq!(text(&cut), "Renew the car  …");
+        assert_eq!(cut.spans.last().unwrap().style, badge);
+        assert_eq!(text(&truncate(line.clone(), 16, "...")), "Renew the car...");
+        assert_eq!(text(&truncate(line.clone(), 2, "...")), "Re");
+        // Wide characters count double
+        assert_eq!(text(&truncate(Line::from("日本語のメモ"), 7, "…")), "日本語…");
+
+        let lines = wrap(line, 10);
+        let lines: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(lines, vec!["Renew the ", "car  due ", "Mar 03"]);
+        assert_eq!(wrap(Line::from("abcdefghij"), 4).len(), 3);
+    }
+}
@@ -3,30 +3,87 @@ use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use std::time::Duration;
 
 use super::app::{App, InputMode, ViewMode};
+use super::command;
+use crate::crash;
 
 /// Handle keyboard input events
 pub async fn handle_input(app: &mut App) -> Result<()> {
-    if event::poll(Duration::from_millis(100))? {
-        if let Event::Key(key) = event::read()? {
-            match app.input_mode {
-                InputMode::Normal => handle_normal_mode(app, key).await?,
-                InputMode::AddProject => handle_add_project_mode(app, key).await?,
-                InputMode::AddTodo => handle_add_todo_mode(app, key).await?,
-                InputMode::EditDescription => handle_edit_description_mode(app, key).await?,
-                InputMode::EditTodoDetails => handle_edit_todo_details_mode(app, key).await?,
-                InputMode::EditTodo => handle_edit_todo_mode(app, key).await?,
-                InputMode::EditProjectName => handle_edit_project_name_mode(app, key).await?,
-                InputMode::Command => handle_command_mode(app, key).await?,
-            }
-        }
+    if event::poll(Duration::from_millis(100))?
+        && let Event::Key(key) = event::read()?
+    {
+        handle_key(app, key).await?;
+    }
+    Ok(())
+}
+
+/// Handle one key press in the current input mode
+pub async fn handle_key(app: &mut App, key: KeyEvent) -> Result<()> {
+    // Any key hides the preview popup until the selection rests again
+    app.dwell.set(None);
+    // Crash reports list keys pressed, but not text typed into a field
+    let typing = !matches!(app.input_mode, InputMode::Normal | InputMode::PickSort | InputMode::PickVersion);
+    if !(typing && matches!(key.code, KeyCode::Char(_)) && !key.modifiers.contains(KeyModifiers::CONTROL)) {
+        crash::record(format!("{:?}/{:?}: {}", app.view_mode, app.input_mode, crash::key_label(&key)));
+    }
+    match app.input_mode {
+        InputMode::Normal => handle_normal_mode(app, key).await?,
+        InputMode::AddProject => handle_add_project_mode(app, key).await?,
+        InputMode::AddTodo | InputMode::AddTodoDetails => handle_add_todo_mode(app, key).await?,
+        InputMode::EditDescription => handle_edit_description_mode(app, key).await?,
+        InputMode::EditTodoDetails => handle_edit_todo_details_mode(app, key).await?,
+        InputMode::EditTodo => handle_edit_todo_mode(app, key).await?,
+        InputMode::EditProjectName => handle_edit_project_name_mode(app, key).await?,
+        InputMode::EditEstimate => handle_edit_estimate_mode(app, key).await?,
+        InputMode::EditTargetDate => handle_edit_target_date_mode(app, key).await?,
+        InputMode::EditDueDate => handle_edit_due_date_mode(app, key).await?,
+        InputMode::CloneProject => handle_clone_project_mode(app, key).await?,
+        InputMode::Command => handle_command_mode(app, key).await?,
+        InputMode::SearchHelp => handle_search_help_mode(app, key),
+        InputMode::PickSort => handle_pick_sort_mode(app, key).await?,
+        InputMode::PickVersion => handle_pick_version_mode(app, key).await?,
+        InputMode::FullText => handle_full_text_mode(app, key),
     }
     Ok(())
 }
 
 /// Handle keys in normal navigation mode
 async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
-    // Clear any status message on keypress
-    app.clear_status();
+    if app.view_mode == ViewMode::Review {
+        return handle_review_mode(app, key).await;
+    }
+    if app.view_mode == ViewMode::Calendar {
+        return handle_calendar_mode(app, key).await;
+    }
+    if app.view_mode == ViewMode::Help {
+        return handle_help_mode(app, key).await;
+    }
+    if app.checklist_focus().is_some() {
+        return handle_checklist_mode(app, key).await;
+    }
+    if app.related_focus().is_some() {
+        return handle_related_mode(app, key).await;
+    }
+
+    // Vim-style count prefix (`5j`); a leading 0 is not a count
+    if let KeyCode::Char(c) = key.code
+        && let Some(digit) = c.to_digit(10)
+        && (digit > 0 || app.pending_count.is_some())
+        && app.pending_key.is_none()
+    {
+        app.push_count_digit(digit);
+        return Ok(());
+    }
+    let count = app.pending_count.take();
+
+    // Second key of `gg` / `dd`; anything else cancels the pending command
+    if let Some(first) = app.pending_key.take() {
+        match (first, key.code) {
+            ('g', KeyCode::Char('g')) => app.select_row(count.map_or(0, |n| n - 1)),
+            ('d', KeyCode::Char('d')) => app.delete_items(count.unwrap_or(1)),
+            _ => {}
+        }
+        return Ok(());
+    }
 
     match key.code {
         // Quit
@@ -36,52 +93,121 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
         }
 
         // Navigation
-        KeyCode::Char('j') | KeyCode::Down => app.next_item(),
-        KeyCode::Char('k') | KeyCode::Up => app.previous_item(),
+        KeyCode::Char('j') | KeyCode::Down => {
+            for _ in 0..count.unwrap_or(1) {
+                app.next_item();
+            }
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            for _ in 0..count.unwrap_or(1) {
+                app.previous_item();
+            }
+        }
+        // Side-by-side projects and todos; Tab switches panes
+        KeyCode::Char('|') => {
+            if matches!(
+                app.view_mode,
+                ViewMode::ProjectList | ViewMode::ArchivedProjects | ViewMode::TodoList(_)
+            ) {
+                app.toggle_split_view();
+            }
+        }
+        KeyCode::Char(c @ ('<' | '>')) if app.split_view => {
+            let steps = count.unwrap_or(1).min(u16::MAX as usize) as u16;
+            app.resize_project_pane(c == '>', steps);
+        }
+        KeyCode::Char('\\') if app.split_view => app.projects_collapsed = !app.projects_collapsed,
+        KeyCode::Tab if app.split_view => match app.view_mode {
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => app.enter_project().await?,
+            ViewMode::TodoList(_) => app.back_to_projects().await?,
+            _ => {}
+        },
+
+        // Scroll, resize and zoom the expanded todo's details
+        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => app.scroll_details(true),
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => app.scroll_details(false),
+        KeyCode::Char(c @ ('+' | '-')) if app.expanded_todo_id.is_some() => {
+            let rows = count.unwrap_or(1).min(u16::MAX as usize) as u16;
+            app.resize_details(c == '+', rows);
+        }
+        KeyCode::Char('z') if matches!(app.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos) => {
+            app.toggle_detail_fullscreen()
+        }
+        KeyCode::Char('x') if matches!(app.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos) => {
+            app.start_checklist()
+        }
+        KeyCode::Char('l') if matches!(app.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos) => {
+            app.start_related()
+        }
+        KeyCode::Char('o') if matches!(app.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos) => {
+            app.start_sort_picker()
+        }
+        KeyCode::Char('u') if matches!(app.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos) => {
+            app.start_history().await?
+        }
+
+        // `G` goes to the last row, or row N with a count
+        KeyCode::Char('G') => app.select_row(count.map_or(usize::MAX, |n| n - 1)),
+        KeyCode::Char(c @ ('H' | 'M' | 'L')) => app.select_visible_row(c),
+
+        // Two-key commands: `gg` (first row, or row N) and `dd` (delete N items)
+        KeyCode::Char(c @ ('g' | 'd')) => {
+            app.pending_count = count;
+            app.pending_key = Some(c);
+        }
 
         // Reordering (only in TodoList view for active todos)
+        KeyCode::Char('J') | KeyCode::Char('K') if app.view_mode == ViewMode::AllTodos => {
+            app.set_warning("Reordering works within a project; open the todo's project to move it");
+        }
+        KeyCode::Char('J') | KeyCode::Char('K')
+            if matches!(app.view_mode, ViewMode::TodoList(_)) && !app.todo_sort.is_manual() =>
+        {
+            app.set_warning("Reordering needs manual order (:sort position)");
+        }
+
         KeyCode::Char('J') if key.modifiers.contains(KeyModifiers::SHIFT) => {
-            if let ViewMode::TodoList(project_id) = &app.view_mode {
-                if let Some(todo) = app.todos.get(app.selected_index) {
-                    // Only allow reordering active todos
-                    if todo.can_reorder() {
-                        let todo_id = todo.id;
-                        if let Err(e) = app.service.move_todo_down(todo_id).await {
-                            app.set_status(format!("Error moving todo: {}", e));
-                        } else {
-                            // Reload todos to reflect new order
-                            app.load_todos(*project_id).await?;
-                            // Move selection down to follow the moved todo
-                            if app.selected_index < app.todos.len() - 1 {
-                                app.selected_index += 1;
-                            }
-                        }
+            if let ViewMode::TodoList(project_id) = &app.view_mode
+                && let Some(todo) = app.todos.get(app.selected_index)
+            {
+                // Only allow reordering active todos
+                if todo.can_reorder() {
+                    let todo_id = todo.id;
+                    if let Err(e) = app.service.move_todo_down(todo_id).await {
+                        app.set_error(format!("Error moving todo: {}", e));
                     } else {
-                        app.set_status("Cannot reorder completed todos");
+                        // Reload todos to reflect new order
+                        app.load_todos(*project_id).await?;
+                        // Move selection down to follow the moved todo
+                        if app.selected_index < app.todos.len() - 1 {
+                            app.selected_index += 1;
+                        }
                     }
+                } else {
+                    app.set_warning("Cannot reorder completed todos");
                 }
             }
         }
 
         KeyCode::Char('K') if key.modifiers.contains(KeyModifiers::SHIFT) => {
-            if let ViewMode::TodoList(project_id) = &app.view_mode {
-                if let Some(todo) = app.todos.get(app.selected_index) {
-                    // Only allow reordering active todos
-                    if todo.can_reorder() {
-                        let todo_id = todo.id;
-                        if let Err(e) = app.service.move_todo_up(todo_id).await {
-                            app.set_status(format!("Error moving todo: {}", e));
-                        } else {
-                            // Reload todos to reflect new order
-                            app.load_todos(*project_id).await?;
-                            // Move selection up to follow the moved todo
-                            if app.selected_index > 0 {
-                                app.selected_index -= 1;
-                            }
-                        }
+            if let ViewMode::TodoList(project_id) = &app.view_mode
+                && let Some(todo) = app.todos.get(app.selected_index)
+            {
+                // Only allow reordering active todos
+                if todo.can_reorder() {
+                    let todo_id = todo.id;
+                    if let Err(e) = app.service.move_todo_up(todo_id).await {
+                        app.set_error(format!("Error moving todo: {}", e));
                     } else {
-                        app.set_status("Cannot reorder completed todos");
+                        // Reload todos to reflect new order
+                        app.load_todos(*project_id).await?;
+                        // Move selection up to follow the moved todo
+                        if app.selected_index > 0 {
+                            app.selected_index -= 1;
+                        }
                     }
+                } else {
+                    app.set_warning("Cannot reorder completed todos");
                 }
             }
         }
@@ -92,24 +218,27 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
                 ViewMode::ProjectList | ViewMode::ArchivedProjects => {
                     app.enter_project().await?;
                 }
-                ViewMode::TodoList(_) => {
+                ViewMode::TodoList(_) | ViewMode::AllTodos => {
                     app.toggle_todo_expand();
                 }
-                ViewMode::Help => app.view_mode = ViewMode::ProjectList,
+                ViewMode::Messages | ViewMode::Log => app.view_mode = ViewMode::ProjectList,
+                ViewMode::Review | ViewMode::Calendar | ViewMode::Help => {}
             }
         }
 
         KeyCode::Esc => {
             match &app.view_mode {
-                ViewMode::TodoList(_) => {
-                    // If a todo is expanded, collapse it first
-                    if app.expanded_todo_id.is_some() {
+                ViewMode::TodoList(_) | ViewMode::AllTodos => {
+                    // Leave full-screen details, then collapse, then go back
+                    if app.detail_fullscreen {
+                        app.detail_fullscreen = false;
+                    } else if app.expanded_todo_id.is_some() {
                         app.expanded_todo_id = None;
                     } else {
                         app.back_to_projects().await?;
                     }
                 }
-                ViewMode::Help => app.view_mode = ViewMode::ProjectList,
+                ViewMode::Messages | ViewMode::Log => app.view_mode = ViewMode::ProjectList,
                 ViewMode::ArchivedProjects => {
                     app.view_mode = ViewMode::ProjectList;
                     app.load_projects().await?;
@@ -127,45 +256,37 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             }
         }
 
-        // Delete
-        KeyCode::Char('d') => {
-            match app.view_mode.clone() {
-                ViewMode::ProjectList | ViewMode::ArchivedProjects => {
-                    if let Some(project) = app.projects.get(app.selected_index) {
-                        let project_id = project.project.id;
-                        if let Err(e) = app.service.delete_project(project_id).await {
-                            app.set_status(format!("Error deleting project: {}", e));
-                        } else {
-                            app.set_status("Project deleted");
-                            app.load_projects().await?;
-                        }
-                    }
-                }
-                ViewMode::TodoList(project_id) => {
-                    if let Some(todo) = app.todos.get(app.selected_index) {
-                        let todo_id = todo.id;
-                        if let Err(e) = app.service.delete_todo(todo_id).await {
-                            app.set_status(format!("Error deleting todo: {}", e));
-                        } else {
-                            app.set_status("Todo deleted");
-                            app.load_todos(project_id).await?;
-                        }
-                    }
-                }
-                _ => {}
+        // Yank todos / paste them into the open project
+        KeyCode::Char('y') => app.yank_todos(count.unwrap_or(1)),
+        KeyCode::Char('p') => {
+            if let Err(e) = app.paste_todos().await {
+                app.set_error(format!("Error pasting todos: {}", e));
             }
         }
 
+        // Copy to the system clipboard
+        KeyCode::Char('Y') => app.copy_to_clipboard().await?,
+
+        // Fold / unfold sub-projects
+        KeyCode::Char(' ') if matches!(app.view_mode, ViewMode::ProjectList | ViewMode::ArchivedProjects) => {
+            app.toggle_project_collapsed().await?;
+        }
+
+        // Up to the parent project
+        KeyCode::Backspace if matches!(app.view_mode, ViewMode::TodoList(_)) => app.open_parent_project().await?,
+
         // Toggle completion (todos only)
         KeyCode::Char(' ') => {
-            if let ViewMode::TodoList(project_id) = &app.view_mode {
-                if let Some(todo) = app.todos.get(app.selected_index) {
-                    let todo_id = todo.id;
-                    if let Err(e) = app.service.toggle_todo(todo_id).await {
-                        app.set_status(format!("Error toggling todo: {}", e));
-                    } else {
-                        app.load_todos(*project_id).await?;
-                    }
+            if matches!(app.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos)
+                && let Some(todo) = app.todos.get(app.selected_index)
+            {
+                let todo_id = todo.id;
+                if let Err(e) = app.service.toggle_todo(todo_id).await {
+                    app.set_error(format!("Error toggling todo: {}", e));
+                } else if let ViewMode::TodoList(project_id) = app.view_mode {
+                    app.load_todos(project_id).await?;
+                } else {
+                    app.load_all_todos().await?;
                 }
             }
         }
@@ -173,32 +294,39 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
         // Archive project
         KeyCode::Char('A') => {
             if matches!(app.view_mode, ViewMode::ProjectList) {
-                if let Some(project) = app.projects.get(app.selected_index) {
+                if let Some(project) = app.selected_project() {
                     let project_id = project.project.id;
                     if let Err(e) = app.service.archive_project(project_id).await {
-                        app.set_status(format!("Error archiving project: {}", e));
+                        app.set_error(format!("Error archiving project: {}", e));
                     } else {
-                        app.set_status("Project archived");
+                        app.set_success("Project archived");
                         app.load_projects().await?;
                     }
                 }
-            } else if matches!(app.view_mode, ViewMode::ArchivedProjects) {
-                if let Some(project) = app.projects.get(app.selected_index) {
-                    let project_id = project.project.id;
-                    if let Err(e) = app.service.unarchive_project(project_id).await {
-                        app.set_status(format!("Error unarchiving project: {}", e));
-                    } else {
-                        app.set_status("Project unarchived");
-                        app.load_projects().await?;
-                    }
+            } else if matches!(app.view_mode, ViewMode::ArchivedProjects)
+                && let Some(project) = app.selected_project()
+            {
+                let project_id = project.project.id;
+                if let Err(e) = app.service.unarchive_project(project_id).await {
+                    app.set_error(format!("Error unarchiving project: {}", e));
+                } else {
+                    app.set_success("Project unarchived");
+                    app.load_projects().await?;
                 }
             }
         }
 
+        // Step through the workspaces
+        KeyCode::Char('W') => {
+            if matches!(app.view_mode, ViewMode::ProjectList | ViewMode::ArchivedProjects | ViewMode::AllTodos) {
+                app.cycle_workspace().await?;
+            }
+        }
+
         // Toggle archived view
         KeyCode::Char('v') => {
             if matches!(app.view_mode, ViewMode::ProjectList | ViewMode::ArchivedProjects) {
-                app.toggle_archived().await?;
+                app.toggle_archived();
             }
         }
 
@@ -207,6 +335,23 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             app.toggle_completed().await?;
         }
 
+        // Compact / detailed todo rows
+        KeyCode::Char('i') => {
+            if matches!(app.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos) {
+                app.toggle_detailed_rows();
+            }
+        }
+
+        // Wrap long descriptions / cut them short
+        KeyCode::Char('w') => {
+            if matches!(app.view_mode, ViewMode::TodoList(_) | ViewMode::AllTodos) {
+                app.toggle_wrap_rows();
+            }
+        }
+
+        // Full text of the selected todo
+        KeyCode::Char('f') => app.show_full_text(),
+
         // Edit: todo details (if expanded) or project description (otherwise)
         KeyCode::Char('e') => {
             if matches!(app.view_mode, ViewMode::TodoList(_)) {
@@ -218,6 +363,9 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             }
         }
 
+        // Same, in $EDITOR
+        KeyCode::Char('E') => app.start_external_edit(),
+
         // Rename item (todo or project)
         KeyCode::Char('r') => {
              match app.view_mode {
@@ -227,6 +375,28 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
              }
         }
 
+        // Daily review
+        KeyCode::Char('R') => {
+            if matches!(app.view_mode, ViewMode::ProjectList) {
+                app.start_review().await?;
+            }
+        }
+
+        // Set effort estimate
+        KeyCode::Char('t') => app.start_edit_estimate(),
+
+        // Set project target date
+        KeyCode::Char('T') => app.start_edit_target_date(),
+
+        // Set todo due date, or duplicate a project
+        KeyCode::Char('D') => match app.view_mode {
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => app.start_clone_project().await?,
+            _ => app.start_edit_due_date(),
+        },
+
+        // Week calendar of due todos
+        KeyCode::Char('C') => app.open_calendar().await?,
+
         // Help
         KeyCode::Char('?') => app.show_help(),
 
@@ -238,6 +408,196 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
     Ok(())
 }
 
+/// Handle keys while the expanded todo's checklist has focus
+async fn handle_checklist_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Char('q') => app.should_quit = true,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.should_quit = true
+        }
+        KeyCode::Char('j') | KeyCode::Down => app.move_checklist(true),
+        KeyCode::Char('k') | KeyCode::Up => app.move_checklist(false),
+        KeyCode::Char(' ') => app.toggle_checklist_item().await?,
+        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => app.scroll_details(true),
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => app.scroll_details(false),
+        KeyCode::Char('z') => app.toggle_detail_fullscreen(),
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('x') => app.checklist_index = None,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Handle keys while the expanded todo's related todos have focus
+async fn handle_related_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Char('q') => app.should_quit = true,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.should_quit = true
+        }
+        KeyCode::Char('j') | KeyCode::Down => app.move_related(true),
+        KeyCode::Char('k') | KeyCode::Up => app.move_related(false),
+        KeyCode::Enter => app.open_related().await?,
+        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => app.scroll_details(true),
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => app.scroll_details(false),
+        KeyCode::Char('d') => app.remove_related().await,
+        KeyCode::Char('z') => app.toggle_detail_fullscreen(),
+        KeyCode::Esc | KeyCode::Char('l') => app.related_index = None,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Handle keys on the help screen
+async fn handle_help_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    let page = (app.visible_rows.get() / 2).max(1) as i32;
+    match key.code {
+        KeyCode::Char('q') => app.should_quit = true,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.should_quit = true
+        }
+        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => app.scroll_help(page),
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => app.scroll_help(-page),
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_help(1),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_help(-1),
+        KeyCode::Char('g') => app.help_scroll = 0,
+        KeyCode::Char('G') => app.scroll_help(i32::MAX / 2),
+        KeyCode::Char('/') => {
+            app.input_buffer = app.help_query.clone();
+            app.input_mode = InputMode::SearchHelp;
+        }
+        // Clear a search first, then close
+        KeyCode::Esc if !app.help_query.is_empty() => {
+            app.help_query.clear();
+            app.help_scroll = 0;
+        }
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('?') => app.close_help().await?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Handle keys while typing a help search; the list narrows as you type
+fn handle_search_help_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => {
+            app.input_mode = InputMode::Normal;
+            app.input_buffer.clear();
+        }
+        KeyCode::Esc => {
+            app.cancel_input();
+            app.help_query.clear();
+        }
+        KeyCode::Char(c) => {
+            app.input_buffer.push(c);
+            app.help_query = app.input_buffer.clone();
+        }
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+            app.help_query = app.input_buffer.clone();
+        }
+        _ => {}
+    }
+    app.help_scroll = 0;
+}
+
+/// Handle keys while walking through projects in review mode
+async fn handle_review_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Char('q') => app.should_quit = true,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.should_quit = true
+        }
+
+        KeyCode::Char('j') | KeyCode::Down => app.next_item(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_item(),
+
+        // Mark done
+        KeyCode::Char(' ') => {
+            if let Some(todo) = app.todos.get(app.selected_index) {
+                let todo_id = todo.id;
+                if let Err(e) = app.service.toggle_todo(todo_id).await {
+                    app.set_error(format!("Error completing todo: {}", e));
+                } else {
+                    app.reload_review_todos().await?;
+                }
+            }
+        }
+
+        // Defer to the end of the list
+        KeyCode::Char('s') => {
+            if let Some(todo) = app.todos.get(app.selected_index) {
+                let todo_id = todo.id;
+                if let Err(e) = app.service.defer_todo(todo_id).await {
+                    app.set_error(format!("Error deferring todo: {}", e));
+                } else {
+                    app.reload_review_todos().await?;
+                    app.set_success("Todo deferred");
+                }
+            }
+        }
+
+        // Reprioritize
+        KeyCode::Char('J') | KeyCode::Char('K') => {
+            if let Some(todo) = app.todos.get(app.selected_index) {
+                let todo_id = todo.id;
+                let result = if key.code == KeyCode::Char('J') {
+                    app.service.move_todo_down(todo_id).await
+                } else {
+                    app.service.move_todo_up(todo_id).await
+                };
+                if let Err(e) = result {
+                    app.set_error(format!("Error moving todo: {}", e));
+                } else {
+                    app.reload_review_todos().await?;
+                    if key.code == KeyCode::Char('J') {
+                        app.next_item();
+                    } else {
+                        app.previous_item();
+                    }
+                }
+            }
+        }
+
+        KeyCode::Char('n') | KeyCode::Enter => app.next_review_project().await?,
+        KeyCode::Char('?') => app.show_help(),
+        KeyCode::Esc => {
+            app.end_review().await?;
+            app.set_status("Review stopped");
+        }
+
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Handle keys in the week calendar
+async fn handle_calendar_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Char('q') => app.should_quit = true,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.should_quit = true
+        }
+
+        KeyCode::Char('j') | KeyCode::Down => app.next_item(),
+        KeyCode::Char('k') | KeyCode::Up => app.previous_item(),
+        KeyCode::Char('h') | KeyCode::Left => app.move_calendar(-1).await?,
+        KeyCode::Char('l') | KeyCode::Right => app.move_calendar(1).await?,
+        KeyCode::Char('[') => app.move_calendar(-7).await?,
+        KeyCode::Char(']') => app.move_calendar(7).await?,
+        KeyCode::Char('t') => app.calendar_today().await?,
+
+        KeyCode::Enter => app.open_calendar_todo().await?,
+        KeyCode::Char(' ') => app.toggle_calendar_todo().await?,
+        KeyCode::Char('D') => app.start_edit_due_date(),
+        KeyCode::Char('?') => app.show_help(),
+        KeyCode::Char(':') => app.start_command_mode(),
+        KeyCode::Esc => app.back_to_projects().await?,
+
+        _ => {}
+    }
+    Ok(())
+}
+
 /// Handle keys when adding a project
 async fn handle_add_project_mode(app: &mut App, key: KeyEvent) -> Result<()> {
     match key.code {
@@ -245,13 +605,17 @@ async fn handle_add_project_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             let name = app.input_buffer.trim().to_string();
             if !name.is_empty() {
                 match app.service.create_project(&name).await {
-                    Ok(_) => {
-                        app.set_status(format!("Project '{}' created", name));
+                    Ok(project) => {
+                        // A project added while a workspace is shown goes in it
+                        if let Some(workspace) = &app.workspace
+                            && let Err(e) = app.service.update_project_workspace(project.id, Some(workspace.id)).await
+                        {
+                            app.report_error(&e);
+                        }
+                        app.set_success(format!("Project '{}' created", name));
                         app.load_projects().await?;
                     }
-                    Err(e) => {
-                        app.set_status(format!("Error: {}", e));
-                    }
+                    Err(e) => app.report_error(&e),
                 }
             }
             app.cancel_input();
@@ -266,26 +630,11 @@ async fn handle_add_project_mode(app: &mut App, key: KeyEvent) -> Result<()> {
     Ok(())
 }
 
-/// Handle keys when adding a todo
+/// Handle keys when adding a todo; Tab moves between description and details
 async fn handle_add_todo_mode(app: &mut App, key: KeyEvent) -> Result<()> {
     match key.code {
-        KeyCode::Enter => {
-            if let ViewMode::TodoList(project_id) = app.view_mode {
-                let description = app.input_buffer.trim().to_string();
-                if !description.is_empty() {
-                    match app.service.create_todo(project_id, &description).await {
-                        Ok(_) => {
-                            app.set_status("Todo created");
-                            app.load_todos(project_id).await?;
-                        }
-                        Err(e) => {
-                            app.set_status(format!("Error: {}", e));
-                        }
-                    }
-                }
-            }
-            app.cancel_input();
-        }
+        KeyCode::Enter => app.save_new_todo().await?,
+        KeyCode::Tab | KeyCode::BackTab => app.switch_new_todo_field(),
         KeyCode::Esc => app.cancel_input(),
         KeyCode::Char(c) => app.input_buffer.push(c),
         KeyCode::Backspace => {
@@ -301,7 +650,7 @@ async fn handle_edit_description_mode(app: &mut App, key: KeyEvent) -> Result<()
     match key.code {
         KeyCode::Enter => {
             if let Err(e) = app.save_description().await {
-                app.set_status(format!("Error: {}", e));
+                app.set_error(format!("Error: {}", e));
                 app.cancel_input();
             }
         }
@@ -320,7 +669,7 @@ async fn handle_edit_todo_details_mode(app: &mut App, key: KeyEvent) -> Result<(
     match key.code {
         KeyCode::Enter => {
             if let Err(e) = app.save_todo_details().await {
-                app.set_status(format!("Error: {}", e));
+                app.set_error(format!("Error: {}", e));
                 app.cancel_input();
             }
         }
@@ -339,7 +688,7 @@ async fn handle_edit_todo_mode(app: &mut App, key: KeyEvent) -> Result<()> {
     match key.code {
         KeyCode::Enter => {
             if let Err(e) = app.save_todo().await {
-                app.set_status(format!("Error: {}", e));
+                app.set_error(format!("Error: {}", e));
                 app.cancel_input();
             }
         }
@@ -358,7 +707,7 @@ async fn handle_edit_project_name_mode(app: &mut App, key: KeyEvent) -> Result<(
     match key.code {
         KeyCode::Enter => {
             if let Err(e) = app.save_project_name().await {
-                app.set_status(format!("Error: {}", e));
+                app.set_error(format!("Error: {}", e));
                 app.cancel_input();
             }
         }
@@ -372,17 +721,139 @@ async fn handle_edit_project_name_mode(app: &mut App, key: KeyEvent) -> Result<(
     Ok(())
 }
 
+/// Handle keys when editing a todo's estimate
+async fn handle_edit_estimate_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Enter => {
+            if let Err(e) = app.save_estimate().await {
+                app.set_error(format!("Error: {}", e));
+                app.cancel_input();
+            }
+        }
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Char(c) if c.is_ascii_digit() => app.input_buffer.push(c),
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Handle keys when editing a project's target date
+async fn handle_edit_target_date_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Enter => {
+            if let Err(e) = app.save_target_date().await {
+                app.set_error(format!("Error: {}", e));
+                app.cancel_input();
+            }
+        }
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Char(c) => app.input_buffer.push(c),
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Handle keys when editing a todo's due date
+async fn handle_edit_due_date_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Enter => {
+            if let Err(e) = app.save_due_date().await {
+                app.set_error(format!("Error: {}", e));
+                app.cancel_input();
+            }
+        }
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Char(c) => app.input_buffer.push(c),
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Handle keys in the sort modal
+async fn handle_pick_sort_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => app.move_sort_picker(true),
+        KeyCode::Char('k') | KeyCode::Up => app.move_sort_picker(false),
+        KeyCode::Char(' ') => app.cycle_sort_key(),
+        KeyCode::Backspace => app.sort_picker.clear(),
+        KeyCode::Enter => app.apply_sort_picker().await?,
+        KeyCode::Esc => app.cancel_input(),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Handle keys in the history modal
+async fn handle_pick_version_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => app.move_history(true),
+        KeyCode::Char('k') | KeyCode::Up => app.move_history(false),
+        KeyCode::Enter => app.restore_history_version().await?,
+        KeyCode::Esc => {
+            app.history.clear();
+            app.cancel_input();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Handle keys while the full-text popup is open
+fn handle_full_text_mode(app: &mut App, key: KeyEvent) {
+    if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('f') | KeyCode::Char('q')) {
+        app.cancel_input();
+    }
+}
+
+/// Handle keys while naming a copy of a project
+async fn handle_clone_project_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Enter => app.save_clone_project().await?,
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Char(c) => app.input_buffer.push(c),
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 /// Handle keys in command mode
 async fn handle_command_mode(app: &mut App, key: KeyEvent) -> Result<()> {
     match key.code {
         KeyCode::Enter => {
-            let command = app.input_buffer.trim().to_lowercase();
-            match command.as_str() {
-                "q" | "quit" => app.should_quit = true,
-                "help" => app.show_help(),
-                _ => app.set_status(format!("Unknown command: {}", command)),
-            }
+            let input = app.input_buffer.trim().to_string();
             app.cancel_input();
+            if !input.is_empty() {
+                match command::parse(&input) {
+                    Ok(cmd) => {
+                        if let Err(e) = app.execute_command(cmd).await {
+                            app.set_error(format!("Error: {}", e));
+                        }
+                    }
+                    Err(e) => app.set_error(e),
+                }
+            }
+        }
+        KeyCode::Tab => {
+            let project_names: Vec<String> =
+                app.projects.iter().map(|p| p.project.name.clone()).collect();
+            let workspace_names: Vec<String> = app.workspaces.iter().map(|w| w.name.clone()).collect();
+            let completed =
+                command::complete(&app.input_buffer, &project_names, &app.template_names, &workspace_names);
+            if let Some(completed) = completed {
+                app.input_buffer = completed;
+            }
         }
         KeyCode::Esc => app.cancel_input(),
         KeyCode::Char(c) => app.input_buffer.push(c),
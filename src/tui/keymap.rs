@@ -0,0 +1,214 @@
+use super::app::ViewMode;
+
+/// Where a key binding applies, used to group and filter the help screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyContext {
+    /// Both the project and the todo lists
+    Lists,
+    Projects,
+    Todos,
+    Review,
+    Calendar,
+    Commands,
+}
+
+impl KeyContext {
+    pub fn title(&self) -> &'static str {
+        match self {
+            KeyContext::Lists => "Lists",
+            KeyContext::Projects => "Projects",
+            KeyContext::Todos => "Todos",
+            KeyContext::Review => "Review",
+            KeyContext::Calendar => "Calendar",
+            KeyContext::Commands => "Commands",
+        }
+    }
+
+    /// Contexts relevant to a view, most specific first
+    pub fn for_view(view: &ViewMode) -> &'static [KeyContext] {
+        match view {
+            ViewMode::ProjectList | ViewMode::ArchivedProjects => {
+                &[KeyContext::Projects, KeyContext::Lists, KeyContext::Commands]
+            }
+            ViewMode::TodoList(_) | ViewMode::AllTodos => &[KeyContext::Todos, KeyContext::Lists, KeyContext::Commands],
+            ViewMode::Review => &[KeyContext::Review],
+            ViewMode::Calendar => &[KeyContext::Calendar, KeyContext::Commands],
+            ViewMode::Help | ViewMode::Messages | ViewMode::Log => ALL,
+        }
+    }
+}
+
+const ALL: &[KeyContext] = &[
+    KeyContext::Lists,
+    KeyContext::Projects,
+    KeyContext::Todos,
+    KeyContext::Review,
+    KeyContext::Calendar,
+    KeyContext::Commands,
+];
+
+/// A key (or `:` command) and what it does
+#[derive(Debug, PartialEq)]
+pub struct Binding {
+    pub keys: &'static str,
+    pub description: &'static str,
+    pub context: KeyContext,
+}
+
+const fn bind(context: KeyContext, keys: &'static str, description: &'static str) -> Binding {
+    Binding { keys, description, context }
+}
+
+/// Every key the TUI handles, the source of the help screen. Keep it in
+/// step with `input.rs` when adding or changing a key.
+pub const BINDINGS: &[Binding] = {
+    use KeyContext::*;
+    &[
+        bind(Lists, "j / Down", "Move down (prefix a count: 5j)"),
+        bind(Lists, "k / Up", "Move up (prefix a count: 3k)"),
+        bind(Lists, "gg / G", "First / last row (5G or 5gg: row 5)"),
+        bind(Lists, "H / M / L", "Top / middle / bottom row on screen"),
+        bind(Lists, "a", "Add a project / todo"),
+        bind(Lists, "dd", "Delete the selected item (3dd: three items)"),
+        bind(Lists, "r", "Rename the selected project / todo"),
+        bind(Lists, "|", "Toggle side-by-side projects and todos (Tab switches pane)"),
+        bind(Lists, "< / >  \\", "Narrow / widen the project pane / Hide it while in todos"),
+        bind(Lists, "C", "Week calendar of due todos"),
+        bind(Lists, ":", "Command mode (Tab completes)"),
+        bind(Lists, "?", "Show this help"),
+        bind(Lists, "q / Ctrl+C", "Quit"),
+        bind(Projects, "Enter", "Open the selected project (top row: all active todos)"),
+        bind(Projects, "Y", "Copy the project as a Markdown checklist to the clipboard"),
+        bind(Projects, "T", "Set the project's target date (YYYY-MM-DD or +days)"),
+        bind(Projects, "D", "Duplicate the project with its open todos"),
+        bind(Projects, "A", "Archive / unarchive the project"),
+        bind(Projects, "v", "Toggle between active and archived projects"),
+        bind(Projects, "Space", "Fold / unfold the project's sub-projects"),
+        bind(Projects, "W", "Next workspace (Work, Home, ...), then all of them again"),
+        bind(Projects, "R", "Review projects not yet reviewed today"),
+        bind(Todos, "Enter", "Expand / collapse the selected todo"),
+        bind(Todos, "Esc", "Collapse the todo / back to the project list"),
+        bind(Todos, "Backspace", "Up to the parent of a sub-project"),
+        bind(Todos, "a ... Tab", "Add a todo, then Tab to fill in its details before Enter"),
+        bind(Todos, "Space", "Toggle completion"),
+        bind(Todos, "J / K", "Move the todo down / up (needs :sort position)"),
+        bind(Todos, "y / p", "Yank the todo (3y: three) / paste into the open project"),
+        bind(Todos, "Y", "Copy the todo's text to the clipboard"),
+        bind(Todos, "e", "Edit details (expanded) / project description"),
+        bind(Todos, "E", "Same, in $EDITOR"),
+        bind(Todos, "t", "Set an effort estimate (empty clears it)"),
+        bind(Todos, "T", "Set the project's target date (YYYY-MM-DD or +days)"),
+        bind(Todos, "D", "Set a due date (YYYY-MM-DD or +days)"),
+        bind(Todos, "x", "Check off the details' - [ ] checklist (j/k, Space, Esc)"),
+        bind(Todos, "l", "Related todos (j/k, Enter: open in its project, d: unlink, Esc)"),
+        bind(Todos, "c", "Show / hide completed todos"),
+        bind(Todos, "i", "Compact / detailed rows (tags, due date and details under each todo)"),
+        bind(Todos, "w", "Wrap long descriptions / cut them short"),
+        bind(Todos, "f", "Show the selected todo's full text"),
+        bind(Todos, "o", "Sort by several keys (Space: asc / desc / off, Enter: sort)"),
+        bind(Todos, "u", "Earlier versions of the todo's text (Enter restores one)"),
+        bind(Todos, "Ctrl+d / u", "Scroll the expanded details down / up"),
+        bind(Todos, "+ / -  z", "Resize the details pane / toggle full-screen details"),
+        bind(Review, "j / k", "Move between todos"),
+        bind(Review, "Space", "Mark the todo done"),
+        bind(Review, "s", "Defer the todo to the end of the list"),
+        bind(Review, "J / K", "Move the todo down / up"),
+        bind(Review, "n / Enter", "Next project"),
+        bind(Review, "Esc", "Stop reviewing"),
+        bind(Calendar, "h / l", "Previous / next day"),
+        bind(Calendar, "j / k", "Move between the day's todos"),
+        bind(Calendar, "[ / ]", "Previous / next week"),
+        bind(Calendar, "t", "Back to today"),
+        bind(Calendar, "Enter", "Open the todo in its project"),
+        bind(Calendar, "Space", "Toggle completion"),
+        bind(Calendar, "D", "Set the todo's due date"),
+        bind(Calendar, "?", "Show this help"),
+        bind(Calendar, "Esc", "Back to the project list"),
+        bind(Commands, ":add <desc>", "Add a todo to the open project"),
+        bind(Commands, ":project <name>", "Jump to a project"),
+        bind(Commands, ":sort <key>, ...", "e.g. due, estimate desc; position alone allows J / K"),
+        bind(Commands, ":filter [query]", "Show matching todos (e.g. tag:home due:<fri); empty clears"),
+        bind(Commands, ":archive", "Archive the selected / open project"),
+        bind(Commands, ":archive-stale", "Archive finished projects with no recent activity"),
+        bind(Commands, ":export [md|csv]", "Write the project to ./<name>.md"),
+        bind(Commands, ":review", "Start the daily review"),
+        bind(Commands, ":calendar", "Show this week's due todos"),
+        bind(Commands, ":template [name]", "Add a todo from a template; empty lists them"),
+        bind(Commands, ":history", "Earlier versions of the selected todo's text, to restore"),
+        bind(Commands, ":columns [names]", "Pick the table's columns, e.g. status, description, due, tags"),
+        bind(Commands, ":link [KEY|URL]", "Link the selected todo to a JIRA issue; empty clears"),
+        bind(Commands, ":relate <todo>", "Link the selected todo to another (text or #id), in any project"),
+        bind(Commands, ":duplicates <todo>", "Mark the selected todo as a duplicate of another"),
+        bind(Commands, ":script <statements>", "Edit the open project's todos in bulk, e.g. tag q3 where invoice"),
+        bind(Commands, ":messages", "Show recent status messages"),
+        bind(Commands, ":log", "Show the end of the log file"),
+        bind(Commands, ":profile [name]", "Reopen on another profile's database; empty lists them"),
+        bind(Commands, ":workspace [name|all]", "Show one workspace's projects (made if new); empty lists them"),
+        bind(Commands, ":nest [project]", "Nest the selected project under another; empty moves it to the top"),
+        bind(Commands, ":assign [workspace]", "File the selected project under a workspace; empty takes it out"),
+        bind(Commands, ":help", "Show this help"),
+        bind(Commands, ":quit", "Quit"),
+    ]
+};
+
+/// A titled group of bindings on the help screen
+#[derive(Debug, PartialEq)]
+pub struct HelpSection {
+    pub title: &'static str,
+    pub bindings: Vec<&'static Binding>,
+}
+
+/// Help for a view: the bindings of its contexts, or with a search query,
+/// every binding whose keys or description contain it
+pub fn help_sections(view: &ViewMode, query: &str) -> Vec<HelpSection> {
+    let query = query.trim().to_lowercase();
+    let contexts = if query.is_empty() { KeyContext::for_view(view) } else { ALL };
+    contexts
+        .iter()
+        .map(|context| HelpSection {
+            title: context.title(),
+            bindings: BINDINGS
+                .iter()
+                .filter(|b| b.context == *context)
+                .filter(|b| {
+                    query.is_empty()
+                        || b.keys.to_lowercase().contains(&query)
+                        || b.description.to_lowercase().contains(&query)
+                })
+                .collect(),
+        })
+        .filter(|section| !section.bindings.is_empty())
+        .collect()
+}
+
+/// Width of the keys column on the help screen
+pub const KEYS_WIDTH: usize = 18;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::command::COMMAND_NAMES;
+
+    #[test]
+    fn test_help_follows_view_and_search() {
+        let sections = help_sections(&ViewMode::Review, "");
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].title, "Review");
+
+        let found = help_sections(&ViewMode::Review, "DUE DATE");
+        let titles: Vec<&str> = found.iter().map(|s| s.title).collect();
+        assert_eq!(titles, vec!["Todos", "Calendar"]);
+    }
+
+    #[test]
+    fn test_every_command_is_documented() {
+        for name in COMMAND_NAMES {
+            let prefix = format!(":{}", name);
+            assert!(
+                BINDINGS.iter().any(|b| b.keys == prefix || b.keys.starts_with(&format!("{} ", prefix))),
+                "{} is missing from the help",
+                prefix
+            );
+        }
+    }
+}
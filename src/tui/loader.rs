@@ -0,0 +1,136 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::sync::mpsc;
+
+use crate::core::models::{ProjectWithStats, RelatedTodo, Todo, TodoMention, Totals};
+
+/// Which list a fetch fills. Only the latest request of each kind is kept,
+/// so moving quickly between projects never shows an older answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadKind {
+    Projects,
+    Todos,
+    Preview,
+    /// JIRA status of the expanded todo's ticket
+    Ticket,
+    /// Todos linked to, mentioned by or mentioning the expanded todo
+    Related,
+}
+
+/// Data fetched off the input loop
+#[derive(Debug)]
+pub enum Loaded {
+    Projects {
+        archived: bool,
+        projects: Vec<ProjectWithStats>,
+        totals: Totals,
+    },
+    Todos {
+        project_id: i64,
+        todos: Vec<Todo>,
+        stats: Box<ProjectWithStats>,
+        /// Refreshed project list for the split view
+        projects: Option<Vec<ProjectWithStats>>,
+    },
+    Preview {
+        project_id: i64,
+        todos: Vec<Todo>,
+    },
+    Ticket {
+        key: String,
+        /// Issue status, or why it could not be fetched
+        status: Result<String, String>,
+    },
+    Related {
+        todo_id: i64,
+        related: Vec<RelatedTodo>,
+        mentions: Vec<TodoMention>,
+    },
+}
+
+type LoadEvent = (LoadKind, u64, Result<Loaded, String>);
+
+/// Database reads running on tokio tasks, so the TUI keeps drawing the data
+/// it has while newer data loads
+pub struct Loader {
+    next_id: u64,
+    /// Latest request of each kind still in flight
+    pending: HashMap<LoadKind, u64>,
+    sender: mpsc::UnboundedSender<LoadEvent>,
+    receiver: mpsc::UnboundedReceiver<LoadEvent>,
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            next_id: 1,
+            pending: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Start a fetch, superseding any earlier one of the same kind
+    pub fn request<Fut>(&mut self, kind: LoadKind, fetch: Fut)
+    where
+        Fut: Future<Output = Result<Loaded>> + Send + 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.insert(kind, id);
+
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            let result = fetch.await.map_err(|e| format!("{:#}", e));
+            let _ = sender.send((kind, id, result));
+        });
+    }
+
+    /// Forget in-flight fetches of a kind, e.g. after loading it directly
+    pub fn cancel(&mut self, kind: LoadKind) {
+        self.pending.remove(&kind);
+    }
+
+    /// Check if a fetch of this kind is in flight
+    pub fn is_pending(&self, kind: LoadKind) -> bool {
+        self.pending.contains_key(&kind)
+    }
+
+    pub fn is_loading(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Results that arrived since the last poll, minus superseded ones
+    pub fn poll(&mut self) -> Vec<Result<Loaded, String>> {
+        let mut loaded = Vec::new();
+        while let Ok(event) = self.receiver.try_recv() {
+            self.accept(event, &mut loaded);
+        }
+        loaded
+    }
+
+    /// Wait for every in-flight fetch
+    pub async fn wait(&mut self) -> Vec<Result<Loaded, String>> {
+        let mut loaded = Vec::new();
+        while self.is_loading() {
+            let Some(event) = self.receiver.recv().await else { break };
+            self.accept(event, &mut loaded);
+        }
+        loaded
+    }
+
+    fn accept(&mut self, (kind, id, result): LoadEvent, loaded: &mut Vec<Result<Loaded, String>>) {
+        if self.pending.get(&kind) == Some(&id) {
+            self.pending.remove(&kind);
+            loaded.push(result);
+        }
+    }
+}
+
+impl Default for Loader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
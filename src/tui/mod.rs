@@ -1,6 +1,18 @@
 pub mod app;
+pub mod clipboard;
+pub mod columns;
+pub mod command;
+pub mod editor;
+pub mod fit;
 pub mod ui;
 pub mod input;
+pub mod keymap;
+pub mod loader;
+pub mod plain;
+pub mod prefs;
+pub mod script;
+pub mod tasks;
+pub mod theme;
 pub mod views;
 
 pub use app::App;
@@ -0,0 +1,382 @@
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::io::Write;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use super::app::{App, InputMode, ViewMode};
+use super::input;
+use crate::core::models::{Todo, TodoAge};
+use crate::core::sort::SortField;
+
+/// Run the line-oriented interface for screen readers and braille displays.
+///
+/// Each line typed is fed to the same key handlers as the full-screen TUI:
+/// `j`, `5j`, `dd` and `:add milk` work as usual, an empty line is `Enter`,
+/// and `esc`, `space` and `tab` name those keys. A key that opens a prompt
+/// (`a`, `r`, `:`) takes the rest of the line as its text, or the next line.
+/// Output is plain text without colours or borders; the list is printed
+/// again only when it changes, and the selected row after every key.
+pub async fn run(app: &mut App) -> Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut shown: Vec<String> = Vec::new();
+    let mut selected: Option<String> = None;
+    let mut last_status = None;
+
+    loop {
+        app.finish_loading().await;
+        app.poll_tasks().await?;
+        app.refresh_if_changed().await?;
+        app.refresh_ticket();
+        app.refresh_related();
+        app.finish_loading().await;
+
+        let mut out = std::io::stdout().lock();
+        let (body, row) = screen(app);
+        let changed = body != shown;
+        if changed {
+            writeln!(out)?;
+            for line in &body {
+                writeln!(out, "{}", line)?;
+            }
+            shown = body;
+        }
+        if changed || row != selected {
+            if let Some(row) = &row {
+                writeln!(out, "Selected: {}", row)?;
+            }
+            selected = row;
+        }
+        if let Some(status) = &app.status_message
+            && last_status.as_ref() != Some(&status.at)
+        {
+            writeln!(out, "{}", status.text)?;
+            last_status = Some(status.at);
+        }
+        write!(out, "{}", prompt(app))?;
+        out.flush()?;
+        drop(out);
+
+        let Some(line) = lines.next_line().await? else { break };
+        feed_line(app, &line).await?;
+        if let Some(target) = app.editor_request.take() {
+            // No screen to hand over: the editor just takes the terminal
+            match super::editor::edit(&app.editor_text(target)) {
+                Ok(Some(text)) => app.save_editor_text(target, &text).await?,
+                Ok(None) => app.set_warning("Editor exited with an error; changes discarded"),
+                Err(e) => app.set_error(format!("Error: {}", e)),
+            }
+        }
+        if app.should_quit {
+            break;
+        }
+    }
+    app.finish_tasks().await
+}
+
+/// Turn a typed line into key presses
+async fn feed_line(app: &mut App, line: &str) -> Result<()> {
+    // The sort modal's keys need a screen; a typed spec does the same
+    if app.input_mode == InputMode::PickSort {
+        app.cancel_input();
+        let spec = line.trim();
+        if spec.is_empty() || spec.eq_ignore_ascii_case("esc") {
+            return Ok(());
+        }
+        app.start_command_mode();
+        return type_text(app, &format!("sort {}", spec)).await;
+    }
+    // So is the history modal's; a typed number picks the version
+    if app.input_mode == InputMode::PickVersion {
+        match line.trim().parse::<usize>() {
+            Ok(n) if (1..=app.history.len()).contains(&n) => {
+                app.history_index = n - 1;
+                app.restore_history_version().await?;
+            }
+            _ => {
+                app.history.clear();
+                app.cancel_input();
+            }
+        }
+        return Ok(());
+    }
+    // The popup only needs closing
+    if app.input_mode == InputMode::FullText {
+        app.cancel_input();
+        return Ok(());
+    }
+    if app.input_mode != InputMode::Normal {
+        return type_text(app, line.trim()).await;
+    }
+    let named = match line.trim().to_lowercase().as_str() {
+        "" | "enter" => Some(KeyCode::Enter),
+        "esc" => Some(KeyCode::Esc),
+        "space" => Some(KeyCode::Char(' ')),
+        "tab" => Some(KeyCode::Tab),
+        _ => None,
+    };
+    if let Some(code) = named {
+        return input::handle_key(app, KeyEvent::new(code, KeyModifiers::NONE)).await;
+    }
+
+    let mut chars = line.trim().chars();
+    while let Some(c) = chars.next() {
+        input::handle_key(app, key(c)).await?;
+        if app.input_mode != InputMode::Normal {
+            let rest = chars.as_str().trim();
+            if !rest.is_empty() {
+                type_text(app, rest).await?;
+            }
+            break;
+        }
+        if app.should_quit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Answer a prompt: the line replaces the text being edited, `esc` cancels
+async fn type_text(app: &mut App, text: &str) -> Result<()> {
+    if text.eq_ignore_ascii_case("esc") {
+        return input::handle_key(app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).await;
+    }
+    app.input_buffer.clear();
+    for c in text.chars() {
+        // A tab moves on to the next field, e.g. from a new todo to its details
+        let code = if c == '\t' { KeyCode::Tab } else { KeyCode::Char(c) };
+        input::handle_key(app, KeyEvent::new(code, KeyModifiers::NONE)).await?;
+    }
+    input::handle_key(app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await
+}
+
+/// Key press for a typed character; capitals carry Shift like a real keyboard
+fn key(c: char) -> KeyEvent {
+    let modifiers = if c.is_uppercase() { KeyModifiers::SHIFT } else { KeyModifiers::NONE };
+    KeyEvent::new(KeyCode::Char(c), modifiers)
+}
+
+/// Prompt for the next line
+fn prompt(app: &App) -> String {
+    let current = |label: &str| {
+        if app.input_buffer.is_empty() {
+            format!("{} (empty line clears, esc cancels): ", label)
+        } else {
+            format!("{} (now: {}; esc cancels): ", label, app.input_buffer)
+        }
+    };
+    match app.input_mode {
+        InputMode::Normal => "> ".to_string(),
+        InputMode::AddProject => "New project: ".to_string(),
+        InputMode::AddTodo => "New todo: ".to_string(),
+        InputMode::AddTodoDetails => "Details: ".to_string(),
+        InputMode::Command => ":".to_string(),
+        InputMode::SearchHelp => "Search help: ".to_string(),
+        InputMode::EditDescription => current("Project description"),
+        InputMode::EditTodoDetails => current("Details"),
+        InputMode::EditTodo => current("Todo"),
+        InputMode::EditProjectName => current("Project name"),
+        InputMode::EditEstimate => current("Estimate"),
+        InputMode::EditTargetDate => current("Target date, YYYY-MM-DD or +days"),
+        InputMode::EditDueDate => current("Due date, YYYY-MM-DD or +days"),
+        InputMode::CloneProject => current("Copy with open todos as"),
+        InputMode::PickSort => format!(
+            "Sort by (now: {}; e.g. due, estimate desc; {}; esc cancels): ",
+            app.todo_sort,
+            SortField::names().join(", ")
+        ),
+        InputMode::PickVersion => format!("Restore version (1-{}, esc cancels): ", app.history.len()),
+        InputMode::FullText => "Enter closes: ".to_string(),
+    }
+}
+
+/// The current view as plain lines, and the selected row
+fn screen(app: &App) -> (Vec<String>, Option<String>) {
+    let zone = app.config.timezone;
+    let mut lines = Vec::new();
+    if app.input_mode == InputMode::PickVersion {
+        lines.push("Earlier versions, newest first:".to_string());
+        for (i, version) in app.history.iter().enumerate() {
+            let at = zone.convert(version.replaced_at).format("%Y-%m-%d %H:%M");
+            lines.push(format!("{}. {}  {}", i + 1, at, version.description));
+            if let Some(details) = &version.details {
+                lines.extend(details.lines().map(|line| format!("     {}", line)));
+            }
+        }
+        return (lines, None);
+    }
+    if app.input_mode == InputMode::FullText
+        && let Some(todo) = app.todos.get(app.selected_index)
+    {
+        lines.push(todo.description.clone());
+        if let Some(details) = &todo.details {
+            lines.extend(details.lines().map(|line| format!("  {}", line)));
+        }
+        return (lines, None);
+    }
+    let rows: Vec<String> = match &app.view_mode {
+        ViewMode::ProjectList | ViewMode::ArchivedProjects => {
+            let scope = app.workspace.as_ref().map_or(String::new(), |w| format!(" in {}", w.name));
+            lines.push(if app.view_mode == ViewMode::ArchivedProjects {
+                format!("Archived projects{}: {}", scope, app.totals_summary())
+            } else {
+                format!("Projects{}: {}", scope, app.totals_summary())
+            });
+            let all = (app.view_mode == ViewMode::ProjectList).then(|| {
+                format!("All todos, {} open", app.listed_open_todos())
+            });
+            all.into_iter()
+                .chain(app.projects.iter().enumerate().map(|(i, p)| {
+                    let tree = app.project_tree.get(i).copied().unwrap_or_default();
+                    let indent = "  ".repeat(tree.depth);
+                    let mut row = format!("{}{}, {} open", indent, p.project.name, tree.active_todos());
+                    if tree.has_children && app.collapsed_projects.contains(&p.project.id) {
+                        row.push_str(", sub-projects folded");
+                    }
+                    match p.project.days_remaining(zone) {
+                        Some(days) if days < 0 => row.push_str(&format!(", {} days overdue", -days)),
+                        Some(days) => row.push_str(&format!(", {} days left", days)),
+                        None => {}
+                    }
+                    row
+                }))
+                .collect()
+        }
+        ViewMode::TodoList(_) | ViewMode::AllTodos | ViewMode::Review => {
+            let name = app.current_project.as_ref().map_or("Todos", |p| p.name.as_str());
+            lines.push(match app.view_mode {
+                ViewMode::Review => format!("Review {} of {}: {}", app.review_position + 1, app.review_queue.len(), name),
+                ViewMode::AllTodos => "All todos".to_string(),
+                _ => app.breadcrumb.iter().map(String::as_str).chain([name]).collect::<Vec<_>>().join(" › "),
+            });
+            if let Some(description) = app.current_project.as_ref().and_then(|p| p.description.as_deref()) {
+                lines.push(description.to_string());
+            }
+            if let Some(filter) = &app.todo_filter {
+                lines.push(format!("Filter: {}", filter));
+            }
+            app.todos.iter().map(|todo| todo_row(app, todo)).collect()
+        }
+        ViewMode::Calendar => {
+            let start = app.calendar_week_start();
+            lines.push(format!("Week of {}", start.format("%A %-d %B %Y")));
+            for offset in 0..7 {
+                let day = start + chrono::Duration::days(offset);
+                let due: Vec<&str> = app
+                    .calendar_todos
+                    .iter()
+                    .filter(|t| t.due_date == Some(day))
+                    .map(|t| t.description.as_str())
+                    .collect();
+                if !due.is_empty() {
+                    lines.push(format!("{}: {}", day.format("%a %-d"), due.join("; ")));
+                }
+            }
+            lines.push(format!("Day: {}", app.calendar_date.format("%A %-d %B")));
+            app.calendar_day_todos().into_iter().map(|todo| todo_row(app, todo)).collect()
+        }
+        ViewMode::Help => {
+            lines.extend(help_lines(app));
+            Vec::new()
+        }
+        ViewMode::Messages => {
+            lines.push("Messages".to_string());
+            lines.extend(
+                app.messages
+                    .iter()
+                    .map(|m| format!("{} {}", zone.convert(m.at).format("%H:%M:%S"), m.text)),
+            );
+            Vec::new()
+        }
+        ViewMode::Log => {
+            lines.push("Log".to_string());
+            lines.extend(app.log_lines.iter().cloned());
+            Vec::new()
+        }
+    };
+
+    if rows.is_empty() && !matches!(app.view_mode, ViewMode::Help | ViewMode::Messages | ViewMode::Log) {
+        lines.push("Nothing here".to_string());
+    }
+    for (i, row) in rows.iter().enumerate() {
+        lines.push(format!("{}. {}", i + 1, row));
+    }
+    if let Some(todo) = app.get_expanded_todo() {
+        lines.push(format!("Details for {} {}:", app.short_id(todo), todo.description));
+        if let Some(reference) = &todo.external_ref {
+            lines.push(format!("Link: {}", reference));
+        }
+        lines.extend((0..app.related_rows()).filter_map(|i| app.related_row(i)).map(related_text));
+        lines.extend(todo.details.as_deref().unwrap_or("No details").lines().map(str::to_string));
+    }
+
+    // While the checklist has focus, the selection is its item
+    if let Some(index) = app.checklist_focus()
+        && let Some(todo) = app.get_expanded_todo()
+    {
+        let items = todo.checklist();
+        let item = items[index];
+        let state = if item.checked { "checked" } else { "not checked" };
+        let selected = format!("Checklist item {} of {}, {}, {}", index + 1, items.len(), state, item.text);
+        return (lines, Some(selected));
+    }
+    if let Some(index) = app.related_focus()
+        && let Some(row) = app.related_row(index)
+    {
+        let related = related_text(row);
+        let selected = format!("Related todo {} of {}, {}", index + 1, app.related_rows(), related);
+        return (lines, Some(selected));
+    }
+    let selected = rows
+        .get(app.selected_index)
+        .map(|row| format!("{} of {}, {}", app.selected_index + 1, rows.len(), row));
+    (lines, selected)
+}
+
+/// `done, Fix login, due 2026-10-20, estimate 3`; in All todos the
+/// project comes after the description
+fn todo_row(app: &App, todo: &Todo) -> String {
+    let mut row = if todo.is_completed() {
+        format!("done, {}", todo.description)
+    } else {
+        todo.description.clone()
+    };
+    if app.view_mode == ViewMode::AllTodos {
+        row.push_str(&format!(", in {}", app.project_name(todo.project_id)));
+    }
+    if let Some(due) = todo.due_date {
+        let overdue = if todo.is_overdue(app.config.timezone) { ", overdue" } else { "" };
+        row.push_str(&format!(", due {}{}", due, overdue));
+    }
+    if let Some(estimate) = todo.estimate {
+        row.push_str(&format!(", estimate {}", estimate));
+    }
+    if let Some((checked, total)) = todo.checklist_progress() {
+        row.push_str(&format!(", checklist {} of {} done", checked, total));
+    }
+    match todo.age() {
+        TodoAge::Fresh => {}
+        TodoAge::Aging => row.push_str(&format!(", open {} days", todo.age_days())),
+        TodoAge::Stale => row.push_str(&format!(", stale, open {} days", todo.age_days())),
+    }
+    row
+}
+
+/// `Duplicated by: Pick up paint, in Work, done`
+fn related_text((label, todo, project): (&str, &Todo, &str)) -> String {
+    let done = if todo.is_completed() { ", done" } else { "" };
+    format!("{}: {}, in {}{}", label, todo.description, project, done)
+}
+
+/// The help screen as text
+fn help_lines(app: &App) -> Vec<String> {
+    let mut lines = vec![if app.help_query.is_empty() {
+        "Help (/ searches)".to_string()
+    } else {
+        format!("Help matching {}", app.help_query)
+    }];
+    for section in app.help_sections() {
+        lines.push(format!("{}:", section.title));
+        lines.extend(section.bindings.iter().map(|b| format!("  {}: {}", b.keys, b.description)));
+    }
+    lines
+}
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::columns::{ProjectColumn, TodoColumn};
+use crate::config::Config;
+
+/// TUI layout remembered between sessions in `layout.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutPrefs {
+    pub split_view: bool,
+    /// Width of the project pane in the split view, in percent
+    pub project_pane_width: u16,
+    /// Hide the project pane while the todos pane has focus
+    pub projects_collapsed: bool,
+    /// Height of the expanded details pane, borders included
+    pub detail_height: u16,
+    /// Give each todo a second line with its tags, due date and details
+    pub detailed_rows: bool,
+    /// Wrap long descriptions over several lines instead of cutting them off
+    pub wrap_rows: bool,
+    /// Todo table columns picked with `:columns`, over the configured ones
+    pub todo_columns: Option<Vec<TodoColumn>>,
+    /// Project table columns picked with `:columns`, over the configured ones
+    pub project_columns: Option<Vec<ProjectColumn>>,
+}
+
+impl Default for LayoutPrefs {
+    fn default() -> Self {
+        Self {
+            split_view: false,
+            project_pane_width: 35,
+            projects_collapsed: false,
+            detail_height: 5,
+            detailed_rows: false,
+            wrap_rows: false,
+            todo_columns: None,
+            project_columns: None,
+        }
+    }
+}
+
+impl LayoutPrefs {
+    /// Load the saved layout, falling back to the defaults if there is none
+    /// or it can't be read
+    pub fn load() -> Self {
+        let Ok(path) = Config::layout_file_path() else {
+            return Self::default();
+        };
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the layout for the next session
+    pub fn save(&self) -> Result<()> {
+        let path = Config::layout_file_path()?;
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Which list the TUI was showing when it quit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionView {
+    #[default]
+    Projects,
+    Archived,
+    Todos,
+}
+
+/// Where the TUI was when it last quit, restored on the next start unless
+/// `--fresh` is given
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    pub view: SessionView,
+    /// Selected project in the project lists, or the open project
+    pub project_id: Option<i64>,
+    /// Selected todo in the open project
+    pub todo_id: Option<i64>,
+    pub expanded_todo_id: Option<i64>,
+    /// Lines scrolled in the expanded todo's details
+    pub detail_scroll: u16,
+}
+
+impl SessionState {
+    /// Load the saved session, or start afresh if there is none or it can't be read
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the session for the next start
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_layout_keeps_defaults() {
+        let layout: LayoutPrefs = serde_json::from_str(r#"{"split_view": true}"#).unwrap();
+        assert!(layout.split_view);
+        assert_eq!(layout.project_pane_width, LayoutPrefs::default().project_pane_width);
+    }
+}
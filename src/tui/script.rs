@@ -0,0 +1,270 @@
+use crate::core::error::{DocketError, Result};
+use crate::core::models::Todo;
+use crate::core::search;
+use crate::core::service_api::DocketServiceApi;
+
+/// A `:script` program: statements separated by `;`, each an edit to the
+/// open project's todos, e.g. `prefix "[Q3]" where invoice; tag q3 where invoice`.
+/// Scripts only reach the open project through the service, so they can't
+/// touch files, other projects or anything outside docket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    statements: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Statement {
+    action: Action,
+    /// Only todos whose description or details contain this, folded like `:filter`
+    filter: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    Prefix(String),
+    Suffix(String),
+    Replace(String, String),
+    Tag(String),
+    Untag(String),
+    Complete,
+    Reopen,
+    Add(String),
+    Count,
+}
+
+/// Statement names, for the usage message
+const ACTIONS: &str = "prefix, suffix, replace, tag, untag, complete, reopen, add or count";
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    /// Double-quoted text, never a keyword
+    Quoted(String),
+    End,
+}
+
+/// Split a script into words, quoted strings and `;`
+fn tokenize(source: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == ';' {
+            chars.next();
+            tokens.push(Token::End);
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => text.extend(chars.next()),
+                    Some(c) => text.push(c),
+                    None => return Err(format!("Missing closing quote after \"{}", text)),
+                }
+            }
+            tokens.push(Token::Quoted(text));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == ';' || c == '"' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+    Ok(tokens)
+}
+
+impl std::str::FromStr for Script {
+    type Err = String;
+
+    fn from_str(source: &str) -> std::result::Result<Self, Self::Err> {
+        let tokens = tokenize(source)?;
+        let statements = tokens
+            .split(|t| *t == Token::End)
+            .filter(|s| !s.is_empty())
+            .enumerate()
+            .map(|(i, tokens)| parse_statement(tokens).map_err(|e| format!("Statement {}: {}", i + 1, e)))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if statements.is_empty() {
+            return Err(format!("Usage: :script <statement>; ... (one of {})", ACTIONS));
+        }
+        Ok(Script { statements })
+    }
+}
+
+fn parse_statement(tokens: &[Token]) -> std::result::Result<Statement, String> {
+    let text = |t: &Token| match t {
+        Token::Word(s) | Token::Quoted(s) => s.clone(),
+        Token::End => String::new(),
+    };
+    let (verb, rest) = match tokens.split_first() {
+        Some((Token::Word(verb), rest)) => (verb.to_lowercase(), rest),
+        _ => return Err(format!("expected one of {}", ACTIONS)),
+    };
+    let where_at = rest.iter().position(|t| matches!(t, Token::Word(w) if w.eq_ignore_ascii_case("where")));
+    let (args, filter) = match where_at {
+        Some(at) if at + 1 == rest.len() => return Err("'where' needs the text to match".to_string()),
+        Some(at) => (&rest[..at], Some(rest[at + 1..].iter().map(text).collect::<Vec<_>>().join(" "))),
+        None => (rest, None),
+    };
+    let args: Vec<String> = args.iter().map(text).collect();
+    let one = |usage: &str| match args.is_empty() {
+        true => Err(format!("usage: {}", usage)),
+        false => Ok(args.join(" ")),
+    };
+    let none = |action: Action| match args.is_empty() {
+        true => Ok(action),
+        false => Err(format!("{} takes no arguments, only 'where <text>'", verb)),
+    };
+    let action = match verb.as_str() {
+        "prefix" => Action::Prefix(one("prefix <text> [where <text>]")?),
+        "suffix" => Action::Suffix(one("suffix <text> [where <text>]")?),
+        "replace" => match <[String; 2]>::try_from(args.clone()) {
+            Ok([from, to]) if !from.is_empty() => Action::Replace(from, to),
+            _ => return Err("usage: replace \"<old>\" \"<new>\" [where <text>]".to_string()),
+        },
+        "tag" => Action::Tag(hashtag(&one("tag <name> [where <text>]")?)?),
+        "untag" => Action::Untag(hashtag(&one("untag <name> [where <text>]")?)?),
+        "complete" => none(Action::Complete)?,
+        "reopen" => none(Action::Reopen)?,
+        "count" => none(Action::Count)?,
+        "add" if filter.is_some() => return Err("add can't be combined with 'where'".to_string()),
+        "add" => Action::Add(one("add <description>")?),
+        other => return Err(format!("unknown action '{}' (expected one of {})", other, ACTIONS)),
+    };
+    Ok(Statement { action, filter: filter.as_deref().map(search::fold) })
+}
+
+/// A tag name without its `#`, lowercased as `Todo::tags` reports it
+fn hashtag(name: &str) -> std::result::Result<String, String> {
+    let tag = name.trim_start_matches('#').to_lowercase();
+    if tag.is_empty() || !tag.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("'{}' is not a valid hashtag", name));
+    }
+    Ok(tag)
+}
+
+impl Statement {
+    fn matches(&self, todo: &Todo) -> bool {
+        let Some(filter) = &self.filter else { return true };
+        search::contains(&todo.description, filter)
+            || todo.details.as_ref().is_some_and(|d| search::contains(d, filter))
+    }
+
+    /// The todo's new description, or `None` when the statement leaves it alone
+    fn rewrite(&self, todo: &Todo) -> Option<String> {
+        let description = &todo.description;
+        let rewritten = match &self.action {
+            Action::Prefix(text) if !description.starts_with(text.as_str()) => format!("{} {}", text, description),
+            Action::Suffix(text) if !description.ends_with(text.as_str()) => format!("{} {}", description, text),
+            Action::Replace(from, to) => description.replace(from.as_str(), to),
+            Action::Tag(tag) if !todo.tags().contains(tag) => format!("{} #{}", description, tag),
+            Action::Untag(tag) => description
+                .split_whitespace()
+                .filter(|word| !word.strip_prefix('#').is_some_and(|w| w.eq_ignore_ascii_case(tag)))
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => return None,
+        };
+        (rewritten != *description && !rewritten.trim().is_empty()).then_some(rewritten)
+    }
+}
+
+impl Script {
+    /// Run the statements in order against the project's todos, returning a
+    /// summary for the status line. Each statement sees the previous ones'
+    /// edits; one that fails stops the script with its earlier edits kept.
+    pub async fn run(&self, service: &dyn DocketServiceApi, project_id: i64) -> Result<String> {
+        let mut changed = 0;
+        let mut counts = Vec::new();
+        for (i, statement) in self.statements.iter().enumerate() {
+            let step = async {
+                let todos = service.list_all_todos(project_id).await?;
+                let open: fn(&&Todo) -> bool = |t| t.completed_at.is_none();
+                let done: fn(&&Todo) -> bool = |t| t.completed_at.is_some();
+                match &statement.action {
+                    Action::Add(description) => {
+                        service.create_todo(project_id, description).await?;
+                        changed += 1;
+                    }
+                    Action::Count => {
+                        counts.push(todos.iter().filter(open).filter(|t| statement.matches(t)).count());
+                    }
+                    Action::Complete | Action::Reopen => {
+                        let wanted = if statement.action == Action::Complete { open } else { done };
+                        for todo in todos.iter().filter(wanted).filter(|t| statement.matches(t)) {
+                            service.toggle_todo(todo.id).await?;
+                            changed += 1;
+                        }
+                    }
+                    _ => {
+                        for todo in todos.iter().filter(open).filter(|t| statement.matches(t)) {
+                            if let Some(description) = statement.rewrite(todo) {
+                                service.update_todo(todo.id, &description).await?;
+                                changed += 1;
+                            }
+                        }
+                    }
+                }
+                Ok::<_, DocketError>(())
+            };
+            step.await.map_err(|e| e.context(format!("Statement {}", i + 1)))?;
+        }
+
+        let mut summary = match changed {
+            0 => "Script changed nothing".to_string(),
+            1 => "Script changed 1 todo".to_string(),
+            n => format!("Script changed {} todos", n),
+        };
+        if !counts.is_empty() {
+            let counts: Vec<String> = counts.iter().map(usize::to_string).collect();
+            summary.push_str(&format!("; count: {}", counts.join(", ")));
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::service::test_service;
+
+    #[test]
+    fn test_parse_errors_name_the_statement() {
+        assert!("prefix \"[Q3]\" where invoice; tag q3".parse::<Script>().is_ok());
+        assert_eq!(
+            "count; shout loudly".parse::<Script>().unwrap_err(),
+            "Statement 2: unknown action 'shout' (expected one of prefix, suffix, replace, tag, untag, \
+             complete, reopen, add or count)"
+        );
+        assert!("prefix x where".parse::<Script>().unwrap_err().contains("needs the text"));
+        assert!("replace \"a\"".parse::<Script>().unwrap_err().contains("usage: replace"));
+        assert!("prefix \"[Q3]".parse::<Script>().unwrap_err().contains("closing quote"));
+    }
+
+    #[tokio::test]
+    async fn test_script_edits_matching_open_todos() {
+        let service = test_service().await;
+        let project = service.create_project("Work").await.unwrap();
+        let invoice = service.create_todo(project.id, "Send invoice #billing").await.unwrap();
+        let other = service.create_todo(project.id, "Call Sam").await.unwrap();
+
+        let script: Script = "prefix \"[Q3]\" where INVOICE; untag billing; tag q3 where invoice; count where q3"
+            .parse()
+            .unwrap();
+        let summary = script.run(&service, project.id).await.unwrap();
+        assert_eq!(summary, "Script changed 3 todos; count: 1");
+        assert_eq!(service.get_todo(invoice.id).await.unwrap().description, "[Q3] Send invoice #q3");
+        assert_eq!(service.get_todo(other.id).await.unwrap().description, "Call Sam");
+
+        // Running it again finds nothing left to do
+        let summary = script.run(&service, project.id).await.unwrap();
+        assert_eq!(summary, "Script changed nothing; count: 1");
+    }
+}
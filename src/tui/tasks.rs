@@ -0,0 +1,147 @@
+use anyhow::Result;
+use std::future::Future;
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+use super::theme::Icons;
+
+/// A long-running operation started from the TUI, such as a bulk delete
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub id: u64,
+    pub label: String,
+    pub done: usize,
+    /// Number of steps, or 0 when progress can't be measured
+    pub total: usize,
+    started: Instant,
+}
+
+impl Task {
+    /// Current spinner frame in `icons`
+    pub fn spinner(&self, icons: Icons) -> char {
+        icons.spinner((self.started.elapsed().as_millis() / 100) as usize)
+    }
+
+    /// Fraction of steps done (0.0 - 1.0), if the task has steps
+    pub fn ratio(&self) -> Option<f64> {
+        (self.total > 0).then(|| (self.done as f64 / self.total as f64).min(1.0))
+    }
+}
+
+/// Messages from background tasks back to the input loop
+#[derive(Debug)]
+enum TaskEvent {
+    Progress { id: u64, done: usize },
+    Finished { id: u64, result: Result<String, String> },
+}
+
+/// Handle a background task uses to report progress
+#[derive(Clone)]
+pub struct Progress {
+    id: u64,
+    sender: mpsc::UnboundedSender<TaskEvent>,
+}
+
+impl Progress {
+    /// Report how many steps are done
+    pub fn set(&self, done: usize) {
+        let _ = self.sender.send(TaskEvent::Progress { id: self.id, done });
+    }
+}
+
+/// Operations running on tokio tasks so the TUI keeps drawing and taking input
+pub struct Tasks {
+    next_id: u64,
+    running: Vec<Task>,
+    sender: mpsc::UnboundedSender<TaskEvent>,
+    receiver: mpsc::UnboundedReceiver<TaskEvent>,
+}
+
+impl Tasks {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            next_id: 1,
+            running: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Run `work` in the background. It returns a status message on success;
+    /// errors are shown as error messages.
+    pub fn spawn<F, Fut>(&mut self, label: impl Into<String>, total: usize, work: F)
+    where
+        F: FnOnce(Progress) -> Fut,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.running.push(Task {
+            id,
+            label: label.into(),
+            done: 0,
+            total,
+            started: Instant::now(),
+        });
+
+        let progress = Progress {
+            id,
+            sender: self.sender.clone(),
+        };
+        let sender = self.sender.clone();
+        let work = work(progress);
+        tokio::spawn(async move {
+            let result = work.await.map_err(|e| format!("{:#}", e));
+            let _ = sender.send(TaskEvent::Finished { id, result });
+        });
+    }
+
+    /// The task to show in the footer, if any is running
+    pub fn current(&self) -> Option<&Task> {
+        self.running.first()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// Apply progress updates and collect the results of finished tasks
+    pub fn poll(&mut self) -> Vec<Result<String, String>> {
+        let mut finished = Vec::new();
+        while let Ok(event) = self.receiver.try_recv() {
+            self.apply(event, &mut finished);
+        }
+        finished
+    }
+
+    /// Wait for every running task to finish, e.g. before quitting
+    pub async fn wait(&mut self) -> Vec<Result<String, String>> {
+        let mut finished = Vec::new();
+        while self.is_busy() {
+            let Some(event) = self.receiver.recv().await else { break };
+            self.apply(event, &mut finished);
+        }
+        finished
+    }
+
+    fn apply(&mut self, event: TaskEvent, finished: &mut Vec<Result<String, String>>) {
+        match event {
+            TaskEvent::Progress { id, done } => {
+                if let Some(task) = self.running.iter_mut().find(|t| t.id == id) {
+                    task.done = done;
+                }
+            }
+            TaskEvent::Finished { id, result } => {
+                self.running.retain(|t| t.id != id);
+                finished.push(result);
+            }
+        }
+    }
+}
+
+impl Default for Tasks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
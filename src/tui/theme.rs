@@ -0,0 +1,303 @@
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+    symbols::{border, line},
+    widgets::Block,
+};
+use std::fmt;
+use std::str::FromStr;
+
+/// Colour scheme of the TUI
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum Palette {
+    #[default]
+    Default,
+    /// Bright colours only, with black-on-white selection
+    HighContrast,
+    /// No colours; selection is shown in reverse video
+    NoColor,
+}
+
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "default" => Ok(Palette::Default),
+            "high-contrast" => Ok(Palette::HighContrast),
+            "no-color" => Ok(Palette::NoColor),
+            other => Err(format!("Unknown theme '{}' (expected default, high-contrast or no-color)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Palette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Palette::Default => write!(f, "default"),
+            Palette::HighContrast => write!(f, "high-contrast"),
+            Palette::NoColor => write!(f, "no-color"),
+        }
+    }
+}
+
+/// Borders drawn with ASCII only
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// Gauge and rule lines drawn with ASCII only
+const ASCII_LINE: line::Set = line::Set {
+    vertical: "|",
+    horizontal: "-",
+    top_right: "+",
+    top_left: "+",
+    bottom_right: "+",
+    bottom_left: "+",
+    vertical_left: "+",
+    vertical_right: "+",
+    horizontal_down: "+",
+    horizontal_up: "+",
+    cross: "+",
+};
+
+/// Spinner frames, one per 100ms while a task runs
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const ASCII_SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Glyphs for todo status, due dates and projects
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum Icons {
+    /// Plain ASCII letters and punctuation
+    Ascii,
+    #[default]
+    Unicode,
+    /// Nerd Font glyphs; needs a patched terminal font
+    NerdFont,
+}
+
+impl Icons {
+    /// Status column of a completed todo
+    pub fn done(&self) -> &'static str {
+        match self {
+            Icons::Ascii => "x",
+            Icons::Unicode => "✓",
+            Icons::NerdFont => "\u{f00c}",
+        }
+    }
+
+    /// Status column of the expanded todo
+    pub fn expanded(&self) -> &'static str {
+        match self {
+            Icons::Ascii => "v",
+            Icons::Unicode => "▼",
+            Icons::NerdFont => "\u{f078}",
+        }
+    }
+
+    /// Marks a due date, flagged when it has passed
+    pub fn due(&self, overdue: bool) -> &'static str {
+        match (self, overdue) {
+            (Icons::Ascii, false) => "due ",
+            (Icons::Ascii, true) => "! due ",
+            (Icons::Unicode, false) => "due ",
+            (Icons::Unicode, true) => "⚑ due ",
+            (Icons::NerdFont, false) => "\u{f073} ",
+            (Icons::NerdFont, true) => "\u{f071} ",
+        }
+    }
+
+    /// Prefix of a project name, archived or not
+    pub fn project(&self, archived: bool) -> &'static str {
+        match (self, archived) {
+            (Icons::NerdFont, false) => "\u{f07b} ",
+            (Icons::NerdFont, true) => "\u{f187} ",
+            _ => "",
+        }
+    }
+
+    /// Between a project name and one of its todos
+    pub fn separator(&self) -> &'static str {
+        match self {
+            Icons::Ascii => ">",
+            Icons::Unicode => "›",
+            Icons::NerdFont => "\u{f105}",
+        }
+    }
+
+    /// Between items on one line, e.g. a todo and its project
+    pub fn dot(&self) -> &'static str {
+        match self {
+            Icons::Ascii => "-",
+            Icons::Unicode | Icons::NerdFont => "·",
+        }
+    }
+
+    /// Before a project with sub-projects, folded away or shown
+    pub fn fold(&self, folded: bool) -> &'static str {
+        match (self, folded) {
+            (Icons::Ascii, true) => "+ ",
+            (Icons::Ascii, false) => "- ",
+            (_, true) => "▸ ",
+            (_, false) => "▾ ",
+        }
+    }
+
+    /// Where text was cut short
+    pub fn ellipsis(&self) -> &'static str {
+        match self {
+            Icons::Ascii => "...",
+            Icons::Unicode | Icons::NerdFont => "…",
+        }
+    }
+
+    /// Spinner frame for the `tick`th 100ms of a task
+    pub fn spinner(&self, tick: usize) -> char {
+        match self {
+            Icons::Ascii => ASCII_SPINNER[tick % ASCII_SPINNER.len()],
+            Icons::Unicode | Icons::NerdFont => SPINNER[tick % SPINNER.len()],
+        }
+    }
+
+    /// Footer key hints, written with ` | ` between them, in this icon set
+    pub fn hints(&self, hints: &str) -> String {
+        match self {
+            Icons::Ascii => hints.to_string(),
+            Icons::Unicode => hints.replace(" | ", " │ "),
+            Icons::NerdFont => hints.replace(" | ", " \u{e0b1} "),
+        }
+    }
+}
+
+impl FromStr for Icons {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ascii" => Ok(Icons::Ascii),
+            "unicode" => Ok(Icons::Unicode),
+            "nerd-font" => Ok(Icons::NerdFont),
+            other => Err(format!("Unknown icon set '{}' (expected ascii, unicode or nerd-font)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Icons {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Icons::Ascii => write!(f, "ascii"),
+            Icons::Unicode => write!(f, "unicode"),
+            Icons::NerdFont => write!(f, "nerd-font"),
+        }
+    }
+}
+
+/// How the TUI is drawn. Views are written against the default colours,
+/// which the theme rewrites in each finished frame; glyphs can't be swapped
+/// that way without touching the user's own text, so views take borders
+/// and lines from the theme and symbols from its icon set.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Theme {
+    pub palette: Palette,
+    /// Draw borders and lines with ASCII
+    pub ascii: bool,
+}
+
+impl Theme {
+    /// A block whose borders, when it has any, follow the theme
+    pub fn block(&self) -> Block<'static> {
+        let block = Block::default();
+        if self.ascii { block.border_set(ASCII_BORDER) } else { block }
+    }
+
+    /// Lines for gauges
+    pub fn line_set(&self) -> line::Set {
+        if self.ascii { ASCII_LINE } else { line::NORMAL }
+    }
+
+    /// Apply the palette to a rendered frame
+    pub fn apply(&self, buffer: &mut Buffer) {
+        if self.palette == Palette::Default {
+            return;
+        }
+        for cell in buffer.content.iter_mut() {
+            match self.palette {
+                Palette::Default => {}
+                Palette::HighContrast => {
+                    if cell.bg != Color::Reset {
+                        cell.set_fg(Color::Black).set_bg(Color::White);
+                        cell.modifier.insert(Modifier::BOLD);
+                    } else {
+                        cell.set_fg(bright(cell.fg));
+                    }
+                }
+                Palette::NoColor => {
+                    if cell.bg != Color::Reset {
+                        cell.modifier.insert(Modifier::REVERSED);
+                    }
+                    cell.set_fg(Color::Reset).set_bg(Color::Reset);
+                }
+            }
+        }
+    }
+}
+
+/// High-contrast version of a foreground colour
+fn bright(color: Color) -> Color {
+    match color {
+        Color::DarkGray | Color::Gray => Color::White,
+        Color::Red => Color::LightRed,
+        Color::Green => Color::LightGreen,
+        Color::Yellow => Color::LightYellow,
+        Color::Blue => Color::LightCyan,
+        Color::Magenta => Color::LightMagenta,
+        Color::Cyan => Color::LightCyan,
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, style::Style};
+
+    #[test]
+    fn test_no_color_ascii_frame() {
+        use ratatui::widgets::{Borders, Paragraph, Widget};
+
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 5, 3));
+        let theme = Theme { palette: Palette::NoColor, ascii: true };
+        Paragraph::new("✓a")
+            .style(Style::default().fg(Color::Green).bg(Color::DarkGray))
+            .block(theme.block().borders(Borders::ALL))
+            .render(buffer.area, &mut buffer);
+        theme.apply(&mut buffer);
+
+        // The border is ASCII, the text inside it is left as written
+        let cell = &buffer[(1, 1)];
+        assert_eq!((buffer[(0, 0)].symbol(), buffer[(1, 0)].symbol(), buffer[(0, 1)].symbol()), ("+", "-", "|"));
+        assert_eq!((cell.symbol(), buffer[(2, 1)].symbol()), ("✓", "a"));
+        assert_eq!((cell.fg, cell.bg), (Color::Reset, Color::Reset));
+        assert!(cell.modifier.contains(Modifier::REVERSED));
+        assert_eq!("High-Contrast".parse::<Palette>(), Ok(Palette::HighContrast));
+    }
+
+    #[test]
+    fn test_icon_sets() {
+        assert_eq!("Nerd-Font".parse::<Icons>(), Ok(Icons::NerdFont));
+        assert!("emoji".parse::<Icons>().is_err());
+        assert!(Icons::Ascii.due(true).is_ascii() && Icons::Ascii.hints("a: add | q: quit").is_ascii());
+        assert!([Icons::Ascii.dot(), Icons::Ascii.fold(true), Icons::Ascii.ellipsis()].iter().all(|s| s.is_ascii()));
+        assert!((0..10).all(|tick| Icons::Ascii.spinner(tick).is_ascii()));
+        assert_eq!(Icons::Unicode.hints("a: add | q: quit"), "a: add │ q: quit");
+        assert_eq!(Icons::Unicode.project(true), "");
+        assert_ne!(Icons::NerdFont.project(true), Icons::NerdFont.project(false));
+    }
+}
@@ -1,10 +1,13 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    widgets::{Borders, Clear, Paragraph, Wrap},
     Frame,
 };
 
+use crate::core::models::Todo;
+use crate::core::sort::SortField;
+
 use super::app::{App, InputMode, ViewMode};
 use super::views;
 
@@ -29,8 +32,16 @@ pub fn render(f: &mut Frame, app: &App) {
         InputMode::EditTodoDetails => render_todo_details_modal(f, app),
         InputMode::EditTodo => render_todo_modal(f, app),
         InputMode::EditProjectName => render_project_name_modal(f, app),
+        InputMode::PickSort => render_sort_modal(f, app),
+        InputMode::PickVersion => render_history_modal(f, app),
+        InputMode::FullText => render_full_text_modal(f, app),
         _ => {}
     }
+    if let Some(todo) = app.hovered_todo() {
+        render_preview_popup(f, app, todo);
+    }
+
+    app.theme().apply(f.buffer_mut());
 }
 // ...
 /// Render the project name edit modal
@@ -52,7 +63,7 @@ fn render_project_name_modal(f: &mut Frame, app: &App) {
     let modal = Paragraph::new(content)
         .style(Style::default().fg(Color::White))
         .block(
-            Block::default()
+            app.theme().block()
                 .borders(Borders::ALL)
                 .title("Edit Project Name")
                 .style(Style::default().fg(Color::Cyan)),
@@ -65,33 +76,93 @@ fn render_project_name_modal(f: &mut Frame, app: &App) {
 /// Render the header
 fn render_header(f: &mut Frame, area: Rect, app: &App) {
     let title = match &app.view_mode {
-        ViewMode::ProjectList => "Docket - Projects".to_string(),
+        ViewMode::ProjectList => format!("Docket - Projects  ({})", app.totals_summary()),
         ViewMode::TodoList(_) => {
             if let Some(project) = &app.current_project {
-                format!("Docket - {}", project.name)
+                let mut path = app.breadcrumb.clone();
+                path.push(project.name.clone());
+                format!("Docket - {}", path.join(&format!(" {} ", app.icons().separator())))
             } else {
                 "Docket - Todos".to_string()
             }
         }
-        ViewMode::ArchivedProjects => "Docket - Archived Projects".to_string(),
+        ViewMode::AllTodos => "Docket - All todos".to_string(),
+        ViewMode::ArchivedProjects => format!("Docket - Archived Projects  ({})", app.totals_summary()),
+        ViewMode::Review => match &app.current_project {
+            Some(project) => format!(
+                "Docket - Review ({}/{}): {}",
+                app.review_position + 1,
+                app.review_queue.len(),
+                project.name
+            ),
+            None => "Docket - Review".to_string(),
+        },
         ViewMode::Help => "Docket - Help".to_string(),
+        ViewMode::Messages => "Docket - Messages".to_string(),
+        ViewMode::Log => "Docket - Log".to_string(),
+        ViewMode::Calendar => format!(
+            "Docket - Calendar, week of {}",
+            app.calendar_week_start().format("%Y-%m-%d")
+        ),
+    };
+    // Name the profile, so work and personal lists aren't mixed up
+    let title = match &app.config.profile {
+        Some(profile) => title.replacen("Docket", &format!("Docket [{}]", profile), 1),
+        None => title,
+    };
+    let title = match &app.workspace {
+        Some(workspace) => title.replacen(" - ", &format!(" {} {} - ", app.icons().dot(), workspace.name), 1),
+        None => title,
+    };
+    let title = if app.loader.is_loading() {
+        format!("{}  (refreshing...)", title)
+    } else {
+        title
     };
 
     let header = Paragraph::new(title)
         .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-        .block(Block::default().borders(Borders::ALL));
+        .block(app.theme().block().borders(Borders::ALL));
 
     f.render_widget(header, area);
 }
 
 /// Render main content area
 fn render_content(f: &mut Frame, area: Rect, app: &App) {
+    let splittable = matches!(
+        app.view_mode,
+        ViewMode::ProjectList | ViewMode::ArchivedProjects | ViewMode::TodoList(_)
+    );
+    // A collapsed project pane only hides while the todos pane has focus
+    let collapsed = app.projects_collapsed && matches!(app.view_mode, ViewMode::TodoList(_));
+    if app.split_view && splittable && !collapsed {
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(app.project_pane_width),
+                Constraint::Percentage(100 - app.project_pane_width),
+            ])
+            .split(area);
+        views::render_project_list(f, panes[0], app);
+        if matches!(app.view_mode, ViewMode::TodoList(_)) {
+            views::render_todo_list(f, panes[1], app);
+        } else {
+            views::render_todo_preview(f, panes[1], app);
+        }
+        return;
+    }
+
     match &app.view_mode {
         ViewMode::ProjectList | ViewMode::ArchivedProjects => {
             views::render_project_list(f, area, app)
         }
-        ViewMode::TodoList(_) => views::render_todo_list(f, area, app),
-        ViewMode::Help => views::render_help(f, area),
+        ViewMode::TodoList(_) | ViewMode::AllTodos | ViewMode::Review => {
+            views::render_todo_list(f, area, app)
+        }
+        ViewMode::Help => views::render_help(f, area, app),
+        ViewMode::Messages => views::render_messages(f, area, app),
+        ViewMode::Log => views::render_log(f, area, app),
+        ViewMode::Calendar => views::render_calendar(f, area, app),
     }
 }
 
@@ -99,26 +170,62 @@ fn render_content(f: &mut Frame, area: Rect, app: &App) {
 fn render_footer(f: &mut Frame, area: Rect, app: &App) {
     let (content, style) = match &app.input_mode {
         InputMode::Normal => {
-            if let Some(msg) = &app.status_message {
-                (msg.clone(), Style::default().fg(Color::Yellow))
+            let pending = app.pending_keys();
+            if !pending.is_empty() {
+                (pending, Style::default().fg(Color::Yellow))
+            } else if let Some(task) = app.tasks.current() {
+                let progress = match task.ratio() {
+                    Some(ratio) => {
+                        let filled = (ratio * 10.0).round() as usize;
+                        format!(" [{}{}] {}/{}", "#".repeat(filled), " ".repeat(10 - filled), task.done, task.total)
+                    }
+                    None => String::new(),
+                };
+                (
+                    format!("{} {}...{}", task.spinner(app.icons()), task.label, progress),
+                    Style::default().fg(Color::Cyan),
+                )
+            } else if let Some(msg) = app.visible_status() {
+                (msg.text.clone(), Style::default().fg(views::severity_color(msg.severity)))
             } else {
                 let hints = match &app.view_mode {
                     ViewMode::ProjectList => {
-                        "j/k: navigate | Enter: open | a: add | d: delete | r: rename | A: archive | v: toggle archived | ?: help | q: quit"
+                        "j/k: navigate | Enter: open | a: add | dd: delete | r: rename | T: target date | A: archive | v: toggle archived | R: review | ?: help | q: quit"
+                    }
+                    ViewMode::TodoList(_) | ViewMode::AllTodos if app.checklist_focus().is_some() => {
+                        "j/k: checklist item | Space: check / uncheck | Esc: back to the list"
+                    }
+                    ViewMode::TodoList(_) | ViewMode::AllTodos if app.related_focus().is_some() => {
+                        "j/k: related todo | Enter: open in its project | d: unlink | Esc: back to the list"
                     }
                     ViewMode::TodoList(_) => {
                         if app.expanded_todo_id.is_some() {
-                            "Enter/Esc: collapse | e: edit details | Space: toggle | d: delete"
+                            "Enter/Esc: collapse | e: edit details | x: checklist | l: related | Space: toggle | dd: delete"
+                        } else {
+                            "j/k: navigate | Enter: expand | Space: toggle | a: add | dd: delete | r: rename | e: edit desc | t: estimate | D: due | Esc: back"
+                        }
+                    }
+                    ViewMode::AllTodos => {
+                        if app.expanded_todo_id.is_some() {
+                            "Enter/Esc: collapse | x: checklist | l: related | Space: toggle"
                         } else {
-                            "j/k: navigate | Enter: expand | Space: toggle | a: add | d: delete | r: rename | e: edit desc | Esc: back"
+                            "j/k: navigate | Enter: expand | Space: toggle | :filter: narrow | Esc: back"
                         }
                     }
                     ViewMode::ArchivedProjects => {
-                        "j/k: navigate | Enter: open | d: delete | A: unarchive | v: back to active | ?: help | q: quit"
+                        "j/k: navigate | Enter: open | dd: delete | A: unarchive | v: back to active | ?: help | q: quit"
+                    }
+                    ViewMode::Review => {
+                        "Space: done | s: defer | J/K: reprioritize | n/Enter: next project | Esc: stop review"
+                    }
+                    ViewMode::Help => "j/k: scroll | /: search | Esc/Enter: close help",
+                    ViewMode::Messages => "Press Esc or Enter to close messages",
+                    ViewMode::Log => "Press Esc or Enter to close the log",
+                    ViewMode::Calendar => {
+                        "h/l: day | j/k: todo | [/]: week | t: today | Enter: open | Space: toggle | D: due date | Esc: back"
                     }
-                    ViewMode::Help => "Press Esc or Enter to close help",
                 };
-                (hints.to_string(), Style::default().fg(Color::DarkGray))
+                (app.icons().hints(hints), Style::default().fg(Color::DarkGray))
             }
         }
         InputMode::AddProject => (
@@ -126,7 +233,11 @@ fn render_footer(f: &mut Frame, area: Rect, app: &App) {
             Style::default().fg(Color::Green),
         ),
         InputMode::AddTodo => (
-            format!("Add Todo: {}", app.input_buffer),
+            format!("Add Todo: {}  (Tab: add details)", app.input_buffer),
+            Style::default().fg(Color::Green),
+        ),
+        InputMode::AddTodoDetails => (
+            format!("Details for '{}': {}  (Tab: back to description)", app.new_todo_other, app.input_buffer),
             Style::default().fg(Color::Green),
         ),
         InputMode::EditDescription => (
@@ -145,15 +256,44 @@ fn render_footer(f: &mut Frame, area: Rect, app: &App) {
             format!("Edit Project Name: {}", app.input_buffer),
             Style::default().fg(Color::Green),
         ),
+        InputMode::EditEstimate => (
+            format!("Estimate (empty to clear): {}", app.input_buffer),
+            Style::default().fg(Color::Green),
+        ),
+        InputMode::EditTargetDate => (
+            format!("Target date (YYYY-MM-DD or +days, empty to clear): {}", app.input_buffer),
+            Style::default().fg(Color::Green),
+        ),
+        InputMode::EditDueDate => (
+            format!("Due date (YYYY-MM-DD or +days, empty to clear): {}", app.input_buffer),
+            Style::default().fg(Color::Green),
+        ),
+        InputMode::CloneProject => (
+            format!("Copy with open todos as: {}", app.input_buffer),
+            Style::default().fg(Color::Green),
+        ),
         InputMode::Command => (
             format!(":{}", app.input_buffer),
             Style::default().fg(Color::Yellow),
         ),
+        InputMode::SearchHelp => (
+            format!("/{}", app.input_buffer),
+            Style::default().fg(Color::Yellow),
+        ),
+        InputMode::PickSort => (
+            "j/k: field | Space: asc / desc / off | Backspace: clear | Enter: sort | Esc: cancel".to_string(),
+            Style::default().fg(Color::Yellow),
+        ),
+        InputMode::PickVersion => (
+            "j/k: version | Enter: restore | Esc: cancel".to_string(),
+            Style::default().fg(Color::Yellow),
+        ),
+        InputMode::FullText => ("Esc/Enter: close".to_string(), Style::default().fg(Color::Yellow)),
     };
 
     let footer = Paragraph::new(content)
         .style(style)
-        .block(Block::default().borders(Borders::ALL));
+        .block(app.theme().block().borders(Borders::ALL));
 
     f.render_widget(footer, area);
 }
@@ -179,6 +319,158 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
+/// Render the sort modal: every field, numbered in the order the keys apply
+fn render_sort_modal(f: &mut Frame, app: &App) {
+    let area = centered_rect(50, 50, f.area());
+
+    // Clear the area behind the modal
+    f.render_widget(Clear, area);
+
+    let mut content = String::new();
+    for (i, field) in SortField::ALL.iter().enumerate() {
+        let cursor = if i == app.sort_picker_index { ">" } else { " " };
+        let key = app.sort_picker.iter().position(|k| k.field == *field).map(|n| (n, app.sort_picker[n]));
+        let line = match key {
+            Some((n, key)) => {
+                let direction = if key.descending { "desc" } else { "asc" };
+                format!("{} {}. {} {}\n", cursor, n + 1, field.name(), direction)
+            }
+            None => format!("{}    {}\n", cursor, field.name()),
+        };
+        content.push_str(&line);
+    }
+    content.push_str("\n[Space] asc / desc / off  [Backspace] Clear\n[Enter] Sort  [Esc] Cancel");
+
+    let modal = Paragraph::new(content)
+        .style(Style::default().fg(Color::White))
+        .block(
+            app.theme().block()
+                .borders(Borders::ALL)
+                .title("Sort Todos")
+                .style(Style::default().fg(Color::Cyan)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(modal, area);
+}
+
+/// Render the history modal: when each earlier version was replaced, newest
+/// first, with the highlighted one's details underneath
+fn render_history_modal(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 60, f.area());
+
+    // Clear the area behind the modal
+    f.render_widget(Clear, area);
+
+    let mut content = String::new();
+    for (i, version) in app.history.iter().enumerate() {
+        let cursor = if i == app.history_index { ">" } else { " " };
+        let at = app.config.timezone.convert(version.replaced_at).format("%Y-%m-%d %H:%M");
+        content.push_str(&format!("{} {}  {}\n", cursor, at, version.description));
+    }
+    if let Some(version) = app.history.get(app.history_index) {
+        content.push('\n');
+        content.push_str(version.details.as_deref().unwrap_or("(no details)"));
+        content.push('\n');
+    }
+    content.push_str("\n[Enter] Restore  [Esc] Cancel");
+
+    let modal = Paragraph::new(content)
+        .style(Style::default().fg(Color::White))
+        .block(
+            app.theme().block()
+                .borders(Borders::ALL)
+                .title("Todo History")
+                .style(Style::default().fg(Color::Cyan)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(modal, area);
+}
+
+/// Render the selected todo's whole description, wrapped, with its details
+fn render_full_text_modal(f: &mut Frame, app: &App) {
+    let Some(todo) = app.todos.get(app.selected_index) else {
+        return;
+    };
+    let area = centered_rect(70, 50, f.area());
+
+    // Clear the area behind the modal
+    f.render_widget(Clear, area);
+
+    let mut content = todo_card(app, todo, None);
+    content.push_str("\n\n[Esc] Close");
+
+    let modal = Paragraph::new(content)
+        .style(Style::default().fg(Color::White))
+        .block(
+            app.theme().block()
+                .borders(Borders::ALL)
+                .title(app.project_name(todo.project_id).to_string())
+                .style(Style::default().fg(Color::Cyan)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(modal, area);
+}
+
+/// Render the preview that pops up once a todo has stayed selected for a
+/// moment; it goes with the next key press
+fn render_preview_popup(f: &mut Frame, app: &App, todo: &Todo) {
+    let area = centered_rect(60, 40, f.area());
+
+    // Clear the area behind the popup
+    f.render_widget(Clear, area);
+
+    let popup = Paragraph::new(todo_card(app, todo, Some(PREVIEW_DETAIL_LINES)))
+        .style(Style::default().fg(Color::White))
+        .block(
+            app.theme().block()
+                .borders(Borders::ALL)
+                .title(app.project_name(todo.project_id).to_string())
+                .style(Style::default().fg(Color::DarkGray)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(popup, area);
+}
+
+/// Lines of details the preview popup shows before cutting them off
+const PREVIEW_DETAIL_LINES: usize = 4;
+
+/// A todo's description, tags and dates, then its details (only the first
+/// `detail_lines` of them, if given)
+fn todo_card(app: &App, todo: &Todo, detail_lines: Option<usize>) -> String {
+    let zone = app.config.timezone;
+    let mut content = format!("{}\n\n", todo.description);
+    let tags = todo.tags();
+    if !tags.is_empty() {
+        let tags: Vec<String> = tags.iter().map(|tag| format!("#{}", tag)).collect();
+        content.push_str(&format!("{}\n", tags.join(" ")));
+    }
+    let mut dates = vec![format!("Created {}", zone.convert(todo.created_at).format("%Y-%m-%d"))];
+    if let Some(due) = todo.due_date {
+        dates.push(format!("due {}", due.format("%Y-%m-%d")));
+    }
+    if let Some(completed_at) = todo.completed_at {
+        dates.push(format!("done {}", zone.convert(completed_at).format("%Y-%m-%d")));
+    }
+    content.push_str(&dates.join(&format!(" {} ", app.icons().dot())));
+
+    if let Some(details) = todo.details.as_deref().filter(|d| !d.trim().is_empty()) {
+        content.push_str("\n\n");
+        match detail_lines {
+            Some(limit) if details.lines().count() > limit => {
+                content.push_str(&details.lines().take(limit).collect::<Vec<_>>().join("\n"));
+                content.push('\n');
+                content.push_str(app.icons().ellipsis());
+            }
+            _ => content.push_str(details),
+        }
+    }
+    content
+}
+
 /// Render the description edit modal
 fn render_description_modal(f: &mut Frame, app: &App) {
     let area = centered_rect(60, 30, f.area());
@@ -198,7 +490,7 @@ fn render_description_modal(f: &mut Frame, app: &App) {
     let modal = Paragraph::new(content)
         .style(Style::default().fg(Color::White))
         .block(
-            Block::default()
+            app.theme().block()
                 .borders(Borders::ALL)
                 .title("Edit Project Description")
                 .style(Style::default().fg(Color::Cyan)),
@@ -227,7 +519,7 @@ fn render_todo_details_modal(f: &mut Frame, app: &App) {
     let modal = Paragraph::new(content)
         .style(Style::default().fg(Color::White))
         .block(
-            Block::default()
+            app.theme().block()
                 .borders(Borders::ALL)
                 .title("Edit Todo Details")
                 .style(Style::default().fg(Color::Cyan)),
@@ -256,7 +548,7 @@ fn render_todo_modal(f: &mut Frame, app: &App) {
     let modal = Paragraph::new(content)
         .style(Style::default().fg(Color::White))
         .block(
-            Block::default()
+            app.theme().block()
                 .borders(Borders::ALL)
                 .title("Edit Todo Description")
                 .style(Style::default().fg(Color::Cyan)),
@@ -1,63 +1,201 @@
+use chrono::{Duration, Utc};
+use std::rc::Rc;
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Flex, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
-    text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+    text::{Line, Span, Text},
+    widgets::{Borders, Cell, LineGauge, Paragraph, Row, Table, Wrap},
     Frame,
 };
 
-use super::app::App;
+use super::app::{App, Severity, ViewMode};
+use super::columns::{Column, ProjectColumn, TodoColumn};
+use super::fit;
+use super::keymap;
+use super::theme::Icons;
+use crate::core::models::{ProjectWithStats, Todo, TodoAge};
+use crate::core::references;
+use crate::core::time::DisplayZone;
+use crate::version;
 
 /// Render the project list table
 pub fn render_project_list(f: &mut Frame, area: Rect, app: &App) {
-    let header_cells = ["Name", "Active", "Completed", "Total"]
+    // Optional columns only appear once some project uses the feature
+    let show_effort = app.projects.iter().any(|p| p.has_estimates());
+    let show_target = app.projects.iter().any(|p| p.project.target_date.is_some());
+    let columns: Vec<ProjectColumn> = app
+        .project_table_columns()
         .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
+        .copied()
+        .filter(|column| match column {
+            ProjectColumn::Effort => show_effort,
+            ProjectColumn::Target => show_target,
+            _ => true,
+        })
+        .collect();
+
+    let widths: Vec<Constraint> = columns.iter().map(Column::width).collect();
+    let name_width = columns
+        .iter()
+        .position(|c| *c == ProjectColumn::Name)
+        .map(|i| column_areas(&widths, area)[i].width as usize)
+        .unwrap_or_default();
+    let header_cells = columns
+        .iter()
+        .map(|c| Cell::from(c.header()).style(Style::default().fg(Color::Yellow)));
     let header = Row::new(header_cells)
         .style(Style::default())
         .height(1)
         .bottom_margin(1);
 
-    let rows = app.projects.iter().enumerate().map(|(i, project)| {
-        let style = if i == app.selected_index {
+    // In the split view the todos pane may have focus
+    let focused = !matches!(app.view_mode, ViewMode::TodoList(_));
+    let selected = if focused { app.selected_index } else { app.project_index };
+    let row_style = |row: usize| {
+        if row == selected && focused {
             Style::default()
                 .bg(Color::DarkGray)
                 .add_modifier(Modifier::BOLD)
+        } else if row == selected {
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
         } else {
             Style::default()
-        };
+        }
+    };
+
+    // The active list opens with All todos, which has no stats beyond its open count
+    let offset = app.project_row_offset();
+    let mut rows = Vec::new();
+    if offset == 1 {
+        let open = app.listed_open_todos();
+        let cells = columns.iter().map(|column| match column {
+            ProjectColumn::Name => {
+                Cell::from(Span::styled("All todos", Style::default().add_modifier(Modifier::ITALIC)))
+            }
+            ProjectColumn::Active => Cell::from(open.to_string()),
+            _ => Cell::from(""),
+        });
+        rows.push(Row::new(cells).style(row_style(0)).height(1));
+    }
+
+    let icons = app.icons();
+    rows.extend(app.projects.iter().enumerate().map(|(i, project)| {
+        let style = row_style(i + offset);
 
-        let name = if project.project.is_archived() {
-            format!("{} [ARCHIVED]", project.project.name)
+        // Sub-projects are indented under their parent, whose counts include theirs
+        let tree = app.project_tree.get(i).copied().unwrap_or_default();
+        let fold = match (tree.has_children, app.collapsed_projects.contains(&project.project.id)) {
+            (false, _) => "",
+            (true, folded) => icons.fold(folded),
+        };
+        let archived = project.project.is_archived();
+        let label = format!("{}{}{}{}", "  ".repeat(tree.depth), fold, icons.project(archived), project.project.name);
+        let name = if archived {
+            Line::from(format!("{} [ARCHIVED]", label))
+        } else if project.is_archive_candidate(app.config.stale_after_days) {
+            Line::from(vec![
+                Span::raw(label),
+                Span::styled(format!("  done {} archive?", icons.dot()), Style::default().fg(Color::DarkGray)),
+            ])
         } else {
-            project.project.name.clone()
+            Line::from(label)
         };
+        let name = fit::truncate(name, name_width, icons.ellipsis());
 
-        let cells = vec![
-            Cell::from(name),
-            Cell::from(project.active_todos().to_string()),
-            Cell::from(project.completed_todos.to_string()),
-            Cell::from(project.total_todos.to_string()),
-        ];
-        Row::new(cells).style(style).height(1)
-    });
+        let zone = app.config.timezone;
+        let cells = columns.iter().map(|column| match column {
+            ProjectColumn::Name => Cell::from(name.clone()),
+            ProjectColumn::Active => Cell::from(tree.active_todos().to_string()),
+            ProjectColumn::Completed => Cell::from(tree.completed_todos.to_string()),
+            ProjectColumn::Total => Cell::from(tree.total_todos.to_string()),
+            ProjectColumn::Created => {
+                Cell::from(zone.convert(project.project.created_at).format("%Y-%m-%d").to_string())
+            }
+            ProjectColumn::Age => Cell::from(format!("{}d", (Utc::now() - project.project.created_at).num_days())),
+            ProjectColumn::Effort => Cell::from(if project.has_estimates() {
+                format!("{}/{}", project.remaining_estimate, project.total_estimate)
+            } else {
+                String::new()
+            }),
+            ProjectColumn::Target => target_cell(project, zone),
+        });
+        Row::new(cells.collect::<Vec<_>>()).style(style).height(1)
+    }));
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(app.theme().block().borders(Borders::ALL).title("Projects"))
+        .row_highlight_style(Style::default().add_modifier(Modifier::BOLD));
+
+    if focused {
+        app.visible_rows.set(table_rows(area));
+    }
+    f.render_widget(table, area);
+}
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Percentage(50),
-            Constraint::Percentage(15),
-            Constraint::Percentage(20),
-            Constraint::Percentage(15),
-        ],
-    )
-    .header(header)
-    .block(Block::default().borders(Borders::ALL).title("Projects"))
-    .row_highlight_style(Style::default().add_modifier(Modifier::BOLD));
+/// Render the highlighted project's todos, read-only, beside the project list
+pub fn render_todo_preview(f: &mut Frame, area: Rect, app: &App) {
+    let title = match app.selected_project() {
+        Some(p) => format!("{} (Tab to focus)", p.project.name),
+        None if app.project_row_offset() == 1 => "All todos (Enter to open)".to_string(),
+        None => String::new(),
+    };
 
+    let icons = app.icons();
+    let rows = app.preview_todos.iter().map(|todo| {
+        let (status, style) = if todo.is_completed() {
+            (
+                icons.done(),
+                Style::default().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT),
+            )
+        } else {
+            (" ", Style::default())
+        };
+        Row::new(vec![Cell::from(status), Cell::from(todo.description.clone())]).style(style)
+    });
+
+    let table = Table::new(rows, [Constraint::Length(3), Constraint::Min(0)])
+        .block(app.theme().block().borders(Borders::ALL).title(title));
     f.render_widget(table, area);
 }
 
+/// Where a bordered table puts each column, spaced the way `Table` does
+fn column_areas(widths: &[Constraint], area: Rect) -> Rc<[Rect]> {
+    Layout::horizontal(widths.iter().copied())
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(area.inner(Margin::new(1, 1)))
+}
+
+/// Body rows that fit in a bordered table with a header and header margin
+fn table_rows(area: Rect) -> usize {
+    area.height.saturating_sub(4) as usize
+}
+
+/// Days-remaining cell for a project's target date, colored by urgency
+fn target_cell(project: &ProjectWithStats, zone: DisplayZone) -> Cell<'static> {
+    let Some(days) = project.project.days_remaining(zone) else {
+        return Cell::from("");
+    };
+
+    let text = match days {
+        d if d < 0 => format!("{}d overdue", -d),
+        0 => "due today".to_string(),
+        d => format!("{}d left", d),
+    };
+    let color = if project.active_todos() == 0 {
+        Color::DarkGray
+    } else if days < 0 {
+        Color::Red
+    } else if project.is_at_risk(zone) {
+        Color::Yellow
+    } else {
+        Color::Green
+    };
+
+    Cell::from(Span::styled(text, Style::default().fg(color)))
+}
+
 /// Render the todo list table
 pub fn render_todo_list(f: &mut Frame, area: Rect, app: &App) {
     // Check if we have a description to display
@@ -67,18 +205,32 @@ pub fn render_todo_list(f: &mut Frame, area: Rect, app: &App) {
         .map(|d| !d.is_empty())
         .unwrap_or(false);
 
+    // Burndown is only shown for projects that use estimates
+    let estimated_stats = app.current_stats.as_ref().filter(|s| s.has_estimates());
+
     // Check if we have an expanded todo with details
     let expanded_todo = app.get_expanded_todo();
     let has_expanded_details = expanded_todo.is_some();
 
+    // Full-screen detail view replaces the table
+    if app.detail_fullscreen
+        && let Some(todo) = expanded_todo
+    {
+        render_todo_details(f, area, app, todo);
+        return;
+    }
+
     // Build layout constraints
     let mut constraints = Vec::new();
     if has_description {
         constraints.push(Constraint::Length(3));  // Description area
     }
+    if estimated_stats.is_some() {
+        constraints.push(Constraint::Length(1));  // Burndown line
+    }
     constraints.push(Constraint::Min(0));  // Table area
     if has_expanded_details {
-        constraints.push(Constraint::Length(5));  // Details area
+        constraints.push(Constraint::Length(app.detail_height));  // Details area
     }
 
     let chunks = Layout::default()
@@ -99,19 +251,51 @@ pub fn render_todo_list(f: &mut Frame, area: Rect, app: &App) {
         chunk_idx += 1;
     }
 
+    // Render remaining-effort burndown line
+    if let Some(stats) = estimated_stats {
+        let gauge = LineGauge::default()
+            .line_set(app.theme().line_set())
+            .filled_style(Style::default().fg(Color::Green))
+            .unfilled_style(Style::default().fg(Color::DarkGray))
+            .label(format!(
+                "Effort: {} of {} left",
+                stats.remaining_estimate, stats.total_estimate
+            ))
+            .ratio(stats.burndown_ratio());
+        f.render_widget(gauge, chunks[chunk_idx]);
+        chunk_idx += 1;
+    }
+
     let table_area = chunks[chunk_idx];
     chunk_idx += 1;
 
-    // Render todo table
-    let header_cells = ["Status", "Description", "Completed"]
+    // Render todo table; only All todos names each todo's project
+    let show_project = app.view_mode == ViewMode::AllTodos;
+    let columns: Vec<TodoColumn> = app
+        .todo_table_columns()
         .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
+        .copied()
+        .filter(|column| *column != TodoColumn::Project || show_project)
+        .collect();
+    // A badge in the description gives way to its own column
+    let badge = |column: TodoColumn| !columns.contains(&column);
+    let header_cells = columns
+        .iter()
+        .map(|c| Cell::from(c.header()).style(Style::default().fg(Color::Yellow)));
     let header = Row::new(header_cells)
         .style(Style::default())
         .height(1)
         .bottom_margin(1);
 
-    let rows = app.todos.iter().enumerate().map(|(i, todo)| {
+    // Long descriptions are cut to, or wrapped at, the width the table gives them
+    let widths: Vec<Constraint> = columns.iter().map(Column::width).collect();
+    let description_width = columns
+        .iter()
+        .position(|c| *c == TodoColumn::Description)
+        .map(|i| column_areas(&widths, table_area)[i].width as usize)
+        .unwrap_or_default();
+
+    let (rows, heights): (Vec<Row>, Vec<usize>) = app.todos.iter().enumerate().map(|(i, todo)| {
         let is_expanded = app.expanded_todo_id == Some(todo.id);
         let style = if i == app.selected_index {
             Style::default()
@@ -126,93 +310,432 @@ pub fn render_todo_list(f: &mut Frame, area: Rect, app: &App) {
         };
 
         let status = if todo.is_completed() {
-            "✓"
+            app.icons().done()
         } else if is_expanded {
-            "▼"
+            app.icons().expanded()
         } else {
             " "
         };
 
-        let cells = vec![
-            Cell::from(status),
-            Cell::from(todo.description.clone()),
-            Cell::from(todo.completion_status()),
-        ];
-        Row::new(cells).style(style).height(1)
-    });
+        let mut description = vec![Span::raw(todo.description.clone())];
+        if let Some((checked, total)) = todo.checklist_progress() {
+            let color = if checked == total { Color::Green } else { Color::DarkGray };
+            description.push(Span::styled(format!("  [{}/{}]", checked, total), Style::default().fg(color)));
+        }
+        if let Some(estimate) = todo.estimate.filter(|_| badge(TodoColumn::Estimate)) {
+            description.push(Span::styled(format!("  ~{}", estimate), Style::default().fg(Color::DarkGray)));
+        }
+        // Detailed rows move the due date to the second line
+        let overdue = todo.is_overdue(app.config.timezone);
+        let due_style = Style::default().fg(if overdue { Color::Red } else { Color::DarkGray });
+        let due = todo.due_date.filter(|_| badge(TodoColumn::Due)).map(|due| {
+            Span::styled(format!("{}{}", app.icons().due(overdue), due.format("%b %d")), due_style)
+        });
+        if !app.detailed_rows
+            && let Some(due) = due.clone()
+        {
+            description.push(Span::raw("  "));
+            description.push(due);
+        }
+        // Long-open todos get a badge, so the ones being passed over stand out
+        let age_color = match todo.age() {
+            TodoAge::Fresh => None,
+            TodoAge::Aging => Some(Color::Yellow),
+            TodoAge::Stale => Some(Color::Magenta),
+        };
+        if let Some(color) = age_color.filter(|_| badge(TodoColumn::Age)) {
+            description.push(Span::styled(format!("  {}d old", todo.age_days()), Style::default().fg(color)));
+        }
+        let description = Line::from(description);
+        let mut lines = if app.wrap_rows {
+            fit::wrap(description, description_width)
+        } else {
+            vec![fit::truncate(description, description_width, app.icons().ellipsis())]
+        };
+        if app.detailed_rows {
+            let detail = detail_line(todo, badge(TodoColumn::Tags), due, app.icons());
+            lines.push(fit::truncate(detail, description_width, app.icons().ellipsis()));
+        }
+        let height = lines.len();
+        let description = Text::from(lines);
+
+        let zone = app.config.timezone;
+        let cells: Vec<Cell> = columns
+            .iter()
+            .map(|column| match column {
+                TodoColumn::Status => Cell::from(status),
+                TodoColumn::Description => Cell::from(description.clone()),
+                TodoColumn::Project => Cell::from(app.project_name(todo.project_id).to_string()),
+                TodoColumn::Created => Cell::from(zone.convert(todo.created_at).format("%Y-%m-%d").to_string()),
+                TodoColumn::Due => match todo.due_date {
+                    Some(due) => Cell::from(Span::styled(due.format("%Y-%m-%d").to_string(), due_style)),
+                    None => Cell::from(""),
+                },
+                // Completed todos drop out of the manual order
+                TodoColumn::Priority if todo.is_completed() => Cell::from(""),
+                TodoColumn::Priority => Cell::from(todo.position.to_string()),
+                TodoColumn::Tags => {
+                    let tags: Vec<String> = todo.tags().iter().map(|tag| format!("#{}", tag)).collect();
+                    Cell::from(Span::styled(tags.join(" "), Style::default().fg(Color::Cyan)))
+                }
+                TodoColumn::Age => {
+                    let style = age_color.map(|color| Style::default().fg(color)).unwrap_or_default();
+                    Cell::from(Span::styled(format!("{}d", todo.age_days()), style))
+                }
+                TodoColumn::Spent => Cell::from(time_spent(todo)),
+                TodoColumn::Estimate => Cell::from(todo.estimate.map(|e| e.to_string()).unwrap_or_default()),
+                TodoColumn::Completed => Cell::from(todo.completion_status(zone)),
+            })
+            .collect();
+        (Row::new(cells).style(style).height(height as u16), height)
+    }).unzip();
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(8),
-            Constraint::Percentage(60),
-            Constraint::Percentage(30),
-        ],
-    )
-    .header(header)
-    .block(Block::default().borders(Borders::ALL).title("Todos"))
-    .row_highlight_style(Style::default().add_modifier(Modifier::BOLD));
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(app.theme().block().borders(Borders::ALL).title(todo_list_title(app)))
+        .row_highlight_style(Style::default().add_modifier(Modifier::BOLD));
 
+    // Rows that fit from the top, for paging and H / M / L
+    let space = table_rows(table_area);
+    let mut used = 0;
+    app.visible_rows.set(heights.iter().take_while(|h| {
+        used += **h;
+        used <= space
+    }).count());
     f.render_widget(table, table_area);
 
     // Render expanded todo details if present
-    if has_expanded_details {
-        let details_area = chunks[chunk_idx];
-        if let Some(todo) = expanded_todo {
-            let details_text = todo.details.as_deref().unwrap_or("[no details - press 'e' to add]");
-            let details_lines = vec![
-                Line::from(Span::styled(
-                    format!("Details for: {}", todo.description),
-                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
-                )),
-                Line::from(""),
-                Line::from(details_text),
-            ];
-            let details_widget = Paragraph::new(details_lines)
-                .block(Block::default().borders(Borders::ALL))
-                .wrap(Wrap { trim: true });
-            f.render_widget(details_widget, details_area);
-        }
+    if let Some(todo) = expanded_todo {
+        render_todo_details(f, chunks[chunk_idx], app, todo);
+    }
+}
+
+/// Time from creating a todo to completing it, in its largest whole unit
+fn time_spent(todo: &Todo) -> String {
+    let Some(completed_at) = todo.completed_at else {
+        return String::new();
+    };
+    let spent = completed_at - todo.created_at;
+    if spent.num_days() > 0 {
+        format!("{}d", spent.num_days())
+    } else if spent.num_hours() > 0 {
+        format!("{}h", spent.num_hours())
+    } else {
+        format!("{}m", spent.num_minutes().max(0))
+    }
+}
+
+/// Second line of a detailed todo row: tags (unless they have a column), due
+/// date and the first line of the details
+fn detail_line(todo: &Todo, show_tags: bool, due: Option<Span<'static>>, icons: Icons) -> Line<'static> {
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut spans = Vec::new();
+    let tags = todo.tags();
+    if show_tags && !tags.is_empty() {
+        let tags: Vec<String> = tags.iter().map(|tag| format!("#{}", tag)).collect();
+        spans.push(Span::styled(tags.join(" "), Style::default().fg(Color::Cyan)));
+    }
+    spans.extend(due);
+    let snippet = todo.details.as_deref().and_then(|d| d.lines().map(str::trim).find(|l| !l.is_empty()));
+    if let Some(snippet) = snippet {
+        spans.push(Span::styled(snippet.to_string(), dim));
+    }
+    // Spaced out the way the first line's badges are
+    let mut line = Vec::new();
+    for (i, span) in spans.into_iter().enumerate() {
+        line.push(Span::raw(if i == 0 { "  ".to_string() } else { format!("  {}  ", icons.dot()) }));
+        line.push(span);
     }
+    Line::from(line)
 }
 
-/// Render the help screen
-pub fn render_help(f: &mut Frame, area: Rect) {
-    let help_text = vec![
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("Docket", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-            Span::raw(" - Project Todo Manager"),
-        ]),
-        Line::from(""),
-        Line::from(Span::styled("Navigation:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-        Line::from("  j / ↓        Move down"),
-        Line::from("  k / ↑        Move up"),
-        Line::from("  Enter        Open project / Expand todo / Close help"),
-        Line::from("  Esc          Collapse todo / Back to project list"),
-        Line::from(""),
-        Line::from(Span::styled("Actions:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-        Line::from("  a            Add new project/todo"),
-        Line::from("  d            Delete selected item"),
-        Line::from("  Space        Toggle todo completion (todo view only)"),
-        Line::from("  r            Rename selected item (project/todo)"),
-        Line::from("  e            Edit details (expanded) / Edit project desc"),
-        Line::from("  A            Archive/Unarchive project"),
-        Line::from("  v            Toggle between active and archived projects"),
-        Line::from("  c            Toggle show/hide completed todos"),
-        Line::from(""),
-        Line::from(Span::styled("Other:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-        Line::from("  :            Command mode"),
-        Line::from("  ?            Show this help"),
-        Line::from("  q            Quit"),
-        Line::from("  Ctrl+C       Quit"),
-        Line::from(""),
-        Line::from(Span::styled("Press Esc or Enter to close", Style::default().fg(Color::Green))),
+/// Render a todo's details, scrolled by `app.detail_scroll`
+fn render_todo_details(f: &mut Frame, area: Rect, app: &App, todo: &Todo) {
+    let details_text = todo.details.as_deref().unwrap_or("[no details - press 'e' to add]");
+    let mut details_lines = vec![
+        Line::from(Span::styled(
+            format!("Details for {}: {}", app.short_id(todo), todo.description),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        )),
     ];
+    if let Some(link) = ticket_line(app, todo) {
+        details_lines.push(link);
+    }
+    if app.related_for == Some(todo.id) {
+        let selected = app.related_focus();
+        for (i, (label, other, project)) in (0..app.related_rows()).filter_map(|i| app.related_row(i)).enumerate() {
+            details_lines.push(related_line(label, other, project, selected == Some(i), app.icons()));
+        }
+    }
+    details_lines.push(Line::from(""));
+    // The selected checklist item is highlighted while the checklist has focus
+    let selected_line = app
+        .checklist_focus()
+        .and_then(|index| todo.checklist().get(index).map(|item| item.line));
+    details_lines.extend(details_text.lines().enumerate().map(|(i, line)| {
+        if selected_line == Some(i) {
+            Line::from(Span::styled(line, Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD)))
+        } else {
+            with_mentions(line)
+        }
+    }));
+
+    let dot = app.icons().dot();
+    let hint = if app.checklist_focus().is_some() {
+        format!(" j/k item {dot} Space check {dot} Esc done ")
+    } else if app.related_focus().is_some() {
+        format!(" j/k todo {dot} Enter open {dot} d unlink {dot} Esc done ")
+    } else if app.detail_fullscreen {
+        format!(" Ctrl+d/u scroll {dot} z back ")
+    } else {
+        format!(" Ctrl+d/u scroll {dot} +/- resize {dot} z full screen ")
+    };
+    let details_widget = Paragraph::new(details_lines)
+        .block(
+            app.theme().block()
+                .borders(Borders::ALL)
+                .title_bottom(Line::from(hint).right_aligned()),
+        )
+        .wrap(Wrap { trim: true })
+        .scroll((app.detail_scroll, 0));
+    f.render_widget(details_widget, area);
+}
+
+/// `See also: Borrow ladder · Work`, struck through once the todo is done
+fn related_line(label: &str, todo: &Todo, project: &str, selected: bool, icons: Icons) -> Line<'static> {
+    let mut style = Style::default();
+    if todo.is_completed() {
+        style = style.add_modifier(Modifier::CROSSED_OUT);
+    }
+    if selected {
+        style = style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+    }
+    Line::from(vec![
+        Span::styled(format!("{}: ", label), Style::default().fg(Color::DarkGray)),
+        Span::styled(todo.description.clone(), style),
+        Span::styled(format!(" {} {}", icons.dot(), project), Style::default().fg(Color::DarkGray)),
+    ])
+}
+
+/// A line of details with its `#123` and `docket://todo/123` mentions
+/// shown as links
+fn with_mentions(line: &str) -> Line<'_> {
+    let link = Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED);
+    let mut spans = Vec::new();
+    let mut at = 0;
+    for mention in references::find(line) {
+        spans.push(Span::raw(&line[at..mention.start]));
+        spans.push(Span::styled(&line[mention.start..mention.end], link));
+        at = mention.end;
+    }
+    spans.push(Span::raw(&line[at..]));
+    Line::from(spans)
+}
+
+/// `Link: OPS-42 · In Progress` for a todo with an external reference; the
+/// status is shown once fetched from JIRA
+fn ticket_line(app: &App, todo: &Todo) -> Option<Line<'static>> {
+    let reference = todo.external_ref.clone()?;
+    let mut spans = vec![Span::styled("Link: ", Style::default().fg(Color::DarkGray)), Span::raw(reference)];
+    if let Some(key) = app.ticket_key(todo) {
+        let status = match app.tickets.get(&key).map(|t| &t.status) {
+            Some(Ok(status)) => Span::styled(status.clone(), Style::default().fg(Color::Green)),
+            Some(Err(e)) => Span::styled(e.clone(), Style::default().fg(Color::Red)),
+            None => Span::styled("fetching status...", Style::default().fg(Color::DarkGray)),
+        };
+        spans.push(Span::raw(format!(" {} ", app.icons().dot())));
+        spans.push(status);
+    }
+    Some(Line::from(spans))
+}
 
-    let help = Paragraph::new(help_text)
-        .block(Block::default().borders(Borders::ALL).title("Help"))
-        .style(Style::default());
+/// Title for the todo table, noting any active filter or non-default sort
+fn todo_list_title(app: &App) -> String {
+    let mut notes = Vec::new();
+    if let Some(filter) = &app.todo_filter {
+        notes.push(format!("filter: {}", filter));
+    }
+    if !app.todo_sort.keys().is_empty() {
+        notes.push(format!("sort: {}", app.todo_sort));
+    }
+    let title = if app.view_mode == ViewMode::AllTodos { "All todos" } else { "Todos" };
+    if notes.is_empty() {
+        title.to_string()
+    } else {
+        format!("{} ({})", title, notes.join(", "))
+    }
+}
+
+/// Render the help screen: the bindings for the view it was opened from,
+/// or those matching a search
+pub fn render_help(f: &mut Frame, area: Rect, app: &App) {
+    let heading = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let sections = app.help_sections();
+    let mut lines = Vec::new();
+    for section in &sections {
+        lines.push(Line::from(Span::styled(format!("{}:", section.title), heading)));
+        for binding in &section.bindings {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<width$}", binding.keys, width = keymap::KEYS_WIDTH), Style::default().fg(Color::Cyan)),
+                Span::raw(binding.description),
+            ]));
+        }
+        lines.push(Line::from(""));
+    }
+    if sections.is_empty() {
+        lines.push(Line::from(Span::styled("No matching keys", Style::default().fg(Color::DarkGray))));
+    }
+
+    let title = if app.help_query.is_empty() {
+        " Help ".to_string()
+    } else {
+        format!(" Help: /{} ", app.help_query)
+    };
+    app.visible_rows.set(area.height.saturating_sub(2) as usize);
+    let keys = format!(" j/k scroll {0} / search {0} Esc close ", app.icons().dot());
+    let help = Paragraph::new(lines)
+        .block(
+            app.theme().block()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(Line::from(format!(" docket {} ", version::LONG_VERSION)).left_aligned())
+                .title_bottom(Line::from(keys).right_aligned()),
+        )
+        .scroll((app.help_scroll, 0));
 
     f.render_widget(help, area);
 }
+
+/// Footer color for a status message
+pub fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Info => Color::Cyan,
+        Severity::Success => Color::Green,
+        Severity::Warning => Color::Yellow,
+        Severity::Error => Color::Red,
+    }
+}
+
+/// Render the status message history, newest at the bottom
+pub fn render_messages(f: &mut Frame, area: Rect, app: &App) {
+    let lines: Vec<Line> = if app.messages.is_empty() {
+        vec![Line::from(Span::styled("No messages yet", Style::default().fg(Color::DarkGray)))]
+    } else {
+        app.messages
+            .iter()
+            .map(|message| {
+                Line::from(vec![
+                    Span::styled(
+                        app.config.timezone.convert(message.at).format("%H:%M:%S  ").to_string(),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(
+                        message.text.clone(),
+                        Style::default().fg(severity_color(message.severity)),
+                    ),
+                ])
+            })
+            .collect()
+    };
+
+    // Keep the newest messages in view
+    let visible = area.height.saturating_sub(2) as usize;
+    let scroll = lines.len().saturating_sub(visible) as u16;
+    let messages = Paragraph::new(lines)
+        .block(app.theme().block().borders(Borders::ALL).title("Messages"))
+        .scroll((scroll, 0));
+    f.render_widget(messages, area);
+}
+
+/// Render the end of the log file, newest at the bottom
+pub fn render_log(f: &mut Frame, area: Rect, app: &App) {
+    let lines: Vec<Line> = if app.log_lines.is_empty() {
+        vec![Line::from(Span::styled("Nothing logged yet", Style::default().fg(Color::DarkGray)))]
+    } else {
+        app.log_lines.iter().map(|line| Line::raw(line.as_str())).collect()
+    };
+    let title = match &app.config.log_file {
+        Some(path) => format!("Log: {}", path.display()),
+        None => "Log".to_string(),
+    };
+
+    let visible = area.height.saturating_sub(2) as usize;
+    let scroll = lines.len().saturating_sub(visible) as u16;
+    let log = Paragraph::new(lines).block(app.theme().block().borders(Borders::ALL).title(title)).scroll((scroll, 0));
+    f.render_widget(log, area);
+}
+
+/// Render the week calendar: a column per day listing the todos due then,
+/// and the selected todo's project underneath
+pub fn render_calendar(f: &mut Frame, area: Rect, app: &App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 7); 7])
+        .split(rows[0]);
+
+    let zone = app.config.timezone;
+    let today = zone.today();
+    let week_start = app.calendar_week_start();
+    for (offset, column) in columns.iter().enumerate() {
+        let date = week_start + Duration::days(offset as i64);
+        let is_selected_day = date == app.calendar_date;
+        let lines: Vec<Line> = app
+            .calendar_todos
+            .iter()
+            .filter(|t| t.due_date == Some(date))
+            .enumerate()
+            .map(|(index, todo)| {
+                let mut style = if todo.is_completed() {
+                    Style::default().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT)
+                } else if todo.is_overdue(zone) {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default()
+                };
+                if is_selected_day && index == app.selected_index {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Line::from(Span::styled(todo.description.clone(), style))
+            })
+            .collect();
+
+        let title_style = if date == today {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let border_style = if is_selected_day {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default()
+        };
+        let day = Paragraph::new(lines).wrap(Wrap { trim: true }).block(
+            app.theme().block()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title(Span::styled(date.format("%a %d").to_string(), title_style)),
+        );
+        f.render_widget(day, *column);
+    }
+
+    let selected = match app.calendar_day_todos().get(app.selected_index) {
+        Some(todo) => {
+            Line::from(vec![
+                Span::styled(
+                    format!("{} {} ", app.project_name(todo.project_id), app.icons().separator()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(todo.description.clone()),
+            ])
+        }
+        None => Line::from(Span::styled(
+            format!("Nothing due on {}", app.calendar_date.format("%a %Y-%m-%d")),
+            Style::default().fg(Color::DarkGray),
+        )),
+    };
+    f.render_widget(Paragraph::new(selected).block(app.theme().block().borders(Borders::ALL)), rows[1]);
+}
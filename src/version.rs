@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "db")]
+use crate::core::db::SCHEMA_VERSION;
+
+/// The crate version, e.g. `0.4.0`
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Short hash of the commit the binary was built from, or `unknown`
+pub const GIT_HASH: &str = env!("DOCKET_GIT_HASH");
+
+/// Day the binary was built, `YYYY-MM-DD`
+pub const BUILD_DATE: &str = env!("DOCKET_BUILD_DATE");
+
+/// What `docket --version` prints after the name, e.g. `0.4.0 (1a2b3c4d5e 2026-10-15)`
+pub const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("DOCKET_GIT_HASH"),
+    " ",
+    env!("DOCKET_BUILD_DATE"),
+    ")"
+);
+
+/// The body of `GET /api/version`, for bug reports and for clients
+/// checking they can sync with a server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub version: String,
+    pub git_hash: String,
+    pub build_date: String,
+    /// Schema migrations applied by this build; clients syncing with a
+    /// server on a newer schema may miss fields
+    pub schema_version: usize,
+}
+
+impl VersionInfo {
+    /// This build's version
+    #[cfg(feature = "db")]
+    pub fn current() -> Self {
+        Self {
+            version: VERSION.to_string(),
+            git_hash: GIT_HASH.to_string(),
+            build_date: BUILD_DATE.to_string(),
+            schema_version: SCHEMA_VERSION,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_version_names_the_build() {
+        assert!(LONG_VERSION.starts_with(VERSION));
+        assert!(LONG_VERSION.contains(GIT_HASH));
+        assert_eq!(BUILD_DATE.len(), "2026-10-15".len());
+        assert!(VersionInfo::current().schema_version > 0);
+    }
+}
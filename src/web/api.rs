@@ -1,24 +1,83 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Json},
+    extract::{Extension, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Json, Response,
+    },
 };
-use serde::Deserialize;
+use chrono::{DateTime, NaiveDate};
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
 
-use super::server::AppState;
-use crate::core::models::{ProjectWithStats, Todo};
+use super::server::{same_secret, AppState};
+use crate::core::audit::{AuditPage, AuditQuery};
+use crate::core::batch::{BatchOp, BatchResult};
+use crate::core::events::{Change, Replay};
+use crate::core::export::{self, ExportFormat};
+use crate::core::sync::{ChangeMarker, ChangeSet, PushChange, PushResult};
+use crate::core::models::{
+    LinkKind, Project, ProjectStats, ProjectWithStats, RelatedTodo, Todo, TodoContext, TodoMention, TodoPatch,
+    TodoTemplate, TodoVersion, Workspace, parse_todo_ref,
+};
+use crate::core::error::DocketError;
+use crate::core::query::TodoQuery;
+use crate::core::sort::TodoOrder;
+use crate::core::share::DEFAULT_SHARE_DAYS;
+use crate::core::time::DisplayZone;
+use crate::version::VersionInfo;
 
 // ===== Request/Response types =====
 
 #[derive(Deserialize)]
 pub struct CreateProjectRequest {
     pub name: String,
+    pub description: Option<String>,
 }
 
+/// `POST /api/projects/:id/clone`; without a name the copy gets a free
+/// numbered one
+#[derive(Deserialize, Default)]
+pub struct CloneProjectRequest {
+    pub name: Option<String>,
+    /// Copy completed todos too, reopened
+    #[serde(default)]
+    pub include_completed: bool,
+}
+
+/// `POST /api/projects/:id/share`
 #[derive(Deserialize)]
-pub struct UpdateProjectNameRequest {
+#[serde(default)]
+pub struct ShareProjectRequest {
+    /// Days the link works for (default 7)
+    pub days: i64,
+}
+
+impl Default for ShareProjectRequest {
+    fn default() -> Self {
+        Self { days: DEFAULT_SHARE_DAYS }
+    }
+}
+
+/// `PATCH /api/projects/:id`: only the fields given change; an empty
+/// description clears it
+#[derive(Deserialize)]
+pub struct UpdateProjectRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// `PUT /api/projects/:id`: every field, missing ones are cleared
+#[derive(Deserialize)]
+pub struct ReplaceProjectRequest {
     pub name: String,
+    pub description: Option<String>,
+    pub target_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub archived: bool,
 }
 
 #[derive(Deserialize)]
@@ -26,9 +85,14 @@ pub struct UpdateDescriptionRequest {
     pub description: Option<String>,
 }
 
+/// `POST /api/projects/:id/todos`; with a `template`, an empty description
+/// takes the template's
 #[derive(Deserialize)]
 pub struct CreateTodoRequest {
+    #[serde(default)]
     pub description: String,
+    pub details: Option<String>,
+    pub template: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -37,8 +101,18 @@ pub struct UpdateTodoDetailsRequest {
 }
 
 #[derive(Deserialize)]
-pub struct UpdateTodoRequest {
-    pub description: String,
+pub struct UpdateTodoEstimateRequest {
+    pub estimate: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTodoDueDateRequest {
+    pub due_date: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTodoExternalRefRequest {
+    pub external_ref: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -46,35 +120,227 @@ pub struct MoveTodoRequest {
     pub direction: String, // "up" or "down"
 }
 
+/// `POST /api/todos/:id/links`
+#[derive(Deserialize)]
+pub struct LinkTodoRequest {
+    pub target_id: i64,
+    /// `see_also` (the default) or `duplicates`
+    #[serde(default = "see_also")]
+    pub kind: LinkKind,
+}
+
+fn see_also() -> LinkKind {
+    LinkKind::SeeAlso
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTargetDateRequest {
+    pub target_date: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateSlugRequest {
+    /// Publish the status page under this slug; `null` takes it down
+    pub slug: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateProjectWorkspaceRequest {
+    /// File the project under this workspace; `null` takes it out
+    pub workspace_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateProjectParentRequest {
+    /// Nest the project under this one; `null` moves it to the top level
+    pub parent_id: Option<i64>,
+}
+
+/// `POST /api/workspaces` and `PATCH /api/workspaces/:id`
+#[derive(Deserialize)]
+pub struct WorkspaceRequest {
+    pub name: String,
+}
+
 #[derive(Deserialize)]
 pub struct ListProjectsQuery {
     #[serde(default)]
     pub include_archived: bool,
+    /// Only projects with a target date on or before this date
+    pub target_before: Option<NaiveDate>,
+    /// Only projects at risk of missing their target date
+    #[serde(default)]
+    pub at_risk: bool,
+    /// Only projects in this workspace
+    pub workspace: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct ChangesQuery {
+    /// Cursor from the previous pull; 0 fetches everything
+    #[serde(default)]
+    pub since: i64,
+}
+
+#[derive(Deserialize)]
+pub struct PushChangesRequest {
+    pub changes: Vec<PushChange>,
+}
+
+#[derive(Deserialize)]
+pub struct WidgetQuery {
+    /// Only count this project (name or ID)
+    pub project: Option<String>,
+    pub token: Option<String>,
+}
+
+/// Compact summary for dashboard widgets
+#[derive(Serialize)]
+pub struct Widget {
+    pub projects: usize,
+    pub open: i64,
+    pub due_today: usize,
+    pub overdue: usize,
+    /// The next todos by due date, overdue ones first
+    pub next_due: Vec<WidgetTodo>,
+}
+
+#[derive(Serialize)]
+pub struct WidgetTodo {
+    pub id: i64,
+    pub description: String,
+    pub project: String,
+    pub due_date: Option<NaiveDate>,
+    pub overdue: bool,
+}
+
+/// Todos listed in `Widget::next_due`
+const WIDGET_TODOS: usize = 5;
+
+#[derive(Deserialize)]
+pub struct BatchRequest {
+    pub operations: Vec<BatchOp>,
+}
+
+#[derive(Serialize)]
+pub struct BatchResponse {
+    pub results: Vec<BatchResult>,
+}
+
+#[derive(Deserialize)]
+pub struct ProjectStatsQuery {
+    /// How many weeks of completions to include
+    #[serde(default = "default_stats_weeks")]
+    pub weeks: i64,
+}
+
+fn default_stats_weeks() -> i64 {
+    12
 }
 
 #[derive(Deserialize)]
 pub struct ListTodosQuery {
     #[serde(default = "default_true")]
     pub include_completed: bool,
+    /// Filter expression, e.g. `tag:home due:<friday`
+    pub q: Option<String>,
+    /// Sort spec, e.g. `due, estimate desc`
+    pub sort: Option<String>,
 }
 
 fn default_true() -> bool {
     true
 }
 
+// ===== HTTP caching =====
+
+/// Format a time as an HTTP date (`Wed, 15 Oct 2026 04:32:10 GMT`)
+fn http_date(at: DateTime<chrono::Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Check the request's `If-None-Match` / `If-Modified-Since` against the
+/// current state. `If-None-Match` wins when both are sent.
+fn is_not_modified(headers: &HeaderMap, etag: &str, marker: &ChangeMarker) -> bool {
+    if let Some(tags) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+        return tags.split(',').any(|t| t.trim() == etag || t.trim() == "*");
+    }
+    let since = headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok());
+    match (since, marker.modified_at) {
+        (Some(since), Some(modified)) => modified.timestamp() <= since.timestamp(),
+        _ => false,
+    }
+}
+
+/// Serve `body` as JSON with `ETag` / `Last-Modified` derived from the latest
+/// change, or an empty 304 when the client's copy is still current. Clients
+/// are asked to revalidate every time, so polling costs a round-trip but no body.
+async fn cached_json<T, F>(
+    state: &AppState,
+    headers: &HeaderMap,
+    body: F,
+) -> Result<Response, AppError>
+where
+    T: Serialize,
+    F: std::future::Future<Output = anyhow::Result<T>>,
+{
+    cached(state, headers, async { Ok(Json(body.await?).into_response()) }).await
+}
+
+/// Like `cached_json`, for handlers that build their own response
+async fn cached<F>(state: &AppState, headers: &HeaderMap, render: F) -> Result<Response, AppError>
+where
+    F: std::future::Future<Output = anyhow::Result<Response>>,
+{
+    let marker = state.service.change_marker().await?;
+    let etag = format!("W/\"{}\"", marker.version);
+
+    let mut response = if is_not_modified(headers, &etag, &marker) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        render.await?
+    };
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::ETAG, HeaderValue::from_str(&etag)?);
+    response_headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    if let Some(modified) = marker.modified_at {
+        response_headers.insert(header::LAST_MODIFIED, HeaderValue::from_str(&http_date(modified))?);
+    }
+    Ok(response)
+}
+
 // ===== Project handlers =====
 
 /// List all projects
 pub async fn list_projects(
     State(state): State<Arc<AppState>>,
     Query(query): Query<ListProjectsQuery>,
-) -> Result<Json<Vec<ProjectWithStats>>, AppError> {
-    let projects = if query.include_archived {
-        state.service.list_all_projects().await?
-    } else {
-        state.service.list_active_projects().await?
-    };
-    Ok(Json(projects))
+    zone: Option<Extension<DisplayZone>>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    cached_json(&state, &headers, async {
+        let mut projects: Vec<ProjectWithStats> = if query.include_archived {
+            state.service.list_all_projects().await?
+        } else {
+            state.service.list_active_projects().await?
+        };
+        if let Some(before) = query.target_before {
+            projects.retain(|p| p.project.target_date.is_some_and(|d| d <= before));
+        }
+        if query.at_risk {
+            let zone = zone.map_or(state.service.timezone(), |Extension(zone)| zone);
+            projects.retain(|p| p.is_at_risk(zone));
+        }
+        if let Some(workspace) = query.workspace {
+            projects.retain(|p| p.project.workspace_id == Some(workspace));
+        }
+        Ok(projects)
+    })
+    .await
 }
 
 /// Create a new project
@@ -82,10 +348,77 @@ pub async fn create_project(
     State(state): State<Arc<AppState>>,
     Json(req): Json<CreateProjectRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let project = state.service.create_project(&req.name).await?;
+    let project = state
+        .service
+        .create_project_with_description(&req.name, req.description.as_deref())
+        .await?;
     Ok((StatusCode::CREATED, Json(project)))
 }
 
+/// Copy a project with its open (and optionally completed) todos
+pub async fn clone_project(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    req: Option<Json<CloneProjectRequest>>,
+) -> Result<impl IntoResponse, AppError> {
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let project = state
+        .service
+        .clone_project(id, req.name.as_deref(), req.include_completed)
+        .await?;
+    Ok((StatusCode::CREATED, Json(project)))
+}
+
+/// Create a signed, expiring link to a read-only view of a project
+pub async fn share_project(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    req: Option<Json<ShareProjectRequest>>,
+) -> Result<impl IntoResponse, AppError> {
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let link = state.service.share_project(id, req.days).await?;
+    Ok((StatusCode::CREATED, Json(link)))
+}
+
+/// The project behind a share link, as HTML, or JSON when the client asks
+/// for it. Needs no other credentials than the token.
+pub async fn shared_project(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()).unwrap_or("");
+    let json = accept.contains("application/json") && !accept.contains("text/html");
+    let project = match state.service.shared_project(&token).await {
+        Ok(project) => project,
+        Err(err) => {
+            let status = match err {
+                DocketError::NotFound(_) => StatusCode::NOT_FOUND,
+                DocketError::Expired(_) => StatusCode::GONE,
+                _ => return Err(err.into()),
+            };
+            let message = err.to_string();
+            return Ok(if json {
+                (status, Json(serde_json::json!({ "error": message }))).into_response()
+            } else {
+                (status, Html(format!("<!DOCTYPE html>\n<p>{}</p>\n", message))).into_response()
+            });
+        }
+    };
+    Ok(if json { Json(project).into_response() } else { Html(project.to_html()).into_response() })
+}
+
+/// A project's public status page, published with `PATCH /api/projects/:id/slug`
+pub async fn public_project(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+) -> Result<Response, AppError> {
+    Ok(match state.service.public_project(&slug).await? {
+        Some(page) => Html(page.to_html()).into_response(),
+        None => (StatusCode::NOT_FOUND, Html("<!DOCTYPE html>\n<p>No such page</p>\n")).into_response(),
+    })
+}
+
 /// Delete a project
 pub async fn delete_project(
     State(state): State<Arc<AppState>>,
@@ -123,16 +456,112 @@ pub async fn update_project_description(
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Update project name
-pub async fn update_project_name(
+/// Update project target date
+pub async fn update_project_target_date(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateTargetDateRequest>,
+) -> Result<StatusCode, AppError> {
+    state.service.update_project_target_date(id, req.target_date).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Publish or take down a project's public status page
+pub async fn update_project_slug(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateSlugRequest>,
+) -> Result<Json<Project>, AppError> {
+    Ok(Json(state.service.update_project_slug(id, req.slug.as_deref()).await?))
+}
+
+/// File a project under a workspace, or take it out of one
+pub async fn update_project_workspace(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateProjectWorkspaceRequest>,
+) -> Result<Json<Project>, AppError> {
+    Ok(Json(state.service.update_project_workspace(id, req.workspace_id).await?))
+}
+
+/// Nest a project under another, or move it to the top level
+pub async fn update_project_parent(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateProjectParentRequest>,
+) -> Result<Json<Project>, AppError> {
+    Ok(Json(state.service.update_project_parent(id, req.parent_id).await?))
+}
+
+/// List workspaces by name
+pub async fn list_workspaces(State(state): State<Arc<AppState>>) -> Result<Json<Vec<Workspace>>, AppError> {
+    Ok(Json(state.service.list_workspaces().await?))
+}
+
+/// Create a workspace
+pub async fn create_workspace(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<WorkspaceRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let workspace = state.service.create_workspace(&req.name).await?;
+    Ok((StatusCode::CREATED, Json(workspace)))
+}
+
+/// Rename a workspace
+pub async fn rename_workspace(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<WorkspaceRequest>,
+) -> Result<Json<Workspace>, AppError> {
+    Ok(Json(state.service.rename_workspace(id, &req.name).await?))
+}
+
+/// Delete a workspace, leaving its projects in none
+pub async fn delete_workspace(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    state.service.delete_workspace(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Record that a project has been reviewed today
+pub async fn mark_project_reviewed(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    state.service.mark_project_reviewed(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Update a project's name and/or description
+pub async fn update_project(
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<UpdateProjectNameRequest>,
+    Json(req): Json<UpdateProjectRequest>,
 ) -> Result<StatusCode, AppError> {
-    state.service.update_project_name(id, &req.name).await?;
+    if let Some(name) = &req.name {
+        state.service.update_project_name(id, name).await?;
+    }
+    if let Some(description) = &req.description {
+        state.service.update_project_description(id, Some(description)).await?;
+    }
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Replace all of a project's editable fields
+pub async fn replace_project(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<ReplaceProjectRequest>,
+) -> Result<Json<crate::core::models::Project>, AppError> {
+    let project = state
+        .service
+        .replace_project(id, &req.name, req.description.as_deref(), req.target_date, req.archived)
+        .await?;
+    Ok(Json(project))
+}
+
 /// Get a single project
 pub async fn get_project(
     State(state): State<Arc<AppState>>,
@@ -142,6 +571,17 @@ pub async fn get_project(
     Ok(Json(project))
 }
 
+/// Get aggregate statistics for a project
+pub async fn get_project_stats(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(query): Query<ProjectStatsQuery>,
+    zone: Option<Extension<DisplayZone>>,
+) -> Result<Json<ProjectStats>, AppError> {
+    let zone = zone.map_or(state.service.timezone(), |Extension(zone)| zone);
+    Ok(Json(state.service.get_project_stats(id, query.weeks, zone).await?))
+}
+
 // ===== Todo handlers =====
 
 /// List todos for a project
@@ -149,13 +589,42 @@ pub async fn list_todos(
     State(state): State<Arc<AppState>>,
     Path(project_id): Path<i64>,
     Query(query): Query<ListTodosQuery>,
-) -> Result<Json<Vec<Todo>>, AppError> {
-    let todos = if query.include_completed {
-        state.service.list_all_todos(project_id).await?
-    } else {
-        state.service.list_active_todos(project_id).await?
+    zone: Option<Extension<DisplayZone>>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let format = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .and_then(ExportFormat::from_accept);
+    let filter = match query.q.as_deref().filter(|q| !q.trim().is_empty()) {
+        Some(q) => Some(q.parse::<TodoQuery>().map_err(|e| DocketError::validation("q", e))?),
+        None => None,
     };
-    Ok(Json(todos))
+    let order = match query.sort.as_deref() {
+        Some(sort) => sort.parse::<TodoOrder>().map_err(|e| DocketError::validation("sort", e))?,
+        None => TodoOrder::default(),
+    };
+
+    let mut response = cached(&state, &headers, async {
+        let include_completed = query.include_completed || filter.as_ref().is_some_and(TodoQuery::has_status);
+        let mut todos = state.service.list_sorted_todos(project_id, include_completed, &order).await?;
+        if let Some(filter) = &filter {
+            let project = state.service.get_project(project_id).await?;
+            let today = zone.map_or(state.service.timezone(), |Extension(zone)| zone).today();
+            todos.retain(|t| filter.matches(t, &project.name, today));
+        }
+        Ok(match format {
+            Some(format) => {
+                let project = state.service.get_project(project_id).await?;
+                let body = export::export_project(format, &project, &todos);
+                ([(header::CONTENT_TYPE, format.mime_type())], body).into_response()
+            }
+            None => Json(todos).into_response(),
+        })
+    })
+    .await?;
+    response.headers_mut().insert(header::VARY, HeaderValue::from_static("Accept"));
+    Ok(response)
 }
 
 /// Create a new todo
@@ -164,10 +633,26 @@ pub async fn create_todo(
     Path(project_id): Path<i64>,
     Json(req): Json<CreateTodoRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let todo = state.service.create_todo(project_id, &req.description).await?;
+    let todo = match &req.template {
+        Some(template) => {
+            let description = Some(req.description.as_str()).filter(|d| !d.trim().is_empty());
+            state.service.create_todo_from_template(project_id, template, description).await?
+        }
+        None => {
+            state
+                .service
+                .create_todo_with_details(project_id, &req.description, req.details.as_deref())
+                .await?
+        }
+    };
     Ok((StatusCode::CREATED, Json(todo)))
 }
 
+/// List todo templates by name
+pub async fn list_templates(State(state): State<Arc<AppState>>) -> Result<Json<Vec<TodoTemplate>>, AppError> {
+    Ok(Json(state.service.list_templates().await?))
+}
+
 /// Toggle todo completion
 pub async fn toggle_todo(
     State(state): State<Arc<AppState>>,
@@ -209,6 +694,72 @@ pub async fn get_todo(
     Ok(Json(todo))
 }
 
+/// Get a todo with its project, tags and position in one response. The
+/// todo can also be named by its short ID, as in `/api/todos/HOME-42/full`.
+pub async fn get_todo_full(
+    State(state): State<Arc<AppState>>,
+    Path(reference): Path<String>,
+) -> Result<Json<TodoContext>, AppError> {
+    let not_found = || DocketError::not_found(format!("Todo {} not found", reference));
+    let todo_ref = parse_todo_ref(&reference).ok_or_else(not_found)?;
+    let context = state.service.get_todo_context(todo_ref.id).await?;
+    if !todo_ref.fits(&context.project) {
+        return Err(not_found().into());
+    }
+    Ok(Json(context))
+}
+
+/// List a todo's earlier descriptions and details, newest first
+pub async fn todo_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<TodoVersion>>, AppError> {
+    Ok(Json(state.service.todo_history(id).await?))
+}
+
+/// Put back a todo's description and details from an earlier version
+pub async fn restore_todo_version(
+    State(state): State<Arc<AppState>>,
+    Path((id, version_id)): Path<(i64, i64)>,
+) -> Result<Json<Todo>, AppError> {
+    Ok(Json(state.service.restore_todo_version(id, version_id).await?))
+}
+
+/// List the todos a todo links to or is linked from
+pub async fn related_todos(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<RelatedTodo>>, AppError> {
+    Ok(Json(state.service.related_todos(id).await?))
+}
+
+/// Link a todo to another, perhaps in another project
+pub async fn link_todos(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<LinkTodoRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let link = state.service.link_todos(id, req.target_id, req.kind).await?;
+    Ok((StatusCode::CREATED, Json(link)))
+}
+
+/// List the todos a todo's details mention, then those mentioning it
+pub async fn todo_mentions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<TodoMention>>, AppError> {
+    Ok(Json(state.service.todo_mentions(id).await?))
+}
+
+/// Remove a link between two todos
+pub async fn unlink_todos(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    state.service.unlink_todos(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Update todo details
 pub async fn update_todo_details(
     State(state): State<Arc<AppState>>,
@@ -219,22 +770,94 @@ pub async fn update_todo_details(
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Update todo description
-pub async fn update_todo(
+/// Update todo effort estimate
+pub async fn update_todo_estimate(
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-    Json(req): Json<UpdateTodoRequest>,
+    Json(req): Json<UpdateTodoEstimateRequest>,
 ) -> Result<StatusCode, AppError> {
-    state.service.update_todo(id, &req.description).await?;
+    state.service.update_todo_estimate(id, req.estimate).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Set or clear a todo's due date
+pub async fn update_todo_due_date(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateTodoDueDateRequest>,
+) -> Result<StatusCode, AppError> {
+    state.service.update_todo_due_date(id, req.due_date).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Link a todo to a JIRA key or URL, or clear the link
+pub async fn update_todo_external_ref(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateTodoExternalRefRequest>,
+) -> Result<StatusCode, AppError> {
+    state.service.update_todo_external_ref(id, req.external_ref.as_deref()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Update any of a todo's fields in one request (JSON merge patch)
+pub async fn update_todo(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(patch): Json<TodoPatch>,
+) -> Result<Json<Todo>, AppError> {
+    Ok(Json(state.service.patch_todo(id, &patch).await?))
+}
+
+/// A page of the audit log, filtered by entity, actor and time
+pub async fn audit_log(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditPage>, AppError> {
+    Ok(Json(state.service.audit_log(&query).await?))
+}
+
+/// Version, commit and schema of this build
+pub async fn version() -> Json<VersionInfo> {
+    Json(VersionInfo::current())
+}
+
+/// Whether the server is up and can read its database, for container and
+/// load balancer health checks; open even when a login is required
+pub async fn health(State(state): State<Arc<AppState>>) -> Response {
+    match state.service.change_marker().await {
+        Ok(_) => Json(serde_json::json!({ "status": "ok" })).into_response(),
+        Err(err) => {
+            tracing::warn!("Health check failed: {}", err);
+            (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({ "status": "unavailable" }))).into_response()
+        }
+    }
+}
+
 // ===== Error handling =====
 
 pub struct AppError(anyhow::Error);
 
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
+        let body = match self.0.downcast_ref::<DocketError>() {
+            Some(DocketError::NotFound(msg)) => Some((StatusCode::NOT_FOUND, serde_json::json!({ "error": msg }))),
+            Some(DocketError::Validation { field, msg }) => Some((
+                StatusCode::BAD_REQUEST,
+                serde_json::json!({ "error": msg, "field": field }),
+            )),
+            Some(DocketError::Conflict { msg, suggestion }) => Some((
+                StatusCode::CONFLICT,
+                serde_json::json!({ "error": msg, "suggestion": suggestion }),
+            )),
+            Some(DocketError::Expired(msg)) => Some((StatusCode::GONE, serde_json::json!({ "error": msg }))),
+            Some(DocketError::LimitReached(msg)) => Some((StatusCode::FORBIDDEN, serde_json::json!({ "error": msg }))),
+            Some(DocketError::Db { .. } | DocketError::Remote(_) | DocketError::Offline(_)) | None => None,
+        };
+        if let Some((status, body)) = body {
+            return (status, Json(body)).into_response();
+        }
+
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Error: {}", self.0),
@@ -251,3 +874,180 @@ where
         Self(err.into())
     }
 }
+
+// ===== Event handlers =====
+
+/// Stream change notifications as server-sent events. Event IDs are sync
+/// versions, so clients reconnecting with `Last-Event-ID` first receive the
+/// changes they missed, even across a restart; when those are no longer
+/// buffered, or they fall too far behind, a `resync` event tells them to
+/// refetch.
+pub async fn events(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let last_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    let (replay, rx) = match last_id {
+        Some(last_id) => {
+            // Unreadable counts as ahead of any ID, which asks for a resync
+            let current = state.service.change_marker().await.map_or(0, |marker| marker.version as u64);
+            state.service.events().resume(last_id, current)
+        }
+        None => (Replay::Changes(Vec::new()), state.service.events().subscribe()),
+    };
+    let replay: VecDeque<_> = match replay {
+        Replay::Changes(changes) => changes.iter().map(change_event).collect(),
+        Replay::Resync => VecDeque::from([Ok(resync_event())]),
+    };
+
+    let stream = stream::unfold((replay, rx), |(mut replay, mut rx)| async move {
+        let event = match replay.pop_front() {
+            Some(event) => event,
+            None => match rx.recv().await {
+                Ok(change) => change_event(&change),
+                Err(RecvError::Lagged(_)) => Ok(resync_event()),
+                Err(RecvError::Closed) => return None,
+            },
+        };
+        Some((event, (replay, rx)))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+fn resync_event() -> Event {
+    Event::default().event("resync").data("{}")
+}
+
+fn change_event(change: &Change) -> Result<Event, axum::Error> {
+    Event::default()
+        .id(change.id.to_string())
+        .event(change.kind.as_str())
+        .json_data(change)
+}
+
+// ===== Sync handlers =====
+
+/// Pull everything that changed after a cursor
+pub async fn get_changes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ChangesQuery>,
+) -> Result<Json<ChangeSet>, AppError> {
+    Ok(Json(state.service.changes_since(query.since).await?))
+}
+
+/// Push a batch of offline edits
+pub async fn push_changes(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<PushChangesRequest>,
+) -> Result<Json<PushResult>, AppError> {
+    Ok(Json(state.service.apply_changes(req.changes).await?))
+}
+
+/// Counts and the next due todos, for dashboards. When `widget_token` is
+/// configured it must be given as `?token=` or a bearer token.
+pub async fn widget(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WidgetQuery>,
+    zone: Option<Extension<DisplayZone>>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let expected = state.settings.read().unwrap().widget_token.clone();
+    if let Some(expected) = expected {
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if !same_secret(query.token.as_deref().or(bearer), &expected) {
+            return Ok((StatusCode::UNAUTHORIZED, Json(serde_json::json!({ "error": "Invalid widget token" })))
+                .into_response());
+        }
+    }
+
+    let mut projects = state.service.list_active_projects().await?;
+    if let Some(wanted) = &query.project {
+        let id = wanted.parse::<i64>().ok();
+        projects.retain(|p| Some(p.project.id) == id || p.project.name.eq_ignore_ascii_case(wanted));
+        if projects.is_empty() {
+            let message = format!("No active project '{}'", wanted);
+            return Ok((StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": message }))).into_response());
+        }
+    }
+
+    let zone = zone.map_or(state.service.timezone(), |Extension(zone)| zone);
+    let today = zone.today();
+    // Dates compare as text in SQLite, where NaiveDate::MAX (`+262142-…`) sorts first
+    let horizon = NaiveDate::from_ymd_opt(9999, 12, 31).expect("valid date");
+    let due: Vec<Todo> = state
+        .service
+        .list_open_todos_due_by(horizon)
+        .await?
+        .into_iter()
+        .filter(|t| projects.iter().any(|p| p.project.id == t.project_id))
+        .collect();
+    let name = |project_id: i64| {
+        projects
+            .iter()
+            .find(|p| p.project.id == project_id)
+            .map(|p| p.project.name.clone())
+            .unwrap_or_default()
+    };
+
+    Ok(Json(Widget {
+        projects: projects.len(),
+        open: projects.iter().map(|p| p.active_todos()).sum(),
+        due_today: due.iter().filter(|t| t.due_date == Some(today)).count(),
+        overdue: due.iter().filter(|t| t.due_date.is_some_and(|d| d < today)).count(),
+        next_due: due
+            .iter()
+            .take(WIDGET_TODOS)
+            .map(|t| WidgetTodo {
+                id: t.id,
+                description: t.description.clone(),
+                project: name(t.project_id),
+                due_date: t.due_date,
+                overdue: t.due_date.is_some_and(|d| d < today),
+            })
+            .collect(),
+    })
+    .into_response())
+}
+
+/// Run several todo operations in one transaction
+pub async fn batch(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BatchRequest>,
+) -> Result<Json<BatchResponse>, AppError> {
+    let results = state.service.apply_batch(req.operations).await?;
+    Ok(Json(BatchResponse { results }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conditional_request_headers() {
+        let modified = DateTime::parse_from_rfc3339("2026-10-15T04:40:04Z").unwrap().to_utc();
+        let marker = ChangeMarker { version: 7, modified_at: Some(modified) };
+        let etag = "W/\"7\"";
+        let request = |name, value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, HeaderValue::from_str(value).unwrap());
+            headers
+        };
+
+        assert!(is_not_modified(&request(header::IF_NONE_MATCH, "W/\"6\", W/\"7\""), etag, &marker));
+        assert!(!is_not_modified(&request(header::IF_NONE_MATCH, "W/\"6\""), etag, &marker));
+        assert!(is_not_modified(&request(header::IF_MODIFIED_SINCE, &http_date(modified)), etag, &marker));
+        assert!(!is_not_modified(
+            &request(header::IF_MODIFIED_SINCE, "Thu, 15 Oct 2026 04:40:03 GMT"),
+            etag,
+            &marker
+        ));
+        assert!(!is_not_modified(&HeaderMap::new(), etag, &marker));
+    }
+}
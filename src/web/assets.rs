@@ -0,0 +1,79 @@
+use axum::{
+    extract::Path,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+// The web UI, embedded from `web-ui/` by `build.rs`
+include!(concat!(env!("OUT_DIR"), "/web_ui.rs"));
+
+/// Embedded file at `path`, relative to `web-ui/`
+fn asset(path: &str) -> Option<&'static [u8]> {
+    ASSETS.iter().find(|(name, _)| *name == path).map(|(_, bytes)| *bytes)
+}
+
+/// Content type for a file, by extension
+fn content_type(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("ico") => "image/x-icon",
+        Some("json") => "application/json",
+        Some("webmanifest") => "application/manifest+json",
+        _ => "application/octet-stream",
+    }
+}
+
+fn serve(path: &str) -> Response {
+    match asset(path) {
+        // Revalidated on every load, so a new binary's UI shows up at once
+        Some(bytes) => (
+            [(header::CONTENT_TYPE, content_type(path)), (header::CACHE_CONTROL, "no-cache")],
+            bytes,
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Serve the web UI's page
+pub async fn index() -> Response {
+    serve("index.html")
+}
+
+/// Serve the service worker from the root, so it may control every page
+pub async fn service_worker() -> Response {
+    serve("sw.js")
+}
+
+/// Serve the manifest that lets the web UI install as an app
+pub async fn manifest() -> Response {
+    serve("manifest.webmanifest")
+}
+
+/// Serve a script, stylesheet or image the page loads from `/ui/`
+pub async fn file(Path(path): Path<String>) -> Response {
+    serve(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ui_is_embedded() {
+        let index = std::str::from_utf8(asset("index.html").unwrap()).unwrap();
+        // Everything the page links to is embedded too
+        for linked in ["style.css", "app.js", "icons/icon-192.png"] {
+            assert!(index.contains(&format!("/ui/{}", linked)));
+            assert!(asset(linked).is_some(), "{} is not embedded", linked);
+        }
+        assert!(asset("sw.js").is_some());
+        assert!(asset("manifest.webmanifest").is_some());
+        assert_eq!(content_type("app.js"), "text/javascript; charset=utf-8");
+        assert!(asset("../Cargo.toml").is_none());
+    }
+}
@@ -1,4 +1,6 @@
 pub mod server;
 pub mod api;
+pub mod assets;
+pub mod pages;
 
-pub use server::start_server;
+pub use server::{Listener, ServerSettings, serve, start_server};
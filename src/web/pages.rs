@@ -0,0 +1,286 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+    Form,
+};
+use serde::Deserialize;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use super::server::AppState;
+use crate::core::error::DocketError;
+use crate::core::html::escape_html;
+use crate::core::models::{Project, ProjectWithStats, Todo};
+use crate::core::sort::TodoOrder;
+use crate::core::time::DisplayZone;
+use crate::version;
+
+// Plain HTML pages with forms for browsers without JavaScript. Every form
+// posts and is answered with a redirect back to the page it came from.
+
+#[derive(Deserialize)]
+pub struct NewProjectForm {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct NewTodoForm {
+    pub description: String,
+}
+
+/// Active projects, with a form to add one
+pub async fn projects(State(state): State<Arc<AppState>>) -> Response {
+    projects_page(&state, None, "").await
+}
+
+/// Add a project and open it. A taken name shows the list again with the
+/// server's suggestion filled in.
+pub async fn create_project(State(state): State<Arc<AppState>>, Form(form): Form<NewProjectForm>) -> Response {
+    match state.service.create_project(form.name.trim()).await {
+        Ok(project) => Redirect::to(&format!("/projects/{}", project.id)).into_response(),
+        Err(DocketError::Conflict { msg, suggestion }) => {
+            let name = suggestion.as_deref().unwrap_or(&form.name);
+            projects_page(&state, Some((StatusCode::CONFLICT, &msg)), name).await
+        }
+        Err(DocketError::Validation { msg, .. }) => {
+            projects_page(&state, Some((StatusCode::BAD_REQUEST, &msg)), &form.name).await
+        }
+        Err(err) => error_page(err),
+    }
+}
+
+/// A project's todos, open ones first, with forms to add, toggle and delete
+pub async fn project(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    zone: Option<Extension<DisplayZone>>,
+) -> Response {
+    let zone = zone.map_or(state.service.timezone(), |Extension(zone)| zone);
+    let read_only = state.settings.read().unwrap().read_only;
+    let loaded = async {
+        let project = state.service.get_project(id).await?;
+        let todos = state.service.list_sorted_todos(id, true, &TodoOrder::default()).await?;
+        Ok::<_, DocketError>((project, todos))
+    };
+    match loaded.await {
+        Ok((project, todos)) => Html(render_project(&project, &todos, zone, read_only)).into_response(),
+        Err(err) => error_page(err),
+    }
+}
+
+/// Add a todo to a project
+pub async fn create_todo(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Form(form): Form<NewTodoForm>,
+) -> Response {
+    match state.service.create_todo(id, form.description.trim()).await {
+        Ok(_) => Redirect::to(&format!("/projects/{}", id)).into_response(),
+        Err(err) => error_page(err),
+    }
+}
+
+/// Check off a todo, or reopen it
+pub async fn toggle_todo(State(state): State<Arc<AppState>>, Path(id): Path<i64>) -> Response {
+    let toggled = async {
+        let todo = state.service.get_todo(id).await?;
+        state.service.toggle_todo(id).await?;
+        Ok::<_, DocketError>(todo.project_id)
+    };
+    match toggled.await {
+        Ok(project_id) => Redirect::to(&format!("/projects/{}", project_id)).into_response(),
+        Err(err) => error_page(err),
+    }
+}
+
+/// Delete a todo
+pub async fn delete_todo(State(state): State<Arc<AppState>>, Path(id): Path<i64>) -> Response {
+    let deleted = async {
+        let todo = state.service.get_todo(id).await?;
+        state.service.delete_todo(id).await?;
+        Ok::<_, DocketError>(todo.project_id)
+    };
+    match deleted.await {
+        Ok(project_id) => Redirect::to(&format!("/projects/{}", project_id)).into_response(),
+        Err(err) => error_page(err),
+    }
+}
+
+/// The project list, optionally answering a rejected form with `notice`
+/// and the name field filled with `name`
+async fn projects_page(state: &AppState, notice: Option<(StatusCode, &str)>, name: &str) -> Response {
+    let read_only = state.settings.read().unwrap().read_only;
+    match state.service.list_active_projects().await {
+        Ok(projects) => {
+            let status = notice.map_or(StatusCode::OK, |(status, _)| status);
+            let page = render_projects(&projects, notice.map(|(_, msg)| msg), name, read_only);
+            (status, Html(page)).into_response()
+        }
+        Err(err) => error_page(err),
+    }
+}
+
+/// A page saying what went wrong, with the status the API would answer
+fn error_page(err: DocketError) -> Response {
+    let status = match err {
+        DocketError::NotFound(_) => StatusCode::NOT_FOUND,
+        DocketError::Validation { .. } => StatusCode::BAD_REQUEST,
+        DocketError::Conflict { .. } => StatusCode::CONFLICT,
+        DocketError::LimitReached(_) => StatusCode::FORBIDDEN,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let body = format!(
+        "<p>{}</p>\n<p><a href=\"/projects\">Back to projects</a></p>\n",
+        escape_html(&err.to_string())
+    );
+    (status, Html(layout("Error", &body))).into_response()
+}
+
+fn render_projects(projects: &[ProjectWithStats], notice: Option<&str>, name: &str, read_only: bool) -> String {
+    let mut body = String::from("<h1>Projects</h1>\n");
+    if let Some(notice) = notice {
+        let _ = writeln!(body, "<p><strong>{}</strong></p>", escape_html(notice));
+    }
+    if !read_only {
+        let _ = writeln!(
+            body,
+            "<form method=\"post\" action=\"/projects\">\n\
+             <input type=\"text\" name=\"name\" value=\"{}\" placeholder=\"New project name\" required>\n\
+             <input type=\"submit\" value=\"Add project\">\n</form>",
+            escape_html(name)
+        );
+    }
+    if projects.is_empty() {
+        body.push_str("<p>No projects yet.</p>\n");
+    }
+    body.push_str("<ul>\n");
+    for p in projects {
+        let _ = write!(
+            body,
+            "<li><a href=\"/projects/{}\">{}</a> <small>{} open, {} done",
+            p.project.id,
+            escape_html(&p.project.name),
+            p.active_todos(),
+            p.completed_todos
+        );
+        if let Some(target) = p.project.target_date {
+            let _ = write!(body, ", target {}", target);
+        }
+        body.push_str("</small></li>\n");
+    }
+    body.push_str("</ul>\n");
+    layout("Projects", &body)
+}
+
+fn render_project(project: &Project, todos: &[Todo], zone: DisplayZone, read_only: bool) -> String {
+    let editable = !read_only && !project.is_archived();
+    let mut body = String::from("<p><a href=\"/projects\">&larr; Projects</a></p>\n");
+    let _ = writeln!(body, "<h1>{}</h1>", escape_html(&project.name));
+    if let Some(description) = &project.description {
+        let _ = writeln!(body, "<p><em>{}</em></p>", escape_html(description));
+    }
+    if project.is_archived() {
+        body.push_str("<p>This project is archived.</p>\n");
+    }
+    if editable {
+        let _ = writeln!(
+            body,
+            "<form method=\"post\" action=\"/projects/{}/todos\">\n\
+             <input type=\"text\" name=\"description\" placeholder=\"New todo\" required>\n\
+             <input type=\"submit\" value=\"Add todo\">\n</form>",
+            project.id
+        );
+    }
+    if todos.is_empty() {
+        body.push_str("<p>No todos yet.</p>\n");
+    }
+
+    body.push_str("<table>\n");
+    let (done, open): (Vec<&Todo>, Vec<&Todo>) = todos.iter().partition(|t| t.is_completed());
+    for todo in open.into_iter().chain(done) {
+        let mut description = escape_html(&todo.description);
+        if todo.is_completed() {
+            description = format!("<s>{}</s>", description);
+        }
+        if let Some(due) = todo.due_date {
+            let due = if todo.is_overdue(zone) {
+                format!("<strong>overdue {}</strong>", due)
+            } else {
+                format!("due {}", due)
+            };
+            let _ = write!(description, " <small>({})</small>", due);
+        }
+        let mark = if todo.is_completed() { "&#x2611;" } else { "&#x2610;" };
+        body.push_str("<tr>");
+        if editable {
+            let label = if todo.is_completed() { "Reopen" } else { "Done" };
+            let _ = write!(
+                body,
+                "<td>{}</td><td>{}</td><td>{} {}</td>",
+                mark,
+                description,
+                button(&format!("/todos/{}/toggle", todo.id), label),
+                button(&format!("/todos/{}/delete", todo.id), "Delete")
+            );
+        } else {
+            let _ = write!(body, "<td>{}</td><td>{}</td>", mark, description);
+        }
+        body.push_str("</tr>\n");
+    }
+    body.push_str("</table>\n");
+    layout(&project.name, &body)
+}
+
+/// A one-button form posting to `action`
+fn button(action: &str, label: &str) -> String {
+    format!(
+        "<form method=\"post\" action=\"{}\" style=\"display: inline\"><input type=\"submit\" value=\"{}\"></form>",
+        action, label
+    )
+}
+
+fn layout(title: &str, body: &str) -> String {
+    let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(out, "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">");
+    let _ = writeln!(out, "<title>{} - Docket</title>", escape_html(title));
+    out.push_str("</head>\n<body style=\"font-family: sans-serif; max-width: 40em; margin: 2em auto\">\n");
+    out.push_str(body);
+    let _ = writeln!(out, "<footer><small>Docket {}</small></footer>", version::LONG_VERSION);
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+
+    #[test]
+    fn test_project_page_has_forms_unless_read_only() {
+        let project: Project = serde_json::from_value(serde_json::json!({
+            "id": 3, "name": "Home <reno>", "description": null, "created_at": Utc::now(),
+            "archived_at": null, "last_reviewed_at": null, "target_date": null, "slug": null,
+            "updated_at": Utc::now(), "version": 1,
+        }))
+        .unwrap();
+        let todos: Vec<Todo> = serde_json::from_value(serde_json::json!([{
+            "id": 9, "project_id": 3, "description": "Tile \"floor\"", "details": null,
+            "created_at": Utc::now(), "completed_at": null, "position": 1, "estimate": null,
+            "due_date": NaiveDate::from_ymd_opt(2000, 1, 1), "external_ref": null,
+            "updated_at": Utc::now(), "version": 1,
+        }]))
+        .unwrap();
+
+        let page = render_project(&project, &todos, DisplayZone::Local, false);
+        assert!(page.contains("<h1>Home &lt;reno&gt;</h1>"));
+        assert!(page.contains("Tile &quot;floor&quot;"));
+        assert!(page.contains("overdue 2000-01-01"));
+        assert!(page.contains("action=\"/projects/3/todos\""));
+        assert!(page.contains("action=\"/todos/9/toggle\""));
+        assert!(page.contains("action=\"/todos/9/delete\""));
+
+        let page = render_project(&project, &todos, DisplayZone::Local, true);
+        assert!(!page.contains("<form"));
+    }
+}
@@ -1,61 +1,836 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use axum::{
-    Router,
-    routing::{get, post, delete, patch},
-    response::Html,
+    Json, Router,
+    body::Body,
+    extract::{Query, Request, State},
+    http::{HeaderValue, Method, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post, put, delete, patch},
 };
-use std::sync::Arc;
-use tower_http::cors::CorsLayer;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
+use crate::config::Config;
+use crate::core::audit;
+use crate::core::error::DocketError;
+use crate::core::models::IdempotentRequest;
 use crate::core::service::DocketService;
-use super::api;
+use crate::core::service_api::DocketServiceApi;
+use crate::core::time::DisplayZone;
+#[cfg(unix)]
+use crate::daemon::{self, ActivatedListener};
+use super::{api, assets, pages};
 
 /// Application state shared across handlers
-#[derive(Clone)]
 pub struct AppState {
-    pub service: DocketService,
+    pub service: Arc<dyn DocketServiceApi>,
+    /// Requests seen under an `Idempotency-Key`, with their responses
+    pub idempotency: Arc<dyn IdempotencyStore>,
+    /// Settings re-read from the config file on SIGHUP
+    pub settings: RwLock<ServerSettings>,
 }
 
-/// Serve the web UI
-async fn serve_ui() -> Html<&'static str> {
-    Html(include_str!("../../static/index.html"))
+/// Server behaviour that can change without a restart
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerSettings {
+    pub read_only: bool,
+    pub cors_origins: Vec<String>,
+    pub widget_token: Option<String>,
+    pub api_token: Option<String>,
+    pub basic_auth: Option<String>,
+    pub admin_token: Option<String>,
+    /// Largest request body accepted, from `limits.max_body_bytes`
+    pub max_body_bytes: Option<usize>,
+    /// Set by `docket server --demo`: visitors may change todos but not
+    /// delete, replace or sync over the sample projects
+    pub demo: bool,
 }
 
-/// Start the web server
-pub async fn start_server(service: DocketService, port: u16) -> Result<()> {
-    let state = Arc::new(AppState { service });
+impl From<&Config> for ServerSettings {
+    fn from(config: &Config) -> Self {
+        Self {
+            read_only: config.read_only,
+            cors_origins: config.cors_origins.clone(),
+            widget_token: config.widget_token.clone(),
+            api_token: config.api_token.clone(),
+            basic_auth: config.basic_auth.clone(),
+            admin_token: config.admin_token.clone(),
+            max_body_bytes: config.limits.max_body_bytes,
+            demo: false,
+        }
+    }
+}
+
+impl ServerSettings {
+    /// Check a browser `Origin` against `cors_origins`
+    fn allows_origin(&self, origin: &str) -> bool {
+        self.cors_origins.is_empty() || self.cors_origins.iter().any(|o| o == origin)
+    }
+}
+
+/// Largest body the idempotency middleware reads when `max_body_bytes` is
+/// unset; axum's own default for extractors
+const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Who sent a request, as far as the credentials tell: set by `auth_guard`
+/// so idempotency keys stay apart between clients
+#[derive(Debug, Clone, PartialEq)]
+struct Principal(String);
+
+/// Require credentials when they are configured, and record who sent the
+/// request for the audit log. `basic_auth` covers every route and challenges
+/// browsers to ask for it; `api_token` covers the API and the no-JS pages,
+/// where either one is enough. A form can't send a bearer token, so the
+/// pages also take the token as the password of a Basic login.
+/// `/api/admin/` takes only `admin_token`. Share links, status pages, the
+/// health check and the widget (which checks its own token) stay open.
+async fn auth_guard(State(state): State<Arc<AppState>>, mut request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let open = matches!(path, "/api/widget" | "/healthz") || path.starts_with("/share/") || path.starts_with("/p/");
+    if open || request.method() == Method::OPTIONS {
+        return next.run(request).await;
+    }
+    let admin = path.starts_with("/api/admin/");
+    let page = path == "/projects" || path.starts_with("/projects/") || path.starts_with("/todos/");
+    let token_guarded = path.starts_with("/api/") || page;
+    let (api_token, basic_auth, admin_token) = {
+        let settings = state.settings.read().unwrap();
+        (settings.api_token.clone(), settings.basic_auth.clone(), settings.admin_token.clone())
+    };
+
+    let authorization = request.headers().get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+    let bearer = authorization.and_then(|v| v.strip_prefix("Bearer "));
+    if admin {
+        return match admin_token {
+            Some(token) if same_secret(bearer, &token) => {
+                request.extensions_mut().insert(Principal("admin".to_string()));
+                audit::as_actor("admin".to_string(), next.run(request)).await
+            }
+            Some(_) => {
+                (StatusCode::UNAUTHORIZED, Json(serde_json::json!({ "error": "Invalid admin token" }))).into_response()
+            }
+            None => (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({ "error": "The admin API is off; set admin_token to use it" })),
+            )
+                .into_response(),
+        };
+    }
+    let credentials = basic_credentials(authorization);
+    let token_login = credentials.as_deref().and_then(|c| c.split_once(':')).map(|(_, password)| password);
+    let has_token = api_token.as_deref().is_some_and(|token| {
+        token_guarded && (same_secret(bearer, token) || (page && same_secret(token_login, token)))
+    });
+    let has_login = basic_auth
+        .as_deref()
+        .is_some_and(|login| same_secret(credentials.as_deref(), login));
+
+    if basic_auth.is_some() && !has_login && !has_token {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Basic realm=\"Docket\", charset=\"UTF-8\"")],
+            Json(serde_json::json!({ "error": "Login required" })),
+        )
+            .into_response();
+    }
+    if api_token.is_some() && token_guarded && basic_auth.is_none() && !has_token {
+        let error = Json(serde_json::json!({ "error": "Invalid API token" }));
+        if page {
+            let challenge = "Basic realm=\"Docket (password: API token)\", charset=\"UTF-8\"";
+            return (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, challenge)], error).into_response();
+        }
+        return (StatusCode::UNAUTHORIZED, error).into_response();
+    }
+
+    let user = basic_auth.as_deref().filter(|_| has_login).and_then(|login| login.split_once(':'));
+    let (actor, principal) = match user {
+        Some((user, _)) => (user.to_string(), format!("user:{}", user)),
+        None if has_token => ("api-token".to_string(), "token".to_string()),
+        None => ("web".to_string(), "anonymous".to_string()),
+    };
+    request.extensions_mut().insert(Principal(principal));
+    audit::as_actor(actor, next.run(request)).await
+}
+
+/// Whether `given` is `expected`, taking as long whichever byte differs, so
+/// response times don't give a token away
+pub(crate) fn same_secret(given: Option<&str>, expected: &str) -> bool {
+    use subtle::ConstantTimeEq;
+
+    given.is_some_and(|given| given.as_bytes().ct_eq(expected.as_bytes()).into())
+}
+
+/// The `user:pass` in an `Authorization: Basic` header
+fn basic_credentials(authorization: Option<&str>) -> Option<String> {
+    let encoded = authorization?.strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+    String::from_utf8(decoded).ok()
+}
+
+/// Reject anything but reads while the server is in read-only mode, and
+/// the changes a demo keeps to itself
+async fn read_only_guard(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let method = request.method();
+    let safe = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+    let (read_only, demo) = {
+        let settings = state.settings.read().unwrap();
+        (settings.read_only, settings.demo)
+    };
+    let error = |message: &str| (StatusCode::FORBIDDEN, Json(serde_json::json!({ "error": message }))).into_response();
+    if !safe && read_only {
+        return error("Server is in read-only mode");
+    }
+    let path = request.uri().path();
+    let replaces = path.starts_with("/api/projects/") && matches!(*method, Method::DELETE | Method::PUT);
+    let reshapes = path.starts_with("/api/workspaces/") && matches!(*method, Method::DELETE | Method::PATCH);
+    if demo && (replaces || reshapes || (path == "/api/changes" && method == Method::POST)) {
+        return error("The demo keeps its projects and workspaces; add, edit and tick off todos instead");
+    }
+    next.run(request).await
+}
+
+/// Refuse form posts to the no-JS pages from other sites. Browsers send
+/// Basic-auth credentials along with any form post, so without this a
+/// third-party page could check off or delete todos. With `basic_auth` set
+/// that holds for every change, API routes included: some need no JSON body
+/// and a plain form can reach them. A bearer token is never sent by the
+/// browser on its own, so requests carrying one are let through.
+async fn same_origin_forms(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let method = request.method();
+    let page = path == "/projects" || path.starts_with("/projects/") || path.starts_with("/todos/");
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("Bearer "));
+    let basic_auth = state.settings.read().unwrap().basic_auth.is_some();
+    let changes = !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+    let guarded = (page && method == Method::POST) || (basic_auth && changes && !bearer);
+    if guarded && !is_same_origin(request.headers()) {
+        let message = "Forms can only be sent from this server's own pages";
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({ "error": message }))).into_response();
+    }
+    next.run(request).await
+}
+
+/// Whether `Origin` (or `Referer`, from browsers that leave `Origin` out)
+/// names the host the request was sent to, directly or through a proxy
+fn is_same_origin(headers: &header::HeaderMap) -> bool {
+    let value = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let Some(source) = value(header::ORIGIN.as_str()).or_else(|| value(header::REFERER.as_str())) else {
+        return false;
+    };
+    let Some((_, rest)) = source.split_once("://") else { return false };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    [value(header::HOST.as_str()), value("x-forwarded-host")]
+        .into_iter()
+        .flatten()
+        .any(|host| host.eq_ignore_ascii_case(authority))
+}
+
+/// Refuse request bodies over `max_body_bytes` with 413. Bodies without a
+/// `Content-Length` are read up to the limit before the handler sees them.
+async fn body_limit(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let Some(max) = state.settings.read().unwrap().max_body_bytes else {
+        return next.run(request).await;
+    };
+    let too_large = || {
+        let message = format!("Request body is too large (max {} bytes)", max);
+        (StatusCode::PAYLOAD_TOO_LARGE, Json(serde_json::json!({ "error": message }))).into_response()
+    };
+    let length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    match length {
+        Some(length) if length > max => too_large(),
+        Some(_) => next.run(request).await,
+        None => {
+            let (parts, body) = request.into_parts();
+            match axum::body::to_bytes(body, max).await {
+                Ok(bytes) => next.run(Request::from_parts(parts, Body::from(bytes))).await,
+                Err(_) => too_large(),
+            }
+        }
+    }
+}
+
+/// Where the idempotency middleware keeps the keys it has seen and the
+/// responses to replay for them
+#[async_trait::async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    /// Claim a key for a request identified by `request_hash`
+    async fn claim(&self, key: &str, request_hash: &str) -> Result<IdempotentRequest, DocketError>;
+
+    /// Remember the response to replay for a claimed key
+    async fn finish(
+        &self,
+        key: &str,
+        status: u16,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Result<(), DocketError>;
+
+    /// Give up a claimed key without a response, so the request can be retried
+    async fn release(&self, key: &str) -> Result<(), DocketError>;
+}
+
+/// Keys live in the served database, so they outlast a restart
+#[async_trait::async_trait]
+impl IdempotencyStore for DocketService {
+    async fn claim(&self, key: &str, request_hash: &str) -> Result<IdempotentRequest, DocketError> {
+        self.claim_idempotency_key(key, request_hash).await
+    }
+
+    async fn finish(
+        &self,
+        key: &str,
+        status: u16,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Result<(), DocketError> {
+        self.finish_idempotency_key(key, status, content_type, body).await
+    }
+
+    async fn release(&self, key: &str) -> Result<(), DocketError> {
+        self.release_idempotency_key(key).await
+    }
+}
+
+/// Replay the stored response when a POST is retried with the same
+/// `Idempotency-Key`, so a flaky connection can't create a todo twice.
+/// Server errors are not stored; the client may retry those for real. Keys
+/// belong to the credentials that sent them, so one client can neither
+/// replay nor block another's.
+async fn idempotency_key(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let key = request
+        .headers()
+        .get("idempotency-key")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let Some(key) = key.filter(|_| request.method() == Method::POST) else {
+        return next.run(request).await;
+    };
+    let error = |status: StatusCode, message: &str| {
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    };
+    let principal = request.extensions().get::<Principal>().map_or("anonymous", |p| p.0.as_str());
+    let key = scoped_key(principal, &key);
+    let max = state.settings.read().unwrap().max_body_bytes.unwrap_or(DEFAULT_BODY_LIMIT);
+
+    let (parts, body) = request.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, max).await else {
+        let message = format!("Request body is too large (max {} bytes)", max);
+        return error(StatusCode::PAYLOAD_TOO_LARGE, &message);
+    };
+    let hash = request_hash(&[parts.method.as_str().as_bytes(), parts.uri.path().as_bytes(), &bytes]);
+    match state.idempotency.claim(&key, &hash).await {
+        Ok(IdempotentRequest::New) => {}
+        Ok(IdempotentRequest::InProgress) => {
+            return error(StatusCode::CONFLICT, "A request with this Idempotency-Key is still in progress");
+        }
+        Ok(IdempotentRequest::Mismatch) => {
+            return error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "This Idempotency-Key was already used for a different request",
+            );
+        }
+        Ok(IdempotentRequest::Done { status, content_type, body }) => {
+            let mut response = Response::new(Body::from(body));
+            *response.status_mut() = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+            if let Some(value) = content_type.and_then(|c| HeaderValue::from_str(&c).ok()) {
+                response.headers_mut().insert(header::CONTENT_TYPE, value);
+            }
+            response.headers_mut().insert("idempotent-replayed", HeaderValue::from_static("true"));
+            return response;
+        }
+        Err(err) => return error(StatusCode::INTERNAL_SERVER_ERROR, &format!("{:#}", err)),
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+    if response.status().is_server_error() {
+        if let Err(err) = state.idempotency.release(&key).await {
+            tracing::error!("Failed to release idempotency key: {:#}", err);
+        }
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let content_type = parts.headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
+    if let Err(err) = state.idempotency.finish(&key, parts.status.as_u16(), content_type, &bytes).await {
+        tracing::error!("Failed to store idempotent response: {:#}", err);
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// The key as stored: header values can't hold a newline, so no two
+/// principals' keys can collide
+fn scoped_key(principal: &str, key: &str) -> String {
+    format!("{}\n{}", principal, key)
+}
+
+/// Stable fingerprint of a request (FNV-1a), to spot a key reused for a
+/// different request
+fn request_hash(parts: &[&[u8]]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        for byte in part.iter().chain(&[0]) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+#[derive(Deserialize)]
+struct TimezoneQuery {
+    tz: Option<String>,
+}
+
+/// Honour `?tz=` on any API request: timestamps in the JSON response are
+/// rewritten with that zone's offset (they are UTC otherwise), and handlers
+/// that care about "today" can pick the zone up as an extension
+async fn timezone_param(mut request: Request, next: Next) -> Response {
+    let tz = Query::<TimezoneQuery>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(query)| query.tz);
+    let Some(tz) = tz else {
+        return next.run(request).await;
+    };
+    // An unescaped `+` in a query string decodes to a space
+    let tz = match tz.strip_prefix(' ') {
+        Some(rest) => format!("+{}", rest),
+        None => tz,
+    };
+    let zone = match tz.parse::<DisplayZone>() {
+        Ok(zone) => zone,
+        Err(message) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": message }))).into_response();
+        }
+    };
+    request.extensions_mut().insert(zone);
+
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes().starts_with(b"application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(mut value) => {
+            localize_timestamps(&mut value, zone);
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(value.to_string())
+        }
+        Err(_) => Body::from(bytes),
+    };
+    Response::from_parts(parts, body)
+}
 
-    let app = Router::new()
+/// Rewrite `*_at` timestamps in a JSON value to `zone`
+fn localize_timestamps(value: &mut Value, zone: DisplayZone) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if key.ends_with("_at")
+                    && let Value::String(text) = value
+                    && let Ok(at) = text.parse::<DateTime<Utc>>()
+                {
+                    *text = zone.convert(at).to_rfc3339();
+                } else {
+                    localize_timestamps(value, zone);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| localize_timestamps(item, zone)),
+        _ => {}
+    }
+}
+
+/// Re-read the config file on SIGHUP; open connections are unaffected
+#[cfg(unix)]
+fn reload_on_hangup(state: Arc<AppState>) -> Result<()> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangup = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            match Config::load() {
+                Ok(config) => {
+                    // --demo comes from the command line, not the file
+                    let demo = state.settings.read().unwrap().demo;
+                    let settings = ServerSettings { demo, ..ServerSettings::from(&config) };
+                    tracing::info!("Reloaded config: {:?}", settings);
+                    *state.settings.write().unwrap() = settings;
+                }
+                Err(err) => tracing::error!("Keeping previous config: {:#}", err),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// A bound socket the server accepts connections on
+pub enum Listener {
+    Tcp(tokio::net::TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener),
+}
+
+impl Listener {
+    /// Bind `bind:port`, or a Unix domain socket when `bind` is
+    /// `unix:<path>`. A socket passed by systemd socket activation takes precedence.
+    pub async fn bind(bind: &str, port: u16) -> Result<Self> {
+        #[cfg(unix)]
+        if let Some(listener) = daemon::systemd_listener()? {
+            return Ok(match listener {
+                ActivatedListener::Tcp(listener) => {
+                    Listener::Tcp(tokio::net::TcpListener::from_std(listener)?)
+                }
+                ActivatedListener::Unix(listener) => {
+                    Listener::Unix(tokio::net::UnixListener::from_std(listener)?)
+                }
+            });
+        }
+
+        if let Some(path) = bind.strip_prefix("unix:") {
+            return bind_unix(Path::new(path));
+        }
+
+        let listener = tokio::net::TcpListener::bind((bind, port))
+            .await
+            .with_context(|| format!("Failed to bind to {}:{}", bind, port))?;
+        Ok(Listener::Tcp(listener))
+    }
+
+    /// Where clients connect, e.g. `http://127.0.0.1:3000` or `unix:/run/docket.sock`
+    pub fn url(&self) -> Result<String> {
+        Ok(match self {
+            Listener::Tcp(listener) => format!("http://{}", listener.local_addr()?),
+            #[cfg(unix)]
+            Listener::Unix(listener) => match listener.local_addr()?.as_pathname() {
+                Some(path) => format!("unix:{}", path.display()),
+                None => "an unnamed Unix socket".to_string(),
+            },
+        })
+    }
+}
+
+/// Start the web server on `bind:port` (see [`Listener::bind`]), announcing
+/// the address on stdout
+pub async fn start_server(
+    service: impl DocketServiceApi + IdempotencyStore + 'static,
+    settings: ServerSettings,
+    bind: &str,
+    port: u16,
+) -> Result<()> {
+    let listener = Listener::bind(bind, port).await?;
+    let url = listener.url()?;
+    println!("🚀 Docket web server running on {}", url);
+    if matches!(listener, Listener::Tcp(_)) {
+        println!("   API: {}/api/*", url);
+    }
+    println!("Press Ctrl+C to stop");
+
+    serve(service, settings, listener).await
+}
+
+/// Serve the API and web UI on an already bound listener until Ctrl+C or SIGTERM
+pub async fn serve(
+    service: impl DocketServiceApi + IdempotencyStore + 'static,
+    settings: ServerSettings,
+    listener: Listener,
+) -> Result<()> {
+    let service = Arc::new(service);
+    let state = Arc::new(AppState {
+        service: service.clone(),
+        idempotency: service,
+        settings: RwLock::new(settings),
+    });
+    #[cfg(unix)]
+    reload_on_hangup(state.clone())?;
+
+    let app = app(state);
+    match listener {
+        Listener::Tcp(listener) => serve_tcp(app, listener).await,
+        #[cfg(unix)]
+        Listener::Unix(listener) => serve_unix(app, listener).await,
+    }
+}
+
+/// The API, pages and web UI with their middleware
+fn app(state: Arc<AppState>) -> Router {
+    let cors_state = state.clone();
+    let cors = CorsLayer::permissive().allow_origin(AllowOrigin::predicate(move |origin, _| {
+        origin
+            .to_str()
+            .is_ok_and(|origin| cors_state.settings.read().unwrap().allows_origin(origin))
+    }));
+
+    Router::new()
         // API routes
         .route("/api/projects", get(api::list_projects))
         .route("/api/projects", post(api::create_project))
         .route("/api/projects/:id", get(api::get_project))
         .route("/api/projects/:id", delete(api::delete_project))
-        .route("/api/projects/:id", patch(api::update_project_name))
+        .route("/api/projects/:id", patch(api::update_project))
+        .route("/api/projects/:id", put(api::replace_project))
+        .route("/api/projects/:id/clone", post(api::clone_project))
+        .route("/api/projects/:id/share", post(api::share_project))
         .route("/api/projects/:id/archive", patch(api::archive_project))
         .route("/api/projects/:id/unarchive", patch(api::unarchive_project))
         .route("/api/projects/:id/description", patch(api::update_project_description))
+        .route("/api/projects/:id/target-date", patch(api::update_project_target_date))
+        .route("/api/projects/:id/slug", patch(api::update_project_slug))
+        .route("/api/projects/:id/workspace", patch(api::update_project_workspace))
+        .route("/api/projects/:id/parent", patch(api::update_project_parent))
+        .route("/api/projects/:id/reviewed", patch(api::mark_project_reviewed))
+        .route("/api/projects/:id/stats", get(api::get_project_stats))
         .route("/api/projects/:id/todos", get(api::list_todos))
         .route("/api/projects/:id/todos", post(api::create_todo))
+        .route("/api/templates", get(api::list_templates))
+        .route("/api/workspaces", get(api::list_workspaces).post(api::create_workspace))
+        .route("/api/workspaces/:id", patch(api::rename_workspace).delete(api::delete_workspace))
         .route("/api/todos/:id", get(api::get_todo))
+        .route("/api/todos/:id/full", get(api::get_todo_full))
         .route("/api/todos/:id", delete(api::delete_todo))
         .route("/api/todos/:id", patch(api::update_todo))
         .route("/api/todos/:id/toggle", patch(api::toggle_todo))
         .route("/api/todos/:id/move", patch(api::move_todo))
         .route("/api/todos/:id/details", patch(api::update_todo_details))
+        .route("/api/todos/:id/estimate", patch(api::update_todo_estimate))
+        .route("/api/todos/:id/due-date", patch(api::update_todo_due_date))
+        .route("/api/todos/:id/external-ref", patch(api::update_todo_external_ref))
+        .route("/api/todos/:id/history", get(api::todo_history))
+        .route("/api/todos/:id/history/:version/restore", post(api::restore_todo_version))
+        .route("/api/todos/:id/links", get(api::related_todos).post(api::link_todos))
+        .route("/api/todos/:id/mentions", get(api::todo_mentions))
+        .route("/api/links/:id", delete(api::unlink_todos))
+        .route("/api/events", get(api::events))
+        .route("/api/changes", get(api::get_changes))
+        .route("/api/changes", post(api::push_changes))
+        .route("/api/batch", post(api::batch))
+        .route("/api/widget", get(api::widget))
+        .route("/api/version", get(api::version))
+        .route("/healthz", get(api::health))
+        .route("/api/admin/audit", get(api::audit_log))
+        // Read-only pages behind share links
+        .route("/share/:token", get(api::shared_project))
+        // Public status pages of projects with a slug
+        .route("/p/:slug", get(api::public_project))
+        // Plain HTML pages for browsers without JavaScript
+        .route("/projects", get(pages::projects).post(pages::create_project))
+        .route("/projects/:id", get(pages::project))
+        .route("/projects/:id/todos", post(pages::create_todo))
+        .route("/todos/:id/toggle", post(pages::toggle_todo))
+        .route("/todos/:id/delete", post(pages::delete_todo))
         // Serve web UI
-        .route("/", get(serve_ui))
-        .layer(CorsLayer::permissive())
-        .with_state(state);
+        .route("/", get(assets::index))
+        .route("/ui/*path", get(assets::file))
+        .route("/sw.js", get(assets::service_worker))
+        .route("/manifest.webmanifest", get(assets::manifest))
+        .layer(middleware::from_fn_with_state(state.clone(), idempotency_key))
+        .layer(middleware::from_fn_with_state(state.clone(), body_limit))
+        .layer(middleware::from_fn_with_state(state.clone(), read_only_guard))
+        .layer(middleware::from_fn_with_state(state.clone(), same_origin_forms))
+        .layer(middleware::from_fn_with_state(state.clone(), auth_guard))
+        .layer(middleware::from_fn(timezone_param))
+        .layer(cors)
+        .with_state(state)
+}
 
-    let addr = format!("0.0.0.0:{}", port);
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
+async fn serve_tcp(app: Router, listener: tokio::net::TcpListener) -> Result<()> {
+    // Not a graceful shutdown: open event streams would hold it up forever
+    tokio::select! {
+        result = axum::serve(listener, app) => result?,
+        _ = shutdown_signal() => {}
+    }
 
-    println!("🚀 Docket web server running on http://{}", addr);
-    println!("   API: http://{}/ api/*", addr);
-    println!("Press Ctrl+C to stop");
+    Ok(())
+}
 
-    axum::serve(listener, app).await?;
+/// Bind a Unix domain socket, for use behind a reverse proxy
+#[cfg(unix)]
+fn bind_unix(path: &Path) -> Result<Listener> {
+    use std::os::unix::fs::FileTypeExt;
 
-    Ok(())
+    // A socket file left by a previous run would make bind fail; anything
+    // else at the path is not ours to remove
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            anyhow::bail!("{} exists and is not a socket", path.display());
+        }
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("Failed to bind to {}", path.display()))?;
+    Ok(Listener::Unix(listener))
+}
+
+#[cfg(not(unix))]
+fn bind_unix(_path: &Path) -> Result<Listener> {
+    anyhow::bail!("Unix sockets are not supported on this platform")
+}
+
+/// Serve HTTP/1 on a Unix domain socket
+#[cfg(unix)]
+async fn serve_unix(app: Router, listener: tokio::net::UnixListener) -> Result<()> {
+    use hyper::server::conn::http1;
+    use hyper_util::{rt::TokioIo, service::TowerToHyperService};
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        let (stream, _) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            _ = &mut shutdown => return Ok(()),
+        };
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!("Connection error: {}", err);
+            }
+        });
+    }
+}
+
+/// Resolve on Ctrl+C or, on Unix, SIGTERM (as sent by `kill` and systemd)
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_only_replaces_a_socket() {
+        let dir = crate::core::db::TestDir::new();
+        let path = dir.path().join("docket.sock");
+        std::fs::write(&path, "notes").unwrap();
+        assert!(bind_unix(&path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "notes");
+
+        // A socket left by an earlier run is replaced
+        std::fs::remove_file(&path).unwrap();
+        drop(bind_unix(&path).unwrap());
+        assert!(bind_unix(&path).is_ok());
+    }
+
+    #[test]
+    fn test_same_secret() {
+        assert!(same_secret(Some("s3cret"), "s3cret"));
+        assert!(!same_secret(Some("s3cres"), "s3cret"));
+        assert!(!same_secret(Some("s3cret!"), "s3cret"));
+        assert!(!same_secret(None, "s3cret"));
+    }
+
+    #[test]
+    fn test_basic_credentials() {
+        // "user:pa:ss", as a browser sends it
+        assert_eq!(basic_credentials(Some("Basic dXNlcjpwYTpzcw==")).as_deref(), Some("user:pa:ss"));
+        assert_eq!(basic_credentials(Some("Bearer dXNlcjpwYTpzcw==")), None);
+        assert_eq!(basic_credentials(Some("Basic not base64!")), None);
+        assert_eq!(basic_credentials(None), None);
+    }
+
+    #[test]
+    fn test_forms_must_come_from_this_server() {
+        let headers = |pairs: &[(&'static str, &'static str)]| {
+            let mut map = header::HeaderMap::new();
+            for (name, value) in pairs {
+                map.insert(*name, HeaderValue::from_static(value));
+            }
+            map
+        };
+        assert!(is_same_origin(&headers(&[("host", "localhost:3000"), ("origin", "http://localhost:3000")])));
+        let proxied = [("x-forwarded-host", "todo.example.com"), ("referer", "https://todo.example.com/projects")];
+        assert!(is_same_origin(&headers(&proxied)));
+        assert!(!is_same_origin(&headers(&[("host", "localhost:3000"), ("origin", "https://evil.example")])));
+        assert!(!is_same_origin(&headers(&[("host", "localhost:3000"), ("origin", "null")])));
+        assert!(!is_same_origin(&headers(&[("host", "localhost:3000")])));
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_refuses_changes_from_other_sites() {
+        use tower::ServiceExt;
+
+        let settings = ServerSettings {
+            read_only: false,
+            cors_origins: Vec::new(),
+            widget_token: None,
+            api_token: Some("t0ken".to_string()),
+            basic_auth: Some("ann:pw".to_string()),
+            admin_token: None,
+            max_body_bytes: None,
+            demo: false,
+        };
+        let service = crate::core::service::test_service().await;
+        let project = service.create_project("Home").await.unwrap();
+        let service = Arc::new(service);
+        let settings = RwLock::new(settings);
+        let state = Arc::new(AppState { service: service.clone(), idempotency: service, settings });
+        let clone = |origin: &'static str, authorization: &'static str| {
+            Request::post(format!("/api/projects/{}/clone", project.id))
+                .header(header::HOST, "localhost:3000")
+                .header(header::ORIGIN, origin)
+                .header(header::AUTHORIZATION, authorization)
+                .body(Body::empty())
+                .unwrap()
+        };
+        // "ann:pw", as a browser resends it with a form from any page
+        let login = "Basic YW5uOnB3";
+        let send = |request: Request| app(state.clone()).oneshot(request);
+
+        assert_eq!(send(clone("https://evil.example", login)).await.unwrap().status(), StatusCode::FORBIDDEN);
+        assert_eq!(send(clone("http://localhost:3000", login)).await.unwrap().status(), StatusCode::CREATED);
+        let token = clone("https://evil.example", "Bearer t0ken");
+        assert_eq!(send(token).await.unwrap().status(), StatusCode::CREATED);
+    }
+
+    #[test]
+    fn test_idempotency_keys_are_per_principal() {
+        assert_ne!(scoped_key("user:ann", "k1"), scoped_key("token", "k1"));
+        assert_eq!(scoped_key("token", "k1"), scoped_key("token", "k1"));
+    }
 }
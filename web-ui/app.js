@@ -0,0 +1,529 @@
+// Docket web UI: a hash-routed single page over the JSON API.
+//   #/               active projects
+//   #/archived       archived projects
+//   #/projects/<id>  a project's todos
+
+const state = {
+    projects: [],
+    archived: false,
+    project: null,
+    todos: [],
+    templates: [],
+    showCompleted: true,
+    filter: '',
+};
+
+// ===== API =====
+
+class ApiError extends Error {
+    constructor(status, body) {
+        super(body.error || `Request failed (${status})`);
+        this.status = status;
+        this.body = body;
+    }
+}
+
+// Call the API, sending the token a server with `api_token` asks for
+async function api(method, path, body) {
+    const headers = {};
+    if (body !== undefined) headers['Content-Type'] = 'application/json';
+    const token = localStorage.getItem('docket.token');
+    if (token) headers['Authorization'] = `Bearer ${token}`;
+
+    const res = await fetch(path, {
+        method,
+        headers,
+        body: body === undefined ? undefined : JSON.stringify(body),
+    });
+    if (res.status === 401) {
+        const entered = prompt('This server needs an API token:');
+        if (entered) {
+            localStorage.setItem('docket.token', entered.trim());
+            return api(method, path, body);
+        }
+    }
+    const text = await res.text();
+    const json = text ? JSON.parse(text) : null;
+    if (!res.ok) throw new ApiError(res.status, json || {});
+    return json;
+}
+
+// Run an action, showing what went wrong instead of failing silently
+async function attempt(action) {
+    try {
+        return await action();
+    } catch (err) {
+        toast(err.message, true);
+        return undefined;
+    }
+}
+
+// Retry a create or rename under another name after a 409, pre-filled
+// with the server's suggestion
+async function withUniqueName(name, send) {
+    for (;;) {
+        try {
+            return await send(name);
+        } catch (err) {
+            if (!(err instanceof ApiError) || err.status !== 409) throw err;
+            name = prompt(`${err.message}\n\nUse this name instead?`, err.body.suggestion || '');
+            if (!name || !name.trim()) return null;
+            name = name.trim();
+        }
+    }
+}
+
+// ===== Helpers =====
+
+// Build an element; strings become text nodes, so nothing is parsed as HTML
+function h(tag, attrs = {}, ...children) {
+    const el = document.createElement(tag);
+    for (const [key, value] of Object.entries(attrs)) {
+        if (value === null || value === undefined || value === false) continue;
+        if (key.startsWith('on')) el.addEventListener(key.slice(2), value);
+        else if (key === 'class') el.className = value;
+        else if (value === true) el.setAttribute(key, '');
+        else el.setAttribute(key, value);
+    }
+    for (const child of children.flat()) {
+        if (child === null || child === undefined || child === false) continue;
+        el.append(child instanceof Node ? child : String(child));
+    }
+    return el;
+}
+
+// Text with its URLs as links
+function linkify(text) {
+    const parts = text.split(/(https?:\/\/[^\s<]+)/g);
+    return parts.map((part, i) =>
+        i % 2 ? h('a', { href: part, target: '_blank', rel: 'noopener noreferrer', onclick: e => e.stopPropagation() }, part) : part
+    );
+}
+
+// Hashtags in a todo's description and details, as `Todo::tags` finds them
+function tags(todo) {
+    const found = [];
+    const text = `${todo.description} ${todo.details || ''}`;
+    for (const match of text.matchAll(/(?:^|[^\p{L}\p{N}#])#([\p{L}\p{N}_-]+)/gu)) {
+        const tag = match[1].toLowerCase();
+        if (!found.includes(tag)) found.push(tag);
+    }
+    return found;
+}
+
+function today() {
+    const now = new Date();
+    return new Date(now.getTime() - now.getTimezoneOffset() * 60000).toISOString().slice(0, 10);
+}
+
+function formatDate(iso) {
+    return new Date(iso.length === 10 ? `${iso}T00:00:00` : iso).toLocaleDateString();
+}
+
+let toastTimer = null;
+function toast(message, error = false) {
+    const el = document.getElementById('toast');
+    el.textContent = message;
+    el.classList.toggle('error', error);
+    el.hidden = false;
+    clearTimeout(toastTimer);
+    toastTimer = setTimeout(() => { el.hidden = true; }, error ? 6000 : 3000);
+}
+
+// Ask for a longer text in a dialog; resolves to null when cancelled
+function editText(title, text) {
+    const dialog = document.getElementById('textDialog');
+    dialog.querySelector('.dialog-title').textContent = title;
+    const textarea = dialog.querySelector('textarea');
+    textarea.value = text || '';
+    dialog.showModal();
+    return new Promise(resolve => {
+        dialog.addEventListener('close', () => {
+            resolve(dialog.returnValue === 'save' ? textarea.value : null);
+        }, { once: true });
+    });
+}
+
+// ===== Routing =====
+
+function route() {
+    const hash = location.hash.replace(/^#/, '') || '/';
+    const project = hash.match(/^\/projects\/(\d+)$/);
+    return {
+        archived: hash === '/archived',
+        projectId: project ? Number(project[1]) : null,
+    };
+}
+
+async function render() {
+    const { archived, projectId } = route();
+    // An open project keeps the list it was picked from in the sidebar
+    if (projectId === null) state.archived = archived;
+    document.getElementById('activeTab').classList.toggle('current', !state.archived);
+    document.getElementById('archivedTab').classList.toggle('current', state.archived);
+
+    await loadProjects();
+    if (projectId === null) {
+        state.project = null;
+        renderEmpty();
+    } else {
+        await loadProject(projectId);
+    }
+}
+
+// ===== Projects =====
+
+async function loadProjects() {
+    const projects = await attempt(() => api('GET', `/api/projects?include_archived=${state.archived}`));
+    if (!projects) return;
+    state.projects = state.archived ? projects.filter(p => p.archived_at) : projects;
+    renderProjectList();
+}
+
+function renderProjectList() {
+    const list = document.getElementById('projectList');
+    if (!state.projects.length) {
+        list.replaceChildren(h('p', { class: 'project-stats' }, state.archived ? 'No archived projects' : 'No projects yet'));
+        return;
+    }
+    list.replaceChildren(...state.projects.map(p => {
+        const stats = [`Active: ${p.total_todos - p.completed_todos}`, `Completed: ${p.completed_todos}`];
+        if (p.total_estimate) stats.push(`Effort left: ${p.remaining_estimate}/${p.total_estimate}`);
+        if (p.target_date) stats.push(`Target: ${formatDate(p.target_date)}`);
+        const selected = state.project && state.project.id === p.id;
+        return h('a', { class: `project-item${selected ? ' selected' : ''}`, href: `#/projects/${p.id}` },
+            h('div', { class: 'project-name' }, p.name),
+            h('div', { class: 'project-stats' }, stats.join(' | ')),
+        );
+    }));
+}
+
+async function addProject(event) {
+    event.preventDefault();
+    const input = event.target.elements.name;
+    const name = input.value.trim();
+    if (!name) return;
+    const project = await attempt(() =>
+        withUniqueName(name, name => api('POST', '/api/projects', { name }))
+    );
+    if (!project) return;
+    input.value = '';
+    location.hash = `#/projects/${project.id}`;
+}
+
+function renderEmpty() {
+    renderProjectList();
+    document.getElementById('main').replaceChildren(
+        h('div', { class: 'main-content' },
+            h('div', { class: 'empty-state' }, h('p', {}, 'Select a project to view todos'))),
+    );
+}
+
+async function loadProject(id) {
+    const query = new URLSearchParams({ include_completed: state.showCompleted });
+    if (state.filter) query.set('q', state.filter);
+    const loaded = await attempt(() => Promise.all([
+        api('GET', `/api/projects/${id}`),
+        api('GET', `/api/projects/${id}/todos?${query}`),
+    ]));
+    if (!loaded) return;
+    [state.project, state.todos] = loaded;
+    renderProjectList();
+    renderProject();
+}
+
+async function reload() {
+    if (state.project) await loadProject(state.project.id);
+    await loadProjects();
+}
+
+function renderProject() {
+    const p = state.project;
+    const archived = Boolean(p.archived_at);
+    const open = state.todos.filter(t => !t.completed_at);
+
+    const meta = [];
+    if (archived) meta.push(h('span', {}, 'Archived'));
+    if (p.target_date) {
+        const late = p.target_date < today() && open.length;
+        meta.push(h('span', { class: late ? 'badge-overdue' : null }, `Target ${formatDate(p.target_date)}`));
+    }
+
+    const content = h('div', { class: 'main-content' },
+        h('h2', {}, p.name),
+        meta.length ? h('div', { class: 'project-meta' }, meta) : null,
+        p.description ? h('div', { class: 'project-description' }, p.description) : null,
+        archived ? null : addTodoForm(),
+        h('div', { class: 'toolbar' },
+            h('input', {
+                type: 'search',
+                placeholder: 'Filter, e.g. tag:home due:<friday',
+                value: state.filter,
+                onchange: e => { state.filter = e.target.value.trim(); reload(); },
+            }),
+            h('label', {},
+                h('input', {
+                    type: 'checkbox',
+                    checked: state.showCompleted,
+                    onchange: e => { state.showCompleted = e.target.checked; reload(); },
+                }),
+                ' Show completed'),
+        ),
+        state.todos.length
+            ? h('div', {}, state.todos.map(t => todoItem(t, open)))
+            : h('p', { class: 'empty-state' }, state.filter ? 'No todos match the filter' : 'No todos yet'),
+    );
+
+    const footer = h('div', { class: 'main-footer' },
+        h('button', { class: 'btn-secondary', onclick: renameProject }, 'Edit Name'),
+        h('button', { class: 'btn-secondary', onclick: editProjectDescription }, 'Edit Description'),
+        h('button', { class: 'btn-secondary', onclick: editTargetDate }, 'Target Date'),
+        archived
+            ? h('button', { class: 'btn-warning', onclick: () => setArchived(false) }, 'Unarchive Project')
+            : h('button', { class: 'btn-warning', onclick: () => setArchived(true) }, 'Archive Project'),
+        h('button', { class: 'btn-danger', onclick: deleteProject }, 'Delete Project'),
+    );
+    document.getElementById('main').replaceChildren(content, footer);
+}
+
+function addTodoForm() {
+    const templates = state.templates.length
+        ? h('select', { name: 'template', title: 'Start from a template' },
+            h('option', { value: '' }, 'No template'),
+            state.templates.map(t => h('option', { value: t.name }, t.name)))
+        : null;
+    return h('form', { class: 'input-group', onsubmit: addTodo },
+        h('input', { type: 'text', name: 'description', placeholder: 'New todo description', autocomplete: 'off' }),
+        templates,
+        h('button', { class: 'btn-secondary' }, 'Add Todo'),
+    );
+}
+
+async function addTodo(event) {
+    event.preventDefault();
+    const form = event.target.elements;
+    const description = form.description.value.trim();
+    const template = form.template ? form.template.value : '';
+    if (!description && !template) return;
+    const body = template ? { description, template } : { description };
+    if (await attempt(() => api('POST', `/api/projects/${state.project.id}/todos`, body)) === undefined) return;
+    form.description.value = '';
+    reload();
+}
+
+async function renameProject() {
+    const current = state.project.name;
+    const name = prompt('Project name:', current);
+    if (!name || !name.trim() || name.trim() === current) return;
+    const id = state.project.id;
+    if (await attempt(() => withUniqueName(name.trim(), name => api('PATCH', `/api/projects/${id}`, { name }))) === undefined) return;
+    reload();
+}
+
+async function editProjectDescription() {
+    const text = await editText('Project Description', state.project.description);
+    if (text === null) return;
+    const description = text.trim() || null;
+    if (await attempt(() => api('PATCH', `/api/projects/${state.project.id}/description`, { description })) === undefined) return;
+    reload();
+}
+
+async function editTargetDate() {
+    const entered = prompt('Target date (YYYY-MM-DD; empty clears):', state.project.target_date || '');
+    if (entered === null) return;
+    const target_date = entered.trim() || null;
+    if (await attempt(() => api('PATCH', `/api/projects/${state.project.id}/target-date`, { target_date })) === undefined) return;
+    reload();
+}
+
+async function setArchived(archived) {
+    const id = state.project.id;
+    if (await attempt(() => api('PATCH', `/api/projects/${id}/${archived ? 'archive' : 'unarchive'}`)) === undefined) return;
+    toast(archived ? 'Project archived' : 'Project unarchived');
+    state.archived = archived;
+    location.hash = archived ? '#/archived' : `#/projects/${id}`;
+    render();
+}
+
+async function deleteProject() {
+    if (!confirm(`Delete "${state.project.name}" and all its todos?`)) return;
+    if (await attempt(() => api('DELETE', `/api/projects/${state.project.id}`)) === undefined) return;
+    location.hash = state.archived ? '#/archived' : '#/';
+}
+
+// ===== Todos =====
+
+function todoItem(todo, open) {
+    const done = Boolean(todo.completed_at);
+    const rank = open.findIndex(t => t.id === todo.id);
+    const archived = Boolean(state.project.archived_at);
+
+    const badges = [];
+    for (const tag of tags(todo)) badges.push(h('span', { class: 'badge-tag' }, `#${tag}`));
+    if (todo.due_date) {
+        const overdue = !done && todo.due_date < today();
+        badges.push(h('span', { class: overdue ? 'badge-overdue' : null }, `due ${formatDate(todo.due_date)}`));
+    }
+    if (todo.estimate !== null && todo.estimate !== undefined) badges.push(h('span', { title: 'Estimate' }, `~${todo.estimate}`));
+    if (todo.external_ref) {
+        const href = /^https?:\/\//.test(todo.external_ref) ? todo.external_ref : null;
+        badges.push(href
+            ? h('a', { href, target: '_blank', rel: 'noopener noreferrer', onclick: e => e.stopPropagation() }, 'link')
+            : h('span', {}, todo.external_ref));
+    }
+    if (todo.details) badges.push(h('span', { title: 'Has details' }, '📝'));
+
+    return h('div', { class: `todo-item${done ? ' completed' : ''}` },
+        h('input', {
+            type: 'checkbox',
+            class: 'todo-checkbox',
+            checked: done,
+            disabled: archived,
+            onchange: () => toggleTodo(todo.id),
+        }),
+        done || archived ? null : h('div', { class: 'todo-reorder' },
+            h('button', { title: 'Move up', disabled: rank === 0, onclick: () => moveTodo(todo.id, 'up') }, '▲'),
+            h('button', { title: 'Move down', disabled: rank === open.length - 1, onclick: () => moveTodo(todo.id, 'down') }, '▼'),
+        ),
+        h('div', { class: 'todo-content', onclick: () => openTodo(todo.id) },
+            h('span', { class: 'todo-text' }, linkify(todo.description)),
+            h('div', { class: 'badges' }, badges),
+        ),
+        done ? h('span', { class: 'todo-date' }, formatDate(todo.completed_at)) : null,
+    );
+}
+
+async function toggleTodo(id) {
+    await attempt(() => api('PATCH', `/api/todos/${id}/toggle`));
+    reload();
+}
+
+async function moveTodo(id, direction) {
+    await attempt(() => api('PATCH', `/api/todos/${id}/move`, { direction }));
+    reload();
+}
+
+// Edit every field of a todo, move it to another project or restore an
+// earlier version of its text
+async function openTodo(id) {
+    const loaded = await attempt(() => Promise.all([
+        api('GET', `/api/todos/${id}`),
+        api('GET', `/api/todos/${id}/history`),
+        api('GET', '/api/projects'),
+    ]));
+    if (!loaded) return;
+    const [todo, history, projects] = loaded;
+    const dialog = document.getElementById('todoDialog');
+    const close = () => dialog.close();
+
+    const save = async event => {
+        event.preventDefault();
+        const f = event.target.elements;
+        const description = f.description.value.trim();
+        if (!description) {
+            toast('Description cannot be empty', true);
+            return;
+        }
+        const estimate = f.estimate.value.trim();
+        const patch = {
+            description,
+            details: f.details.value.trim() ? f.details.value : null,
+            estimate: estimate === '' ? null : Number(estimate),
+            due_date: f.due_date.value || null,
+            external_ref: f.external_ref.value.trim() || null,
+        };
+        if (Number(f.project_id.value) !== todo.project_id) patch.project_id = Number(f.project_id.value);
+        if (await attempt(() => api('PATCH', `/api/todos/${id}`, patch)) === undefined) return;
+        close();
+        reload();
+    };
+
+    const remove = async () => {
+        if (!confirm('Delete this todo?')) return;
+        if (await attempt(() => api('DELETE', `/api/todos/${id}`)) === undefined) return;
+        close();
+        reload();
+    };
+
+    const restore = async version => {
+        if (await attempt(() => api('POST', `/api/todos/${id}/history/${version.id}/restore`)) === undefined) return;
+        toast('Earlier version restored');
+        close();
+        reload();
+    };
+
+    const projectChoices = projects.some(p => p.id === todo.project_id)
+        ? projects
+        : [{ id: todo.project_id, name: state.project ? state.project.name : 'Current project' }, ...projects];
+
+    dialog.replaceChildren(h('form', { class: 'todo-form', onsubmit: save },
+        h('h3', {}, 'Edit Todo'),
+        h('label', {}, 'Description', h('input', { type: 'text', name: 'description', value: todo.description, required: true })),
+        h('label', {}, 'Details', h('textarea', { name: 'details' }, todo.details || '')),
+        h('div', { class: 'row' },
+            h('label', {}, 'Due date', h('input', { type: 'date', name: 'due_date', value: todo.due_date || '' })),
+            h('label', {}, 'Estimate', h('input', { type: 'number', name: 'estimate', min: 0, value: todo.estimate ?? '' })),
+        ),
+        h('div', { class: 'row' },
+            h('label', {}, 'Link (JIRA key or URL)', h('input', { type: 'text', name: 'external_ref', value: todo.external_ref || '' })),
+            h('label', {}, 'Project',
+                h('select', { name: 'project_id' },
+                    projectChoices.map(p => h('option', { value: p.id, selected: p.id === todo.project_id }, p.name)))),
+        ),
+        history.length ? h('details', { class: 'history' },
+            h('summary', {}, `Earlier versions (${history.length})`),
+            h('ul', {}, history.map(v => h('li', {},
+                h('span', { title: v.details || '' }, v.description),
+                h('small', {}, new Date(v.replaced_at).toLocaleString()),
+                h('button', { type: 'button', class: 'btn-plain', onclick: () => restore(v) }, 'Restore'),
+            ))),
+        ) : null,
+        h('div', { class: 'dialog-buttons' },
+            h('button', { type: 'button', class: 'btn-danger', onclick: remove }, 'Delete'),
+            h('button', { class: 'btn-primary' }, 'Save'),
+            h('button', { type: 'button', class: 'btn-secondary', onclick: close }, 'Cancel'),
+        ),
+    ));
+    dialog.showModal();
+}
+
+// ===== Live updates =====
+
+// Refresh when something changes elsewhere (another tab or client)
+function listen() {
+    const kinds = [
+        'project.created', 'project.updated', 'project.archived', 'project.unarchived', 'project.deleted',
+        'todo.created', 'todo.updated', 'todo.toggled', 'todo.moved', 'todo.deleted', 'resync',
+    ];
+    // EventSource can't send the token, so a protected server gets no feed
+    if (localStorage.getItem('docket.token')) return;
+    const events = new EventSource('/api/events');
+    let pending = null;
+    for (const kind of kinds) {
+        events.addEventListener(kind, () => {
+            // Leave an open dialog alone; a burst of changes refreshes once
+            if (document.querySelector('dialog[open]')) return;
+            clearTimeout(pending);
+            pending = setTimeout(() => {
+                if (kind === 'project.deleted' && state.project) {
+                    api('GET', `/api/projects/${state.project.id}`).then(reload, () => { location.hash = '#/'; });
+                } else {
+                    reload();
+                }
+            }, 100);
+        });
+    }
+}
+
+// ===== Start =====
+
+document.getElementById('newProject').addEventListener('submit', addProject);
+window.addEventListener('hashchange', render);
+if ('serviceWorker' in navigator) {
+    navigator.serviceWorker.register('/sw.js').catch(err => console.warn('Service worker not registered:', err));
+}
+attempt(async () => { state.templates = await api('GET', '/api/templates'); }).then(render);
+api('GET', '/api/version').then(v => {
+    document.getElementById('version').textContent = `Docket ${v.version} (${v.git_hash} ${v.build_date})`;
+}, () => {});
+listen();
@@ -0,0 +1,75 @@
+// Docket's service worker, served from /sw.js so it controls the whole
+// site. The page shell and its assets come from the cache first and are
+// refreshed in the background; API reads go to the network and fall back
+// to the last answer while offline.
+
+const CACHE = 'docket-v1';
+const SHELL = [
+    '/',
+    '/ui/app.js',
+    '/ui/style.css',
+    '/manifest.webmanifest',
+    '/ui/icons/icon.svg',
+    '/ui/icons/icon-192.png',
+];
+
+self.addEventListener('install', event => {
+    event.waitUntil(caches.open(CACHE).then(cache => cache.addAll(SHELL)).then(() => self.skipWaiting()));
+});
+
+self.addEventListener('activate', event => {
+    event.waitUntil(
+        caches.keys()
+            .then(keys => Promise.all(keys.filter(key => key !== CACHE).map(key => caches.delete(key))))
+            .then(() => self.clients.claim())
+    );
+});
+
+self.addEventListener('fetch', event => {
+    const request = event.request;
+    const url = new URL(request.url);
+    // Writes, the event stream and other sites go straight to the network
+    if (request.method !== 'GET' || url.origin !== location.origin || url.pathname === '/api/events') return;
+
+    if (url.pathname.startsWith('/api/')) {
+        event.respondWith(networkFirst(request));
+    } else if (request.mode === 'navigate' && url.pathname !== '/') {
+        // The plain HTML pages; offline, the app shell stands in for them
+        event.respondWith(networkFirst(request).catch(() => caches.match('/')));
+    } else {
+        event.respondWith(cacheFirst(event, request));
+    }
+});
+
+async function networkFirst(request) {
+    const cache = await caches.open(CACHE);
+    try {
+        const response = await fetch(request);
+        if (response.ok) cache.put(request, response.clone());
+        return response;
+    } catch (err) {
+        const cached = await cache.match(request);
+        if (cached) return cached;
+        if (new URL(request.url).pathname.startsWith('/api/')) {
+            return new Response(JSON.stringify({ error: 'Offline, and this has not been loaded before' }), {
+                status: 503,
+                headers: { 'Content-Type': 'application/json' },
+            });
+        }
+        throw err;
+    }
+}
+
+async function cacheFirst(event, request) {
+    const cache = await caches.open(CACHE);
+    const cached = await cache.match(request, { ignoreSearch: true });
+    const refresh = fetch(request).then(response => {
+        if (response.ok) cache.put(request, response.clone());
+        return response;
+    });
+    if (cached) {
+        event.waitUntil(refresh.catch(() => {}));
+        return cached;
+    }
+    return refresh;
+}